@@ -17,6 +17,7 @@
 //! Polkadot CLI library.
 
 use clap::Parser;
+use std::path::PathBuf;
 
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
@@ -59,6 +60,10 @@ pub enum Subcommand {
 	/// capabilities of running a validator.
 	HostPerfCheck,
 
+	/// Compiles a PVF into an artifact which can be imported by validators running the same
+	/// version of the node.
+	PrepareArtifacts(PrepareArtifactsCmd),
+
 	/// Try some command against runtime state.
 	#[cfg(feature = "try-runtime")]
 	TryRuntime(try_runtime_cli::TryRuntimeCmd),
@@ -86,6 +91,28 @@ pub struct ValidationWorkerCommand {
 	pub node_impl_version: String,
 }
 
+#[allow(missing_docs)]
+#[derive(Debug, Parser)]
+pub struct PrepareArtifactsCmd {
+	/// The path to the PVF code, compressed or not.
+	#[arg(long, value_name = "PATH")]
+	pub code: PathBuf,
+
+	/// The hex-encoded executor parameters of the session the artifact is prepared for, as
+	/// returned by the `session_executor_params` runtime API. Defaults to an empty set.
+	#[arg(long, value_name = "HEX")]
+	pub executor_params: Option<String>,
+
+	/// The directory to write the artifact and its manifest to.
+	#[arg(long, value_name = "PATH", default_value = ".")]
+	pub output: PathBuf,
+
+	/// The secret URI of the ed25519 key to sign the manifest with. Validators import the artifact
+	/// only if they trust the corresponding public key.
+	#[arg(long, value_name = "SURI")]
+	pub signing_key: String,
+}
+
 #[allow(missing_docs)]
 #[derive(Debug, Parser)]
 #[group(skip)]
@@ -147,6 +174,87 @@ pub struct RunCmd {
 	/// **Dangerous!** Do not touch unless explicitly adviced to.
 	#[arg(long)]
 	pub overseer_channel_capacity_override: Option<usize>,
+
+	/// Size the PVF worker pools from the available CPU cores and memory.
+	///
+	/// The chosen sizes and the resulting upper bound of worker processes are logged at
+	/// startup. Pool sizes given explicitly take precedence.
+	#[arg(long)]
+	pub pvf_workers_auto: bool,
+
+	/// The maximum number of PVF prepare workers for jobs other than backing.
+	#[arg(long, value_name = "COUNT")]
+	pub pvf_prepare_workers_soft_max: Option<usize>,
+
+	/// The maximum number of PVF prepare workers.
+	#[arg(long, value_name = "COUNT")]
+	pub pvf_prepare_workers_hard_max: Option<usize>,
+
+	/// The maximum number of PVF execute workers.
+	#[arg(long, value_name = "COUNT")]
+	pub pvf_execute_workers_max: Option<usize>,
+
+	/// The time in seconds allotted for a PVF worker to spawn.
+	#[arg(long, value_name = "SECONDS")]
+	pub pvf_worker_spawn_timeout: Option<u64>,
+
+	/// Import precompiled PVF artifacts from the given directory, e.g. ones produced by the
+	/// `prepare-artifacts` subcommand.
+	///
+	/// Only artifacts signed by one of the `--pvf-artifact-trusted-signer` keys are imported.
+	#[arg(long, value_name = "PATH")]
+	pub pvf_artifact_import_dir: Option<PathBuf>,
+
+	/// The SS58 encoded ed25519 key of a party trusted to sign imported PVF artifacts, e.g. a
+	/// trusted peer or the deployment pipeline. May be given multiple times.
+	///
+	/// **Dangerous!** Artifacts are executed as native code, only trust keys of parties which may
+	/// run code on this machine.
+	#[arg(long, value_name = "SS58")]
+	pub pvf_artifact_trusted_signer: Vec<String>,
+
+	/// How imported PVF artifacts are verified.
+	#[arg(long, value_enum, default_value_t = PvfArtifactVerification::Recompile)]
+	pub pvf_artifact_import_verification: PvfArtifactVerification,
+
+	/// Count the host calls made by the executed PVFs and report them in the metrics.
+	///
+	/// The PVFs are instrumented for this, which adds some overhead to their execution.
+	/// Precompiled artifacts are not instrumented and thus are not imported if this is set.
+	#[arg(long)]
+	pub pvf_count_host_calls: bool,
+
+	/// The number of times a PVF pre-check which failed for a possibly transient reason, e.g. a
+	/// timeout, is retried before voting against the PVF.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub pvf_precheck_retries: u32,
+
+	/// The delay in seconds before retrying a failed PVF pre-check.
+	#[arg(long, value_name = "SECONDS", default_value_t = 30)]
+	pub pvf_precheck_retry_delay: u64,
+
+	/// How many times an updated availability bitfield is signed per block if more chunks arrive
+	/// after our bitfield was signed. Disabled by default.
+	///
+	/// Updated bitfields are used by our own block authoring, and only gossiped to peers which
+	/// have not received a bitfield of ours for the block yet.
+	#[arg(long, value_name = "COUNT", default_value_t = 0)]
+	pub bitfield_max_resigns: u32,
+
+	/// The delay in milliseconds between checks for chunks which arrived after our availability
+	/// bitfield was signed.
+	#[arg(long, value_name = "MILLISECONDS", default_value_t = 500)]
+	pub bitfield_resign_interval: u64,
+}
+
+/// How imported PVF artifacts are verified.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum PvfArtifactVerification {
+	/// Trust artifacts signed by a trusted signer.
+	Signature,
+	/// Use artifacts signed by a trusted signer and replace them with locally compiled ones in the
+	/// background.
+	Recompile,
 }
 
 #[allow(missing_docs)]
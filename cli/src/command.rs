@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::cli::{Cli, Subcommand};
+use crate::cli::{Cli, PvfArtifactVerification, Subcommand};
 use frame_benchmarking_cli::{BenchmarkCmd, ExtrinsicFactory, SUBSTRATE_REFERENCE_HARDWARE};
 use futures::future::TryFutureExt;
 use log::info;
@@ -23,9 +23,9 @@ use polkadot_client::benchmarking::{
 };
 use sc_cli::{RuntimeVersion, SubstrateCli};
 use service::{self, HeaderBackend, IdentifyVariant};
-use sp_core::crypto::Ss58AddressFormatRegistry;
+use sp_core::crypto::{Ss58AddressFormatRegistry, Ss58Codec};
 use sp_keyring::Sr25519Keyring;
-use std::net::ToSocketAddrs;
+use std::{net::ToSocketAddrs, time::Duration};
 
 pub use crate::{error::Error, service::BlockId};
 #[cfg(feature = "hostperfcheck")]
@@ -333,6 +333,43 @@ where
 		None
 	};
 
+	let pvf_worker_pools = service::PvfWorkerPools {
+		auto: cli.run.pvf_workers_auto,
+		prepare_workers_soft_max_num: cli.run.pvf_prepare_workers_soft_max,
+		prepare_workers_hard_max_num: cli.run.pvf_prepare_workers_hard_max,
+		execute_workers_max_num: cli.run.pvf_execute_workers_max,
+		worker_spawn_timeout: cli.run.pvf_worker_spawn_timeout.map(Duration::from_secs),
+	};
+	let pvf_artifact_trusted_signers = cli
+		.run
+		.pvf_artifact_trusted_signer
+		.iter()
+		.map(|signer| {
+			sp_core::ed25519::Public::from_ss58check(signer).map_err(|e| {
+				Error::Other(format!("invalid PVF artifact signer {}: {:?}", signer, e))
+			})
+		})
+		.collect::<Result<Vec<_>>>()?;
+	let pvf_artifact_import =
+		cli.run.pvf_artifact_import_dir.clone().map(|dir| service::PvfArtifactImport {
+			dir,
+			trusted_signers: pvf_artifact_trusted_signers,
+			verification: match cli.run.pvf_artifact_import_verification {
+				PvfArtifactVerification::Signature => service::PvfImportVerification::Signature,
+				PvfArtifactVerification::Recompile => service::PvfImportVerification::Recompile,
+			},
+		});
+
+	let pvf_checker_retry_policy = service::PvfCheckerRetryPolicy {
+		max_retries: cli.run.pvf_precheck_retries,
+		delay: Duration::from_secs(cli.run.pvf_precheck_retry_delay),
+	};
+
+	let bitfield_signing_config = service::BitfieldSigningConfig {
+		resign_interval: Duration::from_millis(cli.run.bitfield_resign_interval),
+		max_resigns: cli.run.bitfield_max_resigns,
+	};
+
 	runner.run_node_until_exit(move |config| async move {
 		let hwbench = (!cli.run.no_hardware_benchmarks)
 			.then_some(config.database.path().map(|database_path| {
@@ -354,6 +391,11 @@ where
 			cli.run.overseer_channel_capacity_override,
 			maybe_malus_finality_delay,
 			hwbench,
+			pvf_worker_pools,
+			pvf_artifact_import,
+			cli.run.pvf_count_host_calls,
+			pvf_checker_retry_policy,
+			bitfield_signing_config,
 		)
 		.map(|full| full.task_manager)?;
 
@@ -652,6 +694,13 @@ pub fn run() -> Result<()> {
 
 			host_perf_check()
 		},
+		Some(Subcommand::PrepareArtifacts(cmd)) => {
+			let mut builder = sc_cli::LoggerBuilder::new("");
+			builder.with_colors(true);
+			builder.init()?;
+
+			crate::prepare_artifacts::prepare_artifacts(cmd)
+		},
 		Some(Subcommand::Key(cmd)) => Ok(cmd.run(&cli)?),
 		#[cfg(feature = "try-runtime")]
 		Some(Subcommand::TryRuntime(cmd)) => {
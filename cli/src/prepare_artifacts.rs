@@ -0,0 +1,67 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{cli::PrepareArtifactsCmd, error::Error};
+use log::info;
+use parity_scale_codec::Decode;
+use polkadot_node_core_pvf::{export_artifact, PvfPrepData};
+use polkadot_node_primitives::VALIDATION_CODE_BOMB_LIMIT;
+use polkadot_primitives::ExecutorParams;
+use sp_core::{crypto::Ss58Codec, ed25519, Pair};
+use std::time::{Duration, Instant};
+
+/// Compiles the PVF given by `cmd` and exports the artifact for import by validators.
+pub fn prepare_artifacts(cmd: &PrepareArtifactsCmd) -> Result<(), Error> {
+	let code = std::fs::read(&cmd.code)
+		.map_err(|e| Error::Other(format!("cannot read {}: {}", cmd.code.display(), e)))?;
+	let code = sp_maybe_compressed_blob::decompress(&code, VALIDATION_CODE_BOMB_LIMIT)
+		.map_err(|e| Error::Other(format!("cannot decompress the code: {}", e)))?;
+
+	let signer = ed25519::Pair::from_string(&cmd.signing_key, None)
+		.map_err(|e| Error::Other(format!("invalid signing key: {:?}", e)))?;
+
+	let executor_params = match cmd.executor_params {
+		Some(ref hex) => {
+			let encoded = sp_core::bytes::from_hex(hex)
+				.map_err(|e| Error::Other(format!("invalid executor parameters: {}", e)))?;
+			ExecutorParams::decode(&mut &encoded[..])
+				.map_err(|e| Error::Other(format!("invalid executor parameters: {}", e)))?
+		},
+		None => ExecutorParams::default(),
+	};
+
+	// The preparation timeout only applies to preparation by the validation host.
+	let pvf = PvfPrepData::from_code(code.into_owned(), executor_params, Duration::ZERO);
+
+	info!("Compiling the PVF...");
+	let start = Instant::now();
+	let blob = polkadot_node_core_pvf_worker::prevalidate(&pvf.code())
+		.map_err(|e| Error::Other(format!("prevalidation failed: {:?}", e)))?;
+	let artifact = polkadot_node_core_pvf_worker::prepare(blob, &pvf.executor_params(), false)
+		.map_err(|e| Error::Other(format!("preparation failed: {:?}", e)))?;
+
+	let path = std::fs::create_dir_all(&cmd.output)
+		.and_then(|_| export_artifact(&cmd.output, &pvf, &artifact, &signer))
+		.map_err(|e| Error::Other(format!("cannot write the artifact: {}", e)))?;
+	info!(
+		"Compiled in {:?}, written to {}, signed by {}",
+		start.elapsed(),
+		path.display(),
+		signer.public().to_ss58check(),
+	);
+
+	Ok(())
+}
@@ -61,6 +61,9 @@ pub enum Error {
 	#[error(transparent)]
 	ValidationFailed(#[from] ValidationFailed),
 
+	#[error("Accessing the misbehavior store failed")]
+	MisbehaviorStore(#[source] std::io::Error),
+
 	#[fatal]
 	#[error(transparent)]
 	BackgroundValidationMpsc(#[from] mpsc::SendError),
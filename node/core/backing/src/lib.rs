@@ -30,8 +30,10 @@ use futures::{
 };
 
 use error::{Error, FatalResult};
+use parity_scale_codec::Encode;
 use polkadot_node_primitives::{
-	AvailableData, InvalidCandidate, PoV, SignedFullStatement, Statement, ValidationResult,
+	AvailableData, BackingMisbehaviorReport, InvalidCandidate, PoV, SignedFullStatement, Statement,
+	ValidationResult,
 };
 use polkadot_node_subsystem::{
 	jaeger,
@@ -44,13 +46,14 @@ use polkadot_node_subsystem::{
 	Stage, SubsystemError,
 };
 use polkadot_node_subsystem_util::{
-	self as util, request_from_runtime, request_session_index_for_child, request_validator_groups,
-	request_validators, Validator,
+	self as util, database::Database, request_from_runtime, request_session_index_for_child,
+	request_validator_groups, request_validators, Validator,
 };
 use polkadot_primitives::{
 	BackedCandidate, CandidateCommitments, CandidateHash, CandidateReceipt, CollatorId,
 	CommittedCandidateReceipt, CoreIndex, CoreState, Hash, Id as ParaId, PvfExecTimeoutKind,
-	SigningContext, ValidatorId, ValidatorIndex, ValidatorSignature, ValidityAttestation,
+	SessionIndex, SigningContext, ValidatorId, ValidatorIndex, ValidatorSignature,
+	ValidityAttestation,
 };
 use sp_keystore::KeystorePtr;
 use statement_table::{
@@ -67,6 +70,10 @@ mod error;
 mod metrics;
 use self::metrics::Metrics;
 
+mod misbehavior;
+pub use self::misbehavior::MISBEHAVIOR_RETENTION_SESSIONS;
+use self::misbehavior::{misbehavior_kind, MisbehaviorStore};
+
 #[cfg(test)]
 mod tests;
 
@@ -116,16 +123,30 @@ impl ValidatedCandidateCommand {
 	}
 }
 
+/// Configuration for the candidate backing subsystem.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+	/// The column of the parachains DB to store misbehavior reports in.
+	pub col_misbehavior_data: u32,
+}
+
 /// The candidate backing subsystem.
 pub struct CandidateBackingSubsystem {
 	keystore: KeystorePtr,
+	db: Arc<dyn Database>,
+	config: Config,
 	metrics: Metrics,
 }
 
 impl CandidateBackingSubsystem {
 	/// Create a new instance of the `CandidateBackingSubsystem`.
-	pub fn new(keystore: KeystorePtr, metrics: Metrics) -> Self {
-		Self { keystore, metrics }
+	pub fn new(
+		keystore: KeystorePtr,
+		db: Arc<dyn Database>,
+		config: Config,
+		metrics: Metrics,
+	) -> Self {
+		Self { keystore, db, config, metrics }
 	}
 }
 
@@ -136,7 +157,8 @@ where
 {
 	fn start(self, ctx: Context) -> SpawnedSubsystem {
 		let future = async move {
-			run(ctx, self.keystore, self.metrics)
+			let misbehavior_store = MisbehaviorStore::new(self.db, self.config);
+			run(ctx, self.keystore, misbehavior_store, self.metrics)
 				.await
 				.map_err(|e| SubsystemError::with_origin("candidate-backing", e))
 		}
@@ -150,6 +172,7 @@ where
 async fn run<Context>(
 	mut ctx: Context,
 	keystore: KeystorePtr,
+	mut misbehavior_store: MisbehaviorStore,
 	metrics: Metrics,
 ) -> FatalResult<()> {
 	let (background_validation_tx, mut background_validation_rx) = mpsc::channel(16);
@@ -159,6 +182,7 @@ async fn run<Context>(
 		let res = run_iteration(
 			&mut ctx,
 			keystore.clone(),
+			&mut misbehavior_store,
 			&metrics,
 			&mut jobs,
 			background_validation_tx.clone(),
@@ -179,6 +203,7 @@ async fn run<Context>(
 async fn run_iteration<Context>(
 	ctx: &mut Context,
 	keystore: KeystorePtr,
+	misbehavior_store: &mut MisbehaviorStore,
 	metrics: &Metrics,
 	jobs: &mut HashMap<Hash, JobAndSpan<Context>>,
 	background_validation_tx: mpsc::Sender<(Hash, ValidatedCandidateCommand)>,
@@ -206,12 +231,18 @@ async fn run_iteration<Context>(
 						update,
 						jobs,
 						&keystore,
+						misbehavior_store,
 						&background_validation_tx,
 						&metrics,
 					).await?,
 					FromOrchestra::Signal(OverseerSignal::BlockFinalized(..)) => {}
 					FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
-					FromOrchestra::Communication { msg } => handle_communication(&mut *ctx, jobs, msg).await?,
+					FromOrchestra::Communication { msg } => handle_communication(
+						&mut *ctx,
+						jobs,
+						misbehavior_store,
+						msg,
+					).await?,
 				}
 			}
 		)
@@ -239,6 +270,7 @@ async fn handle_validated_candidate_command<Context>(
 async fn handle_communication<Context>(
 	ctx: &mut Context,
 	jobs: &mut HashMap<Hash, JobAndSpan<Context>>,
+	misbehavior_store: &MisbehaviorStore,
 	message: CandidateBackingMessage,
 ) -> Result<(), Error> {
 	match message {
@@ -256,6 +288,12 @@ async fn handle_communication<Context>(
 			if let Some(job) = jobs.get_mut(&relay_parent) {
 				job.job.handle_get_backed_candidates_message(requested_candidates, tx)?;
 			},
+		CandidateBackingMessage::MisbehaviorReports(session_index, start_key, count, tx) => {
+			let page = misbehavior_store
+				.load(session_index, start_key.as_deref(), count)
+				.map_err(Error::MisbehaviorStore)?;
+			let _ = tx.send(page);
+		},
 	}
 
 	Ok(())
@@ -267,6 +305,7 @@ async fn handle_active_leaves_update<Context>(
 	update: ActiveLeavesUpdate,
 	jobs: &mut HashMap<Hash, JobAndSpan<Context>>,
 	keystore: &KeystorePtr,
+	misbehavior_store: &mut MisbehaviorStore,
 	background_validation_tx: &mpsc::Sender<(Hash, ValidatedCandidateCommand)>,
 	metrics: &Metrics,
 ) -> Result<(), Error> {
@@ -319,6 +358,10 @@ async fn handle_active_leaves_update<Context>(
 	let session_index = try_runtime_api!(session_index);
 	let cores = try_runtime_api!(cores);
 
+	if let Err(err) = misbehavior_store.note_session(session_index) {
+		gum::warn!(target: LOG_TARGET, ?err, session_index, "Failed to prune misbehavior reports");
+	}
+
 	drop(_span);
 	let _span = span.child("validator-construction");
 
@@ -380,6 +423,7 @@ async fn handle_active_leaves_update<Context>(
 
 	let job = CandidateBackingJob {
 		parent,
+		session_index,
 		assignment,
 		required_collator,
 		issued_statements: HashSet::new(),
@@ -392,6 +436,7 @@ async fn handle_active_leaves_update<Context>(
 		table: Table::default(),
 		table_context,
 		background_validation_tx: background_validation_tx.clone(),
+		misbehavior_store: misbehavior_store.clone(),
 		metrics: metrics.clone(),
 		_marker: std::marker::PhantomData,
 	};
@@ -410,6 +455,8 @@ struct JobAndSpan<Context> {
 struct CandidateBackingJob<Context> {
 	/// The hash of the relay parent on top of which this job is doing it's work.
 	parent: Hash,
+	/// The session of the child of the relay parent.
+	session_index: SessionIndex,
 	/// The `ParaId` assigned to this validator
 	assignment: Option<ParaId>,
 	/// The collator required to author the candidate, if any.
@@ -431,6 +478,7 @@ struct CandidateBackingJob<Context> {
 	table: Table<TableContext>,
 	table_context: TableContext,
 	background_validation_tx: mpsc::Sender<(Hash, ValidatedCandidateCommand)>,
+	misbehavior_store: MisbehaviorStore,
 	metrics: Metrics,
 	_marker: std::marker::PhantomData<Context>,
 }
@@ -801,7 +849,10 @@ impl<Context> CandidateBackingJob<Context> {
 		match command {
 			ValidatedCandidateCommand::Second(res) => {
 				match res {
-					Ok((candidate, commitments, _)) => {
+					Ok((candidate, commitments, pov)) => {
+						self.metrics
+							.on_pov_validated(candidate.descriptor.para_id, pov.encoded_size());
+
 						// sanity check.
 						if self.seconded.is_none() &&
 							!self.issued_statements.contains(&candidate_hash)
@@ -841,7 +892,9 @@ impl<Context> CandidateBackingJob<Context> {
 				self.fallbacks.remove(&candidate_hash);
 				// sanity check.
 				if !self.issued_statements.contains(&candidate_hash) {
-					if res.is_ok() {
+					if let Ok((candidate, _, pov)) = &res {
+						self.metrics
+							.on_pov_validated(candidate.descriptor.para_id, pov.encoded_size());
 						let statement = Statement::Valid(candidate_hash);
 						self.sign_import_and_distribute_statement(ctx, statement, &root_span)?;
 					}
@@ -982,11 +1035,34 @@ impl<Context> CandidateBackingJob<Context> {
 		}
 	}
 
-	/// Check if there have happened any new misbehaviors and issue necessary messages.
+	/// Check if there have happened any new misbehaviors, record them and issue necessary
+	/// messages.
 	fn issue_new_misbehaviors(&mut self, sender: &mut impl overseer::CandidateBackingSenderTrait) {
 		// collect the misbehaviors to avoid double mutable self borrow issues
 		let misbehaviors: Vec<_> = self.table.drain_misbehaviors().collect();
+		let mut records = Vec::with_capacity(misbehaviors.len());
 		for (validator_id, report) in misbehaviors {
+			let kind = misbehavior_kind(&report);
+			gum::warn!(
+				target: LOG_TARGET,
+				relay_parent = ?self.parent,
+				validator_index = validator_id.0,
+				?kind,
+				"Validator misbehaved in candidate backing",
+			);
+			self.metrics.on_misbehavior(kind);
+
+			if let Some(key) = self.table_context.validators.get(validator_id.0 as usize) {
+				records.push(BackingMisbehaviorReport {
+					session_index: self.session_index,
+					relay_parent: self.parent,
+					validator_index: validator_id,
+					validator_id: key.clone(),
+					kind,
+					evidence: report.encode(),
+				});
+			}
+
 			// The provisioner waits on candidate-backing, which means
 			// that we need to send unbounded messages to avoid cycles.
 			//
@@ -997,6 +1073,12 @@ impl<Context> CandidateBackingJob<Context> {
 				ProvisionableData::MisbehaviorReport(self.parent, validator_id, report),
 			));
 		}
+
+		if !records.is_empty() {
+			if let Err(err) = self.misbehavior_store.write(&records) {
+				gum::warn!(target: LOG_TARGET, ?err, "Failed to store misbehavior reports");
+			}
+		}
 	}
 
 	/// Import a statement into the statement table and return the summary of the import.
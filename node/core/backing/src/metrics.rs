@@ -14,7 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+use polkadot_node_primitives::BackingMisbehaviorKind;
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
+use polkadot_primitives::Id as ParaId;
 
 #[derive(Clone)]
 pub(crate) struct MetricsInner {
@@ -23,6 +25,8 @@ pub(crate) struct MetricsInner {
 	pub(crate) process_second: prometheus::Histogram,
 	pub(crate) process_statement: prometheus::Histogram,
 	pub(crate) get_backed_candidates: prometheus::Histogram,
+	pub(crate) misbehaviors_total: prometheus::CounterVec<prometheus::U64>,
+	pub(crate) validated_pov_bytes_total: prometheus::CounterVec<prometheus::U64>,
 }
 
 /// Candidate backing metrics.
@@ -42,6 +46,29 @@ impl Metrics {
 		}
 	}
 
+	pub fn on_misbehavior(&self, kind: BackingMisbehaviorKind) {
+		if let Some(metrics) = &self.0 {
+			let kind = match kind {
+				BackingMisbehaviorKind::ValidityDoubleVote => "validity_double_vote",
+				BackingMisbehaviorKind::MultipleCandidates => "multiple_candidates",
+				BackingMisbehaviorKind::UnauthorizedStatement => "unauthorized_statement",
+				BackingMisbehaviorKind::DoubleSign => "double_sign",
+			};
+			metrics.misbehaviors_total.with_label_values(&[kind]).inc();
+		}
+	}
+
+	/// Note the encoded size of the PoV of a candidate of the para which was validated
+	/// successfully.
+	pub fn on_pov_validated(&self, para_id: ParaId, pov_bytes: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics
+				.validated_pov_bytes_total
+				.with_label_values(&[&para_id.to_string()])
+				.inc_by(pov_bytes as u64);
+		}
+	}
+
 	/// Provide a timer for handling `CandidateBackingMessage:Second` which observes on drop.
 	pub fn time_process_second(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.process_second.start_timer())
@@ -100,6 +127,26 @@ impl metrics::Metrics for Metrics {
 				))?,
 				registry,
 			)?,
+			misbehaviors_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_candidate_backing_misbehaviors_total",
+						"Number of validator misbehaviors detected in the statement table.",
+					),
+					&["kind"],
+				)?,
+				registry,
+			)?,
+			validated_pov_bytes_total: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_candidate_backing_validated_pov_bytes_total",
+						"Total size of the PoVs of the candidates validated successfully, per para.",
+					),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
@@ -0,0 +1,162 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Persistence of the misbehaviors detected in the statement table.
+//!
+//! Reports are keyed by session, validator index and the hash of the evidence, so a misbehavior
+//! detected at multiple relay parents is only stored once per distinct evidence.
+
+use std::{io, sync::Arc};
+
+use parity_scale_codec::{Decode, Encode};
+use polkadot_node_primitives::{
+	BackingMisbehaviorKind, BackingMisbehaviorPage, BackingMisbehaviorReport,
+};
+use polkadot_node_subsystem_util::database::{DBTransaction, Database};
+use polkadot_primitives::SessionIndex;
+use sp_core::hashing::blake2_256;
+use statement_table::v2::Misbehavior;
+
+use crate::{Config, LOG_TARGET};
+
+/// The number of sessions misbehavior reports are kept for, about a week on Polkadot.
+pub const MISBEHAVIOR_RETENTION_SESSIONS: SessionIndex = 42;
+
+const MISBEHAVIOR_PREFIX: &[u8; 11] = b"Misbehavior";
+
+/// The kind of a misbehavior detected in the statement table.
+pub(crate) fn misbehavior_kind(misbehavior: &Misbehavior) -> BackingMisbehaviorKind {
+	match misbehavior {
+		Misbehavior::ValidityDoubleVote(_) => BackingMisbehaviorKind::ValidityDoubleVote,
+		Misbehavior::MultipleCandidates(_) => BackingMisbehaviorKind::MultipleCandidates,
+		Misbehavior::UnauthorizedStatement(_) => BackingMisbehaviorKind::UnauthorizedStatement,
+		Misbehavior::DoubleSign(_) => BackingMisbehaviorKind::DoubleSign,
+	}
+}
+
+fn session_prefix(session_index: SessionIndex) -> [u8; 15] {
+	let mut prefix = [0u8; 15];
+	prefix[..11].copy_from_slice(MISBEHAVIOR_PREFIX);
+	prefix[11..].copy_from_slice(&session_index.to_be_bytes());
+	prefix
+}
+
+fn report_key(report: &BackingMisbehaviorReport) -> Vec<u8> {
+	let mut key = session_prefix(report.session_index).to_vec();
+	key.extend_from_slice(&report.validator_index.0.to_be_bytes());
+	key.extend_from_slice(&blake2_256(&report.evidence));
+	key
+}
+
+fn decode_key_session(key: &[u8]) -> Option<SessionIndex> {
+	if key.len() < 15 || !key.starts_with(MISBEHAVIOR_PREFIX) {
+		return None
+	}
+
+	let mut session = [0u8; 4];
+	session.copy_from_slice(&key[11..15]);
+	Some(SessionIndex::from_be_bytes(session))
+}
+
+/// Stores misbehavior reports in the parachains DB.
+#[derive(Clone)]
+pub(crate) struct MisbehaviorStore {
+	db: Arc<dyn Database>,
+	col_data: u32,
+	/// Reports of sessions before this one have been pruned.
+	earliest_session: SessionIndex,
+}
+
+impl MisbehaviorStore {
+	pub(crate) fn new(db: Arc<dyn Database>, config: Config) -> Self {
+		Self { db, col_data: config.col_misbehavior_data, earliest_session: 0 }
+	}
+
+	/// Persist the given reports. Reports which are already stored are overwritten.
+	pub(crate) fn write(&self, reports: &[BackingMisbehaviorReport]) -> io::Result<()> {
+		let mut tx = DBTransaction::new();
+		for report in reports {
+			tx.put_vec(self.col_data, &report_key(report), report.encode());
+		}
+
+		self.db.write(tx)
+	}
+
+	/// Load a page of at most `count` stored reports, ordered by session and validator index,
+	/// starting after `start_key`. If a session is given, only the reports of that session are
+	/// loaded.
+	pub(crate) fn load(
+		&self,
+		session_index: Option<SessionIndex>,
+		start_key: Option<&[u8]>,
+		count: usize,
+	) -> io::Result<BackingMisbehaviorPage> {
+		let prefix = match session_index {
+			Some(session_index) => session_prefix(session_index).to_vec(),
+			None => MISBEHAVIOR_PREFIX.to_vec(),
+		};
+
+		let mut page = BackingMisbehaviorPage::default();
+		let mut last_key = None;
+		for entry in self.db.iter_with_prefix(self.col_data, &prefix) {
+			let (key, value) = entry?;
+			if start_key.map_or(false, |start_key| &key[..] <= start_key) {
+				continue
+			}
+
+			if page.reports.len() >= count {
+				page.next_key = last_key.map(|key: Vec<u8>| key.into());
+				break
+			}
+
+			match BackingMisbehaviorReport::decode(&mut &value[..]) {
+				Ok(report) => page.reports.push(report),
+				Err(err) => gum::warn!(
+					target: LOG_TARGET,
+					?key,
+					?err,
+					"Skipping misbehavior report which failed to decode",
+				),
+			}
+			last_key = Some(key.to_vec());
+		}
+
+		Ok(page)
+	}
+
+	/// Note that a new session has started, pruning reports which are older than
+	/// [`MISBEHAVIOR_RETENTION_SESSIONS`].
+	pub(crate) fn note_session(&mut self, session_index: SessionIndex) -> io::Result<()> {
+		let earliest_session = session_index.saturating_sub(MISBEHAVIOR_RETENTION_SESSIONS);
+		if earliest_session <= self.earliest_session {
+			return Ok(())
+		}
+
+		let mut tx = DBTransaction::new();
+		for entry in self.db.iter_with_prefix(self.col_data, &MISBEHAVIOR_PREFIX[..]) {
+			let (key, _) = entry?;
+			match decode_key_session(&key) {
+				Some(session) if session < earliest_session => tx.delete(self.col_data, &key),
+				_ => break,
+			}
+		}
+
+		self.db.write(tx)?;
+		self.earliest_session = earliest_session;
+
+		Ok(())
+	}
+}
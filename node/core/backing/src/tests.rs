@@ -21,7 +21,8 @@ use ::test_helpers::{
 };
 use assert_matches::assert_matches;
 use futures::{future, Future};
-use polkadot_node_primitives::{BlockData, InvalidCandidate};
+use parity_scale_codec::Decode;
+use polkadot_node_primitives::{BackingMisbehaviorKind, BlockData, InvalidCandidate};
 use polkadot_node_subsystem::{
 	messages::{
 		AllMessages, CollatorProtocolMessage, RuntimeApiMessage, RuntimeApiRequest,
@@ -30,6 +31,7 @@ use polkadot_node_subsystem::{
 	ActivatedLeaf, ActiveLeavesUpdate, FromOrchestra, LeafStatus, OverseerSignal,
 };
 use polkadot_node_subsystem_test_helpers as test_helpers;
+use polkadot_node_subsystem_util::database::kvdb_impl::DbAdapter;
 use polkadot_primitives::{
 	CandidateDescriptor, CollatorId, GroupRotationInfo, HeadData, PersistedValidationData,
 	PvfExecTimeoutKind, ScheduledCore,
@@ -138,6 +140,28 @@ impl Default for TestState {
 
 type VirtualOverseer = test_helpers::TestSubsystemContextHandle<CandidateBackingMessage>;
 
+const TEST_CONFIG: Config = Config { col_misbehavior_data: 0 };
+
+fn make_misbehavior_store() -> MisbehaviorStore {
+	let db = kvdb_memorydb::create(1);
+	let db = DbAdapter::new(db, &[TEST_CONFIG.col_misbehavior_data]);
+	MisbehaviorStore::new(Arc::new(db), TEST_CONFIG)
+}
+
+fn dummy_misbehavior_report(
+	session_index: SessionIndex,
+	validator_index: ValidatorIndex,
+) -> BackingMisbehaviorReport {
+	BackingMisbehaviorReport {
+		session_index,
+		relay_parent: dummy_hash(),
+		validator_index,
+		validator_id: Sr25519Keyring::Alice.public().into(),
+		kind: BackingMisbehaviorKind::DoubleSign,
+		evidence: vec![1, 2, 3],
+	}
+}
+
 fn test_harness<T: Future<Output = VirtualOverseer>>(
 	keystore: KeystorePtr,
 	test: impl FnOnce(VirtualOverseer) -> T,
@@ -147,7 +171,8 @@ fn test_harness<T: Future<Output = VirtualOverseer>>(
 	let (context, virtual_overseer) = test_helpers::make_subsystem_context(pool.clone());
 
 	let subsystem = async move {
-		if let Err(e) = super::run(context, keystore, Metrics(None)).await {
+		if let Err(e) = super::run(context, keystore, make_misbehavior_store(), Metrics(None)).await
+		{
 			panic!("{:?}", e);
 		}
 	};
@@ -847,6 +872,36 @@ fn backing_misbehavior_works() {
 				).expect("signature must be valid");
 			}
 		);
+
+		// The misbehavior is recorded.
+		let (tx, rx) = oneshot::channel();
+		virtual_overseer
+			.send(FromOrchestra::Communication {
+				msg: CandidateBackingMessage::MisbehaviorReports(
+					Some(test_state.signing_context.session_index),
+					None,
+					10,
+					tx,
+				),
+			})
+			.await;
+
+		let page = rx.await.unwrap();
+		assert_eq!(page.next_key, None);
+		let reports = page.reports;
+		assert_eq!(reports.len(), 1);
+		assert_eq!(reports[0].session_index, test_state.signing_context.session_index);
+		assert_eq!(reports[0].relay_parent, test_state.relay_parent);
+		assert_eq!(reports[0].kind, BackingMisbehaviorKind::ValidityDoubleVote);
+		assert_eq!(
+			reports[0].validator_id,
+			test_state.validator_public[reports[0].validator_index.0 as usize],
+		);
+		assert_matches!(
+			Misbehavior::decode(&mut &reports[0].evidence[..]),
+			Ok(Misbehavior::ValidityDoubleVote(_))
+		);
+
 		virtual_overseer
 	});
 }
@@ -1634,3 +1689,58 @@ fn observes_backing_even_if_not_validator() {
 		virtual_overseer
 	});
 }
+
+#[test]
+fn misbehavior_store_prunes_old_sessions() {
+	let mut store = make_misbehavior_store();
+
+	let old = dummy_misbehavior_report(1, ValidatorIndex(3));
+	let recent = dummy_misbehavior_report(2, ValidatorIndex(1));
+	let mut recent_other_evidence = recent.clone();
+	recent_other_evidence.evidence = vec![4, 5, 6];
+	let other_validator = dummy_misbehavior_report(2, ValidatorIndex(0));
+
+	store
+		.write(&[recent.clone(), old.clone(), recent_other_evidence.clone(), recent.clone()])
+		.unwrap();
+	store.write(&[other_validator.clone()]).unwrap();
+
+	// Duplicate evidence is stored once, reports are ordered by session and validator.
+	let all = store.load(None, None, usize::MAX).unwrap().reports;
+	assert_eq!(all.len(), 4);
+	assert_eq!(all[0], old);
+	assert_eq!(all[1], other_validator);
+	assert_eq!(store.load(Some(2), None, usize::MAX).unwrap().reports.len(), 3);
+
+	store.note_session(MISBEHAVIOR_RETENTION_SESSIONS + 1).unwrap();
+	assert_eq!(store.load(None, None, usize::MAX).unwrap().reports.len(), 4);
+
+	store.note_session(MISBEHAVIOR_RETENTION_SESSIONS + 2).unwrap();
+	assert!(store.load(Some(1), None, usize::MAX).unwrap().reports.is_empty());
+	assert_eq!(store.load(None, None, usize::MAX).unwrap().reports.len(), 3);
+}
+
+#[test]
+fn misbehavior_store_loads_pages() {
+	let store = make_misbehavior_store();
+
+	let reports: Vec<_> =
+		(0..5).map(|i| dummy_misbehavior_report(i / 2, ValidatorIndex(i))).collect();
+	store.write(&reports).unwrap();
+
+	let first = store.load(None, None, 2).unwrap();
+	assert_eq!(first.reports, reports[..2]);
+	let next_key = first.next_key.expect("more reports are stored");
+
+	let second = store.load(None, Some(&next_key[..]), 2).unwrap();
+	assert_eq!(second.reports, reports[2..4]);
+
+	let last = store.load(None, Some(&second.next_key.unwrap()[..]), 2).unwrap();
+	assert_eq!(last.reports, reports[4..]);
+	assert_eq!(last.next_key, None);
+
+	// A page of a single session ends with the session.
+	let session = store.load(Some(1), None, 2).unwrap();
+	assert_eq!(session.reports, reports[2..4]);
+	assert_eq!(session.next_key, None);
+}
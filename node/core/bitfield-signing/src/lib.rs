@@ -49,6 +49,8 @@ mod tests;
 
 /// Delay between starting a bitfield signing job and its attempting to create a bitfield.
 const SPAWNED_TASK_DELAY: Duration = Duration::from_millis(1500);
+/// Default delay between checks for chunks which arrived after a bitfield was signed.
+pub const DEFAULT_RESIGN_INTERVAL: Duration = Duration::from_millis(500);
 const LOG_TARGET: &str = "parachain::bitfield-signing";
 
 // TODO: use `fatality` (https://github.com/paritytech/polkadot/issues/5540).
@@ -135,22 +137,17 @@ async fn get_availability_cores(
 	}
 }
 
-/// - get the list of core states from the runtime
-/// - for each core, concurrently determine chunk availability (see `get_core_availability`)
+/// - for each of the core states fetched from the runtime, concurrently determine chunk
+///   availability (see `get_core_availability`)
 /// - return the bitfield if there were no errors at any point in this process
 ///   (otherwise, it's prone to false negatives)
 async fn construct_availability_bitfield(
 	relay_parent: Hash,
+	availability_cores: &[CoreState],
 	span: &jaeger::Span,
 	validator_idx: ValidatorIndex,
 	sender: &mut impl SubsystemSender<overseer::BitfieldSigningOutgoingMessages>,
 ) -> Result<AvailabilityBitfield, Error> {
-	// get the set of availability cores from the runtime
-	let availability_cores = {
-		let _span = span.child("get-availability-cores");
-		get_availability_cores(relay_parent, sender).await?
-	};
-
 	// Wrap the sender in a Mutex to share it between the futures.
 	//
 	// We use a `Mutex` here to not `clone` the sender inside the future, because
@@ -179,16 +176,42 @@ async fn construct_availability_bitfield(
 	Ok(AvailabilityBitfield(core_bits))
 }
 
+/// Configuration for the bitfield signing subsystem.
+#[derive(Debug, Clone, Copy)]
+pub struct Config {
+	/// Delay between checks for chunks which arrived after our bitfield for a leaf was signed.
+	pub resign_interval: Duration,
+	/// How many times an updated bitfield may be signed and distributed per leaf. `0` signs a
+	/// single bitfield per leaf, but availability is still checked once more after
+	/// `resign_interval` to count stale bitfields.
+	///
+	/// Peers only accept an updated bitfield if it has every bit of the previous one set, so
+	/// re-signing never takes back availability we reported before.
+	pub max_resigns: u32,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self { resign_interval: DEFAULT_RESIGN_INTERVAL, max_resigns: 0 }
+	}
+}
+
 /// The bitfield signing subsystem.
 pub struct BitfieldSigningSubsystem {
 	keystore: KeystorePtr,
+	config: Config,
 	metrics: Metrics,
 }
 
 impl BitfieldSigningSubsystem {
 	/// Create a new instance of the `BitfieldSigningSubsystem`.
 	pub fn new(keystore: KeystorePtr, metrics: Metrics) -> Self {
-		Self { keystore, metrics }
+		Self::with_config(keystore, Config::default(), metrics)
+	}
+
+	/// Create a new instance of the `BitfieldSigningSubsystem` with the given configuration.
+	pub fn with_config(keystore: KeystorePtr, config: Config, metrics: Metrics) -> Self {
+		Self { keystore, config, metrics }
 	}
 }
 
@@ -196,7 +219,7 @@ impl BitfieldSigningSubsystem {
 impl<Context> BitfieldSigningSubsystem {
 	fn start(self, ctx: Context) -> SpawnedSubsystem {
 		let future = async move {
-			run(ctx, self.keystore, self.metrics)
+			run(ctx, self.keystore, self.config, self.metrics)
 				.await
 				.map_err(|e| SubsystemError::with_origin("bitfield-signing", e))
 		}
@@ -210,6 +233,7 @@ impl<Context> BitfieldSigningSubsystem {
 async fn run<Context>(
 	mut ctx: Context,
 	keystore: KeystorePtr,
+	config: Config,
 	metrics: Metrics,
 ) -> SubsystemResult<()> {
 	// Track spawned jobs per active leaf.
@@ -233,6 +257,7 @@ async fn run<Context>(
 						sender,
 						leaf,
 						keystore.clone(),
+						config,
 						metrics.clone(),
 					));
 
@@ -252,6 +277,7 @@ async fn handle_active_leaves_update<Sender>(
 	mut sender: Sender,
 	leaf: ActivatedLeaf,
 	keystore: KeystorePtr,
+	config: Config,
 	metrics: Metrics,
 ) -> Result<(), Error>
 where
@@ -284,40 +310,167 @@ where
 
 	// this timer does not appear at the head of the function because we don't want to include
 	// SPAWNED_TASK_DELAY each time.
-	let _timer = metrics.time_run();
+	let timer = metrics.time_run();
 
 	drop(span_delay);
 	let span_availability = span.child("availability");
 
-	let bitfield = match construct_availability_bitfield(
+	// get the set of availability cores from the runtime
+	let availability_cores = {
+		let _span = span_availability.child("get-availability-cores");
+		match get_availability_cores(leaf.hash, &mut sender).await {
+			Err(Error::Runtime(runtime_err)) => {
+				// Don't take down the node on runtime API errors.
+				gum::warn!(target: LOG_TARGET, err = ?runtime_err, "Encountered a runtime API error");
+				return Ok(())
+			},
+			Err(err) => return Err(err),
+			Ok(cores) => cores,
+		}
+	};
+
+	let bitfield = construct_availability_bitfield(
 		leaf.hash,
+		&availability_cores,
 		&span_availability,
 		validator.index(),
 		&mut sender,
 	)
-	.await
+	.await?;
+
+	drop(span_availability);
+
+	if !sign_and_distribute(
+		leaf.hash,
+		&validator,
+		&keystore,
+		bitfield.clone(),
+		&span,
+		&metrics,
+		&mut sender,
+	)
+	.await?
 	{
-		Err(Error::Runtime(runtime_err)) => {
-			// Don't take down the node on runtime API errors.
-			gum::warn!(target: LOG_TARGET, err = ?runtime_err, "Encountered a runtime API error");
+		return Ok(())
+	}
+
+	drop(timer);
+
+	resign_late_chunks(
+		leaf.hash,
+		&availability_cores,
+		bitfield,
+		&validator,
+		&keystore,
+		config,
+		&span,
+		&metrics,
+		&mut sender,
+	)
+	.await
+}
+
+/// Chunks which arrive after signing would only be reported in the bitfield of the next block,
+/// so look out for them until the leaf is deactivated, re-signing the `bitfield` at most
+/// `config.max_resigns` times. If re-signing is disabled, availability is checked once to note
+/// whether the bitfield was stale.
+async fn resign_late_chunks<Sender>(
+	relay_parent: Hash,
+	availability_cores: &[CoreState],
+	mut bitfield: AvailabilityBitfield,
+	validator: &Validator,
+	keystore: &KeystorePtr,
+	config: Config,
+	span: &PerLeafSpan,
+	metrics: &Metrics,
+	sender: &mut Sender,
+) -> Result<(), Error>
+where
+	Sender: overseer::BitfieldSigningSenderTrait,
+{
+	let occupied_cores = availability_cores
+		.iter()
+		.filter(|core| matches!(core, CoreState::Occupied(_)))
+		.count();
+
+	for _ in 0..config.max_resigns.max(1) {
+		if bitfield.0.count_ones() == occupied_cores {
+			break
+		}
+
+		Delay::new(config.resign_interval).await?;
+
+		let span_availability = span.child("resign-availability");
+		let updated = construct_availability_bitfield(
+			relay_parent,
+			availability_cores,
+			&span_availability,
+			validator.index(),
+			sender,
+		)
+		.await?;
+
+		let newly_available = updated.0.iter_ones().filter(|i| !bitfield.0[*i]).count();
+		if newly_available == 0 {
+			continue
+		}
+
+		metrics.on_bitfield_stale(newly_available);
+		if config.max_resigns == 0 {
+			break
+		}
+
+		gum::debug!(
+			target: LOG_TARGET,
+			?relay_parent,
+			newly_available,
+			"Re-signing bitfield after more chunks became available",
+		);
+
+		// Chunks never get lost, but make sure we never take back availability we reported.
+		updated.0.iter_ones().for_each(|i| bitfield.0.set(i, true));
+		if !sign_and_distribute(
+			relay_parent,
+			validator,
+			keystore,
+			bitfield.clone(),
+			span,
+			metrics,
+			sender,
+		)
+		.await?
+		{
 			return Ok(())
-		},
-		Err(err) => return Err(err),
-		Ok(bitfield) => bitfield,
-	};
+		}
+	}
 
-	drop(span_availability);
+	Ok(())
+}
+
+/// Sign the bitfield and distribute it. Returns `false` if it could not be signed.
+async fn sign_and_distribute<Sender>(
+	relay_parent: Hash,
+	validator: &Validator,
+	keystore: &KeystorePtr,
+	bitfield: AvailabilityBitfield,
+	span: &PerLeafSpan,
+	metrics: &Metrics,
+	sender: &mut Sender,
+) -> Result<bool, Error>
+where
+	Sender: overseer::BitfieldSigningSenderTrait,
+{
 	let span_signing = span.child("signing");
 
 	let signed_bitfield =
-		match validator.sign(keystore, bitfield).map_err(|e| Error::Keystore(e))? {
+		match validator.sign(keystore.clone(), bitfield).map_err(|e| Error::Keystore(e))? {
 			Some(b) => b,
 			None => {
 				gum::error!(
 					target: LOG_TARGET,
 					"Key was found at construction, but while signing it could not be found.",
 				);
-				return Ok(())
+				return Ok(false)
 			},
 		};
 
@@ -327,8 +480,11 @@ where
 	let _span_gossip = span.child("gossip");
 
 	sender
-		.send_message(BitfieldDistributionMessage::DistributeBitfield(leaf.hash, signed_bitfield))
+		.send_message(BitfieldDistributionMessage::DistributeBitfield(
+			relay_parent,
+			signed_bitfield,
+		))
 		.await;
 
-	Ok(())
+	Ok(true)
 }
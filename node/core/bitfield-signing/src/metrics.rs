@@ -19,6 +19,8 @@ use polkadot_node_subsystem_util::metrics::{self, prometheus};
 #[derive(Clone)]
 pub(crate) struct MetricsInner {
 	pub(crate) bitfields_signed_total: prometheus::Counter<prometheus::U64>,
+	pub(crate) stale_bitfields_total: prometheus::Counter<prometheus::U64>,
+	pub(crate) late_chunks_total: prometheus::Counter<prometheus::U64>,
 	pub(crate) run: prometheus::Histogram,
 }
 
@@ -33,6 +35,15 @@ impl Metrics {
 		}
 	}
 
+	/// Note that a bitfield we signed was stale, because `late_chunks` more chunks became
+	/// available afterwards.
+	pub fn on_bitfield_stale(&self, late_chunks: usize) {
+		if let Some(metrics) = &self.0 {
+			metrics.stale_bitfields_total.inc();
+			metrics.late_chunks_total.inc_by(late_chunks as u64);
+		}
+	}
+
 	/// Provide a timer for `prune_povs` which observes on drop.
 	pub fn time_run(&self) -> Option<metrics::prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.run.start_timer())
@@ -49,6 +60,20 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			stale_bitfields_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_bitfield_signing_stale_bitfields_total",
+					"Number of signed bitfields which were stale because more chunks became available before the next block.",
+				)?,
+				registry,
+			)?,
+			late_chunks_total: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_bitfield_signing_late_chunks_total",
+					"Number of chunks which became available only after the bitfield was signed.",
+				)?,
+				registry,
+			)?,
 			run: prometheus::register(
 				prometheus::Histogram::with_opts(
 					prometheus::HistogramOpts::new(
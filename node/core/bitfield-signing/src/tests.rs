@@ -15,9 +15,14 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::*;
+use bitvec::{bitvec, order::Lsb0};
 use futures::{executor::block_on, pin_mut, StreamExt};
 use polkadot_node_subsystem::messages::AllMessages;
-use polkadot_primitives::{CandidateHash, OccupiedCore};
+use polkadot_node_subsystem_util::metrics::{prometheus, Metrics as _};
+use polkadot_primitives::{CandidateHash, OccupiedCore, SigningContext, ValidatorId};
+use sp_application_crypto::AppCrypto;
+use sp_keystore::{testing::MemoryKeystore, Keystore};
+use std::sync::Arc;
 use test_helpers::dummy_candidate_descriptor;
 
 fn occupied_core(para_id: u32, candidate_hash: CandidateHash) -> CoreState {
@@ -39,9 +44,15 @@ fn construct_availability_bitfield_works() {
 		let relay_parent = Hash::default();
 		let validator_index = ValidatorIndex(1u32);
 
+		let hash_a = CandidateHash(Hash::repeat_byte(1));
+		let hash_b = CandidateHash(Hash::repeat_byte(2));
+		let availability_cores =
+			vec![CoreState::Free, occupied_core(1, hash_a), occupied_core(2, hash_b)];
+
 		let (mut sender, mut receiver) = polkadot_node_subsystem_test_helpers::sender_receiver();
 		let future = construct_availability_bitfield(
 			relay_parent,
+			&availability_cores,
 			&jaeger::Span::Disabled,
 			validator_index,
 			&mut sender,
@@ -49,18 +60,9 @@ fn construct_availability_bitfield_works() {
 		.fuse();
 		pin_mut!(future);
 
-		let hash_a = CandidateHash(Hash::repeat_byte(1));
-		let hash_b = CandidateHash(Hash::repeat_byte(2));
-
 		loop {
 			futures::select! {
 				m = receiver.next() => match m.unwrap() {
-					AllMessages::RuntimeApi(
-						RuntimeApiMessage::Request(rp, RuntimeApiRequest::AvailabilityCores(tx)),
-					) => {
-						assert_eq!(relay_parent, rp);
-						tx.send(Ok(vec![CoreState::Free, occupied_core(1, hash_a), occupied_core(2, hash_b)])).unwrap();
-					}
 					AllMessages::AvailabilityStore(
 						AvailabilityStoreMessage::QueryChunkAvailability(c_hash, vidx, tx),
 					) => {
@@ -83,3 +85,136 @@ fn construct_availability_bitfield_works() {
 		}
 	});
 }
+
+/// Re-sign `bitfield` for the occupied cores of the given candidates. The candidates in
+/// `available[n]` are available in the `n`th availability check. Returns the distributed
+/// bitfields.
+fn resign_late_chunks_with(
+	config: Config,
+	metrics: &Metrics,
+	bitfield: AvailabilityBitfield,
+	candidates: &[CandidateHash],
+	available: Vec<Vec<CandidateHash>>,
+) -> Vec<AvailabilityBitfield> {
+	let relay_parent = Hash::repeat_byte(42);
+	let keystore: KeystorePtr = Arc::new(MemoryKeystore::new());
+	let validator_id: ValidatorId =
+		Keystore::sr25519_generate_new(&*keystore, ValidatorId::ID, None)
+			.expect("generating sr25519 key not to fail")
+			.into();
+	let validator = Validator::construct(
+		&[validator_id],
+		SigningContext { session_index: 1, parent_hash: relay_parent },
+		keystore.clone(),
+	)
+	.expect("the key is in the keystore");
+
+	let availability_cores: Vec<_> = candidates
+		.iter()
+		.enumerate()
+		.map(|(i, candidate_hash)| occupied_core(i as u32, *candidate_hash))
+		.collect();
+	let span = PerLeafSpan::new(Arc::new(jaeger::Span::Disabled), "test");
+
+	block_on(async move {
+		let (mut sender, mut receiver) = polkadot_node_subsystem_test_helpers::sender_receiver();
+		let future = resign_late_chunks(
+			relay_parent,
+			&availability_cores,
+			bitfield,
+			&validator,
+			&keystore,
+			config,
+			&span,
+			metrics,
+			&mut sender,
+		)
+		.fuse();
+		pin_mut!(future);
+
+		let mut queries = 0;
+		let mut distributed = Vec::new();
+		loop {
+			futures::select! {
+				m = receiver.next() => match m.unwrap() {
+					AllMessages::AvailabilityStore(
+						AvailabilityStoreMessage::QueryChunkAvailability(c_hash, _, tx),
+					) => {
+						let check = queries / candidates.len();
+						queries += 1;
+						tx.send(available[check].contains(&c_hash)).unwrap();
+					},
+					AllMessages::BitfieldDistribution(
+						BitfieldDistributionMessage::DistributeBitfield(r, signed),
+					) => {
+						assert_eq!(r, relay_parent);
+						distributed.push(signed.into_payload());
+					},
+					o => panic!("Unknown message: {:?}", o),
+				},
+				r = future => match r {
+					Ok(()) => break,
+					Err(e) => panic!("Failed: {:?}", e),
+				},
+			}
+		}
+
+		assert_eq!(queries, available.len() * candidates.len());
+		distributed
+	})
+}
+
+fn stale_bitfields_and_late_chunks(metrics: &Metrics) -> (u64, u64) {
+	let metrics = metrics.0.as_ref().unwrap();
+	(metrics.stale_bitfields_total.get(), metrics.late_chunks_total.get())
+}
+
+#[test]
+fn resigns_late_chunks_at_most_max_resigns_times() {
+	let metrics = Metrics::try_register(&prometheus::Registry::new()).unwrap();
+	let candidates = [1, 2, 3].map(|i| CandidateHash(Hash::repeat_byte(i)));
+	let config = Config { resign_interval: Duration::from_millis(1), max_resigns: 2 };
+	let bitfield = AvailabilityBitfield(bitvec![u8, Lsb0; 1, 0, 0]);
+
+	// No new chunk in the first check, one in the second and one in the third, which is not
+	// checked anymore.
+	let distributed = resign_late_chunks_with(
+		config,
+		&metrics,
+		bitfield,
+		&candidates,
+		vec![vec![candidates[0]], vec![candidates[1]]],
+	);
+
+	// The bit of the first candidate is kept, even though it is not available anymore.
+	assert_eq!(distributed, vec![AvailabilityBitfield(bitvec![u8, Lsb0; 1, 1, 0])]);
+	assert_eq!(stale_bitfields_and_late_chunks(&metrics), (1, 1));
+}
+
+#[test]
+fn resigning_stops_once_all_chunks_are_available() {
+	let metrics = Metrics::try_register(&prometheus::Registry::new()).unwrap();
+	let candidates = [1, 2].map(|i| CandidateHash(Hash::repeat_byte(i)));
+	let config = Config { resign_interval: Duration::from_millis(1), max_resigns: 5 };
+	let bitfield = AvailabilityBitfield(bitvec![u8, Lsb0; 0, 0]);
+
+	let distributed =
+		resign_late_chunks_with(config, &metrics, bitfield, &candidates, vec![candidates.to_vec()]);
+
+	assert_eq!(distributed, vec![AvailabilityBitfield(bitvec![u8, Lsb0; 1, 1])]);
+	assert_eq!(stale_bitfields_and_late_chunks(&metrics), (1, 2));
+}
+
+#[test]
+fn stale_bitfields_are_counted_without_resigning() {
+	let metrics = Metrics::try_register(&prometheus::Registry::new()).unwrap();
+	let candidates = [1, 2].map(|i| CandidateHash(Hash::repeat_byte(i)));
+	let config = Config { resign_interval: Duration::from_millis(1), max_resigns: 0 };
+	let bitfield = AvailabilityBitfield(bitvec![u8, Lsb0; 0, 0]);
+
+	let distributed =
+		resign_late_chunks_with(config, &metrics, bitfield, &candidates, vec![vec![candidates[1]]]);
+
+	assert!(distributed.is_empty());
+	assert_eq!(stale_bitfields_and_late_chunks(&metrics), (1, 1));
+}
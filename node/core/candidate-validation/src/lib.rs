@@ -24,14 +24,16 @@
 #![warn(missing_docs)]
 
 use polkadot_node_core_pvf::{
-	InternalValidationError, InvalidCandidate as WasmInvalidCandidate, PrepareError, PrepareStats,
-	PvfPrepData, ValidationError, ValidationHost,
+	ExecuteStats, InternalValidationError, InvalidCandidate as WasmInvalidCandidate, PrepareError,
+	PrepareFailure, PrepareStats, Priority, PvfPrepData, ValidationError, ValidationHost,
 };
 use polkadot_node_primitives::{
-	BlockData, InvalidCandidate, PoV, ValidationResult, POV_BOMB_LIMIT, VALIDATION_CODE_BOMB_LIMIT,
+	BlockData, InvalidCandidate, PoV, PvfRejection, PvfRejectionClass, ValidationResult,
+	POV_BOMB_LIMIT, VALIDATION_CODE_BOMB_LIMIT,
 };
 use polkadot_node_subsystem::{
 	errors::RuntimeApiError,
+	jaeger,
 	messages::{
 		CandidateValidationMessage, PreCheckOutcome, RuntimeApiMessage, RuntimeApiRequest,
 		ValidationFailed,
@@ -42,12 +44,14 @@ use polkadot_node_subsystem::{
 use polkadot_node_subsystem_util::executor_params_at_relay_parent;
 use polkadot_parachain::primitives::{ValidationParams, ValidationResult as WasmValidationResult};
 use polkadot_primitives::{
-	CandidateCommitments, CandidateDescriptor, CandidateReceipt, ExecutorParams, Hash,
-	OccupiedCoreAssumption, PersistedValidationData, PvfExecTimeoutKind, PvfPrepTimeoutKind,
-	ValidationCode, ValidationCodeHash,
+	CandidateCommitments, CandidateDescriptor, CandidateReceipt, CollatorId, CollatorSignature,
+	ExecutorParam, ExecutorParams, Hash, Id as ParaId, OccupiedCoreAssumption,
+	PersistedValidationData, PvfExecTimeoutKind, PvfPrepTimeoutKind, ValidationCode,
+	ValidationCodeHash,
 };
 
 use parity_scale_codec::Encode;
+use wasmparser::{Validator, WasmFeatures};
 
 use futures::{channel::oneshot, prelude::*};
 
@@ -59,6 +63,11 @@ use std::{
 
 use async_trait::async_trait;
 
+pub use polkadot_node_core_pvf::{
+	ArtifactImport as PvfArtifactImport, ImportVerification as PvfImportVerification,
+	WorkerPools as PvfWorkerPools,
+};
+
 mod metrics;
 use self::metrics::Metrics;
 
@@ -96,6 +105,12 @@ pub struct Config {
 	/// The path to the executable which can be used for spawning PVF compilation & validation
 	/// workers.
 	pub program_path: PathBuf,
+	/// The sizing of the PVF worker pools.
+	pub pvf_worker_pools: PvfWorkerPools,
+	/// The import of precompiled PVF artifacts, if enabled.
+	pub pvf_artifact_import: Option<PvfArtifactImport>,
+	/// Whether to count the host calls made by the executed PVFs.
+	pub pvf_count_host_calls: bool,
 }
 
 /// The candidate validation subsystem.
@@ -130,6 +145,9 @@ impl<Context> CandidateValidationSubsystem {
 			self.pvf_metrics,
 			self.config.artifacts_cache_path,
 			self.config.program_path,
+			self.config.pvf_worker_pools,
+			self.config.pvf_artifact_import,
+			self.config.pvf_count_host_calls,
 		)
 		.map_err(|e| SubsystemError::with_origin("candidate-validation", e))
 		.boxed();
@@ -144,11 +162,15 @@ async fn run<Context>(
 	pvf_metrics: polkadot_node_core_pvf::Metrics,
 	cache_path: PathBuf,
 	program_path: PathBuf,
+	pvf_worker_pools: PvfWorkerPools,
+	pvf_artifact_import: Option<PvfArtifactImport>,
+	pvf_count_host_calls: bool,
 ) -> SubsystemResult<()> {
-	let (validation_host, task) = polkadot_node_core_pvf::start(
-		polkadot_node_core_pvf::Config::new(cache_path, program_path),
-		pvf_metrics,
-	);
+	let mut config = polkadot_node_core_pvf::Config::new(cache_path, program_path)
+		.with_worker_pools(&pvf_worker_pools);
+	config.artifact_import = pvf_artifact_import;
+	config.count_host_calls = pvf_count_host_calls;
+	let (validation_host, task) = polkadot_node_core_pvf::start(config, pvf_metrics);
 	ctx.spawn_blocking("pvf-validation-host", task.boxed())?;
 
 	loop {
@@ -210,6 +232,7 @@ async fn run<Context>(
 								candidate_receipt,
 								pov,
 								timeout,
+								CandidateChecks::Full,
 								&metrics,
 							)
 							.await;
@@ -221,6 +244,36 @@ async fn run<Context>(
 
 					ctx.spawn("validate-from-exhaustive", bg.boxed())?;
 				},
+				CandidateValidationMessage::DryRun(
+					relay_parent,
+					para_id,
+					pov,
+					validation_code,
+					response_sender,
+				) => {
+					let bg = {
+						let mut sender = ctx.sender().clone();
+						let metrics = metrics.clone();
+						let validation_host = validation_host.clone();
+
+						async move {
+							let res = dry_run_candidate(
+								&mut sender,
+								validation_host,
+								relay_parent,
+								para_id,
+								pov,
+								validation_code,
+								&metrics,
+							)
+							.await;
+
+							let _ = response_sender.send(res);
+						}
+					};
+
+					ctx.spawn("candidate-validation-dry-run", bg.boxed())?;
+				},
 				CandidateValidationMessage::PreCheck(
 					relay_parent,
 					validation_code_hash,
@@ -326,7 +379,10 @@ where
 					?validation_code_hash,
 					"precheck: requested validation code is not found on-chain!",
 				);
-				return PreCheckOutcome::Failed
+				return PreCheckOutcome::Failed(pvf_rejection(
+					PvfRejectionClass::CodeUnavailable,
+					"validation code is not found on-chain".into(),
+				))
 			},
 		};
 
@@ -347,7 +403,10 @@ where
 				?validation_code_hash,
 				"precheck: failed to acquire executor params for the session, thus voting against.",
 			);
-			return PreCheckOutcome::Invalid
+			return PreCheckOutcome::Invalid(pvf_rejection(
+				PvfRejectionClass::ExecutorParamsUnavailable,
+				"failed to acquire executor params for the session".into(),
+			))
 		};
 
 	let timeout = pvf_prep_timeout(&executor_params, PvfPrepTimeoutKind::Precheck);
@@ -359,19 +418,105 @@ where
 		Ok(code) => PvfPrepData::from_code(code.into_owned(), executor_params, timeout),
 		Err(e) => {
 			gum::debug!(target: LOG_TARGET, err=?e, "precheck: cannot decompress validation code");
-			return PreCheckOutcome::Invalid
+			return PreCheckOutcome::Invalid(pvf_rejection(
+				PvfRejectionClass::Decompression,
+				format!("cannot decompress validation code: {:?}", e),
+			))
 		},
 	};
 
+	let code = pvf.code();
+	let executor_params = pvf.executor_params();
+	let compile_start = Instant::now();
 	match validation_backend.precheck_pvf(pvf).await {
 		Ok(_) => PreCheckOutcome::Valid,
-		Err(prepare_err) =>
+		Err(PrepareFailure { error: prepare_err, memory_stats }) => {
+			let class = match prepare_err {
+				PrepareError::Prevalidation(_) => PvfRejectionClass::Prevalidation,
+				PrepareError::Preparation(_) => PvfRejectionClass::Preparation,
+				PrepareError::Panic(_) => PvfRejectionClass::Panic,
+				PrepareError::TimedOut => PvfRejectionClass::TimedOut,
+				PrepareError::IoErr(_) |
+				PrepareError::CreateTmpFileErr(_) |
+				PrepareError::RenameTmpFileErr(_) => PvfRejectionClass::WorkerFailure,
+			};
+			let rejection = PvfRejection {
+				compile_time_ms: Some(compile_start.elapsed().as_millis() as u64),
+				memory_used_kb: memory_stats.and_then(|memory_stats| memory_stats.peak_kb()),
+				wasm_feature_violations: wasm_feature_violations(&code, &executor_params),
+				..pvf_rejection(class, prepare_err.to_string())
+			};
+
 			if prepare_err.is_deterministic() {
-				PreCheckOutcome::Invalid
+				PreCheckOutcome::Invalid(rejection)
 			} else {
-				PreCheckOutcome::Failed
-			},
+				PreCheckOutcome::Failed(rejection)
+			}
+		},
+	}
+}
+
+fn pvf_rejection(class: PvfRejectionClass, reason: String) -> PvfRejection {
+	PvfRejection {
+		class,
+		reason,
+		compile_time_ms: None,
+		memory_used_kb: None,
+		wasm_feature_violations: Vec::new(),
+		attempts: 1,
+	}
+}
+
+/// Returns the wasm features used by the PVF which are not enabled by the executor parameters.
+///
+/// The code is validated with all the features enabled except for the one being checked, so a
+/// failing validation means the PVF uses it. Code which does not validate even with all the
+/// features enabled is not a matter of features, and nothing is reported for it.
+fn wasm_feature_violations(code: &[u8], executor_params: &ExecutorParams) -> Vec<String> {
+	type Toggle = fn(&mut WasmFeatures) -> &mut bool;
+	// The features the executor may enable. Sign extension ops, saturating float to int
+	// conversions and mutable globals are always enabled by Wasmtime, so they are not checked.
+	const FEATURES: &[(&str, Toggle)] = &[
+		("bulk-memory", |f| &mut f.bulk_memory),
+		("reference-types", |f| &mut f.reference_types),
+		("multi-value", |f| &mut f.multi_value),
+		("simd", |f| &mut f.simd),
+		("relaxed-simd", |f| &mut f.relaxed_simd),
+		("threads", |f| &mut f.threads),
+		("tail-call", |f| &mut f.tail_call),
+		("multi-memory", |f| &mut f.multi_memory),
+		("exceptions", |f| &mut f.exceptions),
+		("memory64", |f| &mut f.memory64),
+		("extended-const", |f| &mut f.extended_const),
+	];
+
+	let is_enabled = |feature: &str| match feature {
+		"bulk-memory" => executor_params
+			.iter()
+			.any(|param| matches!(param, ExecutorParam::WasmExtBulkMemory)),
+		_ => false,
+	};
+	let validates =
+		|features: WasmFeatures| Validator::new_with_features(features).validate_all(code).is_ok();
+
+	let mut all_features = WasmFeatures::default();
+	for (_, toggle) in FEATURES {
+		*toggle(&mut all_features) = true;
+	}
+	if !validates(all_features) {
+		return Vec::new()
 	}
+
+	FEATURES
+		.iter()
+		.filter(|(feature, _)| !is_enabled(feature))
+		.filter(|(_, toggle)| {
+			let mut features = all_features;
+			*toggle(&mut features) = false;
+			!validates(features)
+		})
+		.map(|(feature, _)| feature.to_string())
+		.collect()
 }
 
 #[derive(Debug)]
@@ -381,6 +526,44 @@ enum AssumptionCheckOutcome {
 	BadRequest,
 }
 
+async fn request_persisted_validation_data<Sender>(
+	sender: &mut Sender,
+	relay_parent: Hash,
+	para_id: ParaId,
+	assumption: OccupiedCoreAssumption,
+) -> Result<Option<PersistedValidationData>, RuntimeRequestFailed>
+where
+	Sender: SubsystemSender<RuntimeApiMessage>,
+{
+	let (tx, rx) = oneshot::channel();
+	runtime_api_request(
+		sender,
+		relay_parent,
+		RuntimeApiRequest::PersistedValidationData(para_id, assumption, tx),
+		rx,
+	)
+	.await
+}
+
+async fn request_validation_code<Sender>(
+	sender: &mut Sender,
+	relay_parent: Hash,
+	para_id: ParaId,
+	assumption: OccupiedCoreAssumption,
+) -> Result<Option<ValidationCode>, RuntimeRequestFailed>
+where
+	Sender: SubsystemSender<RuntimeApiMessage>,
+{
+	let (tx, rx) = oneshot::channel();
+	runtime_api_request(
+		sender,
+		relay_parent,
+		RuntimeApiRequest::ValidationCode(para_id, assumption, tx),
+		rx,
+	)
+	.await
+}
+
 async fn check_assumption_validation_data<Sender>(
 	sender: &mut Sender,
 	descriptor: &CandidateDescriptor,
@@ -389,31 +572,26 @@ async fn check_assumption_validation_data<Sender>(
 where
 	Sender: SubsystemSender<RuntimeApiMessage>,
 {
-	let validation_data = {
-		let (tx, rx) = oneshot::channel();
-		let d = runtime_api_request(
-			sender,
-			descriptor.relay_parent,
-			RuntimeApiRequest::PersistedValidationData(descriptor.para_id, assumption, tx),
-			rx,
-		)
-		.await;
-
-		match d {
-			Ok(None) | Err(RuntimeRequestFailed) => return AssumptionCheckOutcome::BadRequest,
-			Ok(Some(d)) => d,
-		}
+	let validation_data = match request_persisted_validation_data(
+		sender,
+		descriptor.relay_parent,
+		descriptor.para_id,
+		assumption,
+	)
+	.await
+	{
+		Ok(None) | Err(RuntimeRequestFailed) => return AssumptionCheckOutcome::BadRequest,
+		Ok(Some(d)) => d,
 	};
 
 	let persisted_validation_data_hash = validation_data.hash();
 
 	if descriptor.persisted_validation_data_hash == persisted_validation_data_hash {
-		let (code_tx, code_rx) = oneshot::channel();
-		let validation_code = runtime_api_request(
+		let validation_code = request_validation_code(
 			sender,
 			descriptor.relay_parent,
-			RuntimeApiRequest::ValidationCode(descriptor.para_id, assumption, code_tx),
-			code_rx,
+			descriptor.para_id,
+			assumption,
 		)
 		.await;
 
@@ -508,6 +686,7 @@ where
 		candidate_receipt.clone(),
 		pov,
 		exec_timeout_kind,
+		CandidateChecks::Full,
 		metrics,
 	)
 	.await;
@@ -536,6 +715,87 @@ where
 	validation_result
 }
 
+/// Which checks are performed on a candidate besides executing the PVF.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CandidateChecks {
+	/// Check the candidate against its descriptor and its commitments.
+	Full,
+	/// Only check the size of the PoV, because the candidate was made up from the inputs.
+	DryRun,
+}
+
+/// Validates a PoV against the relay-chain state at the given relay-parent, without a candidate.
+///
+/// See [`CandidateValidationMessage::DryRun`].
+async fn dry_run_candidate<Sender>(
+	sender: &mut Sender,
+	validation_backend: impl ValidationBackend + Send,
+	relay_parent: Hash,
+	para_id: ParaId,
+	pov: Arc<PoV>,
+	validation_code: Option<ValidationCode>,
+	metrics: &Metrics,
+) -> Result<ValidationResult, ValidationFailed>
+where
+	Sender: SubsystemSender<RuntimeApiMessage>,
+{
+	// A collator builds on top of the candidate pending availability, if any.
+	let persisted_validation_data_hash = match request_persisted_validation_data(
+		sender,
+		relay_parent,
+		para_id,
+		OccupiedCoreAssumption::Included,
+	)
+	.await
+	{
+		Ok(Some(persisted_validation_data)) => persisted_validation_data.hash(),
+		Ok(None) =>
+			return Err(ValidationFailed(format!(
+				"No persisted validation data of para {} at the relay-parent",
+				para_id
+			))),
+		Err(RuntimeRequestFailed) =>
+			return Err(ValidationFailed("Persisted validation data: Bad request".into())),
+	};
+
+	// There is no collator, so the candidate is made up from the inputs. The collator signature is
+	// not checked for dry runs.
+	let mut descriptor = CandidateDescriptor {
+		para_id,
+		relay_parent,
+		collator: CollatorId::from(sp_core::sr25519::Public::from_raw([0; 32])),
+		persisted_validation_data_hash,
+		pov_hash: pov.hash(),
+		erasure_root: Hash::zero(),
+		signature: CollatorSignature::from(sp_core::sr25519::Signature::from_raw([0; 64])),
+		para_head: Hash::zero(),
+		validation_code_hash: ValidationCodeHash::from(Hash::zero()),
+	};
+
+	// The made up candidate is looked up like any other, trying all occupied core assumptions.
+	let (persisted_validation_data, current_validation_code) =
+		match find_validation_data(sender, &descriptor).await? {
+			Some(found) => found,
+			None => return Ok(ValidationResult::Invalid(InvalidCandidate::BadParent)),
+		};
+	let validation_code = validation_code.unwrap_or(current_validation_code);
+	descriptor.validation_code_hash = validation_code.hash();
+
+	// Dry runs are requested over RPC and must not compete with backing for the execute queue.
+	validate_candidate_exhaustive(
+		sender,
+		validation_backend,
+		persisted_validation_data,
+		validation_code,
+		CandidateReceipt { descriptor, commitments_hash: Hash::zero() },
+		pov,
+		PvfExecTimeoutKind::Approval,
+		CandidateChecks::DryRun,
+		metrics,
+	)
+	.await
+}
+
 async fn validate_candidate_exhaustive<Sender>(
 	sender: &mut Sender,
 	mut validation_backend: impl ValidationBackend + Send,
@@ -544,6 +804,7 @@ async fn validate_candidate_exhaustive<Sender>(
 	candidate_receipt: CandidateReceipt,
 	pov: Arc<PoV>,
 	exec_timeout_kind: PvfExecTimeoutKind,
+	checks: CandidateChecks,
 	metrics: &Metrics,
 ) -> Result<ValidationResult, ValidationFailed>
 where
@@ -553,6 +814,8 @@ where
 
 	let validation_code_hash = validation_code.hash();
 	let para_id = candidate_receipt.descriptor.para_id;
+	let mut span = jaeger::Span::new(candidate_receipt.hash(), "validate-candidate-exhaustive")
+		.with_para_id(para_id);
 	gum::debug!(
 		target: LOG_TARGET,
 		?validation_code_hash,
@@ -560,12 +823,16 @@ where
 		"About to validate a candidate.",
 	);
 
-	if let Err(e) = perform_basic_checks(
-		&candidate_receipt.descriptor,
-		persisted_validation_data.max_pov_size,
-		&pov,
-		&validation_code_hash,
-	) {
+	let basic_checks = match checks {
+		CandidateChecks::Full => perform_basic_checks(
+			&candidate_receipt.descriptor,
+			persisted_validation_data.max_pov_size,
+			&pov,
+			&validation_code_hash,
+		),
+		CandidateChecks::DryRun => check_pov_size(persisted_validation_data.max_pov_size, &pov),
+	};
+	if let Err(e) = basic_checks {
 		gum::info!(target: LOG_TARGET, ?para_id, "Invalid candidate (basic checks)");
 		return Ok(ValidationResult::Invalid(e))
 	}
@@ -639,6 +906,20 @@ where
 		gum::info!(target: LOG_TARGET, ?para_id, ?error, "Failed to validate candidate");
 	}
 
+	let result = result.map(|(res, stats)| {
+		gum::trace!(target: LOG_TARGET, ?para_id, ?stats, "Executed the PVF");
+		metrics.observe_execution_stats(para_id, &stats);
+		span.add_uint_tag("cpu-time-us", stats.cpu_time_elapsed.as_micros() as u64);
+		span.add_uint_tag("wall-time-us", stats.wall_time_elapsed.as_micros() as u64);
+		if let Some(max_rss) = stats.max_rss {
+			span.add_int_tag("max-rss-kb", max_rss);
+		}
+		if let Some(host_calls) = stats.host_calls {
+			span.add_uint_tag("host-calls", host_calls);
+		}
+		res
+	});
+
 	match result {
 		Err(ValidationError::InternalError(e)) => {
 			gum::warn!(
@@ -651,6 +932,9 @@ where
 		},
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::HardTimeout)) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)),
+		Err(ValidationError::InvalidCandidate(
+			WasmInvalidCandidate::InstructionBudgetExhausted,
+		)) => Ok(ValidationResult::Invalid(InvalidCandidate::Timeout)),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::WorkerReportedError(e))) =>
 			Ok(ValidationResult::Invalid(InvalidCandidate::ExecutionError(e))),
 		Err(ValidationError::InvalidCandidate(WasmInvalidCandidate::AmbiguousWorkerDeath)) =>
@@ -673,6 +957,17 @@ where
 			);
 			Err(ValidationFailed(e))
 		},
+		Ok(res) if checks == CandidateChecks::DryRun => {
+			let outputs = CandidateCommitments {
+				head_data: res.head_data,
+				upward_messages: res.upward_messages,
+				horizontal_messages: res.horizontal_messages,
+				new_validation_code: res.new_validation_code,
+				processed_downward_messages: res.processed_downward_messages,
+				hrmp_watermark: res.hrmp_watermark,
+			};
+			Ok(ValidationResult::Valid(outputs, persisted_validation_data))
+		},
 		Ok(res) =>
 			if res.head_data.hash() != candidate_receipt.descriptor.para_head {
 				gum::info!(target: LOG_TARGET, ?para_id, "Invalid candidate (para_head)");
@@ -710,7 +1005,8 @@ trait ValidationBackend {
 		pvf: PvfPrepData,
 		exec_timeout: Duration,
 		encoded_params: Vec<u8>,
-	) -> Result<WasmValidationResult, ValidationError>;
+		priority: Priority,
+	) -> Result<(WasmValidationResult, ExecuteStats), ValidationError>;
 
 	/// Tries executing a PVF. Will retry once if an error is encountered that may have been
 	/// transient.
@@ -724,15 +1020,21 @@ trait ValidationBackend {
 		exec_timeout_kind: PvfExecTimeoutKind,
 		params: ValidationParams,
 		executor_params: ExecutorParams,
-	) -> Result<WasmValidationResult, ValidationError> {
+	) -> Result<(WasmValidationResult, ExecuteStats), ValidationError> {
 		let prep_timeout = pvf_prep_timeout(&executor_params, PvfPrepTimeoutKind::Lenient);
 		// Construct the PVF a single time, since it is an expensive operation. Cloning it is cheap.
 		let pvf = PvfPrepData::from_code(raw_validation_code, executor_params, prep_timeout);
+		// Backing is on the critical path, so it must not wait behind approvals and disputes.
+		let priority = match exec_timeout_kind {
+			PvfExecTimeoutKind::Backing => Priority::Critical,
+			PvfExecTimeoutKind::Approval => Priority::Normal,
+		};
 		// We keep track of the total time that has passed and stop retrying if we are taking too long.
 		let total_time_start = Instant::now();
 
-		let mut validation_result =
-			self.validate_candidate(pvf.clone(), exec_timeout, params.encode()).await;
+		let mut validation_result = self
+			.validate_candidate(pvf.clone(), exec_timeout, params.encode(), priority)
+			.await;
 		if validation_result.is_ok() {
 			return validation_result
 		}
@@ -782,15 +1084,16 @@ trait ValidationBackend {
 
 				// Encode the params again when re-trying. We expect the retry case to be relatively
 				// rare, and we want to avoid unconditionally cloning data.
-				validation_result =
-					self.validate_candidate(pvf.clone(), new_timeout, params.encode()).await;
+				validation_result = self
+					.validate_candidate(pvf.clone(), new_timeout, params.encode(), priority)
+					.await;
 			}
 		}
 
 		validation_result
 	}
 
-	async fn precheck_pvf(&mut self, pvf: PvfPrepData) -> Result<PrepareStats, PrepareError>;
+	async fn precheck_pvf(&mut self, pvf: PvfPrepData) -> Result<PrepareStats, PrepareFailure>;
 }
 
 #[async_trait]
@@ -801,9 +1104,8 @@ impl ValidationBackend for ValidationHost {
 		pvf: PvfPrepData,
 		exec_timeout: Duration,
 		encoded_params: Vec<u8>,
-	) -> Result<WasmValidationResult, ValidationError> {
-		let priority = polkadot_node_core_pvf::Priority::Normal;
-
+		priority: Priority,
+	) -> Result<(WasmValidationResult, ExecuteStats), ValidationError> {
 		let (tx, rx) = oneshot::channel();
 		if let Err(err) = self.execute_pvf(pvf, exec_timeout, encoded_params, priority, tx).await {
 			return Err(InternalValidationError::HostCommunication(format!(
@@ -820,14 +1122,16 @@ impl ValidationBackend for ValidationHost {
 		})?
 	}
 
-	async fn precheck_pvf(&mut self, pvf: PvfPrepData) -> Result<PrepareStats, PrepareError> {
+	async fn precheck_pvf(&mut self, pvf: PvfPrepData) -> Result<PrepareStats, PrepareFailure> {
 		let (tx, rx) = oneshot::channel();
 		if let Err(err) = self.precheck_pvf(pvf, tx).await {
 			// Return an IO error if there was an error communicating with the host.
-			return Err(PrepareError::IoErr(err))
+			return Err(PrepareError::IoErr(err).into())
 		}
 
-		let precheck_result = rx.await.map_err(|err| PrepareError::IoErr(err.to_string()))?;
+		let precheck_result = rx
+			.await
+			.map_err(|err| PrepareFailure::from(PrepareError::IoErr(err.to_string())))?;
 
 		precheck_result
 	}
@@ -843,10 +1147,7 @@ fn perform_basic_checks(
 ) -> Result<(), InvalidCandidate> {
 	let pov_hash = pov.hash();
 
-	let encoded_pov_size = pov.encoded_size();
-	if encoded_pov_size > max_pov_size as usize {
-		return Err(InvalidCandidate::ParamsTooLarge(encoded_pov_size as u64))
-	}
+	check_pov_size(max_pov_size, pov)?;
 
 	if pov_hash != candidate.pov_hash {
 		return Err(InvalidCandidate::PoVHashMismatch)
@@ -863,6 +1164,16 @@ fn perform_basic_checks(
 	Ok(())
 }
 
+/// Checks that the encoded PoV does not exceed the maximum size.
+fn check_pov_size(max_pov_size: u32, pov: &PoV) -> Result<(), InvalidCandidate> {
+	let encoded_pov_size = pov.encoded_size();
+	if encoded_pov_size > max_pov_size as usize {
+		return Err(InvalidCandidate::ParamsTooLarge(encoded_pov_size as u64))
+	}
+
+	Ok(())
+}
+
 fn pvf_prep_timeout(executor_params: &ExecutorParams, kind: PvfPrepTimeoutKind) -> Duration {
 	if let Some(timeout) = executor_params.pvf_prep_timeout(kind) {
 		return timeout
@@ -15,7 +15,9 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 use super::{ValidationFailed, ValidationResult};
+use polkadot_node_core_pvf::ExecuteStats;
 use polkadot_node_metrics::metrics::{self, prometheus};
+use polkadot_primitives::Id as ParaId;
 
 #[derive(Clone)]
 pub(crate) struct MetricsInner {
@@ -25,6 +27,10 @@ pub(crate) struct MetricsInner {
 	pub(crate) validate_candidate_exhaustive: prometheus::Histogram,
 	pub(crate) pov_size: prometheus::Histogram,
 	pub(crate) code_size: prometheus::Histogram,
+	pub(crate) execution_cpu_time: prometheus::HistogramVec,
+	pub(crate) execution_wall_time: prometheus::HistogramVec,
+	pub(crate) execution_max_rss: prometheus::HistogramVec,
+	pub(crate) execution_host_calls: prometheus::HistogramVec,
 }
 
 /// Candidate validation metrics.
@@ -82,6 +88,32 @@ impl Metrics {
 			metrics.pov_size.observe(pov_size as f64);
 		}
 	}
+
+	/// Observe the resources used by a PVF execution of the given para.
+	pub fn observe_execution_stats(&self, para_id: ParaId, stats: &ExecuteStats) {
+		if let Some(metrics) = &self.0 {
+			let para_id = u32::from(para_id).to_string();
+			let labels = [para_id.as_str()];
+
+			metrics
+				.execution_cpu_time
+				.with_label_values(&labels)
+				.observe(stats.cpu_time_elapsed.as_secs_f64());
+			metrics
+				.execution_wall_time
+				.with_label_values(&labels)
+				.observe(stats.wall_time_elapsed.as_secs_f64());
+			if let Some(max_rss) = stats.max_rss {
+				metrics.execution_max_rss.with_label_values(&labels).observe(max_rss as f64);
+			}
+			if let Some(host_calls) = stats.host_calls {
+				metrics
+					.execution_host_calls
+					.with_label_values(&labels)
+					.observe(host_calls as f64);
+			}
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -144,6 +176,62 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			execution_cpu_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_candidate_validation_execution_cpu_time",
+						"CPU time taken by PVF execution, per para",
+					)
+					.buckets(vec![
+						0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0,
+						12.0,
+					]),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
+			execution_wall_time: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_candidate_validation_execution_wall_time",
+						"Wall clock time taken by PVF execution, per para",
+					)
+					.buckets(vec![
+						0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0,
+						12.0,
+					]),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
+			execution_max_rss: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_candidate_validation_execution_max_rss",
+						"ru_maxrss (maximum resident set size) observed for PVF execution (in kilobytes), per para",
+					)
+					.buckets(
+						prometheus::exponential_buckets(8192.0, 2.0, 10)
+							.expect("arguments are always valid; qed"),
+					),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
+			execution_host_calls: prometheus::register(
+				prometheus::HistogramVec::new(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_candidate_validation_execution_host_calls",
+						"Number of host function calls made by PVF execution, per para",
+					)
+					.buckets(
+						prometheus::exponential_buckets(16.0, 4.0, 10)
+							.expect("arguments are always valid; qed"),
+					),
+					&["para_id"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
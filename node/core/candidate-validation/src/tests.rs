@@ -18,7 +18,7 @@ use super::*;
 use ::test_helpers::{dummy_hash, make_valid_candidate_descriptor};
 use assert_matches::assert_matches;
 use futures::executor;
-use polkadot_node_core_pvf::PrepareError;
+use polkadot_node_core_pvf::{PrepareError, PrepareFailure};
 use polkadot_node_subsystem::messages::AllMessages;
 use polkadot_node_subsystem_test_helpers as test_helpers;
 use polkadot_node_subsystem_util::reexports::SubsystemContext;
@@ -378,17 +378,18 @@ fn check_does_not_match() {
 struct MockValidateCandidateBackend {
 	result_list: Vec<Result<WasmValidationResult, ValidationError>>,
 	num_times_called: usize,
+	priorities: Vec<Priority>,
 }
 
 impl MockValidateCandidateBackend {
 	fn with_hardcoded_result(result: Result<WasmValidationResult, ValidationError>) -> Self {
-		Self { result_list: vec![result], num_times_called: 0 }
+		Self::with_hardcoded_result_list(vec![result])
 	}
 
 	fn with_hardcoded_result_list(
 		result_list: Vec<Result<WasmValidationResult, ValidationError>>,
 	) -> Self {
-		Self { result_list, num_times_called: 0 }
+		Self { result_list, num_times_called: 0, priorities: Vec::new() }
 	}
 }
 
@@ -399,20 +400,59 @@ impl ValidationBackend for MockValidateCandidateBackend {
 		_pvf: PvfPrepData,
 		_timeout: Duration,
 		_encoded_params: Vec<u8>,
-	) -> Result<WasmValidationResult, ValidationError> {
+		priority: Priority,
+	) -> Result<(WasmValidationResult, ExecuteStats), ValidationError> {
 		// This is expected to panic if called more times than expected, indicating an error in the
 		// test.
 		let result = self.result_list[self.num_times_called].clone();
 		self.num_times_called += 1;
+		self.priorities.push(priority);
 
-		result
+		result.map(|result| (result, ExecuteStats::default()))
 	}
 
-	async fn precheck_pvf(&mut self, _pvf: PvfPrepData) -> Result<PrepareStats, PrepareError> {
+	async fn precheck_pvf(&mut self, _pvf: PvfPrepData) -> Result<PrepareStats, PrepareFailure> {
 		unreachable!()
 	}
 }
 
+#[test]
+fn backing_is_executed_with_critical_priority() {
+	let validation_result = WasmValidationResult {
+		head_data: HeadData(vec![1, 1, 1]),
+		new_validation_code: None,
+		upward_messages: Default::default(),
+		horizontal_messages: Default::default(),
+		processed_downward_messages: 0,
+		hrmp_watermark: 0,
+	};
+
+	for (exec_timeout_kind, priority) in [
+		(PvfExecTimeoutKind::Backing, Priority::Critical),
+		(PvfExecTimeoutKind::Approval, Priority::Normal),
+	] {
+		let mut backend =
+			MockValidateCandidateBackend::with_hardcoded_result(Ok(validation_result.clone()));
+		let params = ValidationParams {
+			parent_head: HeadData(vec![]),
+			block_data: BlockData(vec![1; 32]),
+			relay_parent_number: 1,
+			relay_parent_storage_root: dummy_hash(),
+		};
+
+		let result = executor::block_on(backend.validate_candidate_with_retry(
+			vec![2; 16],
+			Duration::from_secs(1),
+			exec_timeout_kind,
+			params,
+			ExecutorParams::default(),
+		));
+
+		assert!(result.is_ok());
+		assert_eq!(backend.priorities, vec![priority]);
+	}
+}
+
 #[test]
 fn candidate_validation_ok_is_ok() {
 	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
@@ -474,6 +514,7 @@ fn candidate_validation_ok_is_ok() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	})
@@ -533,6 +574,7 @@ fn candidate_validation_bad_return_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	});
@@ -605,6 +647,7 @@ fn candidate_validation_one_ambiguous_error_is_valid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	})
@@ -665,6 +708,7 @@ fn candidate_validation_multiple_ambiguous_errors_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	})
@@ -722,6 +766,7 @@ fn candidate_validation_retry_internal_errors() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	});
@@ -778,6 +823,7 @@ fn candidate_validation_retry_panic_errors() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	});
@@ -829,6 +875,7 @@ fn candidate_validation_timeout_is_internal_error() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	});
@@ -881,6 +928,7 @@ fn candidate_validation_commitment_hash_mismatch_is_invalid() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	})
@@ -932,6 +980,7 @@ fn candidate_validation_code_mismatch_is_invalid() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		CandidateChecks::Full,
 		&Default::default(),
 	))
 	.unwrap();
@@ -995,6 +1044,7 @@ fn compressed_code_works() {
 			candidate_receipt,
 			Arc::new(pov),
 			PvfExecTimeoutKind::Backing,
+			CandidateChecks::Full,
 			&metrics,
 		)
 	});
@@ -1048,6 +1098,7 @@ fn code_decompression_failure_is_error() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		CandidateChecks::Full,
 		&Default::default(),
 	));
 
@@ -1101,6 +1152,7 @@ fn pov_decompression_failure_is_invalid() {
 		candidate_receipt,
 		Arc::new(pov),
 		PvfExecTimeoutKind::Backing,
+		CandidateChecks::Full,
 		&Default::default(),
 	));
 
@@ -1108,11 +1160,11 @@ fn pov_decompression_failure_is_invalid() {
 }
 
 struct MockPreCheckBackend {
-	result: Result<PrepareStats, PrepareError>,
+	result: Result<PrepareStats, PrepareFailure>,
 }
 
 impl MockPreCheckBackend {
-	fn with_hardcoded_result(result: Result<PrepareStats, PrepareError>) -> Self {
+	fn with_hardcoded_result(result: Result<PrepareStats, PrepareFailure>) -> Self {
 		Self { result }
 	}
 }
@@ -1124,15 +1176,107 @@ impl ValidationBackend for MockPreCheckBackend {
 		_pvf: PvfPrepData,
 		_timeout: Duration,
 		_encoded_params: Vec<u8>,
-	) -> Result<WasmValidationResult, ValidationError> {
+		_priority: Priority,
+	) -> Result<(WasmValidationResult, ExecuteStats), ValidationError> {
 		unreachable!()
 	}
 
-	async fn precheck_pvf(&mut self, _pvf: PvfPrepData) -> Result<PrepareStats, PrepareError> {
+	async fn precheck_pvf(&mut self, _pvf: PvfPrepData) -> Result<PrepareStats, PrepareFailure> {
 		self.result.clone()
 	}
 }
 
+#[test]
+fn dry_run_skips_candidate_checks() {
+	let validation_data = PersistedValidationData { max_pov_size: 1024, ..Default::default() };
+	let current_validation_code = ValidationCode(vec![2; 16]);
+	let validation_code = ValidationCode(vec![3; 16]);
+	let pov = PoV { block_data: BlockData(vec![1; 32]) };
+	let relay_parent = [2; 32].into();
+	let para_id = ParaId::from(5_u32);
+
+	let validation_result = WasmValidationResult {
+		head_data: HeadData(vec![1, 1, 1]),
+		new_validation_code: None,
+		upward_messages: Default::default(),
+		horizontal_messages: Default::default(),
+		processed_downward_messages: 0,
+		hrmp_watermark: 0,
+	};
+
+	let pool = TaskExecutor::new();
+	let (mut ctx, mut ctx_handle) =
+		test_helpers::make_subsystem_context::<AllMessages, _>(pool.clone());
+	let metrics = Metrics::default();
+
+	let (check_fut, check_result) = dry_run_candidate(
+		ctx.sender(),
+		MockValidateCandidateBackend::with_hardcoded_result(Ok(validation_result)),
+		relay_parent,
+		para_id,
+		Arc::new(pov),
+		Some(validation_code),
+		&metrics,
+	)
+	.remote_handle();
+
+	let test_fut = async move {
+		// Once to build the candidate, once to find the validation data of the candidate.
+		for _ in 0..2 {
+			assert_matches!(
+				ctx_handle.recv().await,
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+					rp,
+					RuntimeApiRequest::PersistedValidationData(
+						p,
+						OccupiedCoreAssumption::Included,
+						tx
+					),
+				)) => {
+					assert_eq!(rp, relay_parent);
+					assert_eq!(p, para_id);
+
+					let _ = tx.send(Ok(Some(validation_data.clone())));
+				}
+			);
+		}
+		assert_matches!(
+			ctx_handle.recv().await,
+			AllMessages::RuntimeApi(RuntimeApiMessage::Request(
+				_,
+				RuntimeApiRequest::ValidationCode(_, OccupiedCoreAssumption::Included, tx)
+			)) => {
+				let _ = tx.send(Ok(Some(current_validation_code.clone())));
+			}
+		);
+		assert_matches!(
+			ctx_handle.recv().await,
+			AllMessages::RuntimeApi(
+				RuntimeApiMessage::Request(_, RuntimeApiRequest::SessionIndexForChild(tx))
+			) => {
+				tx.send(Ok(1u32.into())).unwrap();
+			}
+		);
+		assert_matches!(
+			ctx_handle.recv().await,
+			AllMessages::RuntimeApi(
+				RuntimeApiMessage::Request(_, RuntimeApiRequest::SessionExecutorParams(_, tx))
+			) => {
+				tx.send(Ok(Some(ExecutorParams::default()))).unwrap();
+			}
+		);
+
+		// Neither the collator signature, nor the para head, nor the commitments are checked.
+		assert_matches!(check_result.await, Ok(ValidationResult::Valid(outputs, used_validation_data)) => {
+			assert_eq!(outputs.head_data, HeadData(vec![1, 1, 1]));
+			assert_eq!(used_validation_data, validation_data);
+		});
+	};
+
+	let test_fut = future::join(test_fut, check_fut);
+	executor::block_on(test_fut);
+}
+
 #[test]
 fn precheck_works() {
 	let relay_parent = [3; 32].into();
@@ -1245,7 +1389,12 @@ fn precheck_invalid_pvf_blob_compression() {
 				tx.send(Ok(Some(ExecutorParams::default()))).unwrap();
 			}
 		);
-		assert_matches!(check_result.await, PreCheckOutcome::Invalid);
+		assert_matches!(
+			check_result.await,
+			PreCheckOutcome::Invalid(rejection) => {
+				assert_eq!(rejection.class, PvfRejectionClass::Decompression);
+			}
+		);
 	};
 
 	let test_fut = future::join(test_fut, check_fut);
@@ -1254,7 +1403,7 @@ fn precheck_invalid_pvf_blob_compression() {
 
 #[test]
 fn precheck_properly_classifies_outcomes() {
-	let inner = |prepare_result, precheck_outcome| {
+	let inner = |prepare_result, expect_invalid: bool, expected_class| {
 		let relay_parent = [3; 32].into();
 		let validation_code = ValidationCode(vec![3; 16]);
 		let validation_code_hash = validation_code.hash();
@@ -1303,17 +1452,77 @@ fn precheck_properly_classifies_outcomes() {
 					tx.send(Ok(Some(ExecutorParams::default()))).unwrap();
 				}
 			);
-			assert_eq!(check_result.await, precheck_outcome);
+			let rejection = match check_result.await {
+				PreCheckOutcome::Invalid(rejection) if expect_invalid => rejection,
+				PreCheckOutcome::Failed(rejection) if !expect_invalid => rejection,
+				outcome => panic!("unexpected outcome: {:?}", outcome),
+			};
+			assert_eq!(rejection.class, expected_class);
+			assert!(rejection.compile_time_ms.is_some());
+			rejection
 		};
 
-		let test_fut = future::join(test_fut, check_fut);
-		executor::block_on(test_fut);
+		let (rejection, _) = executor::block_on(future::join(test_fut, check_fut));
+		rejection
 	};
 
-	inner(Err(PrepareError::Prevalidation("foo".to_owned())), PreCheckOutcome::Invalid);
-	inner(Err(PrepareError::Preparation("bar".to_owned())), PreCheckOutcome::Invalid);
-	inner(Err(PrepareError::Panic("baz".to_owned())), PreCheckOutcome::Invalid);
+	inner(
+		Err(PrepareError::Prevalidation("foo".to_owned()).into()),
+		true,
+		PvfRejectionClass::Prevalidation,
+	);
+	inner(
+		Err(PrepareError::Preparation("bar".to_owned()).into()),
+		true,
+		PvfRejectionClass::Preparation,
+	);
+	inner(Err(PrepareError::Panic("baz".to_owned()).into()), true, PvfRejectionClass::Panic);
+
+	inner(Err(PrepareError::TimedOut.into()), false, PvfRejectionClass::TimedOut);
+	inner(
+		Err(PrepareError::IoErr("fizz".to_owned()).into()),
+		false,
+		PvfRejectionClass::WorkerFailure,
+	);
+
+	// The memory used by the worker is reported if known.
+	#[cfg(target_os = "linux")]
+	{
+		use polkadot_node_core_pvf::MemoryStats;
+
+		let failure = PrepareFailure {
+			error: PrepareError::Preparation("bar".to_owned()),
+			memory_stats: Some(MemoryStats { max_rss: Some(1024), ..Default::default() }),
+		};
+		let rejection = inner(Err(failure), true, PvfRejectionClass::Preparation);
+		assert_eq!(rejection.memory_used_kb, Some(1024));
+	}
+}
 
-	inner(Err(PrepareError::TimedOut), PreCheckOutcome::Failed);
-	inner(Err(PrepareError::IoErr("fizz".to_owned())), PreCheckOutcome::Failed);
+#[test]
+fn wasm_feature_violations_are_detected() {
+	const HEADER: &[u8] = &[0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00];
+	let module = |sections: &[u8]| [HEADER, sections].concat();
+
+	// A function type returning two values.
+	let multi_value = module(&[0x01, 0x06, 0x01, 0x60, 0x00, 0x02, 0x7f, 0x7f]);
+	// A function type returning a vector.
+	let simd = module(&[0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7b]);
+	// A function executing `memory.fill`.
+	let bulk_memory = module(&[
+		0x01, 0x04, 0x01, 0x60, 0x00, 0x00, 0x03, 0x02, 0x01, 0x00, 0x05, 0x03, 0x01, 0x00, 0x01,
+		0x0a, 0x0d, 0x01, 0x0b, 0x00, 0x41, 0x00, 0x41, 0x00, 0x41, 0x00, 0xfc, 0x0b, 0x00, 0x0b,
+	]);
+	// A truncated type section.
+	let malformed = module(&[0x01, 0x05]);
+
+	let default_params = ExecutorParams::default();
+	let bulk_memory_params = ExecutorParams::from(&[ExecutorParam::WasmExtBulkMemory][..]);
+
+	assert_eq!(wasm_feature_violations(&multi_value, &default_params), vec!["multi-value"]);
+	assert_eq!(wasm_feature_violations(&simd, &default_params), vec!["simd"]);
+	assert_eq!(wasm_feature_violations(&bulk_memory, &default_params), vec!["bulk-memory"]);
+	assert!(wasm_feature_violations(&bulk_memory, &bulk_memory_params).is_empty());
+	assert!(wasm_feature_violations(&malformed, &default_params).is_empty());
+	assert!(wasm_feature_violations(&module(&[]), &default_params).is_empty());
 }
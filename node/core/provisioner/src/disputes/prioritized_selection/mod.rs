@@ -172,7 +172,7 @@ where
 	}
 
 	gum::trace!(target: LOG_TARGET, ?leaf, "Vote selection for recent disputes");
-	let result = vote_selection(sender, partitioned, &onchain).await;
+	let result = vote_selection(sender, partitioned, &onchain, metrics).await;
 
 	gum::trace!(target: LOG_TARGET, ?leaf, "Convert to multi dispute statement set");
 	make_multi_dispute_statement_set(metrics, result)
@@ -185,6 +185,7 @@ async fn vote_selection<Sender>(
 	sender: &mut Sender,
 	partitioned: PartitionedDisputes,
 	onchain: &HashMap<(SessionIndex, CandidateHash), DisputeState>,
+	metrics: &metrics::Metrics,
 ) -> BTreeMap<(SessionIndex, CandidateHash), CandidateVotes>
 where
 	Sender: overseer::ProvisionerSenderTrait,
@@ -234,7 +235,8 @@ where
 		gum::trace!(target: LOG_TARGET, "got {} onchain votes after processing", votes.len());
 
 		// Check if votes are within the limit
-		for (session_index, candidate_hash, selected_votes) in votes {
+		let mut votes = votes.into_iter();
+		while let Some((session_index, candidate_hash, selected_votes)) = votes.next() {
 			let votes_len = selected_votes.valid.raw().len() + selected_votes.invalid.len();
 			if votes_len + total_votes_len > MAX_DISPUTE_VOTES_FORWARDED_TO_RUNTIME {
 				// we are done - no more votes can be added. Importantly, we don't add any votes for a dispute here
@@ -246,6 +248,7 @@ where
 					?total_votes_len,
 					"vote_selection DisputeCoordinatorMessage::QueryCandidateVotes counter",
 				);
+				metrics.on_dropped("dispute", "votes_limit", 1 + votes.len() + disputes.len());
 
 				return result
 			}
@@ -117,6 +117,7 @@ where
 	// upper bound of disputes to pass to wasm `fn create_inherent_data`.
 	// If the active ones are already exceeding the bounds, randomly select a subset.
 	let recent = request_confirmed_disputes(sender, RequestType::Recent).await;
+	let n_recent = recent.len();
 	let disputes = if recent.len() > MAX_DISPUTES_FORWARDED_TO_RUNTIME {
 		gum::warn!(
 			target: LOG_TARGET,
@@ -147,6 +148,8 @@ where
 		recent
 	};
 
+	metrics.on_dropped("dispute", "disputes_limit", n_recent.saturating_sub(disputes.len()));
+
 	// Load all votes for all disputes from the coordinator.
 	let dispute_candidate_votes = super::request_votes(sender, disputes).await;
 
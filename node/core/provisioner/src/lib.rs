@@ -41,14 +41,22 @@ use polkadot_primitives::{
 	BackedCandidate, BlockNumber, CandidateReceipt, CoreState, Hash, OccupiedCoreAssumption,
 	SignedAvailabilityBitfield, ValidatorIndex,
 };
-use std::collections::{BTreeMap, HashMap};
+use std::{
+	collections::{BTreeMap, HashMap},
+	sync::Arc,
+};
 
 mod disputes;
 mod error;
 mod metrics;
+mod policy;
 
-pub use self::metrics::*;
-use error::{Error, FatalResult};
+pub use self::{
+	error::Error,
+	metrics::*,
+	policy::{DefaultProvisioningPolicy, ProvisioningPolicy},
+};
+use error::FatalResult;
 
 #[cfg(test)]
 mod tests;
@@ -64,14 +72,29 @@ const PRIORITIZED_SELECTION_RUNTIME_VERSION_REQUIREMENT: u32 =
 	RuntimeApiRequest::DISPUTES_RUNTIME_REQUIREMENT;
 
 /// The provisioner subsystem.
-pub struct ProvisionerSubsystem {
+///
+/// What goes into the inherent is decided by the [`ProvisioningPolicy`].
+pub struct ProvisionerSubsystem<P = DefaultProvisioningPolicy> {
 	metrics: Metrics,
+	policy: Arc<P>,
 }
 
 impl ProvisionerSubsystem {
-	/// Create a new instance of the `ProvisionerSubsystem`.
+	/// Create a new instance of the `ProvisionerSubsystem` with the default policy.
 	pub fn new(metrics: Metrics) -> Self {
-		Self { metrics }
+		Self::with_policy(DefaultProvisioningPolicy, metrics)
+	}
+}
+
+impl<P: ProvisioningPolicy> ProvisionerSubsystem<P> {
+	/// Create a new instance of the `ProvisionerSubsystem` with the given policy.
+	pub fn with_policy(policy: P, metrics: Metrics) -> Self {
+		Self { metrics, policy: Arc::new(policy) }
+	}
+
+	/// Replace the policy of this instance, keeping its metrics.
+	pub fn replace_policy<Q: ProvisioningPolicy>(self, policy: Q) -> ProvisionerSubsystem<Q> {
+		ProvisionerSubsystem::with_policy(policy, self.metrics)
 	}
 }
 
@@ -103,10 +126,10 @@ impl PerRelayParent {
 type InherentDelays = FuturesUnordered<BoxFuture<'static, Hash>>;
 
 #[overseer::subsystem(Provisioner, error=SubsystemError, prefix=self::overseer)]
-impl<Context> ProvisionerSubsystem {
+impl<Context, P: ProvisioningPolicy> ProvisionerSubsystem<P> {
 	fn start(self, ctx: Context) -> SpawnedSubsystem {
 		let future = async move {
-			run(ctx, self.metrics)
+			run(ctx, self.metrics, self.policy)
 				.await
 				.map_err(|e| SubsystemError::with_origin("provisioner", e))
 		}
@@ -117,13 +140,18 @@ impl<Context> ProvisionerSubsystem {
 }
 
 #[overseer::contextbounds(Provisioner, prefix = self::overseer)]
-async fn run<Context>(mut ctx: Context, metrics: Metrics) -> FatalResult<()> {
+async fn run<Context, P: ProvisioningPolicy>(
+	mut ctx: Context,
+	metrics: Metrics,
+	policy: Arc<P>,
+) -> FatalResult<()> {
 	let mut inherent_delays = InherentDelays::new();
 	let mut per_relay_parent = HashMap::new();
 
 	loop {
 		let result =
-			run_iteration(&mut ctx, &mut per_relay_parent, &mut inherent_delays, &metrics).await;
+			run_iteration(&mut ctx, &mut per_relay_parent, &mut inherent_delays, &metrics, &policy)
+				.await;
 
 		match result {
 			Ok(()) => break,
@@ -135,11 +163,12 @@ async fn run<Context>(mut ctx: Context, metrics: Metrics) -> FatalResult<()> {
 }
 
 #[overseer::contextbounds(Provisioner, prefix = self::overseer)]
-async fn run_iteration<Context>(
+async fn run_iteration<Context, P: ProvisioningPolicy>(
 	ctx: &mut Context,
 	per_relay_parent: &mut HashMap<Hash, PerRelayParent>,
 	inherent_delays: &mut InherentDelays,
 	metrics: &Metrics,
+	policy: &Arc<P>,
 ) -> Result<(), Error> {
 	loop {
 		futures::select! {
@@ -151,7 +180,7 @@ async fn run_iteration<Context>(
 					FromOrchestra::Signal(OverseerSignal::BlockFinalized(..)) => {},
 					FromOrchestra::Signal(OverseerSignal::Conclude) => return Ok(()),
 					FromOrchestra::Communication { msg } => {
-						handle_communication(ctx, per_relay_parent, msg, metrics).await?;
+						handle_communication(ctx, per_relay_parent, msg, metrics, policy).await?;
 					},
 				}
 			},
@@ -167,7 +196,14 @@ async fn run_iteration<Context>(
 
 					let return_senders = std::mem::take(&mut state.awaiting_inherent);
 					if !return_senders.is_empty() {
-						send_inherent_data_bg(ctx, &state, return_senders, metrics.clone()).await?;
+						send_inherent_data_bg(
+							ctx,
+							&state,
+							return_senders,
+							metrics.clone(),
+							policy.clone(),
+						)
+						.await?;
 					}
 				}
 			}
@@ -194,11 +230,12 @@ fn handle_active_leaves_update(
 }
 
 #[overseer::contextbounds(Provisioner, prefix = self::overseer)]
-async fn handle_communication<Context>(
+async fn handle_communication<Context, P: ProvisioningPolicy>(
 	ctx: &mut Context,
 	per_relay_parent: &mut HashMap<Hash, PerRelayParent>,
 	message: ProvisionerMessage,
 	metrics: &Metrics,
+	policy: &Arc<P>,
 ) -> Result<(), Error> {
 	match message {
 		ProvisionerMessage::RequestInherentData(relay_parent, return_sender) => {
@@ -207,8 +244,14 @@ async fn handle_communication<Context>(
 			if let Some(state) = per_relay_parent.get_mut(&relay_parent) {
 				if state.is_inherent_ready {
 					gum::trace!(target: LOG_TARGET, ?relay_parent, "Calling send_inherent_data.");
-					send_inherent_data_bg(ctx, &state, vec![return_sender], metrics.clone())
-						.await?;
+					send_inherent_data_bg(
+						ctx,
+						&state,
+						vec![return_sender],
+						metrics.clone(),
+						policy.clone(),
+					)
+					.await?;
 				} else {
 					gum::trace!(
 						target: LOG_TARGET,
@@ -235,11 +278,12 @@ async fn handle_communication<Context>(
 }
 
 #[overseer::contextbounds(Provisioner, prefix = self::overseer)]
-async fn send_inherent_data_bg<Context>(
+async fn send_inherent_data_bg<Context, P: ProvisioningPolicy>(
 	ctx: &mut Context,
 	per_relay_parent: &PerRelayParent,
 	return_senders: Vec<oneshot::Sender<ProvisionerInherentData>>,
 	metrics: Metrics,
+	policy: Arc<P>,
 ) -> Result<(), Error> {
 	let leaf = per_relay_parent.leaf.clone();
 	let signed_bitfields = per_relay_parent.signed_bitfields.clone();
@@ -265,6 +309,7 @@ async fn send_inherent_data_bg<Context>(
 			return_senders,
 			&mut sender,
 			&metrics,
+			&*policy,
 		) // Make sure call is not taking forever:
 		.timeout(SEND_INHERENT_DATA_TIMEOUT)
 		.map(|v| match v {
@@ -369,13 +414,16 @@ type CoreAvailability = BitVec<u8, bitvec::order::Lsb0>;
 /// When we're choosing bitfields to include, the rule should be simple:
 /// maximize availability. So basically, include all bitfields. And then
 /// choose a coherent set of candidates along with that.
-async fn send_inherent_data(
+///
+/// The actual selection is up to the [`ProvisioningPolicy`].
+async fn send_inherent_data<P: ProvisioningPolicy>(
 	leaf: &ActivatedLeaf,
 	bitfields: &[SignedAvailabilityBitfield],
 	candidates: &[CandidateReceipt],
 	return_senders: Vec<oneshot::Sender<ProvisionerInherentData>>,
 	from_job: &mut impl overseer::ProvisionerSenderTrait,
 	metrics: &Metrics,
+	policy: &P,
 ) -> Result<(), Error> {
 	gum::trace!(
 		target: LOG_TARGET,
@@ -393,16 +441,7 @@ async fn send_inherent_data(
 		"Selecting disputes"
 	);
 
-	let disputes = match has_required_runtime(
-		from_job,
-		leaf.hash,
-		PRIORITIZED_SELECTION_RUNTIME_VERSION_REQUIREMENT,
-	)
-	.await
-	{
-		true => disputes::prioritized_selection::select_disputes(from_job, metrics, leaf).await,
-		false => disputes::random_selection::select_disputes(from_job, metrics).await,
-	};
+	let disputes = policy.select_disputes(from_job, leaf, metrics).await;
 
 	gum::trace!(
 		target: LOG_TARGET,
@@ -413,9 +452,16 @@ async fn send_inherent_data(
 	// Only include bitfields on fresh leaves. On chain reversions, we want to make sure that
 	// there will be at least one block, which cannot get disputed, so the chain can make progress.
 	let bitfields = match leaf.status {
-		LeafStatus::Fresh =>
-			select_availability_bitfields(&availability_cores, bitfields, &leaf.hash),
-		LeafStatus::Stale => Vec::new(),
+		LeafStatus::Fresh => policy.select_availability_bitfields(
+			&availability_cores,
+			bitfields,
+			&leaf.hash,
+			metrics,
+		),
+		LeafStatus::Stale => {
+			metrics.on_dropped("bitfield", "stale_leaf", bitfields.len());
+			Vec::new()
+		},
 	};
 
 	gum::trace!(
@@ -423,8 +469,16 @@ async fn send_inherent_data(
 		relay_parent = ?leaf.hash,
 		"Selected bitfields"
 	);
-	let candidates =
-		select_candidates(&availability_cores, &bitfields, candidates, leaf.hash, from_job).await?;
+	let candidates = policy
+		.select_candidates(
+			&availability_cores,
+			&bitfields,
+			candidates,
+			leaf.hash,
+			from_job,
+			metrics,
+		)
+		.await?;
 
 	gum::trace!(
 		target: LOG_TARGET,
@@ -474,6 +528,7 @@ fn select_availability_bitfields(
 	cores: &[CoreState],
 	bitfields: &[SignedAvailabilityBitfield],
 	leaf_hash: &Hash,
+	metrics: &Metrics,
 ) -> Vec<SignedAvailabilityBitfield> {
 	let mut selected: BTreeMap<ValidatorIndex, SignedAvailabilityBitfield> = BTreeMap::new();
 
@@ -487,6 +542,7 @@ fn select_availability_bitfields(
 	'a: for bitfield in bitfields.iter().cloned() {
 		if bitfield.payload().0.len() != cores.len() {
 			gum::debug!(target: LOG_TARGET, ?leaf_hash, "dropping bitfield due to length mismatch");
+			metrics.on_dropped("bitfield", "length_mismatch", 1);
 			continue
 		}
 
@@ -501,6 +557,7 @@ fn select_availability_bitfields(
 				?leaf_hash,
 				"dropping bitfield due to duplication - the better one is kept"
 			);
+			metrics.on_dropped("bitfield", "duplicate", 1);
 			continue
 		}
 
@@ -513,11 +570,14 @@ fn select_availability_bitfields(
 					?leaf_hash,
 					"dropping invalid bitfield - bit is set for an unoccupied core"
 				);
+				metrics.on_dropped("bitfield", "unoccupied_core", 1);
 				continue 'a
 			}
 		}
 
-		let _ = selected.insert(bitfield.validator_index(), bitfield);
+		if selected.insert(bitfield.validator_index(), bitfield).is_some() {
+			metrics.on_dropped("bitfield", "duplicate", 1);
+		}
 	}
 
 	gum::debug!(
@@ -538,6 +598,7 @@ async fn select_candidates(
 	candidates: &[CandidateReceipt],
 	relay_parent: Hash,
 	sender: &mut impl overseer::ProvisionerSenderTrait,
+	metrics: &Metrics,
 ) -> Result<Vec<BackedCandidate>, Error> {
 	let block_number = get_block_number_under_construction(relay_parent, sender).await?;
 
@@ -612,6 +673,12 @@ async fn select_candidates(
 		}
 	}
 
+	metrics.on_dropped(
+		"candidate",
+		"no_matching_core",
+		candidates.len().saturating_sub(selected_candidates.len()),
+	);
+
 	gum::trace!(target: LOG_TARGET,
 		leaf_hash=?relay_parent,
 		"before GetBackedCandidates");
@@ -633,6 +700,7 @@ async fn select_candidates(
 	// We can't easily map from `BackedCandidate` to `core_idx`, but we know that every selected candidate
 	// maps to either 0 or 1 backed candidate, and the hashes correspond. Therefore, by checking them
 	// in order, we can ensure that the backed candidates are also in order.
+	let selected_candidates_len = selected_candidates.len();
 	let mut backed_idx = 0;
 	for selected in selected_candidates {
 		if selected ==
@@ -644,12 +712,14 @@ async fn select_candidates(
 	if candidates.len() != backed_idx {
 		Err(Error::BackedCandidateOrderingProblem)?;
 	}
+	metrics.on_dropped("candidate", "not_backed", selected_candidates_len - candidates.len());
 
 	// keep only one candidate with validation code.
 	let mut with_validation_code = false;
 	candidates.retain(|c| {
 		if c.candidate.commitments.new_validation_code.is_some() {
 			if with_validation_code {
+				metrics.on_dropped("candidate", "code_upgrade", 1);
 				return false
 			}
 
@@ -39,6 +39,9 @@ struct MetricsInner {
 
 	/// The disputes fetched from the runtime.
 	fetched_onchain_disputes: prometheus::Counter<prometheus::U64>,
+
+	/// Bitfields, candidates and disputes left out of the inherent data, by reason.
+	dropped: prometheus::CounterVec<prometheus::U64>,
 }
 
 /// Provisioner metrics.
@@ -152,6 +155,20 @@ impl Metrics {
 			metrics.fetched_onchain_disputes.inc_by(onchain_count);
 		}
 	}
+
+	/// Called when a [`ProvisioningPolicy`](crate::ProvisioningPolicy) leaves `count` items of the
+	/// given kind (e.g. `bitfield`, `candidate` or `dispute`) out of the inherent data.
+	pub fn on_dropped(&self, item: &str, reason: &str, count: usize) {
+		if count == 0 {
+			return
+		}
+		if let Some(metrics) = &self.0 {
+			metrics
+				.dropped
+				.with_label_values(&[item, reason])
+				.inc_by(count.try_into().unwrap_or(0));
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -222,6 +239,16 @@ impl metrics::Metrics for Metrics {
 				)?,
 				&registry,
 			)?,
+			dropped: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_provisioner_dropped_total",
+						"Number of bitfields, candidates and disputes left out of the inherent data.",
+					),
+					&["item", "reason"],
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Metrics(Some(metrics)))
 	}
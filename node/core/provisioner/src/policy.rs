@@ -0,0 +1,140 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Policies deciding what the provisioner puts into the parachains inherent.
+
+use async_trait::async_trait;
+use polkadot_node_subsystem::{overseer, ActivatedLeaf};
+use polkadot_primitives::{
+	BackedCandidate, CandidateReceipt, CoreState, Hash, MultiDisputeStatementSet,
+	SignedAvailabilityBitfield,
+};
+
+use crate::{
+	disputes, error::Error, has_required_runtime, metrics::Metrics, select_availability_bitfields,
+	select_candidates, PRIORITIZED_SELECTION_RUNTIME_VERSION_REQUIREMENT,
+};
+
+/// Decides which of the data known to the provisioner goes into the parachains inherent of a
+/// relay chain block.
+///
+/// The provisioner collects signed bitfields and backed candidates for each active leaf and
+/// consults the policy once inherent data is requested. Bitfields are selected first, candidates
+/// are then selected on top of the selected bitfields. Whatever a policy leaves out should be
+/// reported with [`Metrics::on_dropped`].
+///
+/// The runtime rejects the whole inherent if any of the selected data is invalid, so a policy
+/// must stick to the constraints documented on each method.
+#[async_trait]
+pub trait ProvisioningPolicy: Send + Sync + 'static {
+	/// Selects the availability bitfields to include.
+	///
+	/// At most one bitfield per validator may be selected, and a bit may only be set for an
+	/// occupied core.
+	fn select_availability_bitfields(
+		&self,
+		cores: &[CoreState],
+		bitfields: &[SignedAvailabilityBitfield],
+		leaf_hash: &Hash,
+		metrics: &Metrics,
+	) -> Vec<SignedAvailabilityBitfield>;
+
+	/// Selects the backed candidates to include, given the selected bitfields.
+	///
+	/// At most one candidate per core may be selected, and only for cores which are free once the
+	/// selected bitfields are applied. Candidates must be ordered by core index.
+	async fn select_candidates<Sender>(
+		&self,
+		availability_cores: &[CoreState],
+		bitfields: &[SignedAvailabilityBitfield],
+		candidates: &[CandidateReceipt],
+		relay_parent: Hash,
+		sender: &mut Sender,
+		metrics: &Metrics,
+	) -> Result<Vec<BackedCandidate>, Error>
+	where
+		Sender: overseer::ProvisionerSenderTrait;
+
+	/// Selects the dispute votes to include.
+	///
+	/// Votes must be sorted by session index and candidate hash.
+	async fn select_disputes<Sender>(
+		&self,
+		sender: &mut Sender,
+		leaf: &ActivatedLeaf,
+		metrics: &Metrics,
+	) -> MultiDisputeStatementSet
+	where
+		Sender: overseer::ProvisionerSenderTrait;
+}
+
+/// The policy used by default.
+///
+/// Maximizes availability by including the bitfield with the most bits set of each validator and
+/// picks the first matching candidate for each free core, allowing for one code upgrade per
+/// block. Dispute votes are selected by priority if the runtime supports it, randomly otherwise.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DefaultProvisioningPolicy;
+
+#[async_trait]
+impl ProvisioningPolicy for DefaultProvisioningPolicy {
+	fn select_availability_bitfields(
+		&self,
+		cores: &[CoreState],
+		bitfields: &[SignedAvailabilityBitfield],
+		leaf_hash: &Hash,
+		metrics: &Metrics,
+	) -> Vec<SignedAvailabilityBitfield> {
+		select_availability_bitfields(cores, bitfields, leaf_hash, metrics)
+	}
+
+	async fn select_candidates<Sender>(
+		&self,
+		availability_cores: &[CoreState],
+		bitfields: &[SignedAvailabilityBitfield],
+		candidates: &[CandidateReceipt],
+		relay_parent: Hash,
+		sender: &mut Sender,
+		metrics: &Metrics,
+	) -> Result<Vec<BackedCandidate>, Error>
+	where
+		Sender: overseer::ProvisionerSenderTrait,
+	{
+		select_candidates(availability_cores, bitfields, candidates, relay_parent, sender, metrics)
+			.await
+	}
+
+	async fn select_disputes<Sender>(
+		&self,
+		sender: &mut Sender,
+		leaf: &ActivatedLeaf,
+		metrics: &Metrics,
+	) -> MultiDisputeStatementSet
+	where
+		Sender: overseer::ProvisionerSenderTrait,
+	{
+		match has_required_runtime(
+			sender,
+			leaf.hash,
+			PRIORITIZED_SELECTION_RUNTIME_VERSION_REQUIREMENT,
+		)
+		.await
+		{
+			true => disputes::prioritized_selection::select_disputes(sender, metrics, leaf).await,
+			false => disputes::random_selection::select_disputes(sender, metrics).await,
+		}
+	}
+}
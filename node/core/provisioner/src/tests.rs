@@ -17,6 +17,7 @@
 use super::*;
 use ::test_helpers::{dummy_candidate_descriptor, dummy_hash};
 use bitvec::bitvec;
+use polkadot_node_subsystem_util::metrics::prometheus;
 use polkadot_primitives::{OccupiedCore, ScheduledCore};
 
 pub fn occupied_core(para_id: u32) -> CoreState {
@@ -54,8 +55,26 @@ pub fn scheduled_core(id: u32) -> ScheduledCore {
 	ScheduledCore { para_id: id.into(), collator: None }
 }
 
+pub fn dropped(registry: &prometheus::Registry, item: &str, reason: &str) -> u64 {
+	let has_label = |metric: &prometheus::proto::Metric, name: &str, value: &str| {
+		metric
+			.get_label()
+			.iter()
+			.any(|l| l.get_name() == name && l.get_value() == value)
+	};
+
+	registry
+		.gather()
+		.iter()
+		.filter(|family| family.get_name() == "polkadot_parachain_provisioner_dropped_total")
+		.flat_map(|family| family.get_metric())
+		.find(|metric| has_label(metric, "item", item) && has_label(metric, "reason", reason))
+		.map_or(0, |metric| metric.get_counter().get_value() as u64)
+}
+
 mod select_availability_bitfields {
-	use super::{super::*, default_bitvec, occupied_core};
+	use super::{super::*, default_bitvec, dropped, occupied_core};
+	use polkadot_node_subsystem_util::metrics::{prometheus, Metrics as _};
 	use polkadot_primitives::{ScheduledCore, SigningContext, ValidatorId, ValidatorIndex};
 	use sp_application_crypto::AppCrypto;
 	use sp_keystore::{testing::MemoryKeystore, Keystore, KeystorePtr};
@@ -97,8 +116,12 @@ mod select_availability_bitfields {
 			signed_bitfield(&keystore, bitvec, ValidatorIndex(1)),
 		];
 
-		let mut selected_bitfields =
-			select_availability_bitfields(&cores, &bitfields, &Hash::repeat_byte(0));
+		let mut selected_bitfields = select_availability_bitfields(
+			&cores,
+			&bitfields,
+			&Hash::repeat_byte(0),
+			&Default::default(),
+		);
 		selected_bitfields.sort_by_key(|bitfield| bitfield.validator_index());
 
 		assert_eq!(selected_bitfields.len(), 2);
@@ -136,8 +159,12 @@ mod select_availability_bitfields {
 			signed_bitfield(&keystore, bitvec2.clone(), ValidatorIndex(2)),
 		];
 
-		let selected_bitfields =
-			select_availability_bitfields(&cores, &bitfields, &Hash::repeat_byte(0));
+		let selected_bitfields = select_availability_bitfields(
+			&cores,
+			&bitfields,
+			&Hash::repeat_byte(0),
+			&Default::default(),
+		);
 
 		// selects only the valid bitfield
 		assert_eq!(selected_bitfields.len(), 1);
@@ -160,8 +187,12 @@ mod select_availability_bitfields {
 			signed_bitfield(&keystore, bitvec1.clone(), ValidatorIndex(1)),
 		];
 
-		let selected_bitfields =
-			select_availability_bitfields(&cores, &bitfields, &Hash::repeat_byte(0));
+		let selected_bitfields = select_availability_bitfields(
+			&cores,
+			&bitfields,
+			&Hash::repeat_byte(0),
+			&Default::default(),
+		);
 		assert_eq!(selected_bitfields.len(), 1);
 		assert_eq!(selected_bitfields[0].payload().0, bitvec1.clone());
 	}
@@ -198,14 +229,52 @@ mod select_availability_bitfields {
 			signed_bitfield(&keystore, bitvec1.clone(), ValidatorIndex(1)),
 		];
 
-		let selected_bitfields =
-			select_availability_bitfields(&cores, &bitfields, &Hash::repeat_byte(0));
+		let selected_bitfields = select_availability_bitfields(
+			&cores,
+			&bitfields,
+			&Hash::repeat_byte(0),
+			&Default::default(),
+		);
 		assert_eq!(selected_bitfields.len(), 4);
 		assert_eq!(selected_bitfields[0].payload().0, bitvec0);
 		assert_eq!(selected_bitfields[1].payload().0, bitvec1);
 		assert_eq!(selected_bitfields[2].payload().0, bitvec2);
 		assert_eq!(selected_bitfields[3].payload().0, bitvec3);
 	}
+
+	#[test]
+	fn dropped_bitfields_are_counted() {
+		let keystore: KeystorePtr = Arc::new(MemoryKeystore::new());
+		let registry = prometheus::Registry::new();
+		let metrics = Metrics::try_register(&registry).unwrap();
+
+		let cores = vec![CoreState::Free, occupied_core(1)];
+
+		let mut bitvec0 = default_bitvec(2);
+		bitvec0.set(0, true);
+
+		let mut bitvec1 = default_bitvec(2);
+		bitvec1.set(1, true);
+
+		let bitfields = vec![
+			// bit set for a free core
+			signed_bitfield(&keystore, bitvec0, ValidatorIndex(0)),
+			// wrong length
+			signed_bitfield(&keystore, default_bitvec(3), ValidatorIndex(1)),
+			// valid, then the same again
+			signed_bitfield(&keystore, bitvec1.clone(), ValidatorIndex(2)),
+			signed_bitfield(&keystore, bitvec1, ValidatorIndex(2)),
+		];
+
+		let selected_bitfields =
+			select_availability_bitfields(&cores, &bitfields, &Hash::repeat_byte(0), &metrics);
+		assert_eq!(selected_bitfields.len(), 1);
+
+		assert_eq!(dropped(&registry, "bitfield", "unoccupied_core"), 1);
+		assert_eq!(dropped(&registry, "bitfield", "length_mismatch"), 1);
+		assert_eq!(dropped(&registry, "bitfield", "duplicate"), 1);
+		assert_eq!(dropped(&registry, "candidate", "not_backed"), 0);
+	}
 }
 
 pub(crate) mod common {
@@ -363,7 +432,9 @@ mod select_candidates {
 		test_harness(
 			|r| mock_overseer(r, Vec::new()),
 			|mut tx: TestSubsystemSender| async move {
-				select_candidates(&[], &[], &[], Default::default(), &mut tx).await.unwrap();
+				select_candidates(&[], &[], &[], Default::default(), &mut tx, &Default::default())
+					.await
+					.unwrap();
 			},
 		)
 	}
@@ -430,10 +501,16 @@ mod select_candidates {
 		test_harness(
 			|r| mock_overseer(r, expected_backed),
 			|mut tx: TestSubsystemSender| async move {
-				let result =
-					select_candidates(&mock_cores, &[], &candidates, Default::default(), &mut tx)
-						.await
-						.unwrap();
+				let result = select_candidates(
+					&mock_cores,
+					&[],
+					&candidates,
+					Default::default(),
+					&mut tx,
+					&Default::default(),
+				)
+				.await
+				.unwrap();
 
 				result.into_iter().for_each(|c| {
 					assert!(
@@ -494,10 +571,16 @@ mod select_candidates {
 		test_harness(
 			|r| mock_overseer(r, expected_backed),
 			|mut tx: TestSubsystemSender| async move {
-				let result =
-					select_candidates(&mock_cores, &[], &candidates, Default::default(), &mut tx)
-						.await
-						.unwrap();
+				let result = select_candidates(
+					&mock_cores,
+					&[],
+					&candidates,
+					Default::default(),
+					&mut tx,
+					&Default::default(),
+				)
+				.await
+				.unwrap();
 
 				result.into_iter().for_each(|c| {
 					assert!(
@@ -510,3 +593,125 @@ mod select_candidates {
 		)
 	}
 }
+
+mod provisioning_policy {
+	use super::{super::*, dropped};
+	use ::test_helpers::{dummy_candidate_descriptor, dummy_hash};
+	use polkadot_node_subsystem::messages::{AllMessages, RuntimeApiRequest::AvailabilityCores};
+	use polkadot_node_subsystem_test_helpers::make_subsystem_context;
+	use polkadot_node_subsystem_util::metrics::{prometheus, Metrics as _};
+	use polkadot_primitives::MultiDisputeStatementSet;
+	use sp_core::testing::TaskExecutor;
+
+	/// Leaves everything out of the inherent, without consulting any other subsystem.
+	struct EmptyInherentPolicy;
+
+	#[async_trait::async_trait]
+	impl ProvisioningPolicy for EmptyInherentPolicy {
+		fn select_availability_bitfields(
+			&self,
+			_cores: &[CoreState],
+			bitfields: &[SignedAvailabilityBitfield],
+			_leaf_hash: &Hash,
+			metrics: &Metrics,
+		) -> Vec<SignedAvailabilityBitfield> {
+			metrics.on_dropped("bitfield", "empty_inherent", bitfields.len());
+			Vec::new()
+		}
+
+		async fn select_candidates<Sender>(
+			&self,
+			_availability_cores: &[CoreState],
+			_bitfields: &[SignedAvailabilityBitfield],
+			candidates: &[CandidateReceipt],
+			_relay_parent: Hash,
+			_sender: &mut Sender,
+			metrics: &Metrics,
+		) -> Result<Vec<BackedCandidate>, Error>
+		where
+			Sender: overseer::ProvisionerSenderTrait,
+		{
+			metrics.on_dropped("candidate", "empty_inherent", candidates.len());
+			Ok(Vec::new())
+		}
+
+		async fn select_disputes<Sender>(
+			&self,
+			_sender: &mut Sender,
+			_leaf: &ActivatedLeaf,
+			_metrics: &Metrics,
+		) -> MultiDisputeStatementSet
+		where
+			Sender: overseer::ProvisionerSenderTrait,
+		{
+			MultiDisputeStatementSet::new()
+		}
+	}
+
+	#[test]
+	fn custom_policy_decides_the_inherent() {
+		let registry = prometheus::Registry::new();
+		let subsystem = ProvisionerSubsystem::with_policy(
+			EmptyInherentPolicy,
+			Metrics::try_register(&registry).unwrap(),
+		);
+
+		let (ctx, mut ctx_handle) =
+			make_subsystem_context::<ProvisionerMessage, _>(TaskExecutor::new());
+
+		let leaf = ActivatedLeaf {
+			hash: Hash::repeat_byte(1),
+			number: 1,
+			status: LeafStatus::Fresh,
+			span: Arc::new(jaeger::Span::Disabled),
+		};
+		let candidate = CandidateReceipt {
+			descriptor: dummy_candidate_descriptor(dummy_hash()),
+			commitments_hash: Default::default(),
+		};
+
+		let test_fut = async move {
+			ctx_handle
+				.send(FromOrchestra::Signal(OverseerSignal::ActiveLeaves(
+					ActiveLeavesUpdate::start_work(leaf.clone()),
+				)))
+				.await;
+			ctx_handle
+				.send(FromOrchestra::Communication {
+					msg: ProvisionerMessage::ProvisionableData(
+						leaf.hash,
+						ProvisionableData::BackedCandidate(candidate),
+					),
+				})
+				.await;
+
+			let (tx, rx) = oneshot::channel();
+			ctx_handle
+				.send(FromOrchestra::Communication {
+					msg: ProvisionerMessage::RequestInherentData(leaf.hash, tx),
+				})
+				.await;
+
+			// The policy doesn't ask other subsystems for anything, the availability cores are
+			// requested by the provisioner itself.
+			match ctx_handle.recv().await {
+				AllMessages::RuntimeApi(RuntimeApiMessage::Request(_, AvailabilityCores(tx))) =>
+					tx.send(Ok(Vec::new())).unwrap(),
+				msg => panic!("Unexpected message: {:?}", msg),
+			}
+
+			let inherent_data = rx.await.unwrap();
+			assert!(inherent_data.bitfields.is_empty());
+			assert!(inherent_data.backed_candidates.is_empty());
+			assert!(inherent_data.disputes.is_empty());
+
+			ctx_handle.send(FromOrchestra::Signal(OverseerSignal::Conclude)).await;
+		};
+
+		let run_fut = run(ctx, subsystem.metrics, subsystem.policy).map(|res| res.unwrap());
+		futures::executor::block_on(future::join(run_fut, test_fut));
+
+		assert_eq!(dropped(&registry, "candidate", "empty_inherent"), 1);
+		assert_eq!(dropped(&registry, "candidate", "not_backed"), 0);
+	}
+}
@@ -147,6 +147,11 @@ impl InterestView {
 		}
 	}
 
+	/// Returns whether the given PVF is in the view and awaits a judgement.
+	pub fn is_pending(&self, subject: &ValidationCodeHash) -> bool {
+		self.pvfs.get(subject).map_or(false, |data| data.judgement.is_none())
+	}
+
 	/// Returns all PVFs that previously received a judgement.
 	pub fn judgements(&self) -> impl Iterator<Item = (ValidationCodeHash, Judgement)> + '_ {
 		self.pvfs
@@ -21,6 +21,7 @@
 
 use futures::{channel::oneshot, future::BoxFuture, prelude::*, stream::FuturesUnordered};
 
+use polkadot_node_primitives::{PvfRejection, PvfRejectionReport};
 use polkadot_node_subsystem::{
 	messages::{CandidateValidationMessage, PreCheckOutcome, PvfCheckerMessage, RuntimeApiMessage},
 	overseer, ActiveLeavesUpdate, FromOrchestra, OverseerSignal, SpawnedSubsystem, SubsystemError,
@@ -31,10 +32,19 @@ use polkadot_primitives::{
 	ValidatorIndex,
 };
 use sp_keystore::KeystorePtr;
-use std::collections::HashSet;
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	time::Duration,
+};
 
 const LOG_TARGET: &str = "parachain::pvf-checker";
 
+/// The number of rejections kept for inspection.
+const MAX_REJECTION_REPORTS: usize = 64;
+
+/// The delay before retrying a failed pre-check, if not configured.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(30);
+
 mod interest_view;
 mod metrics;
 mod runtime_api;
@@ -47,16 +57,41 @@ use self::{
 	metrics::Metrics,
 };
 
+/// How pre-checks that failed for reasons which may be transient are retried before voting
+/// against the PVF.
+///
+/// Only [`PreCheckOutcome::Failed`] is retried, a PVF found to be invalid is voted against right
+/// away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+	/// The number of times a failed pre-check is retried.
+	pub max_retries: u32,
+	/// The delay before each retry.
+	pub delay: Duration,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self { max_retries: 0, delay: DEFAULT_RETRY_DELAY }
+	}
+}
+
 /// PVF pre-checking subsystem.
 pub struct PvfCheckerSubsystem {
 	enabled: bool,
 	keystore: KeystorePtr,
+	retry_policy: RetryPolicy,
 	metrics: Metrics,
 }
 
 impl PvfCheckerSubsystem {
-	pub fn new(enabled: bool, keystore: KeystorePtr, metrics: Metrics) -> Self {
-		PvfCheckerSubsystem { enabled, keystore, metrics }
+	pub fn new(
+		enabled: bool,
+		keystore: KeystorePtr,
+		retry_policy: RetryPolicy,
+		metrics: Metrics,
+	) -> Self {
+		PvfCheckerSubsystem { enabled, keystore, retry_policy, metrics }
 	}
 }
 
@@ -64,7 +99,7 @@ impl PvfCheckerSubsystem {
 impl<Context> PvfCheckerSubsystem {
 	fn start(self, ctx: Context) -> SpawnedSubsystem {
 		if self.enabled {
-			let future = run(ctx, self.keystore, self.metrics)
+			let future = run(ctx, self.keystore, self.retry_policy, self.metrics)
 				.map_err(|e| SubsystemError::with_origin("pvf-checker", e))
 				.boxed();
 
@@ -118,12 +153,25 @@ struct State {
 	/// Resolving to `None` means that the request was dropped before replying.
 	currently_checking:
 		FuturesUnordered<BoxFuture<'static, Option<(PreCheckOutcome, ValidationCodeHash)>>>,
+
+	/// How failed pre-checks are retried.
+	retry_policy: RetryPolicy,
+
+	/// The number of failed pre-checks of the PVFs which are being retried.
+	failed_attempts: HashMap<ValidationCodeHash, u32>,
+
+	/// The futures resolving once a failed pre-check of the given PVF is due to be retried.
+	retries_due: FuturesUnordered<BoxFuture<'static, ValidationCodeHash>>,
+
+	/// The most recent PVFs we voted against, together with the explanations, oldest first.
+	rejections: VecDeque<PvfRejectionReport>,
 }
 
 #[overseer::contextbounds(PvfChecker, prefix = self::overseer)]
 async fn run<Context>(
 	mut ctx: Context,
 	keystore: KeystorePtr,
+	retry_policy: RetryPolicy,
 	metrics: Metrics,
 ) -> SubsystemResult<()> {
 	let mut state = State {
@@ -133,6 +181,10 @@ async fn run<Context>(
 		voted: HashSet::with_capacity(16),
 		view: InterestView::new(),
 		currently_checking: FuturesUnordered::new(),
+		retry_policy,
+		failed_attempts: HashMap::new(),
+		retries_due: FuturesUnordered::new(),
+		rejections: VecDeque::with_capacity(MAX_REJECTION_REPORTS),
 	};
 
 	loop {
@@ -153,6 +205,9 @@ async fn run<Context>(
 					// care here.
 				}
 			}
+			validation_code_hash = state.retries_due.select_next_some() => {
+				handle_retry_due(&mut state, &mut sender, &metrics, validation_code_hash).await;
+			}
 			from_overseer = ctx.recv().fuse() => {
 				let outcome = handle_from_overseer(
 					&mut state,
@@ -186,10 +241,32 @@ async fn handle_pvf_check(
 		outcome,
 	);
 
-	let judgement = match outcome {
-		PreCheckOutcome::Valid => Judgement::Valid,
-		PreCheckOutcome::Invalid => Judgement::Invalid,
-		PreCheckOutcome::Failed => {
+	let failed_attempts = state.failed_attempts.remove(&validation_code_hash).unwrap_or(0);
+	let (judgement, rejection) = match outcome {
+		PreCheckOutcome::Valid => (Judgement::Valid, None),
+		PreCheckOutcome::Invalid(rejection) => (Judgement::Invalid, Some(rejection)),
+		PreCheckOutcome::Failed(rejection) => {
+			let failed_attempts = failed_attempts + 1;
+			if failed_attempts <= state.retry_policy.max_retries &&
+				state.view.is_pending(&validation_code_hash)
+			{
+				gum::info!(
+					target: LOG_TARGET,
+					?validation_code_hash,
+					reason = %rejection.reason,
+					%failed_attempts,
+					"Pre-check failed, retrying",
+				);
+				metrics.on_pre_check_retry();
+				state.failed_attempts.insert(validation_code_hash, failed_attempts);
+				let delay = state.retry_policy.delay;
+				state.retries_due.push(Box::pin(async move {
+					futures_timer::Delay::new(delay).await;
+					validation_code_hash
+				}));
+				return
+			}
+
 			// Always vote against in case of failures. Voting against a PVF when encountering a
 			// timeout (or an unlikely node-specific issue) can be considered safe, since
 			// there is no slashing for being on the wrong side on a pre-check vote.
@@ -201,7 +278,7 @@ async fn handle_pvf_check(
 				?validation_code_hash,
 				"Pre-check failed, voting against",
 			);
-			Judgement::Invalid
+			(Judgement::Invalid, Some(rejection))
 		},
 	};
 
@@ -217,6 +294,11 @@ async fn handle_pvf_check(
 		},
 	}
 
+	if let Some(mut rejection) = rejection {
+		rejection.attempts = failed_attempts + 1;
+		note_rejection(state, metrics, validation_code_hash, rejection);
+	}
+
 	match (state.credentials.as_ref(), state.recent_block, state.latest_session) {
 		// Note, the availability of credentials implies the availability of the recent block and
 		// the session index.
@@ -238,6 +320,57 @@ async fn handle_pvf_check(
 	}
 }
 
+/// Records the explanation of a rejected PVF, so that it can be inspected later.
+fn note_rejection(
+	state: &mut State,
+	metrics: &Metrics,
+	validation_code_hash: ValidationCodeHash,
+	rejection: PvfRejection,
+) {
+	gum::info!(
+		target: LOG_TARGET,
+		?validation_code_hash,
+		class = ?rejection.class,
+		reason = %rejection.reason,
+		compile_time_ms = ?rejection.compile_time_ms,
+		memory_used_kb = ?rejection.memory_used_kb,
+		wasm_feature_violations = ?rejection.wasm_feature_violations,
+		attempts = %rejection.attempts,
+		"PVF rejected",
+	);
+	metrics.on_pvf_rejected(rejection.class);
+
+	if state.rejections.len() == MAX_REJECTION_REPORTS {
+		state.rejections.pop_front();
+	}
+	state.rejections.push_back(PvfRejectionReport {
+		validation_code_hash: Hash::from_slice(validation_code_hash.as_ref()),
+		session_index: state.latest_session,
+		rejection,
+	});
+}
+
+/// Retries the pre-check of the given PVF if it still awaits a judgement.
+async fn handle_retry_due(
+	state: &mut State,
+	sender: &mut impl overseer::PvfCheckerSenderTrait,
+	metrics: &Metrics,
+	validation_code_hash: ValidationCodeHash,
+) {
+	match state.recent_block {
+		Some((_, recent_block_hash)) if state.view.is_pending(&validation_code_hash) => {
+			// The code is pinned while the pre-checking vote is ongoing, so the most recent block
+			// is as good an anchor as any.
+			initiate_precheck(state, sender, recent_block_hash, validation_code_hash, metrics)
+				.await;
+		},
+		_ => {
+			// The PVF left the view in the meantime.
+			state.failed_attempts.remove(&validation_code_hash);
+		},
+	}
+}
+
 /// A marker for the outer loop that the subsystem should stop.
 struct Conclude;
 
@@ -262,8 +395,11 @@ async fn handle_from_overseer(
 			None
 		},
 		FromOrchestra::Communication { msg } => match msg {
-				// uninhabited type, thus statically unreachable.
+			PvfCheckerMessage::Rejections(tx) => {
+				let _ = tx.send(state.rejections.iter().cloned().collect());
+				None
 			},
+		},
 	}
 }
 
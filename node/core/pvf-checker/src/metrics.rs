@@ -16,6 +16,7 @@
 
 //! Metrics definitions for the PVF pre-checking subsystem.
 
+use polkadot_node_primitives::PvfRejectionClass;
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 
 #[derive(Clone)]
@@ -26,6 +27,8 @@ struct MetricsInner {
 	votes_duplicate: prometheus::Counter<prometheus::U64>,
 	pvfs_observed: prometheus::Counter<prometheus::U64>,
 	pvfs_left: prometheus::Counter<prometheus::U64>,
+	pvfs_rejected: prometheus::CounterVec<prometheus::U64>,
+	pre_check_retries: prometheus::Counter<prometheus::U64>,
 }
 
 #[derive(Default, Clone)]
@@ -73,6 +76,30 @@ impl Metrics {
 			metrics.pvfs_left.inc_by(num as u64);
 		}
 	}
+
+	/// Called when a PVF is voted against.
+	pub(crate) fn on_pvf_rejected(&self, class: PvfRejectionClass) {
+		if let Some(metrics) = &self.0 {
+			let class = match class {
+				PvfRejectionClass::CodeUnavailable => "code_unavailable",
+				PvfRejectionClass::ExecutorParamsUnavailable => "executor_params_unavailable",
+				PvfRejectionClass::Decompression => "decompression",
+				PvfRejectionClass::Prevalidation => "prevalidation",
+				PvfRejectionClass::Preparation => "preparation",
+				PvfRejectionClass::Panic => "panic",
+				PvfRejectionClass::TimedOut => "timed_out",
+				PvfRejectionClass::WorkerFailure => "worker_failure",
+			};
+			metrics.pvfs_rejected.with_label_values(&[class]).inc();
+		}
+	}
+
+	/// Called when a failed pre-check is scheduled to be retried.
+	pub(crate) fn on_pre_check_retry(&self) {
+		if let Some(metrics) = &self.0 {
+			metrics.pre_check_retries.inc();
+		}
+	}
 }
 
 impl metrics::Metrics for Metrics {
@@ -124,6 +151,23 @@ the same session.",
 				)?,
 				registry,
 			)?,
+			pvfs_rejected: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_pvf_precheck_pvfs_rejected",
+						"The number of PVFs voted against, by the class of the rejection.",
+					),
+					&["class"],
+				)?,
+				registry,
+			)?,
+			pre_check_retries: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_pvf_precheck_retries",
+					"The number of failed pre-checks that were retried.",
+				)?,
+				registry,
+			)?,
 		};
 		Ok(Self(Some(metrics)))
 	}
@@ -16,6 +16,7 @@
 
 use ::test_helpers::{dummy_digest, dummy_hash};
 use futures::{channel::oneshot, future::BoxFuture, prelude::*};
+use polkadot_node_primitives::{PvfRejection, PvfRejectionClass};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{
@@ -42,6 +43,17 @@ fn dummy_validation_code_hash(descriminator: u8) -> ValidationCodeHash {
 	ValidationCode(vec![descriminator]).hash()
 }
 
+fn dummy_rejection(class: PvfRejectionClass) -> PvfRejection {
+	PvfRejection {
+		class,
+		reason: "dummy".to_string(),
+		compile_time_ms: None,
+		memory_used_kb: None,
+		wasm_feature_violations: Vec::new(),
+		attempts: 1,
+	}
+}
+
 struct StartsNewSession {
 	session_index: SessionIndex,
 	validators: Vec<Sr25519Keyring>,
@@ -358,6 +370,13 @@ impl ExpectSubmitVote {
 }
 
 fn test_harness(test: impl FnOnce(TestState, VirtualOverseer) -> BoxFuture<'static, ()>) {
+	test_harness_with_retry_policy(crate::RetryPolicy::default(), test)
+}
+
+fn test_harness_with_retry_policy(
+	retry_policy: crate::RetryPolicy,
+	test: impl FnOnce(TestState, VirtualOverseer) -> BoxFuture<'static, ()>,
+) {
 	let pool = TaskExecutor::new();
 	let (ctx, handle) = make_subsystem_context::<PvfCheckerMessage, _>(pool.clone());
 	let keystore = Arc::new(sc_keystore::LocalKeystore::in_memory());
@@ -366,7 +385,8 @@ fn test_harness(test: impl FnOnce(TestState, VirtualOverseer) -> BoxFuture<'stat
 	Keystore::sr25519_generate_new(&*keystore, ValidatorId::ID, Some(&OUR_VALIDATOR.to_seed()))
 		.expect("Generating keys for our node failed");
 
-	let subsystem_task = crate::run(ctx, keystore, crate::Metrics::default()).map(|x| x.unwrap());
+	let subsystem_task =
+		crate::run(ctx, keystore, retry_policy, crate::Metrics::default()).map(|x| x.unwrap());
 
 	let test_state = TestState::new();
 	let test_task = test(test_state, handle);
@@ -631,7 +651,8 @@ fn judgements_come_out_of_order() {
 
 			// Resolve the PVF pre-checks out of order.
 			pre_check_2.reply(PreCheckOutcome::Valid);
-			pre_check_1.reply(PreCheckOutcome::Invalid);
+			pre_check_1
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Preparation)));
 
 			// Catch the vote for the second PVF.
 			let vote_2 = test_state.expect_submit_vote(&mut handle).await;
@@ -670,7 +691,7 @@ fn dont_vote_until_a_validator() {
 			test_state
 				.expect_candidate_precheck(&mut handle)
 				.await
-				.reply(PreCheckOutcome::Invalid);
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Preparation)));
 
 			// Now a leaf brings a new session. In this session our validator comes into the active
 			// set. That means it will cast a vote for each judgement it has.
@@ -724,7 +745,8 @@ fn resign_on_session_change() {
 			pre_check_1.reply(PreCheckOutcome::Valid);
 			let pre_check_2 = test_state.expect_candidate_precheck(&mut handle).await;
 			assert_eq!(pre_check_2.validation_code_hash, pvf_2);
-			pre_check_2.reply(PreCheckOutcome::Invalid);
+			pre_check_2
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Preparation)));
 
 			test_state.expect_submit_vote(&mut handle).await.reply_ok();
 			test_state.expect_submit_vote(&mut handle).await.reply_ok();
@@ -785,7 +807,8 @@ fn dont_resign_if_not_us() {
 			pre_check_1.reply(PreCheckOutcome::Valid);
 			let pre_check_2 = test_state.expect_candidate_precheck(&mut handle).await;
 			assert_eq!(pre_check_2.validation_code_hash, pvf_2);
-			pre_check_2.reply(PreCheckOutcome::Invalid);
+			pre_check_2
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Preparation)));
 
 			test_state.expect_submit_vote(&mut handle).await.reply_ok();
 			test_state.expect_submit_vote(&mut handle).await.reply_ok();
@@ -892,7 +915,8 @@ fn unexpected_pvf_check_judgement() {
 
 			// Now deactivate the leaf and reply to the precheck request.
 			test_state.deactivate_leaves(&mut handle, &[block_1.block_hash]).await;
-			pre_check.reply(PreCheckOutcome::Invalid);
+			pre_check
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Preparation)));
 
 			// the subsystem must remain silent.
 
@@ -903,7 +927,7 @@ fn unexpected_pvf_check_judgement() {
 }
 
 // Check that we do not abstain for a nondeterministic failure. Currently, this means the behavior
-// is the same as if the pre-check returned `PreCheckOutcome::Invalid`.
+// is the same as if the pre-check returned `PreCheckOutcome::Invalid`, unless retries are configured.
 #[test]
 fn dont_abstain_for_nondeterministic_pvfcheck_failure() {
 	test_harness(|mut test_state, mut handle| {
@@ -926,7 +950,7 @@ fn dont_abstain_for_nondeterministic_pvfcheck_failure() {
 
 			// Now deactivate the leaf and reply to the precheck request.
 			test_state.deactivate_leaves(&mut handle, &[block_1.block_hash]).await;
-			pre_check.reply(PreCheckOutcome::Failed);
+			pre_check.reply(PreCheckOutcome::Failed(dummy_rejection(PvfRejectionClass::TimedOut)));
 
 			// the subsystem must remain silent.
 
@@ -935,3 +959,100 @@ fn dont_abstain_for_nondeterministic_pvfcheck_failure() {
 		.boxed()
 	});
 }
+
+#[test]
+fn retries_failed_pre_check_before_voting_against() {
+	let retry_policy = crate::RetryPolicy { max_retries: 1, delay: Duration::from_millis(10) };
+	test_harness_with_retry_policy(retry_policy, |mut test_state, mut handle| {
+		async move {
+			let block = FakeLeaf::new(dummy_hash(), 1, vec![dummy_validation_code_hash(1)]);
+			test_state
+				.activate_leaf_with_session(
+					&mut handle,
+					block.clone(),
+					StartsNewSession { session_index: 2, validators: vec![OUR_VALIDATOR] },
+				)
+				.await;
+
+			test_state.expect_pvfs_require_precheck(&mut handle).await.reply_mock();
+			test_state.expect_session_for_child(&mut handle).await;
+			test_state.expect_validators(&mut handle).await;
+
+			test_state
+				.expect_candidate_precheck(&mut handle)
+				.await
+				.reply(PreCheckOutcome::Failed(dummy_rejection(PvfRejectionClass::TimedOut)));
+
+			// The failure is retried instead of being voted against.
+			let pre_check = test_state.expect_candidate_precheck(&mut handle).await;
+			assert_eq!(pre_check.relay_parent, block.block_hash);
+			assert_eq!(pre_check.validation_code_hash, dummy_validation_code_hash(1));
+			pre_check.reply(PreCheckOutcome::Failed(dummy_rejection(PvfRejectionClass::TimedOut)));
+
+			// The retries are exhausted.
+			let vote = test_state.expect_submit_vote(&mut handle).await;
+			assert_eq!(vote.stmt.accept, false);
+			assert_eq!(vote.stmt.subject, dummy_validation_code_hash(1));
+			vote.reply_ok();
+
+			let (tx, rx) = oneshot::channel();
+			handle
+				.send(FromOrchestra::Communication { msg: PvfCheckerMessage::Rejections(tx) })
+				.await;
+			let rejections = rx.await.unwrap();
+			assert_eq!(rejections.len(), 1);
+			assert_eq!(rejections[0].session_index, Some(2));
+			assert_eq!(rejections[0].rejection.class, PvfRejectionClass::TimedOut);
+			assert_eq!(rejections[0].rejection.attempts, 2);
+
+			test_state.send_conclude(&mut handle).await;
+		}
+		.boxed()
+	});
+}
+
+#[test]
+fn invalid_pvfs_are_not_retried() {
+	let retry_policy = crate::RetryPolicy { max_retries: 3, delay: Duration::from_millis(10) };
+	test_harness_with_retry_policy(retry_policy, |mut test_state, mut handle| {
+		async move {
+			let block = FakeLeaf::new(dummy_hash(), 1, vec![dummy_validation_code_hash(1)]);
+			test_state
+				.activate_leaf_with_session(
+					&mut handle,
+					block.clone(),
+					StartsNewSession { session_index: 2, validators: vec![OUR_VALIDATOR] },
+				)
+				.await;
+
+			test_state.expect_pvfs_require_precheck(&mut handle).await.reply_mock();
+			test_state.expect_session_for_child(&mut handle).await;
+			test_state.expect_validators(&mut handle).await;
+
+			test_state
+				.expect_candidate_precheck(&mut handle)
+				.await
+				.reply(PreCheckOutcome::Invalid(dummy_rejection(PvfRejectionClass::Prevalidation)));
+
+			let vote = test_state.expect_submit_vote(&mut handle).await;
+			assert_eq!(vote.stmt.accept, false);
+			vote.reply_ok();
+
+			let (tx, rx) = oneshot::channel();
+			handle
+				.send(FromOrchestra::Communication { msg: PvfCheckerMessage::Rejections(tx) })
+				.await;
+			let rejections = rx.await.unwrap();
+			assert_eq!(rejections.len(), 1);
+			assert_eq!(
+				ValidationCodeHash::from(rejections[0].validation_code_hash),
+				dummy_validation_code_hash(1),
+			);
+			assert_eq!(rejections[0].rejection.class, PvfRejectionClass::Prevalidation);
+			assert_eq!(rejections[0].rejection.attempts, 1);
+
+			test_state.send_conclude(&mut handle).await;
+		}
+		.boxed()
+	});
+}
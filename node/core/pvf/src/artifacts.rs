@@ -55,7 +55,7 @@
 //!    older by a predefined parameter. This process is run very rarely (say, once a day). Once the
 //!    artifact is expired it is removed from disk eagerly atomically.
 
-use crate::{error::PrepareError, host::PrepareResultSender, prepare::PrepareStats};
+use crate::{error::PrepareFailure, host::PrepareResultSender, prepare::PrepareStats};
 use always_assert::always;
 use polkadot_parachain::primitives::ValidationCodeHash;
 use polkadot_primitives::ExecutorParamsHash;
@@ -81,19 +81,26 @@ impl AsRef<[u8]> for CompiledArtifact {
 	}
 }
 
-/// Identifier of an artifact. Encodes a code hash of the PVF and a hash of executor parameter set.
+/// Identifier of an artifact. Encodes a code hash of the PVF, a hash of executor parameter set and
+/// whether the artifact is instrumented to count host calls.
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct ArtifactId {
 	pub(crate) code_hash: ValidationCodeHash,
 	pub(crate) executor_params_hash: ExecutorParamsHash,
+	pub(crate) count_host_calls: bool,
 }
 
 impl ArtifactId {
 	const PREFIX: &'static str = "wasmtime_";
+	const HOST_CALLS_SUFFIX: &'static str = "_host_calls";
 
 	/// Creates a new artifact ID with the given hash.
-	pub fn new(code_hash: ValidationCodeHash, executor_params_hash: ExecutorParamsHash) -> Self {
-		Self { code_hash, executor_params_hash }
+	pub fn new(
+		code_hash: ValidationCodeHash,
+		executor_params_hash: ExecutorParamsHash,
+		count_host_calls: bool,
+	) -> Self {
+		Self { code_hash, executor_params_hash, count_host_calls }
 	}
 
 	/// Tries to recover the artifact id from the given file name.
@@ -103,18 +110,27 @@ impl ArtifactId {
 		use std::str::FromStr as _;
 
 		let file_name = file_name.strip_prefix(Self::PREFIX)?;
+		let (file_name, count_host_calls) = match file_name.strip_suffix(Self::HOST_CALLS_SUFFIX) {
+			Some(file_name) => (file_name, true),
+			None => (file_name, false),
+		};
 		let (code_hash_str, executor_params_hash_str) = file_name.split_once('_')?;
 		let code_hash = Hash::from_str(code_hash_str).ok()?.into();
 		let executor_params_hash =
 			ExecutorParamsHash::from_hash(Hash::from_str(executor_params_hash_str).ok()?);
 
-		Some(Self { code_hash, executor_params_hash })
+		Some(Self { code_hash, executor_params_hash, count_host_calls })
 	}
 
 	/// Returns the expected path to this artifact given the root of the cache.
 	pub fn path(&self, cache_path: &Path) -> PathBuf {
-		let file_name =
-			format!("{}{:#x}_{:#x}", Self::PREFIX, self.code_hash, self.executor_params_hash);
+		let file_name = format!(
+			"{}{:#x}_{:#x}{}",
+			Self::PREFIX,
+			self.code_hash,
+			self.executor_params_hash,
+			if self.count_host_calls { Self::HOST_CALLS_SUFFIX } else { "" },
+		);
 		cache_path.join(file_name)
 	}
 }
@@ -167,7 +183,7 @@ pub enum ArtifactState {
 		/// The number of times this artifact has failed to prepare.
 		num_failures: u32,
 		/// The last error encountered for preparation.
-		error: PrepareError,
+		error: PrepareFailure,
 	},
 }
 
@@ -220,7 +236,6 @@ impl Artifacts {
 	///
 	/// This function must be used only for brand-new artifacts and should never be used for
 	/// replacing existing ones.
-	#[cfg(test)]
 	pub fn insert_prepared(
 		&mut self,
 		artifact_id: ArtifactId,
@@ -283,6 +298,23 @@ mod tests {
 				ExecutorParamsHash::from_hash(sp_core::H256(hex_literal::hex![
 					"0033900000000000000000000000000000000000000000000000000000000000"
 				])),
+				false,
+			)),
+		);
+
+		assert_eq!(
+			ArtifactId::from_file_name(
+				"wasmtime_0x0022800000000000000000000000000000000000000000000000000000000000_0x0033900000000000000000000000000000000000000000000000000000000000_host_calls"
+			),
+			Some(ArtifactId::new(
+				hex_literal::hex![
+					"0022800000000000000000000000000000000000000000000000000000000000"
+				]
+				.into(),
+				ExecutorParamsHash::from_hash(sp_core::H256(hex_literal::hex![
+					"0033900000000000000000000000000000000000000000000000000000000000"
+				])),
+				true,
 			)),
 		);
 	}
@@ -295,11 +327,21 @@ mod tests {
 				.unwrap();
 
 		assert_eq!(
-			ArtifactId::new(hash.into(), ExecutorParamsHash::from_hash(hash)).path(path).to_str(),
+			ArtifactId::new(hash.into(), ExecutorParamsHash::from_hash(hash), false)
+				.path(path)
+				.to_str(),
 			Some(
 				"/test/wasmtime_0x1234567890123456789012345678901234567890123456789012345678901234_0x1234567890123456789012345678901234567890123456789012345678901234"
 			),
 		);
+		assert_eq!(
+			ArtifactId::new(hash.into(), ExecutorParamsHash::from_hash(hash), true)
+				.path(path)
+				.to_str(),
+			Some(
+				"/test/wasmtime_0x1234567890123456789012345678901234567890123456789012345678901234_0x1234567890123456789012345678901234567890123456789012345678901234_host_calls"
+			),
+		);
 	}
 
 	#[tokio::test]
@@ -14,13 +14,35 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
-use crate::prepare::PrepareStats;
+use crate::prepare::{MemoryStats, PrepareStats};
 use parity_scale_codec::{Decode, Encode};
 use std::fmt;
 
 /// Result of PVF preparation performed by the validation host. Contains stats about the preparation if
 /// successful
-pub type PrepareResult = Result<PrepareStats, PrepareError>;
+pub type PrepareResult = Result<PrepareStats, PrepareFailure>;
+
+/// A failed PVF preparation.
+#[derive(Debug, Clone, Encode, Decode)]
+pub struct PrepareFailure {
+	/// The error the preparation failed with.
+	pub error: PrepareError,
+	/// The memory stats of the preparation job. `None` if the preparation did not run to
+	/// completion in the worker, e.g. because it timed out or the worker died.
+	pub memory_stats: Option<MemoryStats>,
+}
+
+impl From<PrepareError> for PrepareFailure {
+	fn from(error: PrepareError) -> Self {
+		Self { error, memory_stats: None }
+	}
+}
+
+impl fmt::Display for PrepareFailure {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.error.fmt(f)
+	}
+}
 
 /// An error that occurred during the prepare part of the PVF pipeline.
 #[derive(Debug, Clone, Encode, Decode)]
@@ -115,6 +137,10 @@ pub enum InvalidCandidate {
 	AmbiguousWorkerDeath,
 	/// PVF execution (compilation is not included) took more time than was allotted.
 	HardTimeout,
+	/// PVF execution exceeded the instruction budget set by
+	/// [`polkadot_primitives::ExecutorParam::PvfExecInstructionBudget`]. Unlike
+	/// [`Self::HardTimeout`], this does not depend on the hardware the PVF is executed on.
+	InstructionBudgetExhausted,
 	/// A panic occurred and we can't be sure whether the candidate is really invalid or some internal glitch occurred.
 	/// Whenever we are unsure, we can never treat an error as internal as we would abstain from voting. This is bad
 	/// because if the issue was due to the candidate, then all validators would abstain, stalling finality on the
@@ -159,6 +185,12 @@ impl From<InternalValidationError> for ValidationError {
 	}
 }
 
+impl From<PrepareFailure> for ValidationError {
+	fn from(failure: PrepareFailure) -> Self {
+		failure.error.into()
+	}
+}
+
 impl From<PrepareError> for ValidationError {
 	fn from(error: PrepareError) -> Self {
 		// Here we need to classify the errors into two errors: deterministic and non-deterministic.
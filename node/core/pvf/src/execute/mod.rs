@@ -25,3 +25,23 @@ mod worker_intf;
 
 pub use queue::{start, PendingExecutionRequest, ToQueue};
 pub use worker_intf::{Handshake as ExecuteHandshake, Response as ExecuteResponse};
+
+use parity_scale_codec::{Decode, Encode};
+use std::time::Duration;
+
+/// Resource usage of a single execution job, as observed by the execute worker.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Encode, Decode)]
+pub struct ExecuteStats {
+	/// The CPU time that elapsed for the execution job.
+	pub cpu_time_elapsed: Duration,
+	/// The wall clock time that elapsed for the execution job.
+	pub wall_time_elapsed: Duration,
+	/// `ru_maxrss` of the execute worker during the execution job, in kilobytes. `None` if it is
+	/// not supported by the OS or an error occurred.
+	pub max_rss: Option<i64>,
+	/// The number of calls the PVF made into host functions, if counting is enabled by the node
+	/// via [`crate::Config::count_host_calls`].
+	///
+	/// Only direct calls are counted, calls of host functions through a table are not.
+	pub host_calls: Option<u64>,
+}
@@ -22,7 +22,7 @@ use crate::{
 	host::ResultSender,
 	metrics::Metrics,
 	worker_common::{IdleWorker, WorkerHandle},
-	InvalidCandidate, ValidationError, LOG_TARGET,
+	InvalidCandidate, Priority, ValidationError, LOG_TARGET,
 };
 use futures::{
 	channel::mpsc,
@@ -46,6 +46,16 @@ use std::{
 /// timeout in use, and less than the block time.
 const MAX_KEEP_WAITING: Duration = Duration::from_secs(4);
 
+/// The number of workers which normal priority jobs (approvals and disputes) cannot occupy, so that
+/// critical jobs (backing) never have to wait for all workers to become free. If the queue has only
+/// a single worker, it is shared by both priorities.
+const WORKERS_RESERVED_FOR_CRITICAL: usize = 1;
+
+/// The maximum number of critical jobs started in a row while normal priority jobs are waiting for
+/// a worker. After that, the next normal priority job is started first, which guarantees that
+/// approvals and disputes make progress even under a constant stream of backing work.
+const MAX_CRITICAL_IN_A_ROW: usize = 4;
+
 slotmap::new_key_type! { struct Worker; }
 
 #[derive(Debug)]
@@ -60,6 +70,7 @@ pub struct PendingExecutionRequest {
 	pub exec_timeout: Duration,
 	pub params: Vec<u8>,
 	pub executor_params: ExecutorParams,
+	pub priority: Priority,
 	pub result_tx: ResultSender,
 }
 
@@ -68,10 +79,58 @@ struct ExecuteJob {
 	exec_timeout: Duration,
 	params: Vec<u8>,
 	executor_params: ExecutorParams,
+	priority: Priority,
 	result_tx: ResultSender,
 	waiting_since: Instant,
 }
 
+/// Jobs waiting for a worker, kept in a separate FIFO per priority.
+#[derive(Default)]
+struct Unscheduled {
+	normal: VecDeque<ExecuteJob>,
+	critical: VecDeque<ExecuteJob>,
+	/// The number of critical jobs started in a row while normal priority jobs were waiting.
+	critical_in_a_row: usize,
+}
+
+impl Unscheduled {
+	fn push(&mut self, job: ExecuteJob) {
+		self.get_mut(job.priority).push_back(job);
+	}
+
+	fn get_mut(&mut self, priority: Priority) -> &mut VecDeque<ExecuteJob> {
+		match priority {
+			Priority::Normal => &mut self.normal,
+			Priority::Critical => &mut self.critical,
+		}
+	}
+
+	/// Select the priority class to start the next job from.
+	///
+	/// Critical jobs go first, unless normal priority jobs were passed over
+	/// [`MAX_CRITICAL_IN_A_ROW`] times already. Normal priority jobs are only considered if
+	/// `normal_allowed`, i.e. if they would not eat into the workers reserved for critical jobs.
+	fn select(&self, normal_allowed: bool) -> Option<Priority> {
+		let normal_waiting = normal_allowed && !self.normal.is_empty();
+		match (!self.critical.is_empty(), normal_waiting) {
+			(true, true) if self.critical_in_a_row >= MAX_CRITICAL_IN_A_ROW =>
+				Some(Priority::Normal),
+			(true, _) => Some(Priority::Critical),
+			(false, true) => Some(Priority::Normal),
+			(false, false) => None,
+		}
+	}
+
+	/// Note that a job of the given priority was started.
+	fn note_started(&mut self, priority: Priority) {
+		match priority {
+			Priority::Normal => self.critical_in_a_row = 0,
+			Priority::Critical if !self.normal.is_empty() => self.critical_in_a_row += 1,
+			Priority::Critical => {},
+		}
+	}
+}
+
 struct WorkerData {
 	idle: Option<IdleWorker>,
 	handle: WorkerHandle,
@@ -93,6 +152,9 @@ struct Workers {
 
 	/// The maximum number of workers queue can have at once.
 	capacity: usize,
+
+	/// The number of normal priority jobs which are executing or waiting for their worker to spawn.
+	normal_jobs_started: usize,
 }
 
 impl Workers {
@@ -100,6 +162,13 @@ impl Workers {
 		self.spawn_inflight + self.running.len() < self.capacity
 	}
 
+	/// Whether another normal priority job can be started without using the workers reserved for
+	/// critical jobs.
+	fn can_start_normal_job(&self) -> bool {
+		let normal_capacity = self.capacity.saturating_sub(WORKERS_RESERVED_FOR_CRITICAL).max(1);
+		self.normal_jobs_started < normal_capacity
+	}
+
 	fn find_available(&self, executor_params_hash: ExecutorParamsHash) -> Option<Worker> {
 		self.running.iter().find_map(|d| {
 			if d.1.idle.is_some() && d.1.executor_params_hash == executor_params_hash {
@@ -126,7 +195,7 @@ impl Workers {
 
 enum QueueEvent {
 	Spawn(IdleWorker, WorkerHandle, ExecuteJob),
-	StartWork(Worker, Outcome, ArtifactId, ResultSender),
+	StartWork(Worker, Outcome, ArtifactId, Priority, ResultSender),
 }
 
 type Mux = FuturesUnordered<BoxFuture<'static, QueueEvent>>;
@@ -139,9 +208,10 @@ struct Queue {
 
 	program_path: PathBuf,
 	spawn_timeout: Duration,
+	count_host_calls: bool,
 
-	/// The queue of jobs that are waiting for a worker to pick up.
-	queue: VecDeque<ExecuteJob>,
+	/// The jobs that are waiting for a worker to pick up.
+	queue: Unscheduled,
 	workers: Workers,
 	mux: Mux,
 }
@@ -152,19 +222,22 @@ impl Queue {
 		program_path: PathBuf,
 		worker_capacity: usize,
 		spawn_timeout: Duration,
+		count_host_calls: bool,
 		to_queue_rx: mpsc::Receiver<ToQueue>,
 	) -> Self {
 		Self {
 			metrics,
 			program_path,
 			spawn_timeout,
+			count_host_calls,
 			to_queue_rx,
-			queue: VecDeque::new(),
+			queue: Unscheduled::default(),
 			mux: Mux::new(),
 			workers: Workers {
 				running: HopSlotMap::with_capacity_and_key(10),
 				spawn_inflight: 0,
 				capacity: worker_capacity,
+				normal_jobs_started: 0,
 			},
 		}
 	}
@@ -186,16 +259,23 @@ impl Queue {
 		}
 	}
 
-	/// Tries to assign a job in the queue to a worker. If an idle worker is provided, it does its
-	/// best to find a job with a compatible execution environment unless there are jobs in the
-	/// queue waiting too long. In that case, it kills an existing idle worker and spawns a new
+	/// Tries to assign a job in the queue to a worker. The job is taken from the priority class
+	/// selected by [`Unscheduled::select`]. If an idle worker is provided, it does its best to
+	/// find a job of that class with a compatible execution environment unless there are jobs in
+	/// the class waiting too long. In that case, it kills an existing idle worker and spawns a new
 	/// one. It may spawn an additional worker if that is affordable.
 	/// If all the workers are busy or the queue is empty, it does nothing.
 	/// Should be called every time a new job arrives to the queue or a job finishes.
 	fn try_assign_next_job(&mut self, finished_worker: Option<Worker>) {
+		let priority = match self.queue.select(self.workers.can_start_normal_job()) {
+			Some(priority) => priority,
+			None => return,
+		};
+		let queue = self.queue.get_mut(priority);
+
 		// New jobs are always pushed to the tail of the queue; the one at its head is always
 		// the eldest one.
-		let eldest = if let Some(eldest) = self.queue.get(0) { eldest } else { return };
+		let eldest = if let Some(eldest) = queue.get(0) { eldest } else { return };
 
 		// By default, we're going to execute the eldest job on any worker slot available, even if
 		// we have to kill and re-spawn a worker
@@ -207,7 +287,7 @@ impl Queue {
 		if eldest.waiting_since.elapsed() < MAX_KEEP_WAITING {
 			if let Some(finished_worker) = finished_worker {
 				if let Some(worker_data) = self.workers.running.get(finished_worker) {
-					for (i, job) in self.queue.iter().enumerate() {
+					for (i, job) in queue.iter().enumerate() {
 						if worker_data.executor_params_hash == job.executor_params.hash() {
 							(worker, job_index) = (Some(finished_worker), i);
 							break
@@ -219,7 +299,7 @@ impl Queue {
 
 		if worker.is_none() {
 			// Try to obtain a worker for the job
-			worker = self.workers.find_available(self.queue[job_index].executor_params.hash());
+			worker = self.workers.find_available(queue[job_index].executor_params.hash());
 		}
 
 		if worker.is_none() {
@@ -237,7 +317,15 @@ impl Queue {
 			return
 		}
 
-		let job = self.queue.remove(job_index).expect("Job is just checked to be in queue; qed");
+		let job = self
+			.queue
+			.get_mut(priority)
+			.remove(job_index)
+			.expect("Job is just checked to be in queue; qed");
+		self.queue.note_started(priority);
+		if priority == Priority::Normal {
+			self.workers.normal_jobs_started += 1;
+		}
 
 		if let Some(worker) = worker {
 			assign(self, worker, job);
@@ -264,11 +352,12 @@ async fn purge_dead(metrics: &Metrics, workers: &mut Workers) {
 
 fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) {
 	let ToQueue::Enqueue { artifact, pending_execution_request } = to_queue;
-	let PendingExecutionRequest { exec_timeout, params, executor_params, result_tx } =
+	let PendingExecutionRequest { exec_timeout, params, executor_params, priority, result_tx } =
 		pending_execution_request;
 	gum::debug!(
 		target: LOG_TARGET,
 		validation_code_hash = ?artifact.id.code_hash,
+		?priority,
 		"enqueueing an artifact for execution",
 	);
 	queue.metrics.execute_enqueued();
@@ -277,10 +366,11 @@ fn handle_to_queue(queue: &mut Queue, to_queue: ToQueue) {
 		exec_timeout,
 		params,
 		executor_params,
+		priority,
 		result_tx,
 		waiting_since: Instant::now(),
 	};
-	queue.queue.push_back(job);
+	queue.queue.push(job);
 	queue.try_assign_next_job(None);
 }
 
@@ -289,8 +379,8 @@ async fn handle_mux(queue: &mut Queue, event: QueueEvent) {
 		QueueEvent::Spawn(idle, handle, job) => {
 			handle_worker_spawned(queue, idle, handle, job);
 		},
-		QueueEvent::StartWork(worker, outcome, artifact_id, result_tx) => {
-			handle_job_finish(queue, worker, outcome, artifact_id, result_tx);
+		QueueEvent::StartWork(worker, outcome, artifact_id, priority, result_tx) => {
+			handle_job_finish(queue, worker, outcome, artifact_id, priority, result_tx);
 		},
 	}
 }
@@ -321,51 +411,59 @@ fn handle_job_finish(
 	worker: Worker,
 	outcome: Outcome,
 	artifact_id: ArtifactId,
+	priority: Priority,
 	result_tx: ResultSender,
 ) {
-	let (idle_worker, result, duration) = match outcome {
-		Outcome::Ok { result_descriptor, duration, idle_worker } => {
+	if priority == Priority::Normal {
+		queue.workers.normal_jobs_started -= 1;
+	}
+
+	let (idle_worker, result) = match outcome {
+		Outcome::Ok { result_descriptor, stats, idle_worker } => {
 			// TODO: propagate the soft timeout
 
-			(Some(idle_worker), Ok(result_descriptor), Some(duration))
+			(Some(idle_worker), Ok((result_descriptor, stats)))
 		},
 		Outcome::InvalidCandidate { err, idle_worker } => (
 			Some(idle_worker),
 			Err(ValidationError::InvalidCandidate(InvalidCandidate::WorkerReportedError(err))),
-			None,
 		),
-		Outcome::InternalError { err } => (None, Err(ValidationError::InternalError(err)), None),
+		Outcome::InternalError { err } => (None, Err(ValidationError::InternalError(err))),
 		Outcome::HardTimeout =>
-			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::HardTimeout)), None),
-		// "Maybe invalid" errors (will retry).
-		Outcome::IoErr => (
-			None,
-			Err(ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath)),
-			None,
+			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::HardTimeout))),
+		Outcome::InstructionBudgetExhausted { idle_worker } => (
+			Some(idle_worker),
+			Err(ValidationError::InvalidCandidate(InvalidCandidate::InstructionBudgetExhausted)),
 		),
+		// "Maybe invalid" errors (will retry).
+		Outcome::IoErr =>
+			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::AmbiguousWorkerDeath))),
 		Outcome::Panic { err } =>
-			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::Panic(err))), None),
+			(None, Err(ValidationError::InvalidCandidate(InvalidCandidate::Panic(err)))),
 	};
 
 	queue.metrics.execute_finished();
-	if let Err(ref err) = result {
-		gum::warn!(
-			target: LOG_TARGET,
-			?artifact_id,
-			?worker,
-			worker_rip = idle_worker.is_none(),
-			"execution worker concluded, error occurred: {:?}",
-			err
-		);
-	} else {
-		gum::trace!(
-			target: LOG_TARGET,
-			?artifact_id,
-			?worker,
-			worker_rip = idle_worker.is_none(),
-			?duration,
-			"execute worker concluded successfully",
-		);
+	match result {
+		Err(ref err) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				?artifact_id,
+				?worker,
+				worker_rip = idle_worker.is_none(),
+				"execution worker concluded, error occurred: {:?}",
+				err
+			);
+		},
+		Ok((_, ref stats)) => {
+			gum::trace!(
+				target: LOG_TARGET,
+				?artifact_id,
+				?worker,
+				worker_rip = idle_worker.is_none(),
+				?stats,
+				"execute worker concluded successfully",
+			);
+		},
 	}
 
 	// First we send the result. It may fail due to the other end of the channel being dropped,
@@ -398,9 +496,15 @@ fn spawn_extra_worker(queue: &mut Queue, job: ExecuteJob) {
 	queue.metrics.execute_worker().on_begin_spawn();
 	gum::debug!(target: LOG_TARGET, "spawning an extra worker");
 
-	queue
-		.mux
-		.push(spawn_worker_task(queue.program_path.clone(), job, queue.spawn_timeout).boxed());
+	queue.mux.push(
+		spawn_worker_task(
+			queue.program_path.clone(),
+			job,
+			queue.spawn_timeout,
+			queue.count_host_calls,
+		)
+		.boxed(),
+	);
 	queue.workers.spawn_inflight += 1;
 }
 
@@ -414,12 +518,18 @@ async fn spawn_worker_task(
 	program_path: PathBuf,
 	job: ExecuteJob,
 	spawn_timeout: Duration,
+	count_host_calls: bool,
 ) -> QueueEvent {
 	use futures_timer::Delay;
 
 	loop {
-		match super::worker_intf::spawn(&program_path, job.executor_params.clone(), spawn_timeout)
-			.await
+		match super::worker_intf::spawn(
+			&program_path,
+			job.executor_params.clone(),
+			count_host_calls,
+			spawn_timeout,
+		)
+		.await
 		{
 			Ok((idle, handle)) => break QueueEvent::Spawn(idle, handle, job),
 			Err(err) => {
@@ -470,7 +580,7 @@ fn assign(queue: &mut Queue, worker: Worker, job: ExecuteJob) {
 				job.params,
 			)
 			.await;
-			QueueEvent::StartWork(worker, outcome, job.artifact.id, job.result_tx)
+			QueueEvent::StartWork(worker, outcome, job.artifact.id, job.priority, job.result_tx)
 		}
 		.boxed(),
 	);
@@ -481,8 +591,94 @@ pub fn start(
 	program_path: PathBuf,
 	worker_capacity: usize,
 	spawn_timeout: Duration,
+	count_host_calls: bool,
 ) -> (mpsc::Sender<ToQueue>, impl Future<Output = ()>) {
 	let (to_queue_tx, to_queue_rx) = mpsc::channel(20);
-	let run = Queue::new(metrics, program_path, worker_capacity, spawn_timeout, to_queue_rx).run();
+	let run = Queue::new(
+		metrics,
+		program_path,
+		worker_capacity,
+		spawn_timeout,
+		count_host_calls,
+		to_queue_rx,
+	)
+	.run();
 	(to_queue_tx, run)
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::PvfPrepData;
+	use futures::channel::oneshot;
+
+	fn job(priority: Priority) -> ExecuteJob {
+		let (result_tx, _) = oneshot::channel();
+		ExecuteJob {
+			artifact: ArtifactPathId::new(
+				PvfPrepData::from_discriminator(1).as_artifact_id(),
+				&std::env::temp_dir(),
+			),
+			exec_timeout: Duration::from_secs(1),
+			params: Vec::new(),
+			executor_params: ExecutorParams::default(),
+			priority,
+			result_tx,
+			waiting_since: Instant::now(),
+		}
+	}
+
+	fn start_next(queue: &mut Unscheduled, normal_allowed: bool) -> Option<Priority> {
+		let priority = queue.select(normal_allowed)?;
+		queue.get_mut(priority).pop_front();
+		queue.note_started(priority);
+		Some(priority)
+	}
+
+	#[test]
+	fn critical_jobs_go_first() {
+		let mut queue = Unscheduled::default();
+		queue.push(job(Priority::Normal));
+		queue.push(job(Priority::Critical));
+
+		assert_eq!(start_next(&mut queue, true), Some(Priority::Critical));
+		assert_eq!(start_next(&mut queue, true), Some(Priority::Normal));
+		assert_eq!(start_next(&mut queue, true), None);
+	}
+
+	#[test]
+	fn normal_jobs_are_not_starved() {
+		let mut queue = Unscheduled::default();
+		queue.push(job(Priority::Normal));
+		for _ in 0..MAX_CRITICAL_IN_A_ROW + 1 {
+			queue.push(job(Priority::Critical));
+		}
+
+		for _ in 0..MAX_CRITICAL_IN_A_ROW {
+			assert_eq!(start_next(&mut queue, true), Some(Priority::Critical));
+		}
+		assert_eq!(start_next(&mut queue, true), Some(Priority::Normal));
+		assert_eq!(start_next(&mut queue, true), Some(Priority::Critical));
+	}
+
+	#[test]
+	fn normal_jobs_respect_reserved_workers() {
+		let mut queue = Unscheduled::default();
+		queue.push(job(Priority::Normal));
+
+		assert_eq!(queue.select(false), None);
+		assert_eq!(queue.select(true), Some(Priority::Normal));
+
+		let workers = |capacity, normal_jobs_started| Workers {
+			running: HopSlotMap::with_capacity_and_key(1),
+			spawn_inflight: 0,
+			capacity,
+			normal_jobs_started,
+		};
+		assert!(workers(1, 0).can_start_normal_job());
+		assert!(!workers(1, 1).can_start_normal_job());
+		assert!(workers(2, 0).can_start_normal_job());
+		assert!(!workers(2, 1).can_start_normal_job());
+		assert!(workers(3, 1).can_start_normal_job());
+	}
+}
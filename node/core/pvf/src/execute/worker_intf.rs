@@ -16,6 +16,7 @@
 
 //! Host interface to the execute worker.
 
+use super::ExecuteStats;
 use crate::{
 	artifacts::ArtifactPathId,
 	error::InternalValidationError,
@@ -41,6 +42,7 @@ use tokio::{io, net::UnixStream};
 pub async fn spawn(
 	program_path: &Path,
 	executor_params: ExecutorParams,
+	count_host_calls: bool,
 	spawn_timeout: Duration,
 ) -> Result<(IdleWorker, WorkerHandle), SpawnErr> {
 	let (mut idle_worker, worker_handle) = spawn_with_program_path(
@@ -50,7 +52,7 @@ pub async fn spawn(
 		spawn_timeout,
 	)
 	.await?;
-	send_handshake(&mut idle_worker.stream, Handshake { executor_params })
+	send_handshake(&mut idle_worker.stream, Handshake { executor_params, count_host_calls })
 		.await
 		.map_err(|error| {
 			gum::warn!(
@@ -70,7 +72,7 @@ pub async fn spawn(
 pub enum Outcome {
 	/// PVF execution completed successfully and the result is returned. The worker is ready for
 	/// another job.
-	Ok { result_descriptor: ValidationResult, duration: Duration, idle_worker: IdleWorker },
+	Ok { result_descriptor: ValidationResult, stats: ExecuteStats, idle_worker: IdleWorker },
 	/// The candidate validation failed. It may be for example because the wasm execution triggered a trap.
 	/// Errors related to the preparation process are not expected to be encountered by the execution workers.
 	InvalidCandidate { err: String, idle_worker: IdleWorker },
@@ -87,6 +89,8 @@ pub enum Outcome {
 	IoErr,
 	/// An unexpected panic has occurred in the execution worker.
 	Panic { err: String },
+	/// The PVF exhausted its instruction budget. The worker is ready for another job.
+	InstructionBudgetExhausted { idle_worker: IdleWorker },
 }
 
 /// Given the idle token of a worker and parameters of work, communicates with the worker and
@@ -142,7 +146,8 @@ pub async fn start_work(
 					return Outcome::IoErr
 				},
 				Ok(response) => {
-					if let Response::Ok{duration, ..} = response {
+					if let Response::Ok{ref stats, ..} = response {
+						let duration = stats.cpu_time_elapsed;
 						if duration > execution_timeout {
 							// The job didn't complete within the timeout.
 							gum::warn!(
@@ -174,13 +179,15 @@ pub async fn start_work(
 	};
 
 	match response {
-		Response::Ok { result_descriptor, duration } =>
-			Outcome::Ok { result_descriptor, duration, idle_worker: IdleWorker { stream, pid } },
+		Response::Ok { result_descriptor, stats } =>
+			Outcome::Ok { result_descriptor, stats, idle_worker: IdleWorker { stream, pid } },
 		Response::InvalidCandidate(err) =>
 			Outcome::InvalidCandidate { err, idle_worker: IdleWorker { stream, pid } },
 		Response::TimedOut => Outcome::HardTimeout,
 		Response::Panic(err) => Outcome::Panic { err },
 		Response::InternalError(err) => Outcome::InternalError { err },
+		Response::InstructionBudgetExhausted =>
+			Outcome::InstructionBudgetExhausted { idle_worker: IdleWorker { stream, pid } },
 	}
 }
 
@@ -215,6 +222,8 @@ async fn recv_response(stream: &mut UnixStream) -> io::Result<Response> {
 pub struct Handshake {
 	/// The executor parameters.
 	pub executor_params: ExecutorParams,
+	/// Whether the artifacts are instrumented to count host calls.
+	pub count_host_calls: bool,
 }
 
 /// The response from an execution job on the worker.
@@ -224,8 +233,8 @@ pub enum Response {
 	Ok {
 		/// The result of parachain validation.
 		result_descriptor: ValidationResult,
-		/// The amount of CPU time taken by the job.
-		duration: Duration,
+		/// The resources used by the job.
+		stats: ExecuteStats,
 	},
 	/// The candidate is invalid.
 	InvalidCandidate(String),
@@ -235,6 +244,8 @@ pub enum Response {
 	Panic(String),
 	/// Some internal error occurred.
 	InternalError(InternalValidationError),
+	/// The PVF exhausted its instruction budget.
+	InstructionBudgetExhausted,
 }
 
 impl Response {
@@ -23,9 +23,15 @@
 use crate::{
 	artifacts::{ArtifactId, ArtifactPathId, ArtifactState, Artifacts},
 	error::PrepareError,
-	execute::{self, PendingExecutionRequest},
+	execute::{self, ExecuteStats, PendingExecutionRequest},
+	import::{self, ArtifactImport, Importer},
 	metrics::Metrics,
-	prepare, PrepareResult, Priority, PvfPrepData, ValidationError, LOG_TARGET,
+	prepare::{self, PrepareStats},
+	worker_pools::{
+		WorkerPools, DEFAULT_EXECUTE_WORKERS_MAX_NUM, DEFAULT_PREPARE_WORKERS_MAX_NUM,
+		DEFAULT_WORKER_SPAWN_TIMEOUT,
+	},
+	PrepareResult, Priority, PvfPrepData, ValidationError, LOG_TARGET,
 };
 use always_assert::never;
 use futures::{
@@ -50,7 +56,8 @@ pub const PREPARE_FAILURE_COOLDOWN: Duration = Duration::from_millis(200);
 pub const NUM_PREPARE_RETRIES: u32 = 5;
 
 /// An alias to not spell the type for the oneshot sender for the PVF execution result.
-pub(crate) type ResultSender = oneshot::Sender<Result<ValidationResult, ValidationError>>;
+pub(crate) type ResultSender =
+	oneshot::Sender<Result<(ValidationResult, ExecuteStats), ValidationError>>;
 
 /// Transmission end used for sending the PVF preparation result.
 pub(crate) type PrepareResultSender = oneshot::Sender<PrepareResult>;
@@ -59,6 +66,7 @@ pub(crate) type PrepareResultSender = oneshot::Sender<PrepareResult>;
 #[derive(Clone)]
 pub struct ValidationHost {
 	to_host_tx: mpsc::Sender<ToHost>,
+	count_host_calls: bool,
 }
 
 impl ValidationHost {
@@ -96,6 +104,7 @@ impl ValidationHost {
 		priority: Priority,
 		result_tx: ResultSender,
 	) -> Result<(), String> {
+		let pvf = pvf.with_host_call_counting(self.count_host_calls);
 		self.to_host_tx
 			.send(ToHost::ExecutePvf(ExecutePvfInputs {
 				pvf,
@@ -115,6 +124,10 @@ impl ValidationHost {
 	///
 	/// Returns an error if the request cannot be sent to the validation host, i.e. if it shut down.
 	pub async fn heads_up(&mut self, active_pvfs: Vec<PvfPrepData>) -> Result<(), String> {
+		let active_pvfs = active_pvfs
+			.into_iter()
+			.map(|pvf| pvf.with_host_call_counting(self.count_host_calls))
+			.collect();
 		self.to_host_tx
 			.send(ToHost::HeadsUp { active_pvfs })
 			.await
@@ -155,6 +168,12 @@ pub struct Config {
 	pub execute_worker_spawn_timeout: Duration,
 	/// The maximum number of execute workers that can run at the same time.
 	pub execute_workers_max_num: usize,
+	/// The import of precompiled artifacts, if enabled.
+	pub artifact_import: Option<ArtifactImport>,
+	/// Whether the artifacts used for execution are instrumented to count host calls. Prechecking
+	/// always uses uninstrumented artifacts, and imported artifacts, which are never instrumented,
+	/// are not used for execution if this is set.
+	pub count_host_calls: bool,
 }
 
 impl Config {
@@ -167,14 +186,31 @@ impl Config {
 		Self {
 			cache_path,
 			prepare_worker_program_path: program_path.clone(),
-			prepare_worker_spawn_timeout: Duration::from_secs(3),
-			prepare_workers_soft_max_num: 1,
-			prepare_workers_hard_max_num: 1,
+			prepare_worker_spawn_timeout: DEFAULT_WORKER_SPAWN_TIMEOUT,
+			prepare_workers_soft_max_num: DEFAULT_PREPARE_WORKERS_MAX_NUM,
+			prepare_workers_hard_max_num: DEFAULT_PREPARE_WORKERS_MAX_NUM,
 			execute_worker_program_path: program_path,
-			execute_worker_spawn_timeout: Duration::from_secs(3),
-			execute_workers_max_num: 2,
+			execute_worker_spawn_timeout: DEFAULT_WORKER_SPAWN_TIMEOUT,
+			execute_workers_max_num: DEFAULT_EXECUTE_WORKERS_MAX_NUM,
+			artifact_import: None,
+			count_host_calls: false,
 		}
 	}
+
+	/// Apply the worker pool configuration given by the node operator.
+	pub fn with_worker_pools(mut self, worker_pools: &WorkerPools) -> Self {
+		let sizes = worker_pools.resolve();
+		self.prepare_workers_soft_max_num = sizes.prepare_workers_soft_max_num;
+		self.prepare_workers_hard_max_num = sizes.prepare_workers_hard_max_num;
+		self.execute_workers_max_num = sizes.execute_workers_max_num;
+
+		if let Some(spawn_timeout) = worker_pools.worker_spawn_timeout {
+			self.prepare_worker_spawn_timeout = spawn_timeout;
+			self.execute_worker_spawn_timeout = spawn_timeout;
+		}
+
+		self
+	}
 }
 
 /// Start the validation host.
@@ -186,9 +222,18 @@ impl Config {
 /// In that case all pending requests will be canceled, dropping the result senders and new ones
 /// will be rejected.
 pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<Output = ()>) {
+	gum::info!(
+		target: LOG_TARGET,
+		prepare_workers_soft_max_num = %config.prepare_workers_soft_max_num,
+		prepare_workers_hard_max_num = %config.prepare_workers_hard_max_num,
+		execute_workers_max_num = %config.execute_workers_max_num,
+		max_workers = %(config.prepare_workers_hard_max_num + config.execute_workers_max_num),
+		"Starting the PVF validation host",
+	);
+
 	let (to_host_tx, to_host_rx) = mpsc::channel(10);
 
-	let validation_host = ValidationHost { to_host_tx };
+	let validation_host = ValidationHost { to_host_tx, count_host_calls: config.count_host_calls };
 
 	let (to_prepare_pool, from_prepare_pool, run_prepare_pool) = prepare::start_pool(
 		metrics.clone(),
@@ -211,6 +256,7 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 		config.execute_worker_program_path.to_owned(),
 		config.execute_workers_max_num,
 		config.execute_worker_spawn_timeout,
+		config.count_host_calls,
 	);
 
 	let (to_sweeper_tx, to_sweeper_rx) = mpsc::channel(100);
@@ -230,6 +276,7 @@ pub fn start(config: Config, metrics: Metrics) -> (ValidationHost, impl Future<O
 			to_execute_queue_tx,
 			to_sweeper_tx,
 			awaiting_prepare: AwaitingPrepare::default(),
+			importer: config.artifact_import.map(Importer::new),
 		})
 		.await
 	};
@@ -278,6 +325,7 @@ struct Inner {
 	to_sweeper_tx: mpsc::Sender<PathBuf>,
 
 	awaiting_prepare: AwaitingPrepare,
+	importer: Option<Importer>,
 }
 
 #[derive(Debug)]
@@ -295,6 +343,7 @@ async fn run(
 		mut to_execute_queue_tx,
 		mut to_sweeper_tx,
 		mut awaiting_prepare,
+		mut importer,
 	}: Inner,
 ) {
 	macro_rules! break_if_fatal {
@@ -353,6 +402,7 @@ async fn run(
 					&mut to_prepare_queue_tx,
 					&mut to_execute_queue_tx,
 					&mut awaiting_prepare,
+					&mut importer,
 					to_host,
 				)
 				.await);
@@ -373,7 +423,9 @@ async fn run(
 					&cache_path,
 					&mut artifacts,
 					&mut to_execute_queue_tx,
+					&mut to_sweeper_tx,
 					&mut awaiting_prepare,
+					&mut importer,
 					from_queue,
 				).await);
 			},
@@ -387,6 +439,7 @@ async fn handle_to_host(
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
 	execute_queue: &mut mpsc::Sender<execute::ToQueue>,
 	awaiting_prepare: &mut AwaitingPrepare,
+	importer: &mut Option<Importer>,
 	to_host: ToHost,
 ) -> Result<(), Fatal> {
 	match to_host {
@@ -400,12 +453,13 @@ async fn handle_to_host(
 				prepare_queue,
 				execute_queue,
 				awaiting_prepare,
+				importer,
 				inputs,
 			)
 			.await?;
 		},
 		ToHost::HeadsUp { active_pvfs } =>
-			handle_heads_up(artifacts, prepare_queue, active_pvfs).await?,
+			handle_heads_up(cache_path, artifacts, prepare_queue, importer, active_pvfs).await?,
 	}
 
 	Ok(())
@@ -416,7 +470,9 @@ async fn handle_to_host(
 /// This tries to prepare the PVF by compiling the WASM blob within a timeout set in
 /// `PvfPrepData`.
 ///
-/// If the prepare job failed previously, we may retry it under certain conditions.
+/// If the prepare job failed previously, we retry it if the error may be transient. Unlike for
+/// execution, no cooldown applies: pre-checks are only repeated by the pvf-checker, which waits
+/// for its own retry delay.
 async fn handle_precheck_pvf(
 	artifacts: &mut Artifacts,
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
@@ -433,11 +489,30 @@ async fn handle_precheck_pvf(
 			},
 			ArtifactState::Preparing { waiting_for_response, num_failures: _ } =>
 				waiting_for_response.push(result_sender),
-			ArtifactState::FailedToProcess { error, .. } => {
-				// Do not retry failed preparation if another pre-check request comes in. We do not retry pre-checking,
-				// anyway.
-				let _ = result_sender.send(PrepareResult::Err(error.clone()));
-			},
+			ArtifactState::FailedToProcess { last_time_failed, num_failures, error } =>
+				if can_retry_prepare(*num_failures, &error.error) {
+					gum::warn!(
+						target: LOG_TARGET,
+						?pvf,
+						?artifact_id,
+						?last_time_failed,
+						%num_failures,
+						%error,
+						"handle_precheck_pvf: Re-trying failed PVF preparation."
+					);
+
+					*state = ArtifactState::Preparing {
+						waiting_for_response: vec![result_sender],
+						num_failures: *num_failures,
+					};
+					send_prepare(
+						prepare_queue,
+						prepare::ToQueue::Enqueue { priority: Priority::Normal, pvf },
+					)
+					.await?;
+				} else {
+					let _ = result_sender.send(PrepareResult::Err(error.clone()));
+				},
 		}
 	} else {
 		artifacts.insert_preparing(artifact_id, vec![result_sender]);
@@ -464,6 +539,7 @@ async fn handle_execute_pvf(
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
 	execute_queue: &mut mpsc::Sender<execute::ToQueue>,
 	awaiting_prepare: &mut AwaitingPrepare,
+	importer: &mut Option<Importer>,
 	inputs: ExecutePvfInputs,
 ) -> Result<(), Fatal> {
 	let ExecutePvfInputs { pvf, exec_timeout, params, priority, result_tx } = inputs;
@@ -487,6 +563,7 @@ async fn handle_execute_pvf(
 								exec_timeout,
 								params,
 								executor_params,
+								priority,
 								result_tx,
 							},
 						},
@@ -515,6 +592,7 @@ async fn handle_execute_pvf(
 							exec_timeout,
 							params,
 							executor_params,
+							priority,
 							result_tx,
 						},
 					)
@@ -524,11 +602,17 @@ async fn handle_execute_pvf(
 			ArtifactState::Preparing { .. } => {
 				awaiting_prepare.add(
 					artifact_id,
-					PendingExecutionRequest { exec_timeout, params, executor_params, result_tx },
+					PendingExecutionRequest {
+						exec_timeout,
+						params,
+						executor_params,
+						priority,
+						result_tx,
+					},
 				);
 			},
 			ArtifactState::FailedToProcess { last_time_failed, num_failures, error } => {
-				if can_retry_prepare_after_failure(*last_time_failed, *num_failures, error) {
+				if can_retry_prepare_after_failure(*last_time_failed, *num_failures, &error.error) {
 					gum::warn!(
 						target: LOG_TARGET,
 						?pvf,
@@ -555,6 +639,7 @@ async fn handle_execute_pvf(
 							exec_timeout,
 							params,
 							executor_params,
+							priority,
 							result_tx,
 						},
 					)
@@ -564,6 +649,21 @@ async fn handle_execute_pvf(
 				}
 			},
 		}
+	} else if try_import_artifact(cache_path, artifacts, prepare_queue, importer, &pvf).await? {
+		send_execute(
+			execute_queue,
+			execute::ToQueue::Enqueue {
+				artifact: ArtifactPathId::new(artifact_id, cache_path),
+				pending_execution_request: PendingExecutionRequest {
+					exec_timeout,
+					params,
+					executor_params,
+					priority,
+					result_tx,
+				},
+			},
+		)
+		.await?;
 	} else {
 		// Artifact is unknown: register it and enqueue a job with the corresponding priority and
 		// PVF.
@@ -574,7 +674,7 @@ async fn handle_execute_pvf(
 			pvf,
 			priority,
 			artifact_id,
-			PendingExecutionRequest { exec_timeout, params, executor_params, result_tx },
+			PendingExecutionRequest { exec_timeout, params, executor_params, priority, result_tx },
 		)
 		.await?;
 	}
@@ -583,8 +683,10 @@ async fn handle_execute_pvf(
 }
 
 async fn handle_heads_up(
+	cache_path: &Path,
 	artifacts: &mut Artifacts,
 	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
+	importer: &mut Option<Importer>,
 	active_pvfs: Vec<PvfPrepData>,
 ) -> Result<(), Fatal> {
 	let now = SystemTime::now();
@@ -600,7 +702,11 @@ async fn handle_heads_up(
 					// The artifact is already being prepared, so we don't need to do anything.
 				},
 				ArtifactState::FailedToProcess { last_time_failed, num_failures, error } => {
-					if can_retry_prepare_after_failure(*last_time_failed, *num_failures, error) {
+					if can_retry_prepare_after_failure(
+						*last_time_failed,
+						*num_failures,
+						&error.error,
+					) {
 						gum::warn!(
 							target: LOG_TARGET,
 							?active_pvf,
@@ -628,6 +734,10 @@ async fn handle_heads_up(
 					}
 				},
 			}
+		} else if try_import_artifact(cache_path, artifacts, prepare_queue, importer, &active_pvf)
+			.await?
+		{
+			// The artifact was imported, there is nothing to prepare.
 		} else {
 			// It's not in the artifacts, so we need to enqueue a job to prepare it.
 			artifacts.insert_preparing(artifact_id.clone(), Vec::new());
@@ -643,15 +753,128 @@ async fn handle_heads_up(
 	Ok(())
 }
 
+/// Tries to import a precompiled artifact for the given PVF, if the import is enabled. On success,
+/// the artifact is registered as prepared and, if required, its recompilation is scheduled.
+///
+/// Returns `true` if the artifact was imported.
+async fn try_import_artifact(
+	cache_path: &Path,
+	artifacts: &mut Artifacts,
+	prepare_queue: &mut mpsc::Sender<prepare::ToQueue>,
+	importer: &mut Option<Importer>,
+	pvf: &PvfPrepData,
+) -> Result<bool, Fatal> {
+	let importer = match importer {
+		Some(importer) => importer,
+		None => return Ok(false),
+	};
+	let artifact_id = pvf.as_artifact_id();
+	if !importer.import(&artifact_id, cache_path).await {
+		return Ok(false)
+	}
+
+	artifacts.insert_prepared(artifact_id, SystemTime::now(), PrepareStats::default());
+	if importer.needs_recompile() {
+		send_prepare(
+			prepare_queue,
+			prepare::ToQueue::Enqueue { priority: Priority::Normal, pvf: pvf.clone() },
+		)
+		.await?;
+	}
+
+	Ok(true)
+}
+
+/// Handles the local recompilation of an imported artifact, which replaced the imported one.
+async fn handle_import_verified(
+	cache_path: &Path,
+	artifacts: &mut Artifacts,
+	sweeper_tx: &mut mpsc::Sender<PathBuf>,
+	artifact_id: ArtifactId,
+	compile_hash: [u8; 32],
+	result: PrepareResult,
+) -> Result<(), Fatal> {
+	let artifact_path = artifact_id.path(cache_path);
+	let state = match artifacts.artifact_state_mut(&artifact_id) {
+		Some(state @ ArtifactState::Prepared { .. }) => state,
+		_ => {
+			never!("only prepared artifacts are verified: {:?}", artifact_id);
+			return Ok(())
+		},
+	};
+
+	*state = match result {
+		Ok(prepare_stats) => {
+			match import::compile_hash(&artifact_path).await {
+				Ok(local_hash) if local_hash == compile_hash =>
+					gum::debug!(target: LOG_TARGET, ?artifact_id, "imported artifact verified"),
+				Ok(_) => gum::warn!(
+					target: LOG_TARGET,
+					?artifact_id,
+					"imported artifact differs from the local compilation, replaced it",
+				),
+				Err(err) => gum::warn!(
+					target: LOG_TARGET,
+					?artifact_id,
+					?err,
+					"failed to read the recompiled artifact",
+				),
+			}
+
+			ArtifactState::Prepared { last_time_needed: SystemTime::now(), prepare_stats }
+		},
+		Err(error) => {
+			// The PVF cannot be compiled locally, so the imported artifact can't be trusted.
+			gum::warn!(
+				target: LOG_TARGET,
+				?artifact_id,
+				"imported artifact failed to compile locally: {}",
+				error,
+			);
+			sweeper_tx.send(artifact_path).await.map_err(|_| Fatal)?;
+			ArtifactState::FailedToProcess {
+				last_time_failed: SystemTime::now(),
+				num_failures: 1,
+				error,
+			}
+		},
+	};
+
+	Ok(())
+}
+
 async fn handle_prepare_done(
 	cache_path: &Path,
 	artifacts: &mut Artifacts,
 	execute_queue: &mut mpsc::Sender<execute::ToQueue>,
+	sweeper_tx: &mut mpsc::Sender<PathBuf>,
 	awaiting_prepare: &mut AwaitingPrepare,
+	importer: &mut Option<Importer>,
 	from_queue: prepare::FromQueue,
 ) -> Result<(), Fatal> {
 	let prepare::FromQueue { artifact_id, result } = from_queue;
 
+	if let Some(compile_hash) =
+		importer.as_mut().and_then(|importer| importer.take_verification(&artifact_id))
+	{
+		match artifacts.artifact_state_mut(&artifact_id) {
+			Some(ArtifactState::Prepared { .. }) =>
+				return handle_import_verified(
+					cache_path,
+					artifacts,
+					sweeper_tx,
+					artifact_id,
+					compile_hash,
+					result,
+				)
+				.await,
+			// The imported artifact was pruned in the meantime, don't keep the recompiled one.
+			None => return sweeper_tx.send(artifact_id.path(cache_path)).await.map_err(|_| Fatal),
+			// The artifact was pruned and requested again, so the result is awaited as usual.
+			Some(_) => {},
+		}
+	}
+
 	// Make some sanity checks and extract the current state.
 	let state = match artifacts.artifact_state_mut(&artifact_id) {
 		None => {
@@ -696,7 +919,7 @@ async fn handle_prepare_done(
 	// It's finally time to dispatch all the execution requests that were waiting for this artifact
 	// to be prepared.
 	let pending_requests = awaiting_prepare.take(&artifact_id);
-	for PendingExecutionRequest { exec_timeout, params, executor_params, result_tx } in
+	for PendingExecutionRequest { exec_timeout, params, executor_params, priority, result_tx } in
 		pending_requests
 	{
 		if result_tx.is_canceled() {
@@ -719,6 +942,7 @@ async fn handle_prepare_done(
 					exec_timeout,
 					params,
 					executor_params,
+					priority,
 					result_tx,
 				},
 			},
@@ -829,15 +1053,20 @@ fn can_retry_prepare_after_failure(
 	num_failures: u32,
 	error: &PrepareError,
 ) -> bool {
+	// Retry if the retry cooldown has elapsed. IO errors may resolve themselves.
+	can_retry_prepare(num_failures, error) &&
+		SystemTime::now() >= last_time_failed + PREPARE_FAILURE_COOLDOWN
+}
+
+/// Check if a prepare job that failed with the given error may be retried at all.
+fn can_retry_prepare(num_failures: u32, error: &PrepareError) -> bool {
 	if error.is_deterministic() {
 		// This error is considered deterministic, so it will probably be reproducible. Don't retry.
 		return false
 	}
 
-	// Retry if the retry cooldown has elapsed and if we have already retried less than `NUM_PREPARE_RETRIES` times. IO
-	// errors may resolve themselves.
-	SystemTime::now() >= last_time_failed + PREPARE_FAILURE_COOLDOWN &&
-		num_failures <= NUM_PREPARE_RETRIES
+	// Retry if we have already retried less than `NUM_PREPARE_RETRIES` times.
+	num_failures <= NUM_PREPARE_RETRIES
 }
 
 /// A stream that yields a pulse continuously at a given interval.
@@ -854,7 +1083,7 @@ fn pulse_every(interval: std::time::Duration) -> impl futures::Stream<Item = ()>
 #[cfg(test)]
 pub(crate) mod tests {
 	use super::*;
-	use crate::{prepare::PrepareStats, InvalidCandidate, PrepareError};
+	use crate::{prepare::PrepareStats, InvalidCandidate, PrepareError, PrepareFailure};
 	use assert_matches::assert_matches;
 	use futures::future::BoxFuture;
 
@@ -938,6 +1167,7 @@ pub(crate) mod tests {
 				to_execute_queue_tx,
 				to_sweeper_tx,
 				awaiting_prepare: AwaitingPrepare::default(),
+				importer: None,
 			})
 			.boxed();
 
@@ -953,7 +1183,7 @@ pub(crate) mod tests {
 
 		fn host_handle(&mut self) -> ValidationHost {
 			let to_host_tx = self.to_host_tx.take().unwrap();
-			ValidationHost { to_host_tx }
+			ValidationHost { to_host_tx, count_host_calls: false }
 		}
 
 		async fn poll_and_recv_result<T>(&mut self, result_rx: oneshot::Receiver<T>) -> T
@@ -1246,7 +1476,7 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(2),
-				result: Err(PrepareError::TimedOut),
+				result: Err(PrepareError::TimedOut.into()),
 			})
 			.await
 			.unwrap();
@@ -1254,7 +1484,7 @@ pub(crate) mod tests {
 		for result_rx in precheck_receivers {
 			assert_matches!(
 				result_rx.now_or_never().unwrap().unwrap(),
-				Err(PrepareError::TimedOut)
+				Err(PrepareFailure { error: PrepareError::TimedOut, .. })
 			);
 		}
 	}
@@ -1292,12 +1522,15 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(1),
-				result: Err(PrepareError::TimedOut),
+				result: Err(PrepareError::TimedOut.into()),
 			})
 			.await
 			.unwrap();
 		test.poll_ensure_to_execute_queue_is_empty().await;
-		assert_matches!(result_rx.now_or_never().unwrap().unwrap(), Err(PrepareError::TimedOut));
+		assert_matches!(
+			result_rx.now_or_never().unwrap().unwrap(),
+			Err(PrepareFailure { error: PrepareError::TimedOut, .. })
+		);
 		assert_matches!(
 			result_rx_execute.now_or_never().unwrap().unwrap(),
 			Err(ValidationError::InternalError(_))
@@ -1344,10 +1577,10 @@ pub(crate) mod tests {
 		}
 	}
 
-	// Test that multiple prechecking requests do not trigger preparation retries if the first one
-	// failed.
+	// Test that a prechecking request triggers a preparation retry if the previous one failed due to
+	// a potentially non-reproducible error, without waiting for a cooldown.
 	#[tokio::test]
-	async fn test_precheck_prepare_no_retry() {
+	async fn test_precheck_prepare_retry() {
 		let mut test = Builder::default().build();
 		let mut host = test.host_handle();
 
@@ -1364,14 +1597,14 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(1),
-				result: Err(PrepareError::TimedOut),
+				result: Err(PrepareError::TimedOut.into()),
 			})
 			.await
 			.unwrap();
 
 		// The result should contain the error.
 		let result = test.poll_and_recv_result(result_rx).await;
-		assert_matches!(result, Err(PrepareError::TimedOut));
+		assert_matches!(result, Err(PrepareFailure { error: PrepareError::TimedOut, .. }));
 
 		// Submit another precheck request.
 		let (result_tx_2, result_rx_2) = oneshot::channel();
@@ -1379,15 +1612,22 @@ pub(crate) mod tests {
 			.await
 			.unwrap();
 
-		// Assert the prepare queue is empty.
-		test.poll_ensure_to_prepare_queue_is_empty().await;
+		// The preparation is retried right away.
+		assert_matches!(
+			test.poll_and_recv_to_prepare_queue().await,
+			prepare::ToQueue::Enqueue { priority: Priority::Normal, .. }
+		);
+		// This time, the PVF turns out to be invalid.
+		test.from_prepare_queue_tx
+			.send(prepare::FromQueue {
+				artifact_id: artifact_id(1),
+				result: Err(PrepareError::Prevalidation("reproducible error".into()).into()),
+			})
+			.await
+			.unwrap();
 
-		// The result should contain the original error.
 		let result = test.poll_and_recv_result(result_rx_2).await;
-		assert_matches!(result, Err(PrepareError::TimedOut));
-
-		// Pause for enough time to reset the cooldown for this failed prepare request.
-		futures_timer::Delay::new(PREPARE_FAILURE_COOLDOWN).await;
+		assert_matches!(result, Err(PrepareFailure { error: PrepareError::Prevalidation(_), .. }));
 
 		// Submit another precheck request.
 		let (result_tx_3, result_rx_3) = oneshot::channel();
@@ -1395,12 +1635,12 @@ pub(crate) mod tests {
 			.await
 			.unwrap();
 
-		// Assert the prepare queue is empty - we do not retry for precheck requests.
+		// Assert the prepare queue is empty - we do not retry deterministic errors.
 		test.poll_ensure_to_prepare_queue_is_empty().await;
 
-		// The result should still contain the original error.
+		// The result should still contain the deterministic error.
 		let result = test.poll_and_recv_result(result_rx_3).await;
-		assert_matches!(result, Err(PrepareError::TimedOut));
+		assert_matches!(result, Err(PrepareFailure { error: PrepareError::Prevalidation(_), .. }));
 	}
 
 	// Test that multiple execution requests trigger preparation retries if the first one failed due
@@ -1431,7 +1671,7 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(1),
-				result: Err(PrepareError::TimedOut),
+				result: Err(PrepareError::TimedOut.into()),
 			})
 			.await
 			.unwrap();
@@ -1533,7 +1773,7 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(1),
-				result: Err(PrepareError::Prevalidation("reproducible error".into())),
+				result: Err(PrepareError::Prevalidation("reproducible error".into()).into()),
 			})
 			.await
 			.unwrap();
@@ -1611,7 +1851,7 @@ pub(crate) mod tests {
 		test.from_prepare_queue_tx
 			.send(prepare::FromQueue {
 				artifact_id: artifact_id(1),
-				result: Err(PrepareError::TimedOut),
+				result: Err(PrepareError::TimedOut.into()),
 			})
 			.await
 			.unwrap();
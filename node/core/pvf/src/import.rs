@@ -0,0 +1,405 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Import of precompiled artifacts.
+//!
+//! Compiling all PVFs after a node upgrade may take minutes. To avoid that, a node may be pointed
+//! to a directory containing artifacts compiled elsewhere, e.g. by the `prepare-artifacts`
+//! subcommand as a part of the deployment, or synchronized from a trusted peer. Whenever the host
+//! needs an artifact it does not know yet, it looks into that directory first.
+//!
+//! Every artifact in the import directory is accompanied by an [`ArtifactManifest`] recording the
+//! version of the node which compiled it, the [`HostTarget`] it was compiled for and the hash of
+//! the compiled artifact. The manifest is signed by whoever compiled the artifact, e.g. a trusted
+//! peer or the deployment pipeline. An artifact is only imported if
+//!
+//! - its manifest is signed by one of the configured trusted signers,
+//! - it was compiled by the same node version,
+//! - it was compiled for a target this host can run, and
+//! - it matches the hash of its manifest.
+//!
+//! See [`ImportVerification`] for what happens next. Artifacts are executed as native code, so only
+//! keys of parties which are trusted to run code on this machine may be configured as signers.
+
+use crate::{artifacts::ArtifactId, worker_common::tmpfile_in, PvfPrepData, LOG_TARGET};
+use parity_scale_codec::{Decode, Encode};
+use sp_core::{blake2_256, ed25519, Pair as _};
+use std::{
+	collections::HashMap,
+	io,
+	path::{Path, PathBuf},
+};
+
+/// The extension of the manifest file stored next to every artifact in the import directory.
+const MANIFEST_EXTENSION: &str = "manifest";
+
+/// How imported artifacts are verified, in addition to the checks every imported artifact passes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportVerification {
+	/// Trust the artifact based on the signature of its manifest.
+	Signature,
+	/// Use the artifact right away, but also compile the PVF in the background. The imported
+	/// artifact is then replaced with the locally compiled one, and a mismatch is reported.
+	Recompile,
+}
+
+/// Configuration of the artifact import.
+#[derive(Clone, Debug)]
+pub struct ArtifactImport {
+	/// The directory to import artifacts from.
+	pub dir: PathBuf,
+	/// The keys which are trusted to sign artifact manifests. Artifacts signed by other keys are
+	/// never imported.
+	pub trusted_signers: Vec<ed25519::Public>,
+	/// How imported artifacts are verified.
+	pub verification: ImportVerification,
+}
+
+/// The target an artifact was compiled for.
+///
+/// Wasmtime compiles for the host it runs on and uses all the CPU features it detects, so an
+/// artifact can only be run on hosts of the same architecture supporting at least the same
+/// features.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct HostTarget {
+	/// The CPU architecture, e.g. `x86_64`.
+	pub arch: String,
+	/// The CPU features relevant to code generation which were detected on the host.
+	pub cpu_features: Vec<String>,
+}
+
+impl HostTarget {
+	/// The target of this host.
+	pub fn native() -> Self {
+		Self { arch: std::env::consts::ARCH.into(), cpu_features: native_cpu_features() }
+	}
+
+	/// Whether artifacts compiled for `target` can be run on this target.
+	fn can_run(&self, target: &HostTarget) -> bool {
+		self.arch == target.arch &&
+			target.cpu_features.iter().all(|feature| self.cpu_features.contains(feature))
+	}
+}
+
+/// The CPU features the code generation of wasmtime depends on.
+#[cfg(target_arch = "x86_64")]
+fn native_cpu_features() -> Vec<String> {
+	let mut features = Vec::new();
+	macro_rules! detect {
+		($($feature:tt),*) => {
+			$(if std::is_x86_feature_detected!($feature) {
+				features.push($feature.to_string());
+			})*
+		};
+	}
+	detect!(
+		"sse3", "ssse3", "sse4.1", "sse4.2", "popcnt", "avx", "avx2", "bmi1", "bmi2", "lzcnt",
+		"fma"
+	);
+	features
+}
+
+/// The CPU features the code generation of wasmtime depends on.
+#[cfg(target_arch = "aarch64")]
+fn native_cpu_features() -> Vec<String> {
+	let mut features = Vec::new();
+	macro_rules! detect {
+		($($feature:tt),*) => {
+			$(if std::arch::is_aarch64_feature_detected!($feature) {
+				features.push($feature.to_string());
+			})*
+		};
+	}
+	detect!("lse", "paca", "bti");
+	features
+}
+
+/// The CPU features the code generation of wasmtime depends on.
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn native_cpu_features() -> Vec<String> {
+	Vec::new()
+}
+
+/// Metadata of a compiled artifact, stored next to it in the import directory.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode)]
+pub struct ArtifactManifest {
+	/// The version of the node which compiled the artifact.
+	pub node_version: String,
+	/// The target the artifact was compiled for.
+	pub target: HostTarget,
+	/// The blake2-256 hash of the compiled artifact.
+	pub compile_hash: [u8; 32],
+	/// The key which signed the manifest.
+	pub signer: ed25519::Public,
+	/// The signature of [`Self::signing_payload`].
+	pub signature: ed25519::Signature,
+}
+
+impl ArtifactManifest {
+	fn new(artifact_id: &ArtifactId, artifact: &[u8], signer: &ed25519::Pair) -> Self {
+		let node_version: String = env!("SUBSTRATE_CLI_IMPL_VERSION").into();
+		let target = HostTarget::native();
+		let compile_hash = blake2_256(artifact);
+		let signature =
+			signer.sign(&Self::signing_payload(artifact_id, &node_version, &target, &compile_hash));
+
+		Self { node_version, target, compile_hash, signer: signer.public(), signature }
+	}
+
+	/// The payload signed by the signer of the manifest. It includes the artifact id, so a signed
+	/// manifest cannot be reused for another PVF.
+	fn signing_payload(
+		artifact_id: &ArtifactId,
+		node_version: &str,
+		target: &HostTarget,
+		compile_hash: &[u8; 32],
+	) -> Vec<u8> {
+		(
+			b"pvf-artifact-manifest",
+			artifact_id.code_hash,
+			artifact_id.executor_params_hash,
+			node_version,
+			target,
+			compile_hash,
+		)
+			.encode()
+	}
+
+	fn verify_signature(&self, artifact_id: &ArtifactId) -> bool {
+		let payload = Self::signing_payload(
+			artifact_id,
+			&self.node_version,
+			&self.target,
+			&self.compile_hash,
+		);
+		ed25519::Pair::verify(&self.signature, payload, &self.signer)
+	}
+}
+
+/// An error importing an artifact.
+#[derive(Debug)]
+enum ImportError {
+	Io(io::Error),
+	InvalidManifest,
+	UntrustedSigner(ed25519::Public),
+	InvalidSignature,
+	VersionMismatch(String),
+	UnsupportedTarget(HostTarget),
+	HashMismatch,
+}
+
+fn manifest_path(artifact_path: &Path) -> PathBuf {
+	artifact_path.with_extension(MANIFEST_EXTENSION)
+}
+
+/// Writes the compiled `artifact` of the given PVF and its manifest signed by `signer` into `dir`,
+/// so it can be imported by nodes of the same version trusting the signer. Returns the path of the
+/// artifact.
+pub fn export_artifact(
+	dir: &Path,
+	pvf: &PvfPrepData,
+	artifact: &[u8],
+	signer: &ed25519::Pair,
+) -> io::Result<PathBuf> {
+	let artifact_id = pvf.as_artifact_id();
+	let path = artifact_id.path(dir);
+	std::fs::write(&path, artifact)?;
+	std::fs::write(
+		manifest_path(&path),
+		ArtifactManifest::new(&artifact_id, artifact, signer).encode(),
+	)?;
+	Ok(path)
+}
+
+/// Imports artifacts into the cache and keeps track of the ones being verified by recompilation.
+pub struct Importer {
+	config: ArtifactImport,
+	/// The target of this host.
+	host_target: HostTarget,
+	/// Compile hashes of imported artifacts which are being recompiled.
+	verifying: HashMap<ArtifactId, [u8; 32]>,
+}
+
+impl Importer {
+	pub fn new(config: ArtifactImport) -> Self {
+		Self { config, host_target: HostTarget::native(), verifying: HashMap::new() }
+	}
+
+	/// Tries to import the artifact with the given id into the cache. Returns `true` if it was
+	/// imported.
+	pub async fn import(&mut self, artifact_id: &ArtifactId, cache_path: &Path) -> bool {
+		match self.try_import(artifact_id, cache_path).await {
+			Ok(None) => false,
+			Ok(Some(compile_hash)) => {
+				gum::debug!(target: LOG_TARGET, ?artifact_id, "imported a precompiled artifact");
+				if self.config.verification == ImportVerification::Recompile {
+					self.verifying.insert(artifact_id.clone(), compile_hash);
+				}
+				true
+			},
+			Err(err) => {
+				gum::warn!(
+					target: LOG_TARGET,
+					?artifact_id,
+					?err,
+					"failed to import a precompiled artifact, preparing it locally",
+				);
+				false
+			},
+		}
+	}
+
+	/// Whether imported artifacts need to be recompiled.
+	pub fn needs_recompile(&self) -> bool {
+		self.config.verification == ImportVerification::Recompile
+	}
+
+	/// Stops tracking the recompilation of the given artifact, returning the compile hash of the
+	/// imported artifact if it was being verified.
+	pub fn take_verification(&mut self, artifact_id: &ArtifactId) -> Option<[u8; 32]> {
+		self.verifying.remove(artifact_id)
+	}
+
+	async fn try_import(
+		&self,
+		artifact_id: &ArtifactId,
+		cache_path: &Path,
+	) -> Result<Option<[u8; 32]>, ImportError> {
+		let source = artifact_id.path(&self.config.dir);
+		let manifest = match tokio::fs::read(manifest_path(&source)).await {
+			Ok(manifest) => manifest,
+			Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+			Err(err) => return Err(ImportError::Io(err)),
+		};
+		let manifest = ArtifactManifest::decode(&mut &manifest[..])
+			.map_err(|_| ImportError::InvalidManifest)?;
+		if !self.config.trusted_signers.contains(&manifest.signer) {
+			return Err(ImportError::UntrustedSigner(manifest.signer))
+		}
+		if !manifest.verify_signature(artifact_id) {
+			return Err(ImportError::InvalidSignature)
+		}
+		if manifest.node_version != env!("SUBSTRATE_CLI_IMPL_VERSION") {
+			return Err(ImportError::VersionMismatch(manifest.node_version))
+		}
+		if !self.host_target.can_run(&manifest.target) {
+			return Err(ImportError::UnsupportedTarget(manifest.target))
+		}
+
+		let artifact = tokio::fs::read(&source).await.map_err(ImportError::Io)?;
+		if blake2_256(&artifact) != manifest.compile_hash {
+			return Err(ImportError::HashMismatch)
+		}
+
+		// Move the artifact into the cache atomically, the same way prepared artifacts are.
+		let tmp = tmpfile_in("import-", cache_path).await.map_err(ImportError::Io)?;
+		tokio::fs::write(&tmp, &artifact).await.map_err(ImportError::Io)?;
+		tokio::fs::rename(&tmp, artifact_id.path(cache_path))
+			.await
+			.map_err(ImportError::Io)?;
+
+		Ok(Some(manifest.compile_hash))
+	}
+}
+
+/// Computes the compile hash of the artifact at the given path.
+pub async fn compile_hash(artifact_path: &Path) -> io::Result<[u8; 32]> {
+	tokio::fs::read(artifact_path).await.map(|artifact| blake2_256(&artifact))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn importer(dir: &Path, trusted_signers: Vec<ed25519::Public>) -> Importer {
+		Importer::new(ArtifactImport {
+			dir: dir.to_owned(),
+			trusted_signers,
+			verification: ImportVerification::Recompile,
+		})
+	}
+
+	#[tokio::test]
+	async fn exported_artifacts_are_imported() {
+		let export_dir = tempfile::tempdir().unwrap();
+		let cache_dir = tempfile::tempdir().unwrap();
+		let signer = ed25519::Pair::from_seed(&[1; 32]);
+		let pvf = PvfPrepData::from_discriminator(1);
+		let artifact_id = pvf.as_artifact_id();
+
+		export_artifact(export_dir.path(), &pvf, b"artifact", &signer).unwrap();
+
+		let mut importer = importer(export_dir.path(), vec![signer.public()]);
+		assert!(importer.import(&artifact_id, cache_dir.path()).await);
+		assert_eq!(std::fs::read(artifact_id.path(cache_dir.path())).unwrap(), b"artifact");
+		assert_eq!(importer.take_verification(&artifact_id), Some(blake2_256(b"artifact")));
+
+		// Unknown artifacts are not imported.
+		let unknown = PvfPrepData::from_discriminator(2).as_artifact_id();
+		assert!(!importer.import(&unknown, cache_dir.path()).await);
+	}
+
+	#[tokio::test]
+	async fn tampered_artifacts_are_rejected() {
+		let export_dir = tempfile::tempdir().unwrap();
+		let cache_dir = tempfile::tempdir().unwrap();
+		let signer = ed25519::Pair::from_seed(&[1; 32]);
+		let pvf = PvfPrepData::from_discriminator(1);
+		let artifact_id = pvf.as_artifact_id();
+
+		let path = export_artifact(export_dir.path(), &pvf, b"artifact", &signer).unwrap();
+		std::fs::write(path, b"tampered").unwrap();
+
+		let mut importer = importer(export_dir.path(), vec![signer.public()]);
+		assert!(!importer.import(&artifact_id, cache_dir.path()).await);
+		assert!(!artifact_id.path(cache_dir.path()).exists());
+	}
+
+	#[tokio::test]
+	async fn artifacts_of_untrusted_signers_are_rejected() {
+		let export_dir = tempfile::tempdir().unwrap();
+		let cache_dir = tempfile::tempdir().unwrap();
+		let signer = ed25519::Pair::from_seed(&[1; 32]);
+		let untrusted = ed25519::Pair::from_seed(&[2; 32]);
+		let pvf = PvfPrepData::from_discriminator(1);
+		let artifact_id = pvf.as_artifact_id();
+
+		// A manifest written by an untrusted party, recomputing the hash of its own artifact.
+		let path = export_artifact(export_dir.path(), &pvf, b"artifact", &untrusted).unwrap();
+		let mut importer = importer(export_dir.path(), vec![signer.public()]);
+		assert!(!importer.import(&artifact_id, cache_dir.path()).await);
+
+		// A trusted signature on the manifest of another PVF.
+		let other = PvfPrepData::from_discriminator(2);
+		let other_path = export_artifact(export_dir.path(), &other, b"artifact", &signer).unwrap();
+		std::fs::rename(manifest_path(&other_path), manifest_path(&path)).unwrap();
+		assert!(!importer.import(&artifact_id, cache_dir.path()).await);
+		assert!(!artifact_id.path(cache_dir.path()).exists());
+	}
+
+	#[test]
+	fn host_target_is_checked() {
+		let host = HostTarget { arch: "x86_64".into(), cpu_features: vec!["sse3".into()] };
+
+		assert!(host.can_run(&HostTarget { arch: "x86_64".into(), cpu_features: vec![] }));
+		assert!(host.can_run(&host));
+		assert!(!host.can_run(&HostTarget {
+			arch: "x86_64".into(),
+			cpu_features: vec!["sse3".into(), "avx2".into()],
+		}));
+		assert!(!host.can_run(&HostTarget { arch: "aarch64".into(), cpu_features: vec![] }));
+	}
+}
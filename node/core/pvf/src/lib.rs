@@ -92,17 +92,20 @@ mod artifacts;
 mod error;
 mod execute;
 mod host;
+mod import;
 mod metrics;
 mod prepare;
 mod priority;
 mod pvf;
 mod worker_common;
+mod worker_pools;
 
 pub use artifacts::CompiledArtifact;
 pub use error::{
-	InternalValidationError, InvalidCandidate, PrepareError, PrepareResult, ValidationError,
+	InternalValidationError, InvalidCandidate, PrepareError, PrepareFailure, PrepareResult,
+	ValidationError,
 };
-pub use execute::{ExecuteHandshake, ExecuteResponse};
+pub use execute::{ExecuteHandshake, ExecuteResponse, ExecuteStats};
 #[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
 pub use prepare::MemoryAllocationStats;
 pub use prepare::{MemoryStats, PrepareStats};
@@ -110,8 +113,10 @@ pub use priority::Priority;
 pub use pvf::PvfPrepData;
 
 pub use host::{start, Config, ValidationHost};
+pub use import::{export_artifact, ArtifactImport, ImportVerification};
 pub use metrics::Metrics;
 pub use worker_common::{framed_recv, framed_send, JOB_TIMEOUT_WALL_CLOCK_FACTOR};
+pub use worker_pools::{PoolSizes, WorkerPools};
 
 const LOG_TARGET: &str = "parachain::pvf";
 
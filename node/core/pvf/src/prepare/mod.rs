@@ -52,6 +52,22 @@ pub struct MemoryStats {
 	pub max_rss: Option<i64>,
 }
 
+impl MemoryStats {
+	/// Returns the peak memory used by the preparation job in KiB, if known. `ru_maxrss` is
+	/// preferred over the resident memory observed by the memory tracker.
+	pub fn peak_kb(&self) -> Option<u64> {
+		#[cfg(target_os = "linux")]
+		if let Some(max_rss) = self.max_rss {
+			return Some(max_rss as u64)
+		}
+		#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
+		if let Some(tracker_stats) = &self.memory_tracker_stats {
+			return Some(tracker_stats.resident / 1024)
+		}
+		None
+	}
+}
+
 /// Statistics of collected memory metrics.
 #[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
 #[derive(Clone, Debug, Default, Encode, Decode)]
@@ -81,7 +81,7 @@ pub enum FromPool {
 		/// Indicates whether the worker process was killed.
 		rip: bool,
 		/// [`Ok`] indicates that compiled artifact is successfully stored on disk.
-		/// Otherwise, an [error](PrepareError) is supplied.
+		/// Otherwise, a [failure](crate::error::PrepareFailure) is supplied.
 		result: PrepareResult,
 	},
 
@@ -303,7 +303,7 @@ fn handle_mux(
 					spawned,
 					worker,
 					idle,
-					Err(PrepareError::CreateTmpFileErr(err)),
+					Err(PrepareError::CreateTmpFileErr(err).into()),
 				),
 				// Return `Concluded`, but do not kill the worker since the error was on the host side.
 				Outcome::RenameTmpFileErr { worker: idle, result: _, err } =>
@@ -312,7 +312,7 @@ fn handle_mux(
 						spawned,
 						worker,
 						idle,
-						Err(PrepareError::RenameTmpFileErr(err)),
+						Err(PrepareError::RenameTmpFileErr(err).into()),
 					),
 				Outcome::Unreachable => {
 					if attempt_retire(metrics, spawned, worker) {
@@ -328,7 +328,7 @@ fn handle_mux(
 							FromPool::Concluded {
 								worker,
 								rip: true,
-								result: Err(PrepareError::IoErr(err)),
+								result: Err(PrepareError::IoErr(err).into()),
 							},
 						)?;
 					}
@@ -342,7 +342,7 @@ fn handle_mux(
 							FromPool::Concluded {
 								worker,
 								rip: true,
-								result: Err(PrepareError::TimedOut),
+								result: Err(PrepareError::TimedOut.into()),
 							},
 						)?;
 					}
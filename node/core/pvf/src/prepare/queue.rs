@@ -46,7 +46,7 @@ pub struct FromQueue {
 	/// Identifier of an artifact.
 	pub(crate) artifact_id: ArtifactId,
 	/// Outcome of the PVF processing. [`Ok`] indicates that compiled artifact
-	/// is successfully stored on disk. Otherwise, an [error](crate::error::PrepareError)
+	/// is successfully stored on disk. Otherwise, a [failure](crate::error::PrepareFailure)
 	/// is supplied.
 	pub(crate) result: PrepareResult,
 }
@@ -757,7 +757,7 @@ mod tests {
 		test.send_from_pool(pool::FromPool::Concluded {
 			worker: w1,
 			rip: true,
-			result: Err(PrepareError::IoErr("test".into())),
+			result: Err(PrepareError::IoErr("test".into()).into()),
 		});
 		test.poll_ensure_to_pool_is_empty().await;
 	}
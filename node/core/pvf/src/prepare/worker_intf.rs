@@ -17,7 +17,7 @@
 //! Host interface to the prepare worker.
 
 use crate::{
-	error::{PrepareError, PrepareResult},
+	error::{PrepareError, PrepareFailure, PrepareResult},
 	metrics::Metrics,
 	prepare::PrepareStats,
 	pvf::PvfPrepData,
@@ -171,7 +171,7 @@ async fn handle_response(
 	let PrepareStats { cpu_time_elapsed, memory_stats } = match result.clone() {
 		Ok(result) => result,
 		// Timed out on the child. This should already be logged by the child.
-		Err(PrepareError::TimedOut) => return Outcome::TimedOut,
+		Err(PrepareFailure { error: PrepareError::TimedOut, .. }) => return Outcome::TimedOut,
 		Err(_) => return Outcome::Concluded { worker, result },
 	};
 
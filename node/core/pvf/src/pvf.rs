@@ -43,6 +43,9 @@ pub struct PvfPrepData {
 	executor_params: Arc<ExecutorParams>,
 	/// Preparation timeout
 	prep_timeout: Duration,
+	/// Whether the artifact is instrumented to count host calls. A node-local option, which is not
+	/// a part of the executor params.
+	count_host_calls: bool,
 }
 
 impl PvfPrepData {
@@ -55,12 +58,18 @@ impl PvfPrepData {
 		let code = Arc::new(code);
 		let code_hash = blake2_256(&code).into();
 		let executor_params = Arc::new(executor_params);
-		Self { code, code_hash, executor_params, prep_timeout }
+		Self { code, code_hash, executor_params, prep_timeout, count_host_calls: false }
+	}
+
+	/// Sets whether the artifact is instrumented to count host calls.
+	pub(crate) fn with_host_call_counting(mut self, count_host_calls: bool) -> Self {
+		self.count_host_calls = count_host_calls;
+		self
 	}
 
 	/// Returns artifact ID that corresponds to the PVF with given executor params
 	pub(crate) fn as_artifact_id(&self) -> ArtifactId {
-		ArtifactId::new(self.code_hash, self.executor_params.hash())
+		ArtifactId::new(self.code_hash, self.executor_params.hash(), self.count_host_calls)
 	}
 
 	/// Returns validation code hash for the PVF
@@ -83,6 +92,11 @@ impl PvfPrepData {
 		self.prep_timeout
 	}
 
+	/// Returns whether the artifact is instrumented to count host calls.
+	pub fn count_host_calls(&self) -> bool {
+		self.count_host_calls
+	}
+
 	/// Creates a structure for tests
 	#[cfg(test)]
 	pub(crate) fn from_discriminator_and_timeout(num: u32, timeout: Duration) -> Self {
@@ -100,8 +114,8 @@ impl fmt::Debug for PvfPrepData {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		write!(
 			f,
-			"Pvf {{ code, code_hash: {:?}, executor_params: {:?}, prep_timeout: {:?} }}",
-			self.code_hash, self.executor_params, self.prep_timeout
+			"Pvf {{ code, code_hash: {:?}, executor_params: {:?}, prep_timeout: {:?}, count_host_calls: {:?} }}",
+			self.code_hash, self.executor_params, self.prep_timeout, self.count_host_calls
 		)
 	}
 }
@@ -109,7 +123,8 @@ impl fmt::Debug for PvfPrepData {
 impl PartialEq for PvfPrepData {
 	fn eq(&self, other: &Self) -> bool {
 		self.code_hash == other.code_hash &&
-			self.executor_params.hash() == other.executor_params.hash()
+			self.executor_params.hash() == other.executor_params.hash() &&
+			self.count_host_calls == other.count_host_calls
 	}
 }
 
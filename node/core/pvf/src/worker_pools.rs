@@ -0,0 +1,243 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Sizing of the prepare and execute worker pools.
+//!
+//! By default the pools are kept small, which is enough for a node that validates a handful of
+//! candidates per block. Machines with plenty of cores can either set the sizes explicitly or let
+//! them be derived from the available cores and memory. In the latter case the sizes never drop
+//! below the defaults and never exceed [`AUTO_MAX_PREPARE_WORKERS`] and
+//! [`AUTO_MAX_EXECUTE_WORKERS`] respectively.
+
+use std::time::Duration;
+
+/// The number of prepare workers used when the pools are not configured.
+pub const DEFAULT_PREPARE_WORKERS_MAX_NUM: usize = 1;
+
+/// The number of execute workers used when the pools are not configured.
+pub const DEFAULT_EXECUTE_WORKERS_MAX_NUM: usize = 2;
+
+/// The time allotted for a worker to spawn when it is not configured.
+pub const DEFAULT_WORKER_SPAWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// The upper bound of prepare workers in the auto mode.
+pub const AUTO_MAX_PREPARE_WORKERS: usize = 4;
+
+/// The upper bound of execute workers in the auto mode.
+pub const AUTO_MAX_EXECUTE_WORKERS: usize = 8;
+
+/// The number of cores left to the rest of the node in the auto mode.
+const AUTO_RESERVED_CORES: usize = 2;
+
+/// The memory budgeted for a single prepare worker in the auto mode. Compilation is by far the
+/// most memory hungry job of the workers.
+const AUTO_PREPARE_WORKER_MEMORY: u64 = 2 * 1024 * 1024 * 1024;
+
+/// The memory budgeted for a single execute worker in the auto mode.
+const AUTO_EXECUTE_WORKER_MEMORY: u64 = 1024 * 1024 * 1024;
+
+/// Configuration of the worker pools, as given by the node operator.
+///
+/// Explicitly set sizes always take precedence over the ones derived in the auto mode.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WorkerPools {
+	/// Derive the pool sizes from the available cores and memory.
+	pub auto: bool,
+	/// The maximum number of prepare workers for jobs with the priority below critical.
+	pub prepare_workers_soft_max_num: Option<usize>,
+	/// The maximum number of prepare workers.
+	pub prepare_workers_hard_max_num: Option<usize>,
+	/// The maximum number of execute workers.
+	pub execute_workers_max_num: Option<usize>,
+	/// The time allotted for a worker to spawn and report to the host.
+	pub worker_spawn_timeout: Option<Duration>,
+}
+
+/// The pool sizes resolved from [`WorkerPools`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PoolSizes {
+	/// See [`WorkerPools::prepare_workers_soft_max_num`].
+	pub prepare_workers_soft_max_num: usize,
+	/// See [`WorkerPools::prepare_workers_hard_max_num`].
+	pub prepare_workers_hard_max_num: usize,
+	/// See [`WorkerPools::execute_workers_max_num`].
+	pub execute_workers_max_num: usize,
+}
+
+impl Default for PoolSizes {
+	fn default() -> Self {
+		Self {
+			prepare_workers_soft_max_num: DEFAULT_PREPARE_WORKERS_MAX_NUM,
+			prepare_workers_hard_max_num: DEFAULT_PREPARE_WORKERS_MAX_NUM,
+			execute_workers_max_num: DEFAULT_EXECUTE_WORKERS_MAX_NUM,
+		}
+	}
+}
+
+impl PoolSizes {
+	/// The maximum number of worker processes which may be alive at the same time.
+	pub fn max_workers(&self) -> usize {
+		self.prepare_workers_hard_max_num + self.execute_workers_max_num
+	}
+
+	/// Derive the pool sizes from the number of `cores` and the amount of `memory` in bytes, if
+	/// it is known.
+	pub fn auto(cores: usize, memory: Option<u64>) -> Self {
+		let cores = cores.saturating_sub(AUTO_RESERVED_CORES);
+		let mut prepare = cores / 4;
+		let mut execute = cores / 3;
+
+		// Half of the memory is left to the rest of the node and the page cache, the other half
+		// is split between the two pools.
+		if let Some(memory) = memory {
+			let budget = memory / 4;
+			prepare = prepare.min((budget / AUTO_PREPARE_WORKER_MEMORY) as usize);
+			execute = execute.min((budget / AUTO_EXECUTE_WORKER_MEMORY) as usize);
+		}
+
+		let prepare = prepare.clamp(DEFAULT_PREPARE_WORKERS_MAX_NUM, AUTO_MAX_PREPARE_WORKERS);
+		let execute = execute.clamp(DEFAULT_EXECUTE_WORKERS_MAX_NUM, AUTO_MAX_EXECUTE_WORKERS);
+
+		Self {
+			// Keep one prepare worker for critical jobs if there is more than one.
+			prepare_workers_soft_max_num: (prepare - 1).max(1),
+			prepare_workers_hard_max_num: prepare,
+			execute_workers_max_num: execute,
+		}
+	}
+}
+
+impl WorkerPools {
+	/// Resolve the pool sizes, inspecting the machine if the auto mode is enabled.
+	pub fn resolve(&self) -> PoolSizes {
+		let base = if self.auto {
+			let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+			let memory = total_memory();
+			let sizes = PoolSizes::auto(cores, memory);
+
+			gum::info!(
+				target: crate::LOG_TARGET,
+				%cores,
+				?memory,
+				?sizes,
+				max_workers = %sizes.max_workers(),
+				"Sized the PVF worker pools automatically",
+			);
+
+			sizes
+		} else {
+			PoolSizes::default()
+		};
+
+		self.apply_to(base)
+	}
+
+	fn apply_to(&self, base: PoolSizes) -> PoolSizes {
+		let prepare_workers_hard_max_num = self
+			.prepare_workers_hard_max_num
+			.unwrap_or(base.prepare_workers_hard_max_num)
+			.max(1);
+		let prepare_workers_soft_max_num = self
+			.prepare_workers_soft_max_num
+			.unwrap_or(base.prepare_workers_soft_max_num)
+			.clamp(1, prepare_workers_hard_max_num);
+		let execute_workers_max_num =
+			self.execute_workers_max_num.unwrap_or(base.execute_workers_max_num).max(1);
+
+		PoolSizes {
+			prepare_workers_soft_max_num,
+			prepare_workers_hard_max_num,
+			execute_workers_max_num,
+		}
+	}
+}
+
+/// The total amount of physical memory in bytes, if it can be determined.
+fn total_memory() -> Option<u64> {
+	// SAFETY: `sysconf` has no preconditions and only reads system configuration.
+	let (pages, page_size) =
+		unsafe { (libc::sysconf(libc::_SC_PHYS_PAGES), libc::sysconf(libc::_SC_PAGESIZE)) };
+
+	if pages <= 0 || page_size <= 0 {
+		return None
+	}
+
+	(pages as u64).checked_mul(page_size as u64)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const GIB: u64 = 1024 * 1024 * 1024;
+
+	#[test]
+	fn auto_never_goes_below_defaults() {
+		assert_eq!(PoolSizes::auto(1, None), PoolSizes::default());
+		assert_eq!(PoolSizes::auto(4, Some(GIB)), PoolSizes::default());
+	}
+
+	#[test]
+	fn auto_scales_with_cores_up_to_the_bound() {
+		assert_eq!(
+			PoolSizes::auto(16, Some(64 * GIB)),
+			PoolSizes {
+				prepare_workers_soft_max_num: 2,
+				prepare_workers_hard_max_num: 3,
+				execute_workers_max_num: 4,
+			},
+		);
+		assert_eq!(
+			PoolSizes::auto(128, Some(512 * GIB)),
+			PoolSizes {
+				prepare_workers_soft_max_num: AUTO_MAX_PREPARE_WORKERS - 1,
+				prepare_workers_hard_max_num: AUTO_MAX_PREPARE_WORKERS,
+				execute_workers_max_num: AUTO_MAX_EXECUTE_WORKERS,
+			},
+		);
+	}
+
+	#[test]
+	fn auto_is_limited_by_memory() {
+		assert_eq!(
+			PoolSizes::auto(32, Some(8 * GIB)),
+			PoolSizes {
+				prepare_workers_soft_max_num: 1,
+				prepare_workers_hard_max_num: 1,
+				execute_workers_max_num: 2,
+			},
+		);
+	}
+
+	#[test]
+	fn explicit_sizes_take_precedence() {
+		let pools = WorkerPools {
+			prepare_workers_soft_max_num: Some(5),
+			prepare_workers_hard_max_num: Some(3),
+			execute_workers_max_num: Some(6),
+			..Default::default()
+		};
+
+		assert_eq!(
+			pools.apply_to(PoolSizes::auto(32, None)),
+			PoolSizes {
+				prepare_workers_soft_max_num: 3,
+				prepare_workers_hard_max_num: 3,
+				execute_workers_max_num: 6,
+			},
+		);
+	}
+}
@@ -14,26 +14,30 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+#[cfg(target_os = "linux")]
+use crate::memory_stats::max_rss_stat::{extract_max_rss_stat, get_max_rss_thread, reset_max_rss};
 use crate::{
 	common::{
 		bytes_to_path, cpu_time_monitor_loop, stringify_panic_payload,
 		thread::{self, WaitOutcome},
 		worker_event_loop,
 	},
-	executor_intf::{Executor, EXECUTE_THREAD_STACK_SIZE},
+	executor_intf::{
+		take_host_calls, take_instruction_budget_exhausted, Executor, EXECUTE_THREAD_STACK_SIZE,
+	},
 	LOG_TARGET,
 };
 use cpu_time::ProcessTime;
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_core_pvf::{
 	framed_recv, framed_send, ExecuteHandshake as Handshake, ExecuteResponse as Response,
-	InternalValidationError,
+	ExecuteStats, InternalValidationError,
 };
 use polkadot_parachain::primitives::ValidationResult;
 use std::{
 	path::{Path, PathBuf},
 	sync::{mpsc::channel, Arc},
-	time::Duration,
+	time::{Duration, Instant},
 };
 use tokio::{io, net::UnixStream};
 
@@ -84,9 +88,10 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 		let worker_pid = std::process::id();
 
 		let handshake = recv_handshake(&mut stream).await?;
-		let executor = Executor::new(handshake.executor_params).map_err(|e| {
-			io::Error::new(io::ErrorKind::Other, format!("cannot create executor: {}", e))
-		})?;
+		let executor = Executor::new(handshake.executor_params, handshake.count_host_calls)
+			.map_err(|e| {
+				io::Error::new(io::ErrorKind::Other, format!("cannot create executor: {}", e))
+			})?;
 
 		loop {
 			let (artifact_path, params, execution_timeout) = recv_request(&mut stream).await?;
@@ -97,10 +102,27 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 				artifact_path.display(),
 			);
 
+			// The worker is reused, reset the peak resident set size of the previous job. If that
+			// fails, no `ru_maxrss` is reported for this job at all.
+			#[cfg(target_os = "linux")]
+			let max_rss_reset = reset_max_rss()
+				.map_err(|err| {
+					gum::warn!(
+						target: LOG_TARGET,
+						%worker_pid,
+						"error resetting `ru_maxrss` in worker: {}",
+						err
+					);
+				})
+				.is_ok();
+			#[cfg(not(target_os = "linux"))]
+			let max_rss_reset = false;
+
 			// Conditional variable to notify us when a thread is done.
 			let condvar = thread::get_condvar();
 
 			let cpu_time_start = ProcessTime::now();
+			let wall_time_start = Instant::now();
 
 			// Spawn a new thread that runs the CPU time monitor.
 			let (cpu_time_monitor_tx, cpu_time_monitor_rx) = channel::<()>();
@@ -116,7 +138,15 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 			let execute_thread = thread::spawn_worker_thread_with_stack_size(
 				"execute thread",
 				move || {
-					validate_using_artifact(&artifact_path, &params, executor_2, cpu_time_start)
+					validate_using_artifact(
+						&artifact_path,
+						&params,
+						executor_2,
+						cpu_time_start,
+						wall_time_start,
+						max_rss_reset,
+						worker_pid,
+					)
 				},
 				Arc::clone(&condvar),
 				WaitOutcome::Finished,
@@ -171,6 +201,9 @@ fn validate_using_artifact(
 	params: &[u8],
 	executor: Executor,
 	cpu_time_start: ProcessTime,
+	wall_time_start: Instant,
+	#[allow(unused_variables)] max_rss_reset: bool,
+	#[allow(unused_variables)] worker_pid: u32,
 ) -> Response {
 	// Check here if the file exists, because the error from Substrate is not match-able.
 	// TODO: Re-evaluate after <https://github.com/paritytech/substrate/issues/13860>.
@@ -185,6 +218,8 @@ fn validate_using_artifact(
 		//         [`executor_intf::prepare`].
 		executor.execute(artifact_path.as_ref(), params)
 	} {
+		Err(_) if take_instruction_budget_exhausted() =>
+			return Response::InstructionBudgetExhausted,
 		Err(err) => return Response::format_invalid("execute", &err),
 		Ok(d) => d,
 	};
@@ -197,7 +232,17 @@ fn validate_using_artifact(
 
 	// Include the decoding in the measured time, to prevent any potential attacks exploiting some
 	// bug in decoding.
-	let duration = cpu_time_start.elapsed();
+	let stats = ExecuteStats {
+		cpu_time_elapsed: cpu_time_start.elapsed(),
+		wall_time_elapsed: wall_time_start.elapsed(),
+		#[cfg(target_os = "linux")]
+		max_rss: max_rss_reset
+			.then(|| extract_max_rss_stat(get_max_rss_thread(), worker_pid))
+			.flatten(),
+		#[cfg(not(target_os = "linux"))]
+		max_rss: None,
+		host_calls: executor.counts_host_calls().then(take_host_calls),
+	};
 
-	Response::Ok { result_descriptor, duration }
+	Response::Ok { result_descriptor, stats }
 }
@@ -18,16 +18,31 @@
 
 use polkadot_primitives::{ExecutorParam, ExecutorParams};
 use sc_executor_common::{
+	error::WasmError,
 	runtime_blob::RuntimeBlob,
 	wasm_runtime::{HeapAllocStrategy, InvokeMethod, WasmModule as _},
 };
 use sc_executor_wasmtime::{Config, DeterministicStackLimit, Semantics};
 use sp_core::storage::{ChildInfo, TrackedStorageKey};
 use sp_externalities::MultiRemovalResults;
+use sp_wasm_interface::{
+	Function, FunctionContext, HostFunctionRegistry, Signature, Value, ValueType,
+};
 use std::{
 	any::{Any, TypeId},
+	cell::Cell,
 	path::Path,
 };
+use wasm_instrument::{
+	gas_metering::{self, host_function, mutable_global, ConstantCostRules, MemoryGrowCost, Rules},
+	parity_wasm::{
+		self,
+		elements::{
+			BlockType, External, FuncBody, FunctionType, GlobalEntry, ImportCountType, InitExpr,
+			Instruction, Instructions, Internal, Module, Type, ValueType as WasmValueType,
+		},
+	},
+};
 
 // Wasmtime powers the Substrate Executor. It compiles the wasm bytecode into native code.
 // That native code does not create any stacks and just reuses the stack of the thread that
@@ -83,6 +98,43 @@ const EXTRA_HEAP_PAGES: u32 = 2048;
 /// The number of bytes devoted for the stack during wasm execution of a PVF.
 const NATIVE_STACK_MAX: u32 = 256 * 1024 * 1024;
 
+// Instruction metering
+//
+// If `ExecutorParam::PvfExecInstructionBudget` is set, the PVF is instrumented at preparation time
+// so that every executed wasm instruction is charged against a global holding the remaining
+// budget. The instrumented code traps as soon as the budget is exhausted, which makes the outcome
+// of an execution independent of the speed of the machine.
+//
+// The instrumentation expects the embedder to set the global before every call, but the executor
+// offers no way to do that. Instead, the budget is baked into the initializer of the global, which
+// is fine since the instance is recreated for every call. It also means the budget is a part of the
+// artifact, which is keyed by the executor parameters anyway.
+//
+// To tell an exhausted budget apart from any other trap, the metering code calls the host function
+// below right before trapping. The metering function is generated here rather than taken from
+// `wasm-instrument`, so that the call does not depend on how that function is laid out.
+/// The name of the exported global holding the remaining instruction budget.
+const INSTRUCTION_BUDGET_GLOBAL: &str = "polkadot_pvf_instruction_budget";
+/// The name of the host function called once the instruction budget is exhausted.
+const OUT_OF_INSTRUCTIONS_FUNCTION: &str = "polkadot_pvf_out_of_instructions";
+
+// If the node enables host call counting, the PVF is instrumented at preparation time to call the
+// host function below right before every call into a host function.
+//
+// Host call counting is a node-local option rather than an executor parameter, so it must never
+// change the outcome of preparation or execution. The counting is applied after the instruction
+// metering, so it is not charged, and the host function takes no arguments, so the deterministic
+// stack usage of the PVF does not change either.
+/// The name of the host function noting a host call.
+const HOST_CALLS_FUNCTION: &str = "polkadot_pvf_note_host_call";
+
+thread_local! {
+	/// The number of host calls made by the PVFs executed on this thread.
+	static HOST_CALLS: Cell<u64> = Cell::new(0);
+	/// Whether a PVF executed on this thread exhausted its instruction budget.
+	static INSTRUCTION_BUDGET_EXHAUSTED: Cell<bool> = Cell::new(false);
+}
+
 // VALUES OF THE DEFAULT CONFIGURATION SHOULD NEVER BE CHANGED
 // They are used as base values for the execution environment parametrization.
 // To overwrite them, add new ones to `EXECUTOR_PARAMS` in the `session_info` pallet and perform
@@ -136,24 +188,258 @@ const DEFAULT_CONFIG: Config = Config {
 /// Runs the prevalidation on the given code. Returns a [`RuntimeBlob`] if it succeeds.
 pub fn prevalidate(code: &[u8]) -> Result<RuntimeBlob, sc_executor_common::error::WasmError> {
 	let blob = RuntimeBlob::new(code)?;
-	// It's assumed this function will take care of any prevalidation logic
-	// that needs to be done.
-	//
-	// Do nothing for now.
+	// The host functions of the instrumentation are only linked for the artifacts instrumented by
+	// the node. A PVF importing them could pretend to have exhausted its budget or tamper with the
+	// node-local host call counts, so reject it regardless of the executor parameters.
+	let module: Module = parity_wasm::deserialize_buffer(code)
+		.map_err(|e| WasmError::Other(format!("cannot deserialize module: {}", e)))?;
+	if let Some(import) = module.import_section().and_then(|section| {
+		section.entries().iter().find(|import| {
+			[OUT_OF_INSTRUCTIONS_FUNCTION, HOST_CALLS_FUNCTION].contains(&import.field())
+		})
+	}) {
+		return Err(WasmError::Other(format!("cannot import {}", import.field())))
+	}
 	Ok(blob)
 }
 
 /// Runs preparation on the given runtime blob. If successful, it returns a serialized compiled
 /// artifact which can then be used to pass into `Executor::execute` after writing it to the disk.
+///
+/// If `count_host_calls` is set, the artifact is instrumented to count the calls it makes into host
+/// functions, as far as the PVF allows for it.
 pub fn prepare(
 	blob: RuntimeBlob,
 	executor_params: &ExecutorParams,
+	count_host_calls: bool,
 ) -> Result<Vec<u8>, sc_executor_common::error::WasmError> {
 	let semantics = params_to_wasmtime_semantics(executor_params)
 		.map_err(|e| sc_executor_common::error::WasmError::Other(e))?;
+	let blob = match executor_params.pvf_exec_instruction_budget() {
+		Some(budget) => inject_instruction_metering(blob, budget)?,
+		None => blob,
+	};
+	let blob = if count_host_calls {
+		// Failing to instrument must not fail the preparation, fall back to the original blob.
+		inject_host_call_counting(&blob).unwrap_or(blob)
+	} else {
+		blob
+	};
 	sc_executor_wasmtime::prepare_runtime_artifact(blob, &semantics)
 }
 
+/// Instruments the given runtime blob to trap once it executed more than `budget` instructions.
+fn inject_instruction_metering(blob: RuntimeBlob, budget: u64) -> Result<RuntimeBlob, WasmError> {
+	let module: Module = parity_wasm::deserialize_buffer(&blob.serialize())
+		.map_err(|e| WasmError::Other(format!("cannot deserialize module: {}", e)))?;
+
+	// Import the host function reporting an exhausted budget. Nothing is charged, so this only
+	// adds the import and shifts the function indices accordingly.
+	let module = gas_metering::inject(
+		module,
+		host_function::Injector::new("env", OUT_OF_INSTRUCTIONS_FUNCTION),
+		&NoCostRules,
+	)
+	.map_err(|_| WasmError::Other("cannot import the out of instructions function".into()))?;
+	let out_of_instructions = module.import_count(ImportCountType::Function) as u32 - 1;
+
+	let mut module = gas_metering::inject(
+		module,
+		mutable_global::Injector::new(INSTRUCTION_BUDGET_GLOBAL),
+		&ConstantCostRules::default(),
+	)
+	.map_err(|_| WasmError::Other("cannot inject instruction metering".into()))?;
+
+	let global_index = module
+		.export_section()
+		.and_then(|section| {
+			section.entries().iter().find_map(|export| match export.internal() {
+				Internal::Global(index) if export.field() == INSTRUCTION_BUDGET_GLOBAL =>
+					Some(*index),
+				_ => None,
+			})
+		})
+		.ok_or_else(|| WasmError::Other("instruction budget global is missing".into()))?;
+
+	// Replace the body of the injected metering function with one reporting the exhausted budget
+	// before trapping.
+	let metering_function = metering_function_index(&module)?;
+	let metering_body = metering_function
+		.checked_sub(module.import_count(ImportCountType::Function) as u32)
+		.and_then(|index| module.code_section_mut()?.bodies_mut().get_mut(index as usize))
+		.ok_or_else(|| WasmError::Other("instruction metering function is missing".into()))?;
+	*metering_body =
+		FuncBody::new(Vec::new(), metering_function_code(global_index, out_of_instructions));
+
+	let global = global_index
+		.checked_sub(module.import_count(ImportCountType::Global) as u32)
+		.and_then(|index| module.global_section_mut()?.entries_mut().get_mut(index as usize))
+		.ok_or_else(|| WasmError::Other("instruction budget global is missing".into()))?;
+	// The metering code stores `u64::MAX` into the global when the budget is exhausted, so never
+	// start with it.
+	let budget = budget.min(u64::MAX - 1) as i64;
+	*global = GlobalEntry::new(
+		*global.global_type(),
+		InitExpr::new(vec![Instruction::I64Const(budget), Instruction::End]),
+	);
+
+	let code = parity_wasm::serialize(module)
+		.map_err(|e| WasmError::Other(format!("cannot serialize module: {}", e)))?;
+	RuntimeBlob::new(&code)
+}
+
+/// Returns the index of the function injected by the instruction metering.
+///
+/// The metering function is appended to the module and takes the amount to charge, which is checked
+/// here so that the wrong function is never replaced.
+fn metering_function_index(module: &Module) -> Result<u32, WasmError> {
+	let malformed = || WasmError::Other("instruction metering function is malformed".into());
+	let functions = module.function_section().map_or(0, |section| section.entries().len());
+	let type_ref = module
+		.function_section()
+		.and_then(|section| section.entries().last())
+		.ok_or_else(malformed)?
+		.type_ref();
+	match module.type_section().and_then(|section| section.types().get(type_ref as usize)) {
+		Some(Type::Function(signature))
+			if signature.params() == [WasmValueType::I64] && signature.results().is_empty() =>
+			Ok((module.import_count(ImportCountType::Function) + functions - 1) as u32),
+		_ => Err(malformed()),
+	}
+}
+
+/// Returns the code of the metering function, which takes the amount to charge and traps once the
+/// budget held by the given global is exhausted.
+///
+/// This is what `wasm-instrument` generates, except that [`OUT_OF_INSTRUCTIONS_FUNCTION`] is called
+/// right before trapping.
+fn metering_function_code(global: u32, out_of_instructions: u32) -> Instructions {
+	Instructions::new(vec![
+		Instruction::GetGlobal(global),
+		Instruction::GetLocal(0),
+		Instruction::I64GeU,
+		Instruction::If(BlockType::NoResult),
+		Instruction::GetGlobal(global),
+		Instruction::GetLocal(0),
+		Instruction::I64Sub,
+		Instruction::SetGlobal(global),
+		Instruction::Else,
+		Instruction::I64Const(0),
+		Instruction::Call(out_of_instructions),
+		// The exhausted budget is marked with `u64::MAX`.
+		Instruction::I64Const(-1),
+		Instruction::SetGlobal(global),
+		Instruction::Unreachable,
+		Instruction::End,
+		Instruction::End,
+	])
+}
+
+/// Charges nothing at all.
+struct NoCostRules;
+
+impl Rules for NoCostRules {
+	fn instruction_cost(&self, _: &Instruction) -> Option<u32> {
+		Some(0)
+	}
+
+	fn memory_grow_cost(&self) -> MemoryGrowCost {
+		MemoryGrowCost::Free
+	}
+
+	fn call_per_local_cost(&self) -> u32 {
+		0
+	}
+}
+
+/// Instruments the given runtime blob to note every call it makes into a host function.
+///
+/// Must be applied after the instruction metering, the calls made by the metering are not noted.
+fn inject_host_call_counting(blob: &RuntimeBlob) -> Result<RuntimeBlob, WasmError> {
+	let module: Module = parity_wasm::deserialize_buffer(&blob.clone().serialize())
+		.map_err(|e| WasmError::Other(format!("cannot deserialize module: {}", e)))?;
+	let imported_functions = module.import_count(ImportCountType::Function) as u32;
+	if imported_functions == 0 {
+		return Ok(blob.clone())
+	}
+	let out_of_instructions = module.import_section().and_then(|section| {
+		section
+			.entries()
+			.iter()
+			.filter(|import| matches!(import.external(), External::Function(_)))
+			.position(|import| import.field() == OUT_OF_INSTRUCTIONS_FUNCTION)
+			.map(|index| index as u32)
+	});
+
+	// Only adds the import and shifts the function indices, see `inject_instruction_metering`.
+	let mut module = gas_metering::inject(
+		module,
+		host_function::Injector::new("env", HOST_CALLS_FUNCTION),
+		&NoCostRules,
+	)
+	.map_err(|_| WasmError::Other("cannot import the host call counting function".into()))?;
+	let note_host_call = module.import_count(ImportCountType::Function) as u32 - 1;
+
+	// The function is imported with the signature of a metering function. Make it take no arguments
+	// instead, so that calling it does not use the stack.
+	let missing = || WasmError::Other("host call counting function is missing".into());
+	let types = module.type_section_mut().ok_or_else(missing)?.types_mut();
+	types.push(Type::Function(FunctionType::new(Vec::new(), Vec::new())));
+	let signature = types.len() as u32 - 1;
+	*module
+		.import_section_mut()
+		.and_then(|section| {
+			section
+				.entries_mut()
+				.iter_mut()
+				.find(|import| import.field() == HOST_CALLS_FUNCTION)
+		})
+		.ok_or_else(missing)?
+		.external_mut() = External::Function(signature);
+
+	let is_host_call = |instruction: &Instruction| match instruction {
+		Instruction::Call(index) =>
+			*index < imported_functions && Some(*index) != out_of_instructions,
+		_ => false,
+	};
+	let bodies = module.code_section_mut().map(|section| section.bodies_mut());
+	for body in bodies.into_iter().flatten() {
+		let code = body.code_mut().elements_mut();
+		let mut instrumented = Vec::with_capacity(code.len());
+		for instruction in code.drain(..) {
+			if is_host_call(&instruction) {
+				instrumented.push(Instruction::Call(note_host_call));
+			}
+			instrumented.push(instruction);
+		}
+		*code = instrumented;
+	}
+
+	let code = parity_wasm::serialize(module)
+		.map_err(|e| WasmError::Other(format!("cannot serialize module: {}", e)))?;
+	RuntimeBlob::new(&code)
+}
+
+/// Returns the number of host calls made by the PVFs executed on the current thread since the last
+/// call to this function.
+pub fn take_host_calls() -> u64 {
+	HOST_CALLS.with(|host_calls| host_calls.replace(0))
+}
+
+fn note_host_call() {
+	HOST_CALLS.with(|host_calls| host_calls.set(host_calls.get().saturating_add(1)));
+}
+
+/// Returns whether a PVF executed on the current thread exhausted its instruction budget since the
+/// last call to this function.
+pub fn take_instruction_budget_exhausted() -> bool {
+	INSTRUCTION_BUDGET_EXHAUSTED.with(|exhausted| exhausted.replace(false))
+}
+
+fn note_instruction_budget_exhausted() {
+	INSTRUCTION_BUDGET_EXHAUSTED.with(|exhausted| exhausted.set(true));
+}
+
 fn params_to_wasmtime_semantics(par: &ExecutorParams) -> Result<Semantics, String> {
 	let mut sem = DEFAULT_CONFIG.semantics.clone();
 	let mut stack_limit = if let Some(stack_limit) = sem.deterministic_stack_limit.clone() {
@@ -172,6 +458,7 @@ fn params_to_wasmtime_semantics(par: &ExecutorParams) -> Result<Semantics, Strin
 			ExecutorParam::WasmExtBulkMemory => sem.wasm_bulk_memory = true,
 			ExecutorParam::PrecheckingMaxMemory(_) => (), // TODO: Not implemented yet
 			ExecutorParam::PvfPrepTimeout(_, _) | ExecutorParam::PvfExecTimeout(_, _) => (), // Not used here
+			ExecutorParam::PvfExecInstructionBudget(_) => (), // Applied by instrumentation in `prepare`
 		}
 	}
 	sem.deterministic_stack_limit = Some(stack_limit);
@@ -181,14 +468,25 @@ fn params_to_wasmtime_semantics(par: &ExecutorParams) -> Result<Semantics, Strin
 #[derive(Clone)]
 pub struct Executor {
 	config: Config,
+	meters_instructions: bool,
+	count_host_calls: bool,
 }
 
 impl Executor {
-	pub fn new(params: ExecutorParams) -> Result<Self, String> {
+	pub fn new(params: ExecutorParams, count_host_calls: bool) -> Result<Self, String> {
 		let mut config = DEFAULT_CONFIG.clone();
 		config.semantics = params_to_wasmtime_semantics(&params)?;
 
-		Ok(Self { config })
+		Ok(Self {
+			config,
+			meters_instructions: params.pvf_exec_instruction_budget().is_some(),
+			count_host_calls,
+		})
+	}
+
+	/// Whether the artifacts executed by this executor were instrumented to count host calls.
+	pub fn counts_host_calls(&self) -> bool {
+		self.count_host_calls
 	}
 
 	/// Executes the given PVF in the form of a compiled artifact and returns the result of execution
@@ -215,8 +513,47 @@ impl Executor {
 
 		let mut ext = ValidationExternalities(extensions);
 
-		match sc_executor::with_externalities_safe(&mut ext, || {
-			let runtime = sc_executor_wasmtime::create_runtime_from_artifact::<HostFunctions>(
+		// Do not attribute the host calls or the exhausted budget of a previous execution to this
+		// one.
+		let _ = take_host_calls();
+		let _ = take_instruction_budget_exhausted();
+
+		// The host functions of the instrumentation are only linked if the artifact was
+		// instrumented accordingly.
+		match (self.meters_instructions, self.count_host_calls) {
+			(false, false) =>
+				self.execute_with::<HostFunctions>(&mut ext, compiled_artifact_path, params),
+			(true, false) => self.execute_with::<(HostFunctions, OutOfInstructions)>(
+				&mut ext,
+				compiled_artifact_path,
+				params,
+			),
+			(false, true) => self.execute_with::<(HostFunctions, HostCallCounter)>(
+				&mut ext,
+				compiled_artifact_path,
+				params,
+			),
+			(true, true) => self
+				.execute_with::<(HostFunctions, OutOfInstructions, HostCallCounter)>(
+					&mut ext,
+					compiled_artifact_path,
+					params,
+				),
+		}
+		.map_err(|err| format!("execute error: {:?}", err))
+	}
+
+	/// # Safety
+	///
+	/// See [`Executor::execute`].
+	unsafe fn execute_with<H: sp_wasm_interface::HostFunctions>(
+		&self,
+		ext: &mut ValidationExternalities,
+		compiled_artifact_path: &Path,
+		params: &[u8],
+	) -> Result<Vec<u8>, sc_executor_common::error::Error> {
+		match sc_executor::with_externalities_safe(ext, || {
+			let runtime = sc_executor_wasmtime::create_runtime_from_artifact::<H>(
 				compiled_artifact_path,
 				self.config.clone(),
 			)?;
@@ -225,7 +562,6 @@ impl Executor {
 			Ok(Ok(ok)) => Ok(ok),
 			Ok(Err(err)) | Err(err) => Err(err),
 		}
-		.map_err(|err| format!("execute error: {:?}", err))
 	}
 }
 
@@ -238,6 +574,79 @@ type HostFunctions = (
 	sp_io::trie::HostFunctions,
 );
 
+/// The host function the instrumented PVFs call before every host call, see [`HOST_CALLS_FUNCTION`].
+struct HostCallCounter;
+
+impl Function for HostCallCounter {
+	fn name(&self) -> &str {
+		HOST_CALLS_FUNCTION
+	}
+
+	fn signature(&self) -> Signature {
+		Signature::new(&[] as &[ValueType], None)
+	}
+
+	fn execute(
+		&self,
+		_: &mut dyn FunctionContext,
+		_: &mut dyn Iterator<Item = Value>,
+	) -> sp_wasm_interface::Result<Option<Value>> {
+		note_host_call();
+		Ok(None)
+	}
+}
+
+impl sp_wasm_interface::HostFunctions for HostCallCounter {
+	fn host_functions() -> Vec<&'static dyn Function> {
+		vec![&HostCallCounter]
+	}
+
+	fn register_static<T: HostFunctionRegistry>(registry: &mut T) -> Result<(), T::Error> {
+		registry.register_static(
+			HOST_CALLS_FUNCTION,
+			|_: sp_wasm_interface::wasmtime::Caller<T::State>| note_host_call(),
+		)
+	}
+}
+
+/// The host function the instrumented PVFs call once they exhausted their instruction budget, see
+/// [`OUT_OF_INSTRUCTIONS_FUNCTION`].
+struct OutOfInstructions;
+
+impl Function for OutOfInstructions {
+	fn name(&self) -> &str {
+		OUT_OF_INSTRUCTIONS_FUNCTION
+	}
+
+	fn signature(&self) -> Signature {
+		Signature::new(&[ValueType::I64][..], None)
+	}
+
+	fn execute(
+		&self,
+		_: &mut dyn FunctionContext,
+		_: &mut dyn Iterator<Item = Value>,
+	) -> sp_wasm_interface::Result<Option<Value>> {
+		note_instruction_budget_exhausted();
+		Ok(None)
+	}
+}
+
+impl sp_wasm_interface::HostFunctions for OutOfInstructions {
+	fn host_functions() -> Vec<&'static dyn Function> {
+		vec![&OutOfInstructions]
+	}
+
+	fn register_static<T: HostFunctionRegistry>(registry: &mut T) -> Result<(), T::Error> {
+		registry.register_static(
+			OUT_OF_INSTRUCTIONS_FUNCTION,
+			|_: sp_wasm_interface::wasmtime::Caller<T::State>, _: i64| {
+				note_instruction_budget_exhausted()
+			},
+		)
+	}
+}
+
 /// The validation externalities that will panic on any storage related access.
 struct ValidationExternalities(sp_externalities::Extensions);
 
@@ -406,3 +815,144 @@ impl sp_core::traits::ReadRuntimeVersion for ReadRuntimeVersion {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use parity_scale_codec::Encode;
+	use polkadot_parachain::primitives::{BlockData, HeadData, ValidationParams};
+	use wasm_instrument::parity_wasm::builder;
+
+	fn adder_code() -> Vec<u8> {
+		sp_maybe_compressed_blob::decompress(adder::wasm_binary_unwrap(), 10 * 1024 * 1024)
+			.unwrap()
+			.into_owned()
+	}
+
+	fn deserialize(blob: RuntimeBlob) -> Module {
+		parity_wasm::deserialize_buffer(&blob.serialize()).unwrap()
+	}
+
+	fn module_importing(field: &str) -> Vec<u8> {
+		let module = builder::module()
+			.function()
+			.signature()
+			.build()
+			.body()
+			.build()
+			.build()
+			.import()
+			.module("env")
+			.field(field)
+			.external()
+			.func(0)
+			.build()
+			.build();
+		parity_wasm::serialize(module).unwrap()
+	}
+
+	#[test]
+	fn prevalidation_rejects_reserved_imports() {
+		assert!(prevalidate(&module_importing("ext_allocator_malloc_version_1")).is_ok());
+		assert!(prevalidate(&module_importing(OUT_OF_INSTRUCTIONS_FUNCTION)).is_err());
+		assert!(prevalidate(&module_importing(HOST_CALLS_FUNCTION)).is_err());
+	}
+
+	// Fails if `wasm-instrument` changes how the metering function is injected.
+	#[test]
+	fn instruction_metering_replaces_the_injected_metering_function() {
+		let code = adder_code();
+		let original: Module = parity_wasm::deserialize_buffer(&code).unwrap();
+		let module =
+			deserialize(inject_instruction_metering(prevalidate(&code).unwrap(), 1_000).unwrap());
+
+		let imports = module.import_count(ImportCountType::Function) as u32;
+		let out_of_instructions = imports - 1;
+		assert_eq!(imports, original.import_count(ImportCountType::Function) as u32 + 1);
+		assert_eq!(
+			module.import_section().unwrap().entries().last().unwrap().field(),
+			OUT_OF_INSTRUCTIONS_FUNCTION,
+		);
+
+		// The metering function is the only function added.
+		let functions = module.function_section().unwrap().entries().len();
+		assert_eq!(functions, original.function_section().unwrap().entries().len() + 1);
+		let metering_function = metering_function_index(&module).unwrap();
+		assert_eq!(metering_function, imports + functions as u32 - 1);
+
+		let global = module
+			.export_section()
+			.unwrap()
+			.entries()
+			.iter()
+			.find_map(|export| match export.internal() {
+				Internal::Global(index) if export.field() == INSTRUCTION_BUDGET_GLOBAL =>
+					Some(*index),
+				_ => None,
+			})
+			.unwrap();
+		let bodies = module.code_section().unwrap().bodies();
+		let (metering_body, pvf_bodies) = bodies.split_last().unwrap();
+		assert_eq!(
+			metering_body.code().elements(),
+			metering_function_code(global, out_of_instructions).elements(),
+		);
+
+		// The PVF charges itself by calling the metering function, and nothing else does.
+		let calls_to = |index| {
+			pvf_bodies
+				.iter()
+				.flat_map(|body| body.code().elements())
+				.filter(|instruction| **instruction == Instruction::Call(index))
+				.count()
+		};
+		assert!(calls_to(metering_function) > 0);
+		assert_eq!(calls_to(out_of_instructions), 0);
+	}
+
+	/// Prepares and executes a block of the adder PVF with the given instruction budget.
+	fn execute_adder(budget: u64, count_host_calls: bool) -> Result<Vec<u8>, String> {
+		let executor_params =
+			ExecutorParams::from(&[ExecutorParam::PvfExecInstructionBudget(budget)][..]);
+		let artifact =
+			prepare(prevalidate(&adder_code()).unwrap(), &executor_params, count_host_calls)
+				.unwrap();
+		let dir = tempfile::tempdir().unwrap();
+		let artifact_path = dir.path().join("artifact");
+		std::fs::write(&artifact_path, artifact).unwrap();
+
+		let parent_head =
+			adder::HeadData { number: 0, parent_hash: [0; 32], post_state: adder::hash_state(0) };
+		let params = ValidationParams {
+			parent_head: HeadData(parent_head.encode()),
+			block_data: BlockData(adder::BlockData { state: 0, add: 512 }.encode()),
+			relay_parent_number: 1,
+			relay_parent_storage_root: Default::default(),
+		};
+		let executor = Executor::new(executor_params, count_host_calls).unwrap();
+		// SAFETY: The artifact is obtained by calling `prepare` and is written into a temporary
+		//         directory in an unmodified state.
+		unsafe { executor.execute(&artifact_path, &params.encode()) }
+	}
+
+	#[test]
+	fn host_call_counting_does_not_change_the_verdict() {
+		// Find the smallest budget the block can be executed with.
+		let (mut exhausted, mut sufficient) = (0, u32::MAX as u64);
+		assert!(execute_adder(sufficient, false).is_ok());
+		while sufficient - exhausted > 1 {
+			let budget = (exhausted + sufficient) / 2;
+			match execute_adder(budget, false) {
+				Ok(_) => sufficient = budget,
+				Err(_) => exhausted = budget,
+			}
+		}
+
+		// The same budgets lead to the same verdicts with host call counting.
+		assert!(execute_adder(exhausted, true).is_err());
+		assert!(take_instruction_budget_exhausted());
+		assert!(execute_adder(sufficient, true).is_ok());
+		assert!(!take_instruction_budget_exhausted());
+		assert!(take_host_calls() > 0);
+	}
+}
@@ -150,9 +150,10 @@ pub mod memory_tracker {
 
 /// Module for dealing with the `ru_maxrss` (peak resident memory) stat from `getrusage`.
 ///
-/// NOTE: `getrusage` with the `RUSAGE_THREAD` parameter is only supported on Linux. `RUSAGE_SELF`
-/// works on MacOS, but we need to get the max rss only for the preparation thread. Gettng it for
-/// the current process would conflate the stats of previous jobs run by the process.
+/// NOTE: `getrusage` with the `RUSAGE_THREAD` parameter is only supported on Linux. Even then, the
+/// reported `ru_maxrss` is the peak of the whole process, which would conflate the stats of
+/// previous jobs run by the process. It is therefore reset with [`max_rss_stat::reset_max_rss`]
+/// before every job.
 #[cfg(target_os = "linux")]
 pub mod max_rss_stat {
 	use crate::LOG_TARGET;
@@ -179,6 +180,15 @@ pub mod max_rss_stat {
 		getrusage_thread().map(|rusage| i64::from(rusage.ru_maxrss))
 	}
 
+	/// Resets the `ru_maxrss` to the current resident set size.
+	///
+	/// Despite `RUSAGE_THREAD`, Linux reports the peak resident set size of the whole process. A
+	/// worker running several jobs has to reset it before each of them, so that a job is not
+	/// attributed the peak of a previous one.
+	pub fn reset_max_rss() -> io::Result<()> {
+		std::fs::write("/proc/self/clear_refs", "5")
+	}
+
 	/// Extracts the max_rss stat and logs any error.
 	pub fn extract_max_rss_stat(max_rss: io::Result<i64>, worker_pid: u32) -> Option<i64> {
 		max_rss
@@ -186,7 +196,7 @@ pub mod max_rss_stat {
 				gum::warn!(
 					target: LOG_TARGET,
 					%worker_pid,
-					"error getting `ru_maxrss` in preparation thread: {}",
+					"error getting `ru_maxrss` in worker thread: {}",
 					err
 				);
 				err
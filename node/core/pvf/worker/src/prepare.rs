@@ -29,8 +29,8 @@ use crate::{
 use cpu_time::ProcessTime;
 use parity_scale_codec::{Decode, Encode};
 use polkadot_node_core_pvf::{
-	framed_recv, framed_send, CompiledArtifact, MemoryStats, PrepareError, PrepareResult,
-	PrepareStats, PvfPrepData,
+	framed_recv, framed_send, CompiledArtifact, MemoryStats, PrepareError, PrepareFailure,
+	PrepareResult, PrepareStats, PvfPrepData,
 };
 use std::{
 	path::PathBuf,
@@ -129,11 +129,14 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 				move || {
 					let result = prepare_artifact(pvf, cpu_time_start);
 
-					// Get the `ru_maxrss` stat. If supported, call getrusage for the thread.
+					// Get the `ru_maxrss` stat, whether or not the preparation succeeded. If
+					// supported, call getrusage for the thread.
 					#[cfg(target_os = "linux")]
-					let result = result.map(|(artifact, elapsed)| (artifact, elapsed, get_max_rss_thread()));
+					let max_rss = extract_max_rss_stat(get_max_rss_thread(), worker_pid);
+					#[cfg(not(target_os = "linux"))]
+					let max_rss: Option<i64> = None;
 
-					result
+					(result, max_rss)
 				},
 				Arc::clone(&condvar),
 				WaitOutcome::Finished,
@@ -145,29 +148,29 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 				WaitOutcome::Finished => {
 					let _ = cpu_time_monitor_tx.send(());
 
-					match prepare_thread.join().unwrap_or_else(|err| {
-						Err(PrepareError::Panic(stringify_panic_payload(err)))
-					}) {
-						Err(err) => {
+					#[cfg_attr(not(target_os = "linux"), allow(unused_variables))]
+					let (result, max_rss) = prepare_thread.join().unwrap_or_else(|err| {
+						(Err(PrepareError::Panic(stringify_panic_payload(err))), None)
+					});
+
+					// Stop the memory stats worker and get its observed memory stats. They are
+					// reported for failed preparations as well.
+					#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
+					let memory_tracker_stats =
+						get_memory_tracker_loop_stats(memory_tracker_thread, worker_pid).await;
+					let memory_stats = MemoryStats {
+						#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
+						memory_tracker_stats,
+						#[cfg(target_os = "linux")]
+						max_rss,
+					};
+
+					match result {
+						Err(error) => {
 							// Serialized error will be written into the socket.
-							Err(err)
+							Err(PrepareFailure { error, memory_stats: Some(memory_stats) })
 						},
-						Ok(ok) => {
-							#[cfg(not(target_os = "linux"))]
-							let (artifact, cpu_time_elapsed) = ok;
-							#[cfg(target_os = "linux")]
-							let (artifact, cpu_time_elapsed, max_rss) = ok;
-
-							// Stop the memory stats worker and get its observed memory stats.
-							#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
-							let memory_tracker_stats = get_memory_tracker_loop_stats(memory_tracker_thread, worker_pid).await;
-							let memory_stats = MemoryStats {
-								#[cfg(any(target_os = "linux", feature = "jemalloc-allocator"))]
-								memory_tracker_stats,
-								#[cfg(target_os = "linux")]
-								max_rss: extract_max_rss_stat(max_rss, worker_pid),
-							};
-
+						Ok((artifact, cpu_time_elapsed)) => {
 							// Write the serialized artifact into a temp file.
 							//
 							// PVF host only keeps artifacts statuses in its memory, successfully
@@ -200,13 +203,14 @@ pub fn worker_entrypoint(socket_path: &str, node_version: Option<&str>) {
 								cpu_time_elapsed.as_millis(),
 								preparation_timeout.as_millis(),
 							);
-							Err(PrepareError::TimedOut)
+							Err(PrepareError::TimedOut.into())
 						},
 						Ok(None) => Err(PrepareError::IoErr(
 							"error communicating over closed channel".into(),
-						)),
+						)
+						.into()),
 						// Errors in this thread are independent of the PVF.
-						Err(err) => Err(PrepareError::IoErr(stringify_panic_payload(err))),
+						Err(err) => Err(PrepareError::IoErr(stringify_panic_payload(err)).into()),
 					}
 				},
 				WaitOutcome::Pending =>
@@ -227,7 +231,7 @@ fn prepare_artifact(
 		Ok(b) => b,
 	};
 
-	match prepare(blob, &pvf.executor_params()) {
+	match prepare(blob, &pvf.executor_params(), pvf.count_host_calls()) {
 		Ok(compiled_artifact) => Ok(CompiledArtifact::new(compiled_artifact)),
 		Err(err) => Err(PrepareError::Preparation(format!("{:?}", err))),
 	}
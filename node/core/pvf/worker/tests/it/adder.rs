@@ -51,6 +51,45 @@ async fn execute_good_block_on_parent() {
 	assert_eq!(new_head.post_state, hash_state(512));
 }
 
+#[tokio::test]
+async fn execute_reports_stats() {
+	let parent_head = HeadData { number: 0, parent_hash: [0; 32], post_state: hash_state(0) };
+
+	let block_data = BlockData { state: 0, add: 512 };
+
+	let host = TestHost::new();
+
+	let params = ValidationParams {
+		parent_head: GenericHeadData(parent_head.encode()),
+		block_data: GenericBlockData(block_data.encode()),
+		relay_parent_number: 1,
+		relay_parent_storage_root: Default::default(),
+	};
+
+	let (_, stats) = host
+		.validate_candidate_with_stats(
+			adder::wasm_binary_unwrap(),
+			params.clone(),
+			Default::default(),
+		)
+		.await
+		.unwrap();
+
+	// Host calls are only counted on request.
+	assert_eq!(stats.host_calls, None);
+
+	let host = TestHost::new_with_config(|cfg| cfg.count_host_calls = true);
+	let (_, stats) = host
+		.validate_candidate_with_stats(adder::wasm_binary_unwrap(), params, Default::default())
+		.await
+		.unwrap();
+
+	// The PVF at least allocates the memory for the result.
+	assert!(stats.host_calls.unwrap() > 0);
+	#[cfg(target_os = "linux")]
+	assert!(stats.max_rss.is_some());
+}
+
 #[tokio::test]
 async fn execute_good_chain_on_parent() {
 	let mut number = 0;
@@ -17,8 +17,8 @@
 use assert_matches::assert_matches;
 use parity_scale_codec::Encode as _;
 use polkadot_node_core_pvf::{
-	start, Config, InvalidCandidate, Metrics, PvfPrepData, ValidationError, ValidationHost,
-	JOB_TIMEOUT_WALL_CLOCK_FACTOR,
+	start, Config, ExecuteStats, InvalidCandidate, Metrics, PvfPrepData, ValidationError,
+	ValidationHost, JOB_TIMEOUT_WALL_CLOCK_FACTOR,
 };
 use polkadot_parachain::primitives::{BlockData, ValidationParams, ValidationResult};
 use polkadot_primitives::{ExecutorParam, ExecutorParams};
@@ -61,6 +61,17 @@ impl TestHost {
 		params: ValidationParams,
 		executor_params: ExecutorParams,
 	) -> Result<ValidationResult, ValidationError> {
+		self.validate_candidate_with_stats(code, params, executor_params)
+			.await
+			.map(|(result, _)| result)
+	}
+
+	async fn validate_candidate_with_stats(
+		&self,
+		code: &[u8],
+		params: ValidationParams,
+		executor_params: ExecutorParams,
+	) -> Result<(ValidationResult, ExecuteStats), ValidationError> {
 		let (result_tx, result_rx) = futures::channel::oneshot::channel();
 
 		let code = sp_maybe_compressed_blob::decompress(code, 16 * 1024 * 1024)
@@ -110,6 +121,29 @@ async fn terminates_on_timeout() {
 	assert!(duration < TEST_EXECUTION_TIMEOUT * JOB_TIMEOUT_WALL_CLOCK_FACTOR);
 }
 
+#[tokio::test]
+async fn terminates_on_exhausted_instruction_budget() {
+	let host = TestHost::new();
+
+	let result = host
+		.validate_candidate(
+			halt::wasm_binary_unwrap(),
+			ValidationParams {
+				block_data: BlockData(Vec::new()),
+				parent_head: Default::default(),
+				relay_parent_number: 1,
+				relay_parent_storage_root: Default::default(),
+			},
+			ExecutorParams::from(&[ExecutorParam::PvfExecInstructionBudget(1_000_000)][..]),
+		)
+		.await;
+
+	assert_matches!(
+		result,
+		Err(ValidationError::InvalidCandidate(InvalidCandidate::InstructionBudgetExhausted))
+	);
+}
+
 #[tokio::test]
 async fn ensure_parallel_execution() {
 	// Run some jobs that do not complete, thus timing out.
@@ -18,6 +18,7 @@
 
 use clap::Parser;
 use color_eyre::eyre;
+use std::time::Duration;
 
 pub(crate) mod interceptor;
 pub(crate) mod shared;
@@ -36,6 +37,12 @@ enum NemesisVariant {
 	BackGarbageCandidate(BackGarbageCandidateOptions),
 	/// Delayed disputing of ancestors that are perfectly fine.
 	DisputeAncestor(DisputeAncestorOptions),
+	/// Back candidates honestly, but withhold availability data and bitfields.
+	WithholdAvailability(WithholdAvailabilityOptions),
+	/// Distribute assignments, but withhold or delay approvals.
+	ApprovalNoShow(ApprovalNoShowOptions),
+	/// Equivocate on statements and bitfields, and spam invalid disputes.
+	SpamEquivocations(SpamEquivocationsOptions),
 
 	#[allow(missing_docs)]
 	#[command(name = "prepare-worker", hide = true)]
@@ -88,6 +95,29 @@ impl MalusCli {
 					finality_delay,
 				)?
 			},
+			NemesisVariant::WithholdAvailability(opts) => {
+				let WithholdAvailabilityOptions { percentage, cli } = opts;
+
+				polkadot_cli::run_node(cli, WithholdAvailability { percentage }, finality_delay)?
+			},
+			NemesisVariant::ApprovalNoShow(opts) => {
+				let ApprovalNoShowOptions { percentage, delay_ms, cli } = opts;
+
+				polkadot_cli::run_node(
+					cli,
+					ApprovalNoShow { percentage, delay: delay_ms.map(Duration::from_millis) },
+					finality_delay,
+				)?
+			},
+			NemesisVariant::SpamEquivocations(opts) => {
+				let SpamEquivocationsOptions { percentage, disputes_per_leaf, cli } = opts;
+
+				polkadot_cli::run_node(
+					cli,
+					SpamEquivocations { percentage, disputes_per_leaf },
+					finality_delay,
+				)?
+			},
 			NemesisVariant::PvfPrepareWorker(cmd) => {
 				#[cfg(target_os = "android")]
 				{
@@ -203,6 +233,67 @@ mod tests {
 		});
 	}
 
+	#[test]
+	fn percentage_works_withhold_availability() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"withhold-availability",
+			"--percentage",
+			"50",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::WithholdAvailability(run),
+			..
+		} => {
+			assert_eq!(run.percentage, 50);
+			assert!(run.cli.run.base.bob);
+		});
+	}
+
+	#[test]
+	fn delay_works_approval_no_show() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"approval-no-show",
+			"--percentage",
+			"50",
+			"--delay-ms",
+			"12000",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::ApprovalNoShow(run),
+			..
+		} => {
+			assert_eq!(run.percentage, 50);
+			assert_eq!(run.delay_ms, Some(12000));
+			assert!(run.cli.run.base.bob);
+		});
+	}
+
+	#[test]
+	fn disputes_per_leaf_works_spam_equivocations() {
+		let cli = MalusCli::try_parse_from(IntoIterator::into_iter([
+			"malus",
+			"spam-equivocations",
+			"--disputes-per-leaf",
+			"10",
+			"--bob",
+		]))
+		.unwrap();
+		assert_matches::assert_matches!(cli, MalusCli {
+			variant: NemesisVariant::SpamEquivocations(run),
+			..
+		} => {
+			assert_eq!(run.percentage, 100);
+			assert_eq!(run.disputes_per_leaf, 10);
+			assert!(run.cli.run.base.bob);
+		});
+	}
+
 	#[test]
 	#[should_panic]
 	fn validate_range_for_percentage() {
@@ -0,0 +1,189 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that triggers and distributes its approval assignments
+//! as usual, but withholds a configurable percentage of its approval votes.
+//! Withheld approvals are either never sent, which makes the node a no-show
+//! for the candidate, or distributed after a configurable delay.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, Handle, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+	Cli,
+};
+use polkadot_node_primitives::approval::IndirectSignedApprovalVote;
+use polkadot_node_subsystem::SpawnGlue;
+use sp_core::traits::SpawnNamed;
+
+use futures::{channel::mpsc, prelude::*};
+use futures_timer::Delay;
+use rand::distributions::{Bernoulli, Distribution};
+
+// Filter wrapping related types.
+use crate::{interceptor::*, shared::MALUS};
+
+use std::{sync::Arc, time::Duration};
+
+/// Intercepts the approvals issued by approval voting before they reach
+/// approval distribution.
+#[derive(Clone)]
+struct WithholdApprovals {
+	distribution: Bernoulli,
+	/// Where to send withheld approvals to, if they should be distributed late.
+	delayed: Option<mpsc::UnboundedSender<IndirectSignedApprovalVote>>,
+}
+
+impl<Sender> MessageInterceptor<Sender> for WithholdApprovals
+where
+	Sender: overseer::ApprovalVotingSenderTrait + Clone + Send + 'static,
+{
+	type Message = ApprovalVotingMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::ApprovalVotingOutgoingMessages,
+	) -> Option<overseer::ApprovalVotingOutgoingMessages> {
+		match msg {
+			overseer::ApprovalVotingOutgoingMessages::ApprovalDistributionMessage(
+				ApprovalDistributionMessage::DistributeApproval(vote),
+			) => {
+				if !self.distribution.sample(&mut rand::thread_rng()) {
+					return Some(ApprovalDistributionMessage::DistributeApproval(vote).into())
+				}
+
+				match self.delayed {
+					Some(ref delayed) => {
+						gum::info!(
+							target: MALUS,
+							block_hash = ?vote.block_hash,
+							candidate_index = vote.candidate_index,
+							"😈 Delaying our approval.",
+						);
+						let _ = delayed.unbounded_send(vote);
+					},
+					None => {
+						gum::info!(
+							target: MALUS,
+							block_hash = ?vote.block_hash,
+							candidate_index = vote.candidate_index,
+							"😈 Not sending our approval.",
+						);
+					},
+				}
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Hand withheld approvals over to approval distribution once `delay` has passed.
+async fn distribute_delayed_approvals(
+	handle: Handle,
+	delay: Duration,
+	approvals: mpsc::UnboundedReceiver<IndirectSignedApprovalVote>,
+) {
+	approvals
+		.for_each_concurrent(None, move |vote| {
+			let mut handle = handle.clone();
+			async move {
+				Delay::new(delay).await;
+				gum::info!(
+					target: MALUS,
+					block_hash = ?vote.block_hash,
+					candidate_index = vote.candidate_index,
+					"😈 Sending delayed approval.",
+				);
+				handle
+					.send_msg(ApprovalDistributionMessage::DistributeApproval(vote), MALUS)
+					.await;
+			}
+		})
+		.await
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct ApprovalNoShowOptions {
+	/// Determines the percentage of candidates our approval is withheld for. Assignments
+	/// are always distributed. Must be in the range [0..=100].
+	#[clap(short, long, ignore_case = true, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub percentage: u8,
+
+	/// Distribute withheld approvals after this many milliseconds instead of never.
+	#[clap(long)]
+	pub delay_ms: Option<u64>,
+
+	#[clap(flatten)]
+	pub cli: Cli,
+}
+
+/// Approval no-show implementation wrapper which implements `OverseerGen` glue.
+pub(crate) struct ApprovalNoShow {
+	/// The probability of withholding an approval.
+	pub percentage: u8,
+	/// The delay after which withheld approvals are sent, if any.
+	pub delay: Option<Duration>,
+}
+
+impl OverseerGen for ApprovalNoShow {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		gum::info!(
+			target: MALUS,
+			"😈 Started Malus node withholding {:?} percent of our approvals, delay: {:?}.",
+			&self.percentage,
+			&self.delay,
+		);
+
+		let distribution = Bernoulli::new(f64::from(self.percentage) / 100.0)
+			.expect("Invalid probability! Percentage must be in range [0..=100].");
+
+		let delayed = self.delay.map(|delay| {
+			let (tx, rx) = mpsc::unbounded();
+			args.spawner.spawn(
+				"malus-delayed-approvals",
+				Some("malus"),
+				Box::pin(distribute_delayed_approvals(Handle::new(connector.handle()), delay, rx)),
+			);
+			tx
+		});
+
+		let withhold_approvals = WithholdApprovals { distribution, delayed };
+
+		prepared_overseer_builder(args)?
+			.replace_approval_voting(move |av| InterceptedSubsystem::new(av, withhold_approvals))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
@@ -16,14 +16,20 @@
 
 //! Collection of behavior variants.
 
+mod approval_no_show;
 mod back_garbage_candidate;
 mod common;
 mod dispute_valid_candidates;
+mod spam_equivocations;
 mod suggest_garbage_candidate;
+mod withhold_availability;
 
 pub(crate) use self::{
+	approval_no_show::{ApprovalNoShow, ApprovalNoShowOptions},
 	back_garbage_candidate::{BackGarbageCandidateOptions, BackGarbageCandidates},
 	dispute_valid_candidates::{DisputeAncestorOptions, DisputeValidCandidates},
+	spam_equivocations::{SpamEquivocations, SpamEquivocationsOptions},
 	suggest_garbage_candidate::{SuggestGarbageCandidateOptions, SuggestGarbageCandidates},
+	withhold_availability::{WithholdAvailability, WithholdAvailabilityOptions},
 };
 pub(crate) use common::*;
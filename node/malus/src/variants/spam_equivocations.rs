@@ -0,0 +1,409 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that equivocates and spams:
+//!
+//! - For every candidate it seconds, it also seconds a conflicting candidate
+//!   at the same relay parent.
+//! - For every availability bitfield it signs, it also signs the inverted bitfield
+//!   and hands it to bitfield distribution. Bitfield distribution drops it unless
+//!   it supersedes the first one, i.e. unless the first one was empty, and even
+//!   then never relays it to peers that already received the first one. So no
+//!   peer ever sees both bitfields.
+//! - On every new leaf it sends a configurable number of disputes about candidates
+//!   that never existed, signing both the valid and the invalid vote itself.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, Handle, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+	Cli,
+};
+use polkadot_node_primitives::{
+	DisputeMessage, SignedDisputeStatement, SignedFullStatement, Statement,
+};
+use polkadot_node_subsystem::{errors::RuntimeApiError, OverseerSignal, SpawnGlue};
+use polkadot_node_subsystem_util::signing_key_and_index;
+use polkadot_primitives::{
+	AvailabilityBitfield, CandidateDescriptor, CandidateReceipt, Hash, SignedAvailabilityBitfield,
+	SigningContext,
+};
+use sp_core::traits::SpawnNamed;
+use sp_keystore::KeystorePtr;
+
+use futures::{
+	channel::{mpsc, oneshot},
+	prelude::*,
+};
+use rand::distributions::{Bernoulli, Distribution};
+
+// Filter wrapping related types.
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Work items for the task producing the conflicting messages.
+enum Equivocation {
+	/// Second a conflicting candidate for this statement of ours.
+	Statement(Hash, SignedFullStatement),
+	/// Distribute a bitfield contradicting this one of ours.
+	Bitfield(Hash, SignedAvailabilityBitfield),
+	/// Send invalid disputes for a freshly activated leaf.
+	Disputes(Hash),
+}
+
+/// Picks up our `Seconded` statements on their way to statement distribution.
+#[derive(Clone)]
+struct EquivocateStatements {
+	distribution: Bernoulli,
+	equivocations: mpsc::UnboundedSender<Equivocation>,
+}
+
+impl<Sender> MessageInterceptor<Sender> for EquivocateStatements
+where
+	Sender: overseer::CandidateBackingSenderTrait + Clone + Send + 'static,
+{
+	type Message = CandidateBackingMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::CandidateBackingOutgoingMessages,
+	) -> Option<overseer::CandidateBackingOutgoingMessages> {
+		if let overseer::CandidateBackingOutgoingMessages::StatementDistributionMessage(
+			StatementDistributionMessage::Share(relay_parent, ref statement),
+		) = msg
+		{
+			if matches!(statement.payload(), Statement::Seconded(_)) &&
+				self.distribution.sample(&mut rand::thread_rng())
+			{
+				let _ = self
+					.equivocations
+					.unbounded_send(Equivocation::Statement(relay_parent, statement.clone()));
+			}
+		}
+		Some(msg)
+	}
+}
+
+/// Picks up our bitfields on their way to bitfield distribution and notes new
+/// leaves for dispute spam.
+#[derive(Clone)]
+struct EquivocateBitfields {
+	distribution: Bernoulli,
+	equivocations: mpsc::UnboundedSender<Equivocation>,
+}
+
+impl<Sender> MessageInterceptor<Sender> for EquivocateBitfields
+where
+	Sender: overseer::BitfieldSigningSenderTrait + Clone + Send + 'static,
+{
+	type Message = BitfieldSigningMessage;
+
+	fn intercept_incoming(
+		&self,
+		_sender: &mut Sender,
+		msg: FromOrchestra<Self::Message>,
+	) -> Option<FromOrchestra<Self::Message>> {
+		if let FromOrchestra::Signal(OverseerSignal::ActiveLeaves(ref update)) = msg {
+			if let Some(ref activated) = update.activated {
+				let _ = self.equivocations.unbounded_send(Equivocation::Disputes(activated.hash));
+			}
+		}
+		Some(msg)
+	}
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::BitfieldSigningOutgoingMessages,
+	) -> Option<overseer::BitfieldSigningOutgoingMessages> {
+		if let overseer::BitfieldSigningOutgoingMessages::BitfieldDistributionMessage(
+			BitfieldDistributionMessage::DistributeBitfield(relay_parent, ref bitfield),
+		) = msg
+		{
+			if self.distribution.sample(&mut rand::thread_rng()) {
+				let _ = self
+					.equivocations
+					.unbounded_send(Equivocation::Bitfield(relay_parent, bitfield.clone()));
+			}
+		}
+		Some(msg)
+	}
+}
+
+/// Issue a runtime API request through the overseer.
+async fn request_from_runtime<T>(
+	handle: &mut Handle,
+	relay_parent: Hash,
+	request: impl FnOnce(oneshot::Sender<Result<T, RuntimeApiError>>) -> RuntimeApiRequest,
+) -> Option<T> {
+	let (tx, rx) = oneshot::channel();
+	handle
+		.send_msg(RuntimeApiMessage::Request(relay_parent, request(tx)), MALUS)
+		.await;
+	rx.await.ok()?.ok()
+}
+
+/// A candidate receipt for a candidate nobody ever produced.
+fn fake_candidate_receipt(relay_parent: Hash) -> CandidateReceipt {
+	use polkadot_primitives::CollatorPair;
+	use sp_core::crypto::Pair;
+
+	let collator_pair = CollatorPair::generate().0;
+	CandidateReceipt {
+		descriptor: CandidateDescriptor {
+			para_id: 0.into(),
+			relay_parent,
+			collator: collator_pair.public(),
+			persisted_validation_data_hash: Hash::zero(),
+			pov_hash: Hash::zero(),
+			erasure_root: Hash::zero(),
+			signature: collator_pair.sign(&[]),
+			para_head: Hash::zero(),
+			validation_code_hash: Hash::zero().into(),
+		},
+		commitments_hash: Hash::from(rand::random::<[u8; 32]>()),
+	}
+}
+
+/// Sign and send the messages conflicting with what the node does honestly.
+async fn run_equivocator(
+	mut handle: Handle,
+	keystore: KeystorePtr,
+	disputes_per_leaf: u32,
+	mut equivocations: mpsc::UnboundedReceiver<Equivocation>,
+) {
+	while let Some(equivocation) = equivocations.next().await {
+		let relay_parent = match &equivocation {
+			Equivocation::Statement(relay_parent, _) |
+			Equivocation::Bitfield(relay_parent, _) |
+			Equivocation::Disputes(relay_parent) => *relay_parent,
+		};
+		if matches!(equivocation, Equivocation::Disputes(_)) && disputes_per_leaf == 0 {
+			continue
+		}
+
+		let session_index = match request_from_runtime(
+			&mut handle,
+			relay_parent,
+			RuntimeApiRequest::SessionIndexForChild,
+		)
+		.await
+		{
+			Some(session_index) => session_index,
+			None => continue,
+		};
+		let validators =
+			request_from_runtime(&mut handle, relay_parent, RuntimeApiRequest::Validators)
+				.await
+				.unwrap_or_default();
+		let (validator_public, validator_index) =
+			match signing_key_and_index(&validators, &keystore) {
+				Some(key_and_index) => key_and_index,
+				None => continue,
+			};
+		let context = SigningContext { session_index, parent_hash: relay_parent };
+
+		match equivocation {
+			Equivocation::Statement(relay_parent, statement) => {
+				let mut candidate = match statement.payload() {
+					Statement::Seconded(candidate) => candidate.clone(),
+					Statement::Valid(_) => continue,
+				};
+				candidate.commitments.head_data.0.push(0xff);
+
+				let conflicting = match SignedFullStatement::sign(
+					&keystore,
+					Statement::Seconded(candidate),
+					&context,
+					validator_index,
+					&validator_public,
+				) {
+					Ok(Some(conflicting)) => conflicting,
+					_ => continue,
+				};
+
+				gum::info!(
+					target: MALUS,
+					?relay_parent,
+					"😈 Seconding a conflicting candidate.",
+				);
+				handle
+					.send_msg(StatementDistributionMessage::Share(relay_parent, conflicting), MALUS)
+					.await;
+			},
+			Equivocation::Bitfield(relay_parent, bitfield) => {
+				let inverted = AvailabilityBitfield(!bitfield.payload().0.clone());
+				let contradicting = match SignedAvailabilityBitfield::sign(
+					&keystore,
+					inverted,
+					&context,
+					validator_index,
+					&validator_public,
+				) {
+					Ok(Some(contradicting)) => contradicting,
+					_ => continue,
+				};
+
+				gum::info!(
+					target: MALUS,
+					?relay_parent,
+					"😈 Distributing a contradicting bitfield.",
+				);
+				handle
+					.send_msg(
+						BitfieldDistributionMessage::DistributeBitfield(
+							relay_parent,
+							contradicting,
+						),
+						MALUS,
+					)
+					.await;
+			},
+			Equivocation::Disputes(leaf) => {
+				let session_info = match request_from_runtime(&mut handle, leaf, |tx| {
+					RuntimeApiRequest::SessionInfo(session_index, tx)
+				})
+				.await
+				.flatten()
+				{
+					Some(session_info) => session_info,
+					None => continue,
+				};
+
+				for _ in 0..disputes_per_leaf {
+					let candidate_receipt = fake_candidate_receipt(leaf);
+					let candidate_hash = candidate_receipt.hash();
+					let sign = |valid| {
+						SignedDisputeStatement::sign_explicit(
+							&keystore,
+							valid,
+							candidate_hash,
+							session_index,
+							validator_public.clone(),
+						)
+						.ok()
+						.flatten()
+					};
+					let (valid_vote, invalid_vote) = match (sign(true), sign(false)) {
+						(Some(valid_vote), Some(invalid_vote)) => (valid_vote, invalid_vote),
+						_ => break,
+					};
+
+					let dispute = match DisputeMessage::from_signed_statements(
+						valid_vote,
+						validator_index,
+						invalid_vote,
+						validator_index,
+						candidate_receipt,
+						&session_info,
+					) {
+						Ok(dispute) => dispute,
+						Err(err) => {
+							gum::warn!(target: MALUS, ?err, "Failed to craft dispute message.");
+							break
+						},
+					};
+
+					gum::info!(
+						target: MALUS,
+						?candidate_hash,
+						"😈 Sending dispute for a non-existent candidate.",
+					);
+					handle.send_msg(DisputeDistributionMessage::SendDispute(dispute), MALUS).await;
+				}
+			},
+		}
+	}
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct SpamEquivocationsOptions {
+	/// Determines the percentage of our `Seconded` statements and bitfields which
+	/// are followed by a conflicting one. Must be in the range [0..=100].
+	#[clap(short, long, ignore_case = true, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub percentage: u8,
+
+	/// Number of invalid disputes to send on every new leaf.
+	#[clap(long, default_value_t = 1)]
+	pub disputes_per_leaf: u32,
+
+	#[clap(flatten)]
+	pub cli: Cli,
+}
+
+/// Equivocation and spam implementation wrapper which implements `OverseerGen` glue.
+pub(crate) struct SpamEquivocations {
+	/// The probability of equivocating on a statement or bitfield.
+	pub percentage: u8,
+	/// Number of invalid disputes to send on every new leaf.
+	pub disputes_per_leaf: u32,
+}
+
+impl OverseerGen for SpamEquivocations {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		gum::info!(
+			target: MALUS,
+			"😈 Started Malus node equivocating on {:?} percent of statements and bitfields, sending {} disputes per leaf.",
+			&self.percentage,
+			&self.disputes_per_leaf,
+		);
+
+		let distribution = Bernoulli::new(f64::from(self.percentage) / 100.0)
+			.expect("Invalid probability! Percentage must be in range [0..=100].");
+
+		let (tx, rx) = mpsc::unbounded();
+		args.spawner.spawn(
+			"malus-equivocator",
+			Some("malus"),
+			Box::pin(run_equivocator(
+				Handle::new(connector.handle()),
+				args.keystore.clone(),
+				self.disputes_per_leaf,
+				rx,
+			)),
+		);
+
+		let equivocate_statements =
+			EquivocateStatements { distribution: distribution.clone(), equivocations: tx.clone() };
+		let equivocate_bitfields = EquivocateBitfields { distribution, equivocations: tx };
+
+		prepared_overseer_builder(args)?
+			.replace_candidate_backing(move |cb| {
+				InterceptedSubsystem::new(cb, equivocate_statements)
+			})
+			.replace_bitfield_signing(move |bs| InterceptedSubsystem::new(bs, equivocate_bitfields))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
@@ -0,0 +1,219 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A malicious node that backs candidates honestly, but refuses to serve
+//! `ChunkFetchingV1`, `PoVFetchingV1` and `AvailableDataFetchingV1` requests
+//! for a configurable percentage of them. It also never distributes its
+//! availability bitfields.
+//!
+//! The requests themselves are answered by `availability-distribution` and
+//! `availability-recovery`, both of which look the data up in the availability
+//! store first. We intercept those lookups and pretend the data is missing, so
+//! the requesting peer receives `NoSuchChunk`/`NoSuchPoV`/`NoSuchData`.
+//!
+//! Note that `availability-recovery` also queries the store before recovering
+//! data for the node itself, so the malus node occasionally recovers its own
+//! data from the network as well.
+//!
+//! Attention: For usage with `zombienet` only!
+
+#![allow(missing_docs)]
+
+use polkadot_cli::{
+	prepared_overseer_builder,
+	service::{
+		AuthorityDiscoveryApi, AuxStore, BabeApi, Block, Error, HeaderBackend, Overseer,
+		OverseerConnector, OverseerGen, OverseerGenArgs, OverseerHandle, ParachainHost,
+		ProvideRuntimeApi,
+	},
+	Cli,
+};
+use polkadot_node_subsystem::SpawnGlue;
+use sp_core::traits::SpawnNamed;
+
+use rand::distributions::{Bernoulli, Distribution};
+
+// Filter wrapping related types.
+use crate::{interceptor::*, shared::MALUS};
+
+use std::sync::Arc;
+
+/// Decides which availability store queries are answered as if the data was missing.
+#[derive(Clone, Debug)]
+struct Withholder {
+	distribution: Bernoulli,
+}
+
+impl Withholder {
+	fn new(percentage: f64) -> Self {
+		let distribution = Bernoulli::new(percentage / 100.0)
+			.expect("Invalid probability! Percentage must be in range [0..=100].");
+		Self { distribution }
+	}
+
+	fn withhold(&self) -> bool {
+		self.distribution.sample(&mut rand::thread_rng())
+	}
+
+	/// Answer a share of the chunk and available data queries with `None` and
+	/// swallow them, pass everything else on to the availability store.
+	fn filter(&self, msg: AvailabilityStoreMessage) -> Option<AvailabilityStoreMessage> {
+		match msg {
+			AvailabilityStoreMessage::QueryChunk(candidate_hash, validator_index, tx)
+				if self.withhold() =>
+			{
+				gum::info!(
+					target: MALUS,
+					?candidate_hash,
+					?validator_index,
+					"😈 Withholding availability chunk.",
+				);
+				let _ = tx.send(None);
+				None
+			},
+			AvailabilityStoreMessage::QueryAvailableData(candidate_hash, tx) if self.withhold() => {
+				gum::info!(target: MALUS, ?candidate_hash, "😈 Withholding available data.",);
+				let _ = tx.send(None);
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Withholds data requested through `ChunkFetchingV1` and `PoVFetchingV1`.
+#[derive(Clone, Debug)]
+struct WithholdChunks(Withholder);
+
+impl<Sender> MessageInterceptor<Sender> for WithholdChunks
+where
+	Sender: overseer::AvailabilityDistributionSenderTrait + Clone + Send + 'static,
+{
+	type Message = AvailabilityDistributionMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::AvailabilityDistributionOutgoingMessages,
+	) -> Option<overseer::AvailabilityDistributionOutgoingMessages> {
+		match msg {
+			overseer::AvailabilityDistributionOutgoingMessages::AvailabilityStoreMessage(msg) =>
+				self.0.filter(msg).map(Into::into),
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Withholds data requested through `AvailableDataFetchingV1`.
+#[derive(Clone, Debug)]
+struct WithholdAvailableData(Withholder);
+
+impl<Sender> MessageInterceptor<Sender> for WithholdAvailableData
+where
+	Sender: overseer::AvailabilityRecoverySenderTrait + Clone + Send + 'static,
+{
+	type Message = AvailabilityRecoveryMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::AvailabilityRecoveryOutgoingMessages,
+	) -> Option<overseer::AvailabilityRecoveryOutgoingMessages> {
+		match msg {
+			overseer::AvailabilityRecoveryOutgoingMessages::AvailabilityStoreMessage(msg) =>
+				self.0.filter(msg).map(Into::into),
+			msg => Some(msg),
+		}
+	}
+}
+
+/// Drops every bitfield produced by `bitfield-signing`, so it is neither
+/// gossiped nor handed to the provisioner.
+#[derive(Clone, Debug)]
+struct DropBitfields;
+
+impl<Sender> MessageInterceptor<Sender> for DropBitfields
+where
+	Sender: overseer::BitfieldSigningSenderTrait + Clone + Send + 'static,
+{
+	type Message = BitfieldSigningMessage;
+
+	fn intercept_outgoing(
+		&self,
+		msg: overseer::BitfieldSigningOutgoingMessages,
+	) -> Option<overseer::BitfieldSigningOutgoingMessages> {
+		match msg {
+			overseer::BitfieldSigningOutgoingMessages::BitfieldDistributionMessage(
+				BitfieldDistributionMessage::DistributeBitfield(relay_parent, _),
+			) => {
+				gum::info!(target: MALUS, ?relay_parent, "😈 Not distributing our bitfield.",);
+				None
+			},
+			msg => Some(msg),
+		}
+	}
+}
+
+#[derive(Debug, clap::Parser)]
+#[clap(rename_all = "kebab-case")]
+#[allow(missing_docs)]
+pub struct WithholdAvailabilityOptions {
+	/// Determines the percentage of chunk, PoV and available data requests that are
+	/// not served. Must be in the range [0..=100].
+	#[clap(short, long, ignore_case = true, default_value_t = 100, value_parser = clap::value_parser!(u8).range(0..=100))]
+	pub percentage: u8,
+
+	#[clap(flatten)]
+	pub cli: Cli,
+}
+
+/// Withhold availability implementation wrapper which implements `OverseerGen` glue.
+pub(crate) struct WithholdAvailability {
+	/// The probability of not serving a request.
+	pub percentage: u8,
+}
+
+impl OverseerGen for WithholdAvailability {
+	fn generate<'a, Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<'a, Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		gum::info!(
+			target: MALUS,
+			"😈 Started Malus node withholding {:?} percent of the requested availability data.",
+			&self.percentage,
+		);
+
+		let withholder = Withholder::new(f64::from(self.percentage));
+		let withhold_chunks = WithholdChunks(withholder.clone());
+		let withhold_available_data = WithholdAvailableData(withholder);
+
+		prepared_overseer_builder(args)?
+			.replace_availability_distribution(move |ad| {
+				InterceptedSubsystem::new(ad, withhold_chunks)
+			})
+			.replace_availability_recovery(move |ar| {
+				InterceptedSubsystem::new(ar, withhold_available_data)
+			})
+			.replace_bitfield_signing(move |bs| InterceptedSubsystem::new(bs, DropBitfields))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
@@ -37,7 +37,9 @@ use polkadot_node_subsystem::{
 };
 use polkadot_node_subsystem_util::{self as util};
 
-use polkadot_primitives::{Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId};
+use polkadot_primitives::{
+	AvailabilityBitfield, Hash, SignedAvailabilityBitfield, SigningContext, ValidatorId,
+};
 use rand::{CryptoRng, Rng, SeedableRng};
 use std::collections::{HashMap, HashSet};
 
@@ -160,6 +162,21 @@ impl PerRelayParentData {
 	}
 }
 
+/// Whether the `new` bitfield has every bit of the `old` one set, and at least one more.
+///
+/// Chunks never become unavailable again, so a validator may replace its bitfield by one which
+/// supersedes it once more chunks arrived. As every replacement sets at least one more bit, a
+/// validator can replace its bitfield at most once per core.
+///
+/// A peer still never sends or receives more than one bitfield per validator: the replacement is
+/// handed to the provisioner and only relayed to peers with which no bitfield of the validator was
+/// exchanged yet, as peers treat a second one as a duplicate.
+fn supersedes(new: &AvailabilityBitfield, old: &AvailabilityBitfield) -> bool {
+	new.0.len() == old.0.len() &&
+		new.0.count_ones() > old.0.count_ones() &&
+		old.0.iter_ones().all(|i| new.0[i])
+}
+
 const LOG_TARGET: &str = "parachain::bitfield-distribution";
 
 /// The bitfield distribution subsystem.
@@ -326,7 +343,21 @@ async fn handle_bitfield_distribution<Context>(
 		return
 	};
 
+	// Re-signed bitfields only replace the one we distributed before if they supersede it.
+	if let Some(old_message) = job_data.one_per_validator.get(&validator) {
+		if !supersedes(signed_availability.payload(), old_message.signed_availability.payload()) {
+			gum::debug!(
+				target: LOG_TARGET,
+				?relay_parent,
+				"Own bitfield does not supersede the one distributed before",
+			);
+			return
+		}
+	}
+
 	let msg = BitfieldGossipMessage { relay_parent, signed_availability };
+	job_data.one_per_validator.insert(validator.clone(), msg.clone());
+
 	let topology = state.topologies.get_topology_or_fallback(session_idx).local_grid_neighbors();
 	let required_routing = topology.required_routing_by_index(validator_index, true);
 
@@ -507,19 +538,27 @@ async fn process_incoming_peer_message<Context>(
 		return
 	};
 
-	let one_per_validator = &mut (job_data.one_per_validator);
-
-	// relay a message received from a validator at most _once_
-	if let Some(old_message) = one_per_validator.get(&validator) {
-		gum::trace!(
-			target: LOG_TARGET,
-			?validator_index,
-			"already received a message for validator",
-		);
-		if old_message.signed_availability.as_unchecked() == &bitfield {
-			modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE).await;
-		}
-		return
+	// A bitfield superseding the one we know for the validator is new information, even if another
+	// peer sent us a message for the validator before.
+	let supersedes_known =
+		job_data.one_per_validator.get(&validator).map_or(false, |old_message| {
+			supersedes(bitfield.unchecked_payload(), old_message.signed_availability.payload())
+		});
+
+	// relay a message received from a validator at most _once_, unless it supersedes the known one
+	match job_data.one_per_validator.get(&validator) {
+		Some(old_message) if !supersedes_known => {
+			gum::trace!(
+				target: LOG_TARGET,
+				?validator_index,
+				"already received a message for validator",
+			);
+			if old_message.signed_availability.as_unchecked() == &bitfield {
+				modify_reputation(ctx.sender(), relay_parent, origin, BENEFIT_VALID_MESSAGE).await;
+			}
+			return
+		},
+		_ => {},
 	}
 	let signed_availability = match bitfield.try_into_checked(&signing_context, &validator) {
 		Err(_) => {
@@ -537,8 +576,13 @@ async fn process_incoming_peer_message<Context>(
 		.local_grid_neighbors();
 	let required_routing = topology.required_routing_by_index(validator_index, false);
 
+	if supersedes_known {
+		gum::trace!(target: LOG_TARGET, ?validator_index, ?origin, "Bitfield superseded");
+		metrics.on_bitfield_superseded();
+	}
+
 	metrics.on_bitfield_received();
-	one_per_validator.insert(validator.clone(), message.clone());
+	job_data.one_per_validator.insert(validator.clone(), message.clone());
 
 	relay_message(
 		ctx,
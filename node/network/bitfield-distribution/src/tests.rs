@@ -417,6 +417,130 @@ fn receive_duplicate_messages() {
 	});
 }
 
+#[test]
+fn superseding_bitfields_are_relayed() {
+	let hash: Hash = [0; 32].into();
+
+	let peer_a = PeerId::random();
+	let peer_b = PeerId::random();
+	let peer_c = PeerId::random();
+	let peer_d = PeerId::random();
+
+	// validator 0 key pair
+	let (mut state, signing_context, keystore, validator) =
+		state_with_view(our_view![hash], hash.clone());
+	state.peer_views.insert(peer_a.clone(), view![hash]);
+	state.peer_views.insert(peer_c.clone(), view![hash]);
+
+	let sign = |payload| {
+		Signed::<AvailabilityBitfield>::sign(
+			&keystore,
+			payload,
+			&signing_context,
+			ValidatorIndex(0),
+			&validator,
+		)
+		.ok()
+		.flatten()
+		.expect("should be signed")
+	};
+
+	// validator 0 first signs a bitfield with half of the chunks available ...
+	let mut bits = bitvec![u8, bitvec::order::Lsb0; 0u8; 32];
+	bits[..16].fill(true);
+	let old_bitfield = sign(AvailabilityBitfield(bits));
+	let old_msg = BitfieldGossipMessage { relay_parent: hash, signed_availability: old_bitfield };
+
+	// ... and later all of them.
+	let new_bitfield = sign(AvailabilityBitfield(bitvec![u8, bitvec::order::Lsb0; 1u8; 32]));
+	let new_msg =
+		BitfieldGossipMessage { relay_parent: hash, signed_availability: new_bitfield.clone() };
+
+	let pool = sp_core::testing::TaskExecutor::new();
+	let (mut ctx, mut handle) = make_subsystem_context::<BitfieldDistributionMessage, _>(pool);
+	let mut rng = dummy_rng();
+
+	executor::block_on(async move {
+		// The old bitfield is received from peer B and relayed to peers A and C, the superseding
+		// one is received from peer C. It is only relayed to peer D, which joined in the meantime:
+		// the other peers already exchanged a bitfield of the validator with us and would treat
+		// a second one as a duplicate.
+		for (origin, msg, expected_peers) in
+			[(peer_b, old_msg, vec![peer_a, peer_c]), (peer_c, new_msg.clone(), vec![peer_d])]
+		{
+			if origin == peer_c {
+				state.peer_views.insert(peer_d.clone(), view![hash]);
+			}
+			launch!(handle_network_msg(
+				&mut ctx,
+				&mut state,
+				&Default::default(),
+				NetworkBridgeEvent::PeerMessage(origin.clone(), msg.clone().into_network_message()),
+				&mut rng,
+			));
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::Provisioner(ProvisionerMessage::ProvisionableData(
+					_,
+					ProvisionableData::Bitfield(h, signed)
+				)) => {
+					assert_eq!(h, hash);
+					assert_eq!(signed, msg.signed_availability)
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridgeTx(
+					NetworkBridgeTxMessage::SendValidationMessage(mut peers, send_msg),
+				) => {
+					peers.sort();
+					let mut expected_peers = expected_peers;
+					expected_peers.sort();
+					assert_eq!(peers, expected_peers);
+					assert_eq!(send_msg, msg.clone().into_validation_protocol());
+				}
+			);
+
+			assert_matches!(
+				handle.recv().await,
+				AllMessages::NetworkBridgeTx(
+					NetworkBridgeTxMessage::ReportPeer(peer, rep)
+				) => {
+					assert_eq!(peer, origin);
+					assert_eq!(rep, BENEFIT_VALID_MESSAGE_FIRST)
+				}
+			);
+		}
+
+		assert_eq!(
+			state.per_relay_parent[&hash].one_per_validator[&validator].signed_availability,
+			new_bitfield,
+		);
+
+		// A second bitfield of the validator from the same peer is a duplicate, even if it
+		// supersedes the first one.
+		launch!(handle_network_msg(
+			&mut ctx,
+			&mut state,
+			&Default::default(),
+			NetworkBridgeEvent::PeerMessage(peer_b.clone(), new_msg.into_network_message()),
+			&mut rng,
+		));
+
+		assert_matches!(
+			handle.recv().await,
+			AllMessages::NetworkBridgeTx(
+				NetworkBridgeTxMessage::ReportPeer(peer, rep)
+			) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_PEER_DUPLICATE_MESSAGE)
+			}
+		);
+	});
+}
+
 #[test]
 fn do_not_relay_message_twice() {
 	let _ = env_logger::builder()
@@ -0,0 +1,233 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Delivery tracking for distributed collations.
+//!
+//! For every relay parent we distributed a collation for, we keep track of which validators of the
+//! assigned group we advertised the collation to, which of them fetched it, how long that took and
+//! who reported back seconding it. Once the relay parent leaves our view the tracked data is
+//! moved into a bounded FIFO, so operators can inspect what happened to recent collations. Uploads
+//! which only finish after that are still attributed to the finished delivery.
+
+use std::{
+	collections::{HashMap, HashSet, VecDeque},
+	time::{Duration, Instant},
+};
+
+use polkadot_node_network_protocol::PeerId;
+use polkadot_node_primitives::{CollationDeliveryOutcome, CollationDeliveryReport, CollationFetch};
+use polkadot_primitives::{
+	AuthorityDiscoveryId, CandidateHash, Hash, Id as ParaId, ValidatorIndex,
+};
+
+/// Number of reports for relay parents which went out of view we keep around.
+pub const MAX_FINISHED_REPORTS: usize = 64;
+
+/// A fetch which is tracked by its peer, so the upload result can be attributed to it.
+struct TrackedFetch {
+	peer: PeerId,
+	fetch: CollationFetch,
+}
+
+/// Delivery data of a single collation.
+struct Delivery {
+	candidate_hash: CandidateHash,
+	para_id: ParaId,
+	group: Vec<AuthorityDiscoveryId>,
+	advertised_at: HashMap<PeerId, Instant>,
+	advertised_to: Vec<AuthorityDiscoveryId>,
+	fetches: Vec<TrackedFetch>,
+	seconded_by: Vec<ValidatorIndex>,
+}
+
+impl Delivery {
+	fn outcome(&self) -> CollationDeliveryOutcome {
+		if !self.seconded_by.is_empty() {
+			CollationDeliveryOutcome::Seconded
+		} else if !self.fetches.is_empty() {
+			CollationDeliveryOutcome::NotSeconded
+		} else if !self.advertised_at.is_empty() {
+			CollationDeliveryOutcome::NotRequested
+		} else {
+			CollationDeliveryOutcome::NotAdvertised
+		}
+	}
+
+	fn report(&self, relay_parent: Hash) -> CollationDeliveryReport {
+		CollationDeliveryReport {
+			relay_parent,
+			candidate_hash: self.candidate_hash.0,
+			para_id: self.para_id,
+			group: self.group.clone(),
+			advertised_to: self.advertised_to.clone(),
+			fetches: self.fetches.iter().map(|f| f.fetch.clone()).collect(),
+			seconded_by: self.seconded_by.clone(),
+			outcome: self.outcome(),
+		}
+	}
+}
+
+/// Tracks the delivery of our collations per relay parent.
+#[derive(Default)]
+pub struct DeliveryTracker {
+	active: HashMap<Hash, Delivery>,
+	finished: VecDeque<(Hash, Delivery)>,
+}
+
+impl DeliveryTracker {
+	/// Start tracking a newly distributed collation.
+	pub fn note_distributed(
+		&mut self,
+		relay_parent: Hash,
+		candidate_hash: CandidateHash,
+		para_id: ParaId,
+		group: Vec<AuthorityDiscoveryId>,
+	) {
+		self.active.insert(
+			relay_parent,
+			Delivery {
+				candidate_hash,
+				para_id,
+				group,
+				advertised_at: HashMap::new(),
+				advertised_to: Vec::new(),
+				fetches: Vec::new(),
+				seconded_by: Vec::new(),
+			},
+		);
+	}
+
+	/// Note that the collation at `relay_parent` was advertised to `peer`.
+	pub fn note_advertised(
+		&mut self,
+		relay_parent: &Hash,
+		peer: PeerId,
+		authority_ids: Option<&HashSet<AuthorityDiscoveryId>>,
+	) {
+		if let Some(delivery) = self.active.get_mut(relay_parent) {
+			delivery.advertised_at.insert(peer, Instant::now());
+			delivery.advertised_to.extend(authority_ids.into_iter().flatten().cloned());
+		}
+	}
+
+	/// Note that `peer` requested the collation at `relay_parent`.
+	///
+	/// Returns the time between advertising the collation to the peer and the request, if we
+	/// advertised it.
+	pub fn note_requested(
+		&mut self,
+		relay_parent: &Hash,
+		peer: PeerId,
+		authority_ids: Option<&HashSet<AuthorityDiscoveryId>>,
+	) -> Option<Duration> {
+		let delivery = self.active.get_mut(relay_parent)?;
+		let time_to_fetch = delivery.advertised_at.get(&peer).map(|at| at.elapsed());
+
+		delivery.fetches.push(TrackedFetch {
+			peer,
+			fetch: CollationFetch {
+				validator: authority_ids.into_iter().flatten().cloned().collect(),
+				time_to_fetch_ms: time_to_fetch.map(|t| t.as_millis() as u64),
+				upload_ms: None,
+				timed_out: false,
+				bytes: 0,
+			},
+		});
+
+		time_to_fetch
+	}
+
+	/// Note that we started uploading `bytes` of the collation at `relay_parent` to `peer`.
+	///
+	/// `bytes` is the size of the full response, that is the candidate receipt and the PoV.
+	pub fn note_upload_started(&mut self, relay_parent: &Hash, peer: &PeerId, bytes: u64) {
+		if let Some(fetch) = self.last_fetch_of(relay_parent, peer) {
+			fetch.bytes = bytes;
+		}
+	}
+
+	/// Note that the upload of the collation at `relay_parent` to `peer` finished or timed out.
+	pub fn note_upload_finished(
+		&mut self,
+		relay_parent: &Hash,
+		peer: &PeerId,
+		upload_time: Option<Duration>,
+	) {
+		if let Some(fetch) = self.last_fetch_of(relay_parent, peer) {
+			fetch.upload_ms = upload_time.map(|t| t.as_millis() as u64);
+			fetch.timed_out = upload_time.is_none();
+		}
+	}
+
+	/// Note that the validator with `validator_index` seconded the collation at `relay_parent`.
+	pub fn note_seconded(
+		&mut self,
+		relay_parent: &Hash,
+		candidate_hash: &CandidateHash,
+		validator_index: ValidatorIndex,
+	) {
+		if let Some(delivery) = self.active.get_mut(relay_parent) {
+			if &delivery.candidate_hash == candidate_hash &&
+				!delivery.seconded_by.contains(&validator_index)
+			{
+				delivery.seconded_by.push(validator_index);
+			}
+		}
+	}
+
+	/// Stop tracking the collation at `relay_parent`, as it went out of view.
+	///
+	/// Returns the final outcome of the delivery, if there was a collation.
+	pub fn note_removed(&mut self, relay_parent: &Hash) -> Option<CollationDeliveryOutcome> {
+		let delivery = self.active.remove(relay_parent)?;
+		let outcome = delivery.outcome();
+
+		if self.finished.len() >= MAX_FINISHED_REPORTS {
+			self.finished.pop_front();
+		}
+		self.finished.push_back((*relay_parent, delivery));
+
+		Some(outcome)
+	}
+
+	/// Reports of all recent collations, the ones which went out of view first.
+	pub fn reports(&self) -> Vec<CollationDeliveryReport> {
+		self.finished
+			.iter()
+			.map(|(relay_parent, d)| d.report(*relay_parent))
+			.chain(self.active.iter().map(|(relay_parent, d)| d.report(*relay_parent)))
+			.collect()
+	}
+
+	fn last_fetch_of(&mut self, relay_parent: &Hash, peer: &PeerId) -> Option<&mut CollationFetch> {
+		let delivery = match self.active.get_mut(relay_parent) {
+			Some(delivery) => delivery,
+			None => self
+				.finished
+				.iter_mut()
+				.rev()
+				.find(|(r, _)| r == relay_parent)
+				.map(|(_, d)| d)?,
+		};
+
+		delivery
+			.fetches
+			.iter_mut()
+			.rev()
+			.find(|f| &f.peer == peer)
+			.map(|f| &mut f.fetch)
+	}
+}
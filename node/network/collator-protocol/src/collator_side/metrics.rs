@@ -14,6 +14,9 @@
 // You should have received a copy of the GNU General Public License
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
+use std::time::Duration;
+
+use polkadot_node_primitives::CollationDeliveryOutcome;
 use polkadot_node_subsystem_util::metrics::{self, prometheus};
 
 #[derive(Clone, Default)]
@@ -38,6 +41,43 @@ impl Metrics {
 		}
 	}
 
+	pub fn on_collation_bytes_sent(&self, bytes: u64) {
+		if let Some(metrics) = &self.0 {
+			metrics.collation_bytes_sent.inc_by(bytes);
+		}
+	}
+
+	/// Record the time between advertising a collation to a validator and its request.
+	pub fn on_collation_fetched(&self, time_to_fetch: Duration) {
+		if let Some(metrics) = &self.0 {
+			metrics.collation_time_to_fetch.observe(time_to_fetch.as_secs_f64());
+		}
+	}
+
+	/// Record the time it took to upload a collation, `None` if the upload timed out.
+	pub fn on_collation_uploaded(&self, upload_time: Option<Duration>) {
+		if let Some(metrics) = &self.0 {
+			match upload_time {
+				Some(upload_time) =>
+					metrics.collation_upload_time.observe(upload_time.as_secs_f64()),
+				None => metrics.collation_upload_timeouts.inc(),
+			}
+		}
+	}
+
+	/// Record what became of a collation once its relay parent went out of view.
+	pub fn on_collation_delivery_outcome(&self, outcome: CollationDeliveryOutcome) {
+		if let Some(metrics) = &self.0 {
+			let label = match outcome {
+				CollationDeliveryOutcome::NotAdvertised => "not-advertised",
+				CollationDeliveryOutcome::NotRequested => "not-requested",
+				CollationDeliveryOutcome::NotSeconded => "not-seconded",
+				CollationDeliveryOutcome::Seconded => "seconded",
+			};
+			metrics.collation_delivery_outcomes.with_label_values(&[label]).inc();
+		}
+	}
+
 	/// Provide a timer for `process_msg` which observes on drop.
 	pub fn time_process_msg(&self) -> Option<prometheus::prometheus::HistogramTimer> {
 		self.0.as_ref().map(|metrics| metrics.process_msg.start_timer())
@@ -59,6 +99,11 @@ struct MetricsInner {
 	advertisements_made: prometheus::Counter<prometheus::U64>,
 	collations_sent: prometheus::Counter<prometheus::U64>,
 	collations_send_requested: prometheus::Counter<prometheus::U64>,
+	collation_bytes_sent: prometheus::Counter<prometheus::U64>,
+	collation_time_to_fetch: prometheus::Histogram,
+	collation_upload_time: prometheus::Histogram,
+	collation_upload_timeouts: prometheus::Counter<prometheus::U64>,
+	collation_delivery_outcomes: prometheus::CounterVec<prometheus::U64>,
 	process_msg: prometheus::Histogram,
 	collation_distribution_time: prometheus::HistogramVec,
 }
@@ -89,6 +134,54 @@ impl metrics::Metrics for Metrics {
 				)?,
 				registry,
 			)?,
+			collation_bytes_sent: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_collation_bytes_sent_total",
+					"Number of collation bytes (candidate receipt and PoV) sent to validators.",
+				)?,
+				registry,
+			)?,
+			collation_time_to_fetch: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_collator_protocol_collator_time_to_fetch",
+						"Time between advertising a collation to a validator and its request.",
+					)
+					.buckets(vec![
+						0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 0.75, 1.0, 1.5, 2.0, 3.0, 4.0, 6.0,
+					]),
+				)?,
+				registry,
+			)?,
+			collation_upload_time: prometheus::register(
+				prometheus::Histogram::with_opts(
+					prometheus::HistogramOpts::new(
+						"polkadot_parachain_collator_protocol_collator_upload_time",
+						"Time it took to upload a collation to a validator.",
+					)
+					.buckets(vec![
+						0.001, 0.002, 0.005, 0.01, 0.025, 0.05, 0.075, 0.1, 0.125, 0.15,
+					]),
+				)?,
+				registry,
+			)?,
+			collation_upload_timeouts: prometheus::register(
+				prometheus::Counter::new(
+					"polkadot_parachain_collation_upload_timeouts_total",
+					"Number of collation uploads which did not finish before we moved on to the next validator.",
+				)?,
+				registry,
+			)?,
+			collation_delivery_outcomes: prometheus::register(
+				prometheus::CounterVec::new(
+					prometheus::Opts::new(
+						"polkadot_parachain_collation_delivery_outcomes_total",
+						"Outcome of collations whose relay parent went out of view.",
+					),
+					&["outcome"],
+				)?,
+				registry,
+			)?,
 			process_msg: prometheus::register(
 				prometheus::Histogram::with_opts(
 					prometheus::HistogramOpts::new(
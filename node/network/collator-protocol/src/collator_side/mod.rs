@@ -23,6 +23,7 @@ use std::{
 use futures::{
 	channel::oneshot, pin_mut, select, stream::FuturesUnordered, Future, FutureExt, StreamExt,
 };
+use parity_scale_codec::Encode;
 use sp_core::Pair;
 
 use polkadot_node_network_protocol::{
@@ -56,9 +57,11 @@ use super::LOG_TARGET;
 use crate::error::{log_error, Error, FatalError, Result};
 use fatality::Split;
 
+mod delivery;
 mod metrics;
 mod validators_buffer;
 
+use delivery::DeliveryTracker;
 use validators_buffer::{ValidatorGroupsBuffer, VALIDATORS_BUFFER_CAPACITY};
 
 pub use metrics::Metrics;
@@ -183,7 +186,8 @@ struct WaitingCollationFetches {
 struct CollationSendResult {
 	relay_parent: Hash,
 	peer_id: PeerId,
-	timed_out: bool,
+	/// How long the upload took, `None` if it did not finish within `MAX_UNSHARED_UPLOAD_TIME`.
+	upload_time: Option<Duration>,
 }
 
 type ActiveCollationFetches =
@@ -235,6 +239,9 @@ struct State {
 	/// Metrics.
 	metrics: Metrics,
 
+	/// Delivery tracking of our collations, for reporting.
+	delivery: DeliveryTracker,
+
 	/// All collation fetching requests that are still waiting to be answered.
 	///
 	/// They are stored per relay parent, when our view changes and the relay parent moves out, we will cancel the fetch
@@ -265,6 +272,7 @@ impl State {
 			peer_ids: Default::default(),
 			validator_groups_buf: ValidatorGroupsBuffer::with_capacity(VALIDATORS_BUFFER_CAPACITY),
 			last_connected_at: None,
+			delivery: Default::default(),
 			waiting_collation_fetches: Default::default(),
 			active_collation_fetches: Default::default(),
 		}
@@ -387,6 +395,7 @@ async fn distribute_collation<Context>(
 	state
 		.collations
 		.insert(relay_parent, Collation { receipt, pov, status: CollationStatus::Created });
+	state.delivery.note_distributed(relay_parent, candidate_hash, id, validators);
 
 	let interested = state.peers_interested_in_leaf(&relay_parent);
 	// Make sure already connected peers get collations:
@@ -569,6 +578,7 @@ async fn advertise_collation<Context>(
 	if let Some(validators) = state.our_validators_groups.get_mut(&relay_parent) {
 		validators.advertised_to_peer(&state.peer_ids, &peer);
 	}
+	state.delivery.note_advertised(&relay_parent, peer, state.peer_ids.get(&peer));
 
 	state.metrics.on_advertisment_made();
 }
@@ -636,6 +646,9 @@ async fn process_msg<Context>(
 				);
 			}
 		},
+		DeliveryReports(tx) => {
+			let _ = tx.send(state.delivery.reports());
+		},
 		_ => {},
 	}
 
@@ -653,6 +666,7 @@ async fn send_collation(
 
 	let relay_parent = request.payload.relay_parent;
 	let peer_id = request.peer;
+	let bytes = (receipt.encoded_size() + pov.encoded_size()) as u64;
 
 	let response = OutgoingResponse {
 		result: Ok(CollationFetchingResponse::Collation(receipt, pov)),
@@ -664,17 +678,20 @@ async fn send_collation(
 		gum::warn!(target: LOG_TARGET, "Sending collation response failed");
 	}
 
+	let started_at = Instant::now();
 	state.active_collation_fetches.push(
 		async move {
 			let r = rx.timeout(MAX_UNSHARED_UPLOAD_TIME).await;
-			let timed_out = r.is_none();
+			let upload_time = r.map(|_| started_at.elapsed());
 
-			CollationSendResult { relay_parent, peer_id, timed_out }
+			CollationSendResult { relay_parent, peer_id, upload_time }
 		}
 		.boxed(),
 	);
 
+	state.delivery.note_upload_started(&relay_parent, &peer_id, bytes);
 	state.metrics.on_collation_sent();
+	state.metrics.on_collation_bytes_sent(bytes);
 }
 
 /// A networking messages switch.
@@ -728,6 +745,12 @@ async fn handle_incoming_peer_message<Context>(
 					.await?
 					.map_err(Error::InvalidStatementSignature)?;
 
+				state.delivery.note_seconded(
+					&relay_parent,
+					&statement.payload().candidate_hash(),
+					statement.validator_index(),
+				);
+
 				let removed =
 					state.collation_result_senders.remove(&statement.payload().candidate_hash());
 
@@ -799,6 +822,15 @@ async fn handle_incoming_request<Context>(
 				return Ok(())
 			}
 
+			let authority_ids = state.peer_ids.get(&req.peer);
+			if let Some(time_to_fetch) =
+				state
+					.delivery
+					.note_requested(&req.payload.relay_parent, req.peer, authority_ids)
+			{
+				state.metrics.on_collation_fetched(time_to_fetch);
+			}
+
 			if waiting.collation_fetch_active {
 				waiting.waiting.push_back(req);
 			} else {
@@ -926,6 +958,9 @@ async fn handle_our_view_change(state: &mut State, view: OurView) -> Result<()>
 				),
 			}
 		}
+		if let Some(outcome) = state.delivery.note_removed(removed) {
+			state.metrics.on_collation_delivery_outcome(outcome);
+		}
 		state.our_validators_groups.remove(removed);
 		state.span_per_relay_parent.remove(removed);
 		state.waiting_collation_fetches.remove(removed);
@@ -972,9 +1007,12 @@ pub(crate) async fn run<Context>(
 			CollationSendResult {
 				relay_parent,
 				peer_id,
-				timed_out,
+				upload_time,
 			} = state.active_collation_fetches.select_next_some() => {
-				if timed_out {
+				state.delivery.note_upload_finished(&relay_parent, &peer_id, upload_time);
+				state.metrics.on_collation_uploaded(upload_time);
+
+				if upload_time.is_none() {
 					gum::debug!(
 						target: LOG_TARGET,
 						?relay_parent,
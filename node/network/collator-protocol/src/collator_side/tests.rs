@@ -35,7 +35,7 @@ use polkadot_node_network_protocol::{
 	request_response::{IncomingRequest, ReqProtocolNames},
 	view,
 };
-use polkadot_node_primitives::BlockData;
+use polkadot_node_primitives::{BlockData, CollationDeliveryOutcome, CollationDeliveryReport};
 use polkadot_node_subsystem::{
 	jaeger,
 	messages::{AllMessages, RuntimeApiMessage, RuntimeApiRequest},
@@ -652,6 +652,93 @@ fn advertise_and_send_collation() {
 	});
 }
 
+/// Query the delivery reports of the subsystem.
+async fn delivery_reports(virtual_overseer: &mut VirtualOverseer) -> Vec<CollationDeliveryReport> {
+	let (tx, rx) = oneshot::channel();
+	overseer_send(virtual_overseer, CollatorProtocolMessage::DeliveryReports(tx)).await;
+	rx.await.expect("Delivery reports are always answered")
+}
+
+#[test]
+fn delivery_report_tracks_advertisement_and_fetch() {
+	let mut test_state = TestState::default();
+	let local_peer_id = test_state.local_peer_id.clone();
+	let collator_pair = test_state.collator_pair.clone();
+
+	test_harness(local_peer_id, collator_pair, |test_harness| async move {
+		let mut virtual_overseer = test_harness.virtual_overseer;
+		let mut req_cfg = test_harness.req_cfg;
+
+		setup_system(&mut virtual_overseer, &test_state).await;
+
+		let DistributeCollation { candidate, pov_block } =
+			distribute_collation(&mut virtual_overseer, &test_state, true).await;
+
+		let reports = delivery_reports(&mut virtual_overseer).await;
+		assert_eq!(reports.len(), 1);
+		assert_eq!(reports[0].outcome, CollationDeliveryOutcome::NotAdvertised);
+		assert_eq!(reports[0].group, test_state.current_group_validator_authority_ids());
+
+		let validator = test_state.current_group_validator_authority_ids()[0].clone();
+		let peer = test_state.current_group_validator_peer_ids()[0].clone();
+
+		connect_peer(&mut virtual_overseer, peer, Some(validator.clone())).await;
+		expect_declare_msg(&mut virtual_overseer, &test_state, &peer).await;
+		send_peer_view_change(&mut virtual_overseer, &peer, vec![test_state.relay_parent]).await;
+		expect_advertise_collation_msg(&mut virtual_overseer, &peer, test_state.relay_parent).await;
+
+		let reports = delivery_reports(&mut virtual_overseer).await;
+		assert_eq!(reports[0].outcome, CollationDeliveryOutcome::NotRequested);
+		assert_eq!(reports[0].advertised_to, vec![validator.clone()]);
+
+		let (pending_response, rx) = oneshot::channel();
+		req_cfg
+			.inbound_queue
+			.as_mut()
+			.unwrap()
+			.send(RawIncomingRequest {
+				peer,
+				payload: CollationFetchingRequest {
+					relay_parent: test_state.relay_parent,
+					para_id: test_state.para_id,
+				}
+				.encode(),
+				pending_response,
+			})
+			.await
+			.unwrap();
+		let feedback = assert_matches!(rx.await, Ok(full_response) => {
+			full_response.sent_feedback.expect("Feedback channel is always set")
+		});
+
+		// The relay parent goes out of view before the upload finishes.
+		let old_relay_parent = test_state.relay_parent;
+		test_state.advance_to_new_round(&mut virtual_overseer, false).await;
+
+		feedback.send(()).unwrap();
+
+		// Give the subsystem a chance to process the upload result.
+		Delay::new(Duration::from_millis(50)).await;
+
+		let reports = delivery_reports(&mut virtual_overseer).await;
+		assert_eq!(reports.len(), 1);
+		let report = &reports[0];
+		assert_eq!(report.relay_parent, old_relay_parent);
+		assert_eq!(report.candidate_hash, candidate.hash().0);
+		assert_eq!(report.outcome, CollationDeliveryOutcome::NotSeconded);
+		assert_eq!(report.fetches.len(), 1);
+
+		let fetch = &report.fetches[0];
+		assert_eq!(fetch.validator, vec![validator]);
+		assert_eq!(fetch.bytes, (candidate.encoded_size() + pov_block.encoded_size()) as u64);
+		assert!(fetch.time_to_fetch_ms.is_some());
+		assert!(fetch.upload_ms.is_some());
+		assert!(!fetch.timed_out);
+
+		TestHarness { virtual_overseer, req_cfg }
+	});
+}
+
 #[test]
 fn send_only_one_collation_per_relay_parent_at_a_time() {
 	test_validator_send_sequence(|mut second_response_receiver, feedback_first_tx| async move {
@@ -0,0 +1,132 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Fair queueing of advertised collations.
+//!
+//! A para may have many collators and not all of them are well behaved. To make sure a noisy
+//! collator cannot crowd out honest ones, we keep a local score for every collator we interacted
+//! with. The score mirrors the reputation changes we report to the network bridge and separately
+//! counts collations which failed validation.
+//!
+//! Advertised collations waiting to be fetched are then dequeued by that score: collators with
+//! failed collations come last, within the remaining ones the best reputation wins and ties are
+//! broken by the order of the advertisements.
+
+use std::{
+	cmp::Reverse,
+	collections::HashMap,
+	time::{Duration, Instant},
+};
+
+use polkadot_node_network_protocol::{PeerId, UnifiedReputationChange as Rep};
+use polkadot_primitives::CollatorId;
+
+use super::PendingCollation;
+
+/// How many advertisements of a single peer may wait to be fetched per relay parent.
+pub const MAX_UNFETCHED_ADVERTISEMENTS_PER_PEER: usize = 1;
+
+/// How many collations a single peer may advertise per relay parent.
+///
+/// A v1 advertisement doesn't name the candidate, so any further advertisement of the same relay
+/// parent is a duplicate.
+pub const MAX_ADVERTISEMENTS_PER_RELAY_PARENT: usize = 1;
+
+/// Scores of collators which were not updated for this long are forgotten.
+pub const COLLATOR_SCORE_RETENTION: Duration = Duration::from_secs(60 * 60);
+
+/// Local score of a collator.
+#[derive(Debug, Clone, Copy)]
+struct CollatorScore {
+	/// Sum of all reputation changes we reported for the collator.
+	reputation: i64,
+	/// Number of collations of the collator which failed validation.
+	invalid_collations: u32,
+	/// When the score was last changed.
+	last_updated: Instant,
+}
+
+impl Default for CollatorScore {
+	fn default() -> Self {
+		Self { reputation: 0, invalid_collations: 0, last_updated: Instant::now() }
+	}
+}
+
+/// Priority of a collator when dequeuing its advertisements. Higher is better.
+type Priority = (Reverse<u32>, i64);
+
+/// Local scores of all collators we recently interacted with.
+#[derive(Debug, Default)]
+pub struct CollatorScores {
+	scores: HashMap<CollatorId, CollatorScore>,
+}
+
+impl CollatorScores {
+	/// Note a reputation change we reported for the collator.
+	pub fn note_reputation_change(&mut self, collator_id: &CollatorId, rep: Rep) {
+		let score = self.scores.entry(collator_id.clone()).or_default();
+		score.reputation = score.reputation.saturating_add(rep.into_base_rep().value as i64);
+		score.last_updated = Instant::now();
+	}
+
+	/// Note that a collation of the collator failed validation.
+	pub fn note_invalid_collation(&mut self, collator_id: &CollatorId) {
+		let score = self.scores.entry(collator_id.clone()).or_default();
+		score.invalid_collations = score.invalid_collations.saturating_add(1);
+		score.last_updated = Instant::now();
+	}
+
+	/// Forget all scores which were not updated within [`COLLATOR_SCORE_RETENTION`].
+	pub fn prune(&mut self) {
+		self.scores
+			.retain(|_, score| score.last_updated.elapsed() < COLLATOR_SCORE_RETENTION);
+	}
+
+	fn priority(&self, collator_id: &CollatorId) -> Priority {
+		self.scores
+			.get(collator_id)
+			.map_or((Reverse(0), 0), |s| (Reverse(s.invalid_collations), s.reputation))
+	}
+}
+
+/// Collations that were advertised to us, but we did not yet fetch.
+#[derive(Debug, Default)]
+pub struct UnfetchedCollations {
+	/// Advertisements in the order they were received.
+	queue: Vec<(PendingCollation, CollatorId)>,
+}
+
+impl UnfetchedCollations {
+	/// Queue an advertised collation.
+	pub fn push(&mut self, pending_collation: PendingCollation, collator_id: CollatorId) {
+		self.queue.push((pending_collation, collator_id));
+	}
+
+	/// Dequeue the advertisement of the collator with the highest priority.
+	pub fn pop(&mut self, scores: &CollatorScores) -> Option<(PendingCollation, CollatorId)> {
+		let (index, _) =
+			self.queue.iter().enumerate().max_by_key(|(index, (_, collator_id))| {
+				(scores.priority(collator_id), Reverse(*index))
+			})?;
+
+		Some(self.queue.remove(index))
+	}
+
+	/// Number of queued advertisements of the given peer.
+	pub fn count_from(&self, peer_id: &PeerId) -> usize {
+		self.queue.iter().filter(|(pc, _)| &pc.peer_id == peer_id).count()
+	}
+}
@@ -59,6 +59,13 @@ use crate::error::Result;
 
 use super::{modify_reputation, tick_stream, LOG_TARGET};
 
+mod collation_queue;
+
+use collation_queue::{
+	CollatorScores, UnfetchedCollations, MAX_ADVERTISEMENTS_PER_RELAY_PARENT,
+	MAX_UNFETCHED_ADVERTISEMENTS_PER_PEER,
+};
+
 #[cfg(test)]
 mod tests;
 
@@ -71,6 +78,8 @@ const COST_INVALID_SIGNATURE: Rep = Rep::Malicious("Invalid network message sign
 const COST_REPORT_BAD: Rep = Rep::Malicious("A collator was reported by another subsystem");
 const COST_WRONG_PARA: Rep = Rep::Malicious("A collator provided a collation for the wrong para");
 const COST_UNNEEDED_COLLATOR: Rep = Rep::CostMinor("An unneeded collator connected");
+const COST_TOO_MANY_ADVERTISEMENTS: Rep =
+	Rep::CostMinor("A collator has too many advertisements waiting to be fetched");
 const BENEFIT_NOTIFY_GOOD: Rep =
 	Rep::BenefitMinor("A collator was noted good by another subsystem");
 
@@ -224,8 +233,8 @@ struct PerRequest {
 struct CollatingPeerState {
 	collator_id: CollatorId,
 	para_id: ParaId,
-	// Advertised relay parents.
-	advertisements: HashSet<Hash>,
+	// Number of accepted advertisements per relay parent.
+	advertisements: HashMap<Hash, usize>,
 	last_active: Instant,
 }
 
@@ -269,24 +278,28 @@ impl PeerData {
 	/// Prune old advertisements relative to our view.
 	fn prune_old_advertisements(&mut self, our_view: &View) {
 		if let PeerState::Collating(ref mut peer_state) = self.state {
-			peer_state.advertisements.retain(|a| our_view.contains(a));
+			peer_state.advertisements.retain(|a, _| our_view.contains(a));
 		}
 	}
 
-	/// Note an advertisement by the collator. Returns `true` if the advertisement was imported
-	/// successfully. Fails if the advertisement is duplicate, out of view, or the peer has not
-	/// declared itself a collator.
-	fn insert_advertisement(
-		&mut self,
+	/// Check an advertisement by the collator. Returns the collator and para id if the
+	/// advertisement can be accepted. Fails if the peer already advertised
+	/// [`MAX_ADVERTISEMENTS_PER_RELAY_PARENT`] collations on the relay parent, it is out of view,
+	/// or the peer has not declared itself a collator.
+	///
+	/// The advertisement is only recorded by [`Self::note_advertisement`].
+	fn check_advertisement(
+		&self,
 		on_relay_parent: Hash,
 		our_view: &View,
 	) -> std::result::Result<(CollatorId, ParaId), AdvertisementError> {
 		match self.state {
 			PeerState::Connected(_) => Err(AdvertisementError::UndeclaredCollator),
 			_ if !our_view.contains(&on_relay_parent) => Err(AdvertisementError::OutOfOurView),
-			PeerState::Collating(ref mut state) =>
-				if state.advertisements.insert(on_relay_parent) {
-					state.last_active = Instant::now();
+			PeerState::Collating(ref state) =>
+				if state.advertisements.get(&on_relay_parent).copied().unwrap_or(0) <
+					MAX_ADVERTISEMENTS_PER_RELAY_PARENT
+				{
 					Ok((state.collator_id.clone(), state.para_id))
 				} else {
 					Err(AdvertisementError::Duplicate)
@@ -294,6 +307,14 @@ impl PeerData {
 		}
 	}
 
+	/// Record an advertisement which passed [`Self::check_advertisement`].
+	fn note_advertisement(&mut self, on_relay_parent: Hash) {
+		if let PeerState::Collating(ref mut state) = self.state {
+			*state.advertisements.entry(on_relay_parent).or_default() += 1;
+			state.last_active = Instant::now();
+		}
+	}
+
 	/// Whether a peer is collating.
 	fn is_collating(&self) -> bool {
 		match self.state {
@@ -310,7 +331,7 @@ impl PeerData {
 		self.state = PeerState::Collating(CollatingPeerState {
 			collator_id,
 			para_id,
-			advertisements: HashSet::new(),
+			advertisements: HashMap::new(),
 			last_active: Instant::now(),
 		});
 	}
@@ -333,7 +354,7 @@ impl PeerData {
 	fn has_advertised(&self, relay_parent: &Hash) -> bool {
 		match self.state {
 			PeerState::Connected(_) => false,
-			PeerState::Collating(ref state) => state.advertisements.contains(relay_parent),
+			PeerState::Collating(ref state) => state.advertisements.contains_key(relay_parent),
 		}
 	}
 
@@ -531,11 +552,12 @@ struct CollationsPerRelayParent {
 	/// yet.
 	waiting_collation: Option<CollatorId>,
 	/// Collation that were advertised to us, but we did not yet fetch.
-	unfetched_collations: Vec<(PendingCollation, CollatorId)>,
+	unfetched_collations: UnfetchedCollations,
 }
 
 impl CollationsPerRelayParent {
-	/// Returns the next collation to fetch from the `unfetched_collations`.
+	/// Returns the next collation to fetch from the `unfetched_collations`, preferring collators
+	/// with a better score.
 	///
 	/// This will reset the status back to `Waiting` using [`CollationStatus::back_to_waiting`].
 	///
@@ -544,6 +566,7 @@ impl CollationsPerRelayParent {
 	pub fn get_next_collation_to_fetch(
 		&mut self,
 		finished_one: Option<&CollatorId>,
+		scores: &CollatorScores,
 	) -> Option<(PendingCollation, CollatorId)> {
 		// If finished one does not match waiting_collation, then we already dequeued another fetch
 		// to replace it.
@@ -562,7 +585,7 @@ impl CollationsPerRelayParent {
 			// We don't need to fetch any other collation when we already have seconded one.
 			CollationStatus::Seconded => None,
 			CollationStatus::Waiting => {
-				let next = self.unfetched_collations.pop();
+				let next = self.unfetched_collations.pop(scores);
 				self.waiting_collation = next.as_ref().map(|(_, collator_id)| collator_id.clone());
 				next
 			},
@@ -612,6 +635,9 @@ struct State {
 
 	/// Keep track of all pending candidate collations
 	pending_candidates: HashMap<Hash, CollationEvent>,
+
+	/// Local scores of collators, used to decide which advertisement to fetch next.
+	collator_scores: CollatorScores,
 }
 
 // O(n) search for collator ID by iterating through the peers map. This should be fast enough
@@ -673,12 +699,21 @@ async fn fetch_collation(
 	state.collation_fetches.push(rx.map(|r| ((id, pc), r)).boxed());
 }
 
+/// Record an advertisement of the peer, whether or not the collation is going to be fetched.
+fn note_advertisement(state: &mut State, peer_id: &PeerId, relay_parent: Hash) {
+	if let Some(peer_data) = state.peer_data.get_mut(peer_id) {
+		peer_data.note_advertisement(relay_parent);
+	}
+}
+
 /// Report a collator for some malicious actions.
 async fn report_collator(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	collator_scores: &mut CollatorScores,
 	id: CollatorId,
 ) {
+	collator_scores.note_reputation_change(&id, COST_REPORT_BAD);
 	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
 		modify_reputation(sender, peer_id, COST_REPORT_BAD).await;
 	}
@@ -688,8 +723,10 @@ async fn report_collator(
 async fn note_good_collation(
 	sender: &mut impl overseer::CollatorProtocolSenderTrait,
 	peer_data: &HashMap<PeerId, PeerData>,
+	collator_scores: &mut CollatorScores,
 	id: CollatorId,
 ) {
+	collator_scores.note_reputation_change(&id, BENEFIT_NOTIFY_GOOD);
 	if let Some(peer_id) = collator_peer_id(peer_data, &id) {
 		modify_reputation(sender, peer_id, BENEFIT_NOTIFY_GOOD).await;
 	}
@@ -894,7 +931,7 @@ async fn process_incoming_peer_message<Context>(
 				return
 			}
 
-			let peer_data = match state.peer_data.get_mut(&origin) {
+			let peer_data = match state.peer_data.get(&origin) {
 				None => {
 					gum::debug!(
 						target: LOG_TARGET,
@@ -908,60 +945,78 @@ async fn process_incoming_peer_message<Context>(
 				Some(p) => p,
 			};
 
-			match peer_data.insert_advertisement(relay_parent, &state.view) {
-				Ok((id, para_id)) => {
+			let (id, para_id) = match peer_data.check_advertisement(relay_parent, &state.view) {
+				Ok(ids) => ids,
+				Err(error) => {
 					gum::debug!(
 						target: LOG_TARGET,
 						peer_id = ?origin,
-						%para_id,
 						?relay_parent,
-						"Received advertise collation",
+						?error,
+						"Invalid advertisement",
 					);
 
-					let pending_collation = PendingCollation::new(relay_parent, &para_id, &origin);
+					modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+					return
+				},
+			};
 
-					let collations =
-						state.collations_per_relay_parent.entry(relay_parent).or_default();
+			gum::debug!(
+				target: LOG_TARGET,
+				peer_id = ?origin,
+				%para_id,
+				?relay_parent,
+				"Received advertise collation",
+			);
 
-					match collations.status {
-						CollationStatus::Fetching | CollationStatus::WaitingOnValidation => {
-							gum::trace!(
-								target: LOG_TARGET,
-								peer_id = ?origin,
-								%para_id,
-								?relay_parent,
-								"Added collation to the pending list"
-							);
-							collations.unfetched_collations.push((pending_collation, id));
-						},
-						CollationStatus::Waiting => {
-							collations.status = CollationStatus::Fetching;
-							collations.waiting_collation = Some(id.clone());
-
-							fetch_collation(ctx.sender(), state, pending_collation.clone(), id)
-								.await;
-						},
-						CollationStatus::Seconded => {
-							gum::trace!(
-								target: LOG_TARGET,
-								peer_id = ?origin,
-								%para_id,
-								?relay_parent,
-								"Valid seconded collation"
-							);
-						},
+			let pending_collation = PendingCollation::new(relay_parent, &para_id, &origin);
+
+			note_advertisement(state, &origin, relay_parent);
+
+			let collations = state.collations_per_relay_parent.entry(relay_parent).or_default();
+
+			match collations.status {
+				CollationStatus::Fetching | CollationStatus::WaitingOnValidation => {
+					if collations.unfetched_collations.count_from(&origin) >=
+						MAX_UNFETCHED_ADVERTISEMENTS_PER_PEER
+					{
+						gum::debug!(
+							target: LOG_TARGET,
+							peer_id = ?origin,
+							%para_id,
+							?relay_parent,
+							"Too many advertisements waiting to be fetched, dropping",
+						);
+						state
+							.collator_scores
+							.note_reputation_change(&id, COST_TOO_MANY_ADVERTISEMENTS);
+						modify_reputation(ctx.sender(), origin, COST_TOO_MANY_ADVERTISEMENTS).await;
+						return
 					}
-				},
-				Err(error) => {
-					gum::debug!(
+
+					gum::trace!(
 						target: LOG_TARGET,
 						peer_id = ?origin,
+						%para_id,
 						?relay_parent,
-						?error,
-						"Invalid advertisement",
+						"Added collation to the pending list"
 					);
+					collations.unfetched_collations.push(pending_collation, id);
+				},
+				CollationStatus::Waiting => {
+					collations.status = CollationStatus::Fetching;
+					collations.waiting_collation = Some(id.clone());
 
-					modify_reputation(ctx.sender(), origin, COST_UNEXPECTED_MESSAGE).await;
+					fetch_collation(ctx.sender(), state, pending_collation.clone(), id).await;
+				},
+				CollationStatus::Seconded => {
+					gum::trace!(
+						target: LOG_TARGET,
+						peer_id = ?origin,
+						%para_id,
+						?relay_parent,
+						"Valid seconded collation"
+					);
 				},
 			}
 		},
@@ -1106,7 +1161,7 @@ async fn process_msg<Context>(
 			);
 		},
 		ReportCollator(id) => {
-			report_collator(ctx.sender(), &state.peer_data, id).await;
+			report_collator(ctx.sender(), &state.peer_data, &mut state.collator_scores, id).await;
 		},
 		NetworkBridgeUpdate(event) => {
 			if let Err(e) = handle_network_msg(ctx, state, keystore, event).await {
@@ -1121,7 +1176,13 @@ async fn process_msg<Context>(
 			if let Some(collation_event) = state.pending_candidates.remove(&parent) {
 				let (collator_id, pending_collation) = collation_event;
 				let PendingCollation { relay_parent, peer_id, .. } = pending_collation;
-				note_good_collation(ctx.sender(), &state.peer_data, collator_id).await;
+				note_good_collation(
+					ctx.sender(),
+					&state.peer_data,
+					&mut state.collator_scores,
+					collator_id,
+				)
+				.await;
 				notify_collation_seconded(ctx.sender(), peer_id, relay_parent, stmt).await;
 
 				if let Some(collations) = state.collations_per_relay_parent.get_mut(&parent) {
@@ -1153,10 +1214,18 @@ async fn process_msg<Context>(
 				Entry::Vacant(_) => return,
 			};
 
-			report_collator(ctx.sender(), &state.peer_data, id.clone()).await;
+			state.collator_scores.note_invalid_collation(&id);
+			report_collator(ctx.sender(), &state.peer_data, &mut state.collator_scores, id.clone())
+				.await;
 
 			dequeue_next_collation_and_fetch(ctx, state, parent, id).await;
 		},
+		DeliveryReports(_) => {
+			gum::warn!(
+				target: LOG_TARGET,
+				"DeliveryReports message is not expected on the validator side of the protocol",
+			);
+		},
 	}
 }
 
@@ -1195,6 +1264,7 @@ pub(crate) async fn run<Context>(
 			}
 			_ = next_inactivity_stream.next() => {
 				disconnect_inactive_peers(ctx.sender(), &eviction_policy, &state.peer_data).await;
+				state.collator_scores.prune();
 			}
 			res = state.collation_fetches.select_next_some() => {
 				handle_collation_fetched_result(&mut ctx, &mut state, res).await;
@@ -1217,6 +1287,11 @@ pub(crate) async fn run<Context>(
 				).await;
 
 				for (peer_id, rep) in reputation_changes {
+					if let Some(collator_id) =
+						state.peer_data.get(&peer_id).and_then(|p| p.collator_id())
+					{
+						state.collator_scores.note_reputation_change(collator_id, rep);
+					}
 					modify_reputation(ctx.sender(), peer_id, rep).await;
 				}
 			},
@@ -1262,7 +1337,7 @@ async fn dequeue_next_collation_and_fetch<Context>(
 	if let Some((next, id)) = state
 		.collations_per_relay_parent
 		.get_mut(&relay_parent)
-		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch)))
+		.and_then(|c| c.get_next_collation_to_fetch(Some(&previous_fetch), &state.collator_scores))
 	{
 		gum::debug!(
 			target: LOG_TARGET,
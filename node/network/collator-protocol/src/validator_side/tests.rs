@@ -822,6 +822,222 @@ fn fetch_next_collation_on_invalid_collation() {
 	});
 }
 
+// Ensure that collators whose collations failed validation are fetched from last.
+#[test]
+fn collators_with_invalid_collations_are_fetched_last() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let second = Hash::random();
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![test_state.relay_parent, second],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+		let peer_d = PeerId::random();
+
+		for (peer, collator) in [peer_b, peer_c, peer_d].into_iter().zip(&test_state.collators) {
+			connect_and_declare_collator(
+				&mut virtual_overseer,
+				peer,
+				collator.clone(),
+				test_state.chain_ids[0],
+			)
+			.await;
+		}
+
+		// The collation of `peer_b` fails validation.
+		advertise_collation(&mut virtual_overseer, peer_b, test_state.relay_parent).await;
+
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		let pov = PoV { block_data: BlockData(vec![]) };
+		let mut candidate_a =
+			dummy_candidate_receipt_bad_sig(dummy_hash(), Some(Default::default()));
+		candidate_a.descriptor.para_id = test_state.chain_ids[0];
+		candidate_a.descriptor.relay_parent = test_state.relay_parent;
+		response_channel
+			.send(Ok(CollationFetchingResponse::Collation(candidate_a, pov.clone()).encode()))
+			.expect("Sending response should succeed");
+
+		let receipt = assert_candidate_backing_second(
+			&mut virtual_overseer,
+			test_state.relay_parent,
+			test_state.chain_ids[0],
+			&pov,
+		)
+		.await;
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::Invalid(test_state.relay_parent, receipt),
+		)
+		.await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_REPORT_BAD);
+			}
+		);
+
+		// On the next relay parent `peer_b` advertises before `peer_c`, while we are busy
+		// fetching from `peer_d`.
+		advertise_collation(&mut virtual_overseer, peer_d, second).await;
+		let response_channel =
+			assert_fetch_collation_request(&mut virtual_overseer, second, test_state.chain_ids[0])
+				.await;
+
+		advertise_collation(&mut virtual_overseer, peer_b, second).await;
+		advertise_collation(&mut virtual_overseer, peer_c, second).await;
+
+		// Dropping the response channel should lead to fetching from `peer_c` first.
+		drop(response_channel);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(reqs, _)) => {
+				assert_matches!(
+					&reqs[..],
+					[Requests::CollationFetchingV1(req)] => {
+						assert_eq!(req.peer, Recipient::Peer(peer_c));
+						assert_eq!(req.payload.relay_parent, second);
+					}
+				);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
+// Ensure that a collator cannot queue more than one advertisement per relay parent and that
+// repeated advertisements are punished, whether or not the first one was fetched.
+#[test]
+fn advertisements_per_relay_parent_are_limited() {
+	let test_state = TestState::default();
+
+	test_harness(|test_harness| async move {
+		let TestHarness { mut virtual_overseer } = test_harness;
+
+		let relay_parent_a = test_state.relay_parent;
+		let relay_parent_b = Hash::repeat_byte(0x42);
+
+		overseer_send(
+			&mut virtual_overseer,
+			CollatorProtocolMessage::NetworkBridgeUpdate(NetworkBridgeEvent::OurViewChange(
+				our_view![relay_parent_a, relay_parent_b],
+			)),
+		)
+		.await;
+
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+		respond_to_core_info_queries(&mut virtual_overseer, &test_state).await;
+
+		let peer_b = PeerId::random();
+		let peer_c = PeerId::random();
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_b,
+			test_state.collators[0].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		connect_and_declare_collator(
+			&mut virtual_overseer,
+			peer_c,
+			test_state.collators[1].clone(),
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// Keep the first relay parent busy with a fetch from `peer_c`.
+		advertise_collation(&mut virtual_overseer, peer_c, relay_parent_a).await;
+		let response_channel = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			relay_parent_a,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// The advertisement of `peer_b` is queued silently, repeating it gets punished.
+		advertise_collation(&mut virtual_overseer, peer_b, relay_parent_a).await;
+		assert!(
+			overseer_recv_with_timeout(&mut virtual_overseer, Duration::from_millis(50))
+				.await
+				.is_none(),
+			"Queueing an advertisement must not be punished",
+		);
+		advertise_collation(&mut virtual_overseer, peer_b, relay_parent_a).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_b);
+				assert_eq!(rep, COST_UNEXPECTED_MESSAGE);
+			}
+		);
+
+		// The limit applies per relay parent.
+		advertise_collation(&mut virtual_overseer, peer_b, relay_parent_b).await;
+		let _response_channel_b = assert_fetch_collation_request(
+			&mut virtual_overseer,
+			relay_parent_b,
+			test_state.chain_ids[0],
+		)
+		.await;
+
+		// The queued advertisement of `peer_b` is fetched once the fetch from `peer_c` failed.
+		drop(response_channel);
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::SendRequests(reqs, _)) => {
+				assert_matches!(
+					&reqs[..],
+					[Requests::CollationFetchingV1(req)] => {
+						assert_eq!(req.peer, Recipient::Peer(peer_b));
+						assert_eq!(req.payload.relay_parent, relay_parent_a);
+					}
+				);
+			}
+		);
+
+		// `peer_c` can't offer another collation on the relay parent either, even though fetching
+		// its first one failed.
+		advertise_collation(&mut virtual_overseer, peer_c, relay_parent_a).await;
+
+		assert_matches!(
+			overseer_recv(&mut virtual_overseer).await,
+			AllMessages::NetworkBridgeTx(NetworkBridgeTxMessage::ReportPeer(peer, rep)) => {
+				assert_eq!(peer, peer_c);
+				assert_eq!(rep, COST_UNEXPECTED_MESSAGE);
+			}
+		);
+
+		virtual_overseer
+	});
+}
+
 #[test]
 fn inactive_disconnected() {
 	let test_state = TestState::default();
@@ -74,14 +74,15 @@ use lru::LruCache;
 use client::{BlockImportNotification, BlockchainEvents, FinalityNotification};
 use polkadot_primitives::{Block, BlockNumber, Hash};
 
-use self::messages::{BitfieldSigningMessage, PvfCheckerMessage};
+use self::messages::BitfieldSigningMessage;
 use polkadot_node_subsystem_types::messages::{
 	ApprovalDistributionMessage, ApprovalVotingMessage, AvailabilityDistributionMessage,
 	AvailabilityRecoveryMessage, AvailabilityStoreMessage, BitfieldDistributionMessage,
 	CandidateBackingMessage, CandidateValidationMessage, ChainApiMessage, ChainSelectionMessage,
 	CollationGenerationMessage, CollatorProtocolMessage, DisputeCoordinatorMessage,
 	DisputeDistributionMessage, GossipSupportMessage, NetworkBridgeRxMessage,
-	NetworkBridgeTxMessage, ProvisionerMessage, RuntimeApiMessage, StatementDistributionMessage,
+	NetworkBridgeTxMessage, ProvisionerMessage, PvfCheckerMessage, RuntimeApiMessage,
+	StatementDistributionMessage,
 };
 
 pub use polkadot_node_subsystem_types::{
@@ -458,7 +459,7 @@ pub struct Overseer<SupportsParachains> {
 	])]
 	candidate_validation: CandidateValidation,
 
-	#[subsystem(sends: [
+	#[subsystem(PvfCheckerMessage, sends: [
 		CandidateValidationMessage,
 		RuntimeApiMessage,
 	])]
@@ -30,10 +30,10 @@ use parity_scale_codec::{Decode, Encode, Error as CodecError, Input};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 
 use polkadot_primitives::{
-	BlakeTwo256, BlockNumber, CandidateCommitments, CandidateHash, CollatorPair,
-	CommittedCandidateReceipt, CompactStatement, EncodeAs, Hash, HashT, HeadData, Id as ParaId,
-	PersistedValidationData, SessionIndex, Signed, UncheckedSigned, ValidationCode, ValidatorIndex,
-	MAX_CODE_SIZE, MAX_POV_SIZE,
+	AuthorityDiscoveryId, BlakeTwo256, BlockNumber, CandidateCommitments, CandidateHash,
+	CollatorPair, CommittedCandidateReceipt, CompactStatement, EncodeAs, Hash, HashT, HeadData,
+	Id as ParaId, PersistedValidationData, SessionIndex, Signed, UncheckedSigned, ValidationCode,
+	ValidatorId, ValidatorIndex, MAX_CODE_SIZE, MAX_POV_SIZE,
 };
 pub use sp_consensus_babe::{
 	AllowedSlots as BabeAllowedSlots, BabeEpochConfiguration, Epoch as BabeEpoch,
@@ -263,6 +263,59 @@ pub enum ValidationResult {
 	Invalid(InvalidCandidate),
 }
 
+/// Outputs of validating a PoV which were produced by the PVF.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DryRunOutputs {
+	/// The head data of the para after the block.
+	pub head_data: HeadData,
+	/// Messages sent upward to the relay chain.
+	pub upward_messages: Vec<sp_core::Bytes>,
+	/// Messages sent to other paras, together with the recipient.
+	pub horizontal_messages: Vec<(ParaId, sp_core::Bytes)>,
+	/// The new validation code, if the para signalled an upgrade.
+	pub new_validation_code: Option<ValidationCode>,
+	/// The number of downward messages processed.
+	pub processed_downward_messages: u32,
+	/// The mark which specifies the block number up to which all inbound HRMP messages are
+	/// processed.
+	pub hrmp_watermark: BlockNumber,
+}
+
+/// Outcome of validating a PoV without a candidate produced by a collator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DryRunOutcome {
+	/// The PoV is valid.
+	Valid(DryRunOutputs),
+	/// The PoV is invalid for the given reason.
+	Invalid(String),
+}
+
+impl From<ValidationResult> for DryRunOutcome {
+	fn from(result: ValidationResult) -> Self {
+		match result {
+			ValidationResult::Valid(commitments, _) => DryRunOutcome::Valid(DryRunOutputs {
+				head_data: commitments.head_data,
+				upward_messages: commitments
+					.upward_messages
+					.into_iter()
+					.map(sp_core::Bytes)
+					.collect(),
+				horizontal_messages: commitments
+					.horizontal_messages
+					.into_iter()
+					.map(|msg| (msg.recipient, sp_core::Bytes(msg.data)))
+					.collect(),
+				new_validation_code: commitments.new_validation_code,
+				processed_downward_messages: commitments.processed_downward_messages,
+				hrmp_watermark: commitments.hrmp_watermark,
+			}),
+			ValidationResult::Invalid(reason) => DryRunOutcome::Invalid(format!("{:?}", reason)),
+		}
+	}
+}
+
 /// A Proof-of-Validity
 #[derive(PartialEq, Eq, Clone, Encode, Decode, Debug)]
 pub struct PoV {
@@ -337,6 +390,161 @@ pub struct CollationSecondedSignal {
 	pub statement: SignedFullStatement,
 }
 
+/// What became of a distributed collation, as far as the collator can tell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CollationDeliveryOutcome {
+	/// No validator of the group assigned to our core was connected, so the collation was never
+	/// advertised.
+	NotAdvertised,
+	/// The collation was advertised, but no validator requested it.
+	NotRequested,
+	/// The collation was fetched, but no validator seconded it (yet). Once the relay parent went
+	/// out of view this usually means that the collation failed validation.
+	NotSeconded,
+	/// At least one validator seconded the collation.
+	Seconded,
+}
+
+/// A single collation fetch by a validator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollationFetch {
+	/// Authority discovery keys of the requesting peer, empty if it is not a known validator.
+	pub validator: Vec<AuthorityDiscoveryId>,
+	/// Milliseconds between advertising the collation to the peer and its request. `None` if the
+	/// peer requested the collation without us advertising it.
+	pub time_to_fetch_ms: Option<u64>,
+	/// Milliseconds it took to upload the collation. `None` while the upload is in progress or if
+	/// it timed out.
+	pub upload_ms: Option<u64>,
+	/// Whether the upload took longer than we were willing to wait before serving the next
+	/// validator.
+	pub timed_out: bool,
+	/// Number of bytes served, that is the encoded candidate receipt and PoV.
+	pub bytes: u64,
+}
+
+/// Delivery report for a collation distributed by the collator.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollationDeliveryReport {
+	/// The relay parent of the collation.
+	pub relay_parent: Hash,
+	/// The hash of the candidate.
+	pub candidate_hash: Hash,
+	/// The para the collation is for.
+	pub para_id: ParaId,
+	/// Authority discovery keys of the validators assigned to our core.
+	pub group: Vec<AuthorityDiscoveryId>,
+	/// Validators the collation was advertised to.
+	pub advertised_to: Vec<AuthorityDiscoveryId>,
+	/// All fetches of the collation, in the order they were requested.
+	pub fetches: Vec<CollationFetch>,
+	/// Validators which reported back seconding the collation.
+	pub seconded_by: Vec<ValidatorIndex>,
+	/// The resulting outcome.
+	pub outcome: CollationDeliveryOutcome,
+}
+
+/// The class of error which made a PVF fail pre-checking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PvfRejectionClass {
+	/// The validation code could not be fetched from the relay chain.
+	CodeUnavailable,
+	/// The executor parameters of the session could not be fetched from the relay chain.
+	ExecutorParamsUnavailable,
+	/// The validation code could not be decompressed.
+	Decompression,
+	/// The validation code is not a valid wasm module.
+	Prevalidation,
+	/// The validation code failed to compile.
+	Preparation,
+	/// The compiler panicked.
+	Panic,
+	/// Compilation took longer than the pre-checking time limit.
+	TimedOut,
+	/// The preparation worker died or failed to report back, e.g. because the machine ran out of
+	/// memory.
+	WorkerFailure,
+}
+
+/// Explanation of why a PVF failed pre-checking on this node.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PvfRejection {
+	/// The class of the error.
+	pub class: PvfRejectionClass,
+	/// The error as reported by the validation host.
+	pub reason: String,
+	/// Milliseconds spent compiling the PVF in the last attempt. `None` if it was not compiled.
+	pub compile_time_ms: Option<u64>,
+	/// Peak memory used by the preparation worker in the last attempt, in KiB. `None` if the
+	/// worker did not report it, e.g. because it timed out or died.
+	pub memory_used_kb: Option<u64>,
+	/// Wasm features used by the PVF which are not enabled by the executor parameters.
+	pub wasm_feature_violations: Vec<String>,
+	/// The number of times the PVF was pre-checked.
+	pub attempts: u32,
+}
+
+/// A PVF this node voted against, together with the explanation.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PvfRejectionReport {
+	/// The hash of the validation code.
+	pub validation_code_hash: Hash,
+	/// The session in which the PVF was pre-checked, if known.
+	pub session_index: Option<SessionIndex>,
+	/// Why the PVF was rejected.
+	pub rejection: PvfRejection,
+}
+
+/// The kind of misbehavior detected in the statement table of candidate backing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BackingMisbehaviorKind {
+	/// Seconded a candidate and issued a separate validity statement on it.
+	ValidityDoubleVote,
+	/// Seconded more than one candidate at the same relay parent.
+	MultipleCandidates,
+	/// Issued a statement about a candidate of a group it is not part of.
+	UnauthorizedStatement,
+	/// Signed the same statement twice with different signatures.
+	DoubleSign,
+}
+
+/// A misbehavior of a validator detected during candidate backing.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackingMisbehaviorReport {
+	/// The session the misbehavior happened in.
+	pub session_index: SessionIndex,
+	/// The relay parent of the statement table the misbehavior was detected in.
+	pub relay_parent: Hash,
+	/// The index of the misbehaving validator in the session.
+	pub validator_index: ValidatorIndex,
+	/// The key of the misbehaving validator.
+	pub validator_id: ValidatorId,
+	/// What the validator did.
+	pub kind: BackingMisbehaviorKind,
+	/// The SCALE-encoded `polkadot_statement_table::v2::Misbehavior`, containing the signed
+	/// statements proving the misbehavior.
+	#[serde(with = "sp_core::bytes")]
+	pub evidence: Vec<u8>,
+}
+
+/// A page of the backing misbehavior reports recorded by a node.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackingMisbehaviorPage {
+	/// The reports of this page, ordered by session and validator index.
+	pub reports: Vec<BackingMisbehaviorReport>,
+	/// The key to start the next page after, `None` if this is the last page.
+	pub next_key: Option<sp_core::Bytes>,
+}
+
 /// Result of the [`CollatorFn`] invocation.
 #[cfg(not(target_os = "unknown"))]
 pub struct CollationResult {
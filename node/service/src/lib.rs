@@ -27,7 +27,9 @@ mod relay_chain_selection;
 pub mod overseer;
 
 #[cfg(feature = "full-node")]
-pub use self::overseer::{OverseerGen, OverseerGenArgs, RealOverseerGen};
+pub use self::overseer::{
+	OverseerGen, OverseerGenArgs, ProvisioningPolicyOverseerGen, RealOverseerGen,
+};
 
 #[cfg(test)]
 mod tests;
@@ -41,6 +43,7 @@ use {
 	},
 	polkadot_node_core_av_store::Config as AvailabilityConfig,
 	polkadot_node_core_av_store::Error as AvailabilityError,
+	polkadot_node_core_backing::Config as CandidateBackingConfig,
 	polkadot_node_core_candidate_validation::Config as CandidateValidationConfig,
 	polkadot_node_core_chain_selection::{
 		self as chain_selection_subsystem, Config as ChainSelectionConfig,
@@ -58,6 +61,11 @@ use polkadot_node_subsystem_util::database::Database;
 
 #[cfg(feature = "full-node")]
 pub use {
+	polkadot_node_core_bitfield_signing::Config as BitfieldSigningConfig,
+	polkadot_node_core_candidate_validation::{
+		PvfArtifactImport, PvfImportVerification, PvfWorkerPools,
+	},
+	polkadot_node_core_pvf_checker::RetryPolicy as PvfCheckerRetryPolicy,
 	polkadot_overseer::{Handle, Overseer, OverseerConnector, OverseerHandle},
 	polkadot_primitives::runtime_api::ParachainHost,
 	relay_chain_selection::SelectRelayChain,
@@ -698,6 +706,11 @@ pub fn new_full<RuntimeApi, ExecutorDispatch, OverseerGenerator>(
 	overseer_message_channel_capacity_override: Option<usize>,
 	_malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	pvf_worker_pools: PvfWorkerPools,
+	pvf_artifact_import: Option<PvfArtifactImport>,
+	pvf_count_host_calls: bool,
+	pvf_checker_retry_policy: PvfCheckerRetryPolicy,
+	bitfield_signing_config: BitfieldSigningConfig,
 ) -> Result<NewFull<Arc<FullClient<RuntimeApi, ExecutorDispatch>>>, Error>
 where
 	RuntimeApi: ConstructRuntimeApi<Block, FullClient<RuntimeApi, ExecutorDispatch>>
@@ -789,6 +802,48 @@ where
 		select_chain,
 	)?;
 
+	// RPCs which are answered by subsystems need the overseer, which is not available yet
+	// when the generic RPC extensions are set up.
+	let rpc_extensions_builder = {
+		let collator_overseer_handle = is_collator.is_collator().then(|| overseer_handle.clone());
+		let pvf_checker_overseer_handle = pvf_checker_enabled.then(|| overseer_handle.clone());
+		let validation_overseer_handle =
+			(auth_or_collator || overseer_enable_anyways).then(|| overseer_handle.clone());
+		let client = client.clone();
+
+		move |deny_unsafe,
+		      subscription_executor: polkadot_rpc::SubscriptionTaskExecutor|
+		      -> Result<polkadot_rpc::RpcExtension, service::Error> {
+			use polkadot_rpc::{
+				backing::{Backing, BackingApiServer},
+				candidate_validation::{CandidateValidation, CandidateValidationApiServer},
+				collator::{Collator, CollatorApiServer},
+				pvf_checker::{PvfChecker, PvfCheckerApiServer},
+			};
+
+			let mut io = rpc_extensions_builder(deny_unsafe, subscription_executor)?;
+			if let Some(overseer_handle) = collator_overseer_handle.clone() {
+				io.merge(Collator::new(overseer_handle).into_rpc())
+					.map_err(|e| service::Error::Application(e.into()))?;
+			}
+			if let Some(overseer_handle) = pvf_checker_overseer_handle.clone() {
+				io.merge(PvfChecker::new(overseer_handle).into_rpc())
+					.map_err(|e| service::Error::Application(e.into()))?;
+			}
+			if let Some(overseer_handle) = validation_overseer_handle.clone() {
+				io.merge(Backing::new(overseer_handle.clone(), deny_unsafe).into_rpc())
+					.map_err(|e| service::Error::Application(e.into()))?;
+				io.merge(
+					CandidateValidation::new(client.clone(), overseer_handle, deny_unsafe)
+						.into_rpc(),
+				)
+				.map_err(|e| service::Error::Application(e.into()))?;
+			}
+
+			Ok(io)
+		}
+	};
+
 	let shared_voter_state = rpc_setup;
 	let auth_disc_publish_non_global_ips = config.network.allow_non_globals_in_dht;
 	let mut net_config = sc_network::config::FullNetworkConfiguration::new(&config.network);
@@ -900,6 +955,10 @@ where
 		slot_duration_millis: slot_duration.as_millis() as u64,
 	};
 
+	let candidate_backing_config = CandidateBackingConfig {
+		col_misbehavior_data: parachains_db::REAL_COLUMNS.col_backing_misbehavior_data,
+	};
+
 	let candidate_validation_config = CandidateValidationConfig {
 		artifacts_cache_path: config
 			.database
@@ -910,6 +969,9 @@ where
 			None => std::env::current_exe()?,
 			Some(p) => p,
 		},
+		pvf_worker_pools,
+		pvf_artifact_import,
+		pvf_count_host_calls,
 	};
 
 	let chain_selection_config = ChainSelectionConfig {
@@ -1025,10 +1087,13 @@ where
 					is_collator,
 					approval_voting_config,
 					availability_config: AVAILABILITY_CONFIG,
+					candidate_backing_config,
 					candidate_validation_config,
 					chain_selection_config,
 					dispute_coordinator_config,
 					pvf_checker_enabled,
+					pvf_checker_retry_policy,
+					bitfield_signing_config,
 					overseer_message_channel_capacity_override,
 					req_protocol_names,
 					peerset_protocol_names,
@@ -1358,6 +1423,11 @@ pub fn build_full(
 	overseer_message_channel_override: Option<usize>,
 	malus_finality_delay: Option<u32>,
 	hwbench: Option<sc_sysinfo::HwBench>,
+	pvf_worker_pools: PvfWorkerPools,
+	pvf_artifact_import: Option<PvfArtifactImport>,
+	pvf_count_host_calls: bool,
+	pvf_checker_retry_policy: PvfCheckerRetryPolicy,
+	bitfield_signing_config: BitfieldSigningConfig,
 ) -> Result<NewFull<Client>, Error> {
 	#[cfg(feature = "rococo-native")]
 	if config.chain_spec.is_rococo() ||
@@ -1377,6 +1447,11 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			pvf_worker_pools,
+			pvf_artifact_import,
+			pvf_count_host_calls,
+			pvf_checker_retry_policy,
+			bitfield_signing_config,
 		)
 		.map(|full| full.with_client(Client::Rococo))
 	}
@@ -1396,6 +1471,11 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			pvf_worker_pools,
+			pvf_artifact_import,
+			pvf_count_host_calls,
+			pvf_checker_retry_policy,
+			bitfield_signing_config,
 		)
 		.map(|full| full.with_client(Client::Kusama))
 	}
@@ -1415,6 +1495,11 @@ pub fn build_full(
 			overseer_message_channel_override,
 			malus_finality_delay,
 			hwbench,
+			pvf_worker_pools,
+			pvf_artifact_import,
+			pvf_count_host_calls,
+			pvf_checker_retry_policy,
+			bitfield_signing_config,
 		)
 		.map(|full| full.with_client(Client::Westend))
 	}
@@ -1437,6 +1522,11 @@ pub fn build_full(
 			}),
 			malus_finality_delay,
 			hwbench,
+			pvf_worker_pools,
+			pvf_artifact_import,
+			pvf_count_host_calls,
+			pvf_checker_retry_policy,
+			bitfield_signing_config,
 		)
 		.map(|full| full.with_client(Client::Polkadot))
 	}
@@ -1454,6 +1544,11 @@ pub fn build_full(
 		let _ = overseer_message_channel_override;
 		let _ = malus_finality_delay;
 		let _ = hwbench;
+		let _ = pvf_worker_pools;
+		let _ = pvf_artifact_import;
+		let _ = pvf_count_host_calls;
+		let _ = pvf_checker_retry_policy;
+		let _ = bitfield_signing_config;
 
 		Err(Error::NoRuntime)
 	}
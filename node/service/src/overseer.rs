@@ -21,6 +21,7 @@ use lru::LruCache;
 use polkadot_availability_distribution::IncomingRequestReceivers;
 use polkadot_node_core_approval_voting::Config as ApprovalVotingConfig;
 use polkadot_node_core_av_store::Config as AvailabilityConfig;
+use polkadot_node_core_backing::Config as CandidateBackingConfig;
 use polkadot_node_core_candidate_validation::Config as CandidateValidationConfig;
 use polkadot_node_core_chain_selection::Config as ChainSelectionConfig;
 use polkadot_node_core_dispute_coordinator::Config as DisputeCoordinatorConfig;
@@ -63,13 +64,19 @@ pub use polkadot_node_collation_generation::CollationGenerationSubsystem;
 pub use polkadot_node_core_approval_voting::ApprovalVotingSubsystem;
 pub use polkadot_node_core_av_store::AvailabilityStoreSubsystem;
 pub use polkadot_node_core_backing::CandidateBackingSubsystem;
-pub use polkadot_node_core_bitfield_signing::BitfieldSigningSubsystem;
+pub use polkadot_node_core_bitfield_signing::{
+	BitfieldSigningSubsystem, Config as BitfieldSigningConfig,
+};
 pub use polkadot_node_core_candidate_validation::CandidateValidationSubsystem;
 pub use polkadot_node_core_chain_api::ChainApiSubsystem;
 pub use polkadot_node_core_chain_selection::ChainSelectionSubsystem;
 pub use polkadot_node_core_dispute_coordinator::DisputeCoordinatorSubsystem;
-pub use polkadot_node_core_provisioner::ProvisionerSubsystem;
-pub use polkadot_node_core_pvf_checker::PvfCheckerSubsystem;
+pub use polkadot_node_core_provisioner::{
+	DefaultProvisioningPolicy, ProvisionerSubsystem, ProvisioningPolicy,
+};
+pub use polkadot_node_core_pvf_checker::{
+	PvfCheckerSubsystem, RetryPolicy as PvfCheckerRetryPolicy,
+};
 pub use polkadot_node_core_runtime_api::RuntimeApiSubsystem;
 use polkadot_node_subsystem_util::rand::{self, SeedableRng};
 pub use polkadot_statement_distribution::StatementDistributionSubsystem;
@@ -111,6 +118,8 @@ where
 	pub approval_voting_config: ApprovalVotingConfig,
 	/// Configuration for the availability store subsystem.
 	pub availability_config: AvailabilityConfig,
+	/// Configuration for the candidate backing subsystem.
+	pub candidate_backing_config: CandidateBackingConfig,
 	/// Configuration for the candidate validation subsystem.
 	pub candidate_validation_config: CandidateValidationConfig,
 	/// Configuration for the chain selection subsystem.
@@ -119,6 +128,10 @@ where
 	pub dispute_coordinator_config: DisputeCoordinatorConfig,
 	/// Enable PVF pre-checking
 	pub pvf_checker_enabled: bool,
+	/// How the PVF pre-checker retries failed pre-checks.
+	pub pvf_checker_retry_policy: PvfCheckerRetryPolicy,
+	/// Configuration for the bitfield signing subsystem.
+	pub bitfield_signing_config: BitfieldSigningConfig,
 	/// Overseer channel capacity override.
 	pub overseer_message_channel_capacity_override: Option<usize>,
 	/// Request-response protocol names source.
@@ -148,10 +161,13 @@ pub fn prepared_overseer_builder<Spawner, RuntimeClient>(
 		is_collator,
 		approval_voting_config,
 		availability_config,
+		candidate_backing_config,
 		candidate_validation_config,
 		chain_selection_config,
 		dispute_coordinator_config,
 		pvf_checker_enabled,
+		pvf_checker_retry_policy,
+		bitfield_signing_config,
 		overseer_message_channel_capacity_override,
 		req_protocol_names,
 		peerset_protocol_names,
@@ -235,12 +251,15 @@ where
 			Metrics::register(registry)?,
 		))
 		.bitfield_distribution(BitfieldDistributionSubsystem::new(Metrics::register(registry)?))
-		.bitfield_signing(BitfieldSigningSubsystem::new(
+		.bitfield_signing(BitfieldSigningSubsystem::with_config(
 			keystore.clone(),
+			bitfield_signing_config,
 			Metrics::register(registry)?,
 		))
 		.candidate_backing(CandidateBackingSubsystem::new(
 			keystore.clone(),
+			parachains_db.clone(),
+			candidate_backing_config,
 			Metrics::register(registry)?,
 		))
 		.candidate_validation(CandidateValidationSubsystem::with_config(
@@ -251,6 +270,7 @@ where
 		.pvf_checker(PvfCheckerSubsystem::new(
 			pvf_checker_enabled,
 			keystore.clone(),
+			pvf_checker_retry_policy,
 			Metrics::register(registry)?,
 		))
 		.chain_api(ChainApiSubsystem::new(runtime_client.clone(), Metrics::register(registry)?))
@@ -369,3 +389,29 @@ impl OverseerGen for RealOverseerGen {
 			.map_err(|e| e.into())
 	}
 }
+
+/// The regular set of subsystems, except for the provisioner assembling the parachains inherent
+/// with the given [`ProvisioningPolicy`].
+///
+/// Pass it as the overseer generator when building the node to change what goes into the
+/// inherent.
+pub struct ProvisioningPolicyOverseerGen<P>(pub P);
+
+impl<P: ProvisioningPolicy + Clone> OverseerGen for ProvisioningPolicyOverseerGen<P> {
+	fn generate<Spawner, RuntimeClient>(
+		&self,
+		connector: OverseerConnector,
+		args: OverseerGenArgs<Spawner, RuntimeClient>,
+	) -> Result<(Overseer<SpawnGlue<Spawner>, Arc<RuntimeClient>>, OverseerHandle), Error>
+	where
+		RuntimeClient: 'static + ProvideRuntimeApi<Block> + HeaderBackend<Block> + AuxStore,
+		RuntimeClient::Api: ParachainHost<Block> + BabeApi<Block> + AuthorityDiscoveryApi<Block>,
+		Spawner: 'static + SpawnNamed + Clone + Unpin,
+	{
+		let policy = self.0.clone();
+		prepared_overseer_builder(args)?
+			.replace_provisioner(move |provisioner| provisioner.replace_policy(policy))
+			.build_with_connector(connector)
+			.map_err(|e| e.into())
+	}
+}
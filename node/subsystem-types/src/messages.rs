@@ -34,9 +34,10 @@ use polkadot_node_network_protocol::{
 };
 use polkadot_node_primitives::{
 	approval::{BlockApprovalMeta, IndirectAssignmentCert, IndirectSignedApprovalVote},
-	AvailableData, BabeEpoch, BlockWeight, CandidateVotes, CollationGenerationConfig,
-	CollationSecondedSignal, DisputeMessage, DisputeStatus, ErasureChunk, PoV,
-	SignedDisputeStatement, SignedFullStatement, ValidationResult,
+	AvailableData, BabeEpoch, BackingMisbehaviorPage, BlockWeight, CandidateVotes,
+	CollationDeliveryReport, CollationGenerationConfig, CollationSecondedSignal, DisputeMessage,
+	DisputeStatus, ErasureChunk, PoV, PvfRejection, PvfRejectionReport, SignedDisputeStatement,
+	SignedFullStatement, ValidationResult,
 };
 use polkadot_primitives::{
 	AuthorityDiscoveryId, BackedCandidate, BlockNumber, CandidateEvent, CandidateHash,
@@ -70,6 +71,15 @@ pub enum CandidateBackingMessage {
 	/// to a broader check by the Disputes Subsystem, though that escalation is deferred until the approval voting
 	/// stage to guarantee availability. Agreements are simply tallied until a quorum is reached.
 	Statement(Hash, SignedFullStatement),
+	/// Get a page of at most the given number of backing misbehaviors recorded by this node,
+	/// optionally restricted to the given session, ordered by session and validator index. The page
+	/// starts after the given key, which is the `next_key` of the previous page.
+	MisbehaviorReports(
+		Option<SessionIndex>,
+		Option<Vec<u8>>,
+		usize,
+		oneshot::Sender<BackingMisbehaviorPage>,
+	),
 }
 
 /// Blanket error for validation failing for internal reasons.
@@ -85,7 +95,7 @@ pub enum PreCheckOutcome {
 	/// The PVF could not be compiled. This variant is used when the candidate-validation subsystem
 	/// can be sure that the PVF is invalid. To give a couple of examples: a PVF that cannot be
 	/// decompressed or that does not represent a structurally valid WebAssembly file.
-	Invalid,
+	Invalid(PvfRejection),
 	/// This variant is used when the PVF cannot be compiled but for other reasons that are not
 	/// included into [`PreCheckOutcome::Invalid`]. This variant can indicate that the PVF in
 	/// question is invalid, however it is not necessary that PVF that received this judgement
@@ -94,7 +104,7 @@ pub enum PreCheckOutcome {
 	/// For example, if during compilation the preparation worker was killed we cannot be sure why
 	/// it happened: because the PVF was malicious made the worker to use too much memory or its
 	/// because the host machine is under severe memory pressure and it decided to kill the worker.
-	Failed,
+	Failed(PvfRejection),
 }
 
 /// Messages received by the Validation subsystem.
@@ -152,6 +162,31 @@ pub enum CandidateValidationMessage {
 		ValidationCodeHash,
 		oneshot::Sender<PreCheckOutcome>,
 	),
+	/// Validate a PoV of the given para against the state of the given relay-parent, as if a
+	/// candidate built on top of it was backed there.
+	///
+	/// The `PersistedValidationData` is the one a collator would build on, i.e. a candidate of the
+	/// para pending availability is assumed to be included. The current validation code of the
+	/// para is used, unless one is provided.
+	///
+	/// There is no candidate produced by a collator, so the collator signature and the
+	/// commitments are not checked. This is only meant for debugging collations.
+	DryRun(
+		// Relay-parent
+		Hash,
+		ParaId,
+		Arc<PoV>,
+		Option<ValidationCode>,
+		oneshot::Sender<Result<ValidationResult, ValidationFailed>>,
+	),
+}
+
+/// Messages received by the PVF pre-checking subsystem.
+#[derive(Debug)]
+pub enum PvfCheckerMessage {
+	/// Get the PVFs this node recently voted against, together with the explanations, oldest
+	/// first.
+	Rejections(oneshot::Sender<Vec<PvfRejectionReport>>),
 }
 
 /// Messages received by the Collator Protocol subsystem.
@@ -183,6 +218,11 @@ pub enum CollatorProtocolMessage {
 	///
 	/// The hash is the relay parent.
 	Seconded(Hash, SignedFullStatement),
+	/// Get delivery reports for the collations we distributed recently, oldest first. This
+	/// includes collations for relay parents that are still in view.
+	///
+	/// Only handled on the collator side.
+	DeliveryReports(oneshot::Sender<Vec<CollationDeliveryReport>>),
 }
 
 impl Default for CollatorProtocolMessage {
@@ -103,6 +103,11 @@ pub fn new_full(
 		None,
 		None,
 		None,
+		Default::default(),
+		None,
+		false,
+		Default::default(),
+		Default::default(),
 	)
 }
 
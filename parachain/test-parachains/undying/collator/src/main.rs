@@ -68,6 +68,11 @@ fn main() -> Result<()> {
 					None,
 					None,
 					None,
+					Default::default(),
+					None,
+					false,
+					Default::default(),
+					Default::default(),
 				)
 				.map_err(|e| e.to_string())?;
 				let mut overseer_handle = full_node
@@ -111,10 +111,10 @@
 //! from the stable primitives.
 
 use crate::{
-	BlockNumber, CandidateCommitments, CandidateEvent, CandidateHash, CommittedCandidateReceipt,
-	CoreState, DisputeState, ExecutorParams, GroupRotationInfo, OccupiedCoreAssumption,
-	PersistedValidationData, PvfCheckStatement, ScrapedOnChainVotes, SessionIndex, SessionInfo,
-	ValidatorId, ValidatorIndex, ValidatorSignature,
+	vstaging, AccountId, BlockNumber, CandidateCommitments, CandidateEvent, CandidateHash,
+	CommittedCandidateReceipt, CoreState, DisputeState, ExecutorParams, GroupRotationInfo,
+	OccupiedCoreAssumption, PersistedValidationData, PvfCheckStatement, ScrapedOnChainVotes,
+	SessionIndex, SessionInfo, ValidatorId, ValidatorIndex, ValidatorSignature,
 };
 use parity_scale_codec::{Decode, Encode};
 use polkadot_core_primitives as pcp;
@@ -218,5 +218,29 @@ sp_api::decl_runtime_apis! {
 
 		/// Returns execution parameters for the session.
 		fn session_executor_params(session_index: SessionIndex) -> Option<ExecutorParams>;
+
+		/***** Staging *****/
+
+		/// Returns the relay chain resources used by the candidates of each para included in the
+		/// given session, if the session is still in the usage ledger.
+		#[api_version(5)]
+		fn staging_para_usage(session_index: SessionIndex) -> Vec<(ppp::Id, vstaging::ParaUsage)>;
+
+		/// Returns the length and size of the downward message queue and the delivery fee factor
+		/// of each para with pending downward messages or raised delivery fees.
+		#[api_version(5)]
+		fn staging_dmq_status() -> Vec<(ppp::Id, vstaging::DmqStatus)>;
+
+		/// Returns the messages in the UMP overweight queue, grouped by the para which sent them.
+		#[api_version(5)]
+		fn staging_ump_overweight_messages() -> Vec<(ppp::Id, Vec<vstaging::UmpOverweightMessage>)>;
+
+		/// Returns the era points each validator earned for its parachain duties in the given era,
+		/// broken down by activity and sorted by validator. The approval points are always zero
+		/// for now.
+		#[api_version(5)]
+		fn staging_validator_reward_points(
+			era: sp_staking::EraIndex,
+		) -> Vec<(AccountId, vstaging::ValidatorRewardPoints)>;
 	}
 }
@@ -53,6 +53,12 @@ pub enum ExecutorParam {
 	/// Enables WASM bulk memory proposal
 	#[codec(index = 7)]
 	WasmExtBulkMemory,
+	/// Enables deterministic instruction metering of PVF execution, with the given number of
+	/// metered instructions allowed per execution. Exhausting the budget deterministically
+	/// invalidates the candidate, independent of the hardware it is executed on. Host calls are
+	/// not metered, so `PvfExecTimeout` still applies and should be set to only serve as a backstop.
+	#[codec(index = 8)]
+	PvfExecInstructionBudget(u64),
 }
 
 /// Unit type wrapper around [`type@Hash`] that represents an execution parameter set hash.
@@ -131,6 +137,14 @@ impl ExecutorParams {
 		}
 		None
 	}
+
+	/// Returns the instruction budget of a single PVF execution, if metering is enabled
+	pub fn pvf_exec_instruction_budget(&self) -> Option<u64> {
+		self.0.iter().find_map(|param| match param {
+			ExecutorParam::PvfExecInstructionBudget(budget) => Some(*budget),
+			_ => None,
+		})
+	}
 }
 
 impl Deref for ExecutorParams {
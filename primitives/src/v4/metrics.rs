@@ -60,8 +60,8 @@ impl RuntimeMetricLabels {
 	}
 }
 
-impl From<&[&'static str]> for RuntimeMetricLabels {
-	fn from(v: &[&'static str]) -> RuntimeMetricLabels {
+impl From<&[&str]> for RuntimeMetricLabels {
+	fn from(v: &[&str]) -> RuntimeMetricLabels {
 		RuntimeMetricLabels(
 			v.iter().map(|label| RuntimeMetricLabel(label.as_bytes().to_vec())).collect(),
 		)
@@ -189,6 +189,17 @@ pub mod metric_definitions {
 			labels: &["validity"],
 		};
 
+	/// Counts the relay chain resources used by the included candidates of each para. The
+	/// `resource` label is one of `candidates`, `upward_messages`, `upward_message_bytes`,
+	/// `horizontal_messages`, `horizontal_message_bytes`, `downward_messages_processed`,
+	/// `weight_ref_time` and `weight_proof_size`.
+	pub const PARACHAIN_PARA_USAGE: CounterVecDefinition = CounterVecDefinition {
+		name: "polkadot_parachain_para_usage",
+		description:
+			"Counts the relay chain resources used by the included candidates of each para.",
+		labels: &["para_id", "resource"],
+	};
+
 	/// Measures how much time does it take to verify a single validator signature of a dispute statement
 	pub const PARACHAIN_VERIFY_DISPUTE_SIGNATURE: HistogramDefinition =
 		HistogramDefinition {
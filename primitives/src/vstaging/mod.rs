@@ -40,3 +40,101 @@ pub struct AsyncBackingParams {
 	/// When async backing is disabled, the only valid value is 0.
 	pub allowed_ancestry_len: u32,
 }
+
+/// The relay chain resources used by the candidates of a para which were included in a session.
+///
+/// The size of the PoVs is not known to the relay chain runtime, the backing validators measure it
+/// in the `polkadot_parachain_candidate_backing_validated_pov_bytes_total` metric instead.
+#[derive(RuntimeDebug, Clone, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParaUsage {
+	/// The number of included candidates.
+	pub candidates: u32,
+	/// The number of upward messages sent.
+	pub upward_messages: u32,
+	/// The total size of the upward messages sent, in bytes.
+	pub upward_message_bytes: u64,
+	/// The number of horizontal messages sent.
+	pub horizontal_messages: u32,
+	/// The total size of the horizontal messages sent, in bytes.
+	pub horizontal_message_bytes: u64,
+	/// The number of downward messages processed.
+	pub downward_messages_processed: u32,
+	/// The reference time weight consumed by enacting the candidates.
+	pub weight_ref_time: u64,
+	/// The proof size weight consumed by enacting the candidates.
+	pub weight_proof_size: u64,
+}
+
+impl ParaUsage {
+	/// Add the usage of `other` to this one.
+	pub fn saturating_accrue(&mut self, other: &ParaUsage) {
+		self.candidates = self.candidates.saturating_add(other.candidates);
+		self.upward_messages = self.upward_messages.saturating_add(other.upward_messages);
+		self.upward_message_bytes =
+			self.upward_message_bytes.saturating_add(other.upward_message_bytes);
+		self.horizontal_messages =
+			self.horizontal_messages.saturating_add(other.horizontal_messages);
+		self.horizontal_message_bytes =
+			self.horizontal_message_bytes.saturating_add(other.horizontal_message_bytes);
+		self.downward_messages_processed = self
+			.downward_messages_processed
+			.saturating_add(other.downward_messages_processed);
+		self.weight_ref_time = self.weight_ref_time.saturating_add(other.weight_ref_time);
+		self.weight_proof_size = self.weight_proof_size.saturating_add(other.weight_proof_size);
+	}
+}
+
+/// The status of the downward message queue of a para.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct DmqStatus {
+	/// The number of messages in the queue.
+	pub message_count: u32,
+	/// The total size of the messages in the queue, in bytes.
+	pub total_bytes: u64,
+	/// The factor the delivery fees of messages sent to the para are currently multiplied by.
+	pub delivery_fee_factor: sp_arithmetic::FixedU128,
+}
+
+/// An upward message which exceeded the weight limit of a single message and waits in the
+/// overweight queue to be serviced.
+#[derive(RuntimeDebug, Clone, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct UmpOverweightMessage {
+	/// The index to service the message with.
+	pub index: u64,
+	/// The size of the message, in bytes.
+	pub size: u32,
+	/// The reference time weight the message required to be executed.
+	///
+	/// Zero for messages which were moved to the overweight queue before it was recorded.
+	pub required_weight_ref_time: u64,
+	/// The proof size weight the message required to be executed.
+	///
+	/// Zero for messages which were moved to the overweight queue before it was recorded.
+	pub required_weight_proof_size: u64,
+	/// The session at the start of which the message is dropped if it is not serviced by then.
+	///
+	/// `None` for messages which never expire.
+	pub expires_at: Option<SessionIndex>,
+}
+
+/// The points a validator earned for its parachain duties in an era, by activity.
+///
+/// Points for producing relay chain blocks are awarded by the staking system itself and are not
+/// part of this breakdown. Approval checking is not part of it either, as approval votes are not
+/// imported on chain.
+#[derive(RuntimeDebug, Clone, Default, PartialEq, Eq, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(serde::Serialize, serde::Deserialize))]
+pub struct ValidatorRewardPoints {
+	/// The era points earned for backing candidates which were included.
+	pub backing: u32,
+	/// The points recorded for contributing to the availability of candidates which were
+	/// included.
+	///
+	/// These are not awarded as era points.
+	pub availability: u32,
+	/// The era points earned for voting in disputes.
+	pub disputes: u32,
+}
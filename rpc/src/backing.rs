@@ -0,0 +1,105 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC for auditing validators which misbehaved during candidate backing.
+//!
+//! The reports are only recorded locally. There is no runtime entry point to report backing
+//! misbehaviors on chain yet, so they are neither slashed nor submitted anywhere by the node.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use polkadot_node_primitives::BackingMisbehaviorPage;
+use polkadot_node_subsystem_types::messages::CandidateBackingMessage;
+use polkadot_overseer::Handle;
+use polkadot_primitives::SessionIndex;
+use sc_rpc::DenyUnsafe;
+use sp_core::Bytes;
+
+const LOG_ORIGIN: &str = "backing-rpc";
+
+/// The maximum number of reports returned in a single page.
+const MAX_PAGE_SIZE: u32 = 1000;
+
+/// Error code returned if candidate backing did not answer.
+const SUBSYSTEM_UNAVAILABLE: i32 = 9000;
+
+/// Candidate backing RPC methods.
+#[rpc(server)]
+pub trait BackingApi {
+	/// A page of at most `count` misbehaviors detected by this node during candidate backing,
+	/// ordered by session and validator index. If a session is given, only misbehaviors of that
+	/// session are returned. The next page is requested by passing the `nextKey` of the returned
+	/// page as `start_key`.
+	#[method(name = "backing_misbehaviors")]
+	async fn misbehaviors(
+		&self,
+		session_index: Option<SessionIndex>,
+		start_key: Option<Bytes>,
+		count: u32,
+	) -> RpcResult<BackingMisbehaviorPage>;
+}
+
+/// Implements the [`BackingApiServer`] RPC trait by querying the candidate backing subsystem.
+pub struct Backing {
+	overseer: Handle,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl Backing {
+	/// Create a new instance of the candidate backing RPC.
+	pub fn new(overseer: Handle, deny_unsafe: DenyUnsafe) -> Self {
+		Self { overseer, deny_unsafe }
+	}
+}
+
+#[async_trait]
+impl BackingApiServer for Backing {
+	async fn misbehaviors(
+		&self,
+		session_index: Option<SessionIndex>,
+		start_key: Option<Bytes>,
+		count: u32,
+	) -> RpcResult<BackingMisbehaviorPage> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let count = count.clamp(1, MAX_PAGE_SIZE) as usize;
+		let (tx, rx) = oneshot::channel();
+		self.overseer
+			.clone()
+			.send_msg(
+				CandidateBackingMessage::MisbehaviorReports(
+					session_index,
+					start_key.map(|key| key.0),
+					count,
+					tx,
+				),
+				LOG_ORIGIN,
+			)
+			.await;
+
+		rx.await.map_err(|_| {
+			JsonRpseeError::from(CallError::Custom(ErrorObject::owned(
+				SUBSYSTEM_UNAVAILABLE,
+				"Candidate backing did not answer",
+				None::<()>,
+			)))
+		})
+	}
+}
@@ -0,0 +1,247 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC for validating PoVs against the relay-chain state, for debugging collations.
+
+use std::sync::Arc;
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use polkadot_node_primitives::{BlockData, DryRunOutcome, PoV};
+use polkadot_node_subsystem_types::messages::CandidateValidationMessage;
+use polkadot_overseer::Handle;
+use polkadot_primitives::{Block, Hash, Id as ParaId, ValidationCode};
+use sc_rpc::DenyUnsafe;
+use sp_blockchain::HeaderBackend;
+use sp_core::Bytes;
+
+const LOG_ORIGIN: &str = "candidate-validation-rpc";
+
+/// Error code returned if candidate validation did not answer.
+const SUBSYSTEM_UNAVAILABLE: i32 = 9000;
+/// Error code returned if the PoV could not be validated, e.g. because of a local issue.
+const VALIDATION_FAILED: i32 = 9001;
+
+/// Candidate validation RPC methods.
+#[rpc(server)]
+pub trait CandidateValidationApi {
+	/// Validate the block data of a PoV of the given para against the state of the best block, or
+	/// of the block `at`, as if a candidate built on top of it was backed there.
+	///
+	/// The current validation code of the para is used, unless `validation_code` is given. The
+	/// collator signature and the commitments are not checked, because there is no candidate.
+	#[method(name = "candidateValidation_dryRun")]
+	async fn dry_run(
+		&self,
+		para_id: ParaId,
+		block_data: Bytes,
+		validation_code: Option<Bytes>,
+		at: Option<Hash>,
+	) -> RpcResult<DryRunOutcome>;
+}
+
+/// Implements the [`CandidateValidationApiServer`] RPC trait by querying the candidate validation
+/// subsystem.
+pub struct CandidateValidation<C> {
+	client: Arc<C>,
+	overseer: Handle,
+	deny_unsafe: DenyUnsafe,
+}
+
+impl<C> CandidateValidation<C> {
+	/// Create a new instance of the candidate validation RPC.
+	pub fn new(client: Arc<C>, overseer: Handle, deny_unsafe: DenyUnsafe) -> Self {
+		Self { client, overseer, deny_unsafe }
+	}
+}
+
+#[async_trait]
+impl<C> CandidateValidationApiServer for CandidateValidation<C>
+where
+	C: HeaderBackend<Block> + Send + Sync + 'static,
+{
+	async fn dry_run(
+		&self,
+		para_id: ParaId,
+		block_data: Bytes,
+		validation_code: Option<Bytes>,
+		at: Option<Hash>,
+	) -> RpcResult<DryRunOutcome> {
+		self.deny_unsafe.check_if_safe()?;
+
+		let relay_parent = at.unwrap_or_else(|| self.client.info().best_hash);
+		let pov = PoV { block_data: BlockData(block_data.0) };
+		let validation_code = validation_code.map(|code| ValidationCode(code.0));
+
+		let (tx, rx) = oneshot::channel();
+		self.overseer
+			.clone()
+			.send_msg(
+				CandidateValidationMessage::DryRun(
+					relay_parent,
+					para_id,
+					Arc::new(pov),
+					validation_code,
+					tx,
+				),
+				LOG_ORIGIN,
+			)
+			.await;
+
+		match rx.await {
+			Ok(Ok(result)) => Ok(result.into()),
+			Ok(Err(failed)) => Err(JsonRpseeError::from(CallError::Custom(ErrorObject::owned(
+				VALIDATION_FAILED,
+				"PoV could not be validated",
+				Some(failed.0),
+			)))),
+			Err(_) => Err(JsonRpseeError::from(CallError::Custom(ErrorObject::owned(
+				SUBSYSTEM_UNAVAILABLE,
+				"Candidate validation did not answer",
+				None::<()>,
+			)))),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use futures::{channel::mpsc, executor::block_on, FutureExt, StreamExt};
+	use polkadot_node_subsystem_test_helpers::ForwardSubsystem;
+	use polkadot_node_subsystem_types::messages::ValidationFailed;
+	use polkadot_overseer::{dummy::dummy_overseer_builder, HeadSupportsParachains};
+	use polkadot_primitives::{BlockNumber, Header};
+	use sp_blockchain::Info as BlockInfo;
+	use sp_core::{testing::TaskExecutor, traits::SpawnNamed};
+
+	struct AlwaysSupportsParachains;
+
+	#[async_trait]
+	impl HeadSupportsParachains for AlwaysSupportsParachains {
+		async fn head_supports_parachains(&self, _head: &Hash) -> bool {
+			true
+		}
+	}
+
+	fn best_hash() -> Hash {
+		Hash::repeat_byte(1)
+	}
+
+	struct TestClient;
+
+	impl HeaderBackend<Block> for TestClient {
+		fn info(&self) -> BlockInfo<Block> {
+			BlockInfo {
+				best_hash: best_hash(),
+				best_number: 1,
+				genesis_hash: Hash::zero(),
+				finalized_hash: Hash::zero(),
+				finalized_number: 0,
+				number_leaves: 0,
+				finalized_state: None,
+				block_gap: None,
+			}
+		}
+		fn number(&self, _hash: Hash) -> sp_blockchain::Result<Option<BlockNumber>> {
+			unimplemented!()
+		}
+		fn hash(&self, _number: BlockNumber) -> sp_blockchain::Result<Option<Hash>> {
+			unimplemented!()
+		}
+		fn header(&self, _hash: Hash) -> sp_blockchain::Result<Option<Header>> {
+			unimplemented!()
+		}
+		fn status(&self, _hash: Hash) -> sp_blockchain::Result<sp_blockchain::BlockStatus> {
+			unimplemented!()
+		}
+	}
+
+	/// Returns the RPC and the receiver of the messages it sends to candidate validation.
+	fn rpc(
+		deny_unsafe: DenyUnsafe,
+	) -> (CandidateValidation<TestClient>, mpsc::Receiver<CandidateValidationMessage>) {
+		let spawner = TaskExecutor::new();
+		let (tx, rx) = mpsc::channel(2);
+		let (overseer, handle) =
+			dummy_overseer_builder(spawner.clone(), AlwaysSupportsParachains, None)
+				.unwrap()
+				.replace_candidate_validation(|_| ForwardSubsystem(tx))
+				.build()
+				.unwrap();
+		spawner.spawn("overseer", None, overseer.run().then(|_| async {}).boxed());
+
+		(CandidateValidation::new(Arc::new(TestClient), Handle::new(handle), deny_unsafe), rx)
+	}
+
+	#[test]
+	fn dry_run_is_unsafe() {
+		let (rpc, mut rx) = rpc(DenyUnsafe::Yes);
+
+		let result = block_on(rpc.into_rpc().call::<_, DryRunOutcome>(
+			"candidateValidation_dryRun",
+			(ParaId::from(5_u32), Bytes(vec![1; 32]), None::<Bytes>, None::<Hash>),
+		));
+
+		assert!(matches!(
+			result,
+			Err(JsonRpseeError::Call(CallError::Custom(err))) if err.message().contains("unsafe")
+		));
+		// Candidate validation is not bothered.
+		assert!(rx.try_next().is_err());
+	}
+
+	#[test]
+	fn dry_run_validates_against_the_best_block() {
+		let (rpc, mut rx) = rpc(DenyUnsafe::No);
+
+		let answer = async move {
+			match rx.next().await {
+				Some(CandidateValidationMessage::DryRun(
+					relay_parent,
+					para_id,
+					pov,
+					validation_code,
+					tx,
+				)) => {
+					assert_eq!(relay_parent, best_hash());
+					assert_eq!(para_id, ParaId::from(5_u32));
+					assert_eq!(pov.block_data, BlockData(vec![1; 32]));
+					assert_eq!(validation_code, None);
+
+					let _ = tx.send(Err(ValidationFailed("no validation host".into())));
+				},
+				msg => panic!("unexpected message: {:?}", msg),
+			}
+		};
+		let (result, ()) = block_on(futures::future::join(
+			rpc.into_rpc().call::<_, DryRunOutcome>(
+				"candidateValidation_dryRun",
+				(ParaId::from(5_u32), Bytes(vec![1; 32]), None::<Bytes>, None::<Hash>),
+			),
+			answer,
+		));
+
+		assert!(matches!(
+			result,
+			Err(JsonRpseeError::Call(CallError::Custom(err))) if err.code() == VALIDATION_FAILED
+		));
+	}
+}
@@ -0,0 +1,73 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC for inspecting the collations distributed by a collator node.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use polkadot_node_primitives::CollationDeliveryReport;
+use polkadot_node_subsystem_types::messages::CollatorProtocolMessage;
+use polkadot_overseer::Handle;
+
+const LOG_ORIGIN: &str = "collator-rpc";
+
+/// Error code returned if the collator protocol did not answer.
+const SUBSYSTEM_UNAVAILABLE: i32 = 9000;
+
+/// Collator RPC methods.
+#[rpc(server)]
+pub trait CollatorApi {
+	/// Delivery reports of the collations this node recently distributed, oldest first.
+	///
+	/// Collations whose relay parent is still in view are reported with their current outcome.
+	#[method(name = "collator_deliveryReports")]
+	async fn delivery_reports(&self) -> RpcResult<Vec<CollationDeliveryReport>>;
+}
+
+/// Implements the [`CollatorApiServer`] RPC trait by querying the collator protocol subsystem.
+pub struct Collator {
+	overseer: Handle,
+}
+
+impl Collator {
+	/// Create a new instance of the collator RPC.
+	pub fn new(overseer: Handle) -> Self {
+		Self { overseer }
+	}
+}
+
+#[async_trait]
+impl CollatorApiServer for Collator {
+	async fn delivery_reports(&self) -> RpcResult<Vec<CollationDeliveryReport>> {
+		let (tx, rx) = oneshot::channel();
+		self.overseer
+			.clone()
+			.send_msg(CollatorProtocolMessage::DeliveryReports(tx), LOG_ORIGIN)
+			.await;
+
+		rx.await.map_err(|_| {
+			JsonRpseeError::from(CallError::Custom(ErrorObject::owned(
+				SUBSYSTEM_UNAVAILABLE,
+				"Collator protocol did not answer",
+				None::<()>,
+			)))
+		})
+	}
+}
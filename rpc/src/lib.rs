@@ -36,6 +36,11 @@ use sp_consensus_babe::BabeApi;
 use sp_keystore::KeystorePtr;
 use txpool_api::TransactionPool;
 
+pub mod backing;
+pub mod candidate_validation;
+pub mod collator;
+pub mod pvf_checker;
+
 /// A type representing all RPC extensions.
 pub type RpcExtension = RpcModule<()>;
 
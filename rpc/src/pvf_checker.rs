@@ -0,0 +1,71 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! RPC for inspecting why a validator node voted against PVFs during pre-checking.
+
+use futures::channel::oneshot;
+use jsonrpsee::{
+	core::{async_trait, Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+use polkadot_node_primitives::PvfRejectionReport;
+use polkadot_node_subsystem_types::messages::PvfCheckerMessage;
+use polkadot_overseer::Handle;
+
+const LOG_ORIGIN: &str = "pvf-checker-rpc";
+
+/// Error code returned if the PVF pre-checker did not answer.
+const SUBSYSTEM_UNAVAILABLE: i32 = 9000;
+
+/// PVF pre-checker RPC methods.
+#[rpc(server)]
+pub trait PvfCheckerApi {
+	/// The PVFs this node recently voted against together with the reasons, oldest first.
+	#[method(name = "pvfChecker_rejections")]
+	async fn rejections(&self) -> RpcResult<Vec<PvfRejectionReport>>;
+}
+
+/// Implements the [`PvfCheckerApiServer`] RPC trait by querying the PVF pre-checking subsystem.
+pub struct PvfChecker {
+	overseer: Handle,
+}
+
+impl PvfChecker {
+	/// Create a new instance of the PVF pre-checker RPC.
+	pub fn new(overseer: Handle) -> Self {
+		Self { overseer }
+	}
+}
+
+#[async_trait]
+impl PvfCheckerApiServer for PvfChecker {
+	async fn rejections(&self) -> RpcResult<Vec<PvfRejectionReport>> {
+		let (tx, rx) = oneshot::channel();
+		self.overseer
+			.clone()
+			.send_msg(PvfCheckerMessage::Rejections(tx), LOG_ORIGIN)
+			.await;
+
+		rx.await.map_err(|_| {
+			JsonRpseeError::from(CallError::Custom(ErrorObject::owned(
+				SUBSYSTEM_UNAVAILABLE,
+				"PVF pre-checker did not answer",
+				None::<()>,
+			)))
+		})
+	}
+}
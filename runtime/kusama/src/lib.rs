@@ -1084,10 +1084,21 @@ impl parachains_session_info::Config for Runtime {
 	type ValidatorSet = Historical;
 }
 
+impl parachains_reward_points::Config for Runtime {
+	type ActiveEra = parachains_reward_points::StakingActiveEra<Runtime>;
+	type HistoryDepth = <Runtime as pallet_staking::Config>::HistoryDepth;
+}
+
+parameter_types! {
+	/// Keep the usage of the paras for about a week.
+	pub const ParaUsageSessions: SessionIndex = 7 * DAYS / EPOCH_DURATION_IN_SLOTS;
+}
+
 impl parachains_inclusion::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type DisputesHandler = ParasDisputes;
 	type RewardValidators = parachains_reward_points::RewardValidatorsWithEraPoints<Runtime>;
+	type ParaUsageSessions = ParaUsageSessions;
 }
 
 parameter_types! {
@@ -1103,6 +1114,8 @@ impl parachains_paras::Config for Runtime {
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	/// Overweight upward messages don't expire until governance opts in.
+	pub const UmpOverweightExpirySessions: SessionIndex = 0;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -1111,6 +1124,7 @@ impl parachains_ump::Config for Runtime {
 		crate::parachains_ump::XcmSink<xcm_executor::XcmExecutor<xcm_config::XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type OverweightExpirySessions = UmpOverweightExpirySessions;
 	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
@@ -1127,7 +1141,10 @@ impl parachains_paras_inherent::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_paras_inherent::WeightInfo<Runtime>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = parachains_scheduler::DefaultAssignmentProvider<Runtime>;
+	type WeightInfo = parachains_scheduler::TestWeightInfo;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -1143,6 +1160,7 @@ impl parachains_disputes::Config for Runtime {
 }
 
 impl parachains_slashing::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type KeyOwnerProofSystem = Historical;
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, ValidatorId)>>::Proof;
@@ -1155,6 +1173,7 @@ impl parachains_slashing::Config for Runtime {
 		Offences,
 		ReportLongevity,
 	>;
+	type CurrentEra = parachains_slashing::StakingActiveEra<Runtime>;
 	type WeightInfo = weights::runtime_parachains_disputes_slashing::WeightInfo<Runtime>;
 	type BenchmarkingConfig = parachains_slashing::BenchConfig<1000>;
 }
@@ -1407,6 +1426,7 @@ construct_runtime! {
 		ParasShared: parachains_shared::{Pallet, Call, Storage} = 52,
 		ParaInclusion: parachains_inclusion::{Pallet, Call, Storage, Event<T>} = 53,
 		ParaInherent: parachains_paras_inherent::{Pallet, Call, Storage, Inherent} = 54,
+		// No `Call` until the node supports parachains on multiple cores.
 		ParaScheduler: parachains_scheduler::{Pallet, Storage} = 55,
 		Paras: parachains_paras::{Pallet, Call, Storage, Event, Config, ValidateUnsigned} = 56,
 		Initializer: parachains_initializer::{Pallet, Call, Storage} = 57,
@@ -1415,7 +1435,8 @@ construct_runtime! {
 		Hrmp: parachains_hrmp::{Pallet, Call, Storage, Event<T>, Config} = 60,
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage} = 61,
 		ParasDisputes: parachains_disputes::{Pallet, Call, Storage, Event<T>} = 62,
-		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, ValidateUnsigned} = 63,
+		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, Event<T>, ValidateUnsigned} = 63,
+		ParasRewardPoints: parachains_reward_points::{Pallet, Storage} = 64,
 
 		// Parachain Onboarding Pallets. Start indices at 70 to leave room.
 		Registrar: paras_registrar::{Pallet, Call, Storage, Event<T>} = 70,
@@ -1484,7 +1505,14 @@ pub mod migrations {
 	);
 
 	/// Unreleased migrations. Add new ones here:
-	pub type Unreleased = ();
+	pub type Unreleased = (
+		parachains_inclusion::migration::v1::MigrateToV1<Runtime>,
+		parachains_dmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_ump::migration::v2::MigrateToV2<Runtime>,
+		parachains_configuration::migration::v6::MigrateToV6<Runtime>,
+		parachains_slashing::migration::v1::MigrateToV1<Runtime>,
+	);
 }
 
 /// Unchecked extrinsic type as expected by this runtime.
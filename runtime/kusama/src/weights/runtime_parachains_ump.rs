@@ -21,6 +21,11 @@
 //! WORST CASE MAP SIZE: `1000000`
 //! HOSTNAME: `bm6`, CPU: `Intel(R) Core(TM) i7-7700K CPU @ 4.20GHz`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("kusama-dev"), DB CACHE: 1024
+//!
+//! `service_overweight_batch` is NOT a benchmark result yet. Its storage accesses are taken from
+//! the benchmark, the execution time is a conservative estimate. `service_overweight` was measured
+//! before it removed the `OverweightInfo` entry of the serviced message, that write is added by
+//! hand. Regenerate this file with the command below before relying on them.
 
 // Executed Command:
 // ./target/production/polkadot
@@ -80,6 +85,8 @@ impl<T: frame_system::Config> runtime_parachains::ump::WeightInfo for WeightInfo
 	/// Proof Skipped: Ump Overweight (max_values: None, max_size: None, mode: Measured)
 	/// Storage: Ump CounterForOverweight (r:1 w:1)
 	/// Proof: Ump CounterForOverweight (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Ump OverweightInfo (r:0 w:1)
+	/// Proof Skipped: Ump OverweightInfo (max_values: None, max_size: None, mode: Measured)
 	fn service_overweight() -> Weight {
 		// Proof Size summary in bytes:
 		//  Measured:  `223`
@@ -88,6 +95,24 @@ impl<T: frame_system::Config> runtime_parachains::ump::WeightInfo for WeightInfo
 		Weight::from_parts(23_963_000, 0)
 			.saturating_add(Weight::from_parts(0, 3688))
 			.saturating_add(T::DbWeight::get().reads(2))
-			.saturating_add(T::DbWeight::get().writes(2))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Storage: Ump Overweight (r:1000 w:1000)
+	/// Proof Skipped: Ump Overweight (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Ump CounterForOverweight (r:1 w:1)
+	/// Proof: Ump CounterForOverweight (max_values: Some(1), max_size: Some(4), added: 499, mode: MaxEncodedLen)
+	/// Storage: Ump OverweightInfo (r:0 w:1000)
+	/// Proof Skipped: Ump OverweightInfo (max_values: None, max_size: None, mode: Measured)
+	/// The range of component `n` is `[1, 1000]`.
+	fn service_overweight_batch(n: u32, ) -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(25_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 1476))
+			.saturating_add(Weight::from_parts(25_000_000, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2701).saturating_mul(n.into()))
 	}
 }
@@ -94,7 +94,7 @@ impl CounterVec {
 
 	/// Returns a `LabeledMetric` instance that provides an interface for incrementing
 	/// the metric.
-	pub fn with_label_values(&self, label_values: &[&'static str]) -> LabeledMetric {
+	pub fn with_label_values(&self, label_values: &[&str]) -> LabeledMetric {
 		LabeledMetric { name: self.name, label_values: label_values.into() }
 	}
 }
@@ -37,7 +37,7 @@ impl CounterVec {
 		CounterVec
 	}
 	/// Sets label values, implementation is a `no op`.
-	pub fn with_label_values(&self, _label_values: &[&'static str]) -> &Self {
+	pub fn with_label_values(&self, _label_values: &[&str]) -> &Self {
 		self
 	}
 	/// Increment counter by value, implementation is a `no op`.
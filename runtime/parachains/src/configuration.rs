@@ -27,7 +27,7 @@ use primitives::{
 	vstaging::AsyncBackingParams, Balance, ExecutorParams, SessionIndex, MAX_CODE_SIZE,
 	MAX_HEAD_DATA_SIZE, MAX_POV_SIZE,
 };
-use sp_runtime::traits::Zero;
+use sp_runtime::{traits::Zero, Perbill};
 use sp_std::prelude::*;
 
 #[cfg(test)]
@@ -245,6 +245,17 @@ pub struct HostConfiguration<BlockNumber> {
 	/// This value should be greater than [`chain_availability_period`] and
 	/// [`thread_availability_period`].
 	pub minimum_validation_upgrade_delay: BlockNumber,
+	/// The fraction of their exposure that validators who backed a candidate are slashed by once
+	/// a dispute concludes the candidate invalid.
+	pub dispute_backer_slash_fraction: Perbill,
+	/// The fraction of their exposure that validators who did not back a candidate, but voted
+	/// for its validity in a dispute concluding it invalid, are slashed by.
+	///
+	/// Zero means that only the backers are slashed.
+	pub dispute_valid_voter_slash_fraction: Perbill,
+	/// The fraction added to the slash of a validator for every dispute it has already lost in
+	/// the current era. The resulting fraction never exceeds 100%.
+	pub dispute_repeat_offence_escalation: Perbill,
 }
 
 impl<BlockNumber: Default + From<u32>> Default for HostConfiguration<BlockNumber> {
@@ -299,6 +310,9 @@ impl<BlockNumber: Default + From<u32>> Default for HostConfiguration<BlockNumber
 			pvf_voting_ttl: 2u32.into(),
 			minimum_validation_upgrade_delay: 2.into(),
 			executor_params: Default::default(),
+			dispute_backer_slash_fraction: Perbill::from_percent(100),
+			dispute_valid_voter_slash_fraction: Perbill::zero(),
+			dispute_repeat_offence_escalation: Perbill::zero(),
 		}
 	}
 }
@@ -1183,6 +1197,56 @@ pub mod pallet {
 				config.executor_params = new;
 			})
 		}
+
+		/// Set the fraction backers of a candidate concluded invalid by a dispute are slashed by.
+		#[pallet::call_index(47)]
+		#[pallet::weight((
+			T::WeightInfo::set_config_with_u32(), // The same size in bytes.
+			DispatchClass::Operational,
+		))]
+		pub fn set_dispute_backer_slash_fraction(
+			origin: OriginFor<T>,
+			new: Perbill,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::schedule_config_update(|config| {
+				config.dispute_backer_slash_fraction = new;
+			})
+		}
+
+		/// Set the fraction validators who voted for the validity of a candidate concluded
+		/// invalid by a dispute, without backing it, are slashed by.
+		#[pallet::call_index(48)]
+		#[pallet::weight((
+			T::WeightInfo::set_config_with_u32(), // The same size in bytes.
+			DispatchClass::Operational,
+		))]
+		pub fn set_dispute_valid_voter_slash_fraction(
+			origin: OriginFor<T>,
+			new: Perbill,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::schedule_config_update(|config| {
+				config.dispute_valid_voter_slash_fraction = new;
+			})
+		}
+
+		/// Set the fraction added to a dispute slash for every dispute the offender has already
+		/// lost in the current era.
+		#[pallet::call_index(49)]
+		#[pallet::weight((
+			T::WeightInfo::set_config_with_u32(), // The same size in bytes.
+			DispatchClass::Operational,
+		))]
+		pub fn set_dispute_repeat_offence_escalation(
+			origin: OriginFor<T>,
+			new: Perbill,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::schedule_config_update(|config| {
+				config.dispute_repeat_offence_escalation = new;
+			})
+		}
 	}
 
 	#[pallet::hooks]
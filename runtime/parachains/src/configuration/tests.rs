@@ -327,6 +327,9 @@ fn setting_pending_config_members() {
 			pvf_voting_ttl: 3,
 			minimum_validation_upgrade_delay: 20,
 			executor_params: Default::default(),
+			dispute_backer_slash_fraction: Perbill::from_percent(80),
+			dispute_valid_voter_slash_fraction: Perbill::from_percent(5),
+			dispute_repeat_offence_escalation: Perbill::from_percent(10),
 		};
 
 		Configuration::set_validation_upgrade_cooldown(
@@ -500,6 +503,21 @@ fn setting_pending_config_members() {
 		.unwrap();
 		Configuration::set_pvf_voting_ttl(RuntimeOrigin::root(), new_config.pvf_voting_ttl)
 			.unwrap();
+		Configuration::set_dispute_backer_slash_fraction(
+			RuntimeOrigin::root(),
+			new_config.dispute_backer_slash_fraction,
+		)
+		.unwrap();
+		Configuration::set_dispute_valid_voter_slash_fraction(
+			RuntimeOrigin::root(),
+			new_config.dispute_valid_voter_slash_fraction,
+		)
+		.unwrap();
+		Configuration::set_dispute_repeat_offence_escalation(
+			RuntimeOrigin::root(),
+			new_config.dispute_repeat_offence_escalation,
+		)
+		.unwrap();
 
 		assert_eq!(PendingConfigs::<Test>::get(), vec![(shared::SESSION_DELAY, new_config)],);
 	})
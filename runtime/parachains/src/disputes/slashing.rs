@@ -20,7 +20,15 @@
 //! wrong side of the dispute. The slashing amount depends on whether the
 //! candidate was valid (none at the moment) or invalid (big). In addition to
 //! that, we might want to kick out the validators from the active set.
-//! Currently, we limit slashing to the backing group for invalid disputes.
+//!
+//! For invalid disputes, the fraction slashed is taken from the severity
+//! schedule in `HostConfiguration`: backers of the candidate are slashed by
+//! `dispute_backer_slash_fraction` and the other validators who voted for its
+//! validity by `dispute_valid_voter_slash_fraction`. Every dispute a validator
+//! already lost in the current era adds `dispute_repeat_offence_escalation` to
+//! its slash. The `offences` pallet slashes all offenders reported in the same
+//! time slot by the fraction of the latest report, so each slash fraction of a
+//! dispute is reported in its own [`OffenceTimeSlot`].
 //!
 //! The `offences` pallet from Substrate provides us with a way to do both.
 //! Currently, the interface expects us to provide staking information including
@@ -42,7 +50,11 @@
 //! producer can submit an unsigned transaction with `KeyOwnershipProof` of an
 //! offender and submit it to the runtime to produce an offence.
 
-use crate::{disputes, initializer::ValidatorSetCount, session_info::IdentificationTuple};
+use crate::{
+	configuration, disputes,
+	initializer::ValidatorSetCount,
+	session_info::{AccountId, IdentificationTuple},
+};
 use frame_support::{
 	dispatch::Pays,
 	traits::{Defensive, Get, KeyOwnerProofSystem, ValidatorSet, ValidatorSetWithIdentification},
@@ -53,7 +65,7 @@ use parity_scale_codec::{Decode, Encode};
 use primitives::{CandidateHash, SessionIndex, ValidatorId, ValidatorIndex};
 use scale_info::TypeInfo;
 use sp_runtime::{
-	traits::Convert,
+	traits::{Convert, Saturating},
 	transaction_validity::{
 		InvalidTransaction, TransactionPriority, TransactionSource, TransactionValidity,
 		TransactionValidityError, ValidTransaction,
@@ -61,7 +73,10 @@ use sp_runtime::{
 	DispatchResult, KeyTypeId, Perbill, RuntimeDebug,
 };
 use sp_session::{GetSessionNumber, GetValidatorCount};
-use sp_staking::offence::{DisableStrategy, Kind, Offence, OffenceError, ReportOffence};
+use sp_staking::{
+	offence::{DisableStrategy, Kind, Offence, OffenceError, ReportOffence},
+	EraIndex,
+};
 use sp_std::{
 	collections::{
 		btree_map::{BTreeMap, Entry},
@@ -72,15 +87,16 @@ use sp_std::{
 
 const LOG_TARGET: &str = "runtime::parachains::slashing";
 
-// These are constants, but we want to make them configurable
-// via `HostConfiguration` in the future.
-const SLASH_FOR_INVALID: Perbill = Perbill::from_percent(100);
-const SLASH_AGAINST_VALID: Perbill = Perbill::zero();
 const DEFENSIVE_PROOF: &'static str = "disputes module should bail on old session";
 
 #[cfg(feature = "runtime-benchmarks")]
 pub mod benchmarking;
 
+pub mod migration;
+
+#[cfg(test)]
+mod tests;
+
 /// The benchmarking configuration.
 pub trait BenchmarkingConfiguration {
 	const MAX_VALIDATORS: u32;
@@ -107,20 +123,31 @@ impl DisputesTimeSlot {
 	}
 }
 
+/// The time slot of a [`SlashingOffence`]: the dispute and the fraction its
+/// offenders are slashed by.
+///
+/// Offenders which lost the same dispute are slashed by different fractions,
+/// depending on whether they backed the candidate and on their repeat offences.
+/// Since all offenders of a time slot are slashed by the fraction of the latest
+/// report, each fraction needs a time slot of its own.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Encode, Decode, TypeInfo, RuntimeDebug)]
+pub struct OffenceTimeSlot {
+	// The order of these matters for `derive(Ord)`.
+	pub dispute: DisputesTimeSlot,
+	pub slash_fraction: Perbill,
+}
+
 /// An offence that is filed when a series of validators lost a dispute.
 #[derive(RuntimeDebug, TypeInfo)]
 #[cfg_attr(feature = "std", derive(Clone, PartialEq, Eq))]
 pub struct SlashingOffence<KeyOwnerIdentification> {
 	/// The size of the validator set in that session.
 	pub validator_set_count: ValidatorSetCount,
-	/// Should be unique per dispute.
-	pub time_slot: DisputesTimeSlot,
+	/// Should be unique per dispute and slash fraction.
+	pub time_slot: OffenceTimeSlot,
 	/// Staking information about the validators that lost the dispute
 	/// needed for slashing.
 	pub offenders: Vec<KeyOwnerIdentification>,
-	/// What fraction of the total exposure that should be slashed for
-	/// this offence.
-	pub slash_fraction: Perbill,
 	/// Whether the candidate was valid or invalid.
 	pub kind: SlashingOffenceKind,
 }
@@ -131,14 +158,14 @@ where
 {
 	const ID: Kind = *b"disputes:slashin";
 
-	type TimeSlot = DisputesTimeSlot;
+	type TimeSlot = OffenceTimeSlot;
 
 	fn offenders(&self) -> Vec<Offender> {
 		self.offenders.clone()
 	}
 
 	fn session_index(&self) -> SessionIndex {
-		self.time_slot.session_index
+		self.time_slot.dispute.session_index
 	}
 
 	fn validator_set_count(&self) -> ValidatorSetCount {
@@ -159,7 +186,7 @@ where
 	}
 
 	fn slash_fraction(&self, _offenders: u32) -> Perbill {
-		self.slash_fraction
+		self.time_slot.slash_fraction
 	}
 }
 
@@ -170,13 +197,41 @@ impl<KeyOwnerIdentification> SlashingOffence<KeyOwnerIdentification> {
 		validator_set_count: ValidatorSetCount,
 		offenders: Vec<KeyOwnerIdentification>,
 		kind: SlashingOffenceKind,
+		slash_fraction: Perbill,
 	) -> Self {
-		let time_slot = DisputesTimeSlot::new(session_index, candidate_hash);
-		let slash_fraction = match kind {
-			SlashingOffenceKind::ForInvalid => SLASH_FOR_INVALID,
-			SlashingOffenceKind::AgainstValid => SLASH_AGAINST_VALID,
-		};
-		Self { time_slot, validator_set_count, offenders, slash_fraction, kind }
+		let dispute = DisputesTimeSlot::new(session_index, candidate_hash);
+		let time_slot = OffenceTimeSlot { dispute, slash_fraction };
+		Self { time_slot, validator_set_count, offenders, kind }
+	}
+}
+
+/// Returns `base` increased by `escalation` for each of the `previous_offences`,
+/// saturating at 100%.
+fn escalated_slash_fraction(base: Perbill, escalation: Perbill, previous_offences: u32) -> Perbill {
+	base.saturating_add(Perbill::from_parts(
+		escalation.deconstruct().saturating_mul(previous_offences),
+	))
+}
+
+/// Provides the index of the active era of `pallet_staking`, or zero before the
+/// first era starts.
+pub struct StakingActiveEra<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: pallet_staking::Config> Get<EraIndex> for StakingActiveEra<T> {
+	fn get() -> EraIndex {
+		pallet_staking::Pallet::<T>::active_era().map_or(0, |era| era.index)
+	}
+}
+
+/// Groups every `P` sessions into an era, for runtimes without `pallet_staking`.
+///
+/// Repeat offences escalate within such an era, and the offences of the
+/// previous one are pruned like they are with staking eras.
+pub struct SessionEras<T, P>(sp_std::marker::PhantomData<(T, P)>);
+
+impl<T: crate::shared::Config, P: Get<SessionIndex>> Get<EraIndex> for SessionEras<T, P> {
+	fn get() -> EraIndex {
+		crate::shared::Pallet::<T>::session_index() / P::get().max(1)
 	}
 }
 
@@ -195,34 +250,21 @@ impl<T> SlashValidatorsForDisputes<Pallet<T>>
 where
 	T: Config<KeyOwnerIdentification = IdentificationTuple<T>>,
 {
-	/// If in the current session, returns the identified validators. `None`
-	/// otherwise.
-	fn maybe_identify_validators(
-		session_index: SessionIndex,
+	/// Returns the identified validators. Must only be called for the current
+	/// session.
+	fn identify_validators(
+		account_ids: &[AccountId<T>],
 		validators: impl IntoIterator<Item = ValidatorIndex>,
-	) -> Option<Vec<IdentificationTuple<T>>> {
-		// We use `ValidatorSet::session_index` and not
-		// `shared::Pallet<T>::session_index()` because at the first block of a new era,
-		// the `IdentificationOf` of a validator in the previous session might be
-		// missing, while `shared` pallet would return the same session index as being
-		// updated at the end of the block.
-		let current_session = T::ValidatorSet::session_index();
-		if session_index == current_session {
-			let account_keys = crate::session_info::Pallet::<T>::account_keys(session_index);
-			let account_ids = account_keys.defensive_unwrap_or_default();
-
-			let fully_identified = validators
-				.into_iter()
-				.flat_map(|i| account_ids.get(i.0 as usize).cloned())
-				.filter_map(|id| {
-					<T::ValidatorSet as ValidatorSetWithIdentification<T::AccountId>>::IdentificationOf::convert(
-						id.clone()
-					).map(|full_id| (id, full_id))
-				})
-				.collect::<Vec<IdentificationTuple<T>>>();
-			return Some(fully_identified)
-		}
-		None
+	) -> Vec<IdentificationTuple<T>> {
+		validators
+			.into_iter()
+			.flat_map(|i| account_ids.get(i.0 as usize).cloned())
+			.filter_map(|id| {
+				<T::ValidatorSet as ValidatorSetWithIdentification<T::AccountId>>::IdentificationOf::convert(
+					id.clone()
+				).map(|full_id| (id, full_id))
+			})
+			.collect()
 	}
 
 	fn do_punish(
@@ -242,45 +284,107 @@ where
 			return
 		}
 		let backers: BTreeSet<_> = backers.into_iter().collect();
-		let to_punish: Vec<ValidatorIndex> = losers.intersection(&backers).cloned().collect();
-		if to_punish.is_empty() {
-			return
-		}
 
 		let session_info = crate::session_info::Pallet::<T>::session_info(session_index);
 		let session_info = match session_info.defensive_proof(DEFENSIVE_PROOF) {
 			Some(info) => info,
 			None => return,
 		};
+		let account_keys = crate::session_info::Pallet::<T>::account_keys(session_index);
+		let account_ids = account_keys.defensive_unwrap_or_default();
+
+		let config = <configuration::Pallet<T>>::config();
+		let era = T::CurrentEra::get();
+
+		// Backers and the other validators who voted for the candidate are slashed
+		// according to the severity schedule, escalated by their repeat offences.
+		let mut slash_fractions = BTreeMap::new();
+		for validator_index in losers {
+			let base = if backers.contains(&validator_index) {
+				config.dispute_backer_slash_fraction
+			} else {
+				config.dispute_valid_voter_slash_fraction
+			};
+			if base == Perbill::zero() {
+				continue
+			}
 
-		let maybe = Self::maybe_identify_validators(session_index, to_punish.iter().cloned());
-		if let Some(offenders) = maybe {
-			let validator_set_count = session_info.discovery_keys.len() as ValidatorSetCount;
-			let offence = SlashingOffence::new(
+			let era_offences = account_ids
+				.get(validator_index.0 as usize)
+				.map_or(1, |who| Pallet::<T>::note_offence(who, era));
+			let slash_fraction = escalated_slash_fraction(
+				base,
+				config.dispute_repeat_offence_escalation,
+				era_offences - 1,
+			);
+			slash_fractions.insert(validator_index, slash_fraction);
+
+			Pallet::<T>::deposit_event(Event::<T>::SlashDetermined(
 				session_index,
 				candidate_hash,
-				validator_set_count,
-				offenders,
-				kind,
-			);
-			// This is the first time we report an offence for this dispute,
-			// so it is not a duplicate.
-			let _ = T::HandleReports::report_offence(offence);
+				validator_index,
+				slash_fraction,
+				era_offences,
+			));
+		}
+		if slash_fractions.is_empty() {
 			return
 		}
 
-		let keys = to_punish
-			.into_iter()
-			.filter_map(|i| session_info.validators.get(i).cloned().map(|id| (i, id)))
+		// We use `ValidatorSet::session_index` and not
+		// `shared::Pallet<T>::session_index()` because at the first block of a new era,
+		// the `IdentificationOf` of a validator in the previous session might be
+		// missing, while `shared` pallet would return the same session index as being
+		// updated at the end of the block.
+		if session_index == T::ValidatorSet::session_index() {
+			let validator_set_count = session_info.discovery_keys.len() as ValidatorSetCount;
+
+			let mut by_fraction = BTreeMap::<Perbill, Vec<ValidatorIndex>>::new();
+			for (validator_index, slash_fraction) in slash_fractions {
+				by_fraction.entry(slash_fraction).or_default().push(validator_index);
+			}
+
+			// Each slash fraction is reported in its own time slot, the most
+			// severe ones first.
+			for (slash_fraction, validators) in by_fraction.into_iter().rev() {
+				let offenders = Self::identify_validators(&account_ids, validators.iter().cloned());
+				let offence = SlashingOffence::new(
+					session_index,
+					candidate_hash,
+					validator_set_count,
+					offenders,
+					kind,
+					slash_fraction,
+				);
+				// This is the first time we report an offence for these validators
+				// in this dispute, so it is not a duplicate.
+				let _ = T::HandleReports::report_offence(offence);
+
+				Pallet::<T>::deposit_event(Event::<T>::OffenceReported(
+					session_index,
+					candidate_hash,
+					validators,
+				));
+			}
+			return
+		}
+
+		let keys = slash_fractions
+			.keys()
+			.filter_map(|&i| session_info.validators.get(i).cloned().map(|id| (i, id)))
 			.collect();
-		let unapplied = PendingSlashes { keys, kind };
+		let unapplied = PendingSlashes { keys, kind, slash_fractions };
 
 		let append = |old: &mut Option<PendingSlashes>| {
-			let old = old
-				.get_or_insert(PendingSlashes { keys: Default::default(), kind: unapplied.kind });
+			let old = old.get_or_insert(PendingSlashes {
+				keys: Default::default(),
+				kind: unapplied.kind,
+				slash_fractions: Default::default(),
+			});
 			debug_assert_eq!(old.kind, unapplied.kind);
 
-			old.keys.extend(unapplied.keys)
+			old.keys.extend(unapplied.keys);
+			old.slash_fractions.extend(unapplied.slash_fractions);
 		};
 		<UnappliedSlashes<T>>::mutate(session_index, candidate_hash, append);
 	}
@@ -354,6 +458,9 @@ pub struct PendingSlashes {
 	pub keys: BTreeMap<ValidatorIndex, ValidatorId>,
 	/// The dispute outcome.
 	pub kind: SlashingOffenceKind,
+	/// The fraction each validator is to be slashed by, determined when the
+	/// dispute concluded.
+	pub slash_fractions: BTreeMap<ValidatorIndex, Perbill>,
 }
 
 /// A trait that defines methods to report an offence (after the slashing report
@@ -374,7 +481,7 @@ pub trait HandleReports<T: Config> {
 	/// reported.
 	fn is_known_offence(
 		offenders: &[T::KeyOwnerIdentification],
-		time_slot: &DisputesTimeSlot,
+		time_slot: &OffenceTimeSlot,
 	) -> bool;
 
 	/// Create and dispatch a slashing report extrinsic.
@@ -396,7 +503,7 @@ impl<T: Config> HandleReports<T> for () {
 
 	fn is_known_offence(
 		_offenders: &[T::KeyOwnerIdentification],
-		_time_slot: &DisputesTimeSlot,
+		_time_slot: &OffenceTimeSlot,
 	) -> bool {
 		true
 	}
@@ -429,6 +536,9 @@ pub mod pallet {
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config + crate::disputes::Config {
+		/// The overarching event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
 		/// The proof of key ownership, used for validating slashing reports.
 		/// The proof must include the session index and validator count of the
 		/// session at which the offence occurred.
@@ -453,6 +563,10 @@ pub mod pallet {
 		/// `ValidateUnsigned` in the runtime definition.
 		type HandleReports: HandleReports<Self>;
 
+		/// The index of the current era, within which repeat offences escalate
+		/// dispute slashes.
+		type CurrentEra: Get<EraIndex>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 
@@ -461,6 +575,7 @@ pub mod pallet {
 	}
 
 	#[pallet::pallet]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
@@ -480,6 +595,27 @@ pub mod pallet {
 	pub(super) type ValidatorSetCounts<T> =
 		StorageMap<_, Twox64Concat, SessionIndex, ValidatorSetCount>;
 
+	/// The number of disputes each validator lost and was slashed for, per era.
+	/// Eras before the current one are pruned on new sessions, so this only
+	/// holds the offenders of about one era.
+	#[pallet::storage]
+	pub(super) type EraOffences<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, EraIndex, Twox64Concat, AccountId<T>, u32, ValueQuery>;
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// A validator lost a dispute and is to be slashed by the given fraction,
+		/// escalated by the number of disputes it lost in the current era.
+		/// Slashes for past sessions are only applied once reported with a key
+		/// ownership proof.
+		/// `\[session index, candidate hash, validator index, slash fraction, era offences\]`
+		SlashDetermined(SessionIndex, CandidateHash, ValidatorIndex, Perbill, u32),
+		/// An offence was reported for the given validators.
+		/// `\[session index, candidate hash, validator indices\]`
+		OffenceReported(SessionIndex, CandidateHash, Vec<ValidatorIndex>),
+	}
+
 	#[pallet::error]
 	pub enum Error<T> {
 		/// The key ownership proof is invalid.
@@ -525,13 +661,14 @@ pub mod pallet {
 			// check that there is a pending slash for the given
 			// validator index and candidate hash
 			let candidate_hash = dispute_proof.time_slot.candidate_hash;
-			let try_remove = |v: &mut Option<PendingSlashes>| -> Result<(), DispatchError> {
+			let validator_index = dispute_proof.validator_index;
+			let try_remove = |v: &mut Option<PendingSlashes>| -> Result<Perbill, DispatchError> {
 				let pending = v.as_mut().ok_or(Error::<T>::InvalidCandidateHash)?;
 				if pending.kind != dispute_proof.kind {
 					return Err(Error::<T>::InvalidCandidateHash.into())
 				}
 
-				match pending.keys.entry(dispute_proof.validator_index) {
+				match pending.keys.entry(validator_index) {
 					Entry::Vacant(_) => return Err(Error::<T>::InvalidValidatorIndex.into()),
 					// check that `validator_index` matches `validator_id`
 					Entry::Occupied(e) if e.get() != &dispute_proof.validator_id =>
@@ -540,16 +677,24 @@ pub mod pallet {
 						e.remove(); // the report is correct
 					},
 				}
+				let slash_fraction = pending
+					.slash_fractions
+					.remove(&validator_index)
+					.ok_or(Error::<T>::InvalidValidatorIndex)?;
 
 				// if the last validator is slashed for this dispute, clean up the storage
 				if pending.keys.is_empty() {
 					*v = None;
 				}
 
-				Ok(())
+				Ok(slash_fraction)
 			};
 
-			<UnappliedSlashes<T>>::try_mutate_exists(&session_index, &candidate_hash, try_remove)?;
+			let slash_fraction = <UnappliedSlashes<T>>::try_mutate_exists(
+				&session_index,
+				&candidate_hash,
+				try_remove,
+			)?;
 
 			let offence = SlashingOffence::new(
 				session_index,
@@ -557,11 +702,18 @@ pub mod pallet {
 				validator_set_count,
 				vec![offender],
 				dispute_proof.kind,
+				slash_fraction,
 			);
 
 			<T::HandleReports as HandleReports<T>>::report_offence(offence)
 				.map_err(|_| Error::<T>::DuplicateSlashingReport)?;
 
+			Self::deposit_event(Event::<T>::OffenceReported(
+				session_index,
+				candidate_hash,
+				vec![validator_index],
+			));
+
 			Ok(Pays::No.into())
 		}
 	}
@@ -595,6 +747,16 @@ impl<T: Config> Pallet<T> {
 		// fine.
 		const REMOVE_LIMIT: u32 = u32::MAX;
 
+		// Only the offences of the current era escalate. There are usually only
+		// those of the previous era left, but eras may be skipped or change their
+		// length, so all earlier ones are pruned.
+		let era = T::CurrentEra::get();
+		let past_eras: BTreeSet<EraIndex> =
+			<EraOffences<T>>::iter_keys().map(|(e, _)| e).filter(|e| *e < era).collect();
+		for past_era in past_eras {
+			let _ = <EraOffences<T>>::clear_prefix(past_era, REMOVE_LIMIT, None);
+		}
+
 		let config = <crate::configuration::Pallet<T>>::config();
 		if session_index <= config.dispute_period + 1 {
 			return
@@ -603,6 +765,15 @@ impl<T: Config> Pallet<T> {
 		let old_session = session_index - config.dispute_period - 1;
 		let _ = <UnappliedSlashes<T>>::clear_prefix(old_session, REMOVE_LIMIT, None);
 	}
+
+	/// Notes that `who` lost a dispute in `era` and returns the number of
+	/// disputes it lost in that era, including this one.
+	fn note_offence(who: &AccountId<T>, era: EraIndex) -> u32 {
+		<EraOffences<T>>::mutate(era, who, |count| {
+			*count = count.saturating_add(1);
+			*count
+		})
+	}
 }
 
 /// Methods for the `ValidateUnsigned` implementation:
@@ -669,12 +840,20 @@ fn is_known_offence<T: Config>(
 	let offender = T::KeyOwnerProofSystem::check_proof(key, key_owner_proof.clone())
 		.ok_or(InvalidTransaction::BadProof)?;
 
+	// the offence is reported in the time slot of its slash fraction. Without a
+	// pending slash, it has already been reported or never existed.
+	let slash_fraction = <UnappliedSlashes<T>>::get(
+		dispute_proof.time_slot.session_index,
+		dispute_proof.time_slot.candidate_hash,
+	)
+	.and_then(|pending| pending.slash_fractions.get(&dispute_proof.validator_index).copied())
+	.ok_or(InvalidTransaction::Stale)?;
+	let time_slot = OffenceTimeSlot { dispute: dispute_proof.time_slot.clone(), slash_fraction };
+
 	// check if the offence has already been reported,
 	// and if so then we can discard the report.
-	let is_known_offence = <T::HandleReports as HandleReports<T>>::is_known_offence(
-		&[offender],
-		&dispute_proof.time_slot,
-	);
+	let is_known_offence =
+		<T::HandleReports as HandleReports<T>>::is_known_offence(&[offender], &time_slot);
 
 	if is_known_offence {
 		Err(InvalidTransaction::Stale.into())
@@ -718,7 +897,7 @@ where
 
 	fn is_known_offence(
 		offenders: &[T::KeyOwnerIdentification],
-		time_slot: &DisputesTimeSlot,
+		time_slot: &OffenceTimeSlot,
 	) -> bool {
 		<R as ReportOffence<
 			T::AccountId,
@@ -0,0 +1,119 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that is responsible for migration of storage.
+
+use super::{Config, Pallet, PendingSlashes, SlashingOffenceKind, UnappliedSlashes};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+#[cfg(feature = "try-runtime")]
+use primitives::{CandidateHash, SessionIndex};
+use primitives::{ValidatorId, ValidatorIndex};
+use sp_runtime::Perbill;
+use sp_std::collections::btree_map::BTreeMap;
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+
+	#[derive(Encode, Decode)]
+	struct OldPendingSlashes {
+		keys: BTreeMap<ValidatorIndex, ValidatorId>,
+		kind: SlashingOffenceKind,
+	}
+
+	/// Records the slash fraction of every pending slash, using the fractions
+	/// that were fixed before they became part of `HostConfiguration`.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			log::trace!("Running pre_upgrade()");
+
+			ensure!(StorageVersion::get::<Pallet<T>>() == 0, "The migration requires version 0");
+			let pending = <UnappliedSlashes<T>>::iter_keys()
+				.map(|(session_index, candidate_hash)| {
+					let old = frame_support::storage::unhashed::get::<OldPendingSlashes>(
+						&<UnappliedSlashes<T>>::hashed_key_for(session_index, candidate_hash),
+					)
+					.ok_or("The pending slashes should decode as version 0")?;
+					Ok((session_index, candidate_hash, old))
+				})
+				.collect::<Result<Vec<_>, &'static str>>()?;
+			Ok(pending.encode())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let mut translated = 0u64;
+				<UnappliedSlashes<T>>::translate::<OldPendingSlashes, _>(|_, _, old| {
+					translated += 1;
+					let slash_fraction = match old.kind {
+						SlashingOffenceKind::ForInvalid => Perbill::from_percent(100),
+						SlashingOffenceKind::AgainstValid => Perbill::zero(),
+					};
+					let slash_fractions = old.keys.keys().map(|&i| (i, slash_fraction)).collect();
+					Some(PendingSlashes { keys: old.keys, kind: old.kind, slash_fractions })
+				});
+				log::info!("Migrated {} pending dispute slashes", translated);
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			log::trace!("Running post_upgrade()");
+
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be 1 after the migration"
+			);
+
+			let pending =
+				Vec::<(SessionIndex, CandidateHash, OldPendingSlashes)>::decode(&mut &state[..])
+					.map_err(|_| "The pre-upgrade state should decode")?;
+			ensure!(
+				<UnappliedSlashes<T>>::iter_keys().count() == pending.len(),
+				"No pending slashes should be added or dropped"
+			);
+			for (session_index, candidate_hash, old) in pending {
+				let new = <UnappliedSlashes<T>>::get(session_index, candidate_hash)
+					.ok_or("The pending slashes should be kept")?;
+				ensure!(
+					new.keys == old.keys && new.kind == old.kind,
+					"The offenders should be kept"
+				);
+				ensure!(
+					new.slash_fractions.keys().eq(new.keys.keys()),
+					"Each offender should have a slash fraction"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
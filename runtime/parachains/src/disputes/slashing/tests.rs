@@ -0,0 +1,321 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::{
+	disputes::SlashingHandler,
+	mock::{
+		new_test_ext, take_reported_offences, CurrentEra, MockGenesisConfig, ParasSlashing,
+		RuntimeEvent, System, Test,
+	},
+};
+use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+use keyring::Sr25519Keyring;
+use primitives::{AuthorityDiscoveryId, Hash, SessionInfo};
+
+type Handler = SlashValidatorsForDisputes<ParasSlashing>;
+
+const KEYS: [Sr25519Keyring; 4] =
+	[Sr25519Keyring::Alice, Sr25519Keyring::Bob, Sr25519Keyring::Charlie, Sr25519Keyring::Dave];
+
+/// Stores the session info of `session`, whose validators at indices `0..4` have
+/// the accounts `1..5`.
+fn seed_session(session: SessionIndex) {
+	let session_info = SessionInfo {
+		validators: KEYS.iter().map(|k| ValidatorId::from(k.public())).collect::<Vec<_>>().into(),
+		discovery_keys: KEYS.iter().map(|k| AuthorityDiscoveryId::from(k.public())).collect(),
+		assignment_keys: Vec::new(),
+		validator_groups: Vec::new().into(),
+		n_cores: 0,
+		zeroth_delay_tranche_width: 0,
+		relay_vrf_modulo_samples: 0,
+		n_delay_tranches: 0,
+		no_show_slots: 0,
+		needed_approvals: 0,
+		active_validator_indices: Vec::new(),
+		random_seed: [0u8; 32],
+		dispute_period: 6,
+	};
+	crate::session_info::Sessions::<Test>::insert(session, session_info);
+	crate::session_info::AccountKeys::<Test>::insert(session, vec![1, 2, 3, 4]);
+}
+
+fn set_severity(backer: u32, valid_voter: u32, escalation: u32) {
+	crate::configuration::ActiveConfig::<Test>::mutate(|config| {
+		config.dispute_backer_slash_fraction = Perbill::from_percent(backer);
+		config.dispute_valid_voter_slash_fraction = Perbill::from_percent(valid_voter);
+		config.dispute_repeat_offence_escalation = Perbill::from_percent(escalation);
+	});
+}
+
+fn slashing_events() -> Vec<Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| match r.event {
+			RuntimeEvent::ParasSlashing(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+fn v(indices: &[u32]) -> Vec<ValidatorIndex> {
+	indices.iter().map(|&i| ValidatorIndex(i)).collect()
+}
+
+#[test]
+fn backers_and_valid_voters_are_slashed_by_their_fractions() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		seed_session(0);
+		set_severity(10, 1, 0);
+
+		Handler::punish_for_invalid(0, CandidateHash(Hash::repeat_byte(1)), v(&[0, 1, 2]), v(&[0]));
+
+		// the most severe offence is reported first
+		assert_eq!(
+			take_reported_offences(),
+			vec![(Perbill::from_percent(10), vec![1]), (Perbill::from_percent(1), vec![2, 3])],
+		);
+	});
+}
+
+#[test]
+fn valid_voters_are_not_slashed_with_a_zero_fraction() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		seed_session(0);
+		set_severity(10, 0, 0);
+
+		Handler::punish_for_invalid(0, CandidateHash(Hash::repeat_byte(1)), v(&[0, 1, 2]), v(&[0]));
+
+		assert_eq!(take_reported_offences(), vec![(Perbill::from_percent(10), vec![1])]);
+	});
+}
+
+#[test]
+fn repeat_offences_escalate_until_the_next_era() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		seed_session(0);
+		set_severity(10, 0, 5);
+
+		for candidate in 1..=3 {
+			Handler::punish_for_invalid(
+				0,
+				CandidateHash(Hash::repeat_byte(candidate)),
+				v(&[0]),
+				v(&[0]),
+			);
+		}
+		assert_eq!(
+			take_reported_offences(),
+			vec![
+				(Perbill::from_percent(10), vec![1]),
+				(Perbill::from_percent(15), vec![1]),
+				(Perbill::from_percent(20), vec![1]),
+			],
+		);
+		assert_eq!(<EraOffences<Test>>::get(0, 1), 3);
+
+		// the offences of the previous era are pruned once a new session starts
+		CurrentEra::set(1);
+		Handler::initializer_on_new_session(1);
+		assert_eq!(<EraOffences<Test>>::iter_prefix(0).count(), 0);
+
+		Handler::punish_for_invalid(0, CandidateHash(Hash::repeat_byte(4)), v(&[0]), v(&[0]));
+		assert_eq!(take_reported_offences(), vec![(Perbill::from_percent(10), vec![1])]);
+		assert_eq!(<EraOffences<Test>>::get(1, 1), 1);
+	});
+}
+
+#[test]
+fn offences_of_all_past_eras_are_pruned() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		seed_session(0);
+		set_severity(10, 0, 5);
+
+		Handler::punish_for_invalid(0, CandidateHash(Hash::repeat_byte(1)), v(&[0]), v(&[0]));
+		CurrentEra::set(1);
+		Handler::punish_for_invalid(0, CandidateHash(Hash::repeat_byte(2)), v(&[1]), v(&[1]));
+
+		// eras 0 and 1 are both pruned when era 2 is skipped.
+		CurrentEra::set(3);
+		Handler::initializer_on_new_session(1);
+		assert_eq!(<EraOffences<Test>>::iter().count(), 0);
+	});
+}
+
+#[test]
+fn session_eras_group_sessions() {
+	type Eras = SessionEras<Test, frame_support::traits::ConstU32<6>>;
+
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		for (session, era) in [(0, 0), (5, 0), (6, 1), (13, 2)] {
+			crate::shared::Pallet::<Test>::set_session_index(session);
+			assert_eq!(Eras::get(), era);
+		}
+	});
+}
+
+#[test]
+fn each_slash_fraction_has_its_own_time_slot() {
+	let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+	let offence = |slash_fraction| {
+		SlashingOffence::new(
+			0,
+			candidate_hash,
+			4,
+			vec![1u64],
+			SlashingOffenceKind::ForInvalid,
+			slash_fraction,
+		)
+	};
+	let backers = offence(Perbill::from_percent(10));
+	let valid_voters = offence(Perbill::from_percent(1));
+
+	assert_ne!(backers.time_slot(), valid_voters.time_slot());
+	assert_eq!(backers.time_slot().dispute, valid_voters.time_slot().dispute);
+	assert_eq!(backers.slash_fraction(1), Perbill::from_percent(10));
+	assert_eq!(valid_voters.slash_fraction(2), Perbill::from_percent(1));
+}
+
+#[test]
+fn escalation_saturates_at_full_slash() {
+	assert_eq!(
+		escalated_slash_fraction(Perbill::from_percent(60), Perbill::from_percent(30), 2),
+		Perbill::from_percent(100),
+	);
+	assert_eq!(
+		escalated_slash_fraction(Perbill::from_percent(60), Perbill::from_percent(30), 1),
+		Perbill::from_percent(90),
+	);
+}
+
+#[test]
+fn current_session_offences_are_reported_with_events() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		System::set_block_number(1);
+		seed_session(0);
+		set_severity(10, 1, 0);
+		let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+
+		Handler::punish_for_invalid(0, candidate_hash, v(&[0, 1]), v(&[0]));
+
+		assert_eq!(
+			slashing_events(),
+			vec![
+				Event::SlashDetermined(
+					0,
+					candidate_hash,
+					ValidatorIndex(0),
+					Perbill::from_percent(10),
+					1
+				),
+				Event::SlashDetermined(
+					0,
+					candidate_hash,
+					ValidatorIndex(1),
+					Perbill::from_percent(1),
+					1
+				),
+				Event::OffenceReported(0, candidate_hash, v(&[0])),
+				Event::OffenceReported(0, candidate_hash, v(&[1])),
+			],
+		);
+		assert!(<UnappliedSlashes<Test>>::get(0, candidate_hash).is_none());
+	});
+}
+
+#[test]
+fn past_session_offences_are_kept_pending() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		System::set_block_number(1);
+		// the mock validator set is always in session 0
+		seed_session(1);
+		set_severity(10, 1, 0);
+		let candidate_hash = CandidateHash(Hash::repeat_byte(1));
+
+		Handler::punish_for_invalid(1, candidate_hash, v(&[0, 1]), v(&[0]));
+
+		assert!(take_reported_offences().is_empty());
+		assert_eq!(
+			slashing_events(),
+			vec![
+				Event::SlashDetermined(
+					1,
+					candidate_hash,
+					ValidatorIndex(0),
+					Perbill::from_percent(10),
+					1
+				),
+				Event::SlashDetermined(
+					1,
+					candidate_hash,
+					ValidatorIndex(1),
+					Perbill::from_percent(1),
+					1
+				),
+			],
+		);
+
+		let pending = <UnappliedSlashes<Test>>::get(1, candidate_hash).unwrap();
+		assert_eq!(pending.kind, SlashingOffenceKind::ForInvalid);
+		assert_eq!(pending.keys.keys().cloned().collect::<Vec<_>>(), v(&[0, 1]));
+		assert_eq!(
+			pending.slash_fractions.into_iter().collect::<Vec<_>>(),
+			vec![
+				(ValidatorIndex(0), Perbill::from_percent(10)),
+				(ValidatorIndex(1), Perbill::from_percent(1)),
+			],
+		);
+	});
+}
+
+#[test]
+fn migrate_to_v1_records_slash_fractions() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		StorageVersion::new(0).put::<ParasSlashing>();
+		let keys: BTreeMap<_, _> = KEYS
+			.iter()
+			.take(2)
+			.enumerate()
+			.map(|(i, k)| (ValidatorIndex(i as u32), ValidatorId::from(k.public())))
+			.collect();
+		let for_invalid = CandidateHash(Hash::repeat_byte(1));
+		let against_valid = CandidateHash(Hash::repeat_byte(2));
+		// `(keys, kind)`
+		for (candidate_hash, kind) in [
+			(for_invalid, SlashingOffenceKind::ForInvalid),
+			(against_valid, SlashingOffenceKind::AgainstValid),
+		] {
+			frame_support::storage::unhashed::put(
+				&<UnappliedSlashes<Test>>::hashed_key_for(1, candidate_hash),
+				&(keys.clone(), kind),
+			);
+		}
+
+		migration::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<ParasSlashing>(), 1);
+		for (candidate_hash, slash_fraction) in
+			[(for_invalid, Perbill::from_percent(100)), (against_valid, Perbill::zero())]
+		{
+			let pending = <UnappliedSlashes<Test>>::get(1, candidate_hash).unwrap();
+			assert_eq!(pending.keys, keys);
+			assert_eq!(
+				pending.slash_fractions.into_iter().collect::<Vec<_>>(),
+				vec![(ValidatorIndex(0), slash_fraction), (ValidatorIndex(1), slash_fraction)],
+			);
+		}
+	});
+}
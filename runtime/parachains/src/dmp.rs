@@ -41,30 +41,58 @@
 //! limit is set to the number of messages in the DownwardMessageQueue. Messages
 //! that would increase the number of messages in the queue above this hard
 //! limit are dropped.
+//!
+//! The queue of each para is stored in pages of up to `QUEUE_PAGE_SIZE` messages,
+//! accompanied by a [`QueueState`] which keeps track of the pages in use and the
+//! size of the queue. Messages are appended to the last page without reading it
+//! and removed from the first pages, so neither depends on the size of the backlog.
 
 use crate::{
 	configuration::{self, HostConfiguration},
 	initializer, FeeTracker,
 };
 use frame_support::pallet_prelude::*;
-use primitives::{DownwardMessage, Hash, Id as ParaId, InboundDownwardMessage};
+use primitives::{
+	vstaging::DmqStatus, DownwardMessage, Hash, Id as ParaId, InboundDownwardMessage,
+};
 use sp_core::MAX_POSSIBLE_ALLOCATION;
 use sp_runtime::{
-	traits::{BlakeTwo256, Hash as HashT, SaturatedConversion},
+	traits::{BlakeTwo256, Hash as HashT},
 	FixedU128, Saturating,
 };
-use sp_std::{fmt, prelude::*};
+use sp_std::{collections::btree_map::BTreeMap, fmt, prelude::*};
 use xcm::latest::SendError;
 
 pub use pallet::*;
 
+pub mod migration;
 #[cfg(test)]
 mod tests;
 
+/// The maximum number of messages stored in a single page of a downward message queue.
+pub(crate) const QUEUE_PAGE_SIZE: u32 = 16;
+
 const THRESHOLD_FACTOR: u32 = 2;
 const EXPONENTIAL_FEE_BASE: FixedU128 = FixedU128::from_rational(105, 100); // 1.05
 const MESSAGE_SIZE_FEE_BASE: FixedU128 = FixedU128::from_rational(1, 1000); // 0.001
 
+/// The bookkeeping of the paged downward message queue of a para.
+///
+/// The messages are stored in the pages `first_page..=last_page`, the oldest ones first.
+#[derive(Encode, Decode, TypeInfo, Clone, Default, PartialEq, RuntimeDebug)]
+pub struct QueueState {
+	/// The index of the page holding the oldest messages.
+	pub first_page: u32,
+	/// The index of the page new messages are appended to.
+	pub last_page: u32,
+	/// The number of messages in the last page.
+	pub last_page_len: u32,
+	/// The number of messages in the queue.
+	pub message_count: u32,
+	/// The total size of the messages in the queue, in bytes.
+	pub total_bytes: u64,
+}
+
 /// An error sending a downward message.
 #[cfg_attr(test, derive(Debug))]
 pub enum QueueDownwardMessageError {
@@ -110,21 +138,32 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
 	pub trait Config: frame_system::Config + configuration::Config {}
 
-	/// The downward messages addressed for a certain para.
+	/// The pages of the downward messages addressed for a certain para, indexed by the para and
+	/// the index of the page.
 	#[pallet::storage]
-	pub(crate) type DownwardMessageQueues<T: Config> = StorageMap<
+	pub(crate) type DownwardMessageQueuePages<T: Config> = StorageDoubleMap<
 		_,
 		Twox64Concat,
 		ParaId,
+		Twox64Concat,
+		u32,
 		Vec<InboundDownwardMessage<T::BlockNumber>>,
 		ValueQuery,
 	>;
 
+	/// The state of the downward message queue of each para.
+	///
+	/// Invariant: present if and only if the queue of the para is not empty.
+	#[pallet::storage]
+	pub(crate) type DownwardMessageQueueStates<T: Config> =
+		StorageMap<_, Twox64Concat, ParaId, QueueState>;
+
 	/// A mapping that stores the downward message queue MQC head for each para.
 	///
 	/// Each link in this chain has a form:
@@ -175,7 +214,11 @@ impl<T: Config> Pallet<T> {
 
 	/// Remove all relevant storage items for an outgoing parachain.
 	fn clean_dmp_after_outgoing(outgoing_para: &ParaId) {
-		DownwardMessageQueues::<T>::remove(outgoing_para);
+		if let Some(state) = DownwardMessageQueueStates::<T>::take(outgoing_para) {
+			for page in state.first_page..=state.last_page {
+				DownwardMessageQueuePages::<T>::remove(outgoing_para, page);
+			}
+		}
 		DownwardMessageQueueHeads::<T>::remove(outgoing_para);
 	}
 
@@ -233,14 +276,22 @@ impl<T: Config> Pallet<T> {
 			*head = new_head;
 		});
 
-		let q_len = DownwardMessageQueues::<T>::mutate(para, |v| {
-			v.push(inbound);
-			v.len()
+		let q_len = DownwardMessageQueueStates::<T>::mutate(para, |state| {
+			let state = state.get_or_insert_with(Default::default);
+			if state.last_page_len >= QUEUE_PAGE_SIZE {
+				state.last_page = state.last_page.wrapping_add(1);
+				state.last_page_len = 0;
+			}
+			DownwardMessageQueuePages::<T>::append(para, state.last_page, inbound);
+			state.last_page_len += 1;
+			state.message_count = state.message_count.saturating_add(1);
+			state.total_bytes = state.total_bytes.saturating_add(serialized_len as u64);
+			state.message_count
 		});
 
 		let threshold =
 			Self::dmq_max_length(config.max_downward_message_size).saturating_div(THRESHOLD_FACTOR);
-		if q_len > (threshold as usize) {
+		if q_len > threshold {
 			let message_size_factor =
 				FixedU128::from_u32(serialized_len.saturating_div(1024) as u32)
 					.saturating_mul(MESSAGE_SIZE_FEE_BASE);
@@ -255,7 +306,17 @@ impl<T: Config> Pallet<T> {
 		para: ParaId,
 		processed_downward_messages: u32,
 	) -> Result<(), ProcessedDownwardMessagesAcceptanceErr> {
-		let dmq_length = Self::dmq_length(para);
+		Self::check_processed_downward_messages_after(para, 0, processed_downward_messages)
+	}
+
+	/// Checks if the number of processed downward messages is valid for a candidate building on
+	/// candidates pending availability, which process `pending_processed` messages before it.
+	pub(crate) fn check_processed_downward_messages_after(
+		para: ParaId,
+		pending_processed: u32,
+		processed_downward_messages: u32,
+	) -> Result<(), ProcessedDownwardMessagesAcceptanceErr> {
+		let dmq_length = Self::dmq_length(para).saturating_sub(pending_processed);
 
 		if dmq_length > 0 && processed_downward_messages == 0 {
 			return Err(ProcessedDownwardMessagesAcceptanceErr::AdvancementRule)
@@ -271,26 +332,69 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Prunes the specified number of messages from the downward message queue of the given para.
+	///
+	/// Only the pages holding the pruned messages are touched.
 	pub(crate) fn prune_dmq(para: ParaId, processed_downward_messages: u32) -> Weight {
-		let q_len = DownwardMessageQueues::<T>::mutate(para, |q| {
-			let processed_downward_messages = processed_downward_messages as usize;
-			if processed_downward_messages > q.len() {
-				// reaching this branch is unexpected due to the constraint established by
-				// `check_processed_downward_messages`. But better be safe than sorry.
-				q.clear();
-			} else {
-				*q = q.split_off(processed_downward_messages);
+		let mut pages_touched = 0u64;
+		let q_len = DownwardMessageQueueStates::<T>::mutate_exists(para, |maybe_state| {
+			let state = match maybe_state {
+				Some(state) => state,
+				None => return 0,
+			};
+
+			// processing more messages than queued is unexpected due to the constraint established
+			// by `check_processed_downward_messages`. But better be safe than sorry.
+			let mut to_prune = processed_downward_messages.min(state.message_count);
+			while to_prune > 0 {
+				let mut page = DownwardMessageQueuePages::<T>::take(para, state.first_page);
+				pages_touched += 1;
+
+				let page_len = page.len() as u32;
+				let pruned = if page_len <= to_prune {
+					// the whole page is processed, move on to the next one.
+					state.first_page = state.first_page.wrapping_add(1);
+					page
+				} else {
+					let rest = page.split_off(to_prune as usize);
+					if state.first_page == state.last_page {
+						state.last_page_len = rest.len() as u32;
+					}
+					DownwardMessageQueuePages::<T>::insert(para, state.first_page, rest);
+					page
+				};
+
+				let pruned_bytes = pruned.iter().map(|m| m.msg.len() as u64).sum::<u64>();
+				to_prune = to_prune.saturating_sub(pruned.len() as u32);
+				state.message_count = state.message_count.saturating_sub(pruned.len() as u32);
+				state.total_bytes = state.total_bytes.saturating_sub(pruned_bytes);
+
+				// defensive: an empty page means the state is out of sync with the pages.
+				if page_len == 0 {
+					state.message_count = 0;
+				}
+				if state.message_count == 0 {
+					break
+				}
 			}
-			q.len()
+
+			let q_len = state.message_count;
+			if q_len == 0 {
+				// only non-empty if the state was out of sync with the pages.
+				for page in state.first_page..=state.last_page {
+					DownwardMessageQueuePages::<T>::remove(para, page);
+				}
+				*maybe_state = None;
+			}
+			q_len
 		});
 
 		let config = configuration::ActiveConfig::<T>::get();
 		let threshold =
 			Self::dmq_max_length(config.max_downward_message_size).saturating_div(THRESHOLD_FACTOR);
-		if q_len <= (threshold as usize) {
+		if q_len <= threshold {
 			Self::decrement_fee_factor(para);
 		}
-		T::DbWeight::get().reads_writes(1, 1)
+		T::DbWeight::get().reads_writes(1 + pages_touched, 1 + pages_touched)
 	}
 
 	/// Returns the Head of Message Queue Chain for the given para or `None` if there is none
@@ -304,9 +408,7 @@ impl<T: Config> Pallet<T> {
 	///
 	/// Returns 0 if the para doesn't have an associated downward message queue.
 	pub(crate) fn dmq_length(para: ParaId) -> u32 {
-		DownwardMessageQueues::<T>::decode_len(&para)
-			.unwrap_or(0)
-			.saturated_into::<u32>()
+		DownwardMessageQueueStates::<T>::get(&para).map_or(0, |state| state.message_count)
 	}
 
 	fn dmq_max_length(max_downward_message_size: u32) -> u32 {
@@ -317,7 +419,44 @@ impl<T: Config> Pallet<T> {
 	///
 	/// The most recent messages are the latest in the vector.
 	pub(crate) fn dmq_contents(recipient: ParaId) -> Vec<InboundDownwardMessage<T::BlockNumber>> {
-		DownwardMessageQueues::<T>::get(&recipient)
+		let state = match DownwardMessageQueueStates::<T>::get(&recipient) {
+			Some(state) => state,
+			None => return Vec::new(),
+		};
+
+		let mut contents = Vec::with_capacity(state.message_count as usize);
+		let mut page = state.first_page;
+		loop {
+			contents.extend(DownwardMessageQueuePages::<T>::get(&recipient, page));
+			if page == state.last_page {
+				break
+			}
+			page = page.wrapping_add(1);
+		}
+		contents
+	}
+
+	/// Returns the length and size of the downward message queue and the delivery fee factor of
+	/// each para which has pending downward messages or a raised delivery fee factor.
+	pub(crate) fn dmq_status() -> Vec<(ParaId, DmqStatus)> {
+		let mut status = BTreeMap::<ParaId, DmqStatus>::new();
+		for (para, state) in DownwardMessageQueueStates::<T>::iter() {
+			let entry = status.entry(para).or_insert_with(Self::initial_dmq_status);
+			entry.message_count = state.message_count;
+			entry.total_bytes = state.total_bytes;
+		}
+		for (para, delivery_fee_factor) in DeliveryFeeFactor::<T>::iter() {
+			if delivery_fee_factor == InitialFactor::get() && !status.contains_key(&para) {
+				continue
+			}
+			status.entry(para).or_insert_with(Self::initial_dmq_status).delivery_fee_factor =
+				delivery_fee_factor;
+		}
+		status.into_iter().collect()
+	}
+
+	fn initial_dmq_status() -> DmqStatus {
+		DmqStatus { message_count: 0, total_bytes: 0, delivery_fee_factor: InitialFactor::get() }
 	}
 
 	/// Raise the delivery fee factor by a multiplicative factor and stores the resulting value.
@@ -0,0 +1,142 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that is responsible for migration of storage.
+
+#[cfg(feature = "try-runtime")]
+use crate::dmp::DownwardMessageQueueHeads;
+use crate::dmp::{
+	Config, DownwardMessageQueuePages, DownwardMessageQueueStates, Pallet, QueueState,
+	QUEUE_PAGE_SIZE,
+};
+use frame_support::{
+	pallet_prelude::*,
+	storage_alias,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use primitives::{Id as ParaId, InboundDownwardMessage};
+use sp_std::prelude::*;
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+
+	#[storage_alias]
+	pub(crate) type DownwardMessageQueues<T: Config> = StorageMap<
+		Pallet<T>,
+		Twox64Concat,
+		ParaId,
+		Vec<InboundDownwardMessage<<T as frame_system::Config>::BlockNumber>>,
+		ValueQuery,
+	>;
+
+	/// Splits the downward message queue of each para, stored as a single vector in storage
+	/// version 0, into pages.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			log::trace!("Running pre_upgrade()");
+
+			ensure!(StorageVersion::get::<Pallet<T>>() == 0, "The migration requires version 0");
+			// The number of messages, the MQC head and the hash of the contents of each queue.
+			let queues = DownwardMessageQueues::<T>::iter()
+				.map(|(para, queue)| {
+					(
+						para,
+						queue.len() as u32,
+						DownwardMessageQueueHeads::<T>::get(para),
+						queue.using_encoded(sp_io::hashing::blake2_256),
+					)
+				})
+				.collect::<Vec<_>>();
+			Ok(queues.encode())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let mut queues = 0u64;
+				let mut pages = 0u64;
+				for (para, queue) in DownwardMessageQueues::<T>::drain() {
+					queues += 1;
+					if queue.is_empty() {
+						continue
+					}
+
+					let mut state = QueueState::default();
+					for (index, page) in queue.chunks(QUEUE_PAGE_SIZE as usize).enumerate() {
+						state.last_page = index as u32;
+						state.last_page_len = page.len() as u32;
+						state.message_count += page.len() as u32;
+						state.total_bytes += page.iter().map(|m| m.msg.len() as u64).sum::<u64>();
+						DownwardMessageQueuePages::<T>::insert(
+							para,
+							state.last_page,
+							page.to_vec(),
+						);
+						pages += 1;
+					}
+					DownwardMessageQueueStates::<T>::insert(para, state);
+				}
+				log::info!("Migrated {} downward message queues into {} pages", queues, pages);
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				T::DbWeight::get().reads_writes(queues + 1, 2 * queues + pages + 1)
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			log::trace!("Running post_upgrade()");
+
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be 1 after the migration"
+			);
+			ensure!(
+				DownwardMessageQueues::<T>::iter_keys().next().is_none(),
+				"The old queues should be drained"
+			);
+
+			let queues = Vec::<(ParaId, u32, primitives::Hash, [u8; 32])>::decode(&mut &state[..])
+				.map_err(|_| "The pre-upgrade state should decode")?;
+			for (para, message_count, mqc_head, contents_hash) in queues {
+				ensure!(
+					Pallet::<T>::dmq_length(para) == message_count,
+					"The number of messages should be kept"
+				);
+				ensure!(
+					DownwardMessageQueueHeads::<T>::get(para) == mqc_head,
+					"The MQC head should be kept"
+				);
+				ensure!(
+					Pallet::<T>::dmq_contents(para).using_encoded(sp_io::hashing::blake2_256) ==
+						contents_hash,
+					"The messages should be kept in order"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
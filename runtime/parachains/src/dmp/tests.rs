@@ -77,9 +77,142 @@ fn clean_dmp_works() {
 		let outgoing_paras = vec![a, b];
 		Dmp::initializer_on_new_session(&notification, &outgoing_paras);
 
-		assert!(DownwardMessageQueues::<Test>::get(&a).is_empty());
-		assert!(DownwardMessageQueues::<Test>::get(&b).is_empty());
-		assert!(!DownwardMessageQueues::<Test>::get(&c).is_empty());
+		assert!(Dmp::dmq_contents(a).is_empty());
+		assert!(Dmp::dmq_contents(b).is_empty());
+		assert!(!Dmp::dmq_contents(c).is_empty());
+		assert!(DownwardMessageQueueStates::<Test>::get(&a).is_none());
+		assert_eq!(DownwardMessageQueuePages::<Test>::iter_prefix(&a).count(), 0);
+	});
+}
+
+#[test]
+fn dmq_is_paged() {
+	let a = ParaId::from(1312);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		let count = 2 * QUEUE_PAGE_SIZE + 3;
+		for i in 0..count {
+			queue_downward_message(a, vec![i as u8; 2]).unwrap();
+		}
+
+		assert_eq!(
+			DownwardMessageQueueStates::<Test>::get(&a),
+			Some(QueueState {
+				first_page: 0,
+				last_page: 2,
+				last_page_len: 3,
+				message_count: count,
+				total_bytes: 2 * count as u64,
+			}),
+		);
+		assert_eq!(DownwardMessageQueuePages::<Test>::iter_prefix(&a).count(), 3);
+		let contents = Dmp::dmq_contents(a);
+		assert_eq!(contents.len(), count as usize);
+		assert!(contents.iter().enumerate().all(|(i, m)| m.msg == vec![i as u8; 2]));
+
+		// prune a whole page and part of the next one.
+		Dmp::prune_dmq(a, QUEUE_PAGE_SIZE + 1);
+		assert_eq!(Dmp::dmq_length(a), QUEUE_PAGE_SIZE + 2);
+		assert_eq!(DownwardMessageQueuePages::<Test>::iter_prefix(&a).count(), 2);
+		let state = DownwardMessageQueueStates::<Test>::get(&a).unwrap();
+		assert_eq!((state.first_page, state.last_page), (1, 2));
+		assert_eq!(state.total_bytes, 2 * (QUEUE_PAGE_SIZE + 2) as u64);
+		assert_eq!(Dmp::dmq_contents(a)[0].msg, vec![QUEUE_PAGE_SIZE as u8 + 1; 2]);
+
+		// prune everything but the last message and keep appending to the last page.
+		Dmp::prune_dmq(a, QUEUE_PAGE_SIZE + 1);
+		let state = DownwardMessageQueueStates::<Test>::get(&a).unwrap();
+		assert_eq!((state.first_page, state.last_page, state.last_page_len), (2, 2, 1));
+		queue_downward_message(a, vec![42]).unwrap();
+		assert_eq!(DownwardMessageQueuePages::<Test>::get(&a, 2).len(), 2);
+		assert_eq!(Dmp::dmq_length(a), 2);
+
+		// pruning more messages than queued empties the queue.
+		Dmp::prune_dmq(a, 3);
+		assert_eq!(Dmp::dmq_length(a), 0);
+		assert!(Dmp::dmq_contents(a).is_empty());
+		assert!(DownwardMessageQueueStates::<Test>::get(&a).is_none());
+		assert_eq!(DownwardMessageQueuePages::<Test>::iter_prefix(&a).count(), 0);
+	});
+}
+
+#[test]
+fn migrate_to_v1_pages_the_queues() {
+	use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+
+	let a = ParaId::from(1312);
+	let b = ParaId::from(228);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		StorageVersion::new(0).put::<Dmp>();
+		let count = 2 * QUEUE_PAGE_SIZE + 3;
+		let queue = (0..count)
+			.map(|i| InboundDownwardMessage { sent_at: i, msg: vec![i as u8; 2] })
+			.collect::<Vec<_>>();
+		migration::v1::DownwardMessageQueues::<Test>::insert(a, queue.clone());
+		migration::v1::DownwardMessageQueues::<Test>::insert(b, Vec::new());
+
+		migration::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<Dmp>(), 1);
+		assert_eq!(migration::v1::DownwardMessageQueues::<Test>::iter_keys().count(), 0);
+		assert_eq!(Dmp::dmq_contents(a), queue);
+		assert_eq!(
+			DownwardMessageQueueStates::<Test>::get(&a),
+			Some(QueueState {
+				first_page: 0,
+				last_page: 2,
+				last_page_len: 3,
+				message_count: count,
+				total_bytes: 2 * count as u64,
+			}),
+		);
+		assert_eq!(DownwardMessageQueuePages::<Test>::iter_prefix(&a).count(), 3);
+
+		// an empty queue doesn't leave any state behind.
+		assert!(Dmp::dmq_contents(b).is_empty());
+		assert!(DownwardMessageQueueStates::<Test>::get(&b).is_none());
+	});
+}
+
+#[test]
+fn dmq_status_reports_queues_and_fee_factors() {
+	let a = ParaId::from(123);
+	let b = ParaId::from(456);
+	let mut genesis = default_genesis_config();
+	genesis.configuration.config.max_downward_message_size = 16777216;
+
+	new_test_ext(genesis).execute_with(|| {
+		assert!(Dmp::dmq_status().is_empty());
+
+		queue_downward_message(a, vec![1, 2, 3]).unwrap();
+		queue_downward_message(a, vec![4, 5]).unwrap();
+		queue_downward_message(b, vec![6]).unwrap();
+
+		let raised = InitialFactor::get().saturating_mul(EXPONENTIAL_FEE_BASE);
+		assert_eq!(
+			Dmp::dmq_status(),
+			vec![
+				(a, DmqStatus { message_count: 2, total_bytes: 5, delivery_fee_factor: raised }),
+				(
+					b,
+					DmqStatus {
+						message_count: 1,
+						total_bytes: 1,
+						delivery_fee_factor: InitialFactor::get()
+					}
+				),
+			],
+		);
+
+		// an emptied queue with raised fees is still reported.
+		DeliveryFeeFactor::<Test>::insert(a, raised.saturating_mul(EXPONENTIAL_FEE_BASE));
+		Dmp::prune_dmq(a, 2);
+		Dmp::prune_dmq(b, 1);
+		assert_eq!(
+			Dmp::dmq_status(),
+			vec![(a, DmqStatus { message_count: 0, total_bytes: 0, delivery_fee_factor: raised })],
+		);
 	});
 }
 
@@ -21,7 +21,7 @@ use crate::{
 use frame_support::{pallet_prelude::*, traits::ReservableCurrency};
 use frame_system::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
-use polkadot_parachain::primitives::HorizontalMessages;
+use polkadot_parachain::primitives::{HorizontalMessages, IsSystem};
 use primitives::{
 	Balance, Hash, HrmpChannelId, Id as ParaId, InboundHrmpMessage, OutboundHrmpMessage,
 	SessionIndex,
@@ -36,6 +36,8 @@ use sp_std::{
 
 pub use pallet::*;
 
+pub mod migration;
+
 /// Maximum bound that can be set for inbound channels.
 ///
 /// If inaccurate, the weighing of this pallet might become inaccurate. It is expected form the
@@ -60,6 +62,11 @@ pub trait WeightInfo {
 	fn hrmp_cancel_open_request(c: u32) -> Weight;
 	fn clean_open_channel_requests(c: u32) -> Weight;
 	fn force_open_hrmp_channel() -> Weight;
+	fn hrmp_request_channel_resize() -> Weight;
+	fn hrmp_accept_channel_resize() -> Weight;
+	fn hrmp_cancel_channel_resize(c: u32) -> Weight;
+	fn process_hrmp_resize_requests(c: u32) -> Weight;
+	fn establish_system_channel() -> Weight;
 }
 
 /// A weight info that is only suitable for testing.
@@ -93,6 +100,21 @@ impl WeightInfo for TestWeightInfo {
 	fn force_open_hrmp_channel() -> Weight {
 		Weight::MAX
 	}
+	fn hrmp_request_channel_resize() -> Weight {
+		Weight::MAX
+	}
+	fn hrmp_accept_channel_resize() -> Weight {
+		Weight::MAX
+	}
+	fn hrmp_cancel_channel_resize(_: u32) -> Weight {
+		Weight::MAX
+	}
+	fn process_hrmp_resize_requests(_: u32) -> Weight {
+		Weight::MAX
+	}
+	fn establish_system_channel() -> Weight {
+		Weight::MAX
+	}
 }
 
 /// A description of a request to open an HRMP channel.
@@ -105,6 +127,9 @@ pub struct HrmpOpenChannelRequest {
 	pub _age: SessionIndex,
 	/// The amount that the sender supplied at the time of creation of this request.
 	pub sender_deposit: Balance,
+	/// The amount that the recipient supplied at the time of accepting this request. Zero until
+	/// the request is confirmed.
+	pub recipient_deposit: Balance,
 	/// The maximum message size that could be put into the channel.
 	pub max_message_size: u32,
 	/// The maximum number of messages that can be pending in the channel at once.
@@ -113,6 +138,24 @@ pub struct HrmpOpenChannelRequest {
 	pub max_total_size: u32,
 }
 
+/// A description of a request to change the capacity and the maximum message size of an open HRMP
+/// channel.
+#[derive(Encode, Decode, TypeInfo)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub struct HrmpChannelResizeRequest {
+	/// Indicates if this request was confirmed by the recipient.
+	pub confirmed: bool,
+	/// The requested maximum number of messages that can be pending in the channel at once.
+	pub max_capacity: u32,
+	/// The requested maximum message size that could be put into the channel.
+	pub max_message_size: u32,
+	/// The amount that the sender reserved on top of the deposit it supplied for the channel.
+	pub sender_deposit_top_up: Balance,
+	/// The amount that the recipient reserved on top of the deposit it supplied for the channel.
+	/// Zero until the request is confirmed.
+	pub recipient_deposit_top_up: Balance,
+}
+
 /// A metadata of an HRMP channel.
 #[derive(Encode, Decode, TypeInfo)]
 #[cfg_attr(test, derive(Debug))]
@@ -236,6 +279,7 @@ pub mod pallet {
 
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
@@ -276,6 +320,17 @@ pub mod pallet {
 		/// An HRMP channel was opened via Root origin.
 		/// `[sender, recipient, proposed_max_capacity, proposed_max_message_size]`
 		HrmpChannelForceOpened(ParaId, ParaId, u32, u32),
+		/// Resize of an HRMP channel requested.
+		/// `[sender, recipient, proposed_max_capacity, proposed_max_message_size]`
+		ChannelResizeRequested(ParaId, ParaId, u32, u32),
+		/// Resize of an HRMP channel accepted. `[sender, recipient]`
+		ChannelResizeAccepted(ParaId, ParaId),
+		/// An HRMP channel resize request was canceled by either party.
+		/// `[by_parachain, channel_id]`
+		ChannelResizeCanceled(ParaId, HrmpChannelId),
+		/// An HRMP channel with a system parachain was requested and accepted at once.
+		/// `[sender, recipient, max_capacity, max_message_size]`
+		HrmpSystemChannelOpened(ParaId, ParaId, u32, u32),
 	}
 
 	#[pallet::error]
@@ -318,6 +373,24 @@ pub mod pallet {
 		OpenHrmpChannelAlreadyConfirmed,
 		/// The provided witness data is wrong.
 		WrongWitness,
+		/// The channel to be resized doesn't exist.
+		ResizeHrmpChannelDoesntExist,
+		/// The channel to be resized is going to be closed.
+		ResizeHrmpChannelClosing,
+		/// There is already a request to resize the same channel.
+		ResizeHrmpChannelAlreadyRequested,
+		/// The requested capacity and maximum message size are the same as the current ones.
+		ResizeHrmpChannelUnchanged,
+		/// The resize request of the channel from the sender to the origin doesn't exist.
+		AcceptHrmpChannelResizeDoesntExist,
+		/// The resize request is already confirmed.
+		AcceptHrmpChannelResizeAlreadyConfirmed,
+		/// Canceling is requested by neither the sender nor recipient of the resize request.
+		CancelHrmpChannelResizeUnauthorized,
+		/// Cannot cancel an HRMP channel resize request because it is already confirmed.
+		HrmpChannelResizeAlreadyConfirmed,
+		/// The counterparty of a system channel is not a system parachain.
+		SystemChannelInvalidRecipient,
 	}
 
 	/// The set of pending HRMP open channel requests.
@@ -400,6 +473,22 @@ pub mod pallet {
 	pub type HrmpEgressChannelsIndex<T: Config> =
 		StorageMap<_, Twox64Concat, ParaId, Vec<ParaId>, ValueQuery>;
 
+	/// The set of pending HRMP channel resize requests. A channel has at most one pending resize
+	/// request and the confirmed ones are applied on the session change.
+	///
+	/// The set is accompanied by a list for iteration.
+	///
+	/// Invariants:
+	/// - There are no channels that exists in list but not in the set and vice versa.
+	/// - Each channel in the set is present in `HrmpChannels`.
+	#[pallet::storage]
+	pub type HrmpChannelResizeRequests<T: Config> =
+		StorageMap<_, Twox64Concat, HrmpChannelId, HrmpChannelResizeRequest>;
+
+	#[pallet::storage]
+	pub type HrmpChannelResizeRequestsList<T: Config> =
+		StorageValue<_, Vec<HrmpChannelId>, ValueQuery>;
+
 	/// Storage for the messages for each channel.
 	/// Invariant: cannot be non-empty if the corresponding channel in `HrmpChannels` is `None`.
 	#[pallet::storage]
@@ -463,6 +552,9 @@ pub mod pallet {
 		///
 		/// These numbers are a subject to the relay-chain configuration limits.
 		///
+		/// The sender deposit configured by the relay chain is reserved, unless either side of the
+		/// channel is a system parachain.
+		///
 		/// The channel can be opened only after the recipient confirms it and only on a session
 		/// change.
 		#[pallet::call_index(0)]
@@ -491,6 +583,9 @@ pub mod pallet {
 
 		/// Accept a pending open channel request from the given sender.
 		///
+		/// The recipient deposit configured by the relay chain is reserved, unless either side of
+		/// the channel is a system parachain.
+		///
 		/// The channel will be opened only on the next session boundary.
 		#[pallet::call_index(1)]
 		#[pallet::weight(<T as Config>::WeightInfo::hrmp_accept_open_channel())]
@@ -547,8 +642,7 @@ pub mod pallet {
 		#[pallet::weight(<T as Config>::WeightInfo::force_process_hrmp_open(*_channels))]
 		pub fn force_process_hrmp_open(origin: OriginFor<T>, _channels: u32) -> DispatchResult {
 			ensure_root(origin)?;
-			let host_config = configuration::Pallet::<T>::config();
-			Self::process_hrmp_open_channel_requests(&host_config);
+			Self::process_hrmp_open_channel_requests();
 			Ok(())
 		}
 
@@ -618,12 +712,113 @@ pub mod pallet {
 			));
 			Ok(())
 		}
+
+		/// Request to change the capacity and the maximum message size of an open channel from the
+		/// parachain to the given recipient. In-flight messages are kept.
+		///
+		/// - `proposed_max_capacity` - specifies how many messages can be in the channel at once.
+		/// - `proposed_max_message_size` - specifies the maximum size of the messages.
+		///
+		/// These numbers are a subject to the relay-chain configuration limits. If the configured
+		/// sender deposit grew since the channel was opened, the difference is reserved.
+		///
+		/// The channel is resized only after the recipient confirms it and only on a session
+		/// change.
+		#[pallet::call_index(8)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_request_channel_resize())]
+		pub fn hrmp_request_channel_resize(
+			origin: OriginFor<T>,
+			recipient: ParaId,
+			proposed_max_capacity: u32,
+			proposed_max_message_size: u32,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			Self::request_channel_resize(
+				origin,
+				recipient,
+				proposed_max_capacity,
+				proposed_max_message_size,
+			)?;
+			Self::deposit_event(Event::ChannelResizeRequested(
+				origin,
+				recipient,
+				proposed_max_capacity,
+				proposed_max_message_size,
+			));
+			Ok(())
+		}
+
+		/// Accept a pending resize request of the channel from the given sender. If the configured
+		/// recipient deposit grew since the channel was opened, the difference is reserved.
+		///
+		/// The channel will be resized only on the next session boundary.
+		#[pallet::call_index(9)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_accept_channel_resize())]
+		pub fn hrmp_accept_channel_resize(origin: OriginFor<T>, sender: ParaId) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			Self::accept_channel_resize(origin, sender)?;
+			Self::deposit_event(Event::ChannelResizeAccepted(sender, origin));
+			Ok(())
+		}
+
+		/// This cancels a pending channel resize request. It can be canceled by either of the
+		/// sender or the recipient of the channel. The origin must be either of those.
+		///
+		/// The cancellation happens immediately. It is not possible to cancel the request if it is
+		/// already accepted.
+		///
+		/// Total number of resize requests (i.e. `HrmpChannelResizeRequestsList`) must be provided
+		/// as witness data.
+		#[pallet::call_index(10)]
+		#[pallet::weight(<T as Config>::WeightInfo::hrmp_cancel_channel_resize(*resize_requests))]
+		pub fn hrmp_cancel_channel_resize(
+			origin: OriginFor<T>,
+			channel_id: HrmpChannelId,
+			resize_requests: u32,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			ensure!(
+				HrmpChannelResizeRequestsList::<T>::decode_len().unwrap_or_default() as u32 <=
+					resize_requests,
+				Error::<T>::WrongWitness
+			);
+			Self::cancel_channel_resize(origin, channel_id.clone())?;
+			Self::deposit_event(Event::ChannelResizeCanceled(origin, channel_id));
+			Ok(())
+		}
+
+		/// Open a pair of channels between the parachain and the given system parachain, one in
+		/// each direction. The channels are accepted on behalf of the recipients and opened with
+		/// the maximum capacity and message size allowed by the relay-chain configuration.
+		///
+		/// Channels opened this way don't require deposits, unlike channels with system parachains
+		/// opened through `hrmp_init_open_channel` and `hrmp_accept_open_channel`. A direction for
+		/// which a channel already exists or is accepted is skipped, a pending request is accepted
+		/// with the parameters and the sender deposit it was requested with.
+		///
+		/// The channels can be opened only on a session change.
+		#[pallet::call_index(11)]
+		#[pallet::weight(<T as Config>::WeightInfo::establish_system_channel())]
+		pub fn establish_system_channel(
+			origin: OriginFor<T>,
+			system_para: ParaId,
+		) -> DispatchResult {
+			let origin = ensure_parachain(<T as Config>::RuntimeOrigin::from(origin))?;
+			for (channel_id, request) in Self::open_system_channels(origin, system_para)? {
+				Self::deposit_event(Event::HrmpSystemChannelOpened(
+					channel_id.sender,
+					channel_id.recipient,
+					request.max_capacity,
+					request.max_message_size,
+				));
+			}
+			Ok(())
+		}
 	}
 }
 
 #[cfg(feature = "std")]
 fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32, u32)]) {
-	let host_config = configuration::Pallet::<T>::config();
 	for &(sender, recipient, max_capacity, max_message_size) in preopen_hrmp_channels {
 		if let Err(err) =
 			preopen_hrmp_channel::<T>(sender, recipient, max_capacity, max_message_size)
@@ -631,7 +826,7 @@ fn initialize_storage<T: Config>(preopen_hrmp_channels: &[(ParaId, ParaId, u32,
 			panic!("failed to initialize the genesis storage: {:?}", err);
 		}
 	}
-	<Pallet<T>>::process_hrmp_open_channel_requests(&host_config);
+	<Pallet<T>>::process_hrmp_open_channel_requests();
 }
 
 #[cfg(feature = "std")]
@@ -658,27 +853,26 @@ impl<T: Config> Pallet<T> {
 
 	/// Called by the initializer to note that a new session has started.
 	pub(crate) fn initializer_on_new_session(
-		notification: &initializer::SessionChangeNotification<T::BlockNumber>,
+		_notification: &initializer::SessionChangeNotification<T::BlockNumber>,
 		outgoing_paras: &[ParaId],
 	) -> Weight {
-		let w1 = Self::perform_outgoing_para_cleanup(&notification.prev_config, outgoing_paras);
-		Self::process_hrmp_open_channel_requests(&notification.prev_config);
+		let w1 = Self::perform_outgoing_para_cleanup(outgoing_paras);
+		Self::process_hrmp_open_channel_requests();
+		let w2 = Self::process_hrmp_channel_resize_requests();
 		Self::process_hrmp_close_channel_requests();
-		w1.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_open(
-			outgoing_paras.len() as u32
-		))
-		.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_close(
-			outgoing_paras.len() as u32
-		))
+		w1.saturating_add(w2)
+			.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_open(
+				outgoing_paras.len() as u32,
+			))
+			.saturating_add(<T as Config>::WeightInfo::force_process_hrmp_close(
+				outgoing_paras.len() as u32,
+			))
 	}
 
 	/// Iterate over all paras that were noted for offboarding and remove all the data
 	/// associated with them.
-	fn perform_outgoing_para_cleanup(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
-		let mut w = Self::clean_open_channel_requests(config, outgoing);
+	fn perform_outgoing_para_cleanup(outgoing: &[ParaId]) -> Weight {
+		let mut w = Self::clean_open_channel_requests(outgoing);
 		for outgoing_para in outgoing {
 			Self::clean_hrmp_after_outgoing(outgoing_para);
 
@@ -699,10 +893,7 @@ impl<T: Config> Pallet<T> {
 	// Go over the HRMP open channel requests and remove all in which offboarding paras participate.
 	//
 	// This will also perform the refunds for the counterparty if it doesn't offboard.
-	pub(crate) fn clean_open_channel_requests(
-		config: &HostConfiguration<T::BlockNumber>,
-		outgoing: &[ParaId],
-	) -> Weight {
+	pub(crate) fn clean_open_channel_requests(outgoing: &[ParaId]) -> Weight {
 		// First collect all the channel ids of the open requests in which there is at least one
 		// party presents in the outgoing list.
 		//
@@ -733,16 +924,13 @@ impl<T: Config> Pallet<T> {
 				);
 			}
 
-			// If the request was confirmed, then it means it was confirmed in the finished session.
-			// Therefore, the config's hrmp_recipient_deposit represents the actual value of the
-			// deposit.
-			//
-			// We still want to refund the deposit only if the para is not being offboarded.
+			// If the request was confirmed, return the deposit that the recipient supplied when
+			// accepting it, but only if the para is not being offboarded.
 			if req_data.confirmed {
 				if !outgoing.contains(&req_id.recipient) {
 					T::Currency::unreserve(
 						&req_id.recipient.into_account_truncating(),
-						config.hrmp_recipient_deposit.unique_saturated_into(),
+						req_data.recipient_deposit.unique_saturated_into(),
 					);
 				}
 				Self::decrease_accepted_channel_request_count(req_id.recipient);
@@ -776,7 +964,7 @@ impl<T: Config> Pallet<T> {
 	///
 	/// - prune the stale requests
 	/// - enact the confirmed requests
-	fn process_hrmp_open_channel_requests(config: &HostConfiguration<T::BlockNumber>) {
+	fn process_hrmp_open_channel_requests() {
 		let mut open_req_channels = HrmpOpenChannelRequestsList::<T>::get();
 		if open_req_channels.is_empty() {
 			return
@@ -805,7 +993,7 @@ impl<T: Config> Pallet<T> {
 						&channel_id,
 						HrmpChannel {
 							sender_deposit: request.sender_deposit,
-							recipient_deposit: config.hrmp_recipient_deposit,
+							recipient_deposit: request.recipient_deposit,
 							max_capacity: request.max_capacity,
 							max_total_size: request.max_total_size,
 							max_message_size: request.max_message_size,
@@ -838,6 +1026,49 @@ impl<T: Config> Pallet<T> {
 		HrmpOpenChannelRequestsList::<T>::put(open_req_channels);
 	}
 
+	/// Iterate over all channel resize requests and apply the confirmed ones. The unconfirmed
+	/// requests are kept.
+	fn process_hrmp_channel_resize_requests() -> Weight {
+		let resize_req_channels = HrmpChannelResizeRequestsList::<T>::get();
+		if resize_req_channels.is_empty() {
+			return Weight::zero()
+		}
+
+		let weight = <T as Config>::WeightInfo::process_hrmp_resize_requests(
+			resize_req_channels.len() as u32,
+		);
+		let (confirmed, pending): (Vec<HrmpChannelId>, Vec<HrmpChannelId>) =
+			resize_req_channels.into_iter().partition(|channel_id| {
+				HrmpChannelResizeRequests::<T>::get(channel_id).map_or(false, |req| req.confirmed)
+			});
+		HrmpChannelResizeRequestsList::<T>::put(pending);
+
+		for channel_id in confirmed {
+			let request = match HrmpChannelResizeRequests::<T>::take(&channel_id) {
+				Some(request) => request,
+				None => continue,
+			};
+
+			HrmpChannels::<T>::mutate(&channel_id, |channel| match channel {
+				Some(channel) => {
+					channel.max_capacity = request.max_capacity;
+					channel.max_message_size = request.max_message_size;
+					channel.sender_deposit =
+						channel.sender_deposit.saturating_add(request.sender_deposit_top_up);
+					channel.recipient_deposit =
+						channel.recipient_deposit.saturating_add(request.recipient_deposit_top_up);
+				},
+				None => {
+					// Can't normally happen since closing a channel removes its resize request, but
+					// return the deposits anyways.
+					Self::refund_channel_resize_deposits(&channel_id, &request);
+				},
+			});
+		}
+
+		weight
+	}
+
 	/// Iterate over all close channel requests unconditionally closing the channels.
 	fn process_hrmp_close_channel_requests() {
 		let close_reqs = HrmpCloseChannelRequestsList::<T>::take();
@@ -867,6 +1098,15 @@ impl<T: Config> Pallet<T> {
 			);
 		}
 
+		if let Some(resize_req) = HrmpChannelResizeRequests::<T>::take(channel_id) {
+			Self::refund_channel_resize_deposits(channel_id, &resize_req);
+			HrmpChannelResizeRequestsList::<T>::mutate(|resize_req_channels| {
+				if let Some(pos) = resize_req_channels.iter().position(|x| x == channel_id) {
+					resize_req_channels.swap_remove(pos);
+				}
+			});
+		}
+
 		HrmpChannelContents::<T>::remove(channel_id);
 
 		HrmpEgressChannelsIndex::<T>::mutate(&channel_id.sender, |v| {
@@ -935,6 +1175,18 @@ impl<T: Config> Pallet<T> {
 		config: &HostConfiguration<T::BlockNumber>,
 		sender: ParaId,
 		out_hrmp_msgs: &[OutboundHrmpMessage<ParaId>],
+	) -> Result<(), OutboundHrmpAcceptanceErr> {
+		Self::check_outbound_hrmp_after(config, sender, &BTreeMap::new(), out_hrmp_msgs)
+	}
+
+	/// Checks the outbound HRMP messages of a candidate building on candidates pending
+	/// availability. The candidates before it send `pending` messages to each recipient, given
+	/// as their count and total size, which are not queued in the channels yet.
+	pub(crate) fn check_outbound_hrmp_after(
+		config: &HostConfiguration<T::BlockNumber>,
+		sender: ParaId,
+		pending: &BTreeMap<ParaId, (u32, u32)>,
+		out_hrmp_msgs: &[OutboundHrmpMessage<ParaId>],
 	) -> Result<(), OutboundHrmpAcceptanceErr> {
 		if out_hrmp_msgs.len() as u32 > config.hrmp_max_message_num_per_candidate {
 			return Err(OutboundHrmpAcceptanceErr::MoreMessagesThanPermitted {
@@ -973,7 +1225,11 @@ impl<T: Config> Pallet<T> {
 				})
 			}
 
-			let new_total_size = channel.total_size + out_msg.data.len() as u32;
+			let (pending_count, pending_size) =
+				pending.get(&out_msg.recipient).copied().unwrap_or_default();
+
+			let new_total_size =
+				channel.total_size.saturating_add(pending_size) + out_msg.data.len() as u32;
 			if new_total_size > channel.max_total_size {
 				return Err(OutboundHrmpAcceptanceErr::TotalSizeExceeded {
 					idx,
@@ -982,7 +1238,7 @@ impl<T: Config> Pallet<T> {
 				})
 			}
 
-			let new_msg_count = channel.msg_count + 1;
+			let new_msg_count = channel.msg_count.saturating_add(pending_count) + 1;
 			if new_msg_count > channel.max_capacity {
 				return Err(OutboundHrmpAcceptanceErr::CapacityExceeded {
 					idx,
@@ -1133,6 +1389,24 @@ impl<T: Config> Pallet<T> {
 		recipient: ParaId,
 		proposed_max_capacity: u32,
 		proposed_max_message_size: u32,
+	) -> DispatchResult {
+		Self::do_init_open_channel(
+			origin,
+			recipient,
+			proposed_max_capacity,
+			proposed_max_message_size,
+			false,
+		)
+	}
+
+	/// Initiate opening a channel, reserving the configured sender deposit unless the channel is
+	/// `deposit_free`.
+	fn do_init_open_channel(
+		origin: ParaId,
+		recipient: ParaId,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+		deposit_free: bool,
 	) -> DispatchResult {
 		ensure!(origin != recipient, Error::<T>::OpenHrmpChannelToSelf);
 		ensure!(
@@ -1141,16 +1415,7 @@ impl<T: Config> Pallet<T> {
 		);
 
 		let config = <configuration::Pallet<T>>::config();
-		ensure!(proposed_max_capacity > 0, Error::<T>::OpenHrmpChannelZeroCapacity);
-		ensure!(
-			proposed_max_capacity <= config.hrmp_channel_max_capacity,
-			Error::<T>::OpenHrmpChannelCapacityExceedsLimit,
-		);
-		ensure!(proposed_max_message_size > 0, Error::<T>::OpenHrmpChannelZeroMessageSize);
-		ensure!(
-			proposed_max_message_size <= config.hrmp_channel_max_message_size,
-			Error::<T>::OpenHrmpChannelMessageSizeExceedsLimit,
-		);
+		Self::check_channel_limits(&config, proposed_max_capacity, proposed_max_message_size)?;
 
 		let channel_id = HrmpChannelId { sender: origin, recipient };
 		ensure!(
@@ -1174,9 +1439,10 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::OpenHrmpChannelLimitExceeded,
 		);
 
+		let sender_deposit = if deposit_free { 0 } else { config.hrmp_sender_deposit };
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_sender_deposit.unique_saturated_into(),
+			sender_deposit.unique_saturated_into(),
 		)?;
 
 		// mutating storage directly now -- shall not bail henceforth.
@@ -1187,7 +1453,8 @@ impl<T: Config> Pallet<T> {
 			HrmpOpenChannelRequest {
 				confirmed: false,
 				_age: 0,
-				sender_deposit: config.hrmp_sender_deposit,
+				sender_deposit,
+				recipient_deposit: 0,
 				max_capacity: proposed_max_capacity,
 				max_message_size: proposed_max_message_size,
 				max_total_size: config.hrmp_channel_max_total_size,
@@ -1226,6 +1493,16 @@ impl<T: Config> Pallet<T> {
 	/// Basically the same as [`hrmp_accept_open_channel`](Pallet::hrmp_accept_open_channel) but
 	/// intended for calling directly from other pallets rather than dispatched.
 	pub fn accept_open_channel(origin: ParaId, sender: ParaId) -> DispatchResult {
+		Self::do_accept_open_channel(origin, sender, false)
+	}
+
+	/// Accept a pending open channel request, reserving the configured recipient deposit unless
+	/// the channel is `deposit_free`.
+	fn do_accept_open_channel(
+		origin: ParaId,
+		sender: ParaId,
+		deposit_free: bool,
+	) -> DispatchResult {
 		let channel_id = HrmpChannelId { sender, recipient: origin };
 		let mut channel_req = HrmpOpenChannelRequests::<T>::get(&channel_id)
 			.ok_or(Error::<T>::AcceptHrmpChannelDoesntExist)?;
@@ -1246,14 +1523,16 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::AcceptHrmpChannelLimitExceeded,
 		);
 
+		let recipient_deposit = if deposit_free { 0 } else { config.hrmp_recipient_deposit };
 		T::Currency::reserve(
 			&origin.into_account_truncating(),
-			config.hrmp_recipient_deposit.unique_saturated_into(),
+			recipient_deposit.unique_saturated_into(),
 		)?;
 
 		// persist the updated open channel request and then increment the number of accepted
 		// channels.
 		channel_req.confirmed = true;
+		channel_req.recipient_deposit = recipient_deposit;
 		HrmpOpenChannelRequests::<T>::insert(&channel_id, channel_req);
 		HrmpAcceptedChannelRequestCount::<T>::insert(&origin, accepted_cnt + 1);
 
@@ -1356,6 +1635,152 @@ impl<T: Config> Pallet<T> {
 		Ok(())
 	}
 
+	/// Request and accept the channels in both directions between the origin and the given system
+	/// parachain, without deposits. Returns the channels that were accepted, with their accepted
+	/// requests.
+	///
+	/// Basically the same as [`establish_system_channel`](Pallet::establish_system_channel) but
+	/// intended for calling directly from other pallets rather than dispatched.
+	pub fn open_system_channels(
+		origin: ParaId,
+		system_para: ParaId,
+	) -> Result<Vec<(HrmpChannelId, HrmpOpenChannelRequest)>, DispatchError> {
+		ensure!(system_para.is_system(), Error::<T>::SystemChannelInvalidRecipient);
+
+		let config = <configuration::Pallet<T>>::config();
+		let mut accepted = Vec::new();
+		for (sender, recipient) in [(origin, system_para), (system_para, origin)] {
+			let channel_id = HrmpChannelId { sender, recipient };
+			if HrmpChannels::<T>::contains_key(&channel_id) {
+				continue
+			}
+
+			match HrmpOpenChannelRequests::<T>::get(&channel_id) {
+				Some(request) if request.confirmed => continue,
+				Some(_) => {},
+				None => Self::do_init_open_channel(
+					sender,
+					recipient,
+					config.hrmp_channel_max_capacity,
+					config.hrmp_channel_max_message_size,
+					true,
+				)?,
+			}
+			Self::do_accept_open_channel(recipient, sender, true)?;
+			let request = HrmpOpenChannelRequests::<T>::get(&channel_id)
+				.ok_or(Error::<T>::AcceptHrmpChannelDoesntExist)?;
+			accepted.push((channel_id, request));
+		}
+
+		ensure!(!accepted.is_empty(), Error::<T>::OpenHrmpChannelAlreadyExists);
+		Ok(accepted)
+	}
+
+	/// Request to resize the channel from the origin to the given recipient.
+	///
+	/// Basically the same as [`hrmp_request_channel_resize`](Pallet::hrmp_request_channel_resize)
+	/// but intended for calling directly from other pallets rather than dispatched.
+	pub fn request_channel_resize(
+		origin: ParaId,
+		recipient: ParaId,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+	) -> DispatchResult {
+		let channel_id = HrmpChannelId { sender: origin, recipient };
+		let channel =
+			HrmpChannels::<T>::get(&channel_id).ok_or(Error::<T>::ResizeHrmpChannelDoesntExist)?;
+		ensure!(
+			!HrmpCloseChannelRequests::<T>::contains_key(&channel_id),
+			Error::<T>::ResizeHrmpChannelClosing,
+		);
+		ensure!(
+			!HrmpChannelResizeRequests::<T>::contains_key(&channel_id),
+			Error::<T>::ResizeHrmpChannelAlreadyRequested,
+		);
+		ensure!(
+			proposed_max_capacity != channel.max_capacity ||
+				proposed_max_message_size != channel.max_message_size,
+			Error::<T>::ResizeHrmpChannelUnchanged,
+		);
+
+		let config = <configuration::Pallet<T>>::config();
+		Self::check_channel_limits(&config, proposed_max_capacity, proposed_max_message_size)?;
+
+		// The channel keeps the deposit it was opened with, so only the difference to the
+		// configured deposit is reserved. This includes the channels opened without deposits by
+		// `establish_system_channel`.
+		let sender_deposit_top_up =
+			config.hrmp_sender_deposit.saturating_sub(channel.sender_deposit);
+		T::Currency::reserve(
+			&origin.into_account_truncating(),
+			sender_deposit_top_up.unique_saturated_into(),
+		)?;
+
+		HrmpChannelResizeRequests::<T>::insert(
+			&channel_id,
+			HrmpChannelResizeRequest {
+				confirmed: false,
+				max_capacity: proposed_max_capacity,
+				max_message_size: proposed_max_message_size,
+				sender_deposit_top_up,
+				recipient_deposit_top_up: 0,
+			},
+		);
+		HrmpChannelResizeRequestsList::<T>::append(channel_id);
+
+		Ok(())
+	}
+
+	/// Accept a pending resize request of the channel from the given sender.
+	///
+	/// Basically the same as [`hrmp_accept_channel_resize`](Pallet::hrmp_accept_channel_resize)
+	/// but intended for calling directly from other pallets rather than dispatched.
+	pub fn accept_channel_resize(origin: ParaId, sender: ParaId) -> DispatchResult {
+		let channel_id = HrmpChannelId { sender, recipient: origin };
+		let mut resize_req = HrmpChannelResizeRequests::<T>::get(&channel_id)
+			.ok_or(Error::<T>::AcceptHrmpChannelResizeDoesntExist)?;
+		ensure!(!resize_req.confirmed, Error::<T>::AcceptHrmpChannelResizeAlreadyConfirmed);
+		let channel =
+			HrmpChannels::<T>::get(&channel_id).ok_or(Error::<T>::ResizeHrmpChannelDoesntExist)?;
+
+		let config = <configuration::Pallet<T>>::config();
+		let recipient_deposit_top_up =
+			config.hrmp_recipient_deposit.saturating_sub(channel.recipient_deposit);
+		T::Currency::reserve(
+			&origin.into_account_truncating(),
+			recipient_deposit_top_up.unique_saturated_into(),
+		)?;
+
+		resize_req.confirmed = true;
+		resize_req.recipient_deposit_top_up = recipient_deposit_top_up;
+		HrmpChannelResizeRequests::<T>::insert(&channel_id, resize_req);
+
+		Ok(())
+	}
+
+	fn cancel_channel_resize(origin: ParaId, channel_id: HrmpChannelId) -> DispatchResult {
+		// check if the origin is allowed to cancel the resize.
+		ensure!(channel_id.is_participant(origin), Error::<T>::CancelHrmpChannelResizeUnauthorized);
+
+		let resize_req = HrmpChannelResizeRequests::<T>::get(&channel_id)
+			.ok_or(Error::<T>::ResizeHrmpChannelDoesntExist)?;
+		ensure!(!resize_req.confirmed, Error::<T>::HrmpChannelResizeAlreadyConfirmed);
+
+		// Remove the request by the channel id and sync the accompanying list with the set.
+		HrmpChannelResizeRequests::<T>::remove(&channel_id);
+		HrmpChannelResizeRequestsList::<T>::mutate(|resize_req_channels| {
+			if let Some(pos) = resize_req_channels.iter().position(|x| x == &channel_id) {
+				resize_req_channels.swap_remove(pos);
+			}
+		});
+
+		// The recipient could not have topped up their deposit because we ensured that the
+		// request is not confirmed.
+		Self::refund_channel_resize_deposits(&channel_id, &resize_req);
+
+		Ok(())
+	}
+
 	/// Returns the list of MQC heads for the inbound channels of the given recipient para paired
 	/// with the sender para ids. This vector is sorted ascending by the para id and doesn't contain
 	/// multiple entries with the same sender.
@@ -1395,6 +1820,41 @@ impl<T: Config> Pallet<T> {
 }
 
 impl<T: Config> Pallet<T> {
+	/// Checks the proposed channel parameters against the limits of the given configuration.
+	fn check_channel_limits(
+		config: &HostConfiguration<T::BlockNumber>,
+		proposed_max_capacity: u32,
+		proposed_max_message_size: u32,
+	) -> DispatchResult {
+		ensure!(proposed_max_capacity > 0, Error::<T>::OpenHrmpChannelZeroCapacity);
+		ensure!(
+			proposed_max_capacity <= config.hrmp_channel_max_capacity,
+			Error::<T>::OpenHrmpChannelCapacityExceedsLimit,
+		);
+		ensure!(proposed_max_message_size > 0, Error::<T>::OpenHrmpChannelZeroMessageSize);
+		ensure!(
+			proposed_max_message_size <= config.hrmp_channel_max_message_size,
+			Error::<T>::OpenHrmpChannelMessageSizeExceedsLimit,
+		);
+		Ok(())
+	}
+
+	/// Returns the deposits that the participants of the channel reserved for the given resize
+	/// request.
+	fn refund_channel_resize_deposits(
+		channel_id: &HrmpChannelId,
+		resize_req: &HrmpChannelResizeRequest,
+	) {
+		T::Currency::unreserve(
+			&channel_id.sender.into_account_truncating(),
+			resize_req.sender_deposit_top_up.unique_saturated_into(),
+		);
+		T::Currency::unreserve(
+			&channel_id.recipient.into_account_truncating(),
+			resize_req.recipient_deposit_top_up.unique_saturated_into(),
+		);
+	}
+
 	/// Decreases the open channel request count for the given sender. If the value reaches zero
 	/// it is removed completely.
 	fn decrease_open_channel_request_count(sender: ParaId) {
@@ -1481,6 +1941,16 @@ impl<T: Config> Pallet<T> {
 			HrmpCloseChannelRequestsList::<T>::get().into_iter().collect::<BTreeSet<_>>(),
 		);
 
+		assert_eq!(
+			HrmpChannelResizeRequests::<T>::iter().map(|(k, _)| k).collect::<BTreeSet<_>>(),
+			HrmpChannelResizeRequestsList::<T>::get().into_iter().collect::<BTreeSet<_>>(),
+		);
+
+		// Only open channels can be resized.
+		for (resized_channel, _) in HrmpChannelResizeRequests::<T>::iter() {
+			assert!(HrmpChannels::<T>::contains_key(&resized_channel));
+		}
+
 		// A HRMP watermark can be None for an onboarded parachain. However, an offboarded parachain
 		// cannot have an HRMP watermark: it should've been cleanup.
 		assert_contains_only_onboarded(
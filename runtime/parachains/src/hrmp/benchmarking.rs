@@ -60,6 +60,7 @@ enum ParachainSetupStep {
 	CloseRequested,
 }
 
+/// Establishes a channel between two parachains up to the given step.
 fn establish_para_connection<T: Config>(
 	from: u32,
 	to: u32,
@@ -106,7 +107,7 @@ where
 		return output
 	}
 
-	Hrmp::<T>::process_hrmp_open_channel_requests(&Configuration::<T>::config());
+	Hrmp::<T>::process_hrmp_open_channel_requests();
 	if matches!(until, ParachainSetupStep::Established) {
 		return output
 	}
@@ -121,6 +122,65 @@ where
 	output
 }
 
+/// The channel parameters to resize an established channel to. The channel is established with the
+/// maximum parameters allowed by the configuration, so these are one less.
+fn resized_channel_params<T: Config>() -> (u32, u32) {
+	let config = Configuration::<T>::config();
+	(
+		config.hrmp_channel_max_capacity.saturating_sub(1).max(1),
+		config.hrmp_channel_max_message_size.saturating_sub(1).max(1),
+	)
+}
+
+/// Makes resizing the given established channel reserve the full configured deposits, which is the
+/// worst case for the resize calls.
+///
+/// The channel is treated as if it was opened while the configured deposits were zero, and both
+/// participants are funded to pay for the top-ups.
+fn reset_channel_deposits<T: Config>(sender: ParaId, recipient: ParaId) {
+	let config = Configuration::<T>::config();
+	let ed = T::Currency::minimum_balance();
+	HrmpChannels::<T>::mutate(&HrmpChannelId { sender, recipient }, |channel| {
+		if let Some(channel) = channel {
+			channel.sender_deposit = 0;
+			channel.recipient_deposit = 0;
+		}
+	});
+
+	let sender_deposit: BalanceOf<T> = config.hrmp_sender_deposit.unique_saturated_into();
+	let recipient_deposit: BalanceOf<T> = config.hrmp_recipient_deposit.unique_saturated_into();
+	T::Currency::make_free_balance_be(&sender.into_account_truncating(), sender_deposit + ed);
+	T::Currency::make_free_balance_be(&recipient.into_account_truncating(), recipient_deposit + ed);
+}
+
+/// Establishes a channel between two parachains and requests to resize it. The request is accepted
+/// by the recipient if `accept` is set.
+fn establish_resize_request<T: Config>(
+	from: u32,
+	to: u32,
+	accept: bool,
+) -> [(ParaId, crate::Origin); 2]
+where
+	<T as frame_system::Config>::RuntimeOrigin: From<crate::Origin>,
+{
+	let output = establish_para_connection::<T>(from, to, ParachainSetupStep::Established);
+	let [(sender, sender_origin), (recipient, recipient_origin)] = output.clone();
+	reset_channel_deposits::<T>(sender, recipient);
+	let (capacity, message_size) = resized_channel_params::<T>();
+
+	assert_ok!(Hrmp::<T>::hrmp_request_channel_resize(
+		sender_origin.into(),
+		recipient,
+		capacity,
+		message_size
+	));
+	if accept {
+		assert_ok!(Hrmp::<T>::hrmp_accept_channel_resize(recipient_origin.into(), sender));
+	}
+
+	output
+}
+
 /// Prefix value for account generation. These numbers are used as seeds to create distinct (para)
 /// accounts.
 ///
@@ -142,10 +202,10 @@ frame_benchmarking::benchmarks! {
 	where_clause { where <T as frame_system::Config>::RuntimeOrigin: From<crate::Origin> }
 
 	hrmp_init_open_channel {
-		let sender_id: ParaId = 1u32.into();
-		let sender_origin: crate::Origin = 1u32.into();
+		let sender_id: ParaId = 1001u32.into();
+		let sender_origin: crate::Origin = 1001u32.into();
 
-		let recipient_id: ParaId = 2u32.into();
+		let recipient_id: ParaId = 1002u32.into();
 
 		// make sure para is registered, and has enough balance.
 		let ed = T::Currency::minimum_balance();
@@ -164,7 +224,7 @@ frame_benchmarking::benchmarks! {
 
 	hrmp_accept_open_channel {
 		let [(sender, _), (recipient, recipient_origin)] =
-			establish_para_connection::<T>(1, 2, ParachainSetupStep::Requested);
+			establish_para_connection::<T>(1001, 1002, ParachainSetupStep::Requested);
 	}: _(recipient_origin, sender)
 	verify {
 		assert_last_event::<T>(Event::<T>::OpenChannelAccepted(sender, recipient).into());
@@ -172,7 +232,7 @@ frame_benchmarking::benchmarks! {
 
 	hrmp_close_channel {
 		let [(sender, sender_origin), (recipient, _)] =
-			establish_para_connection::<T>(1, 2, ParachainSetupStep::Established);
+			establish_para_connection::<T>(1001, 1002, ParachainSetupStep::Established);
 		let channel_id = HrmpChannelId { sender, recipient };
 	}: _(sender_origin, channel_id.clone())
 	verify {
@@ -200,8 +260,8 @@ frame_benchmarking::benchmarks! {
 		let config = Configuration::<T>::config();
 		let deposit: BalanceOf<T> = config.hrmp_sender_deposit.unique_saturated_into();
 
-		let para: ParaId = 1u32.into();
-		let para_origin: crate::Origin = 1u32.into();
+		let para: ParaId = 1001u32.into();
+		let para_origin: crate::Origin = 1001u32.into();
 		register_parachain_with_balance::<T>(para, deposit);
 		T::Currency::make_free_balance_be(&para.into_account_truncating(), deposit * 256u32.into());
 
@@ -272,7 +332,7 @@ frame_benchmarking::benchmarks! {
 		}
 
 		let [(sender, sender_origin), (recipient, _)] =
-			establish_para_connection::<T>(1, 2, ParachainSetupStep::Requested);
+			establish_para_connection::<T>(1001, 1002, ParachainSetupStep::Requested);
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, c + 1);
 		let channel_id = HrmpChannelId { sender, recipient };
 	}: _(sender_origin, channel_id, c + 1)
@@ -292,16 +352,15 @@ frame_benchmarking::benchmarks! {
 
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
 		let outgoing = (0..c).map(|id| (id + PREFIX_1).into()).collect::<Vec<ParaId>>();
-		let config = Configuration::<T>::config();
 	}: {
-		Hrmp::<T>::clean_open_channel_requests(&config, &outgoing);
+		Hrmp::<T>::clean_open_channel_requests(&outgoing);
 	} verify {
 		assert_eq!(HrmpOpenChannelRequestsList::<T>::decode_len().unwrap_or_default() as u32, 0);
 	}
 
 	force_open_hrmp_channel {
-		let sender_id: ParaId = 1u32.into();
-		let recipient_id: ParaId = 2u32.into();
+		let sender_id: ParaId = 1001u32.into();
+		let recipient_id: ParaId = 1002u32.into();
 
 		// make sure para is registered, and has enough balance.
 		let ed = T::Currency::minimum_balance();
@@ -325,6 +384,82 @@ frame_benchmarking::benchmarks! {
 			Event::<T>::HrmpChannelForceOpened(sender_id, recipient_id, capacity, message_size).into()
 		);
 	}
+
+	hrmp_request_channel_resize {
+		let [(sender, sender_origin), (recipient, _)] =
+			establish_para_connection::<T>(1001, 1002, ParachainSetupStep::Established);
+		reset_channel_deposits::<T>(sender, recipient);
+		let (capacity, message_size) = resized_channel_params::<T>();
+	}: _(sender_origin, recipient, capacity, message_size)
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::ChannelResizeRequested(sender, recipient, capacity, message_size).into()
+		);
+	}
+
+	hrmp_accept_channel_resize {
+		let [(sender, _), (recipient, recipient_origin)] =
+			establish_resize_request::<T>(1001, 1002, false);
+	}: _(recipient_origin, sender)
+	verify {
+		assert_last_event::<T>(Event::<T>::ChannelResizeAccepted(sender, recipient).into());
+	}
+
+	hrmp_cancel_channel_resize {
+		// number of items already existing in the `HrmpChannelResizeRequestsList`, other than the
+		// one that we remove.
+		let c in 0 .. MAX_UNIQUE_CHANNELS;
+
+		for id in 0 .. c {
+			let _ = establish_resize_request::<T>(PREFIX_0 + id, PREFIX_1 + id, false);
+		}
+
+		let [(sender, sender_origin), (recipient, _)] = establish_resize_request::<T>(1001, 1002, false);
+		assert_eq!(HrmpChannelResizeRequestsList::<T>::decode_len().unwrap_or_default() as u32, c + 1);
+		let channel_id = HrmpChannelId { sender, recipient };
+	}: _(sender_origin, channel_id, c + 1)
+	verify {
+		assert_eq!(HrmpChannelResizeRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
+	}
+
+	process_hrmp_resize_requests {
+		// number of channels that need to be resized. Worse case is an N-M relation: unique
+		// sender and recipients for all channels.
+		let c in 0 .. MAX_UNIQUE_CHANNELS;
+
+		for id in 0 .. c {
+			let _ = establish_resize_request::<T>(PREFIX_0 + id, PREFIX_1 + id, true);
+		}
+
+		assert_eq!(HrmpChannelResizeRequestsList::<T>::decode_len().unwrap_or_default() as u32, c);
+	}: {
+		Hrmp::<T>::process_hrmp_channel_resize_requests();
+	} verify {
+		assert_eq!(HrmpChannelResizeRequestsList::<T>::decode_len().unwrap_or_default() as u32, 0);
+	}
+
+	establish_system_channel {
+		let sender_id: ParaId = 1001u32.into();
+		let sender_origin: crate::Origin = 1001u32.into();
+		let system_id: ParaId = 1u32.into();
+
+		// make sure paras are registered. No deposits are needed for system channels.
+		let ed = T::Currency::minimum_balance();
+		register_parachain_with_balance::<T>(sender_id, ed);
+		register_parachain_with_balance::<T>(system_id, ed);
+
+		let config = Configuration::<T>::config();
+	}: _(sender_origin, system_id)
+	verify {
+		assert_last_event::<T>(
+			Event::<T>::HrmpSystemChannelOpened(
+				system_id,
+				sender_id,
+				config.hrmp_channel_max_capacity,
+				config.hrmp_channel_max_message_size,
+			).into()
+		);
+	}
 }
 
 frame_benchmarking::impl_benchmark_test_suite!(
@@ -0,0 +1,167 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that is responsible for migration of storage.
+
+use crate::{
+	configuration,
+	hrmp::{Config, HrmpOpenChannelRequest, Pallet},
+};
+use frame_support::{
+	pallet_prelude::*,
+	storage_alias,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use primitives::{Balance, HrmpChannelId, SessionIndex};
+use sp_std::prelude::*;
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+	#[cfg(feature = "try-runtime")]
+	use frame_support::traits::ReservableCurrency;
+	#[cfg(feature = "try-runtime")]
+	use primitives::Id as ParaId;
+	#[cfg(feature = "try-runtime")]
+	use sp_runtime::traits::{AccountIdConversion, UniqueSaturatedInto};
+	#[cfg(feature = "try-runtime")]
+	use sp_std::collections::btree_map::BTreeMap;
+
+	/// The open channel request as stored in storage version 0, without the recipient deposit.
+	#[derive(Encode, Decode)]
+	pub(crate) struct OldHrmpOpenChannelRequest {
+		pub(crate) confirmed: bool,
+		pub(crate) _age: SessionIndex,
+		pub(crate) sender_deposit: Balance,
+		pub(crate) max_message_size: u32,
+		pub(crate) max_capacity: u32,
+		pub(crate) max_total_size: u32,
+	}
+
+	#[storage_alias]
+	pub(crate) type HrmpOpenChannelRequests<T: Config> =
+		StorageMap<Pallet<T>, Twox64Concat, HrmpChannelId, OldHrmpOpenChannelRequest>;
+
+	/// Records the recipient deposit on the pending open channel requests.
+	///
+	/// Storage version 0 doesn't store the amount the recipient reserved on acceptance, it was
+	/// always the `hrmp_recipient_deposit` of the active configuration. The active configuration
+	/// only changes on a session change, which also turns every confirmed request into a channel.
+	/// Hence the confirmed requests found in storage were all accepted under the active
+	/// configuration, and its recipient deposit is the amount actually reserved for them. The
+	/// other requests don't have a recipient deposit yet.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			log::trace!("Running pre_upgrade()");
+
+			ensure!(StorageVersion::get::<Pallet<T>>() == 0, "The migration requires version 0");
+
+			// The recipients must still hold the deposits of the requests they accepted.
+			let recipient_deposit = <configuration::Pallet<T>>::config().hrmp_recipient_deposit;
+			let mut accepted = BTreeMap::<ParaId, Balance>::new();
+			for (channel_id, request) in HrmpOpenChannelRequests::<T>::iter() {
+				if request.confirmed {
+					let total = accepted.entry(channel_id.recipient).or_default();
+					*total = total.saturating_add(recipient_deposit);
+				}
+			}
+			for (recipient, total) in accepted {
+				let reserved: Balance =
+					T::Currency::reserved_balance(&recipient.into_account_truncating())
+						.unique_saturated_into();
+				ensure!(
+					reserved >= total,
+					"The recipients should have reserved the deposits of the accepted requests"
+				);
+			}
+
+			let requests = HrmpOpenChannelRequests::<T>::iter().collect::<Vec<_>>();
+			Ok((recipient_deposit, requests).encode())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let recipient_deposit = <configuration::Pallet<T>>::config().hrmp_recipient_deposit;
+				let mut requests = 0u64;
+				crate::hrmp::HrmpOpenChannelRequests::<T>::translate::<OldHrmpOpenChannelRequest, _>(
+					|_, old| {
+						requests += 1;
+						Some(HrmpOpenChannelRequest {
+							confirmed: old.confirmed,
+							_age: old._age,
+							sender_deposit: old.sender_deposit,
+							recipient_deposit: if old.confirmed { recipient_deposit } else { 0 },
+							max_message_size: old.max_message_size,
+							max_capacity: old.max_capacity,
+							max_total_size: old.max_total_size,
+						})
+					},
+				);
+				log::info!("Migrated {} HRMP open channel requests", requests);
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				T::DbWeight::get().reads_writes(requests + 2, requests + 1)
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			log::trace!("Running post_upgrade()");
+
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be 1 after the migration"
+			);
+
+			let (recipient_deposit, requests) = <(
+				Balance,
+				Vec<(HrmpChannelId, OldHrmpOpenChannelRequest)>,
+			)>::decode(&mut &state[..])
+			.map_err(|_| "The pre-upgrade state should decode")?;
+			ensure!(
+				crate::hrmp::HrmpOpenChannelRequests::<T>::iter_keys().count() == requests.len(),
+				"No open channel request should be added or removed"
+			);
+			for (channel_id, old) in requests {
+				let request = crate::hrmp::HrmpOpenChannelRequests::<T>::get(&channel_id)
+					.ok_or("The open channel requests should be kept")?;
+				ensure!(
+					request.confirmed == old.confirmed &&
+						request.sender_deposit == old.sender_deposit &&
+						request.max_message_size == old.max_message_size &&
+						request.max_capacity == old.max_capacity &&
+						request.max_total_size == old.max_total_size,
+					"The open channel requests should be kept unchanged"
+				);
+				let expected = if old.confirmed { recipient_deposit } else { 0 };
+				ensure!(
+					request.recipient_deposit == expected,
+					"Only the confirmed requests should record the recipient deposit"
+				);
+			}
+			Ok(())
+		}
+	}
+}
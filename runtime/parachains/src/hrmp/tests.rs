@@ -261,6 +261,43 @@ fn close_channel_works() {
 	});
 }
 
+#[test]
+fn outbound_messages_of_pending_candidates_count_towards_channel_capacity() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_channel_max_message_size = 20;
+	genesis.hrmp_channel_max_total_size = 20;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 20).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+		run_to_block(6, Some(vec![6]));
+		assert!(channel_exists(para_a, para_b));
+
+		let msgs = vec![OutboundHrmpMessage { recipient: para_b, data: vec![1; 8] }];
+		let config = Configuration::config();
+		assert!(Hrmp::check_outbound_hrmp_after(&config, para_a, &BTreeMap::new(), &msgs).is_ok());
+
+		// candidates pending availability sent messages to B which are not queued yet.
+		let pending = [(para_b, (1, 16))].into_iter().collect();
+		assert!(matches!(
+			Hrmp::check_outbound_hrmp_after(&config, para_a, &pending, &msgs),
+			Err(OutboundHrmpAcceptanceErr::TotalSizeExceeded { idx: 0, total_size: 24, limit: 20 }),
+		));
+		let pending = [(para_b, (2, 4))].into_iter().collect();
+		assert!(matches!(
+			Hrmp::check_outbound_hrmp_after(&config, para_a, &pending, &msgs),
+			Err(OutboundHrmpAcceptanceErr::CapacityExceeded { idx: 0, count: 3, limit: 2 }),
+		));
+	});
+}
+
 #[test]
 fn send_recv_messages() {
 	let para_a = 32.into();
@@ -490,8 +527,8 @@ fn verify_externally_accessible() {
 
 #[test]
 fn charging_deposits() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
 		register_parachain_with_balance(para_a, 0);
@@ -520,8 +557,8 @@ fn charging_deposits() {
 
 #[test]
 fn refund_deposit_on_normal_closure() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -551,10 +588,125 @@ fn refund_deposit_on_normal_closure() {
 	});
 }
 
+#[test]
+fn recipient_deposit_is_recorded_on_acceptance() {
+	let para_a = 2032.into();
+	let para_b = 2064.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		assert_eq!(
+			HrmpOpenChannelRequests::<Test>::get(&channel_id).unwrap().recipient_deposit,
+			15
+		);
+
+		// The configured deposit changes before the channel is opened.
+		crate::configuration::ActiveConfig::<Test>::mutate(|config| {
+			config.hrmp_recipient_deposit = 40;
+		});
+		run_to_block(6, Some(vec![6]));
+		assert_eq!(HrmpChannels::<Test>::get(&channel_id).unwrap().recipient_deposit, 15);
+
+		// Closing the channel returns what was actually reserved.
+		Hrmp::close_channel(para_a, channel_id).unwrap();
+		run_to_block(7, Some(vec![7]));
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn migrate_to_v1_records_recipient_deposits() {
+	use frame_support::traits::{OnRuntimeUpgrade, ReservableCurrency as _, StorageVersion};
+	use migration::v1::OldHrmpOpenChannelRequest;
+
+	let para_a = 2032.into();
+	let para_b = 2064.into();
+	let confirmed = HrmpChannelId { sender: para_a, recipient: para_b };
+	let pending = HrmpChannelId { sender: para_b, recipient: para_a };
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 2, 8).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		Hrmp::init_open_channel(para_b, para_a, 2, 8).unwrap();
+
+		// Rewrite the requests in the layout of version 0, which doesn't keep the recipient
+		// deposit. The deposits themselves stay reserved.
+		StorageVersion::new(0).put::<Hrmp>();
+		for (id, request) in HrmpOpenChannelRequests::<Test>::drain().collect::<Vec<_>>() {
+			migration::v1::HrmpOpenChannelRequests::<Test>::insert(
+				id,
+				OldHrmpOpenChannelRequest {
+					confirmed: request.confirmed,
+					_age: request._age,
+					sender_deposit: request.sender_deposit,
+					max_message_size: request.max_message_size,
+					max_capacity: request.max_capacity,
+					max_total_size: request.max_total_size,
+				},
+			);
+		}
+
+		migration::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<Hrmp>(), 1);
+		let request = HrmpOpenChannelRequests::<Test>::get(&confirmed).unwrap();
+		assert_eq!(
+			(request.confirmed, request.sender_deposit, request.recipient_deposit),
+			(true, 20, 15)
+		);
+		assert_eq!(
+			(request.max_message_size, request.max_capacity, request.max_total_size),
+			(8, 2, 16)
+		);
+		let request = HrmpOpenChannelRequests::<Test>::get(&pending).unwrap();
+		assert_eq!(
+			(request.confirmed, request.sender_deposit, request.recipient_deposit),
+			(false, 20, 0)
+		);
+
+		// The recorded deposit is what the recipient actually reserved, on top of the sender
+		// deposit of its own request.
+		assert_eq!(
+			<Test as Config>::Currency::reserved_balance(&para_b.into_account_truncating()),
+			20 + 15
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// The accepted request is opened on the next session change, before the configuration it
+		// was accepted with can change, and refunds exactly the recorded deposits when closed.
+		run_to_block(6, Some(vec![6]));
+		assert_eq!(HrmpChannels::<Test>::get(&confirmed).unwrap().recipient_deposit, 15);
+		Hrmp::close_channel(para_a, confirmed).unwrap();
+		run_to_block(7, Some(vec![7]));
+		assert_eq!(
+			<Test as Config>::Currency::reserved_balance(&para_b.into_account_truncating()),
+			20
+		);
+	});
+}
+
 #[test]
 fn refund_deposit_on_offboarding() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -593,8 +745,8 @@ fn refund_deposit_on_offboarding() {
 
 #[test]
 fn no_dangling_open_requests() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -631,8 +783,8 @@ fn no_dangling_open_requests() {
 
 #[test]
 fn cancel_pending_open_channel_request() {
-	let para_a = 32.into();
-	let para_b = 64.into();
+	let para_a = 2032.into();
+	let para_b = 2064.into();
 
 	let mut genesis = GenesisConfigBuilder::default();
 	genesis.hrmp_sender_deposit = 20;
@@ -661,6 +813,351 @@ fn cancel_pending_open_channel_request() {
 	});
 }
 
+#[test]
+fn resize_channel_works() {
+	let para_a = 32.into();
+	let para_a_origin: crate::Origin = 32.into();
+	let para_b = 64.into();
+	let para_b_origin: crate::Origin = 64.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+
+		// A sends a message to B that should survive the resize.
+		run_to_block(6, Some(vec![6]));
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		let msgs: HorizontalMessages =
+			vec![OutboundHrmpMessage { recipient: para_b, data: b"hey".to_vec() }]
+				.try_into()
+				.unwrap();
+		let _ = Hrmp::queue_outbound_hrmp(para_a, msgs);
+
+		Hrmp::hrmp_request_channel_resize(para_a_origin.into(), para_b, 2, 8).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::ChannelResizeRequested(para_a, para_b, 2, 8))));
+
+		// An unconfirmed request is not applied on the session change.
+		run_to_block(7, Some(vec![7]));
+		assert_eq!(HrmpChannels::<Test>::get(&channel_id).unwrap().max_capacity, 1);
+
+		Hrmp::hrmp_accept_channel_resize(para_b_origin.into(), para_a).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events()
+			.iter()
+			.any(|record| record.event ==
+				MockEvent::Hrmp(Event::ChannelResizeAccepted(para_a, para_b))));
+
+		// Advance to a block 8, but without session change. The channel keeps its size.
+		run_to_block(8, None);
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!((channel.max_capacity, channel.max_message_size), (1, 4));
+
+		// Now let the session change happen and thus resize the channel.
+		run_to_block(9, Some(vec![9]));
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!((channel.max_capacity, channel.max_message_size), (2, 8));
+		assert_eq!(channel.msg_count, 1);
+		assert_eq!(HrmpChannelContents::<Test>::get(&channel_id).len(), 1);
+		assert!(HrmpChannelResizeRequestsList::<Test>::get().is_empty());
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn resize_channel_checks() {
+	let para_a = 32.into();
+	let para_b = 64.into();
+	let para_c = 96.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		register_parachain(para_c);
+
+		run_to_block(5, Some(vec![4, 5]));
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 2, 8),
+			Error::<Test>::ResizeHrmpChannelDoesntExist
+		);
+
+		Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 1, 4),
+			Error::<Test>::ResizeHrmpChannelUnchanged
+		);
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 3, 4),
+			Error::<Test>::OpenHrmpChannelCapacityExceedsLimit
+		);
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 1, 0),
+			Error::<Test>::OpenHrmpChannelZeroMessageSize
+		);
+		assert_noop!(
+			Hrmp::accept_channel_resize(para_b, para_a),
+			Error::<Test>::AcceptHrmpChannelResizeDoesntExist
+		);
+
+		Hrmp::request_channel_resize(para_a, para_b, 2, 8).unwrap();
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 2, 4),
+			Error::<Test>::ResizeHrmpChannelAlreadyRequested
+		);
+
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		assert_noop!(
+			Hrmp::cancel_channel_resize(para_c, channel_id.clone()),
+			Error::<Test>::CancelHrmpChannelResizeUnauthorized
+		);
+
+		Hrmp::accept_channel_resize(para_b, para_a).unwrap();
+		assert_noop!(
+			Hrmp::accept_channel_resize(para_b, para_a),
+			Error::<Test>::AcceptHrmpChannelResizeAlreadyConfirmed
+		);
+		assert_noop!(
+			Hrmp::cancel_channel_resize(para_b, channel_id.clone()),
+			Error::<Test>::HrmpChannelResizeAlreadyConfirmed
+		);
+
+		// A channel which is going to be closed cannot be resized.
+		run_to_block(7, Some(vec![7]));
+		Hrmp::close_channel(para_b, channel_id).unwrap();
+		assert_noop!(
+			Hrmp::request_channel_resize(para_a, para_b, 1, 4),
+			Error::<Test>::ResizeHrmpChannelClosing
+		);
+	});
+}
+
+#[test]
+fn resize_channel_tops_up_deposits() {
+	let para_a = 2032.into();
+	let para_b = 2064.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		// The deposits grow after the channel was opened.
+		crate::configuration::ActiveConfig::<Test>::mutate(|config| {
+			config.hrmp_sender_deposit = 30;
+			config.hrmp_recipient_deposit = 25;
+		});
+
+		// Only the difference is reserved, by each party when it takes part in the resize.
+		Hrmp::request_channel_resize(para_a, para_b, 2, 8).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()), 70);
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()), 95);
+		Hrmp::accept_channel_resize(para_b, para_a).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()), 85);
+
+		run_to_block(7, Some(vec![7]));
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		let channel = HrmpChannels::<Test>::get(&channel_id).unwrap();
+		assert_eq!((channel.sender_deposit, channel.recipient_deposit), (30, 25));
+
+		// Closing the channel returns the whole deposits.
+		Hrmp::close_channel(para_a, channel_id).unwrap();
+		run_to_block(8, Some(vec![8]));
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()),
+			100
+		);
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_b.into_account_truncating()),
+			110
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn refund_deposit_on_resize_cancel_and_closure() {
+	let para_a = 2032.into();
+	let para_b = 2064.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(para_b, 110);
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, para_b, 1, 4).unwrap();
+		Hrmp::accept_open_channel(para_b, para_a).unwrap();
+		run_to_block(6, Some(vec![6]));
+
+		crate::configuration::ActiveConfig::<Test>::mutate(|config| {
+			config.hrmp_sender_deposit = 30;
+		});
+
+		// Canceling the request returns the top-up immediately.
+		let channel_id = HrmpChannelId { sender: para_a, recipient: para_b };
+		Hrmp::request_channel_resize(para_a, para_b, 2, 8).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()), 70);
+		Hrmp::cancel_channel_resize(para_b, channel_id.clone()).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()), 80);
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// Closing the channel drops its pending resize request and returns the top-up as well.
+		Hrmp::request_channel_resize(para_a, para_b, 2, 8).unwrap();
+		Hrmp::close_channel(para_b, channel_id.clone()).unwrap();
+		run_to_block(7, Some(vec![7]));
+		assert!(!channel_exists(para_a, para_b));
+		assert!(HrmpChannelResizeRequests::<Test>::get(&channel_id).is_none());
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()),
+			100
+		);
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn establish_system_channel_works() {
+	let para_a = 2032.into();
+	let para_a_origin: crate::Origin = 2032.into();
+	let system_para = 1.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(system_para, 100);
+
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::establish_system_channel(para_a_origin.into(), system_para).unwrap();
+		Hrmp::assert_storage_consistency_exhaustive();
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::HrmpSystemChannelOpened(para_a, system_para, 2, 8))));
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::HrmpSystemChannelOpened(system_para, para_a, 2, 8))));
+
+		// No deposits are taken from either side.
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()),
+			100
+		);
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&system_para.into_account_truncating()),
+			100
+		);
+
+		// Advance to a block 6, but without session change. That means that the channels have
+		// not been created yet.
+		run_to_block(6, None);
+		assert!(!channel_exists(para_a, system_para));
+		assert!(!channel_exists(system_para, para_a));
+
+		// Now let the session change happen and thus open the channels.
+		run_to_block(8, Some(vec![8]));
+		let channel =
+			HrmpChannels::<Test>::get(&HrmpChannelId { sender: para_a, recipient: system_para })
+				.unwrap();
+		assert_eq!((channel.max_capacity, channel.max_message_size), (2, 8));
+		assert_eq!((channel.sender_deposit, channel.recipient_deposit), (0, 0));
+		assert!(channel_exists(system_para, para_a));
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn system_channels_opened_by_request_take_deposits() {
+	let para_a = 2032.into();
+	let system_para = 1.into();
+
+	let mut genesis = GenesisConfigBuilder::default();
+	genesis.hrmp_sender_deposit = 20;
+	genesis.hrmp_recipient_deposit = 15;
+	new_test_ext(genesis.build()).execute_with(|| {
+		register_parachain_with_balance(para_a, 100);
+		register_parachain_with_balance(system_para, 100);
+
+		// Only `establish_system_channel` is deposit free, the regular flow still reserves
+		// deposits when one of the sides is a system parachain.
+		run_to_block(5, Some(vec![4, 5]));
+		Hrmp::init_open_channel(para_a, system_para, 2, 8).unwrap();
+		Hrmp::accept_open_channel(system_para, para_a).unwrap();
+		assert_eq!(<Test as Config>::Currency::free_balance(&para_a.into_account_truncating()), 80);
+		assert_eq!(
+			<Test as Config>::Currency::free_balance(&system_para.into_account_truncating()),
+			85
+		);
+
+		run_to_block(8, Some(vec![8]));
+		let channel =
+			HrmpChannels::<Test>::get(&HrmpChannelId { sender: para_a, recipient: system_para })
+				.unwrap();
+		assert_eq!((channel.sender_deposit, channel.recipient_deposit), (20, 15));
+		Hrmp::assert_storage_consistency_exhaustive();
+	});
+}
+
+#[test]
+fn establish_system_channel_checks() {
+	let para_a = 2032.into();
+	let para_a_origin: crate::Origin = 2032.into();
+	let para_b = 2064.into();
+	let system_para = 1.into();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		register_parachain(para_a);
+		register_parachain(para_b);
+		register_parachain(system_para);
+
+		run_to_block(5, Some(vec![4, 5]));
+		assert_noop!(
+			Hrmp::establish_system_channel(para_a_origin.clone().into(), para_b),
+			Error::<Test>::SystemChannelInvalidRecipient
+		);
+
+		// A pending request of the system parachain is accepted rather than requested again.
+		Hrmp::init_open_channel(system_para, para_a, 1, 4).unwrap();
+		Hrmp::establish_system_channel(para_a_origin.clone().into(), system_para).unwrap();
+		let request = HrmpOpenChannelRequests::<Test>::get(&HrmpChannelId {
+			sender: system_para,
+			recipient: para_a,
+		})
+		.unwrap();
+		assert!(request.confirmed);
+		assert_eq!((request.max_capacity, request.max_message_size), (1, 4));
+		assert!(System::events().iter().any(|record| record.event ==
+			MockEvent::Hrmp(Event::HrmpSystemChannelOpened(system_para, para_a, 1, 4))));
+		Hrmp::assert_storage_consistency_exhaustive();
+
+		// Once both directions are accepted or open there is nothing left to do.
+		assert_noop!(
+			Hrmp::establish_system_channel(para_a_origin.clone().into(), system_para),
+			Error::<Test>::OpenHrmpChannelAlreadyExists
+		);
+		run_to_block(6, Some(vec![6]));
+		assert!(channel_exists(para_a, system_para));
+		assert!(channel_exists(system_para, para_a));
+		assert_noop!(
+			Hrmp::establish_system_channel(para_a_origin.into(), system_para),
+			Error::<Test>::OpenHrmpChannelAlreadyExists
+		);
+	});
+}
+
 #[test]
 fn watermark_maxed_out_at_relay_parent() {
 	let para_a = 32.into();
@@ -0,0 +1,147 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! A module that is responsible for migration of storage.
+
+use crate::inclusion::{CandidatePendingAvailability, Config, Pallet, PendingAvailability};
+use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
+use frame_support::{
+	pallet_prelude::*,
+	storage_alias,
+	traits::{OnRuntimeUpgrade, StorageVersion},
+	weights::Weight,
+};
+use primitives::{
+	CandidateCommitments, CandidateDescriptor, CandidateHash, CoreIndex, GroupIndex, Id as ParaId,
+};
+use sp_std::prelude::*;
+
+/// The current storage version.
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
+pub mod v1 {
+	use super::*;
+
+	/// A candidate pending availability in storage version 0, which stored its commitments
+	/// separately.
+	#[derive(Encode, Decode)]
+	pub struct OldCandidatePendingAvailability<H, N> {
+		pub(crate) core: CoreIndex,
+		pub(crate) hash: CandidateHash,
+		pub(crate) descriptor: CandidateDescriptor<H>,
+		pub(crate) availability_votes: BitVec<u8, BitOrderLsb0>,
+		pub(crate) backers: BitVec<u8, BitOrderLsb0>,
+		pub(crate) relay_parent_number: N,
+		pub(crate) backed_in_number: N,
+		pub(crate) backing_group: GroupIndex,
+	}
+
+	#[storage_alias]
+	pub(crate) type PendingAvailabilityCommitments<T: Config> =
+		StorageMap<Pallet<T>, Twox64Concat, ParaId, CandidateCommitments>;
+
+	/// Turns the single candidate pending availability of each para into a chain of candidates
+	/// holding their commitments.
+	pub struct MigrateToV1<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			log::trace!("Running pre_upgrade()");
+
+			ensure!(StorageVersion::get::<Pallet<T>>() == 0, "The migration requires version 0");
+			// The candidate hash and the commitments of the candidate pending availability of each
+			// para which are kept by the migration.
+			let candidates = PendingAvailability::<T>::iter_keys()
+				.filter_map(|para_id| {
+					let candidate = frame_support::storage::unhashed::get::<
+						OldCandidatePendingAvailability<T::Hash, T::BlockNumber>,
+					>(&PendingAvailability::<T>::hashed_key_for(para_id))?;
+					let commitments = PendingAvailabilityCommitments::<T>::get(para_id)?;
+					Some((para_id, candidate.hash, commitments))
+				})
+				.collect::<Vec<_>>();
+			Ok(candidates.encode())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 0 {
+				let mut translated = 0u64;
+				PendingAvailability::<T>::translate::<
+					OldCandidatePendingAvailability<T::Hash, T::BlockNumber>,
+					_,
+				>(|para_id, old| {
+					translated += 1;
+
+					// defensive: the candidate is dropped if the storage is out of sync.
+					let commitments = PendingAvailabilityCommitments::<T>::take(&para_id)?;
+					Some(vec![CandidatePendingAvailability {
+						core: old.core,
+						hash: old.hash,
+						descriptor: old.descriptor,
+						commitments,
+						availability_votes: old.availability_votes,
+						backers: old.backers,
+						relay_parent_number: old.relay_parent_number,
+						backed_in_number: old.backed_in_number,
+						backing_group: old.backing_group,
+					}])
+				});
+				let _ = PendingAvailabilityCommitments::<T>::clear(u32::MAX, None);
+				log::info!("Migrated {} candidates pending availability", translated);
+
+				StorageVersion::new(1).put::<Pallet<T>>();
+
+				T::DbWeight::get().reads_writes(2 * translated + 1, 2 * translated + 1)
+			} else {
+				log::warn!("skipping v1, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			log::trace!("Running post_upgrade()");
+
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be 1 after the migration"
+			);
+			ensure!(
+				PendingAvailabilityCommitments::<T>::iter_keys().next().is_none(),
+				"The old commitments should be drained"
+			);
+
+			let candidates =
+				Vec::<(ParaId, CandidateHash, CandidateCommitments)>::decode(&mut &state[..])
+					.map_err(|_| "The pre-upgrade state should decode")?;
+			ensure!(
+				PendingAvailability::<T>::iter_keys().count() == candidates.len(),
+				"Only the candidates without commitments should be dropped"
+			);
+			for (para_id, hash, commitments) in candidates {
+				let chain = PendingAvailability::<T>::get(para_id).unwrap_or_default();
+				ensure!(chain.len() == 1, "Each chain should hold a single candidate");
+				ensure!(chain[0].hash == hash, "The candidate should be kept");
+				ensure!(
+					chain[0].commitments == commitments,
+					"The commitments should be moved into the candidate"
+				);
+			}
+
+			Ok(())
+		}
+	}
+}
@@ -19,26 +19,47 @@
 //!
 //! It is responsible for carrying candidates from being backable to being backed, and then from backed
 //! to included.
+//!
+//! A para which is scheduled on multiple cores can have a chain of candidates pending availability,
+//! each building on the previous one. The candidates of a chain are included in order, so a
+//! candidate which became available is only included once all of its predecessors are.
+//!
+//! Governance assigns additional cores to a parachain with
+//! [`crate::scheduler::Pallet::set_parachain_cores`]. Collation generation, backing and the
+//! provisioner still handle a single candidate per para and relay parent, and the persisted
+//! validation data and `candidate_pending_availability` runtime APIs only describe the first
+//! candidate of a chain, so the node side has to catch up before a parachain can make use of its
+//! additional cores.
 
 use crate::{
-	configuration, disputes, dmp, hrmp, paras, paras_inherent::DisputedBitfield,
-	scheduler::CoreAssignment, shared, ump,
+	configuration, disputes, dmp, hrmp,
+	metrics::METRICS,
+	paras,
+	paras_inherent::DisputedBitfield,
+	scheduler::{AssignmentKind, CoreAssignment},
+	shared, ump,
 };
 use bitvec::{order::Lsb0 as BitOrderLsb0, vec::BitVec};
 use frame_support::pallet_prelude::*;
 use parity_scale_codec::{Decode, Encode};
 use primitives::{
-	supermajority_threshold, AvailabilityBitfield, BackedCandidate, CandidateCommitments,
-	CandidateDescriptor, CandidateHash, CandidateReceipt, CommittedCandidateReceipt, CoreIndex,
-	GroupIndex, Hash, HeadData, Id as ParaId, SigningContext, UncheckedSignedAvailabilityBitfields,
-	ValidatorId, ValidatorIndex, ValidityAttestation,
+	supermajority_threshold, vstaging::ParaUsage, AvailabilityBitfield, BackedCandidate,
+	CandidateCommitments, CandidateDescriptor, CandidateHash, CandidateReceipt,
+	CommittedCandidateReceipt, CoreIndex, GroupIndex, Hash, HeadData, Id as ParaId, SessionIndex,
+	SigningContext, UncheckedSignedAvailabilityBitfields, ValidatorId, ValidatorIndex,
+	ValidityAttestation,
 };
 use scale_info::TypeInfo;
 use sp_runtime::{traits::One, DispatchError};
-use sp_std::{collections::btree_set::BTreeSet, prelude::*};
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
 
 pub use pallet::*;
 
+pub mod migration;
+
 #[cfg(test)]
 pub(crate) mod tests;
 
@@ -77,6 +98,8 @@ pub struct CandidatePendingAvailability<H, N> {
 	hash: CandidateHash,
 	/// The candidate descriptor.
 	descriptor: CandidateDescriptor<H>,
+	/// The candidate commitments.
+	commitments: CandidateCommitments,
 	/// The received availability votes. One bit per validator.
 	availability_votes: BitVec<u8, BitOrderLsb0>,
 	/// The backers of the candidate pending availability.
@@ -115,11 +138,17 @@ impl<H, N> CandidatePendingAvailability<H, N> {
 		&self.descriptor
 	}
 
+	/// Get the candidate commitments.
+	pub(crate) fn candidate_commitments(&self) -> &CandidateCommitments {
+		&self.commitments
+	}
+
 	#[cfg(any(feature = "runtime-benchmarks", test))]
 	pub(crate) fn new(
 		core: CoreIndex,
 		hash: CandidateHash,
 		descriptor: CandidateDescriptor<H>,
+		commitments: CandidateCommitments,
 		availability_votes: BitVec<u8, BitOrderLsb0>,
 		backers: BitVec<u8, BitOrderLsb0>,
 		relay_parent_number: N,
@@ -130,6 +159,7 @@ impl<H, N> CandidatePendingAvailability<H, N> {
 			core,
 			hash,
 			descriptor,
+			commitments,
 			availability_votes,
 			backers,
 			relay_parent_number,
@@ -142,11 +172,16 @@ impl<H, N> CandidatePendingAvailability<H, N> {
 /// A hook for applying validator rewards
 pub trait RewardValidators {
 	// Reward the validators with the given indices for issuing backing statements.
-	fn reward_backing(validators: impl IntoIterator<Item = ValidatorIndex>);
+	// Returns the weight consumed.
+	fn reward_backing(validators: impl IntoIterator<Item = ValidatorIndex>) -> Weight;
 	// Reward the validators with the given indices for issuing availability bitfields.
 	// Validators are sent to this hook when they have contributed to the availability
-	// of a candidate by setting a bit in their bitfield.
-	fn reward_bitfields(validators: impl IntoIterator<Item = ValidatorIndex>);
+	// of a candidate by setting a bit in their bitfield, once for every such candidate. The hook
+	// is called once for all candidates enacted in a block.
+	// Returns the weight consumed.
+	fn reward_bitfields(validators: impl IntoIterator<Item = ValidatorIndex>) -> Weight;
+	// The weight of `reward_bitfields` for at most the given number of distinct validators.
+	fn reward_bitfields_weight(validators: u32) -> Weight;
 }
 
 /// Helper return type for `process_candidates`.
@@ -167,6 +202,65 @@ impl<H> Default for ProcessedCandidates<H> {
 	}
 }
 
+/// The candidates of a para pending availability, which a newly backed candidate of the para builds
+/// on.
+pub(crate) struct PendingChain<N> {
+	/// The head data of the last candidate of the chain.
+	head_data: Option<HeadData>,
+	/// The number of downward messages processed by the candidates of the chain.
+	processed_downward_messages: u32,
+	/// The HRMP watermark of the last candidate of the chain.
+	hrmp_watermark: Option<N>,
+	/// Whether any candidate of the chain upgrades the validation code.
+	code_upgrade: bool,
+	/// The number and total size of the upward messages sent by the candidates of the chain.
+	upward_messages: (u32, u32),
+	/// The number and total size of the messages sent by the candidates of the chain to each
+	/// recipient.
+	horizontal_messages: BTreeMap<ParaId, (u32, u32)>,
+}
+
+impl<N> Default for PendingChain<N> {
+	fn default() -> Self {
+		Self {
+			head_data: None,
+			processed_downward_messages: 0,
+			hrmp_watermark: None,
+			code_upgrade: false,
+			upward_messages: (0, 0),
+			horizontal_messages: BTreeMap::new(),
+		}
+	}
+}
+
+impl<N: From<u32>> PendingChain<N> {
+	/// Whether there are no candidates pending availability.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.head_data.is_none()
+	}
+
+	/// Add a candidate with the given commitments to the end of the chain.
+	pub(crate) fn push(&mut self, commitments: &CandidateCommitments) {
+		self.head_data = Some(commitments.head_data.clone());
+		self.processed_downward_messages = self
+			.processed_downward_messages
+			.saturating_add(commitments.processed_downward_messages);
+		self.hrmp_watermark = Some(N::from(commitments.hrmp_watermark));
+		self.code_upgrade |= commitments.new_validation_code.is_some();
+
+		let (count, size) = &mut self.upward_messages;
+		for msg in commitments.upward_messages.iter() {
+			*count = count.saturating_add(1);
+			*size = size.saturating_add(msg.len() as u32);
+		}
+		for msg in commitments.horizontal_messages.iter() {
+			let (count, size) = self.horizontal_messages.entry(msg.recipient).or_default();
+			*count = count.saturating_add(1);
+			*size = size.saturating_add(msg.data.len() as u32);
+		}
+	}
+}
+
 /// Number of backing votes we need for a valid backing.
 ///
 /// WARNING: This check has to be kept in sync with the node side check in the backing
@@ -184,6 +278,7 @@ pub mod pallet {
 	use super::*;
 
 	#[pallet::pallet]
+	#[pallet::storage_version(migration::STORAGE_VERSION)]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
@@ -200,6 +295,11 @@ pub mod pallet {
 		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 		type DisputesHandler: disputes::DisputesHandler<Self::BlockNumber>;
 		type RewardValidators: RewardValidators;
+
+		/// The number of sessions the usage of the paras is kept in the usage ledger for,
+		/// including the current one.
+		#[pallet::constant]
+		type ParaUsageSessions: Get<SessionIndex>;
 	}
 
 	#[pallet::event]
@@ -235,7 +335,8 @@ pub mod pallet {
 		InvalidBitfieldSignature,
 		/// Candidate submitted but para not scheduled.
 		UnscheduledCandidate,
-		/// Candidate scheduled despite pending candidate already existing for the para.
+		/// Parathread candidate scheduled despite pending candidate already existing for the
+		/// parathread.
 		CandidateScheduledBeforeParaFree,
 		/// Candidate included with the wrong collator.
 		WrongCollator,
@@ -283,15 +384,36 @@ pub mod pallet {
 	pub(crate) type AvailabilityBitfields<T: Config> =
 		StorageMap<_, Twox64Concat, ValidatorIndex, AvailabilityBitfieldRecord<T::BlockNumber>>;
 
-	/// Candidates pending availability by `ParaId`.
+	/// The chain of candidates pending availability by `ParaId`, oldest first.
+	///
+	/// Each candidate builds on the previous one and occupies a different core. Never empty.
+	///
+	/// Bounded by the number of cores the para is assigned to.
 	#[pallet::storage]
-	pub(crate) type PendingAvailability<T: Config> =
-		StorageMap<_, Twox64Concat, ParaId, CandidatePendingAvailability<T::Hash, T::BlockNumber>>;
-
-	/// The commitments of candidates pending availability, by `ParaId`.
+	pub(crate) type PendingAvailability<T: Config> = StorageMap<
+		_,
+		Twox64Concat,
+		ParaId,
+		Vec<CandidatePendingAvailability<T::Hash, T::BlockNumber>>,
+	>;
+
+	/// The relay chain resources used by the included candidates of each para, by session.
+	///
+	/// Entries are kept for the last `ParaUsageSessions` sessions.
 	#[pallet::storage]
-	pub(crate) type PendingAvailabilityCommitments<T: Config> =
-		StorageMap<_, Twox64Concat, ParaId, CandidateCommitments>;
+	pub(crate) type ParaUsageLedger<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		SessionIndex,
+		Twox64Concat,
+		ParaId,
+		ParaUsage,
+		ValueQuery,
+	>;
+
+	/// The earliest session which may have entries in the `ParaUsageLedger`.
+	#[pallet::storage]
+	pub(crate) type EarliestParaUsageSession<T: Config> = StorageValue<_, SessionIndex>;
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {}
@@ -310,16 +432,33 @@ impl<T: Config> Pallet<T> {
 
 	/// Handle an incoming session change.
 	pub(crate) fn initializer_on_new_session(
-		_notification: &crate::initializer::SessionChangeNotification<T::BlockNumber>,
+		notification: &crate::initializer::SessionChangeNotification<T::BlockNumber>,
 	) {
 		// unlike most drain methods, drained elements are not cleared on `Drop` of the iterator
 		// and require consumption.
-		for _ in <PendingAvailabilityCommitments<T>>::drain() {}
 		for _ in <PendingAvailability<T>>::drain() {}
 		for _ in <AvailabilityBitfields<T>>::drain() {}
+
+		// drop the usage of all sessions which fell out of the ledger. More than one session falls
+		// out if sessions were skipped or if `ParaUsageSessions` was lowered.
+		let new_earliest = notification
+			.session_index
+			.saturating_add(1)
+			.saturating_sub(T::ParaUsageSessions::get());
+		let earliest = <EarliestParaUsageSession<T>>::get()
+			// the ledger was just introduced, its first entries are of the session which ended.
+			.unwrap_or_else(|| notification.session_index.saturating_sub(1));
+		for session_index in earliest..new_earliest {
+			let _ = <ParaUsageLedger<T>>::clear_prefix(session_index, u32::MAX, None);
+		}
+		<EarliestParaUsageSession<T>>::put(earliest.max(new_earliest));
 	}
 
-	/// Extract the freed cores based on cores that became available.
+	/// Extract the freed cores based on cores that became available, along with the weight of
+	/// enacting the candidates which occupied them, if `enact_candidate` is set.
+	///
+	/// The candidates of a chain are enacted in order, so a candidate which became available only
+	/// frees its core once all of its predecessors became available as well.
 	///
 	/// Updates storage items `PendingAvailability` and `AvailabilityBitfields`.
 	pub(crate) fn update_pending_availability_and_get_freed_cores<F>(
@@ -328,14 +467,22 @@ impl<T: Config> Pallet<T> {
 		signed_bitfields: UncheckedSignedAvailabilityBitfields,
 		core_lookup: F,
 		enact_candidate: bool,
-	) -> Vec<(CoreIndex, CandidateHash)>
+	) -> (Vec<(CoreIndex, CandidateHash)>, Weight)
 	where
 		F: Fn(CoreIndex) -> Option<ParaId>,
 	{
-		let mut assigned_paras_record = (0..expected_bits)
-			.map(|bit_index| core_lookup(CoreIndex::from(bit_index as u32)))
-			.map(|opt_para_id| {
-				opt_para_id.map(|para_id| (para_id, PendingAvailability::<T>::get(&para_id)))
+		// the pending chains of all paras occupying a core, and the para and position in its chain
+		// of the candidate occupying each core.
+		let mut pending_chains = BTreeMap::new();
+		let assigned_paras_record = (0..expected_bits)
+			.map(|bit_index| {
+				let core = CoreIndex::from(bit_index as u32);
+				let para_id = core_lookup(core)?;
+				let chain = pending_chains
+					.entry(para_id)
+					.or_insert_with(|| PendingAvailability::<T>::get(&para_id).unwrap_or_default());
+
+				chain.iter().position(|pending| pending.core == core).map(|i| (para_id, i))
 			})
 			.collect::<Vec<_>>();
 
@@ -348,23 +495,22 @@ impl<T: Config> Pallet<T> {
 				(checked_bitfield, validator_idx)
 			}) {
 			for (bit_idx, _) in checked_bitfield.0.iter().enumerate().filter(|(_, is_av)| **is_av) {
-				let pending_availability = if let Some((_, pending_availability)) =
-					assigned_paras_record[bit_idx].as_mut()
-				{
-					pending_availability
-				} else {
-					// For honest validators, this happens in case of unoccupied cores,
-					// which in turn happens in case of a disputed candidate.
-					// A malicious one might include arbitrary indices, but they are represented
-					// by `None` values and will be sorted out in the next if case.
-					continue
-				};
+				let pending_availability =
+					if let Some((para_id, i)) = assigned_paras_record[bit_idx] {
+						pending_chains.get_mut(&para_id).and_then(|chain| chain.get_mut(i))
+					} else {
+						// For honest validators, this happens in case of unoccupied cores,
+						// which in turn happens in case of a disputed candidate.
+						// A malicious one might include arbitrary indices, but they are represented
+						// by `None` values and will be sorted out in the next if case.
+						continue
+					};
 
 				// defensive check - this is constructed by loading the availability bitfield record,
 				// which is always `Some` if the core is occupied - that's why we're here.
 				let validator_index = validator_index.0 as usize;
 				if let Some(mut bit) =
-					pending_availability.as_mut().and_then(|candidate_pending_availability| {
+					pending_availability.and_then(|candidate_pending_availability| {
 						candidate_pending_availability.availability_votes.get_mut(validator_index)
 					}) {
 					*bit = true;
@@ -380,60 +526,64 @@ impl<T: Config> Pallet<T> {
 		let threshold = availability_threshold(validators.len());
 
 		let mut freed_cores = Vec::with_capacity(expected_bits);
-		for (para_id, pending_availability) in assigned_paras_record
-			.into_iter()
-			.flatten()
-			.filter_map(|(id, p)| p.map(|p| (id, p)))
-		{
-			if pending_availability.availability_votes.count_ones() >= threshold {
-				<PendingAvailability<T>>::remove(&para_id);
-				let commitments = match PendingAvailabilityCommitments::<T>::take(&para_id) {
-					Some(commitments) => commitments,
-					None => {
-						log::warn!(
-							target: LOG_TARGET,
-							"Inclusion::process_bitfields: PendingAvailability and PendingAvailabilityCommitments
-							are out of sync, did someone mess with the storage?",
-						);
-						continue
-					},
-				};
+		let mut weight = Weight::zero();
+		let mut availability_voters = Vec::new();
+		for (para_id, mut chain) in pending_chains {
+			let n_available = chain
+				.iter()
+				.take_while(|pending| pending.availability_votes.count_ones() >= threshold)
+				.count();
+			let still_pending = chain.split_off(n_available);
 
+			for pending_availability in chain {
 				if enact_candidate {
+					availability_voters.extend(voters(&pending_availability.availability_votes));
 					let receipt = CommittedCandidateReceipt {
 						descriptor: pending_availability.descriptor,
-						commitments,
+						commitments: pending_availability.commitments,
 					};
-					let _weight = Self::enact_candidate(
+					weight += Self::enact_candidate(
 						pending_availability.relay_parent_number,
 						receipt,
 						pending_availability.backers,
-						pending_availability.availability_votes,
 						pending_availability.core,
 						pending_availability.backing_group,
 					);
 				}
 
 				freed_cores.push((pending_availability.core, pending_availability.hash));
+			}
+
+			if still_pending.is_empty() {
+				<PendingAvailability<T>>::remove(&para_id);
 			} else {
-				<PendingAvailability<T>>::insert(&para_id, &pending_availability);
+				<PendingAvailability<T>>::insert(&para_id, still_pending);
 			}
 		}
 
-		freed_cores
+		// the availability points of all enacted candidates are recorded at once, so that every
+		// validator's entry is written only once.
+		if !availability_voters.is_empty() {
+			weight += T::RewardValidators::reward_bitfields(availability_voters);
+		}
+
+		// cores are freed in ascending order, as by the scheduler.
+		freed_cores.sort_by_key(|(core, _)| *core);
+
+		(freed_cores, weight)
 	}
 
 	/// Process a set of incoming bitfields.
 	///
 	/// Returns a `Vec` of `CandidateHash`es and their respective `AvailabilityCore`s that became available,
-	/// and cores free.
+	/// and cores free, along with the weight of enacting these candidates.
 	pub(crate) fn process_bitfields(
 		expected_bits: usize,
 		signed_bitfields: UncheckedSignedAvailabilityBitfields,
 		disputed_bitfield: DisputedBitfield,
 		core_lookup: impl Fn(CoreIndex) -> Option<ParaId>,
 		full_check: FullCheck,
-	) -> Result<Vec<(CoreIndex, CandidateHash)>, crate::inclusion::Error<T>> {
+	) -> Result<(Vec<(CoreIndex, CandidateHash)>, Weight), crate::inclusion::Error<T>> {
 		let validators = shared::Pallet::<T>::active_validator_keys();
 		let session_index = shared::Pallet::<T>::session_index();
 		let parent_hash = frame_system::Pallet::<T>::parent_hash();
@@ -448,15 +598,13 @@ impl<T: Config> Pallet<T> {
 			full_check,
 		)?;
 
-		let freed_cores = Self::update_pending_availability_and_get_freed_cores::<_>(
+		Ok(Self::update_pending_availability_and_get_freed_cores::<_>(
 			expected_bits,
 			&validators[..],
 			checked_bitfields,
 			core_lookup,
 			true,
-		);
-
-		Ok(freed_cores)
+		))
 	}
 
 	/// Process candidates that have been backed. Provide the relay storage root, a set of candidates
@@ -492,6 +640,9 @@ impl<T: Config> Pallet<T> {
 		let mut candidate_receipt_with_backing_validator_indices =
 			Vec::with_capacity(candidates.len());
 
+		// The chains the candidates build on, including the candidates checked before.
+		let mut pending_chains = BTreeMap::new();
+
 		// Do all checks before writing storage.
 		let core_indices_and_backers = {
 			let mut skip = 0;
@@ -524,11 +675,16 @@ impl<T: Config> Pallet<T> {
 			'next_backed_candidate: for (candidate_idx, backed_candidate) in
 				candidates.iter().enumerate()
 			{
+				let para_id = backed_candidate.descriptor().para_id;
+				let pending_chain =
+					pending_chains.entry(para_id).or_insert_with(|| Self::pending_chain(para_id));
+
 				match check_ctx.verify_backed_candidate(
 					parent_hash,
 					parent_storage_root,
 					candidate_idx,
 					backed_candidate,
+					pending_chain,
 				)? {
 					Err(FailedToCreatePVD) => {
 						log::debug!(
@@ -545,7 +701,6 @@ impl<T: Config> Pallet<T> {
 					Ok(rpn) => rpn,
 				}
 
-				let para_id = backed_candidate.descriptor().para_id;
 				let mut backers = bitvec::bitvec![u8, BitOrderLsb0; 0; validators.len()];
 
 				for (i, assignment) in scheduled[skip..].iter().enumerate() {
@@ -559,11 +714,13 @@ impl<T: Config> Pallet<T> {
 							);
 						}
 
-						ensure!(
-							<PendingAvailability<T>>::get(&para_id).is_none() &&
-								<PendingAvailabilityCommitments<T>>::get(&para_id).is_none(),
-							Error::<T>::CandidateScheduledBeforeParaFree,
-						);
+						// only parachains can be assigned multiple cores.
+						if let AssignmentKind::Parathread(..) = assignment.kind {
+							ensure!(
+								pending_chain.is_empty(),
+								Error::<T>::CandidateScheduledBeforeParaFree,
+							);
+						}
 
 						// account for already skipped, and then skip this one.
 						skip = i + skip + 1;
@@ -624,6 +781,7 @@ impl<T: Config> Pallet<T> {
 							backers,
 							assignment.group_idx,
 						));
+						pending_chain.push(&backed_candidate.candidate.commitments);
 						continue 'next_backed_candidate
 					}
 				}
@@ -665,12 +823,13 @@ impl<T: Config> Pallet<T> {
 			let (descriptor, commitments) =
 				(candidate.candidate.descriptor, candidate.candidate.commitments);
 
-			<PendingAvailability<T>>::insert(
+			<PendingAvailability<T>>::append(
 				&para_id,
 				CandidatePendingAvailability {
 					core,
 					hash: candidate_hash,
 					descriptor,
+					commitments,
 					availability_votes,
 					relay_parent_number,
 					backers: backers.to_bitvec(),
@@ -678,7 +837,6 @@ impl<T: Config> Pallet<T> {
 					backing_group: group,
 				},
 			);
-			<PendingAvailabilityCommitments<T>>::insert(&para_id, commitments);
 		}
 
 		Ok(ProcessedCandidates::<T::Hash> {
@@ -700,6 +858,7 @@ impl<T: Config> Pallet<T> {
 
 		if let Err(err) = check_ctx.check_validation_outputs(
 			para_id,
+			&PendingChain::default(),
 			&validation_outputs.head_data,
 			&validation_outputs.new_validation_code,
 			validation_outputs.processed_downward_messages,
@@ -723,32 +882,30 @@ impl<T: Config> Pallet<T> {
 		relay_parent_number: T::BlockNumber,
 		receipt: CommittedCandidateReceipt<T::Hash>,
 		backers: BitVec<u8, BitOrderLsb0>,
-		availability_votes: BitVec<u8, BitOrderLsb0>,
 		core_index: CoreIndex,
 		backing_group: GroupIndex,
 	) -> Weight {
 		let plain = receipt.to_plain();
 		let commitments = receipt.commitments;
 		let config = <configuration::Pallet<T>>::config();
-
-		T::RewardValidators::reward_backing(
-			backers
+		let usage = ParaUsage {
+			candidates: 1,
+			upward_messages: commitments.upward_messages.len() as u32,
+			upward_message_bytes: commitments.upward_messages.iter().map(|m| m.len() as u64).sum(),
+			horizontal_messages: commitments.horizontal_messages.len() as u32,
+			horizontal_message_bytes: commitments
+				.horizontal_messages
 				.iter()
-				.enumerate()
-				.filter(|(_, backed)| **backed)
-				.map(|(i, _)| ValidatorIndex(i as _)),
-		);
+				.map(|m| m.data.len() as u64)
+				.sum(),
+			downward_messages_processed: commitments.processed_downward_messages,
+			..Default::default()
+		};
 
-		T::RewardValidators::reward_bitfields(
-			availability_votes
-				.iter()
-				.enumerate()
-				.filter(|(_, voted)| **voted)
-				.map(|(i, _)| ValidatorIndex(i as _)),
-		);
+		let mut weight = T::RewardValidators::reward_backing(voters(&backers));
 
-		// initial weight is config read.
-		let mut weight = T::DbWeight::get().reads_writes(1, 0);
+		// config read.
+		weight += T::DbWeight::get().reads_writes(1, 0);
 		if let Some(new_code) = commitments.new_validation_code {
 			weight += <paras::Pallet<T>>::schedule_code_upgrade(
 				receipt.descriptor.para_id,
@@ -783,12 +940,37 @@ impl<T: Config> Pallet<T> {
 			backing_group,
 		));
 
-		weight +
-			<paras::Pallet<T>>::note_new_head(
-				receipt.descriptor.para_id,
-				commitments.head_data,
-				relay_parent_number,
-			)
+		weight += <paras::Pallet<T>>::note_new_head(
+			receipt.descriptor.para_id,
+			commitments.head_data,
+			relay_parent_number,
+		);
+
+		weight + Self::note_para_usage(receipt.descriptor.para_id, usage, weight)
+	}
+
+	/// Add the usage of an included candidate of the para, which took the given weight to
+	/// enact, to the usage ledger of the current session and to the runtime metrics.
+	fn note_para_usage(para_id: ParaId, mut usage: ParaUsage, weight: Weight) -> Weight {
+		usage.weight_ref_time = weight.ref_time();
+		usage.weight_proof_size = weight.proof_size();
+		METRICS.on_para_usage(para_id, &usage);
+
+		let session_index = <shared::Pallet<T>>::session_index();
+		<ParaUsageLedger<T>>::mutate(session_index, para_id, |total| {
+			total.saturating_accrue(&usage)
+		});
+
+		T::DbWeight::get().reads_writes(2, 1)
+	}
+
+	/// Returns the usage of the paras in the given session, sorted by para id.
+	///
+	/// Empty if the session is not in the usage ledger.
+	pub(crate) fn para_usage(session_index: SessionIndex) -> Vec<(ParaId, ParaUsage)> {
+		let mut usage = <ParaUsageLedger<T>>::iter_prefix(session_index).collect::<Vec<_>>();
+		usage.sort_by_key(|(para_id, _)| *para_id);
+		usage
 	}
 
 	/// Cleans up all paras pending availability that the predicate returns true for.
@@ -796,105 +978,141 @@ impl<T: Config> Pallet<T> {
 	/// The predicate accepts the index of the core and the block number the core has been occupied
 	/// since (i.e. the block number the candidate was backed at in this fork of the relay chain).
 	///
+	/// The candidates building on a cleaned up candidate are cleaned up as well.
+	///
 	/// Returns a vector of cleaned-up core IDs.
 	pub(crate) fn collect_pending(
 		pred: impl Fn(CoreIndex, T::BlockNumber) -> bool,
 	) -> Vec<CoreIndex> {
-		let mut cleaned_up_ids = Vec::new();
-		let mut cleaned_up_cores = Vec::new();
+		let cleaned_up = Self::truncate_pending_chains(|pending_record| {
+			pred(pending_record.core, pending_record.backed_in_number)
+		});
 
-		for (para_id, pending_record) in <PendingAvailability<T>>::iter() {
-			if pred(pending_record.core, pending_record.backed_in_number) {
-				cleaned_up_ids.push(para_id);
-				cleaned_up_cores.push(pending_record.core);
-			}
-		}
-
-		for para_id in cleaned_up_ids {
-			let pending = <PendingAvailability<T>>::take(&para_id);
-			let commitments = <PendingAvailabilityCommitments<T>>::take(&para_id);
-
-			if let (Some(pending), Some(commitments)) = (pending, commitments) {
-				// defensive: this should always be true.
+		cleaned_up
+			.into_iter()
+			.map(|pending| {
 				let candidate = CandidateReceipt {
 					descriptor: pending.descriptor,
-					commitments_hash: commitments.hash(),
+					commitments_hash: pending.commitments.hash(),
 				};
 
 				Self::deposit_event(Event::<T>::CandidateTimedOut(
 					candidate,
-					commitments.head_data,
+					pending.commitments.head_data,
 					pending.core,
 				));
-			}
-		}
 
-		cleaned_up_cores
+				pending.core
+			})
+			.collect()
 	}
 
 	/// Cleans up all paras pending availability that are in the given list of disputed candidates.
 	///
+	/// The candidates building on a disputed candidate are cleaned up as well.
+	///
 	/// Returns a vector of cleaned-up core IDs.
 	pub(crate) fn collect_disputed(disputed: &BTreeSet<CandidateHash>) -> Vec<CoreIndex> {
-		let mut cleaned_up_ids = Vec::new();
-		let mut cleaned_up_cores = Vec::new();
+		Self::truncate_pending_chains(|pending_record| disputed.contains(&pending_record.hash))
+			.into_iter()
+			.map(|pending| pending.core)
+			.collect()
+	}
 
-		for (para_id, pending_record) in <PendingAvailability<T>>::iter() {
-			if disputed.contains(&pending_record.hash) {
-				cleaned_up_ids.push(para_id);
-				cleaned_up_cores.push(pending_record.core);
+	/// Removes the first candidate the predicate returns true for and all of its successors from
+	/// each chain of candidates pending availability.
+	///
+	/// Returns the removed candidates.
+	fn truncate_pending_chains(
+		pred: impl Fn(&CandidatePendingAvailability<T::Hash, T::BlockNumber>) -> bool,
+	) -> Vec<CandidatePendingAvailability<T::Hash, T::BlockNumber>> {
+		let mut truncated_chains = Vec::new();
+		for (para_id, chain) in <PendingAvailability<T>>::iter() {
+			if let Some(i) = chain.iter().position(&pred) {
+				truncated_chains.push((para_id, chain, i));
 			}
 		}
 
-		for para_id in cleaned_up_ids {
-			let _ = <PendingAvailability<T>>::take(&para_id);
-			let _ = <PendingAvailabilityCommitments<T>>::take(&para_id);
+		let mut cleaned_up = Vec::new();
+		for (para_id, mut chain, i) in truncated_chains {
+			cleaned_up.extend(chain.split_off(i));
+
+			if chain.is_empty() {
+				<PendingAvailability<T>>::remove(&para_id);
+			} else {
+				<PendingAvailability<T>>::insert(&para_id, chain);
+			}
 		}
 
-		cleaned_up_cores
+		cleaned_up
 	}
 
-	/// Forcibly enact the candidate with the given ID as though it had been deemed available
+	/// Forcibly enact the candidates of the given para as though they had been deemed available
 	/// by bitfields.
 	///
 	/// Is a no-op if there is no candidate pending availability for this para-id.
 	/// This should generally not be used but it is useful during execution of Runtime APIs,
 	/// where the changes to the state are expected to be discarded directly after.
 	pub(crate) fn force_enact(para: ParaId) {
-		let pending = <PendingAvailability<T>>::take(&para);
-		let commitments = <PendingAvailabilityCommitments<T>>::take(&para);
-
-		if let (Some(pending), Some(commitments)) = (pending, commitments) {
-			let candidate =
-				CommittedCandidateReceipt { descriptor: pending.descriptor, commitments };
+		let mut availability_voters = Vec::new();
+		for pending in <PendingAvailability<T>>::take(&para).unwrap_or_default() {
+			availability_voters.extend(voters(&pending.availability_votes));
+			let candidate = CommittedCandidateReceipt {
+				descriptor: pending.descriptor,
+				commitments: pending.commitments,
+			};
 
 			Self::enact_candidate(
 				pending.relay_parent_number,
 				candidate,
 				pending.backers,
-				pending.availability_votes,
 				pending.core,
 				pending.backing_group,
 			);
 		}
+		if !availability_voters.is_empty() {
+			T::RewardValidators::reward_bitfields(availability_voters);
+		}
 	}
 
-	/// Returns the `CommittedCandidateReceipt` pending availability for the para provided, if any.
+	/// Returns the oldest `CommittedCandidateReceipt` pending availability for the para provided,
+	/// if any.
 	pub(crate) fn candidate_pending_availability(
 		para: ParaId,
 	) -> Option<CommittedCandidateReceipt<T::Hash>> {
-		<PendingAvailability<T>>::get(&para)
-			.map(|p| p.descriptor)
-			.and_then(|d| <PendingAvailabilityCommitments<T>>::get(&para).map(move |c| (d, c)))
-			.map(|(d, c)| CommittedCandidateReceipt { descriptor: d, commitments: c })
+		Self::pending_availability(para).map(|p| CommittedCandidateReceipt {
+			descriptor: p.descriptor,
+			commitments: p.commitments,
+		})
 	}
 
-	/// Returns the metadata around the candidate pending availability for the
+	/// Returns the metadata around the oldest candidate pending availability for the
 	/// para provided, if any.
 	pub(crate) fn pending_availability(
 		para: ParaId,
+	) -> Option<CandidatePendingAvailability<T::Hash, T::BlockNumber>> {
+		<PendingAvailability<T>>::get(&para).and_then(|chain| chain.into_iter().next())
+	}
+
+	/// Returns the metadata around the candidate of the para provided which is pending
+	/// availability on the given core, if any.
+	pub(crate) fn pending_availability_on_core(
+		para: ParaId,
+		core: CoreIndex,
 	) -> Option<CandidatePendingAvailability<T::Hash, T::BlockNumber>> {
 		<PendingAvailability<T>>::get(&para)
+			.and_then(|chain| chain.into_iter().find(|pending| pending.core == core))
+	}
+
+	/// Returns the chain of candidates pending availability of the para provided, which a newly
+	/// backed candidate of the para builds on.
+	pub(crate) fn pending_chain(para: ParaId) -> PendingChain<T::BlockNumber> {
+		let mut pending_chain = PendingChain::default();
+		for pending in <PendingAvailability<T>>::get(&para).unwrap_or_default() {
+			pending_chain.push(&pending.commitments);
+		}
+
+		pending_chain
 	}
 }
 
@@ -902,6 +1120,15 @@ const fn availability_threshold(n_validators: usize) -> usize {
 	supermajority_threshold(n_validators)
 }
 
+/// The indices of the validators whose bit is set.
+fn voters(votes: &BitVec<u8, BitOrderLsb0>) -> impl Iterator<Item = ValidatorIndex> + '_ {
+	votes
+		.iter()
+		.enumerate()
+		.filter(|(_, voted)| **voted)
+		.map(|(i, _)| ValidatorIndex(i as _))
+}
+
 #[derive(derive_more::From, Debug)]
 enum AcceptanceCheckErr<BlockNumber> {
 	HeadDataTooLarge,
@@ -946,7 +1173,8 @@ impl<T: Config> CandidateCheckContext<T> {
 		Self { config: <configuration::Pallet<T>>::config(), now, relay_parent_number }
 	}
 
-	/// Execute verification of the candidate.
+	/// Execute verification of the candidate, which builds on the given chain of candidates
+	/// pending availability.
 	///
 	/// Assures:
 	///  * correct expected relay parent reference
@@ -959,20 +1187,29 @@ impl<T: Config> CandidateCheckContext<T> {
 		parent_storage_root: T::Hash,
 		candidate_idx: usize,
 		backed_candidate: &BackedCandidate<<T as frame_system::Config>::Hash>,
+		pending_chain: &PendingChain<T::BlockNumber>,
 	) -> Result<Result<(), FailedToCreatePVD>, Error<T>> {
 		let para_id = backed_candidate.descriptor().para_id;
 		let now = <frame_system::Pallet<T>>::block_number();
 		let relay_parent_number = now - One::one();
 
 		{
-			// this should never fail because the para is registered
-			let persisted_validation_data = match crate::util::make_persisted_validation_data::<T>(
-				para_id,
-				relay_parent_number,
-				parent_storage_root,
-			) {
-				Some(l) => l,
-				None => return Ok(Err(FailedToCreatePVD)),
+			let persisted_validation_data = match pending_chain.head_data {
+				Some(ref parent_head) =>
+					crate::util::make_persisted_validation_data_with_parent::<T>(
+						parent_head.clone(),
+						relay_parent_number,
+						parent_storage_root,
+					),
+				// this should never fail because the para is registered
+				None => match crate::util::make_persisted_validation_data::<T>(
+					para_id,
+					relay_parent_number,
+					parent_storage_root,
+				) {
+					Some(l) => l,
+					None => return Ok(Err(FailedToCreatePVD)),
+				},
 			};
 
 			let expected = persisted_validation_data.hash();
@@ -1009,6 +1246,7 @@ impl<T: Config> CandidateCheckContext<T> {
 
 		if let Err(err) = self.check_validation_outputs(
 			para_id,
+			pending_chain,
 			&backed_candidate.candidate.commitments.head_data,
 			&backed_candidate.candidate.commitments.new_validation_code,
 			backed_candidate.candidate.commitments.processed_downward_messages,
@@ -1029,10 +1267,14 @@ impl<T: Config> CandidateCheckContext<T> {
 	}
 
 	/// Check the given outputs after candidate validation on whether it passes the acceptance
-	/// criteria.
+	/// criteria, as the successor of the given chain of candidates pending availability.
+	///
+	/// The messages sent by the candidates of the chain count towards the capacity of the upward
+	/// and outbound HRMP message queues, as they are only enqueued once the chain is enacted.
 	fn check_validation_outputs(
 		&self,
 		para_id: ParaId,
+		pending_chain: &PendingChain<T::BlockNumber>,
 		head_data: &HeadData,
 		new_validation_code: &Option<primitives::ValidationCode>,
 		processed_downward_messages: u32,
@@ -1048,7 +1290,8 @@ impl<T: Config> CandidateCheckContext<T> {
 		// if any, the code upgrade attempt is allowed.
 		if let Some(new_validation_code) = new_validation_code {
 			ensure!(
-				<paras::Pallet<T>>::can_upgrade_validation_code(para_id),
+				!pending_chain.code_upgrade &&
+					<paras::Pallet<T>>::can_upgrade_validation_code(para_id),
 				AcceptanceCheckErr::PrematureCodeUpgrade,
 			);
 			ensure!(
@@ -1058,10 +1301,34 @@ impl<T: Config> CandidateCheckContext<T> {
 		}
 
 		// check if the candidate passes the messaging acceptance criteria
-		<dmp::Pallet<T>>::check_processed_downward_messages(para_id, processed_downward_messages)?;
-		<ump::Pallet<T>>::check_upward_messages(&self.config, para_id, upward_messages)?;
+		<dmp::Pallet<T>>::check_processed_downward_messages_after(
+			para_id,
+			pending_chain.processed_downward_messages,
+			processed_downward_messages,
+		)?;
+		<ump::Pallet<T>>::check_upward_messages_after(
+			&self.config,
+			para_id,
+			pending_chain.upward_messages,
+			upward_messages,
+		)?;
 		<hrmp::Pallet<T>>::check_hrmp_watermark(para_id, self.relay_parent_number, hrmp_watermark)?;
-		<hrmp::Pallet<T>>::check_outbound_hrmp(&self.config, para_id, horizontal_messages)?;
+		if let Some(last_watermark) = pending_chain.hrmp_watermark {
+			// the watermark of the chain is not enacted yet.
+			ensure!(
+				hrmp_watermark >= last_watermark,
+				hrmp::HrmpWatermarkAcceptanceErr::AdvancementRule {
+					new_watermark: hrmp_watermark,
+					last_watermark,
+				},
+			);
+		}
+		<hrmp::Pallet<T>>::check_outbound_hrmp_after(
+			&self.config,
+			para_id,
+			&pending_chain.horizontal_messages,
+			horizontal_messages,
+		)?;
 
 		Ok(())
 	}
@@ -316,50 +316,43 @@ fn collect_pending_cleans_up_pending() {
 		let default_candidate = TestCandidateBuilder::default().build();
 		<PendingAvailability<Test>>::insert(
 			chain_a,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(0),
 				hash: default_candidate.hash(),
 				descriptor: default_candidate.descriptor.clone(),
+				commitments: default_candidate.commitments.clone(),
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 0,
 				backed_in_number: 0,
 				backers: default_backing_bitfield(),
 				backing_group: GroupIndex::from(0),
-			},
-		);
-		PendingAvailabilityCommitments::<Test>::insert(
-			chain_a,
-			default_candidate.commitments.clone(),
+			}],
 		);
 
 		<PendingAvailability<Test>>::insert(
 			&chain_b,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(1),
 				hash: default_candidate.hash(),
 				descriptor: default_candidate.descriptor,
+				commitments: default_candidate.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 0,
 				backed_in_number: 0,
 				backers: default_backing_bitfield(),
 				backing_group: GroupIndex::from(1),
-			},
+			}],
 		);
-		PendingAvailabilityCommitments::<Test>::insert(chain_b, default_candidate.commitments);
 
 		run_to_block(5, |_| None);
 
 		assert!(<PendingAvailability<Test>>::get(&chain_a).is_some());
 		assert!(<PendingAvailability<Test>>::get(&chain_b).is_some());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_some());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_b).is_some());
 
 		ParaInclusion::collect_pending(|core, _since| core == CoreIndex::from(0));
 
 		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
 		assert!(<PendingAvailability<Test>>::get(&chain_b).is_some());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_none());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_b).is_some());
 	});
 }
 
@@ -413,16 +406,17 @@ fn bitfield_checks() {
 				let receipt = dummy_candidate_receipt(dummy_hash());
 				PendingAvailability::<Test>::insert(
 					p_id,
-					CandidatePendingAvailability {
+					vec![CandidatePendingAvailability {
 						availability_votes: default_availability_votes(),
 						core: CoreIndex(0),
 						hash: receipt.hash(),
 						descriptor: receipt.descriptor,
+						commitments: Default::default(),
 						backers: BitVec::default(),
 						relay_parent_number: BlockNumber::from(0_u32),
 						backed_in_number: BlockNumber::from(0_u32),
 						backing_group: GroupIndex(0),
-					},
+					}],
 				)
 			}
 		};
@@ -492,8 +486,7 @@ fn bitfield_checks() {
 			.into();
 
 			assert_eq!(
-				<PendingAvailability<Test>>::get(chain_a)
-					.unwrap()
+				<PendingAvailability<Test>>::get(chain_a).unwrap()[0]
 					.availability_votes
 					.count_ones(),
 				0
@@ -513,8 +506,7 @@ fn bitfield_checks() {
 			);
 
 			assert_eq!(
-				<PendingAvailability<Test>>::get(chain_a)
-					.unwrap()
+				<PendingAvailability<Test>>::get(chain_a).unwrap()[0]
 					.availability_votes
 					.count_ones(),
 				0
@@ -552,8 +544,7 @@ fn bitfield_checks() {
 			.into();
 
 			assert_eq!(
-				<PendingAvailability<Test>>::get(chain_a)
-					.unwrap()
+				<PendingAvailability<Test>>::get(chain_a).unwrap()[0]
 					.availability_votes
 					.count_ones(),
 				0
@@ -573,8 +564,7 @@ fn bitfield_checks() {
 			);
 
 			assert_eq!(
-				<PendingAvailability<Test>>::get(chain_a)
-					.unwrap()
+				<PendingAvailability<Test>>::get(chain_a).unwrap()[0]
 					.availability_votes
 					.count_ones(),
 				0
@@ -602,7 +592,7 @@ fn bitfield_checks() {
 				DisputedBitfield::zeros(expected_bits()),
 				&core_lookup,
 				FullCheck::Yes,
-			), Ok(x) => { assert!(x.is_empty())});
+			), Ok((x, _)) => { assert!(x.is_empty())});
 		}
 
 		// empty bitfield signed: always ok, but kind of useless.
@@ -622,7 +612,7 @@ fn bitfield_checks() {
 				DisputedBitfield::zeros(expected_bits()),
 				&core_lookup,
 				FullCheck::Yes,
-			), Ok(x) => { assert!(x.is_empty())});
+			), Ok((x, _)) => { assert!(x.is_empty())});
 		}
 
 		// bitfield signed with pending bit signed.
@@ -634,18 +624,18 @@ fn bitfield_checks() {
 			let default_candidate = TestCandidateBuilder::default().build();
 			<PendingAvailability<Test>>::insert(
 				chain_a,
-				CandidatePendingAvailability {
+				vec![CandidatePendingAvailability {
 					core: CoreIndex::from(0),
 					hash: default_candidate.hash(),
 					descriptor: default_candidate.descriptor,
+					commitments: default_candidate.commitments,
 					availability_votes: default_availability_votes(),
 					relay_parent_number: 0,
 					backed_in_number: 0,
 					backers: default_backing_bitfield(),
 					backing_group: GroupIndex::from(0),
-				},
+				}],
 			);
-			PendingAvailabilityCommitments::<Test>::insert(chain_a, default_candidate.commitments);
 
 			*bare_bitfield.0.get_mut(0).unwrap() = true;
 			let signed = sign_bitfield(
@@ -662,50 +652,9 @@ fn bitfield_checks() {
 				DisputedBitfield::zeros(expected_bits()),
 				&core_lookup,
 				FullCheck::Yes,
-			), Ok(v) => { assert!(v.is_empty())} );
+			), Ok((v, _)) => { assert!(v.is_empty())} );
 
 			<PendingAvailability<Test>>::remove(chain_a);
-			PendingAvailabilityCommitments::<Test>::remove(chain_a);
-		}
-
-		// bitfield signed with pending bit signed, but no commitments.
-		{
-			let mut bare_bitfield = default_bitfield();
-
-			assert_eq!(core_lookup(CoreIndex::from(0)), Some(chain_a));
-
-			let default_candidate = TestCandidateBuilder::default().build();
-			<PendingAvailability<Test>>::insert(
-				chain_a,
-				CandidatePendingAvailability {
-					core: CoreIndex::from(0),
-					hash: default_candidate.hash(),
-					descriptor: default_candidate.descriptor,
-					availability_votes: default_availability_votes(),
-					relay_parent_number: 0,
-					backed_in_number: 0,
-					backers: default_backing_bitfield(),
-					backing_group: GroupIndex::from(0),
-				},
-			);
-
-			*bare_bitfield.0.get_mut(0).unwrap() = true;
-			let signed = sign_bitfield(
-				&keystore,
-				&validators[0],
-				ValidatorIndex(0),
-				bare_bitfield,
-				&signing_context,
-			);
-
-			// no core is freed
-			assert_matches!(ParaInclusion::process_bitfields(
-				expected_bits(),
-				vec![signed.into()],
-				DisputedBitfield::zeros(expected_bits()),
-				&core_lookup,
-				FullCheck::Yes,
-			), Ok(v) => { assert!(v.is_empty()) });
 		}
 	});
 }
@@ -769,18 +718,18 @@ fn supermajority_bitfields_trigger_availability() {
 
 		<PendingAvailability<Test>>::insert(
 			chain_a,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(0),
 				hash: candidate_a.hash(),
 				descriptor: candidate_a.clone().descriptor,
+				commitments: candidate_a.clone().commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 0,
 				backed_in_number: 0,
 				backers: backing_bitfield(&[3, 4]),
 				backing_group: GroupIndex::from(0),
-			},
+			}],
 		);
-		PendingAvailabilityCommitments::<Test>::insert(chain_a, candidate_a.clone().commitments);
 
 		let candidate_b = TestCandidateBuilder {
 			para_id: chain_b,
@@ -791,18 +740,18 @@ fn supermajority_bitfields_trigger_availability() {
 
 		<PendingAvailability<Test>>::insert(
 			chain_b,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(1),
 				hash: candidate_b.hash(),
 				descriptor: candidate_b.descriptor,
+				commitments: candidate_b.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 0,
 				backed_in_number: 0,
 				backers: backing_bitfield(&[0, 2]),
 				backing_group: GroupIndex::from(1),
-			},
+			}],
 		);
-		PendingAvailabilityCommitments::<Test>::insert(chain_b, candidate_b.commitments);
 
 		// this bitfield signals that a and b are available.
 		let a_and_b_available = {
@@ -861,7 +810,7 @@ fn supermajority_bitfields_trigger_availability() {
 				&core_lookup,
 				FullCheck::Yes,
 			),
-			Ok(v) => {
+			Ok((v, _)) => {
 				assert_eq!(vec![(CoreIndex(0), candidate_a.hash())], v);
 			}
 		);
@@ -869,9 +818,7 @@ fn supermajority_bitfields_trigger_availability() {
 		// chain A had 4 signing off, which is >= threshold.
 		// chain B has 3 signing off, which is < threshold.
 		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_none());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_b).is_some());
-		assert_eq!(<PendingAvailability<Test>>::get(&chain_b).unwrap().availability_votes, {
+		assert_eq!(<PendingAvailability<Test>>::get(&chain_b).unwrap()[0].availability_votes, {
 			// check that votes from first 3 were tracked.
 
 			let mut votes = default_availability_votes();
@@ -906,6 +853,209 @@ fn supermajority_bitfields_trigger_availability() {
 	});
 }
 
+#[test]
+fn chained_candidates_are_enacted_in_order() {
+	let chain_a = ParaId::from(1_u32);
+
+	let paras = vec![(chain_a, ParaKind::Parachain)];
+	let validators = vec![
+		Sr25519Keyring::Alice,
+		Sr25519Keyring::Bob,
+		Sr25519Keyring::Charlie,
+		Sr25519Keyring::Dave,
+		Sr25519Keyring::Ferdie,
+	];
+	let keystore: KeystorePtr = Arc::new(LocalKeystore::in_memory());
+	for validator in validators.iter() {
+		Keystore::sr25519_generate_new(
+			&*keystore,
+			PARACHAIN_KEY_TYPE_ID,
+			Some(&validator.to_seed()),
+		)
+		.unwrap();
+	}
+	let validator_public = validator_pubkeys(&validators);
+
+	new_test_ext(genesis_config(paras)).execute_with(|| {
+		shared::Pallet::<Test>::set_active_validators_ascending(validator_public.clone());
+		shared::Pallet::<Test>::set_session_index(5);
+
+		let signing_context =
+			SigningContext { parent_hash: System::parent_hash(), session_index: 5 };
+
+		// chain A occupies both cores.
+		let core_lookup = |core| match core {
+			core if core == CoreIndex::from(0) => Some(chain_a),
+			core if core == CoreIndex::from(1) => Some(chain_a),
+			_ => panic!("Core out of bounds for 1 parachain and 1 parathread core."),
+		};
+
+		let candidates = [vec![1, 2, 3, 4], vec![5, 6, 7, 8]]
+			.into_iter()
+			.map(|head_data| {
+				TestCandidateBuilder {
+					para_id: chain_a,
+					head_data: head_data.into(),
+					..Default::default()
+				}
+				.build()
+			})
+			.collect::<Vec<_>>();
+		let set_pending_chain = || {
+			<PendingAvailability<Test>>::insert(
+				chain_a,
+				candidates
+					.iter()
+					.enumerate()
+					.map(|(i, candidate)| CandidatePendingAvailability {
+						core: CoreIndex::from(i as u32),
+						hash: candidate.hash(),
+						descriptor: candidate.descriptor.clone(),
+						commitments: candidate.commitments.clone(),
+						availability_votes: default_availability_votes(),
+						relay_parent_number: 0,
+						backed_in_number: 0,
+						backers: default_backing_bitfield(),
+						backing_group: GroupIndex::from(i as u32),
+					})
+					.collect::<Vec<_>>(),
+			);
+		};
+		let signed_bitfields = |core: usize| {
+			let mut bare_bitfield = default_bitfield();
+			*bare_bitfield.0.get_mut(core).unwrap() = true;
+
+			validators
+				.iter()
+				.enumerate()
+				.map(|(i, key)| {
+					sign_bitfield(
+						&keystore,
+						key,
+						ValidatorIndex(i as _),
+						bare_bitfield.clone(),
+						&signing_context,
+					)
+					.into()
+				})
+				.collect()
+		};
+
+		set_pending_chain();
+
+		// the second candidate is available, but waits for its parent.
+		assert_matches!(
+			ParaInclusion::process_bitfields(
+				expected_bits(),
+				signed_bitfields(1),
+				DisputedBitfield::zeros(expected_bits()),
+				&core_lookup,
+				FullCheck::Yes,
+			),
+			Ok((v, _)) => { assert!(v.is_empty()) }
+		);
+		let chain = <PendingAvailability<Test>>::get(&chain_a).unwrap();
+		assert_eq!(chain.len(), 2);
+		assert_eq!(chain[1].availability_votes.count_ones(), validators.len());
+		assert_eq!(Paras::para_head(&chain_a), Some(Vec::new().into()));
+
+		// once the first candidate is available, both are enacted in order.
+		assert_matches!(
+			ParaInclusion::process_bitfields(
+				expected_bits(),
+				signed_bitfields(0),
+				DisputedBitfield::zeros(expected_bits()),
+				&core_lookup,
+				FullCheck::Yes,
+			),
+			Ok((v, _)) => {
+				assert_eq!(
+					v,
+					vec![(CoreIndex(0), candidates[0].hash()), (CoreIndex(1), candidates[1].hash())],
+				);
+			}
+		);
+		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
+		assert_eq!(Paras::para_head(&chain_a), Some(vec![5, 6, 7, 8].into()));
+
+		// timing out a candidate drops its successors as well.
+		set_pending_chain();
+		assert_eq!(
+			ParaInclusion::collect_pending(|core, _since| core == CoreIndex::from(0)),
+			vec![CoreIndex::from(0), CoreIndex::from(1)],
+		);
+		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
+
+		set_pending_chain();
+		assert_eq!(
+			ParaInclusion::collect_pending(|core, _since| core == CoreIndex::from(1)),
+			vec![CoreIndex::from(1)],
+		);
+		assert_eq!(
+			<PendingAvailability<Test>>::get(&chain_a).unwrap()[0].hash,
+			candidates[0].hash(),
+		);
+	});
+}
+
+#[test]
+fn para_usage_is_accounted_per_session() {
+	let chain_a = ParaId::from(1_u32);
+	let chain_b = ParaId::from(2_u32);
+
+	let paras = vec![(chain_a, ParaKind::Parachain), (chain_b, ParaKind::Parachain)];
+	new_test_ext(genesis_config(paras)).execute_with(|| {
+		let new_session = |session_index| SessionChangeNotification {
+			new_config: default_config(),
+			session_index,
+			..Default::default()
+		};
+		let enact = |para_id, upward_messages: Vec<Vec<u8>>| {
+			let mut candidate = TestCandidateBuilder { para_id, ..Default::default() }.build();
+			candidate.commitments.upward_messages = upward_messages.try_into().unwrap();
+
+			ParaInclusion::enact_candidate(
+				0,
+				candidate,
+				default_backing_bitfield(),
+				CoreIndex::from(0),
+				GroupIndex::from(0),
+			)
+		};
+
+		run_to_block(1, |_| Some(new_session(1)));
+
+		enact(chain_a, vec![vec![1, 2, 3]]);
+		enact(chain_b, Vec::new());
+		enact(chain_b, vec![vec![4], vec![5, 6]]);
+
+		let usage = ParaInclusion::para_usage(1);
+		assert_eq!(usage.len(), 2);
+		assert_eq!(usage[0].0, chain_a);
+		assert_eq!(usage[0].1.candidates, 1);
+		assert_eq!(usage[0].1.upward_messages, 1);
+		assert_eq!(usage[0].1.upward_message_bytes, 3);
+		assert_eq!(usage[1].0, chain_b);
+		assert_eq!(usage[1].1.candidates, 2);
+		assert_eq!(usage[1].1.upward_messages, 2);
+		assert_eq!(usage[1].1.upward_message_bytes, 3);
+
+		// the usage of a session is kept for `ParaUsageSessions` sessions.
+		run_to_block(2, |_| Some(new_session(3)));
+		assert!(ParaInclusion::para_usage(3).is_empty());
+		assert_eq!(ParaInclusion::para_usage(1), usage);
+
+		run_to_block(3, |_| Some(new_session(4)));
+		assert!(ParaInclusion::para_usage(1).is_empty());
+
+		// all sessions which fell out of the ledger are dropped, even if sessions are skipped.
+		enact(chain_a, Vec::new());
+		assert_eq!(ParaInclusion::para_usage(4).len(), 1);
+		run_to_block(4, |_| Some(new_session(10)));
+		assert!(ParaInclusion::para_usage(4).is_empty());
+	});
+}
+
 #[test]
 fn candidate_checks() {
 	let chain_a = ParaId::from(1_u32);
@@ -1213,24 +1363,24 @@ fn candidate_checks() {
 			);
 		}
 
-		// para occupied - reject.
+		// parathread occupied - reject.
 		{
 			let mut candidate = TestCandidateBuilder {
-				para_id: chain_a,
+				para_id: thread_a,
 				relay_parent: System::parent_hash(),
 				pov_hash: Hash::repeat_byte(1),
-				persisted_validation_data_hash: make_vdata_hash(chain_a).unwrap(),
+				persisted_validation_data_hash: make_vdata_hash(thread_a).unwrap(),
 				hrmp_watermark: RELAY_PARENT_NUM,
 				..Default::default()
 			}
 			.build();
 
-			collator_sign_candidate(Sr25519Keyring::One, &mut candidate);
+			collator_sign_candidate(Sr25519Keyring::Two, &mut candidate);
 
 			let backed = back_candidate(
 				candidate,
 				&validators,
-				group_validators(GroupIndex::from(0)).unwrap().as_ref(),
+				group_validators(GroupIndex::from(2)).unwrap().as_ref(),
 				&keystore,
 				&signing_context,
 				BackingKind::Threshold,
@@ -1238,71 +1388,31 @@ fn candidate_checks() {
 
 			let candidate = TestCandidateBuilder::default().build();
 			<PendingAvailability<Test>>::insert(
-				&chain_a,
-				CandidatePendingAvailability {
-					core: CoreIndex::from(0),
+				&thread_a,
+				vec![CandidatePendingAvailability {
+					core: CoreIndex::from(2),
 					hash: candidate.hash(),
 					descriptor: candidate.descriptor,
+					commitments: candidate.commitments,
 					availability_votes: default_availability_votes(),
 					relay_parent_number: 3,
 					backed_in_number: 4,
 					backers: default_backing_bitfield(),
-					backing_group: GroupIndex::from(0),
-				},
+					backing_group: GroupIndex::from(2),
+				}],
 			);
-			<PendingAvailabilityCommitments<Test>>::insert(&chain_a, candidate.commitments);
 
 			assert_noop!(
 				ParaInclusion::process_candidates(
 					Default::default(),
 					vec![backed],
-					vec![chain_a_assignment.clone()],
-					&group_validators,
-				),
-				Error::<Test>::CandidateScheduledBeforeParaFree
-			);
-
-			<PendingAvailability<Test>>::remove(&chain_a);
-			<PendingAvailabilityCommitments<Test>>::remove(&chain_a);
-		}
-
-		// messed up commitments storage - do not panic - reject.
-		{
-			let mut candidate = TestCandidateBuilder {
-				para_id: chain_a,
-				relay_parent: System::parent_hash(),
-				pov_hash: Hash::repeat_byte(1),
-				persisted_validation_data_hash: make_vdata_hash(chain_a).unwrap(),
-				hrmp_watermark: RELAY_PARENT_NUM,
-				..Default::default()
-			}
-			.build();
-
-			collator_sign_candidate(Sr25519Keyring::One, &mut candidate);
-
-			// this is not supposed to happen
-			<PendingAvailabilityCommitments<Test>>::insert(&chain_a, candidate.commitments.clone());
-
-			let backed = back_candidate(
-				candidate,
-				&validators,
-				group_validators(GroupIndex::from(0)).unwrap().as_ref(),
-				&keystore,
-				&signing_context,
-				BackingKind::Threshold,
-			);
-
-			assert_noop!(
-				ParaInclusion::process_candidates(
-					Default::default(),
-					vec![backed],
-					vec![chain_a_assignment.clone()],
+					vec![thread_a_assignment.clone()],
 					&group_validators,
 				),
 				Error::<Test>::CandidateScheduledBeforeParaFree
 			);
 
-			<PendingAvailabilityCommitments<Test>>::remove(&chain_a);
+			<PendingAvailability<Test>>::remove(&thread_a);
 		}
 
 		// interfering code upgrade - reject
@@ -1681,20 +1791,17 @@ fn backing_works() {
 		};
 		assert_eq!(
 			<PendingAvailability<Test>>::get(&chain_a),
-			Some(CandidatePendingAvailability {
+			Some(vec![CandidatePendingAvailability {
 				core: CoreIndex::from(0),
 				hash: candidate_a.hash(),
 				descriptor: candidate_a.descriptor,
+				commitments: candidate_a.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: System::block_number() - 1,
 				backed_in_number: System::block_number(),
 				backers,
 				backing_group: GroupIndex::from(0),
-			})
-		);
-		assert_eq!(
-			<PendingAvailabilityCommitments<Test>>::get(&chain_a),
-			Some(candidate_a.commitments),
+			}])
 		);
 
 		let backers = {
@@ -1703,38 +1810,32 @@ fn backing_works() {
 		};
 		assert_eq!(
 			<PendingAvailability<Test>>::get(&chain_b),
-			Some(CandidatePendingAvailability {
+			Some(vec![CandidatePendingAvailability {
 				core: CoreIndex::from(1),
 				hash: candidate_b.hash(),
 				descriptor: candidate_b.descriptor,
+				commitments: candidate_b.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: System::block_number() - 1,
 				backed_in_number: System::block_number(),
 				backers,
 				backing_group: GroupIndex::from(1),
-			})
-		);
-		assert_eq!(
-			<PendingAvailabilityCommitments<Test>>::get(&chain_b),
-			Some(candidate_b.commitments),
+			}])
 		);
 
 		assert_eq!(
 			<PendingAvailability<Test>>::get(&thread_a),
-			Some(CandidatePendingAvailability {
+			Some(vec![CandidatePendingAvailability {
 				core: CoreIndex::from(2),
 				hash: candidate_c.hash(),
 				descriptor: candidate_c.descriptor,
+				commitments: candidate_c.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: System::block_number() - 1,
 				backed_in_number: System::block_number(),
 				backers: backing_bitfield(&[4]),
 				backing_group: GroupIndex::from(2),
-			})
-		);
-		assert_eq!(
-			<PendingAvailabilityCommitments<Test>>::get(&thread_a),
-			Some(candidate_c.commitments),
+			}])
 		);
 	});
 }
@@ -1827,20 +1928,17 @@ fn can_include_candidate_with_ok_code_upgrade() {
 		};
 		assert_eq!(
 			<PendingAvailability<Test>>::get(&chain_a),
-			Some(CandidatePendingAvailability {
+			Some(vec![CandidatePendingAvailability {
 				core: CoreIndex::from(0),
 				hash: candidate_a.hash(),
 				descriptor: candidate_a.descriptor,
+				commitments: candidate_a.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: System::block_number() - 1,
 				backed_in_number: System::block_number(),
 				backers,
 				backing_group: GroupIndex::from(0),
-			})
-		);
-		assert_eq!(
-			<PendingAvailabilityCommitments<Test>>::get(&chain_a),
-			Some(candidate_a.commitments),
+			}])
 		);
 	});
 }
@@ -1903,33 +2001,33 @@ fn session_change_wipes() {
 		let candidate = TestCandidateBuilder::default().build();
 		<PendingAvailability<Test>>::insert(
 			&chain_a,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(0),
 				hash: candidate.hash(),
 				descriptor: candidate.descriptor.clone(),
+				commitments: candidate.commitments.clone(),
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 5,
 				backed_in_number: 6,
 				backers: default_backing_bitfield(),
 				backing_group: GroupIndex::from(0),
-			},
+			}],
 		);
-		<PendingAvailabilityCommitments<Test>>::insert(&chain_a, candidate.commitments.clone());
 
 		<PendingAvailability<Test>>::insert(
 			&chain_b,
-			CandidatePendingAvailability {
+			vec![CandidatePendingAvailability {
 				core: CoreIndex::from(1),
 				hash: candidate.hash(),
 				descriptor: candidate.descriptor,
+				commitments: candidate.commitments,
 				availability_votes: default_availability_votes(),
 				relay_parent_number: 6,
 				backed_in_number: 7,
 				backers: default_backing_bitfield(),
 				backing_group: GroupIndex::from(1),
-			},
+			}],
 		);
-		<PendingAvailabilityCommitments<Test>>::insert(&chain_b, candidate.commitments);
 
 		run_to_block(11, |_| None);
 
@@ -1941,8 +2039,6 @@ fn session_change_wipes() {
 
 		assert!(<PendingAvailability<Test>>::get(&chain_a).is_some());
 		assert!(<PendingAvailability<Test>>::get(&chain_b).is_some());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_some());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_b).is_some());
 
 		run_to_block(12, |n| match n {
 			12 => Some(SessionChangeNotification {
@@ -1964,11 +2060,64 @@ fn session_change_wipes() {
 
 		assert!(<PendingAvailability<Test>>::get(&chain_a).is_none());
 		assert!(<PendingAvailability<Test>>::get(&chain_b).is_none());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_a).is_none());
-		assert!(<PendingAvailabilityCommitments<Test>>::get(&chain_b).is_none());
 
 		assert!(<AvailabilityBitfields<Test>>::iter().collect::<Vec<_>>().is_empty());
 		assert!(<PendingAvailability<Test>>::iter().collect::<Vec<_>>().is_empty());
-		assert!(<PendingAvailabilityCommitments<Test>>::iter().collect::<Vec<_>>().is_empty());
+	});
+}
+
+#[test]
+fn migrate_to_v1_chains_pending_candidates() {
+	use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+	use migration::v1::{OldCandidatePendingAvailability, PendingAvailabilityCommitments};
+
+	let chain_a = ParaId::from(1_u32);
+	let chain_b = ParaId::from(2_u32);
+
+	let paras = vec![(chain_a, ParaKind::Parachain), (chain_b, ParaKind::Parachain)];
+	new_test_ext(genesis_config(paras)).execute_with(|| {
+		StorageVersion::new(0).put::<ParaInclusion>();
+
+		let mut candidate = TestCandidateBuilder { para_id: chain_a, ..Default::default() }.build();
+		candidate.commitments.upward_messages = vec![vec![1, 2, 3]].try_into().unwrap();
+		let old =
+			|candidate: &CommittedCandidateReceipt, core: u32| OldCandidatePendingAvailability {
+				core: CoreIndex::from(core),
+				hash: candidate.hash(),
+				descriptor: candidate.descriptor.clone(),
+				availability_votes: default_availability_votes(),
+				backers: default_backing_bitfield(),
+				relay_parent_number: 4,
+				backed_in_number: 5,
+				backing_group: GroupIndex::from(core),
+			};
+		frame_support::storage::unhashed::put(
+			&PendingAvailability::<Test>::hashed_key_for(chain_a),
+			&old(&candidate, 0),
+		);
+		PendingAvailabilityCommitments::<Test>::insert(chain_a, candidate.commitments.clone());
+
+		// a candidate without commitments is dropped.
+		let orphan = TestCandidateBuilder { para_id: chain_b, ..Default::default() }.build();
+		frame_support::storage::unhashed::put(
+			&PendingAvailability::<Test>::hashed_key_for(chain_b),
+			&old(&orphan, 1),
+		);
+
+		migration::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<ParaInclusion>(), 1);
+		assert_eq!(PendingAvailabilityCommitments::<Test>::iter_keys().count(), 0);
+		assert!(PendingAvailability::<Test>::get(chain_b).is_none());
+
+		let chain = PendingAvailability::<Test>::get(chain_a).unwrap();
+		assert_eq!(chain.len(), 1);
+		assert_eq!(chain[0].core, CoreIndex::from(0));
+		assert_eq!(chain[0].hash, candidate.hash());
+		assert_eq!(chain[0].descriptor, candidate.descriptor);
+		assert_eq!(chain[0].commitments, candidate.commitments);
+		assert_eq!(chain[0].relay_parent_number, 4);
+		assert_eq!(chain[0].backed_in_number, 5);
+		assert_eq!(chain[0].backing_group, GroupIndex::from(0));
 	});
 }
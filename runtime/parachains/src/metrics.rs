@@ -17,11 +17,16 @@
 //! Runtime declaration of the parachain metrics.
 
 use polkadot_runtime_metrics::{Counter, CounterVec, Histogram};
-use primitives::metric_definitions::{
-	PARACHAIN_CREATE_INHERENT_BITFIELDS_SIGNATURE_CHECKS,
-	PARACHAIN_INHERENT_DATA_BITFIELDS_PROCESSED, PARACHAIN_INHERENT_DATA_CANDIDATES_PROCESSED,
-	PARACHAIN_INHERENT_DATA_DISPUTE_SETS_INCLUDED, PARACHAIN_INHERENT_DATA_DISPUTE_SETS_PROCESSED,
-	PARACHAIN_INHERENT_DATA_WEIGHT, PARACHAIN_VERIFY_DISPUTE_SIGNATURE,
+use primitives::{
+	metric_definitions::{
+		PARACHAIN_CREATE_INHERENT_BITFIELDS_SIGNATURE_CHECKS,
+		PARACHAIN_INHERENT_DATA_BITFIELDS_PROCESSED, PARACHAIN_INHERENT_DATA_CANDIDATES_PROCESSED,
+		PARACHAIN_INHERENT_DATA_DISPUTE_SETS_INCLUDED,
+		PARACHAIN_INHERENT_DATA_DISPUTE_SETS_PROCESSED, PARACHAIN_INHERENT_DATA_WEIGHT,
+		PARACHAIN_PARA_USAGE, PARACHAIN_VERIFY_DISPUTE_SIGNATURE,
+	},
+	vstaging::ParaUsage,
+	Id as ParaId,
 };
 
 pub struct Metrics {
@@ -38,6 +43,9 @@ pub struct Metrics {
 	/// Counts bitfield signature checks in `enter_inner`.
 	bitfields_signature_checks: CounterVec,
 
+	/// Counts the resources used by the included candidates of each para.
+	para_usage: CounterVec,
+
 	/// Histogram with the time spent checking a validator signature of a dispute statement
 	signature_timings: Histogram,
 }
@@ -111,6 +119,43 @@ impl Metrics {
 	pub fn on_signature_check_complete(&self, val: u128) {
 		self.signature_timings.observe(val);
 	}
+
+	/// Increment the resources used by the included candidates of the para.
+	pub fn on_para_usage(&self, para_id: ParaId, usage: &ParaUsage) {
+		let mut buf = [0u8; 10];
+		let para_id = para_id_label(para_id, &mut buf);
+
+		for (resource, value) in [
+			("candidates", usage.candidates as u64),
+			("upward_messages", usage.upward_messages as u64),
+			("upward_message_bytes", usage.upward_message_bytes),
+			("horizontal_messages", usage.horizontal_messages as u64),
+			("horizontal_message_bytes", usage.horizontal_message_bytes),
+			("downward_messages_processed", usage.downward_messages_processed as u64),
+			("weight_ref_time", usage.weight_ref_time),
+			("weight_proof_size", usage.weight_proof_size),
+		] {
+			if value > 0 {
+				self.para_usage.with_label_values(&[para_id, resource]).inc_by(value);
+			}
+		}
+	}
+}
+
+/// Writes the decimal representation of the para id to the end of `buf` and returns it.
+fn para_id_label(para_id: ParaId, buf: &mut [u8; 10]) -> &str {
+	let mut n = u32::from(para_id);
+	let mut start = buf.len();
+	loop {
+		start -= 1;
+		buf[start] = b'0' + (n % 10) as u8;
+		n /= 10;
+		if n == 0 {
+			break
+		}
+	}
+
+	sp_std::str::from_utf8(&buf[start..]).unwrap_or_default()
 }
 
 pub const METRICS: Metrics = Metrics {
@@ -122,5 +167,6 @@ pub const METRICS: Metrics = Metrics {
 	bitfields_signature_checks: CounterVec::new(
 		PARACHAIN_CREATE_INHERENT_BITFIELDS_SIGNATURE_CHECKS,
 	),
+	para_usage: CounterVec::new(PARACHAIN_PARA_USAGE),
 	signature_timings: Histogram::new(PARACHAIN_VERIFY_DISPUTE_SIGNATURE),
 };
@@ -17,7 +17,9 @@
 //! Mocks for all the traits.
 
 use crate::{
-	configuration, disputes, dmp, hrmp, inclusion, initializer, origin, paras, paras_inherent,
+	configuration,
+	disputes::{self, slashing},
+	dmp, hrmp, inclusion, initializer, on_demand, origin, paras, paras_inherent, reward_points,
 	scheduler, session_info, shared,
 	ump::{self, MessageId, UmpSink},
 	ParaId,
@@ -32,15 +34,16 @@ use frame_support_test::TestRandomness;
 use parity_scale_codec::Decode;
 use primitives::{
 	AuthorityDiscoveryId, Balance, BlockNumber, CandidateHash, Header, Moment, SessionIndex,
-	UpwardMessage, ValidatorIndex,
+	UpwardMessage, ValidatorId, ValidatorIndex,
 };
 use sp_core::H256;
 use sp_io::TestExternalities;
 use sp_runtime::{
 	traits::{BlakeTwo256, IdentityLookup},
 	transaction_validity::TransactionPriority,
-	Permill,
+	DispatchResult, KeyTypeId, Perbill, Permill,
 };
+use sp_staking::{offence::OffenceError, EraIndex};
 use std::{cell::RefCell, collections::HashMap};
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
@@ -54,6 +57,8 @@ frame_support::construct_runtime!(
 	{
 		System: frame_system,
 		Balances: pallet_balances,
+		Timestamp: pallet_timestamp,
+		Staking: pallet_staking,
 		Paras: paras,
 		Configuration: configuration,
 		ParasShared: shared,
@@ -67,6 +72,9 @@ frame_support::construct_runtime!(
 		ParachainsOrigin: origin,
 		SessionInfo: session_info,
 		Disputes: disputes,
+		ParasSlashing: slashing,
+		ParasRewardPoints: reward_points,
+		OnDemand: on_demand,
 		Babe: pallet_babe,
 	}
 );
@@ -172,6 +180,51 @@ impl pallet_timestamp::Config for Test {
 	type WeightInfo = ();
 }
 
+parameter_types! {
+	pub const OffendingValidatorsThreshold: Perbill = Perbill::from_percent(17);
+}
+
+impl pallet_staking::Config for Test {
+	type MaxNominations = ConstU32<16>;
+	type Currency = Balances;
+	type CurrencyBalance = Balance;
+	type UnixTime = Timestamp;
+	type CurrencyToVote = frame_support::traits::U128CurrencyToVote;
+	type RewardRemainder = ();
+	type RuntimeEvent = RuntimeEvent;
+	type Slash = ();
+	type Reward = ();
+	type SessionsPerEra = ConstU32<3>;
+	type BondingDuration = ConstU32<3>;
+	type SlashDeferDuration = ConstU32<0>;
+	type AdminOrigin = frame_system::EnsureRoot<AccountId>;
+	type SessionInterface = ();
+	type EraPayout = ();
+	type MaxNominatorRewardedPerValidator = ConstU32<64>;
+	type OffendingValidatorsThreshold = OffendingValidatorsThreshold;
+	type NextNewSession = ();
+	type ElectionProvider = frame_election_provider_support::NoElection<(
+		AccountId,
+		BlockNumber,
+		Staking,
+		ConstU32<100>,
+	)>;
+	type GenesisElectionProvider = Self::ElectionProvider;
+	type VoterList = pallet_staking::UseNominatorsAndValidatorsMap<Self>;
+	type TargetList = pallet_staking::UseValidatorsMap<Self>;
+	type MaxUnlockingChunks = ConstU32<32>;
+	// Keep the era points of only a few eras, to exercise their pruning.
+	type HistoryDepth = ConstU32<4>;
+	type BenchmarkingConfig = pallet_staking::TestBenchmarkingConfig;
+	type OnStakerSlash = ();
+	type WeightInfo = ();
+}
+
+impl reward_points::Config for Test {
+	type ActiveEra = reward_points::StakingActiveEra<Test>;
+	type HistoryDepth = <Test as pallet_staking::Config>::HistoryDepth;
+}
+
 impl crate::initializer::Config for Test {
 	type Randomness = TestRandomness<Self>;
 	type ForceOrigin = frame_system::EnsureRoot<u64>;
@@ -219,6 +272,7 @@ impl crate::dmp::Config for Test {}
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	pub static UmpOverweightExpirySessions: SessionIndex = 3;
 }
 
 impl crate::ump::Config for Test {
@@ -226,6 +280,7 @@ impl crate::ump::Config for Test {
 	type UmpSink = TestUmpSink;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = frame_system::EnsureRoot<AccountId>;
+	type OverweightExpirySessions = UmpOverweightExpirySessions;
 	type WeightInfo = crate::ump::TestWeightInfo;
 }
 
@@ -290,18 +345,121 @@ impl crate::disputes::SlashingHandler<BlockNumber> for Test {
 	fn initializer_on_new_session(_: SessionIndex) {}
 }
 
-impl crate::scheduler::Config for Test {}
+parameter_types! {
+	pub static CurrentEra: EraIndex = 0;
+}
+
+impl slashing::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type KeyOwnerProof = sp_session::MembershipProof;
+	type KeyOwnerIdentification = (AccountId, ());
+	type KeyOwnerProofSystem = TestKeyOwnerProofSystem;
+	type HandleReports = TestHandleReports;
+	type CurrentEra = CurrentEra;
+	type WeightInfo = slashing::TestWeightInfo;
+	type BenchmarkingConfig = slashing::BenchConfig<200>;
+}
+
+/// A key ownership proof system that never proves anything. Only offences about
+/// the current session can be reported with it.
+pub struct TestKeyOwnerProofSystem;
+
+impl frame_support::traits::KeyOwnerProofSystem<(KeyTypeId, ValidatorId)>
+	for TestKeyOwnerProofSystem
+{
+	type Proof = sp_session::MembershipProof;
+	type IdentificationTuple = (AccountId, ());
+
+	fn prove(_key: (KeyTypeId, ValidatorId)) -> Option<Self::Proof> {
+		None
+	}
+
+	fn check_proof(
+		_key: (KeyTypeId, ValidatorId),
+		_proof: Self::Proof,
+	) -> Option<Self::IdentificationTuple> {
+		None
+	}
+}
+
+thread_local! {
+	pub static REPORTED_OFFENCES: RefCell<Vec<(Perbill, Vec<AccountId>)>> = RefCell::new(Vec::new());
+}
+
+/// Return the slash fractions and offenders of the reported offences and clear the buffer.
+pub fn take_reported_offences() -> Vec<(Perbill, Vec<AccountId>)> {
+	REPORTED_OFFENCES.with(|r| std::mem::take(&mut *r.borrow_mut()))
+}
+
+/// A `HandleReports` implementation that just records which offences were reported.
+pub struct TestHandleReports;
+
+impl slashing::HandleReports<Test> for TestHandleReports {
+	type ReportLongevity = ReportLongevity;
+
+	fn report_offence(
+		offence: slashing::SlashingOffence<(AccountId, ())>,
+	) -> Result<(), OffenceError> {
+		let offenders = offence.offenders.into_iter().map(|(who, _)| who).collect();
+		REPORTED_OFFENCES
+			.with(|r| r.borrow_mut().push((offence.time_slot.slash_fraction, offenders)));
+		Ok(())
+	}
+
+	fn is_known_offence(
+		_offenders: &[(AccountId, ())],
+		_time_slot: &slashing::OffenceTimeSlot,
+	) -> bool {
+		false
+	}
+
+	fn submit_unsigned_slashing_report(
+		_dispute_proof: slashing::DisputeProof,
+		_key_owner_proof: sp_session::MembershipProof,
+	) -> DispatchResult {
+		Ok(())
+	}
+}
+
+impl crate::scheduler::Config for Test {
+	type AssignmentProvider = crate::scheduler::DefaultAssignmentProvider<Test>;
+	type WeightInfo = crate::scheduler::TestWeightInfo;
+}
+
+parameter_types! {
+	pub const OnDemandBaseFee: Balance = 1_000;
+	pub const OnDemandMaxOrders: u32 = 4;
+	pub const OnDemandTargetQueueUtilization: Perbill = Perbill::from_percent(50);
+	pub const OnDemandFeeVariability: Perbill = Perbill::from_percent(10);
+	pub const OnDemandOrderTimeout: BlockNumber = 5;
+}
+
+impl on_demand::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = pallet_balances::Pallet<Test>;
+	type BaseFee = OnDemandBaseFee;
+	type MaxOrders = OnDemandMaxOrders;
+	type TargetQueueUtilization = OnDemandTargetQueueUtilization;
+	type FeeVariability = OnDemandFeeVariability;
+	type OrderTimeout = OnDemandOrderTimeout;
+	type WeightInfo = on_demand::TestWeightInfo;
+}
 
 impl crate::inclusion::Config for Test {
 	type RuntimeEvent = RuntimeEvent;
 	type DisputesHandler = Disputes;
 	type RewardValidators = TestRewardValidators;
+	type ParaUsageSessions = ConstU32<3>;
 }
 
 impl crate::paras_inherent::Config for Test {
 	type WeightInfo = crate::paras_inherent::TestWeightInfo;
 }
 
+parameter_types! {
+	pub static ActiveValidators: Vec<AccountId> = Vec::new();
+}
+
 pub struct MockValidatorSet;
 
 impl ValidatorSet<AccountId> for MockValidatorSet {
@@ -311,7 +469,7 @@ impl ValidatorSet<AccountId> for MockValidatorSet {
 		0
 	}
 	fn validators() -> Vec<Self::ValidatorId> {
-		Vec::new()
+		ActiveValidators::get()
 	}
 }
 
@@ -411,21 +569,26 @@ impl UmpSink for TestUmpSink {
 pub struct TestRewardValidators;
 
 impl inclusion::RewardValidators for TestRewardValidators {
-	fn reward_backing(v: impl IntoIterator<Item = ValidatorIndex>) {
+	fn reward_backing(v: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
 		BACKING_REWARDS.with(|r| {
 			let mut r = r.borrow_mut();
 			for i in v {
 				*r.entry(i).or_insert(0) += 1;
 			}
-		})
+		});
+		Weight::zero()
 	}
-	fn reward_bitfields(v: impl IntoIterator<Item = ValidatorIndex>) {
+	fn reward_bitfields(v: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
 		AVAILABILITY_REWARDS.with(|r| {
 			let mut r = r.borrow_mut();
 			for i in v {
 				*r.entry(i).or_insert(0) += 1;
 			}
-		})
+		});
+		Weight::zero()
+	}
+	fn reward_bitfields_weight(_: u32) -> Weight {
+		Weight::zero()
 	}
 }
 
@@ -438,6 +601,7 @@ pub fn new_test_ext(state: MockGenesisConfig) -> TestExternalities {
 
 	BACKING_REWARDS.with(|r| r.borrow_mut().clear());
 	AVAILABILITY_REWARDS.with(|r| r.borrow_mut().clear());
+	REPORTED_OFFENCES.with(|r| r.borrow_mut().clear());
 
 	let mut t = state.system.build_storage::<Test>().unwrap();
 	state.configuration.assimilate_storage(&mut t).unwrap();
@@ -0,0 +1,319 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! The on-demand module lets anyone buy a single block of a parathread.
+//!
+//! Orders are placed for a parathread at the current spot price, which is reserved from the
+//! account placing the order. At the beginning of each block orders are fed into the parathread
+//! claim queue of the [`scheduler`] in the order they were placed. Once the scheduler accepts the
+//! claim of an order, the reserved price is burned. Orders which are not accepted within
+//! [`Config::OrderTimeout`] blocks, for example because the claim queue stays full, are dropped
+//! and their price is unreserved.
+//!
+//! The spot price is [`Config::BaseFee`] multiplied by the spot traffic. On every block the spot
+//! traffic rises while the order queue is more utilized than [`Config::TargetQueueUtilization`]
+//! and falls otherwise, but never below 1. How fast it moves is set by [`Config::FeeVariability`].
+
+use crate::{
+	configuration, paras,
+	scheduler::{self, ParathreadClaimOutcome},
+};
+use frame_support::{
+	pallet_prelude::*,
+	traits::{Currency, ReservableCurrency},
+};
+use frame_system::pallet_prelude::*;
+use primitives::{CollatorId, Id as ParaId, ParathreadClaim};
+use sp_runtime::{
+	traits::{SaturatedConversion, Saturating},
+	FixedPointNumber, FixedU128, Perbill,
+};
+use sp_std::prelude::*;
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
+
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait WeightInfo {
+	fn place_order(s: u32) -> Weight;
+	fn on_initialize(s: u32) -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn place_order(_: u32) -> Weight {
+		Weight::zero()
+	}
+	fn on_initialize(_: u32) -> Weight {
+		Weight::zero()
+	}
+}
+
+type BalanceOf<T> =
+	<<T as Config>::Currency as Currency<<T as frame_system::Config>::AccountId>>::Balance;
+
+/// An order for a single block of a parathread which was not yet accepted by the scheduler.
+#[derive(Clone, Encode, Decode, TypeInfo, RuntimeDebug)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct OnDemandOrder<AccountId, Balance, BlockNumber> {
+	/// The account which placed the order and has the price reserved.
+	pub who: AccountId,
+	/// The parathread the order is for.
+	pub para_id: ParaId,
+	/// The collator which is allowed to author the block.
+	pub collator: CollatorId,
+	/// The spot price paid for the order.
+	pub price: Balance,
+	/// The block number at which the order was placed.
+	pub placed_at: BlockNumber,
+}
+
+type OrderOf<T> = OnDemandOrder<
+	<T as frame_system::Config>::AccountId,
+	BalanceOf<T>,
+	<T as frame_system::Config>::BlockNumber,
+>;
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + scheduler::Config {
+		/// The outer event type.
+		type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+
+		/// The currency orders are paid with.
+		type Currency: ReservableCurrency<Self::AccountId>;
+
+		/// The spot price of an order while the spot traffic is at its minimum of 1.
+		#[pallet::constant]
+		type BaseFee: Get<BalanceOf<Self>>;
+
+		/// The maximum number of orders waiting to be accepted by the scheduler.
+		#[pallet::constant]
+		type MaxOrders: Get<u32>;
+
+		/// The utilization of the order queue above which the spot traffic rises.
+		#[pallet::constant]
+		type TargetQueueUtilization: Get<Perbill>;
+
+		/// How much the spot traffic changes per block, relative to the difference between the
+		/// utilization of the order queue and [`Config::TargetQueueUtilization`].
+		#[pallet::constant]
+		type FeeVariability: Get<Perbill>;
+
+		/// The number of blocks after which an order which was not accepted by the scheduler is
+		/// refunded.
+		#[pallet::constant]
+		type OrderTimeout: Get<Self::BlockNumber>;
+
+		/// Something that provides the weight of this pallet.
+		type WeightInfo: WeightInfo;
+	}
+
+	#[pallet::event]
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// An order for a block of a parathread was placed.
+		OrderPlaced { para_id: ParaId, who: T::AccountId, price: BalanceOf<T> },
+		/// An order was accepted by the scheduler and its price was burned.
+		OrderScheduled { para_id: ParaId, who: T::AccountId },
+		/// An order was dropped without being scheduled and its price was unreserved.
+		OrderRefunded { para_id: ParaId, who: T::AccountId, price: BalanceOf<T> },
+	}
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// The para is not a parathread.
+		NotParathread,
+		/// No parathread cores are configured, so orders can not be scheduled.
+		NoParathreadCores,
+		/// The order queue is full.
+		QueueFull,
+		/// The spot price is higher than the maximum amount the caller is willing to pay.
+		SpotPriceHigherThanMaxAmount,
+	}
+
+	/// Initialization value for the spot traffic.
+	#[pallet::type_value]
+	pub fn InitialSpotTraffic() -> FixedU128 {
+		FixedU128::from_u32(1)
+	}
+
+	/// The number to multiply [`Config::BaseFee`] by to get the spot price.
+	#[pallet::storage]
+	pub(crate) type SpotTraffic<T: Config> =
+		StorageValue<_, FixedU128, ValueQuery, InitialSpotTraffic>;
+
+	/// The orders which were not yet accepted by the scheduler, oldest first.
+	///
+	/// Bounded by [`Config::MaxOrders`].
+	#[pallet::storage]
+	pub(crate) type OrderQueue<T: Config> = StorageValue<_, Vec<OrderOf<T>>, ValueQuery>;
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		fn on_initialize(now: T::BlockNumber) -> Weight {
+			let queue_len = OrderQueue::<T>::decode_len().unwrap_or(0) as u32;
+
+			Self::update_spot_traffic(queue_len);
+			if queue_len > 0 {
+				Self::process_orders(now);
+			}
+
+			T::WeightInfo::on_initialize(queue_len)
+		}
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Order a single block of a parathread at the current spot price.
+		///
+		/// - `max_amount` - the most the caller is willing to pay for the order.
+		/// - `para_id` - the parathread to order a block for.
+		/// - `collator` - the collator which is allowed to author the block.
+		///
+		/// The spot price is reserved until the order is accepted by the scheduler, at which point
+		/// it is burned. If the order is not accepted within [`Config::OrderTimeout`] blocks, the
+		/// price is unreserved again.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::place_order(T::MaxOrders::get()))]
+		pub fn place_order(
+			origin: OriginFor<T>,
+			max_amount: BalanceOf<T>,
+			para_id: ParaId,
+			collator: CollatorId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			ensure!(<paras::Pallet<T>>::is_parathread(para_id), Error::<T>::NotParathread);
+			ensure!(
+				<configuration::Pallet<T>>::config().parathread_cores > 0,
+				Error::<T>::NoParathreadCores,
+			);
+
+			let price = Self::spot_price();
+			ensure!(price <= max_amount, Error::<T>::SpotPriceHigherThanMaxAmount);
+
+			OrderQueue::<T>::try_mutate(|orders| {
+				ensure!(orders.len() < T::MaxOrders::get() as usize, Error::<T>::QueueFull);
+
+				T::Currency::reserve(&who, price)?;
+				orders.push(OnDemandOrder {
+					who: who.clone(),
+					para_id,
+					collator,
+					price,
+					placed_at: <frame_system::Pallet<T>>::block_number(),
+				});
+
+				Ok::<_, DispatchError>(())
+			})?;
+
+			Self::deposit_event(Event::OrderPlaced { para_id, who, price });
+
+			Ok(())
+		}
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The price of an order placed now.
+	pub fn spot_price() -> BalanceOf<T> {
+		let base_fee: u128 = T::BaseFee::get().saturated_into();
+		SpotTraffic::<T>::get().saturating_mul_int(base_fee).saturated_into()
+	}
+
+	/// Move the spot traffic towards the utilization of an order queue with `queue_len` orders.
+	pub(crate) fn update_spot_traffic(queue_len: u32) {
+		let utilization = Perbill::from_rational(queue_len, T::MaxOrders::get().max(1));
+		let target = T::TargetQueueUtilization::get();
+		let variability = FixedU128::from(T::FeeVariability::get());
+
+		SpotTraffic::<T>::mutate(|traffic| {
+			if utilization > target {
+				let change = variability.saturating_mul(utilization.saturating_sub(target).into());
+				*traffic = traffic.saturating_mul(FixedU128::from_u32(1).saturating_add(change));
+			} else {
+				let change = variability.saturating_mul(target.saturating_sub(utilization).into());
+				*traffic = InitialSpotTraffic::get()
+					.max(*traffic / FixedU128::from_u32(1).saturating_add(change));
+			}
+		});
+	}
+
+	/// Feed the queued orders into the parathread claim queue of the scheduler, oldest first, and
+	/// refund the orders which have timed out.
+	///
+	/// Orders whose parathread already has a claim queued or occupying a core stay queued, so
+	/// multiple orders for the same parathread are scheduled one after another.
+	pub(crate) fn process_orders(now: T::BlockNumber) {
+		let timeout = T::OrderTimeout::get();
+		let mut claim_queue_full = false;
+
+		OrderQueue::<T>::mutate(|orders| {
+			orders.retain(|order| {
+				if !claim_queue_full {
+					let claim = ParathreadClaim(order.para_id, order.collator.clone());
+					match <scheduler::Pallet<T>>::add_parathread_claim(claim) {
+						ParathreadClaimOutcome::Queued => {
+							// Dropping the imbalance burns the price.
+							let _ = T::Currency::slash_reserved(&order.who, order.price);
+							Self::deposit_event(Event::OrderScheduled {
+								para_id: order.para_id,
+								who: order.who.clone(),
+							});
+							return false
+						},
+						ParathreadClaimOutcome::NotParathread => {
+							Self::refund(order);
+							return false
+						},
+						ParathreadClaimOutcome::QueueFull => claim_queue_full = true,
+						ParathreadClaimOutcome::AlreadyClaimed => {},
+					}
+				}
+
+				if now.saturating_sub(order.placed_at) >= timeout {
+					Self::refund(order);
+					return false
+				}
+
+				true
+			});
+		});
+	}
+
+	fn refund(order: &OrderOf<T>) {
+		T::Currency::unreserve(&order.who, order.price);
+		Self::deposit_event(Event::OrderRefunded {
+			para_id: order.para_id,
+			who: order.who.clone(),
+			price: order.price,
+		});
+	}
+}
@@ -0,0 +1,108 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::{
+	configuration::Pallet as Configuration,
+	on_demand::{Pallet as OnDemand, *},
+	paras::{Pallet as Paras, ParaKind, ParachainsCache},
+};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
+use frame_system::RawOrigin;
+use sp_core::sr25519;
+
+fn register_parathread<T: Config>(id: ParaId) {
+	let mut parachains = ParachainsCache::new();
+	Paras::<T>::initialize_para_now(
+		&mut parachains,
+		id,
+		&crate::paras::ParaGenesisArgs {
+			para_kind: ParaKind::Parathread,
+			genesis_head: vec![1].into(),
+			validation_code: vec![1].into(),
+		},
+	);
+
+	let mut config = Configuration::<T>::config();
+	config.parathread_cores = config.parathread_cores.max(1);
+	config.scheduling_lookahead = config.scheduling_lookahead.max(1);
+	Configuration::<T>::force_set_active_config(config);
+}
+
+fn collator() -> CollatorId {
+	sr25519::Public::from_raw([1; 32]).into()
+}
+
+/// Fill the order queue with `n` orders for `para_id`, placed by distinct accounts.
+fn fill_queue<T: Config>(para_id: ParaId, n: u32) {
+	let orders = (0..n)
+		.map(|i| {
+			let who: T::AccountId = account("orderer", i, 0);
+			let price = OnDemand::<T>::spot_price();
+			T::Currency::make_free_balance_be(&who, price + T::Currency::minimum_balance());
+			T::Currency::reserve(&who, price).expect("free balance covers the price; qed");
+
+			OnDemandOrder {
+				who,
+				para_id,
+				collator: collator(),
+				price,
+				placed_at: frame_system::Pallet::<T>::block_number(),
+			}
+		})
+		.collect();
+	OrderQueue::<T>::put(orders);
+}
+
+benchmarks! {
+	place_order {
+		let s in 0 .. T::MaxOrders::get() - 1;
+
+		let para_id = ParaId::from(1000);
+		register_parathread::<T>(para_id);
+		fill_queue::<T>(para_id, s);
+
+		let caller: T::AccountId = whitelisted_caller();
+		let price = OnDemand::<T>::spot_price();
+		T::Currency::make_free_balance_be(&caller, price + T::Currency::minimum_balance());
+	}: _(RawOrigin::Signed(caller), price, para_id, collator())
+	verify {
+		assert_eq!(OrderQueue::<T>::decode_len(), Some(s as usize + 1));
+	}
+
+	on_initialize {
+		// Every order but the first one competes with the claim of the first one, so the whole
+		// queue is iterated. All of them have timed out, so each of them is refunded.
+		let s in 1 .. T::MaxOrders::get();
+
+		let para_id = ParaId::from(1000);
+		register_parathread::<T>(para_id);
+		fill_queue::<T>(para_id, s);
+
+		let now = frame_system::Pallet::<T>::block_number() + T::OrderTimeout::get();
+		frame_system::Pallet::<T>::set_block_number(now);
+	}: {
+		OnDemand::<T>::on_initialize(now);
+	}
+	verify {
+		assert_eq!(OrderQueue::<T>::decode_len(), Some(0));
+	}
+}
+
+frame_benchmarking::impl_benchmark_test_suite!(
+	OnDemand,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
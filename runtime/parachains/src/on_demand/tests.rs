@@ -0,0 +1,243 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+
+use frame_support::{assert_noop, assert_ok};
+use keyring::Sr25519Keyring;
+use primitives::{BlockNumber, ValidationCode};
+
+use crate::{
+	configuration::HostConfiguration,
+	initializer::SessionChangeNotification,
+	mock::{
+		new_test_ext, Balances, MockGenesisConfig, OnDemand, Paras, ParasShared,
+		RuntimeEvent as MockEvent, RuntimeOrigin, Scheduler, System, Test,
+	},
+	paras::{ParaGenesisArgs, ParaKind},
+	scheduler::ParathreadQueue,
+};
+
+const ALICE: u64 = 1;
+
+fn default_genesis_config() -> MockGenesisConfig {
+	MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: HostConfiguration {
+				parathread_cores: 1,
+				scheduling_lookahead: 1,
+				..Default::default()
+			},
+			..Default::default()
+		},
+		..Default::default()
+	}
+}
+
+fn schedule_blank_para(id: ParaId, parakind: ParaKind) {
+	let validation_code: ValidationCode = vec![1, 2, 3].into();
+	assert_ok!(Paras::schedule_para_initialize(
+		id,
+		ParaGenesisArgs {
+			genesis_head: Vec::new().into(),
+			validation_code: validation_code.clone(),
+			para_kind: parakind,
+		}
+	));
+
+	assert_ok!(Paras::add_trusted_validation_code(RuntimeOrigin::root(), validation_code));
+}
+
+/// Enact the scheduled para lifecycle changes.
+fn new_session() {
+	let notification = SessionChangeNotification {
+		session_index: ParasShared::scheduled_session(),
+		..Default::default()
+	};
+
+	let b = System::block_number();
+	Paras::initializer_finalize(b);
+	Paras::initializer_on_new_session(&notification);
+	Scheduler::initializer_on_new_session(&notification);
+	System::set_block_number(b + 1);
+}
+
+fn onboard_parathread(id: ParaId) {
+	schedule_blank_para(id, ParaKind::Parathread);
+	new_session();
+	assert!(Paras::is_parathread(id));
+}
+
+fn collator() -> CollatorId {
+	CollatorId::from(Sr25519Keyring::Alice.public())
+}
+
+fn order_events() -> Vec<Event<Test>> {
+	System::events()
+		.into_iter()
+		.filter_map(|r| match r.event {
+			MockEvent::OnDemand(event) => Some(event),
+			_ => None,
+		})
+		.collect()
+}
+
+fn run_on_initialize(to: BlockNumber) {
+	while System::block_number() < to {
+		let b = System::block_number() + 1;
+		System::set_block_number(b);
+		OnDemand::on_initialize(b);
+	}
+}
+
+#[test]
+fn spot_traffic_follows_queue_utilization() {
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		assert_eq!(OnDemand::spot_price(), 1_000);
+
+		// A full queue is above the target utilization of 50%.
+		OnDemand::update_spot_traffic(4);
+		assert_eq!(SpotTraffic::<Test>::get(), FixedU128::from_rational(105, 100));
+		assert_eq!(OnDemand::spot_price(), 1_050);
+
+		// The target utilization leaves the traffic as is.
+		OnDemand::update_spot_traffic(2);
+		assert_eq!(SpotTraffic::<Test>::get(), FixedU128::from_rational(105, 100));
+
+		// An empty queue brings the traffic down, but not below 1.
+		OnDemand::update_spot_traffic(0);
+		assert_eq!(SpotTraffic::<Test>::get(), FixedU128::from_u32(1));
+		OnDemand::update_spot_traffic(0);
+		assert_eq!(SpotTraffic::<Test>::get(), FixedU128::from_u32(1));
+		assert_eq!(OnDemand::spot_price(), 1_000);
+	});
+}
+
+#[test]
+fn place_order_checks_para_price_and_queue() {
+	let para_id = ParaId::from(10);
+	let parachain_id = ParaId::from(11);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		Balances::make_free_balance_be(&ALICE, 100_000);
+		schedule_blank_para(parachain_id, ParaKind::Parachain);
+		onboard_parathread(para_id);
+
+		assert_noop!(
+			OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, parachain_id, collator()),
+			Error::<Test>::NotParathread,
+		);
+		assert_noop!(
+			OnDemand::place_order(RuntimeOrigin::signed(ALICE), 999, para_id, collator()),
+			Error::<Test>::SpotPriceHigherThanMaxAmount,
+		);
+
+		for _ in 0..4 {
+			assert_ok!(OnDemand::place_order(
+				RuntimeOrigin::signed(ALICE),
+				1_000,
+				para_id,
+				collator()
+			));
+		}
+		assert_noop!(
+			OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, para_id, collator()),
+			Error::<Test>::QueueFull,
+		);
+
+		assert_eq!(Balances::reserved_balance(&ALICE), 4_000);
+		assert_eq!(OrderQueue::<Test>::get().len(), 4);
+	});
+}
+
+#[test]
+fn place_order_fails_without_parathread_cores() {
+	let para_id = ParaId::from(10);
+	let mut genesis_config = default_genesis_config();
+	genesis_config.configuration.config.parathread_cores = 0;
+
+	new_test_ext(genesis_config).execute_with(|| {
+		Balances::make_free_balance_be(&ALICE, 100_000);
+		onboard_parathread(para_id);
+
+		assert_noop!(
+			OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, para_id, collator()),
+			Error::<Test>::NoParathreadCores,
+		);
+	});
+}
+
+#[test]
+fn scheduled_orders_are_paid_and_others_refunded_after_timeout() {
+	let para_id = ParaId::from(10);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		Balances::make_free_balance_be(&ALICE, 100_000);
+		onboard_parathread(para_id);
+		let placed_at = System::block_number();
+
+		assert_ok!(OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, para_id, collator()));
+		assert_ok!(OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, para_id, collator()));
+
+		// The first order is claimed, the second one has to wait as the parathread is claimed.
+		run_on_initialize(placed_at + 1);
+		assert_eq!(ParathreadQueue::<Test>::get().queue.len(), 1);
+		assert_eq!(OrderQueue::<Test>::get().len(), 1);
+		assert_eq!(Balances::reserved_balance(&ALICE), 1_000);
+		assert_eq!(Balances::free_balance(&ALICE), 98_000);
+
+		// The claim is never occupied, so the second order times out.
+		run_on_initialize(placed_at + 4);
+		assert_eq!(OrderQueue::<Test>::get().len(), 1);
+		run_on_initialize(placed_at + 5);
+		assert!(OrderQueue::<Test>::get().is_empty());
+		assert_eq!(Balances::reserved_balance(&ALICE), 0);
+		assert_eq!(Balances::free_balance(&ALICE), 99_000);
+
+		assert_eq!(
+			order_events(),
+			vec![
+				Event::OrderPlaced { para_id, who: ALICE, price: 1_000 },
+				Event::OrderPlaced { para_id, who: ALICE, price: 1_000 },
+				Event::OrderScheduled { para_id, who: ALICE },
+				Event::OrderRefunded { para_id, who: ALICE, price: 1_000 },
+			],
+		);
+	});
+}
+
+#[test]
+fn orders_for_offboarded_parathreads_are_refunded() {
+	let para_id = ParaId::from(10);
+
+	new_test_ext(default_genesis_config()).execute_with(|| {
+		Balances::make_free_balance_be(&ALICE, 100_000);
+		onboard_parathread(para_id);
+
+		assert_ok!(OnDemand::place_order(RuntimeOrigin::signed(ALICE), 1_000, para_id, collator()));
+		assert_ok!(Paras::schedule_para_cleanup(para_id));
+		new_session();
+		assert!(!Paras::is_parathread(para_id));
+
+		run_on_initialize(System::block_number() + 1);
+		assert!(OrderQueue::<Test>::get().is_empty());
+		assert_eq!(Balances::free_balance(&ALICE), 100_000);
+		assert_eq!(
+			order_events().last(),
+			Some(&Event::OrderRefunded { para_id, who: ALICE, price: 1_000 }),
+		);
+	});
+}
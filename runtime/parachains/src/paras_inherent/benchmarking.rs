@@ -87,7 +87,8 @@ benchmarks! {
 	}
 
 	// Variant over `v`, the amount of validity votes for a backed candidate. This gives the weight
-	// of a single backed candidate.
+	// of a single backed candidate, including enacting the available candidate which occupied its
+	// core before.
 	enter_backed_candidates_variable {
 		// NOTE: the starting value must be over half of the max validators per group so the backed
 		// candidate is not rejected. Also, we cannot have more validity votes than validators in
@@ -141,14 +142,13 @@ benchmarks! {
 			assert_eq!(backing_validators.1.len(), v as usize);
 		}
 
-		assert_eq!(
-			inclusion::PendingAvailabilityCommitments::<T>::iter().count(),
-			cores_with_backed.len()
-		);
 		assert_eq!(
 			inclusion::PendingAvailability::<T>::iter().count(),
 			cores_with_backed.len()
 		);
+		// The available candidate was enacted and its usage noted.
+		let session_index = crate::shared::Pallet::<T>::session_index();
+		assert_eq!(inclusion::Pallet::<T>::para_usage(session_index).len(), cores_with_backed.len());
 	}
 
 	enter_backed_candidate_code_upgrade {
@@ -202,10 +202,6 @@ benchmarks! {
 				);
 			}
 
-		assert_eq!(
-			inclusion::PendingAvailabilityCommitments::<T>::iter().count(),
-			cores_with_backed.len()
-		);
 		assert_eq!(
 			inclusion::PendingAvailability::<T>::iter().count(),
 			cores_with_backed.len()
@@ -472,7 +472,7 @@ impl<T: Config> Pallet<T> {
 
 		// Process new availability bitfields, yielding any availability cores whose
 		// work has now concluded.
-		let freed_concluded = <inclusion::Pallet<T>>::process_bitfields(
+		let (freed_concluded, enacted_weight) = <inclusion::Pallet<T>>::process_bitfields(
 			expected_bits,
 			signed_bitfields,
 			disputed_bitfield,
@@ -535,6 +535,11 @@ impl<T: Config> Pallet<T> {
 		// this is max config.ump_service_total_weight
 		let _ump_weight = <ump::Pallet<T>>::process_pending_upward_messages();
 
+		// The weight of a backed candidate covers enacting a candidate, as measured by the
+		// benchmarks. The candidates enacted in this block were backed in earlier blocks though, so
+		// count what enacting them took on top.
+		let total_consumed_weight = total_consumed_weight.saturating_add(enacted_weight);
+
 		METRICS.on_after_filter(total_consumed_weight.ref_time());
 
 		Ok(Some(total_consumed_weight).into())
@@ -686,7 +691,7 @@ impl<T: Config> Pallet<T> {
 				FullCheck::Yes,
 			);
 
-			let freed_concluded =
+			let (freed_concluded, _) =
 				<inclusion::Pallet<T>>::update_pending_availability_and_get_freed_cores::<_>(
 					expected_bits,
 					&validator_public[..],
@@ -707,21 +712,34 @@ impl<T: Config> Pallet<T> {
 			let parent_storage_root = *parent_header.state_root();
 
 			let check_ctx = CandidateCheckContext::<T>::new(now, relay_parent_number);
+			// the chains of candidates pending availability, including the backed candidates
+			// which passed the checks, in order.
+			let mut pending_chains = BTreeMap::new();
 			let backed_candidates = sanitize_backed_candidates::<T, _>(
 				parent_hash,
 				backed_candidates,
 				move |candidate_idx: usize,
 				      backed_candidate: &BackedCandidate<<T as frame_system::Config>::Hash>|
 				      -> bool {
+					let para_id = backed_candidate.descriptor().para_id;
+					let pending_chain = pending_chains
+						.entry(para_id)
+						.or_insert_with(|| <inclusion::Pallet<T>>::pending_chain(para_id));
+
 					// never include a concluded-invalid candidate
-					concluded_invalid_disputes.contains(&backed_candidate.hash()) ||
+					let invalid = concluded_invalid_disputes.contains(&backed_candidate.hash()) ||
 							// Instead of checking the candidates with code upgrades twice
 							// move the checking up here and skip it in the training wheels fallback.
 							// That way we avoid possible duplicate checks while assuring all
 							// backed candidates fine to pass on.
 							check_ctx
-								.verify_backed_candidate(parent_hash, parent_storage_root, candidate_idx, backed_candidate)
-								.is_err()
+								.verify_backed_candidate(parent_hash, parent_storage_root, candidate_idx, backed_candidate, pending_chain)
+								.is_err();
+
+					if !invalid {
+						pending_chain.push(&backed_candidate.candidate.commitments);
+					}
+					invalid
 				},
 				&scheduled[..],
 			);
@@ -897,7 +915,18 @@ fn apply_weight_limit<T: Config + inclusion::Config>(
 				|c| backed_candidate_weight::<T>(c),
 				max_consumable_by_candidates,
 			);
-		candidates.indexed_retain(|idx, _backed_candidate| indices.binary_search(&idx).is_ok());
+		// The candidates of a para build on each other, so once one of them is not picked, none
+		// of the following ones can be included either.
+		let mut broken_chains = BTreeSet::new();
+		candidates.indexed_retain(|idx, backed_candidate| {
+			let para_id = backed_candidate.descriptor().para_id;
+			if indices.binary_search(&idx).is_ok() && !broken_chains.contains(&para_id) {
+				true
+			} else {
+				broken_chains.insert(para_id);
+				false
+			}
+		});
 		// pick all bitfields, and
 		// fill the remaining space with candidates
 		let total_consumed = acc_candidate_weight.saturating_add(total_bitfields_weight);
@@ -913,7 +942,7 @@ fn apply_weight_limit<T: Config + inclusion::Config>(
 		rng,
 		&bitfields,
 		vec![],
-		|_| <<T as Config>::WeightInfo as WeightInfo>::enter_bitfields(),
+		|_| signed_bitfields_weight::<T>(1),
 		max_consumable_weight,
 	);
 
@@ -1098,6 +1127,9 @@ pub(crate) fn assure_sanity_bitfields<T: crate::inclusion::Config>(
 /// `candidate_has_concluded_invalid_dispute` must return `true` if the candidate
 /// is disputed, false otherwise. The passed `usize` is the candidate index.
 ///
+/// The candidates of a para scheduled on multiple cores are expected in the order in which they
+/// build on each other, see [`backed_candidate_cores`].
+///
 /// The returned `Vec` is sorted according to the occupied core index.
 fn sanitize_backed_candidates<
 	T: crate::inclusion::Config,
@@ -1114,33 +1146,59 @@ fn sanitize_backed_candidates<
 		!candidate_has_concluded_invalid_dispute_or_is_invalid(candidate_idx, backed_candidate)
 	});
 
-	let scheduled_paras_to_core_idx = scheduled
-		.into_iter()
-		.map(|core_assignment| (core_assignment.para_id, core_assignment.core))
-		.collect::<BTreeMap<ParaId, CoreIndex>>();
-
 	// Assure the backed candidate's `ParaId`'s core is free.
 	// This holds under the assumption that `Scheduler::schedule` is called _before_.
 	// Also checks the candidate references the correct relay parent.
-
-	backed_candidates.retain(|backed_candidate| {
-		let desc = backed_candidate.descriptor();
-		desc.relay_parent == relay_parent &&
-			scheduled_paras_to_core_idx.get(&desc.para_id).is_some()
-	});
+	backed_candidates
+		.retain(|backed_candidate| backed_candidate.descriptor().relay_parent == relay_parent);
+	let cores = backed_candidate_cores(&backed_candidates, scheduled);
 
 	// Sort the `Vec` last, once there is a guarantee that these
 	// `BackedCandidates` references the expected relay chain parent,
 	// but more importantly are scheduled for a free core.
 	// This both avoids extra work for obviously invalid candidates,
 	// but also allows this to be done in place.
-	backed_candidates.sort_by(|x, y| {
-		// Never panics, since we filtered all panic arguments out in the previous `fn retain`.
-		scheduled_paras_to_core_idx[&x.descriptor().para_id]
-			.cmp(&scheduled_paras_to_core_idx[&y.descriptor().para_id])
-	});
+	let mut backed_candidates = backed_candidates
+		.into_iter()
+		.zip(cores)
+		.filter_map(|(backed_candidate, core)| core.map(|core| (core, backed_candidate)))
+		.collect::<Vec<_>>();
+	backed_candidates.sort_by_key(|(core, _)| *core);
 
 	backed_candidates
+		.into_iter()
+		.map(|(_, backed_candidate)| backed_candidate)
+		.collect()
+}
+
+/// The core each of the backed candidates occupies, if any.
+///
+/// The candidates of a para occupy the cores scheduled for the para in ascending order, so the
+/// n-th candidate of a para occupies the n-th core scheduled for it. Candidates of a para which is
+/// not scheduled or beyond the number of cores scheduled for the para occupy no core.
+///
+/// Assumes `scheduled` is sorted by core index.
+fn backed_candidate_cores<H>(
+	backed_candidates: &[BackedCandidate<H>],
+	scheduled: &[CoreAssignment],
+) -> Vec<Option<CoreIndex>> {
+	// reversed, so the cores of each para can be popped in ascending order.
+	let mut scheduled_paras_to_core_idx = BTreeMap::<ParaId, Vec<CoreIndex>>::new();
+	for core_assignment in scheduled.iter().rev() {
+		scheduled_paras_to_core_idx
+			.entry(core_assignment.para_id)
+			.or_default()
+			.push(core_assignment.core);
+	}
+
+	backed_candidates
+		.iter()
+		.map(|backed_candidate| {
+			scheduled_paras_to_core_idx
+				.get_mut(&backed_candidate.descriptor().para_id)
+				.and_then(|cores| cores.pop())
+		})
+		.collect()
 }
 
 /// Assumes sorted candidates.
@@ -1168,17 +1226,13 @@ pub(crate) fn assure_sanity_backed_candidates<
 		}
 	}
 
-	let scheduled_paras_to_core_idx = scheduled
-		.into_iter()
-		.map(|core_assignment| (core_assignment.para_id, core_assignment.core))
-		.collect::<BTreeMap<ParaId, CoreIndex>>();
-
-	if !IsSortedBy::is_sorted_by(backed_candidates, |x, y| {
-		// Never panics, since we would have early returned on those in the above loop.
-		scheduled_paras_to_core_idx[&x.descriptor().para_id]
-			.cmp(&scheduled_paras_to_core_idx[&y.descriptor().para_id])
-	}) {
-		return Err(Error::<T>::UnsortedOrDuplicateBackedCandidates)
+	let mut last_core = None;
+	for core in backed_candidate_cores(backed_candidates, scheduled) {
+		let core = core.ok_or(Error::<T>::UnscheduledCandidate)?;
+		if last_core.map_or(false, |last_core| core <= last_core) {
+			return Err(Error::<T>::UnsortedOrDuplicateBackedCandidates)
+		}
+		last_core = Some(core);
 	}
 	Ok(())
 }
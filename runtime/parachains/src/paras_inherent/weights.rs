@@ -16,6 +16,7 @@
 use super::{
 	BackedCandidate, Config, DisputeStatementSet, UncheckedSignedAvailabilityBitfield, Weight,
 };
+use crate::inclusion::{self, RewardValidators};
 
 pub trait WeightInfo {
 	/// Variant over `v`, the count of dispute statements in a dispute statement set. This gives the
@@ -24,7 +25,7 @@ pub trait WeightInfo {
 	/// The weight of one bitfield.
 	fn enter_bitfields() -> Weight;
 	/// Variant over `v`, the count of validity votes for a backed candidate. This gives the weight
-	/// of a single backed candidate.
+	/// of a single backed candidate, including enacting a candidate which became available.
 	fn enter_backed_candidates_variable(v: u32) -> Weight;
 	/// The weight of a single backed candidate with a code upgrade.
 	fn enter_backed_candidate_code_upgrade() -> Weight;
@@ -102,9 +103,22 @@ pub fn multi_dispute_statement_sets_weight<
 		.fold(Weight::zero(), |acc_weight, weight| acc_weight.saturating_add(weight))
 }
 
+/// The weight of the given number of bitfields, including recording the availability points of
+/// their validators.
+///
+/// Validators submit a bitfield in every block, so the validators which contributed to the
+/// availability of the candidates enacted in a block are bounded by the bitfields in practice.
 pub fn signed_bitfields_weight<T: Config>(bitfields_len: usize) -> Weight {
+	if bitfields_len == 0 {
+		return Weight::zero()
+	}
 	<<T as Config>::WeightInfo as WeightInfo>::enter_bitfields()
 		.saturating_mul(bitfields_len as u64)
+		.saturating_add(
+			<<T as inclusion::Config>::RewardValidators as RewardValidators>::reward_bitfields_weight(
+				bitfields_len as u32,
+			),
+		)
 }
 
 pub fn backed_candidate_weight<T: frame_system::Config + Config>(
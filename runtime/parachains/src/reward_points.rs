@@ -20,73 +20,351 @@
 //! Based on <https://research.web3.foundation/en/latest/polkadot/overview/2-token-economics.html>
 //! which doesn't currently mention availability bitfields. As such, we don't reward them
 //! for the time being, although we will build schemes to do so in the future.
+//!
+//! The points awarded are also recorded by this pallet, broken down by the activity they were
+//! earned for, to explain the era points of a validator in `pallet-staking`. Contributions to
+//! availability are recorded with [`AVAILABILITY_POINTS`] each, but are not part of the era
+//! points. Runtimes without `pallet-staking` can record the breakdown on its own with
+//! [`RecordRewardPoints`].
 
 use crate::{session_info, shared};
-use frame_support::traits::{Defensive, ValidatorSet};
-use primitives::{SessionIndex, ValidatorIndex};
-use sp_std::collections::btree_set::BTreeSet;
+use frame_support::{
+	traits::{Defensive, Get, ValidatorSet},
+	weights::Weight,
+};
+use primitives::{vstaging::ValidatorRewardPoints, SessionIndex, ValidatorIndex};
+use sp_staking::EraIndex;
+use sp_std::{
+	collections::{btree_map::BTreeMap, btree_set::BTreeSet},
+	prelude::*,
+};
+
+pub use pallet::*;
+
+#[cfg(test)]
+mod tests;
 
 /// The amount of era points given by backing a candidate that is included.
 pub const BACKING_POINTS: u32 = 20;
 /// The amount of era points given by dispute voting on a candidate.
 pub const DISPUTE_STATEMENT_POINTS: u32 = 20;
+/// The amount of points recorded for contributing to the availability of a candidate that is
+/// included. These are not awarded as era points.
+pub const AVAILABILITY_POINTS: u32 = 1;
 
-/// Rewards validators for participating in parachains with era points in pallet-staking.
-pub struct RewardValidatorsWithEraPoints<C>(sp_std::marker::PhantomData<C>);
+/// The parachain activities points are recorded for.
+#[derive(Clone, Copy)]
+enum Activity {
+	Backing,
+	Availability,
+	Disputes,
+}
 
-impl<C> RewardValidatorsWithEraPoints<C>
+impl Activity {
+	/// Whether the points of the activity are awarded as era points in `pallet-staking`.
+	fn earns_era_points(self) -> bool {
+		match self {
+			Activity::Backing | Activity::Disputes => true,
+			Activity::Availability => false,
+		}
+	}
+}
+
+/// Provides the index of the active era of `pallet_staking`.
+pub struct StakingActiveEra<T>(sp_std::marker::PhantomData<T>);
+
+impl<T: pallet_staking::Config> Get<Option<EraIndex>> for StakingActiveEra<T> {
+	fn get() -> Option<EraIndex> {
+		<pallet_staking::Pallet<T>>::active_era().map(|era| era.index)
+	}
+}
+
+/// Provides the era of `E` as the active era, for runtimes without `pallet_staking` whose eras
+/// are always active, such as [`crate::disputes::slashing::SessionEras`].
+pub struct AlwaysActiveEra<E>(sp_std::marker::PhantomData<E>);
+
+impl<E: Get<EraIndex>> Get<Option<EraIndex>> for AlwaysActiveEra<E> {
+	fn get() -> Option<EraIndex> {
+		Some(E::get())
+	}
+}
+
+#[frame_support::pallet]
+pub mod pallet {
+	use super::*;
+	use frame_support::pallet_prelude::*;
+
+	#[pallet::pallet]
+	#[pallet::without_storage_info]
+	pub struct Pallet<T>(_);
+
+	#[pallet::config]
+	pub trait Config: frame_system::Config + shared::Config + session_info::Config {
+		/// The index of the era points are recorded for, `None` before the first era starts.
+		type ActiveEra: Get<Option<EraIndex>>;
+
+		/// The number of eras the breakdown is kept for. With `pallet-staking`, this should be
+		/// its `HistoryDepth`.
+		type HistoryDepth: Get<u32>;
+	}
+
+	/// The points each validator earned for its parachain duties in an era, broken down by
+	/// activity.
+	///
+	/// Entries are kept for `HistoryDepth` eras.
+	#[pallet::storage]
+	pub(crate) type ErasRewardPointsBreakdown<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		EraIndex,
+		Twox64Concat,
+		T::AccountId,
+		ValidatorRewardPoints,
+		ValueQuery,
+	>;
+
+	/// The earliest era for which the breakdown of era points is kept.
+	#[pallet::storage]
+	pub(crate) type EarliestStoredEra<T: Config> = StorageValue<_, EraIndex>;
+}
+
+impl<T: Config> Pallet<T> {
+	/// Records the points awarded in the active era, if any, for the given activity.
+	///
+	/// Returns the weight consumed.
+	fn note_rewards(rewards: &[(T::AccountId, u32)], activity: Activity) -> Weight {
+		let era = match T::ActiveEra::get() {
+			Some(era) => era,
+			None => return T::DbWeight::get().reads(1),
+		};
+		let weight = T::DbWeight::get().reads(1) + Self::prune_old_eras(era);
+
+		for (validator, points) in rewards {
+			<ErasRewardPointsBreakdown<T>>::mutate(era, validator, |entry| {
+				let total = match activity {
+					Activity::Backing => &mut entry.backing,
+					Activity::Availability => &mut entry.availability,
+					Activity::Disputes => &mut entry.disputes,
+				};
+				*total = total.saturating_add(*points);
+			});
+		}
+
+		weight + T::DbWeight::get().reads_writes(rewards.len() as u64, rewards.len() as u64)
+	}
+
+	/// Removes the breakdown of the eras older than `HistoryDepth`.
+	///
+	/// Returns the weight consumed.
+	fn prune_old_eras(era: EraIndex) -> Weight {
+		let new_earliest = era.saturating_sub(T::HistoryDepth::get());
+		let mut weight = T::DbWeight::get().reads(1);
+		match <EarliestStoredEra<T>>::get() {
+			Some(earliest) if earliest < new_earliest =>
+				for pruned in earliest..new_earliest {
+					let removed =
+						<ErasRewardPointsBreakdown<T>>::clear_prefix(pruned, u32::MAX, None);
+					weight += T::DbWeight::get()
+						.reads_writes(removed.loops as u64, removed.unique as u64);
+				},
+			Some(_) => return weight,
+			None => {},
+		}
+		<EarliestStoredEra<T>>::put(new_earliest);
+
+		weight + T::DbWeight::get().writes(1)
+	}
+
+	/// Returns the breakdown of the points each validator earned for its parachain duties in the
+	/// given era, sorted by validator.
+	///
+	/// Empty if the era is not kept.
+	pub(crate) fn reward_points_breakdown(
+		era: EraIndex,
+	) -> Vec<(T::AccountId, ValidatorRewardPoints)> {
+		let mut breakdown = <ErasRewardPointsBreakdown<T>>::iter_prefix(era).collect::<Vec<_>>();
+		breakdown.sort_by(|(a, _), (b, _)| a.cmp(b));
+		breakdown
+	}
+}
+
+impl<T: Config> Pallet<T>
 where
-	C: pallet_staking::Config + session_info::Config,
-	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
+	T::ValidatorSet: ValidatorSet<T::AccountId, ValidatorId = T::AccountId>,
 {
-	/// Reward validators in session with points, but only if they are in the active set.
+	/// Records the points of the validators in session, but only if they are in the active set.
+	///
+	/// A validator may appear several times and gets the points for each time, but its entry is
+	/// only written once.
+	///
+	/// Returns the rewards recorded and the weight consumed.
 	fn reward_only_active(
 		session_index: SessionIndex,
 		indices: impl IntoIterator<Item = ValidatorIndex>,
 		points: u32,
-	) {
-		let validators = session_info::Pallet::<C>::account_keys(&session_index);
+		activity: Activity,
+	) -> (Vec<(T::AccountId, u32)>, Weight) {
+		// `AccountKeys` and the active validator set.
+		let weight = T::DbWeight::get().reads(2);
+		let validators = session_info::Pallet::<T>::account_keys(&session_index);
 		let validators = match validators
 			.defensive_proof("account_keys are present for dispute_period sessions")
 		{
 			Some(validators) => validators,
-			None => return,
+			None => return (Vec::new(), weight),
 		};
 		// limit rewards to the active validator set
-		let active_set: BTreeSet<_> = C::ValidatorSet::validators().into_iter().collect();
+		let active_set: BTreeSet<_> = T::ValidatorSet::validators().into_iter().collect();
 
-		let rewards = indices
+		let mut rewards = BTreeMap::<T::AccountId, u32>::new();
+		for validator in indices
 			.into_iter()
 			.filter_map(|i| validators.get(i.0 as usize).cloned())
 			.filter(|v| active_set.contains(v))
-			.map(|v| (v, points));
+		{
+			let total = rewards.entry(validator).or_default();
+			*total = total.saturating_add(points);
+		}
+		let rewards = rewards.into_iter().collect::<Vec<_>>();
+		if rewards.is_empty() {
+			return (rewards, weight)
+		}
+
+		let weight = weight + Self::note_rewards(&rewards, activity);
+		(rewards, weight)
+	}
+
+	/// The weight of [`Self::reward_only_active`] recording the points of at most `validators`
+	/// distinct validators.
+	///
+	/// Pruning the eras beyond `HistoryDepth` is not included, it happens once per era and is
+	/// accounted for when it happens.
+	fn reward_only_active_weight(validators: u32) -> Weight {
+		// `AccountKeys`, the active validator set, the active era and `EarliestStoredEra`, then
+		// the entry of every validator.
+		T::DbWeight::get().reads_writes(4 + validators as u64, validators as u64)
+	}
+}
+
+/// Rewards validators for participating in parachains with era points in pallet-staking.
+///
+/// Contributions to availability are only recorded in the breakdown.
+pub struct RewardValidatorsWithEraPoints<C>(sp_std::marker::PhantomData<C>);
+
+impl<C> RewardValidatorsWithEraPoints<C>
+where
+	C: Config + pallet_staking::Config,
+	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
+{
+	/// Reward validators in session with points, but only if they are in the active set.
+	fn reward_only_active(
+		session_index: SessionIndex,
+		indices: impl IntoIterator<Item = ValidatorIndex>,
+		points: u32,
+		activity: Activity,
+	) -> Weight {
+		let (rewards, weight) =
+			<Pallet<C>>::reward_only_active(session_index, indices, points, activity);
+		if rewards.is_empty() || !activity.earns_era_points() {
+			return weight
+		}
 
 		<pallet_staking::Pallet<C>>::reward_by_ids(rewards);
+
+		// `pallet-staking` reads the active era and updates its era points.
+		weight + C::DbWeight::get().reads_writes(2, 1)
 	}
 }
 
 impl<C> crate::inclusion::RewardValidators for RewardValidatorsWithEraPoints<C>
 where
-	C: pallet_staking::Config + shared::Config + session_info::Config,
+	C: Config + pallet_staking::Config,
 	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
 {
-	fn reward_backing(indices: impl IntoIterator<Item = ValidatorIndex>) {
+	fn reward_backing(indices: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
 		let session_index = shared::Pallet::<C>::session_index();
-		Self::reward_only_active(session_index, indices, BACKING_POINTS);
+		Self::reward_only_active(session_index, indices, BACKING_POINTS, Activity::Backing)
 	}
 
-	fn reward_bitfields(_validators: impl IntoIterator<Item = ValidatorIndex>) {}
+	fn reward_bitfields(validators: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
+		let session_index = shared::Pallet::<C>::session_index();
+		Self::reward_only_active(
+			session_index,
+			validators,
+			AVAILABILITY_POINTS,
+			Activity::Availability,
+		)
+	}
+
+	fn reward_bitfields_weight(validators: u32) -> Weight {
+		// Availability is not awarded as era points.
+		<Pallet<C>>::reward_only_active_weight(validators)
+	}
 }
 
 impl<C> crate::disputes::RewardValidators for RewardValidatorsWithEraPoints<C>
 where
-	C: pallet_staking::Config + session_info::Config,
+	C: Config + pallet_staking::Config,
+	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
+{
+	fn reward_dispute_statement(
+		session: SessionIndex,
+		validators: impl IntoIterator<Item = ValidatorIndex>,
+	) {
+		// The disputes pallet does not account for the weight of its rewards yet.
+		let _ = Self::reward_only_active(
+			session,
+			validators,
+			DISPUTE_STATEMENT_POINTS,
+			Activity::Disputes,
+		);
+	}
+}
+
+/// Records the points validators earn for participating in parachains without awarding them, for
+/// runtimes without `pallet-staking`.
+pub struct RecordRewardPoints<C>(sp_std::marker::PhantomData<C>);
+
+impl<C> crate::inclusion::RewardValidators for RecordRewardPoints<C>
+where
+	C: Config,
+	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
+{
+	fn reward_backing(indices: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
+		let session_index = shared::Pallet::<C>::session_index();
+		<Pallet<C>>::reward_only_active(session_index, indices, BACKING_POINTS, Activity::Backing).1
+	}
+
+	fn reward_bitfields(validators: impl IntoIterator<Item = ValidatorIndex>) -> Weight {
+		let session_index = shared::Pallet::<C>::session_index();
+		<Pallet<C>>::reward_only_active(
+			session_index,
+			validators,
+			AVAILABILITY_POINTS,
+			Activity::Availability,
+		)
+		.1
+	}
+
+	fn reward_bitfields_weight(validators: u32) -> Weight {
+		<Pallet<C>>::reward_only_active_weight(validators)
+	}
+}
+
+impl<C> crate::disputes::RewardValidators for RecordRewardPoints<C>
+where
+	C: Config,
 	C::ValidatorSet: ValidatorSet<C::AccountId, ValidatorId = C::AccountId>,
 {
 	fn reward_dispute_statement(
 		session: SessionIndex,
 		validators: impl IntoIterator<Item = ValidatorIndex>,
 	) {
-		Self::reward_only_active(session, validators, DISPUTE_STATEMENT_POINTS);
+		let _ = <Pallet<C>>::reward_only_active(
+			session,
+			validators,
+			DISPUTE_STATEMENT_POINTS,
+			Activity::Disputes,
+		);
 	}
 }
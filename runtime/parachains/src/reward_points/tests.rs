@@ -0,0 +1,169 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use crate::{
+	disputes::RewardValidators as _,
+	inclusion::RewardValidators as _,
+	mock::{new_test_ext, ActiveValidators, MockGenesisConfig, ParasRewardPoints, Staking, Test},
+	runtime_api_impl::vstaging as runtime_api,
+};
+
+type Rewards = RewardValidatorsWithEraPoints<Test>;
+
+/// Starts `era`, with the validators at indices `0..4` of session 0 having the accounts `1..5`,
+/// of which only the first three are in the active set.
+fn start_era(era: EraIndex) {
+	session_info::AccountKeys::<Test>::insert(0, vec![1, 2, 3, 4]);
+	ActiveValidators::set(vec![1, 2, 3]);
+	pallet_staking::ActiveEra::<Test>::put(pallet_staking::ActiveEraInfo {
+		index: era,
+		start: None,
+	});
+}
+
+fn v(indices: &[u32]) -> Vec<ValidatorIndex> {
+	indices.iter().map(|&i| ValidatorIndex(i)).collect()
+}
+
+fn points(backing: u32, availability: u32, disputes: u32) -> ValidatorRewardPoints {
+	ValidatorRewardPoints { backing, availability, disputes }
+}
+
+#[test]
+fn rewards_are_recorded_by_activity() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_era(1);
+
+		Rewards::reward_backing(v(&[1, 0]));
+		Rewards::reward_bitfields(v(&[0, 1, 2, 3]));
+		Rewards::reward_dispute_statement(0, v(&[2]));
+
+		// the inactive validator 4 is not rewarded
+		let expected = vec![
+			(1, points(BACKING_POINTS, AVAILABILITY_POINTS, 0)),
+			(2, points(BACKING_POINTS, AVAILABILITY_POINTS, 0)),
+			(3, points(0, AVAILABILITY_POINTS, DISPUTE_STATEMENT_POINTS)),
+		];
+		assert_eq!(ParasRewardPoints::reward_points_breakdown(1), expected);
+		assert_eq!(runtime_api::validator_reward_points::<Test>(1), expected);
+		assert!(runtime_api::validator_reward_points::<Test>(2).is_empty());
+
+		// availability is not awarded, so the era points in staking are made of the backing and
+		// dispute points
+		let era_points = Staking::eras_reward_points(1);
+		for (validator, breakdown) in expected {
+			assert_eq!(
+				era_points.individual.get(&validator).cloned().unwrap_or_default(),
+				breakdown.backing + breakdown.disputes,
+			);
+		}
+	});
+}
+
+#[test]
+fn rewards_are_not_recorded_without_active_era() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_era(1);
+		pallet_staking::ActiveEra::<Test>::kill();
+
+		Rewards::reward_backing(v(&[0, 1]));
+
+		assert!(ParasRewardPoints::reward_points_breakdown(1).is_empty());
+		assert_eq!(EarliestStoredEra::<Test>::get(), None);
+	});
+}
+
+#[test]
+fn eras_beyond_history_depth_are_pruned() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		let history_depth = <Test as pallet_staking::Config>::HistoryDepth::get();
+
+		for era in 1..=history_depth + 2 {
+			start_era(era);
+			Rewards::reward_backing(v(&[0]));
+		}
+
+		// the eras `pallet-staking` no longer keeps era points for are pruned
+		assert_eq!(EarliestStoredEra::<Test>::get(), Some(2));
+		assert!(ParasRewardPoints::reward_points_breakdown(1).is_empty());
+		for era in 2..=history_depth + 2 {
+			assert_eq!(
+				ParasRewardPoints::reward_points_breakdown(era),
+				vec![(1, points(BACKING_POINTS, 0, 0))],
+			);
+		}
+	});
+}
+
+#[test]
+fn bitfields_do_not_earn_era_points() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_era(1);
+
+		Rewards::reward_bitfields(v(&[0, 1]));
+
+		assert_eq!(
+			ParasRewardPoints::reward_points_breakdown(1),
+			vec![(1, points(0, AVAILABILITY_POINTS, 0)), (2, points(0, AVAILABILITY_POINTS, 0))],
+		);
+		assert_eq!(Staking::eras_reward_points(1).total, 0);
+	});
+}
+
+#[test]
+fn points_can_be_recorded_without_awarding_them() {
+	type Record = RecordRewardPoints<Test>;
+
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_era(1);
+
+		Record::reward_backing(v(&[0]));
+		Record::reward_bitfields(v(&[0]));
+		Record::reward_dispute_statement(0, v(&[1]));
+
+		assert_eq!(
+			ParasRewardPoints::reward_points_breakdown(1),
+			vec![
+				(1, points(BACKING_POINTS, AVAILABILITY_POINTS, 0)),
+				(2, points(0, 0, DISPUTE_STATEMENT_POINTS)),
+			],
+		);
+		assert_eq!(Staking::eras_reward_points(1).total, 0);
+	});
+}
+
+#[test]
+fn repeated_validators_are_recorded_once() {
+	new_test_ext(MockGenesisConfig::default()).execute_with(|| {
+		start_era(1);
+		// the first rewards of the era also note the earliest stored era.
+		Rewards::reward_backing(v(&[2]));
+
+		// validator 0 contributed to the availability of three candidates.
+		let weight = Rewards::reward_bitfields(v(&[0, 1, 0, 0]));
+
+		assert_eq!(
+			ParasRewardPoints::reward_points_breakdown(1),
+			vec![
+				(1, points(0, 3 * AVAILABILITY_POINTS, 0)),
+				(2, points(0, AVAILABILITY_POINTS, 0)),
+				(3, points(BACKING_POINTS, 0, 0)),
+			],
+		);
+		assert_eq!(weight, Rewards::reward_bitfields_weight(2));
+	});
+}
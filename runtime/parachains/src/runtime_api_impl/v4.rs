@@ -51,7 +51,6 @@ pub fn validator_groups<T: initializer::Config>(
 /// Implementation for the `availability_cores` function of the runtime API.
 pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T::BlockNumber>> {
 	let cores = <scheduler::Pallet<T>>::availability_cores();
-	let parachains = <paras::Pallet<T>>::parachains();
 	let config = <configuration::Pallet<T>>::config();
 
 	let now = <frame_system::Pallet<T>>::block_number() + One::one();
@@ -59,6 +58,7 @@ pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T:
 	<scheduler::Pallet<T>>::schedule(Vec::new(), now);
 
 	let rotation_info = <scheduler::Pallet<T>>::group_rotation_info(now);
+	let assignment_context = scheduler::AssignmentContext::<T>::with_config(config.clone());
 
 	let time_out_at = |backed_in_number, availability_period| {
 		let time_out_at = backed_in_number + availability_period;
@@ -103,14 +103,19 @@ pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T:
 		.map(|(i, core)| match core {
 			Some(occupied) => CoreState::Occupied(match occupied {
 				CoreOccupied::Parachain => {
-					let para_id = parachains[i];
+					let para_id = <scheduler::Pallet<T>>::core_para(CoreIndex(i as u32))
+						.expect("Occupied core always has an occupying para; qed");
 					let pending_availability =
-						<inclusion::Pallet<T>>::pending_availability(para_id)
-							.expect("Occupied core always has pending availability; qed");
+						<inclusion::Pallet<T>>::pending_availability_on_core(
+							para_id,
+							CoreIndex(i as u32),
+						)
+						.expect("Occupied core always has pending availability; qed");
 
 					let backed_in_number = *pending_availability.backed_in_number();
 					OccupiedCore {
 						next_up_on_available: <scheduler::Pallet<T>>::next_up_on_available(
+							&assignment_context,
 							CoreIndex(i as u32),
 						),
 						occupied_since: backed_in_number,
@@ -119,6 +124,7 @@ pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T:
 							config.chain_availability_period,
 						),
 						next_up_on_time_out: <scheduler::Pallet<T>>::next_up_on_time_out(
+							&assignment_context,
 							CoreIndex(i as u32),
 						),
 						availability: pending_availability.availability_votes().clone(),
@@ -133,12 +139,16 @@ pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T:
 				CoreOccupied::Parathread(p) => {
 					let para_id = p.claim.0;
 					let pending_availability =
-						<inclusion::Pallet<T>>::pending_availability(para_id)
-							.expect("Occupied core always has pending availability; qed");
+						<inclusion::Pallet<T>>::pending_availability_on_core(
+							para_id,
+							CoreIndex(i as u32),
+						)
+						.expect("Occupied core always has pending availability; qed");
 
 					let backed_in_number = *pending_availability.backed_in_number();
 					OccupiedCore {
 						next_up_on_available: <scheduler::Pallet<T>>::next_up_on_available(
+							&assignment_context,
 							CoreIndex(i as u32),
 						),
 						occupied_since: backed_in_number,
@@ -147,6 +157,7 @@ pub fn availability_cores<T: initializer::Config>() -> Vec<CoreState<T::Hash, T:
 							config.thread_availability_period,
 						),
 						next_up_on_time_out: <scheduler::Pallet<T>>::next_up_on_time_out(
+							&assignment_context,
 							CoreIndex(i as u32),
 						),
 						availability: pending_availability.availability_votes().clone(),
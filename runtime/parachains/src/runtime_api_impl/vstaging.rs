@@ -15,3 +15,33 @@
 // along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
 
 //! Put implementations of functions from staging APIs here.
+
+use crate::{dmp, inclusion, reward_points, ump};
+use primitives::{
+	vstaging::{DmqStatus, ParaUsage, UmpOverweightMessage, ValidatorRewardPoints},
+	Id as ParaId, SessionIndex,
+};
+use sp_staking::EraIndex;
+use sp_std::prelude::*;
+
+/// Implementation for the `staging_para_usage` function of the runtime API.
+pub fn para_usage<T: inclusion::Config>(session_index: SessionIndex) -> Vec<(ParaId, ParaUsage)> {
+	<inclusion::Pallet<T>>::para_usage(session_index)
+}
+
+/// Implementation for the `staging_dmq_status` function of the runtime API.
+pub fn dmq_status<T: dmp::Config>() -> Vec<(ParaId, DmqStatus)> {
+	<dmp::Pallet<T>>::dmq_status()
+}
+
+/// Implementation for the `staging_ump_overweight_messages` function of the runtime API.
+pub fn ump_overweight_messages<T: ump::Config>() -> Vec<(ParaId, Vec<UmpOverweightMessage>)> {
+	<ump::Pallet<T>>::overweight_messages()
+}
+
+/// Implementation for the `staging_validator_reward_points` function of the runtime API.
+pub fn validator_reward_points<T: reward_points::Config>(
+	era: EraIndex,
+) -> Vec<(T::AccountId, ValidatorRewardPoints)> {
+	<reward_points::Pallet<T>>::reward_points_breakdown(era)
+}
@@ -34,6 +34,11 @@
 //! used for multiplexing parathreads. Validators will be partitioned into groups, with the same
 //! number of groups as availability cores. Validator groups will be assigned to different availability cores
 //! over time.
+//!
+//! Which para is assigned to which core is decided by the [`AssignmentProvider`] of the runtime. By default
+//! one core is assigned to each parachain and the parathread claims are multiplexed onto the remaining cores,
+//! see [`DefaultAssignmentProvider`]. Governance can assign additional cores to a parachain with
+//! [`Pallet::set_parachain_cores`], which allows it to have a candidate backed on each of them in the same block.
 
 use frame_support::pallet_prelude::*;
 use primitives::{
@@ -44,13 +49,35 @@ use scale_info::TypeInfo;
 use sp_runtime::traits::{One, Saturating};
 use sp_std::prelude::*;
 
-use crate::{configuration, initializer::SessionChangeNotification, paras};
+use crate::{configuration, initializer::SessionChangeNotification, paras, shared};
 
 pub use pallet::*;
 
+mod assignment;
+pub use assignment::{
+	Assignment, AssignmentContext, AssignmentProvider, CoreSplit, DefaultAssignmentProvider,
+	LeaseAssignmentProvider, OnDemandAssignmentProvider,
+};
+
 #[cfg(test)]
 mod tests;
 
+#[cfg(feature = "runtime-benchmarks")]
+mod benchmarking;
+
+pub trait WeightInfo {
+	fn set_parachain_cores() -> Weight;
+}
+
+/// A weight info that is only suitable for testing.
+pub struct TestWeightInfo;
+
+impl WeightInfo for TestWeightInfo {
+	fn set_parachain_cores() -> Weight {
+		Weight::MAX
+	}
+}
+
 /// A queued parathread entry, pre-assigned to a core.
 #[derive(Encode, Decode, TypeInfo)]
 #[cfg_attr(test, derive(PartialEq, Debug))]
@@ -98,6 +125,19 @@ impl Default for ParathreadClaimQueue {
 	}
 }
 
+/// The outcome of adding a parathread claim to the queue.
+#[derive(Clone, Copy, PartialEq, Eq, RuntimeDebug)]
+pub enum ParathreadClaimOutcome {
+	/// The claim was added to the queue.
+	Queued,
+	/// The claim does not correspond to any live parathread.
+	NotParathread,
+	/// The queue is full.
+	QueueFull,
+	/// There is a competing claim for the same parathread in the queue or occupying a core.
+	AlreadyClaimed,
+}
+
 /// Reasons a core might be freed
 #[derive(Clone, Copy)]
 pub enum FreedReason {
@@ -156,13 +196,20 @@ impl CoreAssignment {
 #[frame_support::pallet]
 pub mod pallet {
 	use super::*;
+	use frame_system::pallet_prelude::*;
 
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
 	pub struct Pallet<T>(_);
 
 	#[pallet::config]
-	pub trait Config: frame_system::Config + configuration::Config + paras::Config {}
+	pub trait Config: frame_system::Config + configuration::Config + paras::Config {
+		/// Decides which paras are assigned to the availability cores.
+		type AssignmentProvider: AssignmentProvider<Self>;
+
+		/// Weight information for extrinsics in this pallet.
+		type WeightInfo: WeightInfo;
+	}
 
 	/// All the validator groups. One for each core. Indices are into `ActiveValidators` - not the
 	/// broader set of Polkadot validators, but instead just the subset used for parachains during
@@ -193,6 +240,15 @@ pub mod pallet {
 	#[pallet::getter(fn availability_cores)]
 	pub(crate) type AvailabilityCores<T> = StorageValue<_, Vec<Option<CoreOccupied>>, ValueQuery>;
 
+	/// The parachain occupying each core which is occupied by a [`CoreOccupied::Parachain`].
+	///
+	/// Cores occupied before this was introduced are missing and occupied by the parachain of the
+	/// same index in [`paras::Pallet::parachains`].
+	///
+	/// Bounded by the number of cores.
+	#[pallet::storage]
+	pub(crate) type OccupiedParachains<T> = StorageMap<_, Twox64Concat, CoreIndex, ParaId>;
+
 	/// An index used to ensure that only one claim on a parathread exists in the queue or is
 	/// currently being handled by an occupied core.
 	///
@@ -210,6 +266,19 @@ pub mod pallet {
 	#[pallet::getter(fn session_start_block)]
 	pub(crate) type SessionStartBlock<T: Config> = StorageValue<_, T::BlockNumber, ValueQuery>;
 
+	/// The number of cores assigned to each parachain which is assigned more than one core.
+	///
+	/// Changes take effect at the next session.
+	#[pallet::storage]
+	pub(crate) type ParachainCores<T> = StorageMap<_, Twox64Concat, ParaId, u32>;
+
+	/// The parachain of each core which a parachain is assigned to in addition to its first core
+	/// in this session, see [`LeaseAssignmentProvider`].
+	///
+	/// Bounded by the number of cores.
+	#[pallet::storage]
+	pub(crate) type ElasticCores<T> = StorageValue<_, Vec<ParaId>, ValueQuery>;
+
 	/// Currently scheduled cores - free but up to be occupied.
 	///
 	/// Bounded by the number of cores: one for each parachain and parathread multiplexer.
@@ -220,6 +289,47 @@ pub mod pallet {
 	#[pallet::getter(fn scheduled)]
 	pub(crate) type Scheduled<T> = StorageValue<_, Vec<CoreAssignment>, ValueQuery>;
 	// sorted ascending by CoreIndex.
+
+	#[pallet::error]
+	pub enum Error<T> {
+		/// A parachain is always assigned at least one core.
+		ZeroCores,
+		/// A parachain can not be assigned more cores than there are validators.
+		TooManyCores,
+	}
+
+	#[pallet::call]
+	impl<T: Config> Pallet<T> {
+		/// Set the number of cores the given parachain is assigned to, starting with the next
+		/// session.
+		///
+		/// Parachains are assigned a single core by default. A parachain which is assigned more
+		/// cores can have a candidate backed on each of them in the same block, each building on
+		/// the previous one.
+		#[pallet::call_index(0)]
+		#[pallet::weight(<T as Config>::WeightInfo::set_parachain_cores())]
+		pub fn set_parachain_cores(
+			origin: OriginFor<T>,
+			para_id: ParaId,
+			cores: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+
+			ensure!(cores > 0, Error::<T>::ZeroCores);
+			ensure!(
+				cores as usize <= <shared::Pallet<T>>::active_validator_keys().len(),
+				Error::<T>::TooManyCores,
+			);
+
+			if cores == 1 {
+				ParachainCores::<T>::remove(para_id);
+			} else {
+				ParachainCores::<T>::insert(para_id, cores);
+			}
+
+			Ok(())
+		}
+	}
 }
 
 impl<T: Config> Pallet<T> {
@@ -238,31 +348,29 @@ impl<T: Config> Pallet<T> {
 		let SessionChangeNotification { validators, new_config, .. } = notification;
 		let config = new_config;
 
-		let mut thread_queue = ParathreadQueue::<T>::get();
-		let n_parachains = <paras::Pallet<T>>::parachains().len() as u32;
+		// clear all occupied cores.
+		let mut dropped = Vec::new();
+		AvailabilityCores::<T>::mutate(|cores| {
+			for (i, maybe_occupied) in cores.iter_mut().enumerate() {
+				if let Some(occupied) = maybe_occupied.take() {
+					let core = CoreIndex(i as u32);
+					dropped.extend(Self::occupied_assignment(core, occupied));
+				}
+			}
+		});
+		let _ = OccupiedParachains::<T>::clear(u32::MAX, None);
+
+		T::AssignmentProvider::on_new_session(config, &dropped);
+
+		let context = AssignmentContext::<T>::with_config(config.clone());
 		let n_cores = core::cmp::max(
-			n_parachains + config.parathread_cores,
+			T::AssignmentProvider::session_core_count(&context),
 			match config.max_validators_per_core {
 				Some(x) if x != 0 => validators.len() as u32 / x,
 				_ => 0,
 			},
 		);
-
-		AvailabilityCores::<T>::mutate(|cores| {
-			// clear all occupied cores.
-			for maybe_occupied in cores.iter_mut() {
-				if let Some(CoreOccupied::Parathread(claim)) = maybe_occupied.take() {
-					let queued = QueuedParathread {
-						claim,
-						core_offset: 0, // this gets set later in the re-balancing.
-					};
-
-					thread_queue.queue.push(queued);
-				}
-			}
-
-			cores.resize(n_cores as _, None);
-		});
+		AvailabilityCores::<T>::mutate(|cores| cores.resize(n_cores as _, None));
 
 		// shuffle validators into groups.
 		if n_cores == 0 || validators.is_empty() {
@@ -298,57 +406,18 @@ impl<T: Config> Pallet<T> {
 			ValidatorGroups::<T>::set(groups);
 		}
 
-		// prune out all parathread claims with too many retries.
-		// assign all non-pruned claims to new cores, if they've changed.
-		ParathreadClaimIndex::<T>::mutate(|claim_index| {
-			// wipe all parathread metadata if no parathread cores are configured.
-			if config.parathread_cores == 0 {
-				thread_queue = ParathreadClaimQueue { queue: Vec::new(), next_core_offset: 0 };
-				claim_index.clear();
-				return
-			}
-
-			// prune out all entries beyond retry or that no longer correspond to live parathread.
-			thread_queue.queue.retain(|queued| {
-				let will_keep = queued.claim.retries <= config.parathread_retries &&
-					<paras::Pallet<T>>::is_parathread(queued.claim.claim.0);
-
-				if !will_keep {
-					let claim_para = queued.claim.claim.0;
-
-					// clean up the pruned entry from the index.
-					if let Ok(i) = claim_index.binary_search(&claim_para) {
-						claim_index.remove(i);
-					}
-				}
-
-				will_keep
-			});
-
-			// do re-balancing of claims.
-			{
-				for (i, queued) in thread_queue.queue.iter_mut().enumerate() {
-					queued.core_offset = (i as u32) % config.parathread_cores;
-				}
-
-				thread_queue.next_core_offset =
-					((thread_queue.queue.len()) as u32) % config.parathread_cores;
-			}
-		});
-		ParathreadQueue::<T>::set(thread_queue);
-
 		let now = <frame_system::Pallet<T>>::block_number() + One::one();
 		<SessionStartBlock<T>>::set(now);
 	}
 
 	/// Add a parathread claim to the queue. If there is a competing claim in the queue or currently
-	/// assigned to a core, this call will fail. This call will also fail if the queue is full.
+	/// assigned to a core, the claim is not added. The claim is also not added if the queue is full
+	/// or if it does not correspond to any live parathread.
 	///
-	/// Fails if the claim does not correspond to any live parathread.
-	#[allow(unused)]
-	pub fn add_parathread_claim(claim: ParathreadClaim) {
+	/// Returns whether the claim was added and if not, why.
+	pub fn add_parathread_claim(claim: ParathreadClaim) -> ParathreadClaimOutcome {
 		if !<paras::Pallet<T>>::is_parathread(claim.0) {
-			return
+			return ParathreadClaimOutcome::NotParathread
 		}
 
 		let config = <configuration::Pallet<T>>::config();
@@ -356,7 +425,7 @@ impl<T: Config> Pallet<T> {
 
 		ParathreadQueue::<T>::mutate(|queue| {
 			if queue.queue.len() >= queue_max_size as usize {
-				return
+				return ParathreadClaimOutcome::QueueFull
 			}
 
 			let para_id = claim.0;
@@ -371,44 +440,55 @@ impl<T: Config> Pallet<T> {
 				});
 
 			if competes_with_another {
-				return
+				return ParathreadClaimOutcome::AlreadyClaimed
 			}
 
 			let entry = ParathreadEntry { claim, retries: 0 };
 			queue.enqueue_entry(entry, config.parathread_cores);
+
+			ParathreadClaimOutcome::Queued
 		})
 	}
 
 	/// Free unassigned cores. Provide a list of cores that should be considered newly-freed along with the reason
 	/// for them being freed. The list is assumed to be sorted in ascending order by core index.
 	pub(crate) fn free_cores(just_freed_cores: impl IntoIterator<Item = (CoreIndex, FreedReason)>) {
-		let config = <configuration::Pallet<T>>::config();
+		Self::free_cores_in_context(&AssignmentContext::load(), just_freed_cores)
+	}
 
+	/// Like [`Self::free_cores`], with a context which was already read in this block.
+	fn free_cores_in_context(
+		context: &AssignmentContext<T>,
+		just_freed_cores: impl IntoIterator<Item = (CoreIndex, FreedReason)>,
+	) {
 		AvailabilityCores::<T>::mutate(|cores| {
 			for (freed_index, freed_reason) in just_freed_cores {
 				if (freed_index.0 as usize) < cores.len() {
-					match cores[freed_index.0 as usize].take() {
+					let occupied = match cores[freed_index.0 as usize].take() {
 						None => continue,
-						Some(CoreOccupied::Parachain) => {},
-						Some(CoreOccupied::Parathread(entry)) => {
-							match freed_reason {
-								FreedReason::Concluded => {
-									// After a parathread candidate has successfully been included,
-									// open it up for further claims!
-									ParathreadClaimIndex::<T>::mutate(|index| {
-										if let Ok(i) = index.binary_search(&entry.claim.0) {
-											index.remove(i);
-										}
-									})
-								},
-								FreedReason::TimedOut => {
-									// If a parathread candidate times out, it's not the collator's fault,
-									// so we don't increment retries.
-									ParathreadQueue::<T>::mutate(|queue| {
-										queue.enqueue_entry(entry, config.parathread_cores);
-									})
-								},
-							}
+						Some(occupied) => occupied,
+					};
+
+					let assignment = match Self::occupied_assignment(freed_index, occupied) {
+						None => continue,
+						Some(assignment) => assignment,
+					};
+					OccupiedParachains::<T>::remove(freed_index);
+
+					match freed_reason {
+						FreedReason::Concluded => T::AssignmentProvider::report_processed(
+							context,
+							freed_index,
+							assignment,
+						),
+						FreedReason::TimedOut => {
+							// If a parathread candidate times out, it's not the collator's fault,
+							// so we don't increment retries.
+							T::AssignmentProvider::push_back_assignment(
+								context,
+								freed_index,
+								assignment,
+							)
 						},
 					}
 				}
@@ -423,12 +503,12 @@ impl<T: Config> Pallet<T> {
 		just_freed_cores: impl IntoIterator<Item = (CoreIndex, FreedReason)>,
 		now: T::BlockNumber,
 	) {
-		Self::free_cores(just_freed_cores);
+		// The providers are asked for every core, read what they assign based on only once.
+		let context = AssignmentContext::load();
+		Self::free_cores_in_context(&context, just_freed_cores);
 
 		let cores = AvailabilityCores::<T>::get();
-		let parachains = <paras::Pallet<T>>::parachains();
 		let mut scheduled = Scheduled::<T>::get();
-		let mut parathread_queue = ParathreadQueue::<T>::get();
 
 		if ValidatorGroups::<T>::get().is_empty() {
 			return
@@ -477,31 +557,18 @@ impl<T: Config> Pallet<T> {
 
 				let core = CoreIndex(core_index as u32);
 
-				let core_assignment = if core_index < parachains.len() {
-					// parachain core.
-					Some(CoreAssignment {
-						kind: AssignmentKind::Parachain,
-						para_id: parachains[core_index],
-						core,
-						group_idx: Self::group_assigned_to_core(core, now).expect(
-							"core is not out of bounds and we are guaranteed \
+				let core_assignment = T::AssignmentProvider::pop_assignment_for_core(
+					&context, core,
+				)
+				.map(|Assignment { para_id, kind }| CoreAssignment {
+					kind,
+					para_id,
+					core,
+					group_idx: Self::group_assigned_to_core(core, now).expect(
+						"core is not out of bounds and we are guaranteed \
 									to be after the most recent session start; qed",
-						),
-					})
-				} else {
-					// parathread core offset, rel. to beginning.
-					let core_offset = (core_index - parachains.len()) as u32;
-
-					parathread_queue.take_next_on_core(core_offset).map(|entry| CoreAssignment {
-						kind: AssignmentKind::Parathread(entry.claim.1, entry.retries),
-						para_id: entry.claim.0,
-						core,
-						group_idx: Self::group_assigned_to_core(core, now).expect(
-							"core is not out of bounds and we are guaranteed \
-									to be after the most recent session start; qed",
-						),
-					})
-				};
+					),
+				});
 
 				if let Some(assignment) = core_assignment {
 					scheduled_updates.push((schedule_and_insert_at, assignment))
@@ -527,7 +594,6 @@ impl<T: Config> Pallet<T> {
 		}
 
 		Scheduled::<T>::set(scheduled);
-		ParathreadQueue::<T>::set(parathread_queue);
 	}
 
 	/// Note that the given cores have become occupied. Behavior undefined if any of the given cores were not scheduled
@@ -558,6 +624,9 @@ impl<T: Config> Pallet<T> {
 
 					availability_cores[assignment.core.0 as usize] =
 						Some(assignment.to_core_occupied());
+					if let AssignmentKind::Parachain = assignment.kind {
+						OccupiedParachains::<T>::insert(assignment.core, assignment.para_id);
+					}
 				}
 
 				retain
@@ -573,14 +642,27 @@ impl<T: Config> Pallet<T> {
 		let cores = AvailabilityCores::<T>::get();
 		match cores.get(core_index.0 as usize).and_then(|c| c.as_ref()) {
 			None => None,
-			Some(CoreOccupied::Parachain) => {
-				let parachains = <paras::Pallet<T>>::parachains();
-				Some(parachains[core_index.0 as usize])
-			},
+			Some(CoreOccupied::Parachain) => Self::occupied_parachain(core_index),
 			Some(CoreOccupied::Parathread(ref entry)) => Some(entry.claim.0),
 		}
 	}
 
+	/// The parachain occupying a core occupied by a [`CoreOccupied::Parachain`].
+	fn occupied_parachain(core_index: CoreIndex) -> Option<ParaId> {
+		OccupiedParachains::<T>::get(core_index)
+			.or_else(|| <paras::Pallet<T>>::parachains().get(core_index.0 as usize).copied())
+	}
+
+	/// The assignment which the given core is occupied with.
+	fn occupied_assignment(core_index: CoreIndex, occupied: CoreOccupied) -> Option<Assignment> {
+		let para_id = match occupied {
+			CoreOccupied::Parachain => Self::occupied_parachain(core_index),
+			CoreOccupied::Parathread(_) => None,
+		};
+
+		Assignment::from_occupied(occupied, para_id)
+	}
+
 	/// Get the validators in the given group, if the group index is valid for this session.
 	pub(crate) fn group_validators(group_index: GroupIndex) -> Option<Vec<ValidatorIndex>> {
 		ValidatorGroups::<T>::get().get(group_index.0 as usize).map(|g| g.clone())
@@ -681,82 +763,65 @@ impl<T: Config> Pallet<T> {
 	/// Return the next thing that will be scheduled on this core assuming it is currently
 	/// occupied and the candidate occupying it became available.
 	///
-	/// For parachains, this is always the ID of the parachain and no specified collator.
-	/// For parathreads, this is based on the next item in the `ParathreadQueue` assigned to that
-	/// core, and is None if there isn't one.
-	pub(crate) fn next_up_on_available(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Pallet<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore { para_id: parachains[core.0 as usize], collator: None })
-		} else {
-			let queue = ParathreadQueue::<T>::get();
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
-			queue.get_next_on_core(core_offset).map(|entry| ScheduledCore {
-				para_id: entry.claim.0,
-				collator: Some(entry.claim.1.clone()),
-			})
-		}
+	/// This is the next assignment of the [`AssignmentProvider`] for the core. By default, for
+	/// parachains this is always the ID of the parachain and no specified collator. For parathreads,
+	/// this is based on the next item in the `ParathreadQueue` assigned to that core, and is None if
+	/// there isn't one.
+	pub(crate) fn next_up_on_available(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<ScheduledCore> {
+		T::AssignmentProvider::peek_assignment_for_core(context, core).map(Self::scheduled_core)
 	}
 
 	/// Return the next thing that will be scheduled on this core assuming it is currently
-	/// occupied and the candidate occupying it became available.
+	/// occupied and the candidate occupying it timed out.
 	///
-	/// For parachains, this is always the ID of the parachain and no specified collator.
-	/// For parathreads, this is based on the next item in the `ParathreadQueue` assigned to that
-	/// core, or if there isn't one, the claim that is currently occupying the core, as long
-	/// as the claim's retries would not exceed the limit. Otherwise None.
-	pub(crate) fn next_up_on_time_out(core: CoreIndex) -> Option<ScheduledCore> {
-		let parachains = <paras::Pallet<T>>::parachains();
-		if (core.0 as usize) < parachains.len() {
-			Some(ScheduledCore { para_id: parachains[core.0 as usize], collator: None })
-		} else {
-			let queue = ParathreadQueue::<T>::get();
-
-			// This is the next scheduled para on this core.
-			let core_offset = (core.0 as usize - parachains.len()) as u32;
-			queue
-				.get_next_on_core(core_offset)
-				.map(|entry| ScheduledCore {
-					para_id: entry.claim.0,
-					collator: Some(entry.claim.1.clone()),
-				})
-				.or_else(|| {
-					// Or, if none, the claim currently occupying the core,
-					// as it would be put back on the queue after timing out.
-					let cores = AvailabilityCores::<T>::get();
-					cores.get(core.0 as usize).and_then(|c| c.as_ref()).and_then(|o| {
-						match o {
-							CoreOccupied::Parathread(entry) => Some(ScheduledCore {
-								para_id: entry.claim.0,
-								collator: Some(entry.claim.1.clone()),
-							}),
-							CoreOccupied::Parachain => None, // defensive; not possible.
-						}
-					})
-				})
-		}
+	/// This is the next assignment of the [`AssignmentProvider`] for the core or if there isn't
+	/// one, the assignment that is currently occupying the core, as it would be pushed back after
+	/// timing out. By default, for parachains this is always the ID of the parachain and no
+	/// specified collator.
+	pub(crate) fn next_up_on_time_out(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<ScheduledCore> {
+		T::AssignmentProvider::peek_assignment_for_core(context, core)
+			.or_else(|| {
+				let cores = AvailabilityCores::<T>::get();
+				cores
+					.get(core.0 as usize)
+					.cloned()
+					.flatten()
+					.and_then(|occupied| Self::occupied_assignment(core, occupied))
+			})
+			.map(Self::scheduled_core)
 	}
 
-	// Free all scheduled cores and return parathread claims to queue, with retries incremented.
-	pub(crate) fn clear() {
-		let config = <configuration::Pallet<T>>::config();
-		ParathreadQueue::<T>::mutate(|queue| {
-			for core_assignment in Scheduled::<T>::take() {
-				if let AssignmentKind::Parathread(collator, retries) = core_assignment.kind {
-					if !<paras::Pallet<T>>::is_parathread(core_assignment.para_id) {
-						continue
-					}
+	fn scheduled_core(assignment: Assignment) -> ScheduledCore {
+		let collator = match assignment.kind {
+			AssignmentKind::Parachain => None,
+			AssignmentKind::Parathread(collator, _) => Some(collator),
+		};
 
-					let entry = ParathreadEntry {
-						claim: ParathreadClaim(core_assignment.para_id, collator),
-						retries: retries + 1,
-					};
+		ScheduledCore { para_id: assignment.para_id, collator }
+	}
 
-					if entry.retries <= config.parathread_retries {
-						queue.enqueue_entry(entry, config.parathread_cores);
-					}
-				}
-			}
-		});
+	// Free all scheduled cores and push back their assignments, with the retries of parathread
+	// claims incremented.
+	pub(crate) fn clear() {
+		let context = AssignmentContext::load();
+		for core_assignment in Scheduled::<T>::take() {
+			let kind = match core_assignment.kind {
+				AssignmentKind::Parachain => AssignmentKind::Parachain,
+				AssignmentKind::Parathread(collator, retries) =>
+					AssignmentKind::Parathread(collator, retries + 1),
+			};
+
+			T::AssignmentProvider::push_back_assignment(
+				&context,
+				core_assignment.core,
+				Assignment { para_id: core_assignment.para_id, kind },
+			);
+		}
 	}
 }
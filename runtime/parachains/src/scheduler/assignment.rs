@@ -0,0 +1,391 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Providers of the assignments of paras to availability cores.
+//!
+//! The scheduler asks its [`AssignmentProvider`] for the next assignment of every free core. The
+//! provider shipped by default is [`DefaultAssignmentProvider`], which assigns one core to every
+//! lease holding parachain with [`LeaseAssignmentProvider`] and multiplexes the remaining
+//! `parathread_cores` with [`OnDemandAssignmentProvider`].
+
+use sp_std::marker::PhantomData;
+
+use super::*;
+use crate::configuration::HostConfiguration;
+
+/// A para assigned to a core by an [`AssignmentProvider`].
+#[derive(Clone, Encode, Decode, TypeInfo)]
+#[cfg_attr(feature = "std", derive(PartialEq, Debug))]
+pub struct Assignment {
+	/// The para which is assigned.
+	pub para_id: ParaId,
+	/// The kind of the assignment.
+	pub kind: AssignmentKind,
+}
+
+impl Assignment {
+	/// The assignment of a core occupied by `occupied`, if the para is known.
+	pub(crate) fn from_occupied(occupied: CoreOccupied, para_id: Option<ParaId>) -> Option<Self> {
+		match occupied {
+			CoreOccupied::Parachain =>
+				para_id.map(|para_id| Assignment { para_id, kind: AssignmentKind::Parachain }),
+			CoreOccupied::Parathread(entry) => Some(entry.into()),
+		}
+	}
+}
+
+impl From<ParathreadEntry> for Assignment {
+	fn from(entry: ParathreadEntry) -> Self {
+		Assignment {
+			para_id: entry.claim.0,
+			kind: AssignmentKind::Parathread(entry.claim.1, entry.retries),
+		}
+	}
+}
+
+/// The state of the relay chain the providers assign cores based on.
+///
+/// The scheduler reads it once and passes it to every call of a provider in the same block,
+/// instead of each call reading it again for every core.
+pub struct AssignmentContext<T: Config> {
+	/// The active configuration.
+	pub config: HostConfiguration<T::BlockNumber>,
+	/// The parachains holding a lease, see [`paras::Pallet::parachains`].
+	pub parachains: Vec<ParaId>,
+	/// The [`ElasticCores`] of the session.
+	pub elastic_cores: Vec<ParaId>,
+}
+
+impl<T: Config> AssignmentContext<T> {
+	/// Read the context of the current block.
+	pub fn load() -> Self {
+		Self::with_config(<configuration::Pallet<T>>::config())
+	}
+
+	/// Read the context of the current block, with the given configuration.
+	pub(crate) fn with_config(config: HostConfiguration<T::BlockNumber>) -> Self {
+		AssignmentContext {
+			config,
+			parachains: <paras::Pallet<T>>::parachains(),
+			elastic_cores: ElasticCores::<T>::get(),
+		}
+	}
+}
+
+/// Assigns paras to availability cores.
+///
+/// Cores are numbered from 0 for every provider, [`CoreSplit`] combines providers by giving each
+/// a range of the availability cores.
+///
+/// The scheduler pops an assignment from the provider for every free core. Once the candidate of
+/// the assignment became available, the assignment is reported as processed. Assignments which
+/// were not processed are pushed back.
+pub trait AssignmentProvider<T: Config> {
+	/// The number of cores the provider assigns paras to in a session with the given context.
+	///
+	/// Must not change within a session. It is first queried after
+	/// [`AssignmentProvider::on_new_session`].
+	fn session_core_count(context: &AssignmentContext<T>) -> u32;
+
+	/// Called on session change.
+	///
+	/// `dropped` are the assignments which occupied a core when the session ended. The cores are
+	/// cleared on session change, so providers should take back the ones they made and drop all
+	/// others.
+	fn on_new_session(config: &HostConfiguration<T::BlockNumber>, dropped: &[Assignment]);
+
+	/// Take the next assignment of the given core, if any.
+	fn pop_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment>;
+
+	/// Get the next assignment of the given core without taking it, if any.
+	fn peek_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment>;
+
+	/// Note that the candidate of an assignment popped for the given core became available.
+	fn report_processed(context: &AssignmentContext<T>, core: CoreIndex, assignment: Assignment);
+
+	/// Hand back an assignment popped for the given core which was not processed in the same
+	/// session.
+	///
+	/// This happens if its candidate timed out or if no candidate was backed while the assignment
+	/// was scheduled. In the latter case the retries of a parathread assignment are already
+	/// incremented.
+	fn push_back_assignment(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+		assignment: Assignment,
+	);
+}
+
+/// Assigns the core of the same index to each parachain, in the order of
+/// [`paras::Pallet::parachains`], followed by the additional cores of the parachains assigned
+/// more than one core with [`Pallet::set_parachain_cores`].
+///
+/// These are the parachains holding a lease, which never need to claim a core.
+pub struct LeaseAssignmentProvider<T>(PhantomData<T>);
+
+impl<T: Config> AssignmentProvider<T> for LeaseAssignmentProvider<T> {
+	fn session_core_count(context: &AssignmentContext<T>) -> u32 {
+		(context.parachains.len() + context.elastic_cores.len()) as u32
+	}
+
+	fn on_new_session(_config: &HostConfiguration<T::BlockNumber>, _dropped: &[Assignment]) {
+		let elastic_cores = <paras::Pallet<T>>::parachains()
+			.into_iter()
+			.flat_map(|para_id| {
+				let cores = ParachainCores::<T>::get(para_id).unwrap_or(1);
+				sp_std::iter::repeat(para_id).take(cores.saturating_sub(1) as usize)
+			})
+			.collect();
+		ElasticCores::<T>::set(elastic_cores);
+	}
+
+	fn pop_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		Self::peek_assignment_for_core(context, core)
+	}
+
+	fn peek_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		let para_id = match context.parachains.get(core.0 as usize) {
+			Some(para_id) => *para_id,
+			None => *context.elastic_cores.get(core.0 as usize - context.parachains.len())?,
+		};
+
+		Some(Assignment { para_id, kind: AssignmentKind::Parachain })
+	}
+
+	fn report_processed(
+		_context: &AssignmentContext<T>,
+		_core: CoreIndex,
+		_assignment: Assignment,
+	) {
+	}
+
+	fn push_back_assignment(
+		_context: &AssignmentContext<T>,
+		_core: CoreIndex,
+		_assignment: Assignment,
+	) {
+	}
+}
+
+/// Assigns the parathread claims of the [`ParathreadQueue`] to `parathread_cores` cores.
+///
+/// Claims are added with [`Pallet::add_parathread_claim`]. A claim which was not backed while it
+/// was scheduled is retried up to `parathread_retries` times.
+pub struct OnDemandAssignmentProvider<T>(PhantomData<T>);
+
+impl<T: Config> OnDemandAssignmentProvider<T> {
+	/// Remove the claim on the parathread from the index, allowing new claims.
+	fn remove_from_index(para_id: ParaId) {
+		ParathreadClaimIndex::<T>::mutate(|index| {
+			if let Ok(i) = index.binary_search(&para_id) {
+				index.remove(i);
+			}
+		})
+	}
+}
+
+impl<T: Config> AssignmentProvider<T> for OnDemandAssignmentProvider<T> {
+	fn session_core_count(context: &AssignmentContext<T>) -> u32 {
+		context.config.parathread_cores
+	}
+
+	fn on_new_session(config: &HostConfiguration<T::BlockNumber>, dropped: &[Assignment]) {
+		let mut thread_queue = ParathreadQueue::<T>::get();
+
+		// put the claims which occupied a core back on the queue.
+		for assignment in dropped {
+			if let AssignmentKind::Parathread(ref collator, retries) = assignment.kind {
+				let claim = ParathreadEntry {
+					claim: ParathreadClaim(assignment.para_id, collator.clone()),
+					retries,
+				};
+				thread_queue.queue.push(QueuedParathread {
+					claim,
+					core_offset: 0, // this gets set later in the re-balancing.
+				});
+			}
+		}
+
+		// prune out all parathread claims with too many retries.
+		// assign all non-pruned claims to new cores, if they've changed.
+		ParathreadClaimIndex::<T>::mutate(|claim_index| {
+			// wipe all parathread metadata if no parathread cores are configured.
+			if config.parathread_cores == 0 {
+				thread_queue = ParathreadClaimQueue { queue: Vec::new(), next_core_offset: 0 };
+				claim_index.clear();
+				return
+			}
+
+			// prune out all entries beyond retry or that no longer correspond to live parathread.
+			thread_queue.queue.retain(|queued| {
+				let will_keep = queued.claim.retries <= config.parathread_retries &&
+					<paras::Pallet<T>>::is_parathread(queued.claim.claim.0);
+
+				if !will_keep {
+					let claim_para = queued.claim.claim.0;
+
+					// clean up the pruned entry from the index.
+					if let Ok(i) = claim_index.binary_search(&claim_para) {
+						claim_index.remove(i);
+					}
+				}
+
+				will_keep
+			});
+
+			// do re-balancing of claims.
+			{
+				for (i, queued) in thread_queue.queue.iter_mut().enumerate() {
+					queued.core_offset = (i as u32) % config.parathread_cores;
+				}
+
+				thread_queue.next_core_offset =
+					((thread_queue.queue.len()) as u32) % config.parathread_cores;
+			}
+		});
+		ParathreadQueue::<T>::set(thread_queue);
+	}
+
+	fn pop_assignment_for_core(
+		_context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		ParathreadQueue::<T>::mutate(|queue| queue.take_next_on_core(core.0)).map(Into::into)
+	}
+
+	fn peek_assignment_for_core(
+		_context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		ParathreadQueue::<T>::get().get_next_on_core(core.0).cloned().map(Into::into)
+	}
+
+	fn report_processed(_context: &AssignmentContext<T>, _core: CoreIndex, assignment: Assignment) {
+		// After a parathread candidate has successfully been included,
+		// open it up for further claims!
+		Self::remove_from_index(assignment.para_id);
+	}
+
+	fn push_back_assignment(
+		context: &AssignmentContext<T>,
+		_core: CoreIndex,
+		assignment: Assignment,
+	) {
+		let (collator, retries) = match assignment.kind {
+			AssignmentKind::Parathread(collator, retries) => (collator, retries),
+			AssignmentKind::Parachain => return,
+		};
+
+		let config = &context.config;
+		if config.parathread_cores == 0 ||
+			retries > config.parathread_retries ||
+			!<paras::Pallet<T>>::is_parathread(assignment.para_id)
+		{
+			Self::remove_from_index(assignment.para_id);
+			return
+		}
+
+		let entry =
+			ParathreadEntry { claim: ParathreadClaim(assignment.para_id, collator), retries };
+		ParathreadQueue::<T>::mutate(|queue| {
+			queue.enqueue_entry(entry, config.parathread_cores);
+		})
+	}
+}
+
+/// Assigns the first [`AssignmentProvider::session_core_count`] cores with `First` and the
+/// following ones with `Second`.
+///
+/// The assignments dropped on session change are passed to both providers.
+pub struct CoreSplit<First, Second>(PhantomData<(First, Second)>);
+
+/// The core of `Second` if the given core is not one of `First`.
+fn second_core<T: Config, First: AssignmentProvider<T>>(
+	context: &AssignmentContext<T>,
+	core: CoreIndex,
+) -> Option<CoreIndex> {
+	core.0.checked_sub(First::session_core_count(context)).map(CoreIndex)
+}
+
+impl<T, First, Second> AssignmentProvider<T> for CoreSplit<First, Second>
+where
+	T: Config,
+	First: AssignmentProvider<T>,
+	Second: AssignmentProvider<T>,
+{
+	fn session_core_count(context: &AssignmentContext<T>) -> u32 {
+		First::session_core_count(context).saturating_add(Second::session_core_count(context))
+	}
+
+	fn on_new_session(config: &HostConfiguration<T::BlockNumber>, dropped: &[Assignment]) {
+		First::on_new_session(config, dropped);
+		Second::on_new_session(config, dropped);
+	}
+
+	fn pop_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		match second_core::<T, First>(context, core) {
+			None => First::pop_assignment_for_core(context, core),
+			Some(core) => Second::pop_assignment_for_core(context, core),
+		}
+	}
+
+	fn peek_assignment_for_core(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+	) -> Option<Assignment> {
+		match second_core::<T, First>(context, core) {
+			None => First::peek_assignment_for_core(context, core),
+			Some(core) => Second::peek_assignment_for_core(context, core),
+		}
+	}
+
+	fn report_processed(context: &AssignmentContext<T>, core: CoreIndex, assignment: Assignment) {
+		match second_core::<T, First>(context, core) {
+			None => First::report_processed(context, core, assignment),
+			Some(core) => Second::report_processed(context, core, assignment),
+		}
+	}
+
+	fn push_back_assignment(
+		context: &AssignmentContext<T>,
+		core: CoreIndex,
+		assignment: Assignment,
+	) {
+		match second_core::<T, First>(context, core) {
+			None => First::push_back_assignment(context, core, assignment),
+			Some(core) => Second::push_back_assignment(context, core, assignment),
+		}
+	}
+}
+
+/// The lease holding parachains followed by the on-demand parathread cores.
+pub type DefaultAssignmentProvider<T> =
+	CoreSplit<LeaseAssignmentProvider<T>, OnDemandAssignmentProvider<T>>;
@@ -0,0 +1,50 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+use super::*;
+use frame_benchmarking::{benchmarks, impl_benchmark_test_suite};
+use frame_system::RawOrigin;
+use primitives::ValidatorId;
+use sp_core::sr25519;
+
+/// The number of active validators the number of cores is checked against. Decoding them
+/// dominates the weight of the call.
+const MAX_VALIDATORS: u32 = 1_000;
+
+fn validator(index: u32) -> ValidatorId {
+	let mut raw = [0u8; 32];
+	raw[..4].copy_from_slice(&index.to_le_bytes());
+	sr25519::Public::from_raw(raw).into()
+}
+
+benchmarks! {
+	set_parachain_cores {
+		shared::Pallet::<T>::set_active_validators_ascending(
+			(0..MAX_VALIDATORS).map(validator).collect(),
+		);
+		let para_id = ParaId::from(1000);
+		ParachainCores::<T>::insert(para_id, 2);
+	}: _(RawOrigin::Root, para_id, 3)
+	verify {
+		assert_eq!(ParachainCores::<T>::get(para_id), Some(3));
+	}
+}
+
+impl_benchmark_test_suite!(
+	Pallet,
+	crate::mock::new_test_ext(Default::default()),
+	crate::mock::Test
+);
@@ -16,7 +16,7 @@
 
 use super::*;
 
-use frame_support::assert_ok;
+use frame_support::{assert_noop, assert_ok};
 use keyring::Sr25519Keyring;
 use primitives::{BlockNumber, CollatorId, SessionIndex, ValidationCode, ValidatorId};
 
@@ -135,7 +135,10 @@ fn add_parathread_claim_works() {
 		assert!(Paras::is_parathread(thread_id));
 
 		{
-			Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator.clone()));
+			assert_eq!(
+				Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator.clone())),
+				ParathreadClaimOutcome::Queued,
+			);
 			let queue = ParathreadQueue::<Test>::get();
 			assert_eq!(queue.next_core_offset, 1);
 			assert_eq!(queue.queue.len(), 1);
@@ -154,7 +157,10 @@ fn add_parathread_claim_works() {
 		// due to the index, completing claims are not allowed.
 		{
 			let collator2 = CollatorId::from(Sr25519Keyring::Bob.public());
-			Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator2.clone()));
+			assert_eq!(
+				Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator2.clone())),
+				ParathreadClaimOutcome::AlreadyClaimed,
+			);
 			let queue = ParathreadQueue::<Test>::get();
 			assert_eq!(queue.next_core_offset, 1);
 			assert_eq!(queue.queue.len(), 1);
@@ -173,7 +179,10 @@ fn add_parathread_claim_works() {
 		// claims on non-live parathreads have no effect.
 		{
 			let thread_id2 = ParaId::from(11);
-			Scheduler::add_parathread_claim(ParathreadClaim(thread_id2, collator.clone()));
+			assert_eq!(
+				Scheduler::add_parathread_claim(ParathreadClaim(thread_id2, collator.clone())),
+				ParathreadClaimOutcome::NotParathread,
+			);
 			let queue = ParathreadQueue::<Test>::get();
 			assert_eq!(queue.next_core_offset, 1);
 			assert_eq!(queue.queue.len(), 1);
@@ -215,7 +224,10 @@ fn cannot_add_claim_when_no_parathread_cores() {
 
 		assert!(Paras::is_parathread(thread_id));
 
-		Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator.clone()));
+		assert_eq!(
+			Scheduler::add_parathread_claim(ParathreadClaim(thread_id, collator.clone())),
+			ParathreadClaimOutcome::QueueFull,
+		);
 		assert_eq!(ParathreadQueue::<Test>::get(), Default::default());
 	});
 }
@@ -1114,7 +1126,9 @@ fn next_up_on_available_uses_next_scheduled_or_none_for_thread() {
 				_ => panic!("with no chains, only core should be a thread core"),
 			}
 
-			assert!(Scheduler::next_up_on_available(CoreIndex(0)).is_none());
+			assert!(
+				Scheduler::next_up_on_available(&AssignmentContext::load(), CoreIndex(0)).is_none()
+			);
 
 			Scheduler::add_parathread_claim(thread_claim_b);
 
@@ -1125,7 +1139,7 @@ fn next_up_on_available_uses_next_scheduled_or_none_for_thread() {
 			);
 
 			assert_eq!(
-				Scheduler::next_up_on_available(CoreIndex(0)).unwrap(),
+				Scheduler::next_up_on_available(&AssignmentContext::load(), CoreIndex(0)).unwrap(),
 				ScheduledCore { para_id: thread_b, collator: Some(collator.clone()) }
 			);
 		}
@@ -1189,7 +1203,7 @@ fn next_up_on_time_out_reuses_claim_if_nothing_queued() {
 			let queue = ParathreadQueue::<Test>::get();
 			assert!(queue.get_next_on_core(0).is_none());
 			assert_eq!(
-				Scheduler::next_up_on_time_out(CoreIndex(0)).unwrap(),
+				Scheduler::next_up_on_time_out(&AssignmentContext::load(), CoreIndex(0)).unwrap(),
 				ScheduledCore { para_id: thread_a, collator: Some(collator.clone()) }
 			);
 
@@ -1203,7 +1217,7 @@ fn next_up_on_time_out_reuses_claim_if_nothing_queued() {
 
 			// Now that there is an earlier next-up, we use that.
 			assert_eq!(
-				Scheduler::next_up_on_available(CoreIndex(0)).unwrap(),
+				Scheduler::next_up_on_available(&AssignmentContext::load(), CoreIndex(0)).unwrap(),
 				ScheduledCore { para_id: thread_b, collator: Some(collator.clone()) }
 			);
 		}
@@ -1257,7 +1271,7 @@ fn next_up_on_available_is_parachain_always() {
 
 			// Now that there is an earlier next-up, we use that.
 			assert_eq!(
-				Scheduler::next_up_on_available(CoreIndex(0)).unwrap(),
+				Scheduler::next_up_on_available(&AssignmentContext::load(), CoreIndex(0)).unwrap(),
 				ScheduledCore { para_id: chain_a, collator: None }
 			);
 		}
@@ -1311,7 +1325,7 @@ fn next_up_on_time_out_is_parachain_always() {
 
 			// Now that there is an earlier next-up, we use that.
 			assert_eq!(
-				Scheduler::next_up_on_available(CoreIndex(0)).unwrap(),
+				Scheduler::next_up_on_available(&AssignmentContext::load(), CoreIndex(0)).unwrap(),
 				ScheduledCore { para_id: chain_a, collator: None }
 			);
 		}
@@ -1453,3 +1467,164 @@ fn parathread_claims_are_pruned_after_deregistration() {
 		assert_eq!(Scheduler::scheduled().len(), 1);
 	});
 }
+
+#[test]
+fn default_assignment_provider_assigns_parathread_cores_after_parachain_cores() {
+	let genesis_config = MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	};
+
+	let chain_a = ParaId::from(1_u32);
+	let thread_a = ParaId::from(2_u32);
+
+	let collator = CollatorId::from(Sr25519Keyring::Alice.public());
+
+	new_test_ext(genesis_config).execute_with(|| {
+		schedule_blank_para(chain_a, ParaKind::Parachain);
+		schedule_blank_para(thread_a, ParaKind::Parathread);
+
+		run_to_block(1, |number| match number {
+			1 => Some(SessionChangeNotification {
+				new_config: default_config(),
+				validators: vec![
+					ValidatorId::from(Sr25519Keyring::Alice.public()),
+					ValidatorId::from(Sr25519Keyring::Bob.public()),
+					ValidatorId::from(Sr25519Keyring::Charlie.public()),
+					ValidatorId::from(Sr25519Keyring::Dave.public()),
+				],
+				..Default::default()
+			}),
+			_ => None,
+		});
+
+		let context = AssignmentContext::load();
+		assert_eq!(<DefaultAssignmentProvider<Test>>::session_core_count(&context), 4);
+		assert_eq!(Scheduler::availability_cores().len(), 4);
+
+		Scheduler::add_parathread_claim(ParathreadClaim(thread_a, collator.clone()));
+		assert_eq!(
+			<DefaultAssignmentProvider<Test>>::peek_assignment_for_core(&context, CoreIndex(0)),
+			Some(Assignment { para_id: chain_a, kind: AssignmentKind::Parachain }),
+		);
+		assert_eq!(
+			<DefaultAssignmentProvider<Test>>::peek_assignment_for_core(&context, CoreIndex(1)),
+			Some(Assignment {
+				para_id: thread_a,
+				kind: AssignmentKind::Parathread(collator.clone(), 0),
+			}),
+		);
+		assert_eq!(
+			<DefaultAssignmentProvider<Test>>::peek_assignment_for_core(&context, CoreIndex(2)),
+			None
+		);
+
+		run_to_block(2, |_| None);
+		Scheduler::occupied(&[CoreIndex(0), CoreIndex(1)]);
+
+		// The occupying parachain is recorded, so it is known without consulting the leases.
+		assert_eq!(OccupiedParachains::<Test>::get(CoreIndex(0)), Some(chain_a));
+		assert_eq!(Scheduler::core_para(CoreIndex(0)), Some(chain_a));
+		assert_eq!(Scheduler::core_para(CoreIndex(1)), Some(thread_a));
+
+		// Including the parathread candidate opens the parathread up for further claims.
+		Scheduler::free_cores(vec![
+			(CoreIndex(0), FreedReason::Concluded),
+			(CoreIndex(1), FreedReason::Concluded),
+		]);
+		assert!(OccupiedParachains::<Test>::get(CoreIndex(0)).is_none());
+		assert!(ParathreadClaimIndex::<Test>::get().is_empty());
+	});
+}
+
+#[test]
+fn parachain_cores_apply_from_next_session() {
+	let genesis_config = MockGenesisConfig {
+		configuration: crate::configuration::GenesisConfig {
+			config: default_config(),
+			..Default::default()
+		},
+		..Default::default()
+	};
+
+	let chain_a = ParaId::from(1_u32);
+	let chain_b = ParaId::from(2_u32);
+
+	let validators = vec![
+		ValidatorId::from(Sr25519Keyring::Alice.public()),
+		ValidatorId::from(Sr25519Keyring::Bob.public()),
+		ValidatorId::from(Sr25519Keyring::Charlie.public()),
+		ValidatorId::from(Sr25519Keyring::Dave.public()),
+	];
+	let new_session = || SessionChangeNotification {
+		new_config: default_config(),
+		validators: validators.clone(),
+		..Default::default()
+	};
+
+	new_test_ext(genesis_config).execute_with(|| {
+		schedule_blank_para(chain_a, ParaKind::Parachain);
+		schedule_blank_para(chain_b, ParaKind::Parachain);
+
+		run_to_block(1, |number| match number {
+			1 => Some(new_session()),
+			_ => None,
+		});
+
+		ParasShared::set_active_validators_ascending(validators.clone());
+
+		assert_noop!(
+			Scheduler::set_parachain_cores(RuntimeOrigin::signed(1), chain_a, 2),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Scheduler::set_parachain_cores(RuntimeOrigin::root(), chain_a, 0),
+			Error::<Test>::ZeroCores,
+		);
+		assert_noop!(
+			Scheduler::set_parachain_cores(RuntimeOrigin::root(), chain_a, 5),
+			Error::<Test>::TooManyCores,
+		);
+		assert_ok!(Scheduler::set_parachain_cores(RuntimeOrigin::root(), chain_a, 3));
+
+		// the additional cores are only assigned once the session changes.
+		assert_eq!(Scheduler::availability_cores().len(), 5);
+
+		run_to_block(2, |number| match number {
+			2 => Some(new_session()),
+			_ => None,
+		});
+
+		assert_eq!(ElasticCores::<Test>::get(), vec![chain_a, chain_a]);
+		let context = AssignmentContext::load();
+		assert_eq!(Scheduler::availability_cores().len(), 7);
+		for (core, para_id) in [(0, chain_a), (1, chain_b), (2, chain_a), (3, chain_a)] {
+			assert_eq!(
+				<DefaultAssignmentProvider<Test>>::peek_assignment_for_core(
+					&context,
+					CoreIndex(core)
+				),
+				Some(Assignment { para_id, kind: AssignmentKind::Parachain }),
+			);
+		}
+		assert_eq!(
+			<DefaultAssignmentProvider<Test>>::peek_assignment_for_core(&context, CoreIndex(4)),
+			None
+		);
+
+		// assigning a single core again drops the additional cores.
+		assert_ok!(Scheduler::set_parachain_cores(RuntimeOrigin::root(), chain_a, 1));
+		assert!(ParachainCores::<Test>::get(chain_a).is_none());
+
+		run_to_block(3, |number| match number {
+			3 => Some(new_session()),
+			_ => None,
+		});
+
+		assert!(ElasticCores::<Test>::get().is_empty());
+		assert_eq!(Scheduler::availability_cores().len(), 5);
+	});
+}
@@ -16,12 +16,12 @@
 
 use crate::{
 	configuration::{self, HostConfiguration},
-	initializer,
+	initializer, shared,
 };
 use frame_support::{pallet_prelude::*, traits::EnsureOrigin};
 use frame_system::pallet_prelude::*;
 use polkadot_parachain::primitives::UpwardMessages;
-use primitives::{Id as ParaId, UpwardMessage};
+use primitives::{vstaging::UmpOverweightMessage, Id as ParaId, SessionIndex, UpwardMessage};
 use sp_std::{collections::btree_map::BTreeMap, fmt, marker::PhantomData, mem, prelude::*};
 use xcm::latest::Outcome;
 
@@ -88,6 +88,19 @@ pub type MessageId = [u8; 32];
 /// Index used to identify overweight messages.
 pub type OverweightIndex = u64;
 
+/// The bookkeeping of a message in the overweight queue.
+#[derive(Encode, Decode, TypeInfo, Clone, PartialEq, RuntimeDebug)]
+pub struct OverweightMessageInfo {
+	/// The weight the message required when it was moved to the overweight queue.
+	///
+	/// Zero for messages which were moved to the overweight queue before it was recorded.
+	pub required_weight: Weight,
+	/// The session at the start of which the message is dropped if it was not serviced by then.
+	///
+	/// `None` if expiry was disabled when the message was moved to the overweight queue.
+	pub expires_at: Option<SessionIndex>,
+}
+
 /// A specific implementation of a `UmpSink` where messages are in the XCM format
 /// and will be forwarded to the XCM Executor.
 pub struct XcmSink<XcmExecutor, Config>(PhantomData<(XcmExecutor, Config)>);
@@ -190,6 +203,7 @@ impl fmt::Debug for AcceptanceCheckErr {
 /// Weight information of this pallet.
 pub trait WeightInfo {
 	fn service_overweight() -> Weight;
+	fn service_overweight_batch(n: u32) -> Weight;
 	fn process_upward_message(s: u32) -> Weight;
 	fn clean_ump_after_outgoing() -> Weight;
 }
@@ -201,6 +215,10 @@ impl WeightInfo for TestWeightInfo {
 		Weight::MAX
 	}
 
+	fn service_overweight_batch(_n: u32) -> Weight {
+		Weight::MAX
+	}
+
 	fn process_upward_message(_msg_size: u32) -> Weight {
 		Weight::MAX
 	}
@@ -238,6 +256,13 @@ pub mod pallet {
 		/// Origin which is allowed to execute overweight messages.
 		type ExecuteOverweightOrigin: EnsureOrigin<Self::RuntimeOrigin>;
 
+		/// The number of sessions an overweight message is kept for before it is dropped, counting
+		/// from the session it was moved to the overweight queue in.
+		///
+		/// `0` disables the expiry: overweight messages are kept until they are serviced.
+		#[pallet::constant]
+		type OverweightExpirySessions: Get<SessionIndex>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 	}
@@ -272,6 +297,11 @@ pub mod pallet {
 		///
 		/// \[ overweight_index, used \]
 		OverweightServiced(OverweightIndex, Weight),
+		/// Upward message from the overweight queue was dropped without being executed because it
+		/// was not serviced within `OverweightExpirySessions` sessions.
+		///
+		/// \[ overweight_index \]
+		OverweightExpired(OverweightIndex),
 	}
 
 	#[pallet::error]
@@ -280,6 +310,8 @@ pub mod pallet {
 		UnknownMessageIndex,
 		/// The amount of weight given is possibly not enough for executing the message.
 		WeightOverLimit,
+		/// More message indices were given than overweight messages can exist.
+		TooManyIndices,
 	}
 
 	/// The messages waiting to be handled by the relay-chain originating from a certain parachain.
@@ -337,6 +369,24 @@ pub mod pallet {
 	#[pallet::storage]
 	pub type OverweightCount<T: Config> = StorageValue<_, OverweightIndex, ValueQuery>;
 
+	/// The required weight and the expiry of the messages in `Overweight`.
+	///
+	/// Invariant:
+	/// - The set of keys should be a subset of the keys of `Overweight`. Messages which were moved
+	///   to the overweight queue before this was introduced have no info and never expire.
+	#[pallet::storage]
+	pub type OverweightInfo<T: Config> =
+		StorageMap<_, Twox64Concat, OverweightIndex, OverweightMessageInfo>;
+
+	/// The indices of the overweight messages to be dropped at the start of a session.
+	///
+	/// Servicing a message doesn't remove its index from here, as that would need to read and
+	/// rewrite the whole list. Messages which were serviced in the meantime are skipped when the
+	/// entry is due, and the weight of reading them is accounted for at that point.
+	#[pallet::storage]
+	pub type OverweightExpiries<T: Config> =
+		StorageMap<_, Twox64Concat, SessionIndex, Vec<OverweightIndex>, ValueQuery>;
+
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		/// Service a single overweight upward message.
@@ -364,10 +414,55 @@ pub mod pallet {
 				Overweight::<T>::get(index).ok_or(Error::<T>::UnknownMessageIndex)?;
 			let used = T::UmpSink::process_upward_message(sender, &data[..], weight_limit)
 				.map_err(|_| Error::<T>::WeightOverLimit)?;
-			Overweight::<T>::remove(index);
+			Self::remove_overweight(index);
 			Self::deposit_event(Event::OverweightServiced(index, used));
 			Ok(Some(used.saturating_add(<T as Config>::WeightInfo::service_overweight())).into())
 		}
+
+		/// Service several overweight upward messages, sharing a single weight budget.
+		///
+		/// The messages are executed in the given order. Unknown indices and messages which
+		/// need more than the remaining weight are skipped and stay in the overweight queue.
+		///
+		/// - `origin`: Must pass `ExecuteOverweightOrigin`.
+		/// - `indices`: The indices of the overweight messages to service.
+		/// - `weight_limit`: The amount of weight that the execution of all messages may take.
+		///
+		/// Errors:
+		/// - `TooManyIndices`: More than `MAX_OVERWEIGHT_MESSAGES` indices were given.
+		///
+		/// Events:
+		/// - `OverweightServiced`: For each serviced message.
+		#[pallet::call_index(1)]
+		#[pallet::weight(weight_limit.saturating_add(
+			<T as Config>::WeightInfo::service_overweight_batch(indices.len() as u32)
+		))]
+		pub fn service_overweight_batch(
+			origin: OriginFor<T>,
+			indices: Vec<OverweightIndex>,
+			weight_limit: Weight,
+		) -> DispatchResultWithPostInfo {
+			T::ExecuteOverweightOrigin::ensure_origin(origin)?;
+			ensure!(indices.len() as u32 <= MAX_OVERWEIGHT_MESSAGES, Error::<T>::TooManyIndices);
+
+			let count = indices.len() as u32;
+			let mut used = Weight::zero();
+			for index in indices {
+				let (sender, data) = match Overweight::<T>::get(index) {
+					Some(message) => message,
+					None => continue,
+				};
+				let remaining = weight_limit.saturating_sub(used);
+				if let Ok(weight) = T::UmpSink::process_upward_message(sender, &data[..], remaining)
+				{
+					used.saturating_accrue(weight);
+					Self::remove_overweight(index);
+					Self::deposit_event(Event::OverweightServiced(index, weight));
+				}
+			}
+			let weight = <T as Config>::WeightInfo::service_overweight_batch(count);
+			Ok(Some(used.saturating_add(weight)).into())
+		}
 	}
 }
 
@@ -383,10 +478,34 @@ impl<T: Config> Pallet<T> {
 
 	/// Called by the initializer to note that a new session has started.
 	pub(crate) fn initializer_on_new_session(
-		_notification: &initializer::SessionChangeNotification<T::BlockNumber>,
+		notification: &initializer::SessionChangeNotification<T::BlockNumber>,
 		outgoing_paras: &[ParaId],
 	) -> Weight {
-		Self::perform_outgoing_para_cleanup(outgoing_paras)
+		let weight = Self::perform_outgoing_para_cleanup(outgoing_paras);
+		weight.saturating_add(Self::expire_overweight_messages(notification.session_index))
+	}
+
+	/// Drop the overweight messages which were due to expire at the start of the given session.
+	///
+	/// The messages are dropped without being executed and nothing is refunded to their senders:
+	/// the relay chain holds no deposit for a stashed message and can't tell what the message
+	/// would have done. Any assets that a message would have moved are lost, so senders have to
+	/// get their messages serviced before they expire.
+	///
+	/// The indices of the messages serviced in the meantime are still part of the expiring entry,
+	/// so they are read and weighed here.
+	fn expire_overweight_messages(session_index: SessionIndex) -> Weight {
+		let expiring = OverweightExpiries::<T>::take(session_index);
+		let mut expired = 0u64;
+		for index in &expiring {
+			if Overweight::<T>::contains_key(index) {
+				Self::remove_overweight(*index);
+				Self::deposit_event(Event::OverweightExpired(*index));
+				expired += 1;
+			}
+		}
+
+		T::DbWeight::get().reads_writes(1 + expiring.len() as u64 + expired, 1 + 3 * expired)
 	}
 
 	/// Iterate over all paras that were noted for offboarding and remove all the data
@@ -425,6 +544,18 @@ impl<T: Config> Pallet<T> {
 		config: &HostConfiguration<T::BlockNumber>,
 		para: ParaId,
 		upward_messages: &[UpwardMessage],
+	) -> Result<(), AcceptanceCheckErr> {
+		Self::check_upward_messages_after(config, para, (0, 0), upward_messages)
+	}
+
+	/// Check that all the upward messages sent by a candidate building on candidates pending
+	/// availability pass the acceptance criteria. The candidates before it send `pending`
+	/// messages, given as their count and total size, which are not enqueued yet.
+	pub(crate) fn check_upward_messages_after(
+		config: &HostConfiguration<T::BlockNumber>,
+		para: ParaId,
+		pending: (u32, u32),
+		upward_messages: &[UpwardMessage],
 	) -> Result<(), AcceptanceCheckErr> {
 		if upward_messages.len() as u32 > config.max_upward_message_num_per_candidate {
 			return Err(AcceptanceCheckErr::MoreMessagesThanPermitted {
@@ -433,7 +564,10 @@ impl<T: Config> Pallet<T> {
 			})
 		}
 
-		let (mut para_queue_count, mut para_queue_size) = RelayDispatchQueueSize::<T>::get(&para);
+		let (queue_count, queue_size) = RelayDispatchQueueSize::<T>::get(&para);
+		let (pending_count, pending_size) = pending;
+		let mut para_queue_count = queue_count.saturating_add(pending_count);
+		let mut para_queue_size = queue_size.saturating_add(pending_size);
 
 		for (idx, msg) in upward_messages.into_iter().enumerate() {
 			let msg_size = msg.len() as u32;
@@ -552,7 +686,8 @@ impl<T: Config> Pallet<T> {
 								if we get into this branch then `peek_front` returned `Some`;\
 								thus `upward_message` cannot be `None`; qed",
 							);
-							let index = Self::stash_overweight(dispatchee, upward_message);
+							let index =
+								Self::stash_overweight(dispatchee, upward_message, required);
 							Self::deposit_event(Event::OverweightEnqueued(
 								dispatchee, id, index, required,
 							));
@@ -581,17 +716,67 @@ impl<T: Config> Pallet<T> {
 	}
 
 	/// Puts a given upward message into the list of overweight messages allowing it to be executed
-	/// later.
-	fn stash_overweight(sender: ParaId, upward_message: Vec<u8>) -> OverweightIndex {
+	/// later, until it expires.
+	fn stash_overweight(
+		sender: ParaId,
+		upward_message: Vec<u8>,
+		required_weight: Weight,
+	) -> OverweightIndex {
 		let index = OverweightCount::<T>::mutate(|count| {
 			let index = *count;
 			*count += 1;
 			index
 		});
 
+		let expires_at = Self::overweight_expiry(shared::Pallet::<T>::session_index());
 		Overweight::<T>::insert(index, (sender, upward_message));
+		OverweightInfo::<T>::insert(index, OverweightMessageInfo { required_weight, expires_at });
+		if let Some(expires_at) = expires_at {
+			OverweightExpiries::<T>::append(expires_at, index);
+		}
 		index
 	}
+
+	/// The session at the start of which an overweight message stashed in the given session
+	/// expires, if expiry is enabled.
+	fn overweight_expiry(session_index: SessionIndex) -> Option<SessionIndex> {
+		match T::OverweightExpirySessions::get() {
+			0 => None,
+			sessions => Some(session_index.saturating_add(sessions)),
+		}
+	}
+
+	/// Removes a message from the overweight queue.
+	///
+	/// The entry in `OverweightExpiries` is left in place and skipped once it is due, see
+	/// [`Self::expire_overweight_messages`].
+	fn remove_overweight(index: OverweightIndex) {
+		Overweight::<T>::remove(index);
+		OverweightInfo::<T>::remove(index);
+	}
+
+	/// Returns the messages in the overweight queue, grouped by the para which sent them.
+	pub(crate) fn overweight_messages() -> Vec<(ParaId, Vec<UmpOverweightMessage>)> {
+		let mut messages = BTreeMap::<ParaId, Vec<UmpOverweightMessage>>::new();
+		for (index, (sender, data)) in Overweight::<T>::iter() {
+			let info = OverweightInfo::<T>::get(index);
+			let required_weight = info.as_ref().map_or(Weight::zero(), |i| i.required_weight);
+			messages.entry(sender).or_default().push(UmpOverweightMessage {
+				index,
+				size: data.len() as u32,
+				required_weight_ref_time: required_weight.ref_time(),
+				required_weight_proof_size: required_weight.proof_size(),
+				expires_at: info.and_then(|i| i.expires_at),
+			});
+		}
+		messages
+			.into_iter()
+			.map(|(para, mut messages)| {
+				messages.sort_by_key(|m| m.index);
+				(para, messages)
+			})
+			.collect()
+	}
 }
 
 /// To avoid constant fetching, deserializing and serialization the queues are cached.
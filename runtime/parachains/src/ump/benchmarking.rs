@@ -128,6 +128,27 @@ frame_benchmarking::benchmarks! {
 	verify {
 		assert_last_event_type::<T>(Event::OverweightServiced(0, Weight::zero()).into());
 	}
+
+	service_overweight_batch {
+		let n in 1..MAX_OVERWEIGHT_MESSAGES;
+		let para = ParaId::from(1978);
+		// As for `service_overweight`, the execution weight of the messages is accounted for
+		// separately.
+		let msg = create_message_overweight::<T>();
+		for _ in 0..n {
+			Ump::<T>::stash_overweight(para, msg.clone(), Weight::MAX / 2);
+		}
+		// Start with the block number 1. This is needed because should an event be
+		// emitted during the genesis block they will be implicitly wiped.
+		frame_system::Pallet::<T>::set_block_number(1u32.into());
+		let indices = (0..n as OverweightIndex).collect::<Vec<_>>();
+	}: _(RawOrigin::Root, indices, Weight::MAX)
+	verify {
+		assert_eq!(Overweight::<T>::count(), 0);
+		assert_last_event_type::<T>(
+			Event::OverweightServiced(n as OverweightIndex - 1, Weight::zero()).into()
+		);
+	}
 }
 
 frame_benchmarking::impl_benchmark_test_suite!(
@@ -20,8 +20,12 @@ use frame_support::{
 	traits::{OnRuntimeUpgrade, StorageVersion},
 	weights::Weight,
 };
+use sp_std::prelude::*;
 
-pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+#[cfg(feature = "try-runtime")]
+use crate::ump::{OverweightExpiries, OverweightIndex, OverweightInfo};
+
+pub const STORAGE_VERSION: StorageVersion = StorageVersion::new(2);
 
 pub mod v1 {
 	use super::*;
@@ -47,3 +51,61 @@ pub mod v1 {
 		}
 	}
 }
+
+pub mod v2 {
+	use super::*;
+
+	/// Introduces `OverweightInfo` and `OverweightExpiries`.
+	///
+	/// The messages already in the overweight queue are left as they are: their senders never
+	/// agreed to an expiry, so they are kept until they are serviced.
+	pub struct MigrateToV2<T>(sp_std::marker::PhantomData<T>);
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			log::trace!("Running pre_upgrade()");
+
+			ensure!(StorageVersion::get::<Pallet<T>>() == 1, "The migration requires version 1");
+			ensure!(
+				OverweightInfo::<T>::iter_keys().next().is_none() &&
+					OverweightExpiries::<T>::iter_keys().next().is_none(),
+				"No overweight message should have an expiry before the migration"
+			);
+			Ok((Overweight::<T>::count() as OverweightIndex).encode())
+		}
+
+		fn on_runtime_upgrade() -> Weight {
+			if StorageVersion::get::<Pallet<T>>() == 1 {
+				StorageVersion::new(2).put::<Pallet<T>>();
+
+				T::DbWeight::get().reads_writes(1, 1)
+			} else {
+				log::warn!("skipping v2, should be removed");
+				T::DbWeight::get().reads(1)
+			}
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			log::trace!("Running post_upgrade()");
+
+			ensure!(
+				StorageVersion::get::<Pallet<T>>() == STORAGE_VERSION,
+				"Storage version should be 2 after the migration"
+			);
+
+			let count = OverweightIndex::decode(&mut &state[..])
+				.map_err(|_| "The pre-upgrade state should decode")?;
+			ensure!(
+				Overweight::<T>::count() as OverweightIndex == count,
+				"No overweight message should be added or removed"
+			);
+			ensure!(
+				OverweightInfo::<T>::iter_keys().next().is_none() &&
+					OverweightExpiries::<T>::iter_keys().next().is_none(),
+				"The overweight messages should not be scheduled to expire"
+			);
+			Ok(())
+		}
+	}
+}
@@ -17,7 +17,7 @@
 use super::*;
 use crate::mock::{
 	assert_last_event, new_test_ext, take_processed, Configuration, MockGenesisConfig,
-	RuntimeOrigin, System, Test, Ump,
+	RuntimeEvent, RuntimeOrigin, System, Test, Ump, UmpOverweightExpirySessions,
 };
 use frame_support::{assert_noop, assert_ok, weights::Weight};
 use std::collections::HashSet;
@@ -288,6 +288,29 @@ fn verify_relay_dispatch_queue_size_is_externally_accessible() {
 	});
 }
 
+#[test]
+fn upward_messages_of_pending_candidates_count_towards_queue_capacity() {
+	let a = ParaId::from(228);
+	let msgs = vec![vec![2; 8], vec![3; 8]];
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		let config = Configuration::config();
+		queue_upward_msg(a, vec![1; 8]);
+		assert!(Ump::check_upward_messages_after(&config, a, (0, 0), &msgs).is_ok());
+
+		// candidates pending availability sent messages which are not enqueued yet.
+		assert!(matches!(
+			Ump::check_upward_messages_after(&config, a, (2, 16), &msgs),
+			Err(AcceptanceCheckErr::CapacityExceeded { count: 5, limit: 4 }),
+		));
+		assert!(Ump::check_upward_messages_after(&config, a, (1, 40), &msgs).is_ok());
+		assert!(matches!(
+			Ump::check_upward_messages_after(&config, a, (1, 41), &msgs),
+			Err(AcceptanceCheckErr::TotalSizeExceeded { total_size: 65, limit: 64 }),
+		));
+	});
+}
+
 #[test]
 fn service_overweight_unknown() {
 	// This test just makes sure that 0 is not a valid index and we can use it not worrying in
@@ -360,3 +383,217 @@ fn overweight_queue_works() {
 		);
 	});
 }
+
+fn assert_overweight_consistency() {
+	let overweight = Overweight::<Test>::iter_keys().collect::<HashSet<_>>();
+	let info = OverweightInfo::<Test>::iter_keys().collect::<HashSet<_>>();
+	assert!(info.is_subset(&overweight));
+	assert_eq!(Overweight::<Test>::count() as usize, overweight.len());
+}
+
+#[test]
+fn overweight_batch_servicing_works() {
+	let para_a = ParaId::from(2021);
+	let para_b = ParaId::from(2022);
+
+	let msg_0 = (200u32, "msg_0").encode();
+	let msg_1 = (500u32, "msg_1").encode();
+	let msg_2 = (300u32, "msg_2").encode();
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		System::set_block_number(1);
+
+		Ump::stash_overweight(para_a, msg_0.clone(), Weight::from_parts(200, 200));
+		Ump::stash_overweight(para_b, msg_1.clone(), Weight::from_parts(500, 500));
+		Ump::stash_overweight(para_a, msg_2.clone(), Weight::from_parts(300, 300));
+		assert_overweight_consistency();
+
+		assert_noop!(
+			Ump::service_overweight_batch(
+				RuntimeOrigin::signed(1),
+				vec![0],
+				Weight::from_parts(1000, 1000)
+			),
+			sp_runtime::DispatchError::BadOrigin,
+		);
+		assert_noop!(
+			Ump::service_overweight_batch(
+				RuntimeOrigin::root(),
+				vec![0; MAX_OVERWEIGHT_MESSAGES as usize + 1],
+				Weight::from_parts(1000, 1000)
+			),
+			Error::<Test>::TooManyIndices,
+		);
+
+		// the second message doesn't fit into what is left of the budget after the first one
+		// and the unknown index is skipped.
+		assert_ok!(Ump::service_overweight_batch(
+			RuntimeOrigin::root(),
+			vec![0, 1, 2, 7],
+			Weight::from_parts(600, 600),
+		));
+		assert_eq!(take_processed(), vec![(para_a, msg_0), (para_a, msg_2)]);
+		let events = System::events().into_iter().map(|r| r.event).collect::<Vec<_>>();
+		assert_eq!(
+			events[events.len() - 2..],
+			[
+				RuntimeEvent::Ump(Event::OverweightServiced(0, Weight::from_parts(200, 200))),
+				RuntimeEvent::Ump(Event::OverweightServiced(2, Weight::from_parts(300, 300))),
+			],
+		);
+		assert_eq!(Overweight::<Test>::iter_keys().collect::<Vec<_>>(), vec![1]);
+		assert_overweight_consistency();
+
+		assert_ok!(Ump::service_overweight_batch(
+			RuntimeOrigin::root(),
+			vec![1],
+			Weight::from_parts(500, 500),
+		));
+		assert_eq!(take_processed(), vec![(para_b, msg_1)]);
+		assert_eq!(Overweight::<Test>::count(), 0);
+		assert_overweight_consistency();
+	});
+}
+
+#[test]
+fn overweight_messages_expire() {
+	let para_a = ParaId::from(2021);
+	let new_session = |session_index| crate::initializer::SessionChangeNotification {
+		session_index,
+		..Default::default()
+	};
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		System::set_block_number(1);
+		crate::shared::Pallet::<Test>::set_session_index(1);
+
+		Ump::stash_overweight(para_a, (500u32, "msg_0").encode(), Weight::from_parts(500, 500));
+		Ump::stash_overweight(para_a, (500u32, "msg_1").encode(), Weight::from_parts(500, 500));
+		Ump::stash_overweight(para_a, (500u32, "msg_2").encode(), Weight::from_parts(500, 500));
+		assert_eq!(OverweightInfo::<Test>::get(0).unwrap().expires_at, Some(4));
+		assert_eq!(OverweightExpiries::<Test>::get(4), vec![0, 1, 2]);
+
+		// a message serviced in the meantime is not expired.
+		assert_ok!(Ump::service_overweight(RuntimeOrigin::root(), 1, Weight::from_parts(500, 500)));
+
+		Ump::initializer_on_new_session(&new_session(3), &[]);
+		assert_eq!(Overweight::<Test>::count(), 2);
+
+		Ump::initializer_on_new_session(&new_session(4), &[]);
+		assert_eq!(Overweight::<Test>::count(), 0);
+		assert!(OverweightExpiries::<Test>::get(4).is_empty());
+		assert_overweight_consistency();
+		let events = System::events().into_iter().map(|r| r.event).collect::<Vec<_>>();
+		assert_eq!(
+			events[events.len() - 2..],
+			[
+				RuntimeEvent::Ump(Event::OverweightExpired(0)),
+				RuntimeEvent::Ump(Event::OverweightExpired(2))
+			],
+		);
+	});
+}
+
+#[test]
+fn overweight_messages_do_not_expire_if_disabled() {
+	let para_a = ParaId::from(2021);
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		UmpOverweightExpirySessions::set(0);
+		crate::shared::Pallet::<Test>::set_session_index(1);
+
+		Ump::stash_overweight(para_a, (500u32, "msg_0").encode(), Weight::from_parts(500, 500));
+		assert_eq!(OverweightInfo::<Test>::get(0).unwrap().expires_at, None);
+		assert_eq!(OverweightExpiries::<Test>::iter_keys().count(), 0);
+
+		for session_index in 2..10 {
+			Ump::initializer_on_new_session(
+				&crate::initializer::SessionChangeNotification {
+					session_index,
+					..Default::default()
+				},
+				&[],
+			);
+		}
+		assert_eq!(Overweight::<Test>::count(), 1);
+		assert_overweight_consistency();
+	});
+}
+
+#[test]
+fn migrate_to_v2_keeps_the_overweight_messages() {
+	use frame_support::traits::{OnRuntimeUpgrade, StorageVersion};
+
+	let para_a = ParaId::from(2021);
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		crate::shared::Pallet::<Test>::set_session_index(5);
+		StorageVersion::new(1).put::<Ump>();
+		// Version 1 keeps only the messages themselves.
+		Overweight::<Test>::insert(0, (para_a, (500u32, "msg_0").encode()));
+		Overweight::<Test>::insert(1, (para_a, (500u32, "msg_1").encode()));
+		OverweightCount::<Test>::put(2);
+
+		migration::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+
+		assert_eq!(StorageVersion::get::<Ump>(), 2);
+		assert_overweight_consistency();
+		assert_eq!(OverweightInfo::<Test>::iter_keys().count(), 0);
+		assert_eq!(OverweightExpiries::<Test>::iter_keys().count(), 0);
+
+		// The migrated messages never expire, but can still be serviced.
+		Ump::initializer_on_new_session(
+			&crate::initializer::SessionChangeNotification {
+				session_index: 8,
+				..Default::default()
+			},
+			&[],
+		);
+		assert_eq!(Overweight::<Test>::count(), 2);
+		assert_ok!(Ump::service_overweight(RuntimeOrigin::root(), 1, Weight::from_parts(500, 500)));
+		assert_eq!(Overweight::<Test>::count(), 1);
+		assert_overweight_consistency();
+		assert_eq!(
+			Ump::overweight_messages(),
+			vec![(
+				para_a,
+				vec![UmpOverweightMessage {
+					index: 0,
+					size: (500u32, "msg_0").encode().len() as u32,
+					required_weight_ref_time: 0,
+					required_weight_proof_size: 0,
+					expires_at: None,
+				}]
+			)],
+		);
+	});
+}
+
+#[test]
+fn overweight_messages_are_listed_per_para() {
+	let para_a = ParaId::from(2021);
+	let para_b = ParaId::from(2022);
+
+	new_test_ext(GenesisConfigBuilder::default().build()).execute_with(|| {
+		assert!(Ump::overweight_messages().is_empty());
+
+		Ump::stash_overweight(para_b, vec![0; 3], Weight::from_parts(300, 30));
+		Ump::stash_overweight(para_a, vec![0; 5], Weight::from_parts(500, 50));
+		Ump::stash_overweight(para_b, vec![0; 7], Weight::from_parts(700, 70));
+
+		let message = |index, size: u32, weight: u64| UmpOverweightMessage {
+			index,
+			size,
+			required_weight_ref_time: weight,
+			required_weight_proof_size: weight / 10,
+			expires_at: Some(3),
+		};
+		assert_eq!(
+			Ump::overweight_messages(),
+			vec![
+				(para_a, vec![message(1, 5, 500)]),
+				(para_b, vec![message(0, 3, 300), message(2, 7, 700)]),
+			],
+		);
+	});
+}
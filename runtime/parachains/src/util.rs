@@ -17,7 +17,7 @@
 //! Utilities that don't belong to any particular module but may draw
 //! on all modules.
 
-use primitives::{Id as ParaId, PersistedValidationData, ValidatorIndex};
+use primitives::{HeadData, Id as ParaId, PersistedValidationData, ValidatorIndex};
 use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
 
 use crate::{configuration, hrmp, paras};
@@ -31,14 +31,31 @@ pub fn make_persisted_validation_data<T: paras::Config + hrmp::Config>(
 	relay_parent_number: T::BlockNumber,
 	relay_parent_storage_root: T::Hash,
 ) -> Option<PersistedValidationData<T::Hash, T::BlockNumber>> {
+	Some(make_persisted_validation_data_with_parent::<T>(
+		<paras::Pallet<T>>::para_head(&para_id)?,
+		relay_parent_number,
+		relay_parent_storage_root,
+	))
+}
+
+/// Make the persisted validation data for a candidate building on the given parent head, a
+/// specified relay-parent and it's storage root.
+///
+/// The parent head differs from the current head of the para if the candidate builds on
+/// candidates pending availability.
+pub fn make_persisted_validation_data_with_parent<T: configuration::Config>(
+	parent_head: HeadData,
+	relay_parent_number: T::BlockNumber,
+	relay_parent_storage_root: T::Hash,
+) -> PersistedValidationData<T::Hash, T::BlockNumber> {
 	let config = <configuration::Pallet<T>>::config();
 
-	Some(PersistedValidationData {
-		parent_head: <paras::Pallet<T>>::para_head(&para_id)?,
+	PersistedValidationData {
+		parent_head,
 		relay_parent_number,
 		relay_parent_storage_root,
 		max_pov_size: config.max_pov_size,
-	})
+	}
 }
 
 /// Take an active subset of a set containing all validators.
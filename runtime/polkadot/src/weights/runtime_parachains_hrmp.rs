@@ -21,6 +21,11 @@
 //! WORST CASE MAP SIZE: `1000000`
 //! HOSTNAME: `bm5`, CPU: `Intel(R) Core(TM) i7-7700K CPU @ 4.20GHz`
 //! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("polkadot-dev"), DB CACHE: 1024
+//!
+//! `hrmp_request_channel_resize`, `hrmp_accept_channel_resize`, `hrmp_cancel_channel_resize`,
+//! `process_hrmp_resize_requests` and `establish_system_channel` are NOT benchmark results yet.
+//! Their storage accesses are taken from the benchmarks, the execution times are conservative
+//! estimates. Regenerate this file with the command below before relying on them.
 
 // Executed Command:
 // ./target/production/polkadot
@@ -289,4 +294,101 @@ impl<T: frame_system::Config> runtime_parachains::hrmp::WeightInfo for WeightInf
 			.saturating_add(T::DbWeight::get().reads(14))
 			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	/// Storage: Hrmp HrmpChannels (r:1 w:0)
+	/// Proof Skipped: Hrmp HrmpChannels (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpCloseChannelRequests (r:1 w:0)
+	/// Proof Skipped: Hrmp HrmpCloseChannelRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannelResizeRequests (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Configuration ActiveConfig (r:1 w:0)
+	/// Proof Skipped: Configuration ActiveConfig (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Hrmp HrmpChannelResizeRequestsList (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequestsList (max_values: Some(1), max_size: None, mode: Measured)
+	fn hrmp_request_channel_resize() -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(45_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 8000))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Storage: Hrmp HrmpChannelResizeRequests (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannels (r:1 w:0)
+	/// Proof Skipped: Hrmp HrmpChannels (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Configuration ActiveConfig (r:1 w:0)
+	/// Proof Skipped: Configuration ActiveConfig (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn hrmp_accept_channel_resize() -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(40_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 8000))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	/// Storage: Hrmp HrmpChannelResizeRequests (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannelResizeRequestsList (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequestsList (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// The range of component `c` is `[0, 128]`.
+	fn hrmp_cancel_channel_resize(c: u32, ) -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(35_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 8000))
+			.saturating_add(Weight::from_parts(100_000, 0).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(Weight::from_parts(0, 15).saturating_mul(c.into()))
+	}
+	/// Storage: Hrmp HrmpChannelResizeRequestsList (r:1 w:1)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequestsList (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannelResizeRequests (r:128 w:128)
+	/// Proof Skipped: Hrmp HrmpChannelResizeRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannels (r:128 w:128)
+	/// Proof Skipped: Hrmp HrmpChannels (max_values: None, max_size: None, mode: Measured)
+	/// The range of component `c` is `[0, 128]`.
+	fn process_hrmp_resize_requests(c: u32, ) -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(10_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 1728))
+			.saturating_add(Weight::from_parts(15_000_000, 0).saturating_mul(c.into()))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(c.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(c.into())))
+			.saturating_add(Weight::from_parts(0, 2600).saturating_mul(c.into()))
+	}
+	/// Storage: Paras ParaLifecycles (r:4 w:0)
+	/// Proof Skipped: Paras ParaLifecycles (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Configuration ActiveConfig (r:2 w:0)
+	/// Proof Skipped: Configuration ActiveConfig (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpOpenChannelRequests (r:2 w:2)
+	/// Proof Skipped: Hrmp HrmpOpenChannelRequests (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpChannels (r:2 w:0)
+	/// Proof Skipped: Hrmp HrmpChannels (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpEgressChannelsIndex (r:2 w:0)
+	/// Proof Skipped: Hrmp HrmpEgressChannelsIndex (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpOpenChannelRequestCount (r:2 w:2)
+	/// Proof Skipped: Hrmp HrmpOpenChannelRequestCount (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpOpenChannelRequestsList (r:2 w:2)
+	/// Proof Skipped: Hrmp HrmpOpenChannelRequestsList (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: Dmp DownwardMessageQueues (r:4 w:4)
+	/// Proof Skipped: Dmp DownwardMessageQueues (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Dmp DownwardMessageQueueHeads (r:4 w:4)
+	/// Proof Skipped: Dmp DownwardMessageQueueHeads (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpIngressChannelsIndex (r:2 w:0)
+	/// Proof Skipped: Hrmp HrmpIngressChannelsIndex (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Hrmp HrmpAcceptedChannelRequestCount (r:2 w:2)
+	/// Proof Skipped: Hrmp HrmpAcceptedChannelRequestCount (max_values: None, max_size: None, mode: Measured)
+	fn establish_system_channel() -> Weight {
+		// Hand-estimated, see the note at the top of this file.
+		Weight::from_parts(117_764_000, 0)
+			.saturating_add(Weight::from_parts(0, 6593))
+			.saturating_add(T::DbWeight::get().reads(28))
+			.saturating_add(T::DbWeight::get().writes(16))
+	}
 }
@@ -39,12 +39,16 @@ use sp_std::{cmp::Ordering, collections::btree_map::BTreeMap, prelude::*};
 
 use runtime_parachains::{
 	configuration as parachains_configuration, disputes as parachains_disputes,
-	disputes::slashing as parachains_slashing, dmp as parachains_dmp, hrmp as parachains_hrmp,
-	inclusion as parachains_inclusion, initializer as parachains_initializer,
+	disputes::slashing as parachains_slashing,
+	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
+	initializer as parachains_initializer, on_demand as parachains_on_demand,
 	origin as parachains_origin, paras as parachains_paras,
-	paras_inherent as parachains_paras_inherent,
-	runtime_api_impl::v4 as parachains_runtime_api_impl, scheduler as parachains_scheduler,
-	session_info as parachains_session_info, shared as parachains_shared, ump as parachains_ump,
+	paras_inherent as parachains_paras_inherent, reward_points as parachains_reward_points,
+	runtime_api_impl::{
+		v4 as parachains_runtime_api_impl, vstaging as parachains_staging_runtime_api_impl,
+	},
+	scheduler as parachains_scheduler, session_info as parachains_session_info,
+	shared as parachains_shared, ump as parachains_ump,
 };
 
 use authority_discovery_primitives::AuthorityId as AuthorityDiscoveryId;
@@ -1014,17 +1018,29 @@ impl parachains_session_info::Config for Runtime {
 	type ValidatorSet = Historical;
 }
 
-/// Special `RewardValidators` that does nothing ;)
-pub struct RewardValidators;
-impl runtime_parachains::inclusion::RewardValidators for RewardValidators {
-	fn reward_backing(_: impl IntoIterator<Item = ValidatorIndex>) {}
-	fn reward_bitfields(_: impl IntoIterator<Item = ValidatorIndex>) {}
+parameter_types! {
+	pub const RewardPointsHistoryDepth: u32 = 84;
+}
+
+impl parachains_reward_points::Config for Runtime {
+	// Rococo has no staking, so the points are recorded for eras of `SessionsPerEra` sessions
+	// and are never awarded.
+	type ActiveEra = parachains_reward_points::AlwaysActiveEra<
+		parachains_slashing::SessionEras<Runtime, SessionsPerEra>,
+	>;
+	type HistoryDepth = RewardPointsHistoryDepth;
+}
+
+parameter_types! {
+	/// Keep the usage of the paras for about a week.
+	pub ParaUsageSessions: SessionIndex = 7 * DAYS / EpochDurationInBlocks::get();
 }
 
 impl parachains_inclusion::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type DisputesHandler = ParasDisputes;
-	type RewardValidators = RewardValidators;
+	type RewardValidators = parachains_reward_points::RecordRewardPoints<Runtime>;
+	type ParaUsageSessions = ParaUsageSessions;
 }
 
 parameter_types! {
@@ -1040,6 +1056,8 @@ impl parachains_paras::Config for Runtime {
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	/// Keep the overweight upward messages for about a week.
+	pub UmpOverweightExpirySessions: SessionIndex = 7 * DAYS / EpochDurationInBlocks::get();
 }
 
 impl parachains_ump::Config for Runtime {
@@ -1048,6 +1066,7 @@ impl parachains_ump::Config for Runtime {
 		crate::parachains_ump::XcmSink<xcm_executor::XcmExecutor<xcm_config::XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type OverweightExpirySessions = UmpOverweightExpirySessions;
 	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
@@ -1064,7 +1083,29 @@ impl parachains_paras_inherent::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_paras_inherent::WeightInfo<Runtime>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = parachains_scheduler::DefaultAssignmentProvider<Runtime>;
+	type WeightInfo = parachains_scheduler::TestWeightInfo;
+}
+
+parameter_types! {
+	pub const OnDemandBaseFee: Balance = 10 * CENTS;
+	pub const OnDemandMaxOrders: u32 = 100;
+	pub const OnDemandTargetQueueUtilization: Perbill = Perbill::from_percent(25);
+	pub const OnDemandFeeVariability: Perbill = Perbill::from_percent(3);
+	pub const OnDemandOrderTimeout: BlockNumber = 1 * HOURS;
+}
+
+impl parachains_on_demand::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
+	type Currency = Balances;
+	type BaseFee = OnDemandBaseFee;
+	type MaxOrders = OnDemandMaxOrders;
+	type TargetQueueUtilization = OnDemandTargetQueueUtilization;
+	type FeeVariability = OnDemandFeeVariability;
+	type OrderTimeout = OnDemandOrderTimeout;
+	type WeightInfo = weights::runtime_parachains_on_demand::WeightInfo<Runtime>;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -1074,12 +1115,13 @@ impl parachains_initializer::Config for Runtime {
 
 impl parachains_disputes::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
-	type RewardValidators = ();
+	type RewardValidators = parachains_reward_points::RecordRewardPoints<Runtime>;
 	type SlashingHandler = parachains_slashing::SlashValidatorsForDisputes<ParasSlashing>;
 	type WeightInfo = weights::runtime_parachains_disputes::WeightInfo<Runtime>;
 }
 
 impl parachains_slashing::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type KeyOwnerProofSystem = Historical;
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, ValidatorId)>>::Proof;
@@ -1092,6 +1134,8 @@ impl parachains_slashing::Config for Runtime {
 		Offences,
 		ReportLongevity,
 	>;
+	// Rococo has no staking eras, so repeat offences escalate within `SessionsPerEra` sessions.
+	type CurrentEra = parachains_slashing::SessionEras<Runtime, SessionsPerEra>;
 	type WeightInfo = parachains_slashing::TestWeightInfo;
 	type BenchmarkingConfig = parachains_slashing::BenchConfig<200>;
 }
@@ -1410,7 +1454,7 @@ construct_runtime! {
 		ParasShared: parachains_shared::{Pallet, Call, Storage} = 52,
 		ParaInclusion: parachains_inclusion::{Pallet, Call, Storage, Event<T>} = 53,
 		ParaInherent: parachains_paras_inherent::{Pallet, Call, Storage, Inherent} = 54,
-		ParaScheduler: parachains_scheduler::{Pallet, Storage} = 55,
+		ParaScheduler: parachains_scheduler::{Pallet, Call, Storage} = 55,
 		Paras: parachains_paras::{Pallet, Call, Storage, Event, Config, ValidateUnsigned} = 56,
 		Initializer: parachains_initializer::{Pallet, Call, Storage} = 57,
 		Dmp: parachains_dmp::{Pallet, Storage} = 58,
@@ -1418,7 +1462,9 @@ construct_runtime! {
 		Hrmp: parachains_hrmp::{Pallet, Call, Storage, Event<T>, Config} = 60,
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage} = 61,
 		ParasDisputes: parachains_disputes::{Pallet, Call, Storage, Event<T>} = 62,
-		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, ValidateUnsigned} = 63,
+		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, Event<T>, ValidateUnsigned} = 63,
+		OnDemand: parachains_on_demand::{Pallet, Call, Storage, Event<T>} = 64,
+		ParasRewardPoints: parachains_reward_points::{Pallet, Storage} = 65,
 
 		// Parachain Onboarding Pallets. Start indices at 70 to leave room.
 		Registrar: paras_registrar::{Pallet, Call, Storage, Event<T>, Config} = 70,
@@ -1495,7 +1541,14 @@ pub mod migrations {
 	);
 
 	/// Unreleased migrations. Add new ones here:
-	pub type Unreleased = ();
+	pub type Unreleased = (
+		parachains_inclusion::migration::v1::MigrateToV1<Runtime>,
+		parachains_dmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_ump::migration::v2::MigrateToV2<Runtime>,
+		parachains_configuration::migration::v6::MigrateToV6<Runtime>,
+		parachains_slashing::migration::v1::MigrateToV1<Runtime>,
+	);
 }
 
 /// Executive: handles dispatch to the various modules.
@@ -1554,8 +1607,10 @@ mod benches {
 		[runtime_parachains::hrmp, Hrmp]
 		[runtime_parachains::disputes, ParasDisputes]
 		[runtime_parachains::initializer, Initializer]
+		[runtime_parachains::on_demand, OnDemand]
 		[runtime_parachains::paras_inherent, ParaInherent]
 		[runtime_parachains::paras, Paras]
+		[runtime_parachains::scheduler, ParaScheduler]
 		[runtime_parachains::ump, Ump]
 		// Substrate
 		[pallet_balances, Balances]
@@ -1660,6 +1715,7 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(5)]
 	impl primitives::runtime_api::ParachainHost<Block, Hash, BlockNumber> for Runtime {
 		fn validators() -> Vec<ValidatorId> {
 			parachains_runtime_api_impl::validators::<Runtime>()
@@ -1727,6 +1783,27 @@ sp_api::impl_runtime_apis! {
 			parachains_runtime_api_impl::session_executor_params::<Runtime>(session_index)
 		}
 
+		fn staging_para_usage(
+			session_index: SessionIndex,
+		) -> Vec<(ParaId, primitives::vstaging::ParaUsage)> {
+			parachains_staging_runtime_api_impl::para_usage::<Runtime>(session_index)
+		}
+
+		fn staging_dmq_status() -> Vec<(ParaId, primitives::vstaging::DmqStatus)> {
+			parachains_staging_runtime_api_impl::dmq_status::<Runtime>()
+		}
+
+		fn staging_ump_overweight_messages(
+		) -> Vec<(ParaId, Vec<primitives::vstaging::UmpOverweightMessage>)> {
+			parachains_staging_runtime_api_impl::ump_overweight_messages::<Runtime>()
+		}
+
+		fn staging_validator_reward_points(
+			era: sp_staking::EraIndex,
+		) -> Vec<(AccountId, primitives::vstaging::ValidatorRewardPoints)> {
+			parachains_staging_runtime_api_impl::validator_reward_points::<Runtime>(era)
+		}
+
 		fn dmq_contents(recipient: ParaId) -> Vec<InboundDownwardMessage<BlockNumber>> {
 			parachains_runtime_api_impl::dmq_contents::<Runtime>(recipient)
 		}
@@ -0,0 +1,98 @@
+// Copyright (C) Parity Technologies (UK) Ltd.
+// This file is part of Polkadot.
+
+// Polkadot is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+
+// Polkadot is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+
+// You should have received a copy of the GNU General Public License
+// along with Polkadot.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Weights for `runtime_parachains::on_demand`
+//!
+//! THESE WEIGHTS ARE NOT BENCHMARK RESULTS YET. The storage accesses are taken from the
+//! benchmarks, the execution times are conservative estimates. Regenerate this file with the
+//! command below before relying on it.
+
+// Command to generate this file:
+// ./target/production/polkadot
+// benchmark
+// pallet
+// --chain=rococo-dev
+// --steps=50
+// --repeat=20
+// --pallet=runtime_parachains::on_demand
+// --extrinsic=*
+// --execution=wasm
+// --wasm-execution=compiled
+// --header=./file_header.txt
+// --output=./runtime/rococo/src/weights/runtime_parachains_on_demand.rs
+
+#![cfg_attr(rustfmt, rustfmt_skip)]
+#![allow(unused_parens)]
+#![allow(unused_imports)]
+#![allow(missing_docs)]
+
+use frame_support::{traits::Get, weights::Weight};
+use core::marker::PhantomData;
+
+/// Weight functions for `runtime_parachains::on_demand`.
+pub struct WeightInfo<T>(PhantomData<T>);
+impl<T: frame_system::Config> runtime_parachains::on_demand::WeightInfo for WeightInfo<T> {
+	/// Storage: Paras ParaLifecycles (r:1 w:0)
+	/// Proof Skipped: Paras ParaLifecycles (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Configuration ActiveConfig (r:1 w:0)
+	/// Proof Skipped: Configuration ActiveConfig (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: OnDemand SpotTraffic (r:1 w:0)
+	/// Proof Skipped: OnDemand SpotTraffic (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: OnDemand OrderQueue (r:1 w:1)
+	/// Proof Skipped: OnDemand OrderQueue (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// The range of component `s` is `[0, 99]`.
+	fn place_order(s: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `1000 + s * (88 ±0)`
+		//  Estimated: `4465 + s * (88 ±0)`
+		Weight::from_parts(60_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 4465))
+			.saturating_add(Weight::from_parts(100_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(2))
+			.saturating_add(Weight::from_parts(0, 88).saturating_mul(s.into()))
+	}
+	/// Storage: OnDemand OrderQueue (r:1 w:1)
+	/// Proof Skipped: OnDemand OrderQueue (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: OnDemand SpotTraffic (r:1 w:1)
+	/// Proof Skipped: OnDemand SpotTraffic (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: Paras ParaLifecycles (r:1 w:0)
+	/// Proof Skipped: Paras ParaLifecycles (max_values: None, max_size: None, mode: Measured)
+	/// Storage: Configuration ActiveConfig (r:1 w:0)
+	/// Proof Skipped: Configuration ActiveConfig (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ParaScheduler ParathreadQueue (r:1 w:1)
+	/// Proof Skipped: ParaScheduler ParathreadQueue (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: ParaScheduler ParathreadClaimIndex (r:1 w:1)
+	/// Proof Skipped: ParaScheduler ParathreadClaimIndex (max_values: Some(1), max_size: None, mode: Measured)
+	/// Storage: System Account (r:100 w:100)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// The range of component `s` is `[1, 100]`.
+	fn on_initialize(s: u32, ) -> Weight {
+		// Proof Size summary in bytes:
+		//  Measured:  `1000 + s * (88 ±0)`
+		//  Estimated: `4465 + s * (2603 ±0)`
+		Weight::from_parts(40_000_000, 0)
+			.saturating_add(Weight::from_parts(0, 4465))
+			.saturating_add(Weight::from_parts(30_000_000, 0).saturating_mul(s.into()))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(s.into())))
+			.saturating_add(T::DbWeight::get().writes(4))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(s.into())))
+			.saturating_add(Weight::from_parts(0, 2603).saturating_mul(s.into()))
+	}
+}
@@ -50,12 +50,15 @@ use runtime_common::{
 };
 use runtime_parachains::{
 	configuration as parachains_configuration, disputes as parachains_disputes,
-	disputes::slashing as parachains_slashing, dmp as parachains_dmp, hrmp as parachains_hrmp,
-	inclusion as parachains_inclusion, initializer as parachains_initializer,
-	origin as parachains_origin, paras as parachains_paras,
+	disputes::slashing as parachains_slashing,
+	dmp as parachains_dmp, hrmp as parachains_hrmp, inclusion as parachains_inclusion,
+	initializer as parachains_initializer, origin as parachains_origin, paras as parachains_paras,
 	paras_inherent as parachains_paras_inherent, reward_points as parachains_reward_points,
-	runtime_api_impl::v4 as parachains_runtime_api_impl, scheduler as parachains_scheduler,
-	session_info as parachains_session_info, shared as parachains_shared, ump as parachains_ump,
+	runtime_api_impl::{
+		v4 as parachains_runtime_api_impl, vstaging as parachains_staging_runtime_api_impl,
+	},
+	scheduler as parachains_scheduler, session_info as parachains_session_info,
+	shared as parachains_shared, ump as parachains_ump,
 };
 use scale_info::TypeInfo;
 use sp_core::{OpaqueMetadata, RuntimeDebug};
@@ -891,10 +894,21 @@ impl parachains_session_info::Config for Runtime {
 	type ValidatorSet = Historical;
 }
 
+impl parachains_reward_points::Config for Runtime {
+	type ActiveEra = parachains_reward_points::StakingActiveEra<Runtime>;
+	type HistoryDepth = <Runtime as pallet_staking::Config>::HistoryDepth;
+}
+
+parameter_types! {
+	/// Keep the usage of the paras for about a week.
+	pub const ParaUsageSessions: SessionIndex = 7 * DAYS / EPOCH_DURATION_IN_SLOTS;
+}
+
 impl parachains_inclusion::Config for Runtime {
 	type RuntimeEvent = RuntimeEvent;
 	type DisputesHandler = ParasDisputes;
 	type RewardValidators = parachains_reward_points::RewardValidatorsWithEraPoints<Runtime>;
+	type ParaUsageSessions = ParaUsageSessions;
 }
 
 parameter_types! {
@@ -910,6 +924,8 @@ impl parachains_paras::Config for Runtime {
 
 parameter_types! {
 	pub const FirstMessageFactorPercent: u64 = 100;
+	/// Keep the overweight upward messages for about a week.
+	pub const UmpOverweightExpirySessions: SessionIndex = 7 * DAYS / EPOCH_DURATION_IN_SLOTS;
 }
 
 impl parachains_ump::Config for Runtime {
@@ -918,6 +934,7 @@ impl parachains_ump::Config for Runtime {
 		crate::parachains_ump::XcmSink<xcm_executor::XcmExecutor<xcm_config::XcmConfig>, Runtime>;
 	type FirstMessageFactorPercent = FirstMessageFactorPercent;
 	type ExecuteOverweightOrigin = EnsureRoot<AccountId>;
+	type OverweightExpirySessions = UmpOverweightExpirySessions;
 	type WeightInfo = weights::runtime_parachains_ump::WeightInfo<Runtime>;
 }
 
@@ -934,7 +951,10 @@ impl parachains_paras_inherent::Config for Runtime {
 	type WeightInfo = weights::runtime_parachains_paras_inherent::WeightInfo<Runtime>;
 }
 
-impl parachains_scheduler::Config for Runtime {}
+impl parachains_scheduler::Config for Runtime {
+	type AssignmentProvider = parachains_scheduler::DefaultAssignmentProvider<Runtime>;
+	type WeightInfo = parachains_scheduler::TestWeightInfo;
+}
 
 impl parachains_initializer::Config for Runtime {
 	type Randomness = pallet_babe::RandomnessFromOneEpochAgo<Runtime>;
@@ -971,6 +991,7 @@ impl parachains_disputes::Config for Runtime {
 }
 
 impl parachains_slashing::Config for Runtime {
+	type RuntimeEvent = RuntimeEvent;
 	type KeyOwnerProofSystem = Historical;
 	type KeyOwnerProof =
 		<Self::KeyOwnerProofSystem as KeyOwnerProofSystem<(KeyTypeId, ValidatorId)>>::Proof;
@@ -983,6 +1004,7 @@ impl parachains_slashing::Config for Runtime {
 		Offences,
 		ReportLongevity,
 	>;
+	type CurrentEra = parachains_slashing::StakingActiveEra<Runtime>;
 	type WeightInfo = weights::runtime_parachains_disputes_slashing::WeightInfo<Runtime>;
 	type BenchmarkingConfig = parachains_slashing::BenchConfig<300>;
 }
@@ -1158,7 +1180,7 @@ construct_runtime! {
 		ParasShared: parachains_shared::{Pallet, Call, Storage} = 43,
 		ParaInclusion: parachains_inclusion::{Pallet, Call, Storage, Event<T>} = 44,
 		ParaInherent: parachains_paras_inherent::{Pallet, Call, Storage, Inherent} = 45,
-		ParaScheduler: parachains_scheduler::{Pallet, Storage} = 46,
+		ParaScheduler: parachains_scheduler::{Pallet, Call, Storage} = 46,
 		Paras: parachains_paras::{Pallet, Call, Storage, Event, Config, ValidateUnsigned} = 47,
 		Initializer: parachains_initializer::{Pallet, Call, Storage} = 48,
 		Dmp: parachains_dmp::{Pallet, Storage} = 49,
@@ -1166,7 +1188,8 @@ construct_runtime! {
 		Hrmp: parachains_hrmp::{Pallet, Call, Storage, Event<T>, Config} = 51,
 		ParaSessionInfo: parachains_session_info::{Pallet, Storage} = 52,
 		ParasDisputes: parachains_disputes::{Pallet, Call, Storage, Event<T>} = 53,
-		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, ValidateUnsigned} = 54,
+		ParasSlashing: parachains_slashing::{Pallet, Call, Storage, Event<T>, ValidateUnsigned} = 54,
+		ParasRewardPoints: parachains_reward_points::{Pallet, Storage} = 55,
 
 		// Parachain Onboarding Pallets. Start indices at 60 to leave room.
 		Registrar: paras_registrar::{Pallet, Call, Storage, Event<T>, Config} = 60,
@@ -1237,7 +1260,14 @@ pub mod migrations {
 	);
 
 	/// Unreleased migrations. Add new ones here:
-	pub type Unreleased = ();
+	pub type Unreleased = (
+		parachains_inclusion::migration::v1::MigrateToV1<Runtime>,
+		parachains_dmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_hrmp::migration::v1::MigrateToV1<Runtime>,
+		parachains_ump::migration::v2::MigrateToV2<Runtime>,
+		parachains_configuration::migration::v6::MigrateToV6<Runtime>,
+		parachains_slashing::migration::v1::MigrateToV1<Runtime>,
+	);
 }
 
 /// Unchecked extrinsic type as expected by this runtime.
@@ -1276,6 +1306,7 @@ mod benches {
 		[runtime_parachains::initializer, Initializer]
 		[runtime_parachains::paras, Paras]
 		[runtime_parachains::paras_inherent, ParaInherent]
+		[runtime_parachains::scheduler, ParaScheduler]
 		[runtime_parachains::ump, Ump]
 		// Substrate
 		[pallet_bags_list, VoterList]
@@ -1375,6 +1406,7 @@ sp_api::impl_runtime_apis! {
 		}
 	}
 
+	#[api_version(5)]
 	impl primitives::runtime_api::ParachainHost<Block, Hash, BlockNumber> for Runtime {
 		fn validators() -> Vec<ValidatorId> {
 			parachains_runtime_api_impl::validators::<Runtime>()
@@ -1442,6 +1474,27 @@ sp_api::impl_runtime_apis! {
 			parachains_runtime_api_impl::session_executor_params::<Runtime>(session_index)
 		}
 
+		fn staging_para_usage(
+			session_index: SessionIndex,
+		) -> Vec<(ParaId, primitives::vstaging::ParaUsage)> {
+			parachains_staging_runtime_api_impl::para_usage::<Runtime>(session_index)
+		}
+
+		fn staging_dmq_status() -> Vec<(ParaId, primitives::vstaging::DmqStatus)> {
+			parachains_staging_runtime_api_impl::dmq_status::<Runtime>()
+		}
+
+		fn staging_ump_overweight_messages(
+		) -> Vec<(ParaId, Vec<primitives::vstaging::UmpOverweightMessage>)> {
+			parachains_staging_runtime_api_impl::ump_overweight_messages::<Runtime>()
+		}
+
+		fn staging_validator_reward_points(
+			era: sp_staking::EraIndex,
+		) -> Vec<(AccountId, primitives::vstaging::ValidatorRewardPoints)> {
+			parachains_staging_runtime_api_impl::validator_reward_points::<Runtime>(era)
+		}
+
 		fn dmq_contents(recipient: ParaId) -> Vec<InboundDownwardMessage<BlockNumber>> {
 			parachains_runtime_api_impl::dmq_contents::<Runtime>(recipient)
 		}
@@ -1884,8 +1937,8 @@ sp_api::impl_runtime_apis! {
 				hex_literal::hex!("26aa394eea5630e07c48ae0c9558cef7b99d880ec681799c0cf30e8886371da95ecffd7b6c0f78751baa9d281e0bfa3a6d6f646c70792f74727372790000000000000000000000000000000000000000").to_vec().into(),
 				// Dmp DownwardMessageQueueHeads
 				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0c4d7fefc408aac59dbfe80a72ac8e3ce5").to_vec().into(),
-				// Dmp DownwardMessageQueues
-				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0ca95dac46c07a40d91506e7637ec4ba57").to_vec().into(),
+				// Dmp DownwardMessageQueueStates
+				hex_literal::hex!("63f78c98723ddc9073523ef3beefda0c2ed17122b1cd56638193d54a98b6cbda").to_vec().into(),
 				// Configuration ActiveConfig
 				hex_literal::hex!("06de3d8a54d27e44a9d5ce189618f22db4b49d95320d9021994c850f25b8e385").to_vec().into(),
 				// The transactional storage limit.
@@ -89,7 +89,7 @@ pub struct SignedStatement<Candidate, Digest, AuthorityId, Signature> {
 ///
 /// Since there are three possible ways to vote, a double vote is possible in
 /// three possible combinations (unordered)
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum ValidityDoubleVote<Candidate, Digest, Signature> {
 	/// Implicit vote by issuing and explicitly voting validity.
 	IssuedAndValidity((Candidate, Signature), (Digest, Signature)),
@@ -115,7 +115,7 @@ impl<Candidate, Digest, Signature> ValidityDoubleVote<Candidate, Digest, Signatu
 }
 
 /// Misbehavior: multiple signatures on same statement.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum DoubleSign<Candidate, Digest, Signature> {
 	/// On candidate.
 	Seconded(Candidate, Signature, Signature),
@@ -135,7 +135,7 @@ impl<Candidate, Digest, Signature> DoubleSign<Candidate, Digest, Signature> {
 }
 
 /// Misbehavior: declaring multiple candidates.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub struct MultipleCandidates<Candidate, Signature> {
 	/// The first candidate seen.
 	pub first: (Candidate, Signature),
@@ -144,7 +144,7 @@ pub struct MultipleCandidates<Candidate, Signature> {
 }
 
 /// Misbehavior: submitted statement for wrong group.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub struct UnauthorizedStatement<Candidate, Digest, AuthorityId, Signature> {
 	/// A signed statement which was submitted without proper authority.
 	pub statement: SignedStatement<Candidate, Digest, AuthorityId, Signature>,
@@ -152,7 +152,7 @@ pub struct UnauthorizedStatement<Candidate, Digest, AuthorityId, Signature> {
 
 /// Different kinds of misbehavior. All of these kinds of malicious misbehavior
 /// are easily provable and extremely disincentivized.
-#[derive(PartialEq, Eq, Debug, Clone)]
+#[derive(PartialEq, Eq, Debug, Clone, Encode, Decode)]
 pub enum Misbehavior<Candidate, Digest, AuthorityId, Signature> {
 	/// Voted invalid and valid on validity.
 	ValidityDoubleVote(ValidityDoubleVote<Candidate, Digest, Signature>),